@@ -0,0 +1,70 @@
+# Compares the ways of passing large blobs across the FFI: as a list of bytes
+# (`sequence<u8>` in the UDL), as a native byte buffer (`bytes`) and as a file path.
+# Executed by the `bench` CLI command, with the python bindings in the PYTHONPATH.
+import argparse
+import os
+import tempfile
+import time
+
+from zcash import *
+
+
+def measure(iterations, fn):
+    start = time.perf_counter()
+    for _ in range(iterations):
+        fn()
+    return (time.perf_counter() - start) / iterations * 1000
+
+
+def report(title, cases):
+    baseline = cases[0][1]
+    print(f"\n### {title}\n")
+    print("| Variant | Per call (ms) | Speedup |")
+    print("|---|---|---|")
+    for name, millis in cases:
+        print(f"| {name} | {millis:.3f} | {baseline / millis:.1f}x |")
+
+
+def transaction_cases(tx_path, iterations):
+    with open(tx_path, "rb") as f:
+        data = f.read()
+    as_list = list(data)
+    tx = ZcashTransaction.from_bytes(as_list, ZcashBranchId.NU5)
+    out_path = os.path.join(tempfile.mkdtemp(), "tx.bin")
+
+    report(f"Transaction decoding ({len(data)} bytes)", [
+        ("from_bytes", measure(iterations, lambda: ZcashTransaction.from_bytes(list(data), ZcashBranchId.NU5))),
+        ("from_byte_buffer", measure(iterations, lambda: ZcashTransaction.from_byte_buffer(data, ZcashBranchId.NU5))),
+        ("from_file", measure(iterations, lambda: ZcashTransaction.from_file(tx_path, ZcashBranchId.NU5))),
+    ])
+    report(f"Transaction encoding ({len(data)} bytes)", [
+        ("to_bytes", measure(iterations, lambda: bytes(tx.to_bytes()))),
+        ("to_byte_buffer", measure(iterations, lambda: tx.to_byte_buffer())),
+        ("write_to_file", measure(iterations, lambda: tx.write_to_file(out_path))),
+    ])
+
+
+def prover_cases(spend_path, output_path, iterations):
+    with open(spend_path, "rb") as f:
+        spend = f.read()
+    with open(output_path, "rb") as f:
+        output = f.read()
+
+    report(f"Sapling prover loading ({len(spend) + len(output)} bytes)", [
+        ("from_bytes", measure(iterations, lambda: ZcashLocalTxProver.from_bytes(list(spend), list(output)))),
+        ("from_byte_buffers", measure(iterations, lambda: ZcashLocalTxProver.from_byte_buffers(spend, output))),
+        ("constructor (paths)", measure(iterations, lambda: ZcashLocalTxProver(spend_path, output_path))),
+    ])
+
+
+if __name__ == "__main__":
+    parser = argparse.ArgumentParser()
+    parser.add_argument("--tx", required=True)
+    parser.add_argument("--spend-params", required=True)
+    parser.add_argument("--output-params", required=True)
+    parser.add_argument("--iterations", type=int, required=True)
+    args = parser.parse_args()
+
+    transaction_cases(args.tx, args.iterations)
+    # Loading the parameters is expensive on its own, so fewer rounds are enough.
+    prover_cases(args.spend_params, args.output_params, max(1, args.iterations // 100))
@@ -0,0 +1,383 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    fs::{read_dir, read_to_string},
+    path::Path,
+    process::Command,
+};
+
+use anyhow::anyhow;
+use uniffi_bindgen::{
+    interface::{Argument, AsType, Field, Type},
+    ComponentInterface,
+};
+
+use crate::codegen::parse_udl;
+
+/// The folders holding the UDL files the `uniffi-zcash` build script assembles,
+/// relative to the workspace root.
+const UDL_DIRS: [&str; 2] = ["uniffi-zcash/src/udl", "uniffi-zcash-test/src/udl"];
+
+/// Compares the exported API (as described by the UDL files) at the `old_ref` git ref
+/// against the one at `new_ref`, or against the working tree if not provided.
+/// It prints, in markdown, the removed, changed and added functions, types and members,
+/// along with the semver bump they call for.
+pub fn generate_api_diff(
+    root_dir: &Path,
+    old_ref: &str,
+    new_ref: Option<&str>,
+) -> anyhow::Result<()> {
+    print!("{}", api_diff_report(root_dir, old_ref, new_ref)?);
+    Ok(())
+}
+
+/// Builds the markdown report printed by [`generate_api_diff`].
+pub fn api_diff_report(
+    root_dir: &Path,
+    old_ref: &str,
+    new_ref: Option<&str>,
+) -> anyhow::Result<String> {
+    let old_api = api_items(&parse_udl(&udl_at_ref(root_dir, old_ref)?)?);
+    let new_api = api_items(&parse_udl(&match new_ref {
+        Some(new_ref) => udl_at_ref(root_dir, new_ref)?,
+        None => udl_at_working_tree(root_dir)?,
+    })?);
+
+    let diff = ApiDiff::between(&old_api, &new_api);
+
+    let mut report = String::new();
+    writeln!(
+        report,
+        "## API changes from `{}` to `{}`",
+        old_ref,
+        new_ref.unwrap_or("working tree")
+    )?;
+    write_section(
+        &mut report,
+        "Removed",
+        diff.removed.iter().map(|sig| format!("`{sig}`")),
+    )?;
+    write_section(
+        &mut report,
+        "Changed",
+        diff.changed
+            .iter()
+            .map(|(old, new)| format!("`{old}` -> `{new}`")),
+    )?;
+    write_section(
+        &mut report,
+        "Added",
+        diff.added.iter().map(|sig| format!("`{sig}`")),
+    )?;
+    writeln!(report)?;
+    writeln!(report, "Semver: {}", diff.semver_bump())?;
+
+    Ok(report)
+}
+
+struct ApiDiff {
+    removed: Vec<String>,
+    changed: Vec<(String, String)>,
+    added: Vec<String>,
+}
+
+impl ApiDiff {
+    /// Members of removed or added types are not listed on their own.
+    fn between(old: &ApiItems, new: &ApiItems) -> Self {
+        let removed_keys: BTreeSet<&String> =
+            old.keys().filter(|key| !new.contains_key(*key)).collect();
+        let added_keys: BTreeSet<&String> =
+            new.keys().filter(|key| !old.contains_key(*key)).collect();
+
+        let listed = |keys: &BTreeSet<&String>, items: &ApiItems| {
+            keys.iter()
+                .filter(|key| {
+                    items[**key]
+                        .parent
+                        .as_ref()
+                        .map(|parent| !keys.contains(parent))
+                        .unwrap_or(true)
+                })
+                .map(|key| items[*key].signature.clone())
+                .collect()
+        };
+
+        ApiDiff {
+            removed: listed(&removed_keys, old),
+            changed: old
+                .iter()
+                .filter_map(|(key, old_item)| {
+                    new.get(key)
+                        .filter(|new_item| new_item.signature != old_item.signature)
+                        .map(|new_item| (old_item.signature.clone(), new_item.signature.clone()))
+                })
+                .collect(),
+            added: listed(&added_keys, new),
+        }
+    }
+
+    fn semver_bump(&self) -> &'static str {
+        if !self.removed.is_empty() || !self.changed.is_empty() {
+            "breaking changes, bump the major version (the minor one while in 0.x)."
+        } else if !self.added.is_empty() {
+            "backwards compatible additions, bump the minor version (the patch one while in 0.x)."
+        } else {
+            "no API changes, bump the patch version."
+        }
+    }
+}
+
+fn write_section(
+    report: &mut String,
+    title: &str,
+    lines: impl Iterator<Item = String>,
+) -> std::fmt::Result {
+    let lines: Vec<String> = lines.collect();
+    if lines.is_empty() {
+        return Ok(());
+    }
+    writeln!(report)?;
+    writeln!(report, "### {title} ({})", lines.len())?;
+    writeln!(report)?;
+    for line in lines {
+        writeln!(report, "- {line}")?;
+    }
+    Ok(())
+}
+
+struct ApiItem {
+    /// The key of the type holding this item, for members.
+    parent: Option<String>,
+    /// The UDL like signature, used for both comparing and reporting.
+    signature: String,
+}
+
+/// Exported API items, keyed by their path, like `ZcashWalletDb.get_balance`.
+type ApiItems = BTreeMap<String, ApiItem>;
+
+fn api_items(ci: &ComponentInterface) -> ApiItems {
+    let mut items = ApiItems::new();
+    let mut add = |key: String, parent: Option<&str>, signature: String| {
+        items.insert(
+            key,
+            ApiItem {
+                parent: parent.map(ToString::to_string),
+                signature,
+            },
+        );
+    };
+
+    for func in ci.function_definitions() {
+        add(
+            func.name().to_string(),
+            None,
+            callable_signature(
+                func.name(),
+                &func.arguments(),
+                func.return_type(),
+                func.throws_type(),
+            ),
+        );
+    }
+
+    for obj in ci.object_definitions() {
+        let name = obj.name();
+        add(name.to_string(), None, format!("interface {name}"));
+        for cons in obj.constructors() {
+            add(
+                format!("{name}::{}", cons.name()),
+                Some(name),
+                format!(
+                    "constructor {name}::{}",
+                    callable_signature(cons.name(), &cons.arguments(), None, cons.throws_type(),)
+                ),
+            );
+        }
+        for meth in obj.methods() {
+            add(
+                format!("{name}.{}", meth.name()),
+                Some(name),
+                format!(
+                    "{name}.{}",
+                    callable_signature(
+                        meth.name(),
+                        &meth.arguments(),
+                        meth.return_type(),
+                        meth.throws_type(),
+                    )
+                ),
+            );
+        }
+    }
+
+    for cbi in ci.callback_interface_definitions() {
+        let name = cbi.name();
+        add(name.to_string(), None, format!("callback interface {name}"));
+        for meth in cbi.methods() {
+            add(
+                format!("{name}.{}", meth.name()),
+                Some(name),
+                format!(
+                    "{name}.{}",
+                    callable_signature(
+                        meth.name(),
+                        &meth.arguments(),
+                        meth.return_type(),
+                        meth.throws_type(),
+                    )
+                ),
+            );
+        }
+    }
+
+    for rec in ci.record_definitions() {
+        let name = rec.name();
+        add(name.to_string(), None, format!("dictionary {name}"));
+        for field in rec.fields() {
+            add(
+                format!("{name}.{}", field.name()),
+                Some(name),
+                format!("{name}.{}", field_signature(field)),
+            );
+        }
+    }
+
+    for e in ci.enum_definitions() {
+        let name = e.name();
+        let kind = if ci.is_name_used_as_error(name) {
+            "[Error] enum"
+        } else {
+            "enum"
+        };
+        add(name.to_string(), None, format!("{kind} {name}"));
+        for variant in e.variants() {
+            let fields = variant
+                .fields()
+                .iter()
+                .map(field_signature)
+                .collect::<Vec<_>>()
+                .join(", ");
+            add(
+                format!("{name}::{}", variant.name()),
+                Some(name),
+                format!("{name}::{}({fields})", variant.name()),
+            );
+        }
+    }
+
+    items
+}
+
+fn callable_signature(
+    name: &str,
+    args: &[&Argument],
+    return_type: Option<&Type>,
+    throws_type: Option<&Type>,
+) -> String {
+    let args = args
+        .iter()
+        .map(|arg| format!("{} {}", type_name(&arg.as_type()), arg.name()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut signature = format!("{name}({args})");
+    if let Some(return_type) = return_type {
+        signature.push_str(&format!(" -> {}", type_name(return_type)));
+    }
+    if let Some(throws_type) = throws_type {
+        signature.push_str(&format!(" throws {}", type_name(throws_type)));
+    }
+    signature
+}
+
+fn field_signature(field: &Field) -> String {
+    format!("{} {}", type_name(&field.as_type()), field.name())
+}
+
+/// Renders the type as written in the UDL.
+fn type_name(t: &Type) -> String {
+    match t {
+        Type::UInt8 => "u8".into(),
+        Type::Int8 => "i8".into(),
+        Type::UInt16 => "u16".into(),
+        Type::Int16 => "i16".into(),
+        Type::UInt32 => "u32".into(),
+        Type::Int32 => "i32".into(),
+        Type::UInt64 => "u64".into(),
+        Type::Int64 => "i64".into(),
+        Type::Float32 => "f32".into(),
+        Type::Float64 => "f64".into(),
+        Type::Boolean => "boolean".into(),
+        Type::String => "string".into(),
+        Type::Bytes => "bytes".into(),
+        Type::Timestamp => "timestamp".into(),
+        Type::Duration => "duration".into(),
+        Type::ForeignExecutor => "ForeignExecutor".into(),
+        Type::Object { name, .. }
+        | Type::Record { name, .. }
+        | Type::Enum { name, .. }
+        | Type::CallbackInterface { name, .. }
+        | Type::External { name, .. }
+        | Type::Custom { name, .. } => name.clone(),
+        Type::Optional { inner_type } => format!("{}?", type_name(inner_type)),
+        Type::Sequence { inner_type } => format!("sequence<{}>", type_name(inner_type)),
+        Type::Map {
+            key_type,
+            value_type,
+        } => format!("record<{}, {}>", type_name(key_type), type_name(value_type)),
+    }
+}
+
+/// Assembles the UDL files of the given git ref, like the build script does.
+fn udl_at_ref(root_dir: &Path, git_ref: &str) -> anyhow::Result<String> {
+    let files = git_output(
+        root_dir,
+        &[
+            &["ls-tree", "-r", "--name-only", git_ref, "--"],
+            &UDL_DIRS[..],
+        ]
+        .concat(),
+    )?;
+
+    files
+        .lines()
+        .filter(|file| file.ends_with(".udl"))
+        .map(|file| git_output(root_dir, &["show", &format!("{git_ref}:./{file}")]))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map(|contents| contents.join("\n"))
+}
+
+/// Assembles the UDL files of the working tree, like the build script does.
+fn udl_at_working_tree(root_dir: &Path) -> anyhow::Result<String> {
+    fn join_udl_files(dir: &Path, contents: &mut Vec<String>) -> anyhow::Result<()> {
+        for entry in read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                join_udl_files(&path, contents)?;
+            } else if path.extension().map(|ext| ext == "udl").unwrap_or(false) {
+                contents.push(read_to_string(&path)?);
+            }
+        }
+        Ok(())
+    }
+
+    let mut contents = Vec::new();
+    for dir in UDL_DIRS {
+        join_udl_files(&root_dir.join(dir), &mut contents)?;
+    }
+    Ok(contents.join("\n"))
+}
+
+fn git_output(root_dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root_dir)
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
@@ -0,0 +1,75 @@
+use std::{
+    fs::{self, read_to_string},
+    path::Path,
+    process::Command,
+};
+
+use anyhow::anyhow;
+use zcash_proofs::download_sapling_parameters;
+
+use crate::helper::{cmd_success, tmp_folder};
+
+/// The test data entry holding the transaction used for benchmarking.
+const BENCH_TRANSACTION: &str = "transaction_standard_fee";
+
+/// Runs the large blob benchmarks against the generated python bindings. They compare
+/// passing transactions and sapling parameters as lists of bytes, as native byte
+/// buffers and as file paths. Results are printed in markdown.
+pub fn run_blob_benchmarks(
+    root_dir: &Path,
+    bindings_dir: &Path,
+    iterations: u32,
+) -> anyhow::Result<()> {
+    let params = download_sapling_parameters(None).map_err(|err| anyhow!(err.to_string()))?;
+
+    let tx_path = tmp_folder()?.join("tx.bin");
+    fs::write(&tx_path, test_data_bytes(root_dir, BENCH_TRANSACTION)?)?;
+
+    println!("Running blob benchmarks ({} iterations) ...", iterations);
+    cmd_success(
+        Command::new("python3")
+            .arg(
+                root_dir
+                    .join("uniffi-zcash-cli")
+                    .join("bench")
+                    .join("blobs.py"),
+            )
+            .arg("--tx")
+            .arg(&tx_path)
+            .arg("--spend-params")
+            .arg(&params.spend)
+            .arg("--output-params")
+            .arg(&params.output)
+            .arg("--iterations")
+            .arg(iterations.to_string())
+            .env("PYTHONPATH", bindings_dir)
+            .spawn()?
+            .wait(),
+    )
+}
+
+/// Reads an entry of the test data file, which holds `key:[1,2,3]` lines.
+fn test_data_bytes(root_dir: &Path, key: &str) -> anyhow::Result<Vec<u8>> {
+    let test_data_path = root_dir
+        .join("uniffi-zcash")
+        .join("tests")
+        .join("test_data.csv");
+    let content = read_to_string(&test_data_path)?;
+
+    let value = content
+        .lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+        .ok_or_else(|| {
+            anyhow!(
+                "Cannot find {} in {}. Is the test data generated ? Check CLI help.",
+                key,
+                test_data_path.to_string_lossy()
+            )
+        })?;
+
+    value
+        .trim_matches(|c| c == '[' || c == ']')
+        .split(',')
+        .map(|byte| Ok(byte.trim().parse::<u8>()?))
+        .collect()
+}
@@ -1,18 +1,47 @@
 use std::{
-    path::Path,
-    process::{Command, ExitStatus},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
 };
 
+use anyhow::bail;
 use fs_extra::file::{self, CopyOptions};
+use heck::ToUpperCamelCase;
+use uniffi_bindgen::ComponentInterface;
 
 use crate::{
+    codegen::{
+        component_interface,
+        docs::ApiDocs,
+        domains::{group_by_domain, DomainTypes},
+        kotlin, python,
+        stability::ApiStability,
+        swift,
+    },
     helper::{cmd_success, LINUX_SHARED_LIB_NAME, MACOS_SHARED_LIB_NAME},
-    KOTLIN, PYTHON, RUBY, SUPPORTED_LANGUAGES, SWIFT,
+    C, KOTLIN, PYTHON, RUBY, SUPPORTED_LANGUAGES, SWIFT,
 };
 
-pub fn generate_bindings(root_dir: &Path, enabled_languages: &[String]) -> anyhow::Result<()> {
+/// Generates the bindings of the enabled languages in a folder per language of
+/// `bindings_dir`, from the shared libraries of `shared_libs_dir`, which are copied along
+/// with them. Any of the libraries is enough, as they all hold the same interface.
+pub fn generate_bindings(
+    root_dir: &Path,
+    udl_path: &Path,
+    shared_libs_dir: &Path,
+    bindings_dir: &Path,
+    enabled_languages: &[String],
+    group_by_domain: bool,
+) -> anyhow::Result<()> {
     // eliminate directory if it exists already
-    fs_extra::dir::remove(root_dir.join("bindings"))?;
+    fs_extra::dir::remove(bindings_dir)?;
+
+    let ci = component_interface(udl_path)?;
+    let stability = ApiStability::load(
+        &root_dir.join("uniffi-zcash").join("api_stability.toml"),
+        &ci,
+    )?;
+    let docs = ApiDocs::collect(&root_dir.join("uniffi-zcash").join("src"), &ci)?;
 
     println!("Generating bindings ...");
     SUPPORTED_LANGUAGES
@@ -21,18 +50,36 @@ pub fn generate_bindings(root_dir: &Path, enabled_languages: &[String]) -> anyho
         .try_for_each(|lang| {
             println!("Generating language bindings for {}", lang);
 
-            let lang_binding_path = root_dir.join("bindings").join(lang);
+            let lang_binding_path = bindings_dir.join(lang);
 
-            let command = generate_binding(root_dir, lang, &lang_binding_path);
+            // UniFFI has no C generator, the C header comes from the Swift one.
+            let generator_lang = if lang == C { SWIFT } else { lang };
+            generate_binding(root_dir, shared_libs_dir, generator_lang, &lang_binding_path)?;
 
-            cmd_success(command)?;
-
-            copy_bindings(root_dir, &lang_binding_path)?;
+            copy_bindings(shared_libs_dir, &lang_binding_path)?;
 
             match lang {
-                KOTLIN => kotlin_binding_generation(root_dir, &lang_binding_path),
-                SWIFT => swift_binding_generation(&lang_binding_path),
-                RUBY | PYTHON => Ok(()),
+                KOTLIN => {
+                    kotlin_binding_generation(root_dir, &lang_binding_path)?;
+                    kotlin_documentation(&ci, &docs, &lang_binding_path)?;
+                    kotlin_stability_annotations(&ci, &stability, &lang_binding_path)?;
+                    if group_by_domain {
+                        kotlin_domain_packages(root_dir, &ci, &lang_binding_path)?;
+                    }
+                    Ok(())
+                }
+                SWIFT => {
+                    swift_documentation(&docs, &lang_binding_path)?;
+                    swift_stability_annotations(&stability, &lang_binding_path)?;
+                    swift_binding_generation(&lang_binding_path)?;
+                    if group_by_domain {
+                        swift_domain_namespaces(root_dir, &ci, &lang_binding_path)?;
+                    }
+                    Ok(())
+                }
+                PYTHON => python_binding_generation(&ci, &stability, &docs, &lang_binding_path),
+                RUBY => Ok(()),
+                C => c_binding_generation(&lang_binding_path),
                 &_ => panic!("Unrecognized language (programming error). A language was added to supported list, but has no support in code !"),
             }
         })
@@ -40,53 +87,61 @@ pub fn generate_bindings(root_dir: &Path, enabled_languages: &[String]) -> anyho
 
 fn generate_binding(
     root_dir: &Path,
+    shared_libs_dir: &Path,
     lang: &str,
     lang_binding_path: &Path,
-) -> Result<ExitStatus, std::io::Error> {
+) -> anyhow::Result<()> {
     // let config_path = root_dir.join("uniffi-zcash").join("uniffi.toml");
 
-    // #[cfg(target_os = "macos")]
-    // let releases_path = root_dir.join("shared_libs").join("libuniffi_zcash.dylib");
-
-    // #[cfg(not(target_os = "macos"))]
-    // The static library should be OK for all OSs
-    let releases_path = root_dir.join("shared_libs").join("libuniffi_zcash.so");
-
-    Command::new("cargo")
-        .arg("run")
-        .arg("--bin")
-        .arg("uniffi-bindgen")
-        .arg("generate")
-        .arg("--library")
-        .arg(releases_path)
-        // .arg("--config")
-        // .arg(&config_path)
-        .arg("--language")
-        .arg(lang)
-        .arg("--out-dir")
-        .arg(lang_binding_path)
-        .spawn()?
-        .wait()
-}
+    // Any of the libraries should be OK for all OSs
+    let releases_path = shared_lib_paths(shared_libs_dir)?.remove(0);
 
-fn copy_bindings(root_dir: &Path, shared_lib_dest_path: &Path) -> Result<(), anyhow::Error> {
-    println!("Copying bindings to proper folder...");
+    cmd_success(
+        Command::new("cargo")
+            .current_dir(root_dir)
+            .arg("run")
+            .arg("--bin")
+            .arg("uniffi-bindgen")
+            .arg("generate")
+            .arg("--library")
+            .arg(releases_path)
+            // .arg("--config")
+            // .arg(&config_path)
+            .arg("--language")
+            .arg(lang)
+            .arg("--out-dir")
+            .arg(lang_binding_path)
+            .spawn()?
+            .wait(),
+    )
+}
 
-    let shared_libs_dir = root_dir.join("shared_libs");
+/// The shared libraries found in the folder, the Linux one first.
+fn shared_lib_paths(shared_libs_dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let paths: Vec<PathBuf> = [LINUX_SHARED_LIB_NAME, MACOS_SHARED_LIB_NAME]
+        .into_iter()
+        .map(|name| shared_libs_dir.join(name))
+        .filter(|path| path.exists())
+        .collect();
+    if paths.is_empty() {
+        bail!(
+            "No shared library found in {}",
+            shared_libs_dir.to_string_lossy()
+        )
+    }
+    Ok(paths)
+}
 
-    let linux_shared_lib_path = shared_libs_dir.join(LINUX_SHARED_LIB_NAME);
-    file::copy(
-        linux_shared_lib_path,
-        shared_lib_dest_path.join(LINUX_SHARED_LIB_NAME),
-        &CopyOptions::default(),
-    )?;
+fn copy_bindings(shared_libs_dir: &Path, shared_lib_dest_path: &Path) -> Result<(), anyhow::Error> {
+    println!("Copying bindings to proper folder...");
 
-    let macos_shared_lib_path = shared_libs_dir.join(MACOS_SHARED_LIB_NAME);
-    file::copy(
-        macos_shared_lib_path,
-        shared_lib_dest_path.join(MACOS_SHARED_LIB_NAME),
-        &CopyOptions::default(),
-    )?;
+    for shared_lib_path in shared_lib_paths(shared_libs_dir)? {
+        file::copy(
+            &shared_lib_path,
+            shared_lib_dest_path.join(shared_lib_path.file_name().unwrap()),
+            &CopyOptions::default(),
+        )?;
+    }
 
     Ok(())
 }
@@ -123,6 +178,49 @@ fn swift_binding_generation(bindings_dir: &Path) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+/// Makes the C header out of the Swift generated one, which also holds the raw
+/// FFI functions of the shared libraries, and drops the rest of the Swift files.
+fn c_binding_generation(bindings_dir: &Path) -> Result<(), anyhow::Error> {
+    println!("Generating C header ...");
+
+    // The nullability qualifiers are a clang extension.
+    let header = format!(
+        "#ifndef __clang__\n#define _Nullable\n#define _Nonnull\n#endif\n\n{}",
+        fs::read_to_string(bindings_dir.join("zcashFFI.h"))?
+    );
+    fs::write(bindings_dir.join("zcash.h"), header)?;
+
+    for file_name in ["zcashFFI.h", "zcashFFI.modulemap", "zcash.swift"] {
+        fs::remove_file(bindings_dir.join(file_name))?;
+    }
+
+    Ok(())
+}
+
+// The generated python module is untyped, so we provide type stubs for it.
+// See https://peps.python.org/pep-0484/#stub-files
+fn python_binding_generation(
+    ci: &ComponentInterface,
+    stability: &ApiStability,
+    docs: &ApiDocs,
+    bindings_dir: &Path,
+) -> Result<(), anyhow::Error> {
+    println!("Generating python docstrings ...");
+
+    let binding_file = bindings_dir.join("zcash.py");
+    let source = fs::read_to_string(&binding_file)?;
+    fs::write(binding_file, python::document(&source, docs)?)?;
+
+    println!("Generating python type stubs ...");
+
+    fs::write(
+        bindings_dir.join("zcash.pyi"),
+        python::type_stubs(ci, stability, docs)?,
+    )?;
+
+    Ok(())
+}
+
 fn kotlin_binding_generation(root_dir: &Path, bindings_dir: &Path) -> Result<(), anyhow::Error> {
     let inner_dir = bindings_dir.join("uniffi").join("zcash");
 
@@ -147,3 +245,97 @@ fn kotlin_binding_generation(root_dir: &Path, bindings_dir: &Path) -> Result<(),
 
     Ok(())
 }
+
+// UniFFI does not carry the Rust doc comments to the bindings.
+fn kotlin_documentation(
+    ci: &ComponentInterface,
+    docs: &ApiDocs,
+    bindings_dir: &Path,
+) -> Result<(), anyhow::Error> {
+    let binding_file = bindings_dir.join("uniffi").join("zcash").join("zcash.kt");
+    let source = fs::read_to_string(&binding_file)?;
+    fs::write(binding_file, kotlin::document(ci, &source, docs)?)?;
+
+    Ok(())
+}
+
+// See the api_stability.toml manifest in the uniffi-zcash crate.
+fn kotlin_stability_annotations(
+    ci: &ComponentInterface,
+    stability: &ApiStability,
+    bindings_dir: &Path,
+) -> Result<(), anyhow::Error> {
+    let binding_file = bindings_dir.join("uniffi").join("zcash").join("zcash.kt");
+    let source = fs::read_to_string(&binding_file)?;
+    fs::write(
+        binding_file,
+        kotlin::annotate_stability(ci, &source, stability)?,
+    )?;
+
+    Ok(())
+}
+
+// UniFFI does not carry the Rust doc comments to the bindings.
+fn swift_documentation(docs: &ApiDocs, bindings_dir: &Path) -> Result<(), anyhow::Error> {
+    let binding_file = bindings_dir.join("zcash.swift");
+    let source = fs::read_to_string(&binding_file)?;
+    fs::write(binding_file, swift::document(&source, docs)?)?;
+
+    Ok(())
+}
+
+// See the api_stability.toml manifest in the uniffi-zcash crate.
+fn swift_stability_annotations(
+    stability: &ApiStability,
+    bindings_dir: &Path,
+) -> Result<(), anyhow::Error> {
+    let binding_file = bindings_dir.join("zcash.swift");
+    let source = fs::read_to_string(&binding_file)?;
+    fs::write(binding_file, swift::annotate_stability(&source, stability)?)?;
+
+    Ok(())
+}
+
+/// The domain of each type comes from where it is declared in the UDL sources.
+fn domain_types(root_dir: &Path, ci: &ComponentInterface) -> anyhow::Result<DomainTypes> {
+    let udl_dir = root_dir.join("uniffi-zcash").join("src").join("udl");
+    group_by_domain(ci, &udl_dir)
+}
+
+fn kotlin_domain_packages(
+    root_dir: &Path,
+    ci: &ComponentInterface,
+    bindings_dir: &Path,
+) -> Result<(), anyhow::Error> {
+    println!("Generating kotlin domain packages ...");
+
+    let grouped = domain_types(root_dir, ci)?;
+    let inner_dir = bindings_dir.join("uniffi").join("zcash");
+
+    for (domain, content) in kotlin::domain_packages(ci, &grouped)? {
+        let package_dir = inner_dir.join(domain.name());
+        fs::create_dir_all(&package_dir)?;
+        fs::write(
+            package_dir.join(format!("{}.kt", domain.name().to_upper_camel_case())),
+            content,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn swift_domain_namespaces(
+    root_dir: &Path,
+    ci: &ComponentInterface,
+    bindings_dir: &Path,
+) -> Result<(), anyhow::Error> {
+    println!("Generating swift domain namespaces ...");
+
+    let grouped = domain_types(root_dir, ci)?;
+    fs::write(
+        bindings_dir.join("ZcashDomains.swift"),
+        swift::domain_namespaces(&grouped)?,
+    )?;
+
+    Ok(())
+}
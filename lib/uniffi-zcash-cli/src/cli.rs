@@ -1,10 +1,10 @@
 use std::path::Path;
 
-use crate::SUPPORTED_LANGUAGES;
+use crate::{examples::EXAMPLE_LANGUAGES, SUPPORTED_LANGUAGES};
 use anyhow::anyhow;
 use clap::{
     builder::{PossibleValuesParser, ValueParser},
-    Arg, ArgMatches, ColorChoice, Command,
+    value_parser, Arg, ArgAction, ArgMatches, ColorChoice, Command,
 };
 
 pub fn get_matches() -> ArgMatches {
@@ -27,10 +27,22 @@ pub fn get_matches() -> ArgMatches {
             .subcommand(
                 Command::new("saplingparams")
                 .about("Downloads the sapling parameters to the default location, normally in your home directory.")
+                .arg(
+                    Arg::new("verify_prover")
+                    .long("verify-prover")
+                    .action(ArgAction::SetTrue)
+                    .help("Also checks the parameters work, by building a prover with them and generating a dummy proof.")
+                )
             )
             .subcommand(
                 Command::new("testdata")
                 .about("Generates the pre-built golden file with test data for the test cases.")
+                .arg(
+                    Arg::new("reorg")
+                    .long("reorg")
+                    .action(ArgAction::SetTrue)
+                    .help("Generates instead the golden file of the chain reorg tests: two competing chains sharing a prefix, with the expected balances on each.")
+                )
             )
         )
         .subcommand(
@@ -49,22 +61,57 @@ pub fn get_matches() -> ArgMatches {
                 .required(false)
                 .default_values(SUPPORTED_LANGUAGES)
             )
+            .arg(
+                Arg::new("group_by_domain")
+                .long("group-by-domain")
+                .env("GROUP_BY_DOMAIN")
+                .action(ArgAction::SetTrue)
+                .help("Also group the generated Kotlin and Swift types by domain (keys, wallet, tx, net, core), dropping their 'Zcash' prefix. Kotlin gets a 'uniffi.zcash.<domain>' package per domain, Swift a namespace per domain.")
+            )
         )
         .subcommand(
             Command::new("release")
             .about("Prepares a release for a given a version (semantic versioning). It needs to be executed after the 'bindgen' command.")
             .subcommand(Command::new("python").about("Prepares release for Python.")
                 .arg(arg_version())
+                .arg(arg_force())
             )
             .subcommand(Command::new("ruby").about("Prepares release for Ruby.")
                 .arg(arg_version())
+                .arg(arg_force())
             )
             .subcommand(Command::new("kotlin").about("Prepares release for Kotlin.")
                 .arg(arg_version())
+                .arg(arg_force())
             )
             .subcommand(Command::new("swift").about("Prepares release for Swift.")
                 .arg(arg_version())
                 .arg(arg_swift_git_repo_url())
+                .arg(arg_force())
+            )
+            .subcommand(Command::new("c").about("Prepares release for C, an archive with the header and the shared libraries.")
+                .arg(arg_version())
+                .arg(arg_force())
+            )
+            .subcommand(Command::new("react-native").about("Prepares release for React Native, an npm package with the Kotlin and Swift bindings and their TypeScript declarations. It needs the Kotlin and Swift bindings.")
+                .arg(arg_version())
+                .arg(arg_force())
+                .arg(
+                    Arg::new("android_libs_dir")
+                    .long("android-libs-dir")
+                    .env("ANDROID_LIBS_DIR")
+                    .required(false)
+                    .value_parser(validator_existing_path())
+                    .help("A folder with the prebuilt Android shared libraries, in a subfolder per ABI (arm64-v8a, x86_64 ...).")
+                )
+                .arg(
+                    Arg::new("ios_xcframework")
+                    .long("ios-xcframework")
+                    .env("IOS_XCFRAMEWORK")
+                    .required(false)
+                    .value_parser(validator_existing_path())
+                    .help("The prebuilt zcashFFI.xcframework for iOS.")
+                )
             )
         )
         .subcommand(
@@ -112,6 +159,7 @@ pub fn get_matches() -> ArgMatches {
 
             )
             .subcommand(Command::new("kotlin")
+                .arg(arg_version())
                 .arg(
                     Arg::new("registry_url")
                     .long("registry-url")
@@ -119,6 +167,13 @@ pub fn get_matches() -> ArgMatches {
                     .env("KOTLIN_REGISTRY_URL")
                     .help("The http[s] URL of the target kotlin package index. i.e https://repo.maven.apache.org/maven2/")
                 )
+                .arg(
+                    Arg::new("snapshot_registry_url")
+                    .long("snapshot-registry-url")
+                    .required(false)
+                    .env("KOTLIN_SNAPSHOT_REGISTRY_URL")
+                    .help("The http[s] URL of the kotlin package index for -SNAPSHOT versions. Defaults to the registry URL.")
+                )
                 .arg(
                     Arg::new("registry_username")
                     .long("registry-username")
@@ -134,6 +189,38 @@ pub fn get_matches() -> ArgMatches {
                     .help("The kotlin registry password, can be also a token.")
                 )
             )
+            .subcommand(Command::new("github-release")
+                .about("Uploads the shared libraries, the Swift xcframework zip if given, and their checksums to a GitHub release, creating it if needed.")
+                .arg(
+                    Arg::new("tag")
+                    .long("tag")
+                    .required(true)
+                    .env("RELEASE_TAG")
+                    .value_parser(validator_release_tag())
+                    .help("The git tag of the release. i.e v1.2.3")
+                )
+                .arg(
+                    Arg::new("repo")
+                    .long("repo")
+                    .required(true)
+                    .env("GITHUB_REPOSITORY")
+                    .help("The GitHub repository holding the release. i.e eigerco/uniffi-zcash-lib")
+                )
+                .arg(
+                    Arg::new("token")
+                    .long("token")
+                    .required(true)
+                    .env("GITHUB_TOKEN")
+                    .help("A GitHub token allowed to write the repository releases.")
+                )
+                .arg(
+                    Arg::new("xcframework")
+                    .long("xcframework")
+                    .required(false)
+                    .value_parser(validator_existing_file())
+                    .help("The zipped xcframework the Swift binary target points to.")
+                )
+            )
             .subcommand(Command::new("swift").about("Holds the Swift language subcommands")
                 .subcommand(Command::new("git-repo")
                     .about("Publish a previously generated package to a Git swift repository.")
@@ -200,6 +287,87 @@ pub fn get_matches() -> ArgMatches {
                 .required(false)
             )
         )
+        .subcommand(
+            Command::new("examples")
+            .about(format!("Renders the example programs (create a wallet, sync it and send funds) for the languages having examples ({}) from their templates, and places them in the examples directory, along with a README.md gallery. The same templates are compiled by the 'release' command, along with the test applications.", EXAMPLE_LANGUAGES.join(",")))
+            .arg(
+                Arg::new("languages")
+                .long("languages")
+                .env("LANGUAGES")
+                .value_delimiter(',')
+                .value_parser(PossibleValuesParser::new(EXAMPLE_LANGUAGES))
+                .required(false)
+                .default_values(EXAMPLE_LANGUAGES)
+            )
+        )
+        .subcommand(
+            Command::new("demo")
+            .about("Syncs a throwaway wallet against a public lightwalletd server with the library APIs, and prints its balances. It checks the whole stack (network, scanning and database) end to end, and shows how to sync a wallet.")
+            .arg(
+                Arg::new("network")
+                .long("network")
+                .value_parser(["main", "test"])
+                .default_value("test")
+                .help("The network to sync the wallet on.")
+            ).arg(
+                Arg::new("seed")
+                .long("seed")
+                .env("DEMO_SEED")
+                .required(false)
+                .help("The seed of the wallet, hex encoded. Defaults to a random one.")
+            ).arg(
+                Arg::new("lightwalletd_url")
+                .long("lightwalletd-url")
+                .required(false)
+                .help("The lightwalletd server to sync from. Defaults to the one of the Electric Coin Company for the network.")
+            ).arg(
+                Arg::new("blocks")
+                .long("blocks")
+                .value_parser(value_parser!(u32).range(1..))
+                .default_value("100")
+                .help("How many blocks before the chain tip the wallet birthday is, so how many blocks are synced.")
+            )
+        )
+        .subcommand(
+            Command::new("wallet")
+            .subcommand_required(true)
+            .about("Support tools for the wallet databases of the apps using the bindings.")
+            .subcommand(
+                Command::new("info")
+                .about("Prints the schema, the accounts with their birthdays and balances, and the scanning status of a wallet database, as the bindings read them.")
+                .arg(arg_wallet_path())
+                .arg(arg_wallet_network())
+            )
+            .subcommand(
+                Command::new("audit")
+                .about("Checks the integrity of a wallet database: the note commitments against the note commitment tree, the balances against the notes and outputs, and the rows referring to missing accounts or transactions. Fails if any issue is found.")
+                .arg(arg_wallet_path())
+                .arg(arg_wallet_network())
+            )
+        )
+        .subcommand(
+            Command::new("apidiff").about("Reports the functions, types and members added, removed or changed in the exported API (as described by the UDL files) since the given git ref. Useful for release notes and semver decisions.")
+            .arg(
+                Arg::new("old_ref")
+                .required(true)
+                .help("The git ref (tag, branch, commit ...) to compare against. i.e the last release tag.")
+            ).arg(
+                Arg::new("new_ref")
+                .long("new-ref")
+                .required(false)
+                .help("The git ref holding the newer API. Defaults to the working tree.")
+            )
+        )
+        .subcommand(
+            Command::new("bench").about("Benchmarks passing large blobs, like transactions and sapling parameters, across the FFI as lists of bytes, as native byte buffers and as file paths. Runs against the python bindings, so the 'bindgen' command for python must be executed first.")
+            .arg(
+                Arg::new("iterations")
+                .long("iterations")
+                .value_parser(value_parser!(u32).range(1..))
+                .default_value("1000")
+                .help("How many times each operation is executed. Loading the sapling parameters is done a hundred times less.")
+            )
+        )
         .subcommand(
             Command::new("docgen").about("It generates API surface docs per each language and places them in the 'docs' folder.")
             .subcommand_required(true)
@@ -215,6 +383,11 @@ pub fn get_matches() -> ArgMatches {
             .subcommand(
                 Command::new("swift").arg(arg_version())
             )
+            .subcommand(
+                Command::new("all")
+                .about("It generates the docs of all the languages, plus an index page linking them.")
+                .arg(arg_version())
+            )
         )
         .get_matches()
 }
@@ -228,6 +401,14 @@ fn arg_version() -> Arg {
         .value_parser(validator_semver())
 }
 
+fn arg_force() -> Arg {
+    Arg::new("force")
+        .long("force")
+        .env("FORCE_RELEASE")
+        .action(ArgAction::SetTrue)
+        .help("Rebuilds the package even if its templates and bindings did not change since the last release.")
+}
+
 fn arg_swift_git_repo_url() -> Arg {
     Arg::new("git_repo_url")
     .long("git-repo-url")
@@ -236,6 +417,21 @@ fn arg_swift_git_repo_url() -> Arg {
     .help("For auth, use a Github personal access token.\nSee https://docs.github.com/en/authentication/keeping-your-account-and-data-secure/creating-a-personal-access-token\nExample: https://<github-username>:<github-token>@github.com/<your-repository>.git")
 }
 
+fn arg_wallet_path() -> Arg {
+    Arg::new("path")
+        .required(true)
+        .value_parser(validator_existing_file())
+        .help("The path of the wallet database.")
+}
+
+fn arg_wallet_network() -> Arg {
+    Arg::new("network")
+        .long("network")
+        .value_parser(["main", "test"])
+        .default_value("main")
+        .help("The network of the wallet.")
+}
+
 /// See https://semver.org/#is-there-a-suggested-regular-expression-regex-to-check-a-semver-string
 const REGEX_SEMVER: &str = r"^(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)(?:-((?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)(?:\.(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*))*))?(?:\+([0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?$";
 /// It generates a validator for semantic versioning
@@ -243,6 +439,23 @@ pub fn validator_semver() -> ValueParser {
     validator_regex(REGEX_SEMVER, "semver: https://semver.org")
 }
 
+/// It generates a validator for release tags, which are semantic versions prefixed by "v"
+pub fn validator_release_tag() -> ValueParser {
+    ValueParser::from(move |input: &str| -> anyhow::Result<String> {
+        let reg = regex::Regex::new(REGEX_SEMVER).unwrap();
+        match input
+            .strip_prefix('v')
+            .is_some_and(|version| reg.is_match(version))
+        {
+            true => Ok(input.to_owned()),
+            false => Err(anyhow!(
+                "Value \"{}\" is not matching format: v + semver: https://semver.org",
+                input
+            )),
+        }
+    })
+}
+
 /// Creates a clap validator (using ValueParser API) with a regex.
 /// # Arguments
 ///
@@ -269,3 +482,11 @@ pub fn validator_existing_path() -> ValueParser {
         Ok(input.to_string())
     })
 }
+
+/// Validates if a file exists
+pub fn validator_existing_file() -> ValueParser {
+    ValueParser::from(move |input: &str| -> anyhow::Result<String> {
+        anyhow::ensure!(Path::new(input).is_file(), "file \"{input}\" not found");
+        Ok(input.to_string())
+    })
+}
@@ -0,0 +1,36 @@
+use std::{fs::read_to_string, path::Path};
+
+use anyhow::anyhow;
+use uniffi_bindgen::ComponentInterface;
+
+pub mod docs;
+pub mod domains;
+pub mod kotlin;
+pub mod python;
+pub mod ruby;
+pub mod stability;
+pub mod swift;
+pub mod typescript;
+
+/// The crate name the scaffolding is generated for. UniFFI needs it for
+/// resolving the module path of the types described in the UDL.
+const CRATE_NAME: &str = "uniffi_zcash";
+
+/// Parses the UDL file assembled by the `uniffi-zcash` build script, so
+/// the companion files of each language (overlays, stubs, signatures ...)
+/// can be generated from the same interface definition UniFFI uses.
+pub fn component_interface(udl_path: &Path) -> anyhow::Result<ComponentInterface> {
+    let udl = read_to_string(udl_path).map_err(|err| {
+        anyhow!(
+            "Cannot read {}: {}. Is the uniffi-zcash crate already built ? Check CLI help.",
+            udl_path.to_string_lossy(),
+            err
+        )
+    })?;
+    parse_udl(&udl)
+}
+
+/// Parses UDL content, which may come from somewhere else than the build script.
+pub fn parse_udl(udl: &str) -> anyhow::Result<ComponentInterface> {
+    ComponentInterface::from_webidl(udl, CRATE_NAME)
+}
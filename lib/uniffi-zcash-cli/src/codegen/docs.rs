@@ -0,0 +1,162 @@
+use std::{
+    collections::BTreeMap,
+    fs::{read_dir, read_to_string},
+    path::Path,
+};
+
+use anyhow::bail;
+use uniffi_bindgen::ComponentInterface;
+
+use super::stability::ApiTarget;
+
+/// The Rust doc comments of the uniffi-zcash crate, keyed by the UDL name of the
+/// API they document, like `ZcashWalletDb` or `ZcashWalletDb.get_balance`.
+/// UniFFI 0.25 does not carry them to the bindings, so they are read from the
+/// Rust sources and inserted in the generated code afterwards.
+#[derive(Debug, Default)]
+pub struct ApiDocs {
+    docs: BTreeMap<String, String>,
+}
+
+impl ApiDocs {
+    /// Collects the doc comments of the exported types, their methods and constructors,
+    /// and the namespace functions, out of the Rust sources at `src_dir`. UniFFI maps
+    /// them by name, so the Rust items are named as in the UDL. Doc comments of items
+    /// not in the component interface, like the helpers, are left out.
+    ///
+    /// The items are indexed by their full path, so that two items of the same name in
+    /// different modules, like a helper named as an exported type, are not mixed up. Two
+    /// of them documenting the same API is an error.
+    pub fn collect(src_dir: &Path, ci: &ComponentInterface) -> anyhow::Result<Self> {
+        let mut by_path = BTreeMap::new();
+        index_src_dir(src_dir, "crate", &mut by_path)?;
+
+        let mut docs = BTreeMap::new();
+        let mut paths: BTreeMap<&str, &str> = BTreeMap::new();
+        for (path, doc) in &by_path {
+            let api = path.rsplit("::").next().unwrap_or(path);
+            if !ApiTarget::parse(api).exists_in(ci) {
+                continue;
+            }
+            if let Some(other) = paths.insert(api, path) {
+                bail!("Both {other} and {path} are documented as {api}, rename one of them");
+            }
+            docs.insert(api.to_string(), doc.clone());
+        }
+        Ok(ApiDocs { docs })
+    }
+
+    pub fn get(&self, target: ApiTarget) -> Option<&str> {
+        self.docs.get(&target.to_string()).map(String::as_str)
+    }
+
+    /// The documented APIs, types before their members.
+    pub fn targets(&self) -> impl Iterator<Item = ApiTarget<'_>> {
+        self.docs.keys().map(|api| ApiTarget::parse(api))
+    }
+}
+
+/// Indexes the Rust files of `dir`, the directory of the module at `module`. The files
+/// of the crate root and of the `mod.rs` modules are those of their directory module.
+fn index_src_dir(
+    dir: &Path,
+    module: &str,
+    docs: &mut BTreeMap<String, String>,
+) -> anyhow::Result<()> {
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if path.is_dir() {
+            index_src_dir(&path, &format!("{module}::{name}"), docs)?;
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            let file_module = match name {
+                "lib" | "mod" => module.to_string(),
+                _ => format!("{module}::{name}"),
+            };
+            index_rust_file(&file_module, &read_to_string(&path)?, docs);
+        }
+    }
+    Ok(())
+}
+
+/// Reads the doc comments of a Rust file formatted by rustfmt: items are declared at
+/// the start of a line, their methods indented once, and blocks closed by a lone `}`.
+/// They are keyed by their path, like `crate::zcash_client_sqlite::ZcashWalletDb.get_balance`.
+fn index_rust_file(module: &str, source: &str, docs: &mut BTreeMap<String, String>) {
+    let mut pending: Vec<&str> = Vec::new();
+    // The type of the `impl` or `trait` block being read, if any.
+    let mut block: Option<&str> = None;
+
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        if let Some(doc) = trimmed.strip_prefix("///") {
+            pending.push(doc.strip_prefix(' ').unwrap_or(doc));
+            continue;
+        }
+        // Attributes go between the doc comment and the item.
+        if trimmed.starts_with("#[") {
+            continue;
+        }
+
+        let api = if line == "}" {
+            block = None;
+            None
+        } else if let Some(name) = impl_type(line) {
+            block = Some(name);
+            None
+        } else if let Some(name) = item_name(line, &["pub trait "]) {
+            block = Some(name);
+            Some(name.to_string())
+        } else if let Some(name) = item_name(line, &["pub struct ", "pub enum "]) {
+            Some(name.to_string())
+        } else if let Some(name) = item_name(line, &["pub fn "]) {
+            Some(name.to_string())
+        } else if let (Some(type_name), Some(name)) =
+            (block, item_name(line, &["    pub fn ", "    fn "]))
+        {
+            Some(format!("{type_name}.{name}"))
+        } else {
+            None
+        };
+
+        if let Some(api) = api {
+            if !pending.is_empty() {
+                docs.insert(format!("{module}::{api}"), rustdoc_to_text(&pending));
+            }
+        }
+        pending.clear();
+    }
+}
+
+/// The type of an inherent `impl` block, like `impl ZcashWalletDb {`.
+/// Trait implementations are not exported as such, so they are skipped.
+fn impl_type(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("impl ")?;
+    if rest.contains(" for ") {
+        return None;
+    }
+    rest.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .filter(|name| !name.is_empty())
+}
+
+fn item_name<'a>(line: &'a str, keywords: &[&str]) -> Option<&'a str> {
+    keywords.iter().find_map(|keyword| {
+        line.strip_prefix(keyword)?
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .next()
+            .filter(|name| !name.is_empty())
+    })
+}
+
+/// Joins the doc comment lines, turning the intra doc links, which only make sense
+/// in the Rust docs, into plain code spans.
+fn rustdoc_to_text(lines: &[&str]) -> String {
+    lines
+        .join("\n")
+        .trim()
+        .replace("[`", "`")
+        .replace("`]", "`")
+}
@@ -0,0 +1,148 @@
+use std::{
+    collections::BTreeMap,
+    fs::{read_dir, read_to_string},
+    path::Path,
+};
+
+use uniffi_bindgen::ComponentInterface;
+
+/// The domains the generated types are grouped by, when requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Domain {
+    Keys,
+    Wallet,
+    Tx,
+    Net,
+    Core,
+}
+
+impl Domain {
+    /// Lowercase name, used for packages and folders.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Domain::Keys => "keys",
+            Domain::Wallet => "wallet",
+            Domain::Tx => "tx",
+            Domain::Net => "net",
+            Domain::Core => "core",
+        }
+    }
+
+    /// Decides the domain of the types declared in an UDL file, given its path
+    /// relative to the UDL root folder. Any `keys` module goes to [`Domain::Keys`],
+    /// otherwise the first matching path prefix wins.
+    fn of_udl_file(relative_path: &str) -> Domain {
+        const RULES: [(&str, Domain); 19] = [
+            ("hdwallet/", Domain::Keys),
+            ("secp256k1/", Domain::Keys),
+            ("zcash_primitives/zip32/", Domain::Keys),
+            ("zcash_primitives/sapling/sapling_ivk.udl", Domain::Keys),
+            ("zcash_client_backend/service.udl", Domain::Net),
+            ("zcash_primitives/consensus/", Domain::Net),
+            ("zcash_primitives/block.udl", Domain::Net),
+            ("zcash_client_backend/data_api/", Domain::Wallet),
+            ("zcash_client_backend/fees", Domain::Wallet),
+            ("zcash_client_backend/wallet.udl", Domain::Wallet),
+            ("zcash_client_backend/decrypt.udl", Domain::Wallet),
+            ("zcash_client_backend/zip321.udl", Domain::Wallet),
+            ("zcash_client_sqlite/", Domain::Wallet),
+            ("payment.udl", Domain::Wallet),
+            ("zcash_primitives/transaction/", Domain::Tx),
+            ("zcash_primitives/memo/", Domain::Tx),
+            ("zcash_proofs/", Domain::Tx),
+            ("orchard/bundle.udl", Domain::Tx),
+            ("orchard/action.udl", Domain::Tx),
+        ];
+
+        if relative_path.contains("/keys/") {
+            return Domain::Keys;
+        }
+        RULES
+            .iter()
+            .find(|(prefix, _)| relative_path.starts_with(prefix))
+            .map(|(_, domain)| *domain)
+            .unwrap_or(Domain::Core)
+    }
+}
+
+/// The UDL names of the component interface types, grouped by domain.
+pub type DomainTypes = BTreeMap<Domain, Vec<String>>;
+
+/// Groups the component interface types by domain. The domain of each type is
+/// taken from the location of the UDL file declaring it (see `udl_dir`), as the
+/// UDL files are laid out after the librustzcash crates and modules.
+/// Types not found there (like the test support ones) end up in [`Domain::Core`].
+pub fn group_by_domain(ci: &ComponentInterface, udl_dir: &Path) -> anyhow::Result<DomainTypes> {
+    let mut declared_in = BTreeMap::new();
+    index_udl_dir(udl_dir, udl_dir, &mut declared_in)?;
+
+    let mut grouped = DomainTypes::new();
+    ci.enum_definitions()
+        .map(|e| e.name())
+        .chain(ci.record_definitions().map(|r| r.name()))
+        .chain(ci.object_definitions().iter().map(|o| o.name()))
+        .chain(ci.callback_interface_definitions().iter().map(|c| c.name()))
+        .for_each(|name| {
+            let domain = declared_in.get(name).copied().unwrap_or(Domain::Core);
+            grouped.entry(domain).or_default().push(name.to_string());
+        });
+    grouped.values_mut().for_each(|types| types.sort());
+    Ok(grouped)
+}
+
+fn index_udl_dir(
+    root: &Path,
+    dir: &Path,
+    declared_in: &mut BTreeMap<String, Domain>,
+) -> anyhow::Result<()> {
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            index_udl_dir(root, &path, declared_in)?;
+            continue;
+        }
+        if path.extension().map(|ext| ext != "udl").unwrap_or(true) {
+            continue;
+        }
+        let relative_path = path
+            .strip_prefix(root)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let domain = Domain::of_udl_file(&relative_path);
+        for name in declared_types(&read_to_string(&path)?) {
+            declared_in.insert(name, domain);
+        }
+    }
+    Ok(())
+}
+
+/// Names of the interfaces, dictionaries, enums and callback interfaces
+/// declared in the given UDL content.
+fn declared_types(udl: &str) -> Vec<String> {
+    udl.lines()
+        .filter_map(|line| {
+            // Attributes may be in the same line, like in `[Enum] interface ...`.
+            let line = line.trim().rsplit(']').next()?.trim();
+            let rest = line
+                .strip_prefix("callback interface ")
+                .or_else(|| line.strip_prefix("interface "))
+                .or_else(|| line.strip_prefix("dictionary "))
+                .or_else(|| line.strip_prefix("enum "))?;
+            let name = rest.split(|c: char| c.is_whitespace() || c == '{').next()?;
+            (!name.is_empty()).then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// The generated type name without the `Zcash` prefix, which is redundant inside a domain.
+/// The prefix is kept when the name would otherwise shadow the language error types.
+pub fn short_name(class_name: &str) -> &str {
+    match class_name.strip_prefix("Zcash") {
+        Some(rest)
+            if rest.starts_with(char::is_uppercase) && !matches!(rest, "Error" | "Exception") =>
+        {
+            rest
+        }
+        _ => class_name,
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt::Write;
+
+use heck::{ToLowerCamelCase, ToUpperCamelCase};
+use uniffi_bindgen::ComponentInterface;
+
+use super::{
+    docs::ApiDocs,
+    domains::{short_name, Domain, DomainTypes},
+    stability::{
+        annotate_declarations, declares, insert_before_declarations, ApiStability, ApiTarget,
+    },
+};
+
+/// The opt-in requirement marking experimental APIs.
+const EXPERIMENTAL_ANNOTATION: &str = "ExperimentalZcashApi";
+
+/// Generates a Kotlin file per domain, declaring a `uniffi.zcash.<domain>` package
+/// with type aliases to the UniFFI generated types, without their `Zcash` prefix.
+/// The generated types stay in the `uniffi.zcash` package, so both ways of referring
+/// to them can be mixed.
+pub fn domain_packages(
+    ci: &ComponentInterface,
+    grouped: &DomainTypes,
+) -> anyhow::Result<Vec<(Domain, String)>> {
+    let base_package = format!("uniffi.{}", ci.namespace());
+
+    grouped
+        .iter()
+        .map(|(domain, names)| {
+            let mut out = String::new();
+            writeln!(
+                out,
+                "// Autogenerated by the uniffi-zcash-cli - do not modify"
+            )?;
+            writeln!(out)?;
+            writeln!(out, "package {base_package}.{}", domain.name())?;
+            writeln!(out)?;
+            for name in names {
+                let class_name = class_name(ci, name);
+                if short_name(&class_name) == class_name {
+                    continue;
+                }
+                writeln!(
+                    out,
+                    "typealias {} = {base_package}.{class_name}",
+                    short_name(&class_name)
+                )?;
+            }
+            Ok((*domain, out))
+        })
+        .collect()
+}
+
+/// Annotates the UniFFI generated Kotlin source with the API stability manifest notes.
+/// Deprecated APIs get `@Deprecated`, while experimental ones require opting in
+/// with `@OptIn(ExperimentalZcashApi::class)`, or get a warning otherwise.
+pub fn annotate_stability(
+    ci: &ComponentInterface,
+    source: &str,
+    stability: &ApiStability,
+) -> anyhow::Result<String> {
+    let mut out = annotate_declarations(
+        source,
+        stability,
+        |lines, from, target| find_declaration(ci, lines, from, target),
+        |indent, deprecated, experimental| {
+            let mut annotations = Vec::new();
+            if let Some(note) = experimental {
+                annotations.push(format!("{indent}// Experimental: {}", note.text()));
+                annotations.push(format!("{indent}@{EXPERIMENTAL_ANNOTATION}"));
+            }
+            if let Some(note) = deprecated {
+                annotations.push(format!(
+                    "{indent}@Deprecated({})",
+                    note.quoted_text().replace('$', "\\$")
+                ));
+            }
+            annotations
+        },
+    )?;
+
+    if !stability.experimental.is_empty() {
+        // The generated code itself makes use of the experimental APIs.
+        out = out.replacen(
+            "@file:Suppress(\"NAME_SHADOWING\")",
+            &format!(
+                "@file:Suppress(\"NAME_SHADOWING\")\n@file:OptIn({EXPERIMENTAL_ANNOTATION}::class)"
+            ),
+            1,
+        );
+        writeln!(out)?;
+        writeln!(
+            out,
+            "@RequiresOptIn(message = \"This API is experimental. It may change or be removed without a deprecation period.\", level = RequiresOptIn.Level.WARNING)"
+        )?;
+        writeln!(out, "@Retention(AnnotationRetention.BINARY)")?;
+        writeln!(out, "annotation class {EXPERIMENTAL_ANNOTATION}")?;
+    }
+
+    Ok(out)
+}
+
+/// Inserts the Rust doc comments of the APIs as KDoc comments in the UniFFI generated
+/// Kotlin source. It goes before [`annotate_stability`], so the annotations end up
+/// between the comments and the declarations.
+pub fn document(ci: &ComponentInterface, source: &str, docs: &ApiDocs) -> anyhow::Result<String> {
+    insert_before_declarations(
+        source,
+        docs.targets(),
+        |lines, from, target| find_declaration(ci, lines, from, target),
+        |indent, target| {
+            let doc = docs.get(target).unwrap_or_default().replace("*/", "*&#47;");
+            let mut comment = vec![format!("{indent}/**")];
+            comment.extend(
+                doc.lines()
+                    .map(|line| format!("{indent} * {line}").trim_end().to_string()),
+            );
+            comment.push(format!("{indent} */"));
+            comment
+        },
+    )
+}
+
+fn find_declaration(
+    ci: &ComponentInterface,
+    lines: &[&str],
+    from: usize,
+    target: ApiTarget,
+) -> Option<usize> {
+    // Converted once, as the whole source may be searched.
+    let name = match target {
+        ApiTarget::Type(name) => class_name(ci, name),
+        ApiTarget::Function(name) | ApiTarget::Member(_, name) => name.to_lower_camel_case(),
+    };
+    lines
+        .iter()
+        .enumerate()
+        .skip(from)
+        .find(|(_, line)| declares_target(line, target, &name))
+        .map(|(idx, _)| idx)
+}
+
+/// Whether the line declares the target, named `name` in Kotlin.
+fn declares_target(line: &str, target: ApiTarget, name: &str) -> bool {
+    match target {
+        ApiTarget::Type(_) => declares(
+            line,
+            &[
+                "class ",
+                "data class ",
+                "enum class ",
+                "sealed class ",
+                "public interface ",
+            ],
+            name,
+        ),
+        ApiTarget::Function(_) => declares(skip_annotations(line), &["fun ", "suspend fun "], name),
+        // The primary constructor of objects.
+        ApiTarget::Member(_, "new") => line.trim_start().starts_with("constructor("),
+        ApiTarget::Member(..) => declares(
+            skip_annotations(line.trim_start()),
+            &[
+                "fun ",
+                "override fun ",
+                "suspend fun ",
+                "override suspend fun ",
+            ],
+            name,
+        ),
+    }
+}
+
+/// Skips the annotations, like `@Throws(ZcashException::class)`, UniFFI places
+/// in the same line of the declaration when the code is not formatted.
+fn skip_annotations(mut line: &str) -> &str {
+    while let Some(rest) = line.strip_prefix('@') {
+        let end = match (rest.find('('), rest.find(char::is_whitespace)) {
+            (Some(open), Some(space)) if open < space => rest.find(')').map(|close| close + 1),
+            (Some(_), None) => rest.find(')').map(|close| close + 1),
+            (_, space) => space,
+        };
+        match end {
+            Some(end) => line = rest[end..].trim_start(),
+            None => break,
+        }
+    }
+    line
+}
+
+/// Mirrors the class naming of the UniFFI Kotlin backend, where errors end with `Exception`.
+fn class_name(ci: &ComponentInterface, name: &str) -> String {
+    let class_name = name.to_upper_camel_case();
+    if !ci.is_name_used_as_error(name) {
+        return class_name;
+    }
+    match class_name.strip_suffix("Error") {
+        Some(stripped) => format!("{stripped}Exception"),
+        None => class_name,
+    }
+}
@@ -0,0 +1,480 @@
+use std::fmt::Write;
+
+use heck::{ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use uniffi_bindgen::{
+    interface::{Argument, AsType, Enum, Field, Function, Method, Object, Record, Type},
+    ComponentInterface,
+};
+
+use super::{
+    docs::ApiDocs,
+    stability::{declares, insert_before_declarations, ApiStability, ApiTarget},
+};
+
+const KEYWORDS: [&str; 36] = [
+    "False",
+    "None",
+    "True",
+    "__peg_parser__",
+    "and",
+    "as",
+    "assert",
+    "async",
+    "await",
+    "break",
+    "class",
+    "continue",
+    "def",
+    "del",
+    "elif",
+    "else",
+    "except",
+    "finally",
+    "for",
+    "from",
+    "global",
+    "if",
+    "import",
+    "in",
+    "is",
+    "lambda",
+    "nonlocal",
+    "not",
+    "or",
+    "pass",
+    "raise",
+    "return",
+    "try",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Generates the `.pyi` type stubs for the UniFFI generated `zcash.py` module,
+/// so IDEs and type checkers like mypy can check the code written against
+/// the bindings. Names follow the same rules as the UniFFI Python backend.
+/// Deprecated APIs are flagged as per [PEP 702](https://peps.python.org/pep-0702/),
+/// and the documented ones get the docstrings of [`document`].
+pub fn type_stubs(
+    ci: &ComponentInterface,
+    stability: &ApiStability,
+    docs: &ApiDocs,
+) -> anyhow::Result<String> {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "# Autogenerated by the uniffi-zcash-cli - do not modify"
+    )?;
+    writeln!(out)?;
+    writeln!(out, "import datetime")?;
+    writeln!(out, "import enum")?;
+    writeln!(out, "import typing")?;
+    if !stability.deprecated.is_empty() {
+        writeln!(out, "import typing_extensions")?;
+    }
+
+    for e in ci.enum_definitions() {
+        writeln!(out)?;
+        write_stability(&mut out, "", stability, ApiTarget::Type(e.name()))?;
+        if ci.is_name_used_as_error(e.name()) {
+            write_error(&mut out, docs, e)?;
+        } else {
+            write_enum(&mut out, docs, e)?;
+        }
+    }
+
+    for rec in ci.record_definitions() {
+        writeln!(out)?;
+        write_stability(&mut out, "", stability, ApiTarget::Type(rec.name()))?;
+        write_record(&mut out, docs, rec)?;
+    }
+
+    for obj in ci.object_definitions() {
+        writeln!(out)?;
+        write_stability(&mut out, "", stability, ApiTarget::Type(obj.name()))?;
+        write_object(&mut out, stability, docs, obj)?;
+    }
+
+    for cbi in ci.callback_interface_definitions() {
+        writeln!(out)?;
+        write_stability(&mut out, "", stability, ApiTarget::Type(cbi.name()))?;
+        writeln!(out, "class {}:", class_name(cbi.name()))?;
+        write_docstring(&mut out, "    ", docs.get(ApiTarget::Type(cbi.name())))?;
+        write_methods(&mut out, stability, docs, cbi.name(), &cbi.methods())?;
+    }
+
+    if !ci.function_definitions().is_empty() {
+        writeln!(out)?;
+    }
+    for func in ci.function_definitions() {
+        write_stability(&mut out, "", stability, ApiTarget::Function(func.name()))?;
+        write_function(&mut out, docs, func)?;
+    }
+
+    Ok(out)
+}
+
+fn write_enum(out: &mut String, docs: &ApiDocs, e: &Enum) -> anyhow::Result<()> {
+    let name = class_name(e.name());
+    let doc = docs.get(ApiTarget::Type(e.name()));
+
+    if e.is_flat() {
+        writeln!(out, "class {name}(enum.Enum):")?;
+        write_docstring(out, "    ", doc)?;
+        for (idx, variant) in e.variants().iter().enumerate() {
+            writeln!(
+                out,
+                "    {} = {}",
+                enum_variant_name(variant.name()),
+                idx + 1
+            )?;
+        }
+        return Ok(());
+    }
+
+    writeln!(out, "class {name}:")?;
+    write_docstring(out, "    ", doc)?;
+    for variant in e.variants() {
+        let variant_name = enum_variant_name(variant.name());
+        writeln!(out, "    class {variant_name}({name}):")?;
+        write_fields(out, "        ", variant.fields())?;
+        writeln!(
+            out,
+            "        def __init__(self{}) -> None: ...",
+            field_list_decl(variant.fields())
+        )?;
+    }
+    for variant in e.variants() {
+        writeln!(
+            out,
+            "    def is_{}(self) -> bool: ...",
+            var_name(variant.name())
+        )?;
+    }
+    Ok(())
+}
+
+fn write_error(out: &mut String, docs: &ApiDocs, e: &Enum) -> anyhow::Result<()> {
+    let name = class_name(e.name());
+
+    writeln!(out, "class {name}(Exception):")?;
+    write_docstring(out, "    ", docs.get(ApiTarget::Type(e.name())))?;
+    for variant in e.variants() {
+        writeln!(out, "    class {}({name}):", class_name(variant.name()))?;
+        if e.is_flat() {
+            writeln!(out, "        ...")?;
+        } else {
+            write_fields(out, "        ", variant.fields())?;
+            writeln!(
+                out,
+                "        def __init__(self{}) -> None: ...",
+                field_list_decl(variant.fields())
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write_record(out: &mut String, docs: &ApiDocs, rec: &Record) -> anyhow::Result<()> {
+    writeln!(out, "class {}:", class_name(rec.name()))?;
+    write_docstring(out, "    ", docs.get(ApiTarget::Type(rec.name())))?;
+    write_fields(out, "    ", rec.fields())?;
+    writeln!(
+        out,
+        "    def __init__(self{}) -> None: ...",
+        field_list_decl(rec.fields())
+    )?;
+    Ok(())
+}
+
+fn write_object(
+    out: &mut String,
+    stability: &ApiStability,
+    docs: &ApiDocs,
+    obj: &Object,
+) -> anyhow::Result<()> {
+    let name = class_name(obj.name());
+
+    writeln!(out, "class {name}:")?;
+    write_docstring(out, "    ", docs.get(ApiTarget::Type(obj.name())))?;
+    if let Some(cons) = obj.primary_constructor() {
+        write!(
+            out,
+            "    def __init__(self{}) -> None",
+            arg_list_decl(&cons.arguments())
+        )?;
+        write_def_body(
+            out,
+            "    ",
+            docs.get(ApiTarget::Member(obj.name(), cons.name())),
+        )?;
+    }
+    for cons in obj.alternate_constructors() {
+        writeln!(out, "    @classmethod")?;
+        write_stability(
+            out,
+            "    ",
+            stability,
+            ApiTarget::Member(obj.name(), cons.name()),
+        )?;
+        write!(
+            out,
+            "    def {}(cls{}) -> \"{name}\"",
+            fn_name(cons.name()),
+            arg_list_decl(&cons.arguments())
+        )?;
+        write_def_body(
+            out,
+            "    ",
+            docs.get(ApiTarget::Member(obj.name(), cons.name())),
+        )?;
+    }
+    write_methods(out, stability, docs, obj.name(), &obj.methods())?;
+    Ok(())
+}
+
+fn write_methods(
+    out: &mut String,
+    stability: &ApiStability,
+    docs: &ApiDocs,
+    type_name: &str,
+    methods: &[&Method],
+) -> anyhow::Result<()> {
+    if methods.is_empty() {
+        writeln!(out, "    ...")?;
+    }
+    for meth in methods {
+        write_stability(
+            out,
+            "    ",
+            stability,
+            ApiTarget::Member(type_name, meth.name()),
+        )?;
+        write!(
+            out,
+            "    def {}(self{}) -> {}",
+            fn_name(meth.name()),
+            arg_list_decl(&meth.arguments()),
+            return_type_name(meth.return_type())
+        )?;
+        write_def_body(
+            out,
+            "    ",
+            docs.get(ApiTarget::Member(type_name, meth.name())),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_function(out: &mut String, docs: &ApiDocs, func: &Function) -> anyhow::Result<()> {
+    let args = arg_list_decl(&func.arguments());
+    write!(
+        out,
+        "def {}({}) -> {}",
+        fn_name(func.name()),
+        args.trim_start_matches(", "),
+        return_type_name(func.return_type())
+    )?;
+    write_def_body(out, "", docs.get(ApiTarget::Function(func.name())))
+}
+
+/// Ends a `def` signature, with the docstring as body if documented.
+fn write_def_body(out: &mut String, indent: &str, doc: Option<&str>) -> anyhow::Result<()> {
+    match doc {
+        Some(_) => {
+            writeln!(out, ":")?;
+            write_docstring(out, &format!("{indent}    "), doc)
+        }
+        None => Ok(writeln!(out, ": ...")?),
+    }
+}
+
+fn write_docstring(out: &mut String, indent: &str, doc: Option<&str>) -> anyhow::Result<()> {
+    if let Some(doc) = doc {
+        for line in docstring(indent, doc) {
+            writeln!(out, "{line}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Inserts the Rust doc comments of the APIs as docstrings in the UniFFI generated
+/// `zcash.py` module, so `help()` and pydoc show them. Docstrings go right after the
+/// `class` or `def` line, indented as the body following it.
+pub fn document(source: &str, docs: &ApiDocs) -> anyhow::Result<String> {
+    insert_before_declarations(
+        source,
+        docs.targets(),
+        |lines, from, target| {
+            // Converted once, as the whole source may be searched.
+            let name = match target {
+                ApiTarget::Type(name) => class_name(name),
+                ApiTarget::Function(name) | ApiTarget::Member(_, name) => fn_name(name),
+            };
+            lines
+                .iter()
+                .enumerate()
+                .skip(from)
+                .find(|(_, line)| declares_target(line, target, &name))
+                .map(|(idx, _)| idx + 1)
+        },
+        |indent, target| docstring(indent, docs.get(target).unwrap_or_default()),
+    )
+}
+
+/// Whether the line declares the target, named `name` in Python.
+fn declares_target(line: &str, target: ApiTarget, name: &str) -> bool {
+    match target {
+        ApiTarget::Type(_) => declares(line, &["class "], name),
+        ApiTarget::Function(_) => declares(line, &["def "], name),
+        // The primary constructor of objects.
+        ApiTarget::Member(_, "new") => line.trim_start().starts_with("def __init__("),
+        ApiTarget::Member(..) => declares(line.trim_start(), &["def "], name),
+    }
+}
+
+/// The lines of a docstring, with its quotes escaped so it cannot end early.
+fn docstring(indent: &str, doc: &str) -> Vec<String> {
+    let doc = doc.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut lines: Vec<String> = doc
+        .lines()
+        .map(|line| format!("{indent}{line}").trim_end().to_string())
+        .collect();
+    if lines.len() == 1 {
+        return vec![format!("{indent}\"\"\"{doc}\"\"\"")];
+    }
+    if let Some(first) = lines.first_mut() {
+        *first = format!("{indent}\"\"\"{}", first.trim_start());
+    }
+    lines.push(format!("{indent}\"\"\""));
+    lines
+}
+
+fn write_stability(
+    out: &mut String,
+    indent: &str,
+    stability: &ApiStability,
+    target: ApiTarget,
+) -> anyhow::Result<()> {
+    if let Some(note) = stability.experimental(target) {
+        writeln!(out, "{indent}# Experimental: {}", note.text())?;
+    }
+    if let Some(note) = stability.deprecation(target) {
+        writeln!(
+            out,
+            "{indent}@typing_extensions.deprecated({})",
+            note.quoted_text()
+        )?;
+    }
+    Ok(())
+}
+
+fn write_fields(out: &mut String, indent: &str, fields: &[Field]) -> anyhow::Result<()> {
+    for field in fields {
+        writeln!(
+            out,
+            "{indent}{}: {}",
+            var_name(field.name()),
+            type_name(&field.as_type())
+        )?;
+    }
+    Ok(())
+}
+
+/// Renders the arguments with a leading separator, as they always follow `self` or `cls`.
+fn arg_list_decl(args: &[&Argument]) -> String {
+    args.iter()
+        .map(|arg| {
+            let default = arg.default_value().map(|_| " = ...").unwrap_or_default();
+            format!(
+                ", {}: {}{}",
+                var_name(arg.name()),
+                type_name(&arg.as_type()),
+                default
+            )
+        })
+        .collect()
+}
+
+fn field_list_decl(fields: &[Field]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            let default = field.default_value().map(|_| " = ...").unwrap_or_default();
+            format!(
+                ", {}: {}{}",
+                var_name(field.name()),
+                type_name(&field.as_type()),
+                default
+            )
+        })
+        .collect()
+}
+
+fn return_type_name(return_type: Option<&Type>) -> String {
+    return_type
+        .map(type_name)
+        .unwrap_or_else(|| "None".to_string())
+}
+
+/// Mirrors the type naming of the UniFFI Python backend.
+fn type_name(t: &Type) -> String {
+    match t {
+        Type::UInt8
+        | Type::Int8
+        | Type::UInt16
+        | Type::Int16
+        | Type::UInt32
+        | Type::Int32
+        | Type::UInt64
+        | Type::Int64 => "int".into(),
+        Type::Float32 | Type::Float64 => "float".into(),
+        Type::Boolean => "bool".into(),
+        Type::String => "str".into(),
+        Type::Bytes => "bytes".into(),
+        Type::Timestamp => "datetime.datetime".into(),
+        Type::Duration => "datetime.timedelta".into(),
+        Type::ForeignExecutor => "typing.Any".into(),
+        Type::Object { name, .. }
+        | Type::Record { name, .. }
+        | Type::Enum { name, .. }
+        | Type::CallbackInterface { name, .. }
+        | Type::External { name, .. }
+        | Type::Custom { name, .. } => format!("\"{}\"", class_name(name)),
+        Type::Optional { inner_type } => format!("typing.Optional[{}]", type_name(inner_type)),
+        Type::Sequence { inner_type } => format!("typing.List[{}]", type_name(inner_type)),
+        Type::Map {
+            key_type,
+            value_type,
+        } => format!(
+            "typing.Dict[{}, {}]",
+            type_name(key_type),
+            type_name(value_type)
+        ),
+    }
+}
+
+fn fixup_keyword(name: String) -> String {
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+fn class_name(name: &str) -> String {
+    fixup_keyword(name.to_upper_camel_case())
+}
+
+fn fn_name(name: &str) -> String {
+    fixup_keyword(name.to_snake_case())
+}
+
+fn var_name(name: &str) -> String {
+    fixup_keyword(name.to_snake_case())
+}
+
+fn enum_variant_name(name: &str) -> String {
+    fixup_keyword(name.to_shouty_snake_case())
+}
@@ -0,0 +1,308 @@
+use std::fmt::Write;
+
+use heck::{ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use uniffi_bindgen::{
+    interface::{Argument, AsType, Enum, Field, Function, Method, Object, Record, Type},
+    ComponentInterface,
+};
+
+const RESERVED_WORDS: [&str; 39] = [
+    "alias", "and", "BEGIN", "begin", "break", "case", "class", "def", "defined?", "do", "else",
+    "elsif", "END", "end", "ensure", "false", "for", "if", "module", "next", "nil", "not", "or",
+    "redo", "rescue", "retry", "return", "self", "super", "then", "true", "undef", "unless",
+    "until", "when", "while", "yield", "__FILE__", "__LINE__",
+];
+
+/// Generates the RBS signatures for the UniFFI generated `zcash.rb` module,
+/// so Steep (or Sorbet, through its RBS support) can type check the code
+/// written against the bindings. Names follow the same rules as the UniFFI
+/// Ruby backend.
+pub fn rbs_signatures(ci: &ComponentInterface) -> anyhow::Result<String> {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "# Autogenerated by the uniffi-zcash-cli - do not modify"
+    )?;
+    writeln!(out)?;
+    writeln!(out, "module {}", class_name(ci.namespace()))?;
+
+    for e in ci.enum_definitions() {
+        if ci.is_name_used_as_error(e.name()) {
+            write_error(&mut out, ci, e)?;
+        } else {
+            write_enum(&mut out, ci, e)?;
+        }
+        writeln!(out)?;
+    }
+
+    for rec in ci.record_definitions() {
+        write_record(&mut out, ci, rec)?;
+        writeln!(out)?;
+    }
+
+    for obj in ci.object_definitions() {
+        write_object(&mut out, ci, obj)?;
+        writeln!(out)?;
+    }
+
+    for func in ci.function_definitions() {
+        write_function(&mut out, ci, func)?;
+    }
+
+    writeln!(out, "end")?;
+
+    Ok(out)
+}
+
+fn write_enum(out: &mut String, ci: &ComponentInterface, e: &Enum) -> anyhow::Result<()> {
+    let name = class_name(e.name());
+
+    writeln!(out, "  class {name}")?;
+    if e.is_flat() {
+        // Flat enums are plain integer constants in the Ruby backend.
+        for variant in e.variants() {
+            writeln!(out, "    {}: Integer", enum_name(variant.name()))?;
+        }
+        writeln!(out, "  end")?;
+        return Ok(());
+    }
+
+    for variant in e.variants() {
+        writeln!(out, "    class {} < {name}", enum_name(variant.name()))?;
+        write_fields(out, ci, "      ", variant.fields())?;
+        writeln!(
+            out,
+            "      def initialize: ({}) -> void",
+            field_list_decl(ci, variant.fields())
+        )?;
+        writeln!(out, "    end")?;
+    }
+    for variant in e.variants() {
+        writeln!(out, "    def {}?: () -> bool", var_name(variant.name()))?;
+    }
+    writeln!(out, "  end")?;
+    Ok(())
+}
+
+fn write_error(out: &mut String, ci: &ComponentInterface, e: &Enum) -> anyhow::Result<()> {
+    let name = class_name(e.name());
+
+    if e.is_flat() {
+        writeln!(out, "  class {name}")?;
+        for variant in e.variants() {
+            writeln!(
+                out,
+                "    class {} < StandardError",
+                class_name(variant.name())
+            )?;
+            writeln!(out, "    end")?;
+        }
+        writeln!(out, "  end")?;
+        return Ok(());
+    }
+
+    writeln!(out, "  module {name}")?;
+    for variant in e.variants() {
+        writeln!(
+            out,
+            "    class {} < StandardError",
+            class_name(variant.name())
+        )?;
+        write_fields(out, ci, "      ", variant.fields())?;
+        writeln!(
+            out,
+            "      def initialize: ({}) -> void",
+            field_list_decl(ci, variant.fields())
+        )?;
+        writeln!(out, "    end")?;
+    }
+    writeln!(out, "  end")?;
+    Ok(())
+}
+
+fn write_record(out: &mut String, ci: &ComponentInterface, rec: &Record) -> anyhow::Result<()> {
+    writeln!(out, "  class {}", class_name(rec.name()))?;
+    write_fields(out, ci, "    ", rec.fields())?;
+    writeln!(
+        out,
+        "    def initialize: ({}) -> void",
+        field_list_decl(ci, rec.fields())
+    )?;
+    writeln!(out, "  end")?;
+    Ok(())
+}
+
+fn write_object(out: &mut String, ci: &ComponentInterface, obj: &Object) -> anyhow::Result<()> {
+    let name = class_name(obj.name());
+
+    writeln!(out, "  class {name}")?;
+    if let Some(cons) = obj.primary_constructor() {
+        writeln!(
+            out,
+            "    def initialize: ({}) -> void",
+            arg_list_decl(ci, &cons.arguments())
+        )?;
+    }
+    for cons in obj.alternate_constructors() {
+        writeln!(
+            out,
+            "    def self.{}: ({}) -> {name}",
+            fn_name(cons.name()),
+            arg_list_decl(ci, &cons.arguments())
+        )?;
+    }
+    write_methods(out, ci, &obj.methods())?;
+    writeln!(out, "  end")?;
+    Ok(())
+}
+
+fn write_methods(
+    out: &mut String,
+    ci: &ComponentInterface,
+    methods: &[&Method],
+) -> anyhow::Result<()> {
+    for meth in methods {
+        writeln!(
+            out,
+            "    def {}: ({}) -> {}",
+            fn_name(meth.name()),
+            arg_list_decl(ci, &meth.arguments()),
+            return_type_name(ci, meth.return_type())
+        )?;
+    }
+    Ok(())
+}
+
+fn write_function(
+    out: &mut String,
+    ci: &ComponentInterface,
+    func: &Function,
+) -> anyhow::Result<()> {
+    writeln!(
+        out,
+        "  def self.{}: ({}) -> {}",
+        fn_name(func.name()),
+        arg_list_decl(ci, &func.arguments()),
+        return_type_name(ci, func.return_type())
+    )?;
+    Ok(())
+}
+
+fn write_fields(
+    out: &mut String,
+    ci: &ComponentInterface,
+    indent: &str,
+    fields: &[Field],
+) -> anyhow::Result<()> {
+    for field in fields {
+        writeln!(
+            out,
+            "{indent}attr_reader {}: {}",
+            var_name(field.name()),
+            type_name(ci, &field.as_type())
+        )?;
+    }
+    Ok(())
+}
+
+/// Arguments with a default value are optional positional ones (`?T name`) in RBS.
+fn arg_list_decl(ci: &ComponentInterface, args: &[&Argument]) -> String {
+    args.iter()
+        .map(|arg| {
+            let optional = arg.default_value().map(|_| "?").unwrap_or_default();
+            format!(
+                "{}{} {}",
+                optional,
+                type_name(ci, &arg.as_type()),
+                var_name(arg.name())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn field_list_decl(ci: &ComponentInterface, fields: &[Field]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            format!(
+                "{} {}",
+                type_name(ci, &field.as_type()),
+                var_name(field.name())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn return_type_name(ci: &ComponentInterface, return_type: Option<&Type>) -> String {
+    return_type
+        .map(|t| type_name(ci, t))
+        .unwrap_or_else(|| "void".to_string())
+}
+
+/// Mirrors the type mapping of the UniFFI Ruby backend.
+fn type_name(ci: &ComponentInterface, t: &Type) -> String {
+    match t {
+        Type::UInt8
+        | Type::Int8
+        | Type::UInt16
+        | Type::Int16
+        | Type::UInt32
+        | Type::Int32
+        | Type::UInt64
+        | Type::Int64 => "Integer".into(),
+        Type::Float32 | Type::Float64 => "Float".into(),
+        Type::Boolean => "bool".into(),
+        Type::String | Type::Bytes => "String".into(),
+        // The Ruby backend lifts durations as `Time` instances too.
+        Type::Timestamp | Type::Duration => "Time".into(),
+        Type::ForeignExecutor => "untyped".into(),
+        Type::Enum { name, .. }
+            if ci
+                .get_enum_definition(name)
+                .map(|e| e.is_flat() && !ci.is_name_used_as_error(name))
+                .unwrap_or(false) =>
+        {
+            "Integer".into()
+        }
+        Type::Object { name, .. }
+        | Type::Record { name, .. }
+        | Type::Enum { name, .. }
+        | Type::CallbackInterface { name, .. }
+        | Type::External { name, .. }
+        | Type::Custom { name, .. } => class_name(name),
+        Type::Optional { inner_type } => format!("{}?", type_name(ci, inner_type)),
+        Type::Sequence { inner_type } => format!("Array[{}]", type_name(ci, inner_type)),
+        Type::Map {
+            key_type,
+            value_type,
+        } => format!(
+            "Hash[{}, {}]",
+            type_name(ci, key_type),
+            type_name(ci, value_type)
+        ),
+    }
+}
+
+fn class_name(name: &str) -> String {
+    name.to_upper_camel_case()
+}
+
+fn fn_name(name: &str) -> String {
+    name.to_snake_case()
+}
+
+fn var_name(name: &str) -> String {
+    let prefix = if RESERVED_WORDS.contains(&name) {
+        "_"
+    } else {
+        ""
+    };
+    format!("{prefix}{}", name.to_snake_case())
+}
+
+fn enum_name(name: &str) -> String {
+    name.to_shouty_snake_case()
+}
@@ -0,0 +1,211 @@
+use std::{fmt, fs::read_to_string, path::Path};
+
+use anyhow::anyhow;
+use serde::Deserialize;
+use uniffi_bindgen::ComponentInterface;
+
+/// The API stability manifest, see `lib/uniffi-zcash/api_stability.toml`.
+/// Anything not listed in it is considered stable.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiStability {
+    #[serde(default)]
+    pub deprecated: Vec<ApiNote>,
+    #[serde(default)]
+    pub experimental: Vec<ApiNote>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiNote {
+    /// The UDL name of a type (`ZcashFixedFeeRule`), of a type member
+    /// (`ZcashFixedFeeRule.standard`) or of a namespace function (`decode_payment_address`).
+    pub api: String,
+    pub message: String,
+    /// The uniffi-zcash version the note applies from.
+    pub since: Option<String>,
+}
+
+/// What an [`ApiNote`] refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiTarget<'a> {
+    Type(&'a str),
+    Member(&'a str, &'a str),
+    Function(&'a str),
+}
+
+impl<'a> ApiTarget<'a> {
+    /// Parses an UDL name, as written in the manifest. UDL types are UpperCamelCase
+    /// while namespace functions are snake_case.
+    pub fn parse(api: &'a str) -> Self {
+        match api.split_once('.') {
+            Some((type_name, member)) => ApiTarget::Member(type_name, member),
+            None if api.starts_with(char::is_uppercase) => ApiTarget::Type(api),
+            None => ApiTarget::Function(api),
+        }
+    }
+
+    pub fn exists_in(self, ci: &ComponentInterface) -> bool {
+        match self {
+            ApiTarget::Type(name) => ci.get_type(name).is_some(),
+            ApiTarget::Function(name) => ci.get_function_definition(name).is_some(),
+            ApiTarget::Member(type_name, member) => {
+                if let Some(obj) = ci.get_object_definition(type_name) {
+                    obj.constructors().iter().any(|c| c.name() == member)
+                        || obj.methods().iter().any(|m| m.name() == member)
+                } else if let Some(cbi) = ci.get_callback_interface_definition(type_name) {
+                    cbi.methods().iter().any(|m| m.name() == member)
+                } else {
+                    false
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for ApiTarget<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiTarget::Type(name) | ApiTarget::Function(name) => write!(f, "{name}"),
+            ApiTarget::Member(type_name, member) => write!(f, "{type_name}.{member}"),
+        }
+    }
+}
+
+impl ApiStability {
+    /// Reads the manifest, checking every listed API exists in the component interface,
+    /// so notes do not silently go stale when the UDL changes.
+    pub fn load(path: &Path, ci: &ComponentInterface) -> anyhow::Result<Self> {
+        let content = read_to_string(path)
+            .map_err(|err| anyhow!("Cannot read {}: {}", path.to_string_lossy(), err))?;
+        let stability: ApiStability = toml::from_str(&content)?;
+
+        for note in stability.deprecated.iter().chain(&stability.experimental) {
+            if !note.target().exists_in(ci) {
+                return Err(anyhow!(
+                    "{}: the API \"{}\" is not part of the UDL",
+                    path.to_string_lossy(),
+                    note.api
+                ));
+            }
+        }
+        Ok(stability)
+    }
+
+    pub fn deprecation(&self, target: ApiTarget) -> Option<&ApiNote> {
+        self.deprecated.iter().find(|note| note.target() == target)
+    }
+
+    pub fn experimental(&self, target: ApiTarget) -> Option<&ApiNote> {
+        self.experimental
+            .iter()
+            .find(|note| note.target() == target)
+    }
+}
+
+impl ApiNote {
+    pub fn target(&self) -> ApiTarget<'_> {
+        ApiTarget::parse(&self.api)
+    }
+
+    /// The note message, including the version it applies from, if any.
+    pub fn text(&self) -> String {
+        match &self.since {
+            Some(since) => format!("{} (since {})", self.message, since),
+            None => self.message.clone(),
+        }
+    }
+
+    /// The note text as a double quoted string literal, valid in Swift and Python.
+    /// Kotlin needs `$` escaped on top of it, as it starts string templates there.
+    pub fn quoted_text(&self) -> String {
+        format!(
+            "\"{}\"",
+            self.text().replace('\\', "\\\\").replace('"', "\\\"")
+        )
+    }
+}
+
+/// Annotates the declarations of the given source, generated by UniFFI, with the
+/// manifest notes. The `annotate` closure returns the lines to insert before the
+/// declaration of an API, for the given indentation, out of its notes.
+pub fn annotate_declarations<'a>(
+    source: &str,
+    stability: &'a ApiStability,
+    find: impl Fn(&[&str], usize, ApiTarget) -> Option<usize>,
+    annotate: impl Fn(&str, Option<&'a ApiNote>, Option<&'a ApiNote>) -> Vec<String>,
+) -> anyhow::Result<String> {
+    insert_before_declarations(
+        source,
+        stability
+            .deprecated
+            .iter()
+            .chain(&stability.experimental)
+            .map(ApiNote::target),
+        find,
+        |indent, target| {
+            annotate(
+                indent,
+                stability.deprecation(target),
+                stability.experimental(target),
+            )
+        },
+    )
+}
+
+/// Inserts lines before the declarations of the given targets, in a source generated
+/// by UniFFI. The `find` closure locates the line declaring a target, starting at the
+/// given line index, while `insert` returns the lines to insert before it, for the
+/// given indentation. Members are searched after the declaration of their type,
+/// where UniFFI places them. Targets found at the same line are handled once.
+pub fn insert_before_declarations<'a>(
+    source: &str,
+    targets: impl IntoIterator<Item = ApiTarget<'a>>,
+    find: impl Fn(&[&str], usize, ApiTarget) -> Option<usize>,
+    insert: impl Fn(&str, ApiTarget<'a>) -> Vec<String>,
+) -> anyhow::Result<String> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut insertions: Vec<(usize, Vec<String>)> = Vec::new();
+
+    for target in targets {
+        let from = match target {
+            ApiTarget::Member(type_name, _) => find(&lines, 0, ApiTarget::Type(type_name))
+                .ok_or_else(|| anyhow!("Cannot find the declaration of {type_name}"))?,
+            _ => 0,
+        };
+        let idx = find(&lines, from, target)
+            .ok_or_else(|| anyhow!("Cannot find the declaration of {target}"))?;
+        if insertions.iter().any(|(i, _)| *i == idx) {
+            continue;
+        }
+        let line = lines[idx];
+        let indent = &line[..line.len() - line.trim_start().len()];
+        insertions.push((idx, insert(indent, target)));
+    }
+
+    insertions.sort_by_key(|(idx, _)| *idx);
+    let mut out = String::with_capacity(source.len());
+    let mut next = insertions.iter().peekable();
+    for (idx, line) in lines.iter().enumerate() {
+        while let Some((_, inserted)) = next.next_if(|(i, _)| *i == idx) {
+            for inserted_line in inserted {
+                out.push_str(inserted_line);
+                out.push('\n');
+            }
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Whether the line declares `name` with any of the given keywords, like `public func`.
+/// Names quoted in backticks, as Kotlin functions are, are matched too.
+pub fn declares(line: &str, keywords: &[&str], name: &str) -> bool {
+    keywords.iter().any(|keyword| {
+        line.strip_prefix(keyword)
+            .and_then(|rest| rest.trim_start_matches('`').strip_prefix(name))
+            .map(|after| !after.starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+            .unwrap_or(false)
+    })
+}
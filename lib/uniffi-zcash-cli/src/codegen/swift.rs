@@ -0,0 +1,387 @@
+use std::fmt::Write;
+
+use heck::{ToLowerCamelCase, ToUpperCamelCase};
+use uniffi_bindgen::{
+    interface::{Argument, AsType, CallbackInterface, Function, Method, Object, Type},
+    ComponentInterface,
+};
+
+use super::{
+    docs::ApiDocs,
+    domains::{short_name, DomainTypes},
+    stability::{
+        annotate_declarations, declares, insert_before_declarations, ApiStability, ApiTarget,
+    },
+};
+
+/// Generates a Swift overlay for the bindings, to be placed next to the UniFFI
+/// generated `zcash.swift` file. It provides:
+///
+/// * `async` overloads of every throwing top level function and object method.
+///   The blocking call is moved to a global dispatch queue, so callers can `await`
+///   database and network bound operations without blocking their actors.
+/// * An `AsyncThrowingStream` adapter per each callback interface, so callback
+///   driven APIs can be consumed with `for try await`.
+///
+/// The UDL is kept language neutral, this only adds Swift sugar on top of it.
+pub fn async_overlay(ci: &ComponentInterface) -> anyhow::Result<String> {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "// Autogenerated by the uniffi-zcash-cli - do not modify"
+    )?;
+    writeln!(out)?;
+    writeln!(out, "import Foundation")?;
+
+    for func in ci.function_definitions().iter().filter(|f| f.throws()) {
+        writeln!(out)?;
+        write_function(&mut out, func)?;
+    }
+
+    for obj in ci.object_definitions() {
+        write_object_extension(&mut out, obj)?;
+    }
+
+    for cbi in ci.callback_interface_definitions() {
+        write_callback_stream(&mut out, cbi)?;
+    }
+
+    Ok(out)
+}
+
+/// Annotates the UniFFI generated Swift source with the API stability manifest notes.
+/// Deprecated APIs get an `@available(*, deprecated)` attribute. Swift has no opt-in
+/// mechanism, so experimental ones are only flagged in their documentation.
+pub fn annotate_stability(source: &str, stability: &ApiStability) -> anyhow::Result<String> {
+    annotate_declarations(
+        source,
+        stability,
+        find_declaration,
+        |indent, deprecated, experimental| {
+            let mut annotations = Vec::new();
+            if let Some(note) = experimental {
+                annotations.push(format!("{indent}/// - Experimental: {}", note.text()));
+            }
+            if let Some(note) = deprecated {
+                annotations.push(format!(
+                    "{indent}@available(*, deprecated, message: {})",
+                    note.quoted_text()
+                ));
+            }
+            annotations
+        },
+    )
+}
+
+/// Inserts the Rust doc comments of the APIs as documentation comments in the UniFFI
+/// generated Swift source. It goes before [`annotate_stability`], so the experimental
+/// notes are appended to the comments.
+pub fn document(source: &str, docs: &ApiDocs) -> anyhow::Result<String> {
+    insert_before_declarations(
+        source,
+        docs.targets(),
+        find_declaration,
+        |indent, target| {
+            docs.get(target)
+                .unwrap_or_default()
+                .lines()
+                .map(|line| format!("{indent}/// {line}").trim_end().to_string())
+                .collect()
+        },
+    )
+}
+
+fn find_declaration(lines: &[&str], from: usize, target: ApiTarget) -> Option<usize> {
+    // Converted once, as the whole source may be searched.
+    let name = match target {
+        ApiTarget::Type(name) => name.to_upper_camel_case(),
+        ApiTarget::Function(name) | ApiTarget::Member(_, name) => name.to_lower_camel_case(),
+    };
+    lines
+        .iter()
+        .enumerate()
+        .skip(from)
+        .find(|(_, line)| declares_target(line, target, &name))
+        .map(|(idx, _)| idx)
+}
+
+/// Whether the line declares the target, named `name` in Swift.
+fn declares_target(line: &str, target: ApiTarget, name: &str) -> bool {
+    match target {
+        ApiTarget::Type(_) => declares(
+            line,
+            &[
+                "public class ",
+                "public struct ",
+                "public enum ",
+                "public protocol ",
+            ],
+            name,
+        ),
+        ApiTarget::Function(_) => declares(line, &["public func "], name),
+        // The primary constructor of objects.
+        ApiTarget::Member(_, "new") => line.trim_start().starts_with("public convenience init("),
+        ApiTarget::Member(..) => declares(
+            line.trim_start(),
+            // Callback interface protocols declare their methods without `public`.
+            &["public func ", "public static func ", "func "],
+            name,
+        ),
+    }
+}
+
+fn write_function(out: &mut String, func: &Function) -> anyhow::Result<()> {
+    let name = func.name().to_lower_camel_case();
+    writeln!(
+        out,
+        "public func {}({}) async throws{} {{",
+        name,
+        arg_list_decl(&func.arguments()),
+        return_decl(func.return_type())
+    )?;
+    write_dispatch_body(
+        out,
+        "",
+        &format!("{}({})", name, arg_list_call(&func.arguments())),
+    )?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+fn write_object_extension(out: &mut String, obj: &Object) -> anyhow::Result<()> {
+    let methods: Vec<&Method> = obj
+        .methods()
+        .into_iter()
+        .filter(|m| m.throws() && !m.is_async())
+        .collect();
+
+    if methods.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(out)?;
+    writeln!(out, "extension {} {{", obj.name().to_upper_camel_case())?;
+    for (idx, meth) in methods.iter().enumerate() {
+        if idx > 0 {
+            writeln!(out)?;
+        }
+        let name = meth.name().to_lower_camel_case();
+        writeln!(
+            out,
+            "    public func {}({}) async throws{} {{",
+            name,
+            arg_list_decl(&meth.arguments()),
+            return_decl(meth.return_type())
+        )?;
+        write_dispatch_body(
+            out,
+            "    ",
+            &format!("self.{}({})", name, arg_list_call(&meth.arguments())),
+        )?;
+        writeln!(out, "    }}")?;
+    }
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+/// Only callback interfaces made of notification like methods (no return value)
+/// can be turned into a stream of events.
+fn write_callback_stream(out: &mut String, cbi: &CallbackInterface) -> anyhow::Result<()> {
+    let methods = cbi.methods();
+    if methods.is_empty() || methods.iter().any(|m| m.return_type().is_some()) {
+        return Ok(());
+    }
+
+    let protocol = cbi.name().to_upper_camel_case();
+
+    writeln!(out)?;
+    writeln!(
+        out,
+        "/// Forwards the `{protocol}` callbacks as events of an `AsyncThrowingStream`."
+    )?;
+    writeln!(out, "public final class {protocol}Stream: {protocol} {{")?;
+    writeln!(out, "    public enum Event {{")?;
+    for meth in &methods {
+        let args = meth.arguments();
+        if args.is_empty() {
+            writeln!(out, "        case {}", meth.name().to_lower_camel_case())?;
+        } else {
+            writeln!(
+                out,
+                "        case {}({})",
+                meth.name().to_lower_camel_case(),
+                arg_list_decl(&args)
+            )?;
+        }
+    }
+    writeln!(out, "    }}")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "    public let events: AsyncThrowingStream<Event, Error>"
+    )?;
+    writeln!(
+        out,
+        "    private let continuation: AsyncThrowingStream<Event, Error>.Continuation"
+    )?;
+    writeln!(out)?;
+    writeln!(out, "    public init() {{")?;
+    writeln!(
+        out,
+        "        var continuation: AsyncThrowingStream<Event, Error>.Continuation!"
+    )?;
+    writeln!(
+        out,
+        "        self.events = AsyncThrowingStream {{ continuation = $0 }}"
+    )?;
+    writeln!(out, "        self.continuation = continuation")?;
+    writeln!(out, "    }}")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "    /// Ends the stream, optionally with an error, once no more callbacks are expected."
+    )?;
+    writeln!(
+        out,
+        "    public func finish(throwing error: Error? = nil) {{"
+    )?;
+    writeln!(out, "        continuation.finish(throwing: error)")?;
+    writeln!(out, "    }}")?;
+    for meth in &methods {
+        let args = meth.arguments();
+        let name = meth.name().to_lower_camel_case();
+        writeln!(out)?;
+        writeln!(out, "    public func {}({}) {{", name, arg_list_decl(&args))?;
+        if args.is_empty() {
+            writeln!(out, "        continuation.yield(.{name})")?;
+        } else {
+            writeln!(
+                out,
+                "        continuation.yield(.{}({}))",
+                name,
+                arg_list_call(&args)
+            )?;
+        }
+        writeln!(out, "    }}")?;
+    }
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+fn write_dispatch_body(out: &mut String, indent: &str, call: &str) -> anyhow::Result<()> {
+    writeln!(
+        out,
+        "{indent}    try await withCheckedThrowingContinuation {{ continuation in"
+    )?;
+    writeln!(
+        out,
+        "{indent}        DispatchQueue.global(qos: .userInitiated).async {{"
+    )?;
+    writeln!(
+        out,
+        "{indent}            continuation.resume(with: Result {{ try {call} }})"
+    )?;
+    writeln!(out, "{indent}        }}")?;
+    writeln!(out, "{indent}    }}")?;
+    Ok(())
+}
+
+fn arg_list_decl(args: &[&Argument]) -> String {
+    args.iter()
+        .map(|arg| {
+            format!(
+                "{}: {}",
+                arg.name().to_lower_camel_case(),
+                type_name(&arg.as_type())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn arg_list_call(args: &[&Argument]) -> String {
+    args.iter()
+        .map(|arg| {
+            let name = arg.name().to_lower_camel_case();
+            format!("{name}: {name}")
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn return_decl(return_type: Option<&Type>) -> String {
+    return_type
+        .map(|t| format!(" -> {}", type_name(t)))
+        .unwrap_or_default()
+}
+
+/// Mirrors the type naming of the UniFFI Swift backend.
+fn type_name(t: &Type) -> String {
+    match t {
+        Type::UInt8 => "UInt8".into(),
+        Type::Int8 => "Int8".into(),
+        Type::UInt16 => "UInt16".into(),
+        Type::Int16 => "Int16".into(),
+        Type::UInt32 => "UInt32".into(),
+        Type::Int32 => "Int32".into(),
+        Type::UInt64 => "UInt64".into(),
+        Type::Int64 => "Int64".into(),
+        Type::Float32 => "Float".into(),
+        Type::Float64 => "Double".into(),
+        Type::Boolean => "Bool".into(),
+        Type::String => "String".into(),
+        Type::Bytes => "Data".into(),
+        Type::Timestamp => "Date".into(),
+        Type::Duration => "TimeInterval".into(),
+        Type::ForeignExecutor => "UniFfiForeignExecutor".into(),
+        Type::Object { name, .. }
+        | Type::Record { name, .. }
+        | Type::Enum { name, .. }
+        | Type::CallbackInterface { name, .. }
+        | Type::External { name, .. }
+        | Type::Custom { name, .. } => name.to_upper_camel_case(),
+        Type::Optional { inner_type } => format!("{}?", type_name(inner_type)),
+        Type::Sequence { inner_type } => format!("[{}]", type_name(inner_type)),
+        Type::Map {
+            key_type,
+            value_type,
+        } => format!("[{}: {}]", type_name(key_type), type_name(value_type)),
+    }
+}
+
+/// Generates a namespace (a case-less enum) per domain, holding type aliases
+/// to the UniFFI generated types without their `Zcash` prefix, like `Keys.UnifiedSpendingKey`.
+/// Swift has no submodules, so this is the closest grouping available within the `Zcash` module.
+pub fn domain_namespaces(grouped: &DomainTypes) -> anyhow::Result<String> {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "// Autogenerated by the uniffi-zcash-cli - do not modify"
+    )?;
+
+    for (domain, names) in grouped {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "public enum {} {{",
+            domain.name().to_upper_camel_case()
+        )?;
+        for name in names {
+            let class_name = name.to_upper_camel_case();
+            // Unprefixed types would alias themselves.
+            if short_name(&class_name) == class_name {
+                continue;
+            }
+            writeln!(
+                out,
+                "    public typealias {} = {class_name}",
+                short_name(&class_name)
+            )?;
+        }
+        writeln!(out, "}}")?;
+    }
+
+    Ok(out)
+}
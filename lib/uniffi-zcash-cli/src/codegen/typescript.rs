@@ -0,0 +1,348 @@
+use std::fmt::Write;
+
+use heck::{ToLowerCamelCase, ToUpperCamelCase};
+use uniffi_bindgen::{
+    interface::{Argument, AsType, Enum, Field, Function, Method, Object, Record, Type},
+    ComponentInterface,
+};
+
+use super::stability::{ApiStability, ApiTarget};
+
+const RESERVED_WORDS: [&str; 38] = [
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "with",
+];
+
+/// Generates the TypeScript declarations of the bindings API for the React Native
+/// package. Names follow the JavaScript conventions: types in upper camel case and
+/// members in lower camel case. 64 bits integers are `bigint`s, as they do not fit
+/// in a `number`.
+pub fn type_declarations(
+    ci: &ComponentInterface,
+    stability: &ApiStability,
+) -> anyhow::Result<String> {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "// Autogenerated by the uniffi-zcash-cli - do not modify"
+    )?;
+
+    for e in ci.enum_definitions() {
+        writeln!(out)?;
+        write_stability(&mut out, "", stability, ApiTarget::Type(e.name()))?;
+        if ci.is_name_used_as_error(e.name()) {
+            write_error(&mut out, e)?;
+        } else {
+            write_enum(&mut out, e)?;
+        }
+    }
+
+    for rec in ci.record_definitions() {
+        writeln!(out)?;
+        write_stability(&mut out, "", stability, ApiTarget::Type(rec.name()))?;
+        write_record(&mut out, rec)?;
+    }
+
+    for obj in ci.object_definitions() {
+        writeln!(out)?;
+        write_stability(&mut out, "", stability, ApiTarget::Type(obj.name()))?;
+        write_object(&mut out, stability, obj)?;
+    }
+
+    for cbi in ci.callback_interface_definitions() {
+        writeln!(out)?;
+        write_stability(&mut out, "", stability, ApiTarget::Type(cbi.name()))?;
+        writeln!(out, "export interface {} {{", class_name(cbi.name()))?;
+        write_methods(&mut out, stability, cbi.name(), &cbi.methods())?;
+        writeln!(out, "}}")?;
+    }
+
+    if !ci.function_definitions().is_empty() {
+        writeln!(out)?;
+    }
+    for func in ci.function_definitions() {
+        write_stability(&mut out, "", stability, ApiTarget::Function(func.name()))?;
+        write_function(&mut out, func)?;
+    }
+
+    Ok(out)
+}
+
+fn write_enum(out: &mut String, e: &Enum) -> anyhow::Result<()> {
+    let name = class_name(e.name());
+
+    if e.is_flat() {
+        writeln!(out, "export declare enum {name} {{")?;
+        for variant in e.variants() {
+            writeln!(out, "  {},", class_name(variant.name()))?;
+        }
+        writeln!(out, "}}")?;
+        return Ok(());
+    }
+
+    // Data enums are unions discriminated by the variant name.
+    writeln!(out, "export type {name} =")?;
+    for variant in e.variants() {
+        writeln!(
+            out,
+            "  | {}",
+            tagged_fields(variant.name(), variant.fields())
+        )?;
+    }
+    writeln!(out, ";")?;
+    Ok(())
+}
+
+fn write_error(out: &mut String, e: &Enum) -> anyhow::Result<()> {
+    let name = class_name(e.name());
+
+    writeln!(out, "export declare class {name} extends Error {{")?;
+    writeln!(out, "  readonly variant: {name}Variant;")?;
+    writeln!(out, "}}")?;
+    writeln!(out, "export type {name}Variant =")?;
+    for variant in e.variants() {
+        // Flat errors only carry the message.
+        let fields = if e.is_flat() {
+            &[][..]
+        } else {
+            variant.fields()
+        };
+        writeln!(out, "  | {}", tagged_fields(variant.name(), fields))?;
+    }
+    writeln!(out, ";")?;
+    Ok(())
+}
+
+fn tagged_fields(variant_name: &str, fields: &[Field]) -> String {
+    let mut members = vec![format!("tag: \"{}\"", class_name(variant_name))];
+    members.extend(fields.iter().map(|field| {
+        format!(
+            "readonly {}: {}",
+            var_name(field.name()),
+            type_name(&field.as_type())
+        )
+    }));
+    format!("{{ {} }}", members.join("; "))
+}
+
+fn write_record(out: &mut String, rec: &Record) -> anyhow::Result<()> {
+    writeln!(out, "export interface {} {{", class_name(rec.name()))?;
+    for field in rec.fields() {
+        let optional = field.default_value().map(|_| "?").unwrap_or_default();
+        writeln!(
+            out,
+            "  readonly {}{optional}: {};",
+            var_name(field.name()),
+            type_name(&field.as_type())
+        )?;
+    }
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+fn write_object(out: &mut String, stability: &ApiStability, obj: &Object) -> anyhow::Result<()> {
+    let name = class_name(obj.name());
+
+    writeln!(out, "export declare class {name} {{")?;
+    if let Some(cons) = obj.primary_constructor() {
+        write_member_doc(
+            out,
+            stability,
+            ApiTarget::Member(obj.name(), cons.name()),
+            cons.throws_type(),
+        )?;
+        writeln!(out, "  constructor({});", arg_list_decl(&cons.arguments()))?;
+    }
+    for cons in obj.alternate_constructors() {
+        write_member_doc(
+            out,
+            stability,
+            ApiTarget::Member(obj.name(), cons.name()),
+            cons.throws_type(),
+        )?;
+        writeln!(
+            out,
+            "  static {}({}): {name};",
+            fn_name(cons.name()),
+            arg_list_decl(&cons.arguments())
+        )?;
+    }
+    write_methods(out, stability, obj.name(), &obj.methods())?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+fn write_methods(
+    out: &mut String,
+    stability: &ApiStability,
+    type_name: &str,
+    methods: &[&Method],
+) -> anyhow::Result<()> {
+    for meth in methods {
+        write_member_doc(
+            out,
+            stability,
+            ApiTarget::Member(type_name, meth.name()),
+            meth.throws_type(),
+        )?;
+        writeln!(
+            out,
+            "  {}({}): {};",
+            fn_name(meth.name()),
+            arg_list_decl(&meth.arguments()),
+            return_type_name(meth.return_type())
+        )?;
+    }
+    Ok(())
+}
+
+fn write_function(out: &mut String, func: &Function) -> anyhow::Result<()> {
+    if let Some(throws) = func.throws_type() {
+        writeln!(out, "/** @throws {{{}}} */", type_name(throws))?;
+    }
+    writeln!(
+        out,
+        "export declare function {}({}): {};",
+        fn_name(func.name()),
+        arg_list_decl(&func.arguments()),
+        return_type_name(func.return_type())
+    )?;
+    Ok(())
+}
+
+/// The JSDoc tags of a member: its stability notes and the error it throws, if any.
+fn write_member_doc(
+    out: &mut String,
+    stability: &ApiStability,
+    target: ApiTarget,
+    throws: Option<&Type>,
+) -> anyhow::Result<()> {
+    write_stability(out, "  ", stability, target)?;
+    if let Some(throws) = throws {
+        writeln!(out, "  /** @throws {{{}}} */", type_name(throws))?;
+    }
+    Ok(())
+}
+
+fn write_stability(
+    out: &mut String,
+    indent: &str,
+    stability: &ApiStability,
+    target: ApiTarget,
+) -> anyhow::Result<()> {
+    if let Some(note) = stability.experimental(target) {
+        writeln!(out, "{indent}/** @experimental {} */", note.text())?;
+    }
+    if let Some(note) = stability.deprecation(target) {
+        writeln!(out, "{indent}/** @deprecated {} */", note.text())?;
+    }
+    Ok(())
+}
+
+/// Arguments with a default value are optional ones in TypeScript.
+fn arg_list_decl(args: &[&Argument]) -> String {
+    args.iter()
+        .map(|arg| {
+            let optional = arg.default_value().map(|_| "?").unwrap_or_default();
+            format!(
+                "{}{optional}: {}",
+                var_name(arg.name()),
+                type_name(&arg.as_type())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn return_type_name(return_type: Option<&Type>) -> String {
+    return_type
+        .map(type_name)
+        .unwrap_or_else(|| "void".to_string())
+}
+
+fn type_name(t: &Type) -> String {
+    match t {
+        Type::UInt8
+        | Type::Int8
+        | Type::UInt16
+        | Type::Int16
+        | Type::UInt32
+        | Type::Int32
+        | Type::Float32
+        | Type::Float64 => "number".into(),
+        Type::UInt64 | Type::Int64 => "bigint".into(),
+        Type::Boolean => "boolean".into(),
+        Type::String => "string".into(),
+        Type::Bytes => "ArrayBuffer".into(),
+        Type::Timestamp => "Date".into(),
+        // Durations are in milliseconds, as JavaScript timers.
+        Type::Duration => "number".into(),
+        Type::ForeignExecutor => "unknown".into(),
+        Type::Object { name, .. }
+        | Type::Record { name, .. }
+        | Type::Enum { name, .. }
+        | Type::CallbackInterface { name, .. }
+        | Type::External { name, .. }
+        | Type::Custom { name, .. } => class_name(name),
+        Type::Optional { inner_type } => format!("{} | undefined", type_name(inner_type)),
+        Type::Sequence { inner_type } => format!("Array<{}>", type_name(inner_type)),
+        Type::Map {
+            key_type,
+            value_type,
+        } => format!("Map<{}, {}>", type_name(key_type), type_name(value_type)),
+    }
+}
+
+fn class_name(name: &str) -> String {
+    name.to_upper_camel_case()
+}
+
+fn fn_name(name: &str) -> String {
+    name.to_lower_camel_case()
+}
+
+fn var_name(name: &str) -> String {
+    let name = name.to_lower_camel_case();
+    if RESERVED_WORDS.contains(&name.as_str()) {
+        format!("{name}_")
+    } else {
+        name
+    }
+}
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use rand::RngCore;
+use uniffi_zcash::{
+    download_blocks, fast_forward_to_birthday, network_info, scan_cached_blocks,
+    ZcashAccountBirthday, ZcashBlockHeight, ZcashConsensusParameters, ZcashFsBlockDb,
+    ZcashLightwalletdClient, ZcashNonNegativeAmount, ZcashWalletDb,
+};
+
+use crate::helper::tmp_folder;
+
+/// Creates a throwaway wallet for the given seed, random if none, with a birthday
+/// `blocks` blocks before the chain tip, syncs it from lightwalletd and prints its
+/// balances. Everything is done with the library APIs the bindings expose, in the order
+/// apps call them, so it checks the whole stack against a live server, and shows how a
+/// wallet is synced.
+pub fn run_demo(
+    params: ZcashConsensusParameters,
+    seed: Option<&str>,
+    lightwalletd_url: Option<&str>,
+    blocks: u32,
+) -> anyhow::Result<()> {
+    let seed = match seed {
+        Some(seed) => hex::decode(seed)?,
+        None => {
+            let mut seed = vec![0; 32];
+            rand::thread_rng().fill_bytes(&mut seed);
+            seed
+        }
+    };
+    let lightwalletd_url = match lightwalletd_url {
+        Some(url) => url.to_string(),
+        None => network_info(params).default_lightwalletd_urls[0].clone(),
+    };
+
+    let work_dir = tmp_folder()?;
+    let result = sync_wallet(
+        params,
+        seed,
+        &lightwalletd_url,
+        blocks,
+        &work_dir.to_string_lossy(),
+    );
+    fs_extra::dir::remove(&work_dir)?;
+    result
+}
+
+fn sync_wallet(
+    params: ZcashConsensusParameters,
+    seed: Vec<u8>,
+    lightwalletd_url: &str,
+    blocks: u32,
+    work_dir: &str,
+) -> anyhow::Result<()> {
+    println!("Connecting to {} ...", lightwalletd_url);
+    let client = Arc::new(ZcashLightwalletdClient::new(lightwalletd_url.to_string())?);
+    let chain_tip_height = client.latest_block_height()?;
+
+    let db_path = format!("{}/wallet.db", work_dir);
+    let db = Arc::new(ZcashWalletDb::for_path(db_path.clone(), params)?);
+    db.initialize(seed.clone())?;
+
+    // The birthday is made of the tree state at the end of the block before it.
+    let birthday_height = chain_tip_height.saturating_sub(blocks).max(1);
+    let tree_state = client.get_tree_state(birthday_height - 1)?;
+    let birthday = ZcashAccountBirthday::from_treestate(tree_state, None)?;
+    let account = db.create_account(seed, Arc::new(birthday))?.account_id;
+    let address = db
+        .get_current_address(account)?
+        .ok_or_else(|| anyhow!("The account has no address"))?;
+    println!(
+        "Created account {} with birthday {}, receiving funds at {}",
+        account.id,
+        birthday_height,
+        address.encode(params)
+    );
+
+    let report = fast_forward_to_birthday(client.clone(), db.clone())?;
+    println!(
+        "Stored {} Sapling subtree roots, the chain tip is {}",
+        report.subtree_roots, report.chain_tip_height
+    );
+
+    let cache = Arc::new(ZcashFsBlockDb::for_network(
+        format!("{}/cache", work_dir),
+        params,
+        Some(lightwalletd_url.to_string()),
+    )?);
+    for range in db.suggest_scan_ranges()? {
+        let (start, end) = (range.start_height(), range.end_height());
+        download_blocks(client.clone(), cache.clone(), start, end, None)?;
+        scan_cached_blocks(
+            params,
+            cache.root(),
+            db_path.clone(),
+            Arc::new(ZcashBlockHeight::new(start)),
+            end - start,
+        )?;
+        println!("Scanned blocks {} to {}", start, end - 1);
+    }
+
+    let summary = db
+        .get_wallet_summary(1)?
+        .ok_or_else(|| anyhow!("The wallet has no summary after syncing"))?;
+    println!(
+        "Synced up to {}, {} blocks behind the chain tip",
+        summary.fully_scanned_height_value(),
+        summary.sync_lag()
+    );
+    for (account, balance) in summary.account_balance_values() {
+        println!(
+            "Account {} has {}, {} of them spendable",
+            account,
+            ZcashNonNegativeAmount::from_u64(balance.total)?.to_display_string(),
+            ZcashNonNegativeAmount::from_u64(balance.sapling_spendable)?.to_display_string()
+        );
+    }
+    Ok(())
+}
@@ -2,7 +2,7 @@
 
 use fs_extra::dir::create_all;
 
-use crate::{helper::cmd_success, setup::dokka_install_dir};
+use crate::{helper::cmd_success, setup::dokka_install_dir, KOTLIN, PYTHON, RUBY, SWIFT};
 
 pub fn python(sources_dir: &Path, target_dir: &Path) -> anyhow::Result<()> {
     create_all(target_dir, false)?;
@@ -96,3 +96,45 @@ pub fn swift(sources_dir: &Path, target_dir: &Path) -> anyhow::Result<()> {
             .wait(),
     )
 }
+
+/// Generates the API reference of every language, at `<docs_dir>/<language>/<version>`,
+/// and an `index.html` page at `<docs_dir>/<version>` linking them.
+pub fn all(packages_dir: &Path, docs_dir: &Path, version: &str) -> anyhow::Result<()> {
+    python(
+        &packages_dir.join(PYTHON),
+        &docs_dir.join(PYTHON).join(version),
+    )?;
+    ruby(&packages_dir.join(RUBY), &docs_dir.join(RUBY).join(version))?;
+    kotlin(
+        &packages_dir.join(KOTLIN),
+        &docs_dir.join(KOTLIN).join(version),
+    )?;
+    swift(
+        &packages_dir.join(SWIFT),
+        &docs_dir.join(SWIFT).join(version),
+    )?;
+
+    // pydoc names the page after the module.
+    let sites = [
+        (PYTHON, "zcash.html"),
+        (RUBY, "index.html"),
+        (KOTLIN, "index.html"),
+        (SWIFT, "index.html"),
+    ];
+    let links: String = sites
+        .iter()
+        .map(|(lang, page)| {
+            format!("    <li><a href=\"../{lang}/{version}/{page}\">{lang}</a></li>\n")
+        })
+        .collect();
+
+    let index_dir = docs_dir.join(version);
+    create_all(&index_dir, false)?;
+    fs::write(
+        index_dir.join("index.html"),
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n  <meta charset=\"utf-8\">\n  <title>uniffi-zcash {version} API reference</title>\n</head>\n<body>\n  <h1>uniffi-zcash {version} API reference</h1>\n  <ul>\n{links}  </ul>\n</body>\n</html>\n"
+        ),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use fs_extra::dir::create_all;
+use handlebars::Handlebars;
+use heck::ToUpperCamelCase;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{helper::cmd_success, KOTLIN, PYTHON, RUBY, SWIFT};
+
+/// The languages examples are rendered in. C has none, its raw ABI being meant for
+/// generators of other languages bindings, not for apps.
+pub const EXAMPLE_LANGUAGES: [&str; 4] = [PYTHON, RUBY, KOTLIN, SWIFT];
+
+/// The examples, along with the values they share in all the languages, like the
+/// lightwalletd server or the amount sent, as described by `examples.json`.
+#[derive(Deserialize)]
+struct Examples {
+    values: serde_json::Map<String, Value>,
+    examples: Vec<Example>,
+}
+
+#[derive(Deserialize)]
+struct Example {
+    name: String,
+    title: String,
+    description: String,
+}
+
+/// The name of the file of the example in the language, after its conventions.
+fn file_name(language: &str, example: &str) -> String {
+    let type_name = format!("{}Example", example.to_upper_camel_case());
+    match language {
+        PYTHON => format!("{example}.py"),
+        RUBY => format!("{example}.rb"),
+        KOTLIN => format!("{type_name}.kt"),
+        SWIFT => format!("{type_name}.swift"),
+        _ => unreachable!("No examples for {}", language),
+    }
+}
+
+/// Renders the examples of the language from their templates, in the given folder.
+/// Returns the paths of the rendered files.
+pub fn render(
+    templates_dir: &Path,
+    language: &str,
+    target_dir: &Path,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let examples: Examples =
+        serde_json::from_str(&fs::read_to_string(templates_dir.join("examples.json"))?)?;
+    let mut reg = Handlebars::new();
+    reg.set_strict_mode(true);
+    reg.register_escape_fn(handlebars::no_escape);
+
+    create_all(target_dir, false)?;
+    let mut files = Vec::new();
+    for example in &examples.examples {
+        let template = fs::read_to_string(
+            templates_dir
+                .join(language)
+                .join(&example.name)
+                .with_extension("hbs"),
+        )?;
+        let mut data = examples.values.clone();
+        data.insert("title".into(), json!(example.title));
+        data.insert("description".into(), json!(example.description));
+        data.insert(
+            "type_name".into(),
+            json!(format!("{}Example", example.name.to_upper_camel_case())),
+        );
+
+        let path = target_dir.join(file_name(language, &example.name));
+        fs::write(&path, reg.render_template(&template, &data)?)?;
+        files.push(path);
+    }
+    Ok(files)
+}
+
+/// Renders the examples of the given languages, in a folder per language, along with
+/// a `README.md` gallery linking them all.
+pub fn generate(
+    templates_dir: &Path,
+    languages: &[String],
+    target_dir: &Path,
+) -> anyhow::Result<()> {
+    let examples: Examples =
+        serde_json::from_str(&fs::read_to_string(templates_dir.join("examples.json"))?)?;
+    for language in languages {
+        render(templates_dir, language, &target_dir.join(language))?;
+    }
+
+    let gallery: Vec<Value> = examples
+        .examples
+        .iter()
+        .map(|example| {
+            let files: Vec<Value> = languages
+                .iter()
+                .map(|language| {
+                    json!({
+                        "language": language,
+                        "path": format!("{}/{}", language, file_name(language, &example.name)),
+                    })
+                })
+                .collect();
+            json!({
+                "title": example.title,
+                "description": example.description,
+                "files": files,
+            })
+        })
+        .collect();
+    let mut reg = Handlebars::new();
+    reg.register_escape_fn(handlebars::no_escape);
+    let readme = reg.render_template(
+        &fs::read_to_string(templates_dir.join("README.md.hbs"))?,
+        &json!({ "examples": gallery }),
+    )?;
+    fs::write(target_dir.join("README.md"), readme)?;
+
+    println!("Examples generated at {} ✅", target_dir.to_string_lossy());
+    Ok(())
+}
+
+/// Checks the Python or Ruby examples compile, without running them, as they need the
+/// network. The Kotlin and Swift ones are instead compiled along with the test app of the
+/// release, see [`render`].
+pub fn check_syntax(templates_dir: &Path, language: &str, work_dir: &Path) -> anyhow::Result<()> {
+    for file in render(templates_dir, language, &work_dir.join("examples"))? {
+        let mut command = match language {
+            PYTHON => {
+                let mut command = Command::new("python3");
+                command.arg("-m").arg("py_compile");
+                command
+            }
+            RUBY => {
+                let mut command = Command::new("ruby");
+                command.arg("-c");
+                command
+            }
+            _ => unreachable!("The {} examples are compiled with the test app", language),
+        };
+        cmd_success(command.arg(file).spawn()?.wait())?;
+    }
+    Ok(())
+}
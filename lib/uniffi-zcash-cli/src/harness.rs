@@ -0,0 +1,107 @@
+use std::{path::Path, process::Command, sync::Mutex};
+
+use anyhow::bail;
+use fs_extra::file::{self, CopyOptions};
+
+use crate::{
+    bindgen::generate_bindings,
+    helper::{clean_dir, cmd_success, tmp_folder, LINUX_SHARED_LIB_NAME, MACOS_SHARED_LIB_NAME},
+    PYTHON, RUBY,
+};
+
+/// The languages [`check_bindings`] supports, those whose scripts run without a build step.
+pub const HARNESS_LANGUAGES: [&str; 2] = [PYTHON, RUBY];
+
+/// Held while building the shared library and generating the bindings from it, as they
+/// are shared by the checks, which may build the library with other features.
+static BUILD_LOCK: Mutex<()> = Mutex::new(());
+
+/// Checks the bindings of the language work, by building the shared library for the
+/// host, with the given features of the library, generating the bindings from it and
+/// running the given script against them.
+///
+/// Unlike the test apps of the releases, it needs neither the cross compilation
+/// toolchains nor the libraries of all the targets, so that binding regressions are
+/// caught by `cargo test`. The library is built in the `target` folder of the workspace,
+/// as for the other tests, and the bindings in a temporary folder.
+pub fn check_bindings(
+    root_dir: &Path,
+    language: &str,
+    script: &Path,
+    features: &[&str],
+) -> anyhow::Result<()> {
+    if !HARNESS_LANGUAGES.contains(&language) {
+        bail!(
+            "The {} bindings cannot be checked by running a script",
+            language
+        )
+    }
+
+    let build_lock = BUILD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    println!("Generating shared library for the host ...");
+    cmd_success(
+        Command::new("cargo")
+            .arg("build")
+            .arg("--package")
+            .arg("zcash")
+            .arg("--features")
+            .arg(features.join(","))
+            .current_dir(root_dir)
+            .spawn()?
+            .wait(),
+    )?;
+
+    let work_dir = tmp_folder()?;
+    let shared_libs_dir = work_dir.join("shared_libs");
+    clean_dir(&shared_libs_dir)?;
+    let shared_lib_name = if cfg!(target_os = "macos") {
+        MACOS_SHARED_LIB_NAME
+    } else {
+        LINUX_SHARED_LIB_NAME
+    };
+    file::copy(
+        root_dir.join("target").join("debug").join(shared_lib_name),
+        shared_libs_dir.join(shared_lib_name),
+        &CopyOptions::default(),
+    )?;
+
+    let bindings_dir = work_dir.join("bindings");
+    generate_bindings(
+        root_dir,
+        &root_dir.join("uniffi-zcash").join("src").join("zcash.udl"),
+        &shared_libs_dir,
+        &bindings_dir,
+        &[language.to_owned()],
+        false,
+    )?;
+    drop(build_lock);
+
+    println!(
+        "Running {} against the {} bindings ...",
+        script.to_string_lossy(),
+        language
+    );
+    let lang_bindings_dir = bindings_dir.join(language);
+    let mut command = match language {
+        PYTHON => {
+            let mut command = Command::new("python3");
+            command.env("PYTHONPATH", &lang_bindings_dir);
+            command
+        }
+        RUBY => {
+            // The Ruby bindings load the library from the library path.
+            let mut command = Command::new("ruby");
+            command
+                .arg("-I")
+                .arg(&lang_bindings_dir)
+                .env("LD_LIBRARY_PATH", &lang_bindings_dir)
+                .env("DYLD_LIBRARY_PATH", &lang_bindings_dir);
+            command
+        }
+        _ => unreachable!("No harness for {}", language),
+    };
+    let result = cmd_success(command.arg(script).current_dir(&work_dir).spawn()?.wait());
+
+    fs_extra::dir::remove(&work_dir)?;
+    result
+}
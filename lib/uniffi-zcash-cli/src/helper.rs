@@ -99,6 +99,64 @@ pub fn cmd_retry<I>(
     .map_err(|err| err.error)
 }
 
+/// Splits a semantic version in its release and pre-release parts,
+/// dropping the build metadata, which package registries do not accept.
+fn split_semver(version: &str) -> (&str, Option<&str>) {
+    let version = version.split('+').next().unwrap_or(version);
+    match version.split_once('-') {
+        Some((release, pre_release)) => (release, Some(pre_release)),
+        None => (version, None),
+    }
+}
+
+/// Converts a semantic version to a PEP 440 one, as PyPI requires.
+/// See https://peps.python.org/pep-0440/#pre-releases
+///
+/// Pre-releases must look like `alpha[.N]`, `beta[.N]` or `rc[.N]`,
+/// which become pre-releases, or any other label like `SNAPSHOT[.N]` or
+/// `nightly[.N]`, which become development releases.
+pub fn pep440_version(version: &str) -> anyhow::Result<String> {
+    let (release, pre_release) = split_semver(version);
+    let Some(pre_release) = pre_release else {
+        return Ok(release.to_owned());
+    };
+
+    let mut identifiers = pre_release.split('.');
+    let label = identifiers.next().unwrap_or_default();
+    let number = identifiers.next().unwrap_or("0");
+    if identifiers.next().is_some() || number.parse::<u64>().is_err() {
+        bail!(
+            "Version {} cannot be converted to a PEP 440 version, use a <label>[.N] pre-release",
+            version
+        )
+    }
+
+    let segment = match label.to_lowercase().as_str() {
+        "alpha" | "a" => "a",
+        "beta" | "b" => "b",
+        "rc" | "c" => "rc",
+        _ => ".dev",
+    };
+    Ok(format!("{}{}{}", release, segment, number))
+}
+
+/// Converts a semantic version to a RubyGems one, where any version
+/// holding letters is a pre-release. See https://guides.rubygems.org/patterns/#prerelease-gems
+pub fn gem_version(version: &str) -> String {
+    match split_semver(version) {
+        (release, Some(pre_release)) => {
+            format!("{}.{}", release, pre_release.replace('-', "."))
+        }
+        (release, None) => release.to_owned(),
+    }
+}
+
+/// Whether the version is a Maven snapshot, which are published to a
+/// dedicated repository.
+pub fn is_snapshot_version(version: &str) -> bool {
+    version.ends_with("-SNAPSHOT")
+}
+
 /// Generates a collision free /tmp folder
 pub fn tmp_folder() -> anyhow::Result<PathBuf> {
     let uuid = Uuid::new_v4();
@@ -0,0 +1,29 @@
+//! The tooling of the bindings: building the shared libraries, generating the bindings
+//! and releasing their packages, as driven by the CLI. It is a library too, so that its
+//! steps can be run from the integration tests, see [`harness`].
+
+pub mod apidiff;
+pub mod bench;
+pub mod bindgen;
+pub mod cli;
+pub mod codegen;
+pub mod demo;
+pub mod diff;
+pub mod docgen;
+pub mod examples;
+pub mod harness;
+pub mod helper;
+pub mod publish;
+pub mod release;
+pub mod setup;
+pub mod sharedlibs;
+pub mod wallet;
+
+pub const PYTHON: &str = "python";
+pub const RUBY: &str = "ruby";
+pub const KOTLIN: &str = "kotlin";
+pub const SWIFT: &str = "swift";
+pub const C: &str = "c";
+pub const REACT_NATIVE: &str = "react-native";
+
+pub const SUPPORTED_LANGUAGES: [&str; 5] = [PYTHON, RUBY, KOTLIN, SWIFT, C];
@@ -1,34 +1,32 @@
-use std::env::set_current_dir;
+use std::{
+    env::{current_dir, set_current_dir},
+    path::PathBuf,
+};
 
-use bindgen::generate_bindings;
 use clap::ColorChoice;
-use cli::get_matches;
 
 use anyhow::anyhow;
-use diff::generate_diff;
-use helper::{workspace_root_dir, PathChecker};
-use setup::{add_rust_targets, install_dokka_cli, install_macos_sdk, install_zig_build};
-use sharedlibs::generate_shared_libs;
-use uniffi_zcash_test::test_data::generate_test_data;
+use uniffi_zcash::ZcashConsensusParameters;
+use uniffi_zcash_cli::{
+    apidiff, bench,
+    bindgen::generate_bindings,
+    cli::get_matches,
+    demo::run_demo,
+    diff::generate_diff,
+    docgen, examples,
+    helper::{workspace_root_dir, PathChecker},
+    publish, release,
+    setup::{
+        add_rust_targets, install_dokka_cli, install_macos_sdk, install_zig_build,
+        verify_sapling_prover,
+    },
+    sharedlibs::generate_shared_libs,
+    wallet::{audit_wallet, wallet_info},
+    C, KOTLIN, PYTHON, REACT_NATIVE, RUBY, SWIFT,
+};
+use uniffi_zcash_test::test_data::{generate_reorg_test_data, generate_test_data};
 use zcash_proofs::download_sapling_parameters;
 
-mod bindgen;
-mod cli;
-mod diff;
-mod docgen;
-mod helper;
-mod publish;
-mod release;
-mod setup;
-mod sharedlibs;
-
-const PYTHON: &str = "python";
-const RUBY: &str = "ruby";
-const KOTLIN: &str = "kotlin";
-const SWIFT: &str = "swift";
-
-const SUPPORTED_LANGUAGES: [&str; 4] = [PYTHON, RUBY, KOTLIN, SWIFT];
-
 fn main() -> anyhow::Result<()> {
     let matches = get_matches();
 
@@ -37,7 +35,11 @@ fn main() -> anyhow::Result<()> {
     let bindings_dir = root_dir.join("bindings");
     let packages_dir = root_dir.join("packages");
     let docs_dir = root_dir.join("docs");
+    let examples_dir = root_dir.join("examples");
+    let udl_path = root_dir.join("uniffi-zcash").join("src").join("zcash.udl");
 
+    // The paths given as arguments are relative to where the CLI is invoked from.
+    let invocation_dir = current_dir()?;
     set_current_dir(&root_dir)?;
 
     match matches.subcommand() {
@@ -48,7 +50,7 @@ fn main() -> anyhow::Result<()> {
                 Ok(install_macos_sdk()?)
             }
             Some(("builddoc", _)) => Ok(install_dokka_cli()?),
-            Some(("saplingparams", _)) => match download_sapling_parameters(None) {
+            Some(("saplingparams", args)) => match download_sapling_parameters(None) {
                 Ok(paths) => {
                     println!(
                         "Downloaded spend parameters at : {}",
@@ -58,12 +60,21 @@ fn main() -> anyhow::Result<()> {
                         "Downloaded output parameters at : {}",
                         paths.output.to_string_lossy()
                     );
+                    if args.get_flag("verify_prover") {
+                        println!("Verifying the prover ...");
+                        verify_sapling_prover(&paths.spend, &paths.output)?;
+                        println!("Sapling prover successfully verified ✅");
+                    }
                     Ok(())
                 }
                 Err(err) => Err(anyhow!(err.to_string())),
             },
-            Some(("testdata", _)) => {
-                generate_test_data(true);
+            Some(("testdata", args)) => {
+                if args.get_flag("reorg") {
+                    generate_reorg_test_data();
+                } else {
+                    generate_test_data(true);
+                }
                 Ok(())
             }
             _ => Err(anyhow!("Command not found. See help.")),
@@ -79,12 +90,21 @@ fn main() -> anyhow::Result<()> {
                 .unwrap()
                 .map(Clone::clone)
                 .collect();
-            Ok(generate_bindings(&root_dir, &languages)?)
+            let group_by_domain = args.get_flag("group_by_domain");
+            Ok(generate_bindings(
+                &root_dir,
+                &udl_path,
+                &shared_libs_dir,
+                &bindings_dir,
+                &languages,
+                group_by_domain,
+            )?)
         }
         Some(("release", args)) => {
             bindings_dir
                 .informed_exists("Are the language bindings already built ? Check CLI help.")?;
             let package_template_dir = root_dir.join("uniffi-zcash-cli").join("templates");
+            let examples_template_dir = package_template_dir.join("examples");
             match args.subcommand() {
                 Some((PYTHON, args)) => {
                     let cfg = release::Config {
@@ -92,10 +112,15 @@ fn main() -> anyhow::Result<()> {
                         git_repo_url: None,
                         package_template_dir: package_template_dir.join(PYTHON),
                         test_app_template_dir: package_template_dir.join("python_test_app"),
+                        examples_template_dir: Some(examples_template_dir.clone()),
                         bindings_dir: bindings_dir.join(PYTHON),
                         package_dir: packages_dir.join(PYTHON),
+                        udl_path: udl_path.clone(),
+                        manifest_path: root_dir.join("Cargo.toml"),
+                        force: args.get_flag("force"),
+                        mobile_libs: Default::default(),
                     };
-                    Ok(release::python::run(&cfg)?)
+                    cfg.run_if_changed(PYTHON, release::python::run)
                 }
                 Some((RUBY, args)) => {
                     let cfg = release::Config {
@@ -103,10 +128,15 @@ fn main() -> anyhow::Result<()> {
                         git_repo_url: None,
                         package_template_dir: package_template_dir.join(RUBY),
                         test_app_template_dir: package_template_dir.join("ruby_test_app"),
+                        examples_template_dir: Some(examples_template_dir.clone()),
                         bindings_dir: bindings_dir.join(RUBY),
                         package_dir: packages_dir.join(RUBY),
+                        udl_path: udl_path.clone(),
+                        manifest_path: root_dir.join("Cargo.toml"),
+                        force: args.get_flag("force"),
+                        mobile_libs: Default::default(),
                     };
-                    Ok(release::ruby::run(&cfg)?)
+                    cfg.run_if_changed(RUBY, release::ruby::run)
                 }
                 Some((KOTLIN, args)) => {
                     let cfg = release::Config {
@@ -114,10 +144,15 @@ fn main() -> anyhow::Result<()> {
                         git_repo_url: None,
                         package_template_dir: package_template_dir.join(KOTLIN),
                         test_app_template_dir: package_template_dir.join("kotlin_test_app"),
+                        examples_template_dir: Some(examples_template_dir.clone()),
                         bindings_dir: bindings_dir.join(KOTLIN),
                         package_dir: packages_dir.join(KOTLIN),
+                        udl_path: udl_path.clone(),
+                        manifest_path: root_dir.join("Cargo.toml"),
+                        force: args.get_flag("force"),
+                        mobile_libs: Default::default(),
                     };
-                    Ok(release::kotlin::run(&cfg)?)
+                    cfg.run_if_changed(KOTLIN, release::kotlin::run)
                 }
                 Some((SWIFT, args)) => {
                     let cfg = release::Config {
@@ -129,10 +164,55 @@ fn main() -> anyhow::Result<()> {
                         ),
                         package_template_dir: package_template_dir.join(SWIFT),
                         test_app_template_dir: package_template_dir.join("swift_test_app"),
+                        examples_template_dir: Some(examples_template_dir.clone()),
                         bindings_dir: bindings_dir.join(SWIFT),
                         package_dir: packages_dir.join(SWIFT),
+                        udl_path: udl_path.clone(),
+                        manifest_path: root_dir.join("Cargo.toml"),
+                        force: args.get_flag("force"),
+                        mobile_libs: Default::default(),
                     };
-                    Ok(release::swift::run(&cfg)?)
+                    cfg.run_if_changed(SWIFT, release::swift::run)
+                }
+                Some((C, args)) => {
+                    let cfg = release::Config {
+                        version: args.try_get_one::<String>("version")?.unwrap().to_owned(),
+                        git_repo_url: None,
+                        package_template_dir: package_template_dir.join(C),
+                        test_app_template_dir: package_template_dir.join("c_test_app"),
+                        examples_template_dir: None,
+                        bindings_dir: bindings_dir.join(C),
+                        package_dir: packages_dir.join(C),
+                        udl_path: udl_path.clone(),
+                        manifest_path: root_dir.join("Cargo.toml"),
+                        force: args.get_flag("force"),
+                        mobile_libs: Default::default(),
+                    };
+                    cfg.run_if_changed(C, release::c::run)
+                }
+                Some((REACT_NATIVE, args)) => {
+                    let cfg = release::Config {
+                        version: args.try_get_one::<String>("version")?.unwrap().to_owned(),
+                        git_repo_url: None,
+                        package_template_dir: package_template_dir.join("react_native"),
+                        test_app_template_dir: package_template_dir.join("react_native_test_app"),
+                        examples_template_dir: None,
+                        // It packages the Kotlin and Swift bindings.
+                        bindings_dir: bindings_dir.clone(),
+                        package_dir: packages_dir.join(REACT_NATIVE),
+                        udl_path: udl_path.clone(),
+                        manifest_path: root_dir.join("Cargo.toml"),
+                        force: args.get_flag("force"),
+                        mobile_libs: release::MobileLibs {
+                            android_libs_dir: args
+                                .try_get_one::<String>("android_libs_dir")?
+                                .map(PathBuf::from),
+                            ios_xcframework: args
+                                .try_get_one::<String>("ios_xcframework")?
+                                .map(PathBuf::from),
+                        },
+                    };
+                    cfg.run_if_changed(REACT_NATIVE, release::react_native::run)
                 }
                 _ => Err(anyhow!("Command not found. See help.")),
             }
@@ -180,10 +260,14 @@ fn main() -> anyhow::Result<()> {
                 Some((KOTLIN, args)) => {
                     let cfg = publish::KotlinConfig {
                         lang_package_path: packages_dir.join(KOTLIN),
+                        version: args.try_get_one::<String>("version")?.unwrap().to_owned(),
                         registry_url: args
                             .try_get_one::<String>("registry_url")?
                             .unwrap()
                             .to_owned(),
+                        snapshot_registry_url: args
+                            .try_get_one::<String>("snapshot_registry_url")?
+                            .cloned(),
                         registry_username: args
                             .try_get_one::<String>("registry_username")?
                             .unwrap()
@@ -196,6 +280,20 @@ fn main() -> anyhow::Result<()> {
                     cfg.lang_package_path.try_exists()?;
                     Ok(publish::kotlin(&cfg)?)
                 }
+                Some(("github-release", args)) => {
+                    shared_libs_dir
+                        .informed_exists("Are the shared libs already built ? Check CLI help.")?;
+                    let cfg = publish::GithubReleaseConfig {
+                        shared_libs_path: shared_libs_dir,
+                        xcframework_path: args
+                            .try_get_one::<String>("xcframework")?
+                            .map(PathBuf::from),
+                        tag: args.try_get_one::<String>("tag")?.unwrap().to_owned(),
+                        repo: args.try_get_one::<String>("repo")?.unwrap().to_owned(),
+                        token: args.try_get_one::<String>("token")?.unwrap().to_owned(),
+                    };
+                    Ok(publish::github_release(&cfg)?)
+                }
                 Some((SWIFT, args)) => match args.subcommand() {
                     Some(("git-repo", args)) => {
                         let cfg = publish::SwiftRepoConfig {
@@ -262,9 +360,77 @@ fn main() -> anyhow::Result<()> {
                         &docs_dir.join(SWIFT).join(version),
                     )?)
                 }
+                Some(("all", args)) => {
+                    let version = args.try_get_one::<String>("version")?.unwrap().to_owned();
+                    Ok(docgen::all(&packages_dir, &docs_dir, &version)?)
+                }
                 _ => Err(anyhow!("Command not found. See help.")),
             }
         }
+        Some(("examples", args)) => {
+            let languages: Vec<String> = args
+                .try_get_many::<String>("languages")?
+                .unwrap()
+                .map(Clone::clone)
+                .collect();
+            Ok(examples::generate(
+                &root_dir
+                    .join("uniffi-zcash-cli")
+                    .join("templates")
+                    .join("examples"),
+                &languages,
+                &examples_dir,
+            )?)
+        }
+        Some(("apidiff", args)) => {
+            let old_ref = args.try_get_one::<String>("old_ref")?.unwrap();
+            let new_ref = args.try_get_one::<String>("new_ref")?;
+            Ok(apidiff::generate_api_diff(
+                &root_dir,
+                old_ref,
+                new_ref.map(String::as_str),
+            )?)
+        }
+        Some(("bench", args)) => {
+            let python_bindings_dir = bindings_dir.join(PYTHON);
+            python_bindings_dir
+                .informed_exists("Are the python bindings already built ? Check CLI help.")?;
+            let iterations = *args.try_get_one::<u32>("iterations")?.unwrap();
+            Ok(bench::run_blob_benchmarks(
+                &root_dir,
+                &python_bindings_dir,
+                iterations,
+            )?)
+        }
+        Some(("demo", args)) => {
+            let params = match args.try_get_one::<String>("network")?.unwrap().as_str() {
+                "main" => ZcashConsensusParameters::MainNetwork,
+                _ => ZcashConsensusParameters::TestNetwork,
+            };
+            let seed = args.try_get_one::<String>("seed")?;
+            let lightwalletd_url = args.try_get_one::<String>("lightwalletd_url")?;
+            let blocks = *args.try_get_one::<u32>("blocks")?.unwrap();
+            run_demo(
+                params,
+                seed.map(String::as_str),
+                lightwalletd_url.map(String::as_str),
+                blocks,
+            )
+        }
+        Some(("wallet", args)) => match args.subcommand() {
+            Some(("info", args)) => {
+                let path = args.try_get_one::<String>("path")?.unwrap();
+                let network = args.try_get_one::<String>("network")?.unwrap();
+                print!("{}", wallet_info(&invocation_dir.join(path), network)?);
+                Ok(())
+            }
+            Some(("audit", args)) => {
+                let path = args.try_get_one::<String>("path")?.unwrap();
+                let network = args.try_get_one::<String>("network")?.unwrap();
+                audit_wallet(&invocation_dir.join(path), network)
+            }
+            _ => Err(anyhow!("Command not found. See help.")),
+        },
         Some(("diff", args)) => {
             let lib_name = args.try_get_one::<String>("lib_name")?.unwrap();
             let color = args
@@ -1,12 +1,16 @@
 use std::{
+    fs,
     path::{Path, PathBuf},
     process::Command,
 };
 
-use fs_extra::file::read_to_string;
+use fs_extra::file::{self, read_to_string};
 use retry::delay::Exponential;
 
-use crate::helper::{cmd_retry, cmd_success};
+use crate::helper::{
+    cmd_retry, cmd_success, gem_version, is_snapshot_version, tmp_folder, LINUX_SHARED_LIB_NAME,
+    MACOS_SHARED_LIB_NAME, TARGET_LINUX_X86_64, TARGET_MACOS_UNIVERSAL2,
+};
 
 pub fn python(config: &PythonConfig) -> anyhow::Result<()> {
     // Ensure deps are installed.
@@ -54,7 +58,7 @@ pub fn ruby(config: &RubyConfig) -> anyhow::Result<()> {
     let mut publish_cmd = Command::new("gem");
     publish_cmd
         .arg("push")
-        .arg(format!("zcash-{}.gem", &config.version))
+        .arg(format!("zcash-{}.gem", gem_version(&config.version)))
         .arg("--norc")
         .arg("--host")
         .arg(&config.registry_url)
@@ -77,6 +81,11 @@ pub struct RubyConfig {
 }
 
 pub fn kotlin(config: &KotlinConfig) -> anyhow::Result<()> {
+    // Snapshots go to their own repository, if any. See build.gradle.kts.
+    if is_snapshot_version(&config.version) && config.snapshot_registry_url.is_none() {
+        println!("No snapshot registry given, publishing the snapshot to the main registry.");
+    }
+
     let mut publish_cmd = Command::new("./gradlew");
     publish_cmd
         .arg("publish")
@@ -84,6 +93,9 @@ pub fn kotlin(config: &KotlinConfig) -> anyhow::Result<()> {
         .env("KOTLIN_REGISTRY_USERNAME", &config.registry_username)
         .env("KOTLIN_REGISTRY_PASSWORD", &config.registry_password)
         .current_dir(&config.lang_package_path);
+    if let Some(snapshot_registry_url) = &config.snapshot_registry_url {
+        publish_cmd.env("KOTLIN_SNAPSHOT_REGISTRY_URL", snapshot_registry_url);
+    }
 
     cmd_retry(
         "Kotlin publication",
@@ -95,7 +107,9 @@ pub fn kotlin(config: &KotlinConfig) -> anyhow::Result<()> {
 
 pub struct KotlinConfig {
     pub lang_package_path: PathBuf,
+    pub version: String,
     pub registry_url: String,
+    pub snapshot_registry_url: Option<String>,
     pub registry_username: String,
     pub registry_password: String,
 }
@@ -184,3 +198,101 @@ pub struct SwiftRegistryConfig {
     pub registry_url: String,
     pub registry_token: String,
 }
+
+pub fn github_release(config: &GithubReleaseConfig) -> anyhow::Result<()> {
+    // Gather the assets in a tmp folder, as the shared libraries of every target
+    // need distinct names in the release.
+    let assets_path = tmp_folder()?;
+    let mut assets = vec![
+        (
+            config.shared_libs_path.join(LINUX_SHARED_LIB_NAME),
+            format!("libuniffi_zcash-{}.so", TARGET_LINUX_X86_64),
+        ),
+        (
+            config.shared_libs_path.join(MACOS_SHARED_LIB_NAME),
+            format!("libuniffi_zcash-{}.dylib", TARGET_MACOS_UNIVERSAL2),
+        ),
+    ];
+    if let Some(xcframework_path) = &config.xcframework_path {
+        assets.push((
+            xcframework_path.clone(),
+            "Zcash.xcframework.zip".to_string(),
+        ));
+    }
+
+    // The checksums file follows the sha256sum format. The xcframework one is
+    // the checksum the Swift binary target needs.
+    let mut checksums = String::new();
+    for (path, name) in &assets {
+        file::copy(path, assets_path.join(name), &file::CopyOptions::default())?;
+        checksums.push_str(&format!(
+            "{}  {}\n",
+            sha256::digest(fs::read(path)?.as_slice()),
+            name
+        ));
+    }
+    fs::write(assets_path.join("SHA256SUMS"), checksums)?;
+
+    // Create the release if it does not exist yet. See https://cli.github.com/manual/gh_release
+    let release_exists = Command::new("gh")
+        .arg("release")
+        .arg("view")
+        .arg(&config.tag)
+        .arg("--repo")
+        .arg(&config.repo)
+        .env("GH_TOKEN", &config.token)
+        .output()?
+        .status
+        .success();
+
+    if !release_exists {
+        let mut create_cmd = Command::new("gh");
+        create_cmd
+            .arg("release")
+            .arg("create")
+            .arg(&config.tag)
+            .arg("--repo")
+            .arg(&config.repo)
+            .arg("--title")
+            .arg(&config.tag)
+            .arg("--notes")
+            .arg("")
+            .env("GH_TOKEN", &config.token);
+
+        cmd_retry(
+            "GitHub release creation",
+            Exponential::from_millis(1000),
+            10,
+            create_cmd,
+        )?;
+    }
+
+    // Upload the assets, replacing the ones of a previous attempt.
+    let mut upload_cmd = Command::new("gh");
+    upload_cmd
+        .arg("release")
+        .arg("upload")
+        .arg(&config.tag)
+        .args(assets.iter().map(|(_, name)| name))
+        .arg("SHA256SUMS")
+        .arg("--clobber")
+        .arg("--repo")
+        .arg(&config.repo)
+        .env("GH_TOKEN", &config.token)
+        .current_dir(&assets_path);
+
+    cmd_retry(
+        "GitHub release upload",
+        Exponential::from_millis(1000),
+        10,
+        upload_cmd,
+    )
+}
+
+pub struct GithubReleaseConfig {
+    pub shared_libs_path: PathBuf,
+    pub xcframework_path: Option<PathBuf>,
+    pub tag: String,
+    pub repo: String,
+    pub token: String,
+}
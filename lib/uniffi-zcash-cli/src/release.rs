@@ -1,7 +1,15 @@
-use std::path::PathBuf;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
+use serde::{Deserialize, Serialize};
+
+pub mod c;
 pub mod kotlin;
+pub mod notices;
 pub mod python;
+pub mod react_native;
 pub mod ruby;
 pub mod swift;
 
@@ -10,6 +18,112 @@ pub struct Config {
     pub git_repo_url: Option<String>,
     pub package_template_dir: PathBuf,
     pub test_app_template_dir: PathBuf,
+    /// The templates of the examples, compiled along with the test app, for the languages
+    /// having examples.
+    pub examples_template_dir: Option<PathBuf>,
     pub bindings_dir: PathBuf,
     pub package_dir: PathBuf,
+    pub udl_path: PathBuf,
+    /// The workspace manifest, used to list the crates bundled in the packages.
+    pub manifest_path: PathBuf,
+    /// Rebuilds the package even if its inputs did not change since the last release.
+    pub force: bool,
+    pub mobile_libs: MobileLibs,
+}
+
+/// The prebuilt shared libraries of the mobile platforms, which the `sharedlibs`
+/// command does not build. Only used by the React Native release.
+#[derive(Default)]
+pub struct MobileLibs {
+    /// A folder with a subfolder per Android ABI (`arm64-v8a`, `x86_64` ...).
+    pub android_libs_dir: Option<PathBuf>,
+    pub ios_xcframework: Option<PathBuf>,
+}
+
+/// What a language package was last built from, stored next to the package folder.
+#[derive(Serialize, Deserialize, PartialEq, Eq)]
+struct ReleaseManifest {
+    version: String,
+    git_repo_url: Option<String>,
+    /// The digest of the package template, test app template, examples templates and
+    /// bindings files.
+    inputs_digest: String,
+}
+
+impl Config {
+    /// Runs the given language release, unless the package was already built from the same
+    /// version, templates and bindings, which cuts the iteration time when only the packaging
+    /// of some languages changed. Changes to the CLI itself need `force`.
+    pub fn run_if_changed(
+        &self,
+        language: &str,
+        run: fn(&Config) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let manifest_path = self.package_dir.with_extension("manifest.json");
+        let manifest = ReleaseManifest {
+            version: self.version.clone(),
+            git_repo_url: self.git_repo_url.clone(),
+            inputs_digest: self.inputs_digest()?,
+        };
+
+        let last_manifest = fs::read_to_string(&manifest_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<ReleaseManifest>(&content).ok());
+        if !self.force && self.package_dir.exists() && last_manifest.as_ref() == Some(&manifest) {
+            println!(
+                "The {} package is up to date with its templates and bindings, skipping. Use --force to rebuild it.",
+                language
+            );
+            return Ok(());
+        }
+
+        // Removed first, so a failed release is never taken as up to date.
+        if manifest_path.exists() {
+            fs::remove_file(&manifest_path)?;
+        }
+        run(self)?;
+        fs::write(manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+        Ok(())
+    }
+
+    fn inputs_digest(&self) -> anyhow::Result<String> {
+        let mut files = Vec::new();
+        for dir in [
+            Some(&self.package_template_dir),
+            Some(&self.test_app_template_dir),
+            self.examples_template_dir.as_ref(),
+            Some(&self.bindings_dir),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            collect_files(dir, &mut files)?;
+        }
+
+        let mut listing = String::new();
+        for path in files {
+            listing.push_str(&format!(
+                "{} {}\n",
+                path.to_string_lossy(),
+                sha256::digest(fs::read(&path)?.as_slice())
+            ));
+        }
+        Ok(sha256::digest(listing))
+    }
+}
+
+/// Recursively collects the files of a folder, in a stable order.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
 }
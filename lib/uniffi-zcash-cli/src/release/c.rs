@@ -0,0 +1,102 @@
+use crate::release::{
+    notices::{write_third_party_notices, THIRD_PARTY_NOTICES},
+    Config,
+};
+use std::process::Command;
+
+use fs_extra::{
+    dir::{self, CopyOptions},
+    file,
+};
+use serde_json::json;
+
+use crate::helper::{
+    clean_dir, cmd_success, in_file_template_replace, tmp_folder, LINUX_SHARED_LIB_NAME,
+    MACOS_SHARED_LIB_NAME,
+};
+
+pub fn run(cfg: &Config) -> anyhow::Result<()> {
+    cfg.bindings_dir.try_exists()?;
+    clean_dir(&cfg.package_dir)?;
+
+    // The archive content, extracted in a folder named after the package.
+    let archive_name = format!("zcash-c-{}", cfg.version);
+    let archive_dir = cfg.package_dir.join(&archive_name);
+    let include_dir = archive_dir.join("include");
+    let lib_dir = archive_dir.join("lib");
+    clean_dir(&include_dir)?;
+    clean_dir(&lib_dir)?;
+
+    dir::copy(
+        &cfg.package_template_dir,
+        &archive_dir,
+        &CopyOptions::new().content_only(true),
+    )?;
+
+    // Copy all needed files from previously generated bindings operation
+    file::copy(
+        cfg.bindings_dir.join(LINUX_SHARED_LIB_NAME),
+        lib_dir.join(LINUX_SHARED_LIB_NAME),
+        &file::CopyOptions::default(),
+    )?;
+    file::copy(
+        cfg.bindings_dir.join(MACOS_SHARED_LIB_NAME),
+        lib_dir.join(MACOS_SHARED_LIB_NAME),
+        &file::CopyOptions::default(),
+    )?;
+    file::copy(
+        cfg.bindings_dir.join("zcash.h"),
+        include_dir.join("zcash.h"),
+        &file::CopyOptions::default(),
+    )?;
+
+    write_third_party_notices(&cfg.manifest_path, &archive_dir.join(THIRD_PARTY_NOTICES))?;
+
+    // Modify in place the README.md in order to set version in the template.
+    in_file_template_replace(
+        archive_dir.join("README.md"),
+        &json!({ "version": cfg.version }),
+    )?;
+
+    cmd_success(
+        Command::new("tar")
+            .arg("-czf")
+            .arg(format!("{}.tar.gz", archive_name))
+            .arg(&archive_name)
+            .current_dir(&cfg.package_dir)
+            .spawn()?
+            .wait(),
+    )?;
+
+    // Build and run the test app against the packaged header and library,
+    // found at runtime through the rpath.
+    let test_app_path = tmp_folder()?;
+    dir::copy(
+        &cfg.test_app_template_dir,
+        &test_app_path,
+        &CopyOptions::new().content_only(true),
+    )?;
+
+    cmd_success(
+        Command::new("cc")
+            .arg("app.c")
+            .arg("-I")
+            .arg(&include_dir)
+            .arg("-L")
+            .arg(&lib_dir)
+            .arg("-luniffi_zcash")
+            .arg(format!("-Wl,-rpath,{}", lib_dir.to_string_lossy()))
+            .arg("-o")
+            .arg("app")
+            .current_dir(&test_app_path)
+            .spawn()?
+            .wait(),
+    )?;
+
+    cmd_success(
+        Command::new("./app")
+            .current_dir(test_app_path)
+            .spawn()?
+            .wait(),
+    )
+}
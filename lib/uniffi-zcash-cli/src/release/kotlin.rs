@@ -1,5 +1,8 @@
-use crate::release::Config;
-use std::process::Command;
+use crate::release::{
+    notices::{write_third_party_notices, THIRD_PARTY_NOTICES},
+    Config,
+};
+use std::{fs, process::Command};
 
 use fs_extra::{
     dir::{self, CopyOptions},
@@ -12,6 +15,7 @@
     clean_dir, cmd_success, in_file_template_replace, tmp_folder, LINUX_SHARED_LIB_NAME,
     MACOS_SHARED_LIB_NAME,
 };
+use crate::{examples, KOTLIN};
 
 pub fn run(cfg: &Config) -> anyhow::Result<()> {
     cfg.bindings_dir.try_exists()?;
@@ -51,6 +55,33 @@ pub fn run(cfg: &Config) -> anyhow::Result<()> {
         &file::CopyOptions::default(),
     )?;
 
+    // Bundled in the jar, next to the shared libraries.
+    let resources_dir = cfg
+        .package_dir
+        .join("lib")
+        .join("src")
+        .join("main")
+        .join("resources");
+    fs::create_dir_all(&resources_dir)?;
+    write_third_party_notices(&cfg.manifest_path, &resources_dir.join(THIRD_PARTY_NOTICES))?;
+
+    // Domain packages are only there if bindings were generated grouping them by domain.
+    for entry in bindings_code.read_dir()? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dir::copy(
+                &path,
+                cfg.package_dir
+                    .join("lib")
+                    .join("src")
+                    .join("main")
+                    .join("kotlin")
+                    .join("zcash"),
+                &CopyOptions::new(),
+            )?;
+        }
+    }
+
     // Modify in place the build.gradle.kts in order to set version in the template.
     let gradle_path = cfg.package_dir.join("lib").join("build.gradle.kts");
     in_file_template_replace(gradle_path, &json!({ "version": cfg.version }))?;
@@ -78,6 +109,12 @@ pub fn run(cfg: &Config) -> anyhow::Result<()> {
         &json!({ "version": cfg.version }),
     )?;
 
+    // The examples are compiled along with the app, so they never drift from the API.
+    if let Some(examples_template_dir) = &cfg.examples_template_dir {
+        let examples_dir = test_app_path.join("app/src/main/kotlin/examples");
+        examples::render(examples_template_dir, KOTLIN, &examples_dir)?;
+    }
+
     cmd_success(
         Command::new("gradle")
             .arg("run")
@@ -0,0 +1,138 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt::Write,
+    fs,
+    path::Path,
+};
+
+use anyhow::anyhow;
+use cargo_metadata::{DependencyKind, MetadataCommand, Package, PackageId};
+
+/// The name of the generated notices file, bundled in every language package.
+pub const THIRD_PARTY_NOTICES: &str = "THIRD-PARTY-NOTICES";
+
+/// The crate whose dependencies are statically linked in the shared libraries.
+const LIB_CRATE: &str = "zcash";
+
+/// File name prefixes of the license texts shipped in the crates.
+const LICENSE_FILE_PREFIXES: [&str; 4] = ["LICENSE", "LICENCE", "COPYING", "NOTICE"];
+
+/// Writes the [`THIRD_PARTY_NOTICES`] file at `dest`, listing the Rust crates that get
+/// statically linked in the shared libraries, with their licenses.
+///
+/// The crates come from the cargo metadata of the workspace at `manifest_path`, following
+/// the normal dependencies of the library crate, so build and dev dependencies are left
+/// out. As the metadata is resolved for all targets, dependencies of other platforms
+/// may be listed too.
+pub fn write_third_party_notices(manifest_path: &Path, dest: &Path) -> anyhow::Result<()> {
+    let metadata = MetadataCommand::new().manifest_path(manifest_path).exec()?;
+    let resolve = metadata
+        .resolve
+        .as_ref()
+        .ok_or(anyhow!("The cargo metadata has no dependency graph."))?;
+    let packages: BTreeMap<&PackageId, &Package> =
+        metadata.packages.iter().map(|p| (&p.id, p)).collect();
+    let nodes: BTreeMap<_, _> = resolve.nodes.iter().map(|n| (&n.id, n)).collect();
+
+    let root = metadata
+        .packages
+        .iter()
+        .find(|p| p.name == LIB_CRATE && metadata.workspace_members.contains(&p.id))
+        .ok_or(anyhow!("Cannot find the {} crate.", LIB_CRATE))?;
+
+    // Walk the normal dependencies from the library crate.
+    let mut linked = BTreeSet::new();
+    let mut pending = VecDeque::from([&root.id]);
+    while let Some(id) = pending.pop_front() {
+        let node = nodes
+            .get(id)
+            .ok_or(anyhow!("Cannot find {} in the dependency graph.", id))?;
+        for dep in &node.deps {
+            let is_normal = dep
+                .dep_kinds
+                .iter()
+                .any(|info| info.kind == DependencyKind::Normal);
+            if is_normal && linked.insert(&dep.pkg) {
+                pending.push_back(&dep.pkg);
+            }
+        }
+    }
+
+    let mut crates: Vec<&Package> = linked
+        .into_iter()
+        .filter(|id| !metadata.workspace_members.contains(id))
+        .map(|id| packages[id])
+        .collect();
+    crates.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+
+    // Crates very often ship the very same license texts, so they are only written once.
+    let mut texts: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut notices = String::new();
+    writeln!(
+        notices,
+        "THIRD-PARTY SOFTWARE NOTICES\n\n\
+        This package includes the following Rust crates, statically linked in its \
+        shared libraries.\n"
+    )?;
+    for package in &crates {
+        let crate_id = format!("{} {}", package.name, package.version);
+        writeln!(
+            notices,
+            "- {} ({}){}",
+            crate_id,
+            package.license.as_deref().unwrap_or("see license text"),
+            package
+                .repository
+                .as_ref()
+                .map(|repository| format!(" {}", repository))
+                .unwrap_or_default()
+        )?;
+        for text in license_texts(package)? {
+            texts.entry(text).or_default().push(crate_id.clone());
+        }
+    }
+
+    for (text, crate_ids) in texts {
+        writeln!(
+            notices,
+            "\n{}\n\nLicense text of: {}\n\n{}",
+            "-".repeat(80),
+            crate_ids.join(", "),
+            text.trim_end()
+        )?;
+    }
+
+    fs::write(dest, notices)?;
+    Ok(())
+}
+
+/// The license texts found in the root folder of a crate, if any.
+fn license_texts(package: &Package) -> anyhow::Result<Vec<String>> {
+    let crate_dir = package
+        .manifest_path
+        .parent()
+        .ok_or(anyhow!("Cannot find the folder of {}.", package.name))?;
+
+    let mut paths: Vec<_> = fs::read_dir(crate_dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| {
+                        let name = name.to_uppercase();
+                        LICENSE_FILE_PREFIXES
+                            .iter()
+                            .any(|prefix| name.starts_with(prefix))
+                    })
+        })
+        .collect();
+    paths.sort();
+
+    Ok(paths
+        .into_iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .collect())
+}
@@ -1,4 +1,7 @@
-use crate::release::Config;
+use crate::release::{
+    notices::{write_third_party_notices, THIRD_PARTY_NOTICES},
+    Config,
+};
 use std::process::Command;
 
 use fs_extra::{
@@ -8,9 +11,10 @@
 use serde_json::json;
 
 use crate::helper::{
-    clean_dir, cmd_success, in_file_template_replace, tmp_folder, LINUX_SHARED_LIB_NAME,
-    MACOS_SHARED_LIB_NAME,
+    clean_dir, cmd_success, in_file_template_replace, pep440_version, tmp_folder,
+    LINUX_SHARED_LIB_NAME, MACOS_SHARED_LIB_NAME,
 };
+use crate::{examples, PYTHON};
 
 pub fn run(cfg: &Config) -> anyhow::Result<()> {
     clean_dir(&cfg.package_dir)?;
@@ -38,10 +42,24 @@ pub fn run(cfg: &Config) -> anyhow::Result<()> {
         cfg.package_dir.join("zcash").join("zcash.py"),
         &file::CopyOptions::default(),
     )?;
+    file::copy(
+        cfg.bindings_dir.join("zcash.pyi"),
+        cfg.package_dir.join("zcash").join("zcash.pyi"),
+        &file::CopyOptions::default(),
+    )?;
+
+    write_third_party_notices(
+        &cfg.manifest_path,
+        &cfg.package_dir.join("zcash").join(THIRD_PARTY_NOTICES),
+    )?;
 
     // Modify in place setup.py in order to set version in the template.
     let setup_py_path = cfg.package_dir.join("setup.py");
-    in_file_template_replace(setup_py_path, &json!({ "version": cfg.version }))?;
+    // Pre-releases need a PEP 440 version, so pip only installs them when asked to.
+    in_file_template_replace(
+        setup_py_path,
+        &json!({ "version": pep440_version(&cfg.version)? }),
+    )?;
 
     // Prepare python distribution files
     cmd_success(
@@ -86,6 +104,11 @@ pub fn run(cfg: &Config) -> anyhow::Result<()> {
         &CopyOptions::new().content_only(true),
     )?;
 
+    // The examples need the network, so they are only compiled.
+    if let Some(examples_template_dir) = &cfg.examples_template_dir {
+        examples::check_syntax(examples_template_dir, PYTHON, &test_app_path)?;
+    }
+
     cmd_success(
         Command::new("python3")
             .arg("app.py")
@@ -0,0 +1,137 @@
+use crate::release::{
+    notices::{write_third_party_notices, THIRD_PARTY_NOTICES},
+    Config,
+};
+use std::{fs, process::Command};
+
+use fs_extra::{
+    dir::{self, CopyOptions},
+    file,
+};
+use serde_json::json;
+
+use crate::codegen::{component_interface, stability::ApiStability, typescript::type_declarations};
+use crate::helper::{clean_dir, cmd_success, in_file_template_replace, tmp_folder};
+
+pub fn run(cfg: &Config) -> anyhow::Result<()> {
+    cfg.bindings_dir.try_exists()?;
+    clean_dir(&cfg.package_dir)?;
+
+    dir::copy(
+        &cfg.package_template_dir,
+        &cfg.package_dir,
+        &CopyOptions::new().content_only(true),
+    )?;
+
+    // Copy all needed files from previously generated bindings operation
+    let kotlin_dir = cfg.package_dir.join("android").join("src").join("main");
+    clean_dir(&kotlin_dir.join("kotlin").join("uniffi").join("zcash"))?;
+    file::copy(
+        cfg.bindings_dir
+            .join("kotlin")
+            .join("uniffi")
+            .join("zcash")
+            .join("zcash.kt"),
+        kotlin_dir
+            .join("kotlin")
+            .join("uniffi")
+            .join("zcash")
+            .join("zcash.kt"),
+        &file::CopyOptions::default(),
+    )?;
+    file::copy(
+        cfg.bindings_dir.join("swift").join("zcash.swift"),
+        cfg.package_dir.join("ios").join("zcash.swift"),
+        &file::CopyOptions::default(),
+    )?;
+
+    match &cfg.mobile_libs.android_libs_dir {
+        Some(android_libs_dir) => {
+            dir::copy(
+                android_libs_dir,
+                kotlin_dir.join("jniLibs"),
+                &CopyOptions::new().content_only(true),
+            )?;
+        }
+        None => println!("No Android shared libraries given, the package will not have them."),
+    }
+    match &cfg.mobile_libs.ios_xcframework {
+        Some(ios_xcframework) => {
+            dir::copy(
+                ios_xcframework,
+                cfg.package_dir.join("ios").join("zcashFFI.xcframework"),
+                &CopyOptions::new().content_only(true),
+            )?;
+        }
+        None => println!("No iOS xcframework given, the package will not have it."),
+    }
+
+    // Generate the TypeScript declarations of the bindings.
+    let ci = component_interface(&cfg.udl_path)?;
+    let workspace_dir = cfg.manifest_path.parent().unwrap_or(&cfg.package_dir);
+    let stability = ApiStability::load(
+        &workspace_dir
+            .join("uniffi-zcash")
+            .join("api_stability.toml"),
+        &ci,
+    )?;
+    fs::write(
+        cfg.package_dir.join("index.d.ts"),
+        type_declarations(&ci, &stability)?,
+    )?;
+
+    write_third_party_notices(
+        &cfg.manifest_path,
+        &cfg.package_dir.join(THIRD_PARTY_NOTICES),
+    )?;
+
+    // Modify in place the package.json and README.md in order to set version in the template.
+    let data = &json!({ "version": cfg.version });
+    in_file_template_replace(cfg.package_dir.join("package.json"), data)?;
+    in_file_template_replace(cfg.package_dir.join("README.md"), data)?;
+
+    // Prepare the npm package tarball.
+    cmd_success(
+        Command::new("npm")
+            .arg("pack")
+            .current_dir(&cfg.package_dir)
+            .spawn()?
+            .wait(),
+    )?;
+
+    // Install and type check the test app against the package declarations.
+    let test_app_path = tmp_folder()?;
+    dir::copy(
+        &cfg.test_app_template_dir,
+        &test_app_path,
+        &CopyOptions::new().content_only(true),
+    )?;
+
+    cmd_success(
+        Command::new("npm")
+            .arg("install")
+            .arg("--no-save")
+            .arg("--legacy-peer-deps")
+            .arg(
+                cfg.package_dir
+                    .join(format!("uniffi-zcash-react-native-{}.tgz", cfg.version)),
+            )
+            .arg("typescript")
+            .current_dir(&test_app_path)
+            .spawn()?
+            .wait(),
+    )?;
+
+    cmd_success(
+        Command::new("npx")
+            .arg("tsc")
+            .arg("-p")
+            .arg(".")
+            .current_dir(&test_app_path)
+            .spawn()?
+            .wait(),
+    )?;
+
+    println!("React Native test application successfully type checked ✅");
+    Ok(())
+}
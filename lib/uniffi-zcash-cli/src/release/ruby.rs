@@ -1,5 +1,13 @@
-use crate::release::Config;
-use std::{fs::OpenOptions, io::Write, ops::Add, process::Command};
+use crate::release::{
+    notices::{write_third_party_notices, THIRD_PARTY_NOTICES},
+    Config,
+};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    ops::Add,
+    process::Command,
+};
 
 use fs_extra::{
     dir::{self, CopyOptions},
@@ -7,10 +15,12 @@
 };
 use serde_json::json;
 
+use crate::codegen::{component_interface, ruby::rbs_signatures};
 use crate::helper::{
-    clean_dir, cmd_success, in_file_template_replace, tmp_folder, LINUX_SHARED_LIB_NAME,
-    MACOS_SHARED_LIB_NAME,
+    clean_dir, cmd_success, gem_version, in_file_template_replace, tmp_folder,
+    LINUX_SHARED_LIB_NAME, MACOS_SHARED_LIB_NAME,
 };
+use crate::{examples, RUBY};
 
 pub fn run(cfg: &Config) -> anyhow::Result<()> {
     cfg.bindings_dir.try_exists()?;
@@ -39,10 +49,27 @@ pub fn run(cfg: &Config) -> anyhow::Result<()> {
         &file::CopyOptions::default(),
     )?;
 
+    write_third_party_notices(
+        &cfg.manifest_path,
+        &cfg.package_dir.join(THIRD_PARTY_NOTICES),
+    )?;
+
+    // Generate the RBS signatures of the bindings, shipped in the gem "sig" folder.
+    let sig_dir = cfg.package_dir.join("sig");
+    fs::create_dir_all(&sig_dir)?;
+    fs::write(
+        sig_dir.join("zcash.rbs"),
+        rbs_signatures(&component_interface(&cfg.udl_path)?)?,
+    )?;
+
     // Modify in place the gemspec in order to set version in the template.
 
     let gemspec_path = cfg.package_dir.join("zcash.gemspec");
-    in_file_template_replace(gemspec_path, &json!({ "version": cfg.version }))?;
+    // Pre-releases need a gem version, as RubyGems does not take semver ones.
+    in_file_template_replace(
+        gemspec_path,
+        &json!({ "version": gem_version(&cfg.version) }),
+    )?;
 
     // Super hack 🔥. In order to be able to load shared library provided in the gem,
     // we need either to provide to the "ffi_lib" function loader (see zcash.rb) an absolute path
@@ -84,7 +111,7 @@ pub fn run(cfg: &Config) -> anyhow::Result<()> {
     cmd_success(
         Command::new("gem")
             .arg("install")
-            .arg(format!("./zcash-{}.gem", cfg.version))
+            .arg(format!("./zcash-{}.gem", gem_version(&cfg.version)))
             .current_dir(&cfg.package_dir)
             .spawn()?
             .wait(),
@@ -97,6 +124,11 @@ pub fn run(cfg: &Config) -> anyhow::Result<()> {
         &CopyOptions::new().content_only(true),
     )?;
 
+    // The examples need the network, so they are only compiled.
+    if let Some(examples_template_dir) = &cfg.examples_template_dir {
+        examples::check_syntax(examples_template_dir, RUBY, &test_app_path)?;
+    }
+
     cmd_success(
         Command::new("ruby")
             .arg("app.rb")
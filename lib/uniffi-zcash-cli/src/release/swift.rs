@@ -1,5 +1,14 @@
-use crate::release::Config;
-use std::{env, fs::OpenOptions, io::Write, process::Command};
+use crate::codegen::{component_interface, swift::async_overlay};
+use crate::release::{
+    notices::{write_third_party_notices, THIRD_PARTY_NOTICES},
+    Config,
+};
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    process::Command,
+};
 
 use fs_extra::{
     dir::{self, CopyOptions},
@@ -11,6 +20,7 @@
     clean_dir, cmd_success, in_file_template_replace, tmp_folder, LINUX_SHARED_LIB_NAME,
     MACOS_SHARED_LIB_NAME,
 };
+use crate::{examples, SWIFT};
 
 pub fn run(cfg: &Config) -> anyhow::Result<()> {
     cfg.bindings_dir.try_exists()?;
@@ -85,6 +95,34 @@ pub fn run(cfg: &Config) -> anyhow::Result<()> {
         &file::CopyOptions::default(),
     )?;
 
+    write_third_party_notices(
+        &cfg.manifest_path,
+        &package_subfolder.join(THIRD_PARTY_NOTICES),
+    )?;
+
+    // Add the async/await overlay on top of the generated bindings.
+    let ci = component_interface(&cfg.udl_path)?;
+    fs::write(
+        package_subfolder
+            .join("Sources")
+            .join("Zcash")
+            .join("ZcashAsync.swift"),
+        async_overlay(&ci)?,
+    )?;
+
+    // Only there if bindings were generated grouping them by domain.
+    let domains_path = cfg.bindings_dir.join("ZcashDomains.swift");
+    if domains_path.exists() {
+        file::copy(
+            domains_path,
+            package_subfolder
+                .join("Sources")
+                .join("Zcash")
+                .join("ZcashDomains.swift"),
+            &file::CopyOptions::default(),
+        )?;
+    }
+
     env::set_current_dir(&package_subfolder)?;
 
     // Commit and tag the version
@@ -133,6 +171,12 @@ pub fn run(cfg: &Config) -> anyhow::Result<()> {
     let data = &json!({ "version": cfg.version, "git_repo_path": &package_subfolder});
     in_file_template_replace(test_app_path.join("Package.swift"), data)?;
 
+    // The examples are compiled along with the app, so they never drift from the API.
+    if let Some(examples_template_dir) = &cfg.examples_template_dir {
+        let examples_dir = test_app_path.join("Sources").join("Examples");
+        examples::render(examples_template_dir, SWIFT, &examples_dir)?;
+    }
+
     let linked_lib_path = generated_shared_lib_path.as_path().to_string_lossy();
 
     cmd_success(
@@ -1,6 +1,13 @@
 use crate::helper::{clean_dir, cmd_success, TARGETS};
 use anyhow::anyhow;
-use std::{fs, path::PathBuf, process::Command};
+use std::{
+    fs,
+    panic::catch_unwind,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use zcash_primitives::{sapling::prover::TxProver, zip32::ExtendedSpendingKey};
+use zcash_proofs::prover::LocalTxProver;
 
 pub fn add_rust_targets() -> anyhow::Result<()> {
     TARGETS.iter().try_for_each(|arch| {
@@ -137,3 +144,28 @@ pub fn install_dokka_cli() -> anyhow::Result<()> {
 pub fn dokka_install_dir() -> PathBuf {
     home_dir().unwrap().join("dokka")
 }
+
+/// Checks the sapling parameters work, by loading them in a prover, which fails if they
+/// do not match their known hashes, and generating a dummy output proof with it. So
+/// corrupted downloads, or CPUs the proving code does not run on, are found at setup
+/// rather than when spending.
+pub fn verify_sapling_prover(spend_path: &Path, output_path: &Path) -> anyhow::Result<()> {
+    // The prover panics when the parameters cannot be read or are not the expected ones.
+    let prover = catch_unwind(|| LocalTxProver::new(spend_path, output_path)).map_err(|_| {
+        anyhow!("The sapling parameters are corrupted. Remove them and download them again.")
+    })?;
+
+    let (_, payment_address) = ExtendedSpendingKey::master(&[0; 32]).default_address();
+    let mut ctx = prover.new_sapling_proving_context();
+    catch_unwind(move || {
+        prover.output_proof(
+            &mut ctx,
+            jubjub::Fr::from(1),
+            payment_address,
+            jubjub::Fr::from(2),
+            1,
+        )
+    })
+    .map_err(|_| anyhow!("Cannot generate a proof with the sapling parameters."))?;
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use std::{fmt::Write, path::Path};
+
+use anyhow::anyhow;
+use uniffi_zcash::{ZcashConsensusParameters, ZcashNonNegativeAmount, ZcashWalletDb};
+
+fn open_wallet(path: &Path, network: &str) -> anyhow::Result<ZcashWalletDb> {
+    let params = match network {
+        "main" => ZcashConsensusParameters::MainNetwork,
+        _ => ZcashConsensusParameters::TestNetwork,
+    };
+    Ok(ZcashWalletDb::for_path(
+        path.to_string_lossy().into_owned(),
+        params,
+    )?)
+}
+
+/// Describes the wallet database at `path`: its schema, accounts with their birthdays
+/// and balances, and its scanning status. It is read with the same calls the bindings
+/// make, so it shows what the apps see.
+pub fn wallet_info(path: &Path, network: &str) -> anyhow::Result<String> {
+    let db = open_wallet(path, network)?;
+    let zec = |zatoshis: u64| -> anyhow::Result<String> {
+        Ok(ZcashNonNegativeAmount::from_u64(zatoshis)?.to_display_string())
+    };
+    let mut info = String::new();
+
+    // The debug report is the only place the applied migrations are read.
+    let report: serde_json::Value = serde_json::from_str(&db.debug_report(true)?)?;
+    let migrations = report["schema"]["migrations"]
+        .as_array()
+        .map_or(0, Vec::len);
+    writeln!(info, "Network: {}", network)?;
+    writeln!(info, "Schema: {} migrations applied", migrations)?;
+
+    let summary = db.get_wallet_summary(1)?;
+    let balances = summary
+        .as_ref()
+        .map(|summary| summary.account_balance_values())
+        .unwrap_or_default();
+    let accounts = db.get_unified_full_viewing_keys()?;
+    writeln!(info, "Accounts: {}", accounts.len())?;
+    for account in accounts {
+        let id = account.account;
+        write!(
+            info,
+            "  {}: birthday {}",
+            id.id,
+            db.get_account_birthday(id)?.value()
+        )?;
+        match balances.get(&id.id) {
+            Some(balance) => writeln!(
+                info,
+                ", balance {} ({} spendable, {} unshielded)",
+                zec(balance.total)?,
+                zec(balance.sapling_spendable)?,
+                zec(balance.unshielded)?
+            )?,
+            None => writeln!(info)?,
+        }
+    }
+
+    match summary {
+        Some(summary) => writeln!(
+            info,
+            "Chain tip: {}, fully scanned: {} ({} blocks behind)",
+            summary.chain_tip_height_value(),
+            summary.fully_scanned_height_value(),
+            summary.sync_lag()
+        )?,
+        None => writeln!(info, "Chain tip: unknown")?,
+    }
+    let ranges = db.suggest_scan_ranges()?;
+    writeln!(info, "Scan queue: {} ranges to scan", ranges.len())?;
+    for range in ranges {
+        writeln!(info, "  {} ({} blocks)", range, range.len())?;
+    }
+    Ok(info)
+}
+
+/// Audits the wallet database at `path`, see `ZcashWalletDb::audit`, printing the issues
+/// found. Fails if there are any, so that it can be scripted.
+pub fn audit_wallet(path: &Path, network: &str) -> anyhow::Result<()> {
+    let report = open_wallet(path, network)?.audit()?;
+
+    println!(
+        "Checked the commitments of {} unspent notes and the balances of {} accounts",
+        report.notes_checked, report.accounts_checked
+    );
+    for issue in &report.issues {
+        println!(
+            "{:?} - {} {}: {}",
+            issue.check, issue.table, issue.row_id, issue.description
+        );
+    }
+
+    if report.issues.is_empty() {
+        println!("No issues found ✅");
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Found {} issues in the wallet",
+            report.issues.len()
+        ))
+    }
+}
@@ -0,0 +1,38 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "zcash.h"
+
+static void check_status(RustCallStatus status, const char *call)
+{
+    if (status.code != 0) {
+        fprintf(stderr, "%s failed with status %d\n", call, status.code);
+        exit(1);
+    }
+}
+
+int main(void)
+{
+    RustCallStatus status = {0};
+
+    if (ffi_uniffi_zcash_uniffi_contract_version() == 0) {
+        fprintf(stderr, "the library has no contract version\n");
+        return 1;
+    }
+
+    void *amount = uniffi_uniffi_zcash_fn_constructor_zcashamount_new(100, &status);
+    check_status(status, "ZcashAmount.new");
+
+    int64_t value = uniffi_uniffi_zcash_fn_method_zcashamount_value(amount, &status);
+    check_status(status, "ZcashAmount.value");
+    if (value != 100) {
+        fprintf(stderr, "the amount value is %lld\n", (long long)value);
+        return 1;
+    }
+
+    uniffi_uniffi_zcash_fn_free_zcashamount(amount, &status);
+    check_status(status, "ZcashAmount.free");
+
+    printf("C test application successfully executed ✅\n");
+    return 0;
+}
@@ -53,7 +53,13 @@ publishing {
    }
    repositories {
      maven {
-        url = uri(System.getenv("KOTLIN_REGISTRY_URL")) // https://example.com/repository/maven
+        // -SNAPSHOT versions go to the snapshots repository, if any.
+        val snapshotUrl = System.getenv("KOTLIN_SNAPSHOT_REGISTRY_URL")
+        url = if ("{{version}}".endsWith("-SNAPSHOT") && snapshotUrl != null) {
+            uri(snapshotUrl) // https://example.com/repository/maven-snapshots
+        } else {
+            uri(System.getenv("KOTLIN_REGISTRY_URL")) // https://example.com/repository/maven
+        }
         isAllowInsecureProtocol = true // uncomment this for testing.
         credentials {
             username = System.getenv("KOTLIN_REGISTRY_USERNAME") // Use "token" as the username for API token authentication
@@ -3,17 +3,76 @@
  */
 package testapp
 
+import java.nio.file.Files
 import uniffi.zcash.*
 
+val PARAMS = ZcashConsensusParameters.TEST_NETWORK
+val SEED = listOf(1.toUByte()) + List(31) { 0.toUByte() }
+const val BIRTHDAY_HEIGHT = 280000u
+const val CHAIN_TIP = BIRTHDAY_HEIGHT + 100u
+
 class App {
     fun callAmount() {
         val amount = ZcashAmount(100)
         amount.value()
     }
+
+    fun createWallet(path: String): Pair<ZcashWalletDb, TupleAccountIdAndUnifiedSpendingKey> {
+        val db = ZcashWalletDb.forPath(path, PARAMS)
+        db.initialize(SEED)
+        val treestate = ZcashTreeState("test", BIRTHDAY_HEIGHT.toULong(), "", 0u, "000000", "")
+        val birthday = ZcashAccountBirthday.fromTreestate(treestate, null)
+        return Pair(db, db.createAccount(SEED, birthday))
+    }
+
+    fun checkAddress(db: ZcashWalletDb, account: TupleAccountIdAndUnifiedSpendingKey): String {
+        val address = db.getCurrentAddress(account.accountId)!!.encode(PARAMS)
+        val decoded = ZcashRecipientAddress.decode(PARAMS, address)
+        check(decoded.encode(PARAMS) == address) { "the address does not round trip" }
+        return address
+    }
+
+    fun checkBalance(db: ZcashWalletDb, account: TupleAccountIdAndUnifiedSpendingKey) {
+        db.updateChainTip(CHAIN_TIP)
+        val summary = checkNotNull(db.getWalletSummary(1u)) { "the wallet has no summary" }
+        val balance = summary.accountBalanceValues()[account.accountId.id]!!
+        check(balance.total == 0uL) { "the new account has funds" }
+    }
+
+    fun checkSpend(db: ZcashWalletDb, account: TupleAccountIdAndUnifiedSpendingKey, address: String) {
+        // Proving needs the sapling parameters, which are not shipped with the package.
+        val prover = try {
+            ZcashLocalTxProver.withDefaultLocation()
+        } catch (e: ZcashException.Message) {
+            println("Sapling parameters not found, skipping the spend")
+            return
+        }
+
+        val inputSelector = ZcashGreedyInputSelector(
+            ZcashFeeRule.zip317(ZcashZip317FeeRule.standard()),
+            ZcashChangeStrategy.SINGLE_OUTPUT,
+            ZcashDustOutputPolicy(ZcashDustAction.REJECT, null))
+        val request = ZcashTransactionRequest.fromUri(PARAMS, "zcash:$address?amount=0.001")
+        try {
+            spend(db, prover, inputSelector, account.unifiedSpendingKey, request,
+                ZcashOvkPolicy.Sender, 1u)
+        } catch (e: ZcashException.Message) {
+            return
+        }
+        error("the spend without funds succeeded")
+    }
 }
 
 fun main() {
     val app = App()
     app.callAmount()
+
+    val folder = Files.createTempDirectory("zcash")
+    val (db, account) = app.createWallet(folder.resolve("wallet.db").toString())
+    val address = app.checkAddress(db, account)
+    app.checkBalance(db, account)
+    app.checkSpend(db, account, address)
+    folder.toFile().deleteRecursively()
+
     println("Kotlin test application successfully executed ✅")
 }
@@ -1,6 +1,66 @@
+import os
+import tempfile
+
 from zcash import *
 
+PARAMS = ZcashConsensusParameters.TEST_NETWORK
+SEED = [1] + [0] * 31
+BIRTHDAY_HEIGHT = 280000
+CHAIN_TIP = BIRTHDAY_HEIGHT + 100
+
+
+def create_wallet(path):
+    db = ZcashWalletDb.for_path(path, PARAMS)
+    db.initialize(SEED)
+    treestate = ZcashTreeState("test", BIRTHDAY_HEIGHT, "", 0, "000000", "")
+    birthday = ZcashAccountBirthday.from_treestate(treestate, None)
+    return db, db.create_account(SEED, birthday)
+
+
+def check_address(db, account):
+    address = db.get_current_address(account.account_id).encode(PARAMS)
+    decoded = ZcashRecipientAddress.decode(PARAMS, address)
+    assert decoded.encode(PARAMS) == address, "the address does not round trip"
+    return address
+
+
+def check_balance(db, account):
+    db.update_chain_tip(CHAIN_TIP)
+    summary = db.get_wallet_summary(1)
+    assert summary is not None, "the wallet has no summary"
+    balance = summary.account_balance_values()[account.account_id.id]
+    assert balance.total == 0, "the new account has funds"
+
+
+def check_spend(db, account, address):
+    # Proving needs the sapling parameters, which are not shipped with the package.
+    try:
+        prover = ZcashLocalTxProver.with_default_location()
+    except ZcashError:
+        print("Sapling parameters not found, skipping the spend")
+        return
+
+    input_selector = ZcashGreedyInputSelector(
+        ZcashFeeRule.zip317(ZcashZip317FeeRule.standard()),
+        ZcashChangeStrategy.SINGLE_OUTPUT,
+        ZcashDustOutputPolicy(ZcashDustAction.REJECT, None))
+    request = ZcashTransactionRequest.from_uri(PARAMS, "zcash:" + address + "?amount=0.001")
+    try:
+        spend(db, prover, input_selector, account.unified_spending_key, request,
+              ZcashOvkPolicy.SENDER(), 1)
+    except ZcashError:
+        return
+    raise AssertionError("the spend without funds succeeded")
+
+
 if __name__ == '__main__':
     amount = ZcashAmount(100)
     amount.value()
-    print("Python test application successfully executed ✅")
\ No newline at end of file
+
+    with tempfile.TemporaryDirectory() as folder:
+        db, account = create_wallet(os.path.join(folder, "wallet.db"))
+        address = check_address(db, account)
+        check_balance(db, account)
+        check_spend(db, account, address)
+
+    print("Python test application successfully executed ✅")
@@ -0,0 +1,13 @@
+// Type checks the declarations of the package, as an app would use them.
+import { ZcashAmount, ZcashConsensusParameters, ZcashError, ZcashUnifiedSpendingKey } from "uniffi-zcash-react-native";
+
+const amount: ZcashAmount = new ZcashAmount(100n);
+const value: bigint = amount.value();
+
+const params: ZcashConsensusParameters = ZcashConsensusParameters.TestNetwork;
+
+function describeError(error: ZcashError): string {
+  return `${error.variant.tag}: ${error.message}`;
+}
+
+export { value, params, describeError, ZcashUnifiedSpendingKey };
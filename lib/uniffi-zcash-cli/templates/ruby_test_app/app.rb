@@ -1,6 +1,65 @@
+require "tmpdir"
 require "zcash"
 
+PARAMS = Zcash::ZcashConsensusParameters::TEST_NETWORK
+SEED = [1] + [0] * 31
+BIRTHDAY_HEIGHT = 280000
+CHAIN_TIP = BIRTHDAY_HEIGHT + 100
+
+def create_wallet(path)
+  db = Zcash::ZcashWalletDb.for_path(path, PARAMS)
+  db.initialize(SEED)
+  treestate = Zcash::ZcashTreeState.new("test", BIRTHDAY_HEIGHT, "", 0, "000000", "")
+  birthday = Zcash::ZcashAccountBirthday.from_treestate(treestate, nil)
+  [db, db.create_account(SEED, birthday)]
+end
+
+def check_address(db, account)
+  address = db.get_current_address(account.account_id).encode(PARAMS)
+  decoded = Zcash::ZcashRecipientAddress.decode(PARAMS, address)
+  raise "the address does not round trip" unless decoded.encode(PARAMS) == address
+  address
+end
+
+def check_balance(db, account)
+  db.update_chain_tip(CHAIN_TIP)
+  summary = db.get_wallet_summary(1)
+  raise "the wallet has no summary" if summary.nil?
+  balance = summary.account_balance_values[account.account_id.id]
+  raise "the new account has funds" unless balance.total == 0
+end
+
+def check_spend(db, account, address)
+  # Proving needs the sapling parameters, which are not shipped with the package.
+  begin
+    prover = Zcash::ZcashLocalTxProver.with_default_location
+  rescue Zcash::ZcashError::Message
+    print "Sapling parameters not found, skipping the spend\n"
+    return
+  end
+
+  input_selector = Zcash::ZcashGreedyInputSelector.new(
+    Zcash::ZcashFeeRule.zip317(Zcash::ZcashZip317FeeRule.standard),
+    Zcash::ZcashChangeStrategy::SINGLE_OUTPUT,
+    Zcash::ZcashDustOutputPolicy.new(Zcash::ZcashDustAction::REJECT, nil))
+  request = Zcash::ZcashTransactionRequest.from_uri(PARAMS, "zcash:#{address}?amount=0.001")
+  begin
+    Zcash.spend(db, prover, input_selector, account.unified_spending_key, request,
+                Zcash::ZcashOvkPolicy::SENDER.new, 1)
+  rescue Zcash::ZcashError::Message
+    return
+  end
+  raise "the spend without funds succeeded"
+end
+
 amount = Zcash::ZcashAmount.new(100)
 amount.value()
 
-print "Ruby test application successfully executed ✅ \n"
\ No newline at end of file
+Dir.mktmpdir do |folder|
+  db, account = create_wallet(File.join(folder, "wallet.db"))
+  address = check_address(db, account)
+  check_balance(db, account)
+  check_spend(db, account, address)
+end
+
+print "Ruby test application successfully executed ✅ \n"
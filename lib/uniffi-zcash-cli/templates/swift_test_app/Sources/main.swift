@@ -1,8 +1,70 @@
 // The Swift Programming Language
 // https://docs.swift.org/swift-book
 
+import Foundation
 import Zcash
 
+let params = ZcashConsensusParameters.testNetwork
+let seed: [UInt8] = [1] + [UInt8](repeating: 0, count: 31)
+let birthdayHeight: UInt32 = 280000
+let chainTip = birthdayHeight + 100
+
+func createWallet(path: String) throws -> (ZcashWalletDb, TupleAccountIdAndUnifiedSpendingKey) {
+    let db = try ZcashWalletDb.forPath(path: path, params: params)
+    try db.initialize(seed: seed)
+    let treestate = ZcashTreeState(network: "test", height: UInt64(birthdayHeight), hash: "", time: 0, saplingTree: "000000", orchardTree: "")
+    let birthday = try ZcashAccountBirthday.fromTreestate(treestate: treestate, recoverUntil: nil)
+    return (db, try db.createAccount(seed: seed, birthday: birthday))
+}
+
+func checkAddress(db: ZcashWalletDb, account: TupleAccountIdAndUnifiedSpendingKey) throws -> String {
+    let address = try db.getCurrentAddress(aid: account.accountId)!.encode(params: params)
+    let decoded = try ZcashRecipientAddress.decode(params: params, address: address)
+    precondition(decoded.encode(params: params) == address, "the address does not round trip")
+    return address
+}
+
+func checkBalance(db: ZcashWalletDb, account: TupleAccountIdAndUnifiedSpendingKey) throws {
+    try db.updateChainTip(tipHeight: chainTip)
+    guard let summary = try db.getWalletSummary(minConfirmations: 1) else {
+        fatalError("the wallet has no summary")
+    }
+    let balance = summary.accountBalanceValues()[account.accountId.id]!
+    precondition(balance.total == 0, "the new account has funds")
+}
+
+func checkSpend(db: ZcashWalletDb, account: TupleAccountIdAndUnifiedSpendingKey, address: String) throws {
+    // Proving needs the sapling parameters, which are not shipped with the package.
+    let prover: ZcashLocalTxProver
+    do {
+        prover = try ZcashLocalTxProver.withDefaultLocation()
+    } catch ZcashError.Message {
+        print("Sapling parameters not found, skipping the spend")
+        return
+    }
+
+    let inputSelector = ZcashGreedyInputSelector(
+        feeRule: ZcashFeeRule.zip317(feeRule: ZcashZip317FeeRule.standard()),
+        changeStrategy: .singleOutput,
+        dustOutputPolicy: ZcashDustOutputPolicy(action: .reject, dustThreshold: nil))
+    let request = try ZcashTransactionRequest.fromUri(params: params, uri: "zcash:\(address)?amount=0.001")
+    do {
+        _ = try spend(zDbData: db, prover: prover, inputSelector: inputSelector, usk: account.unifiedSpendingKey, request: request, ovkPolicy: .sender, minConfirmations: 1)
+    } catch ZcashError.Message {
+        return
+    }
+    fatalError("the spend without funds succeeded")
+}
+
 let amount = try!  ZcashAmount(amount: 100)
 _ = amount.value()
+
+let folder = FileManager.default.temporaryDirectory.appendingPathComponent(UUID().uuidString)
+try! FileManager.default.createDirectory(at: folder, withIntermediateDirectories: true)
+let (db, account) = try! createWallet(path: folder.appendingPathComponent("wallet.db").path)
+let address = try! checkAddress(db: db, account: account)
+try! checkBalance(db: db, account: account)
+try! checkSpend(db: db, account: account, address: address)
+try? FileManager.default.removeItem(at: folder)
+
 print("Swift test application successfully executed ✅")
@@ -0,0 +1,77 @@
+use std::{fs, path::Path, process::Command};
+
+use uniffi_zcash_cli::{apidiff::api_diff_report, helper::tmp_folder};
+
+const OLD_UDL: &str = r#"
+namespace zcash {
+    u32 version();
+};
+
+interface ZcashWallet {
+    constructor();
+    u64 balance();
+    void sync();
+};
+"#;
+
+const NEW_UDL: &str = r#"
+namespace zcash {
+    u32 version();
+};
+
+interface ZcashWallet {
+    constructor();
+    u64 balance(u32 min_confirmations);
+    string address();
+};
+"#;
+
+#[test]
+fn api_diff_against_the_working_tree() -> anyhow::Result<()> {
+    let root_dir = tmp_folder()?;
+    let report = api_diff_of_edited_repo(&root_dir);
+    fs_extra::dir::remove(&root_dir)?;
+    let report = report?;
+
+    assert!(report.starts_with("## API changes from `HEAD` to `working tree`"));
+    assert!(report.contains("### Removed (1)\n\n- `ZcashWallet.sync()`"));
+    assert!(report.contains(
+        "### Changed (1)\n\n- `ZcashWallet.balance() -> u64` -> `ZcashWallet.balance(u32 min_confirmations) -> u64`"
+    ));
+    assert!(report.contains("### Added (1)\n\n- `ZcashWallet.address() -> string`"));
+    assert!(report.ends_with(
+        "Semver: breaking changes, bump the major version (the minor one while in 0.x).\n"
+    ));
+    Ok(())
+}
+
+/// Commits `OLD_UDL` in a new git repo and then overwrites it with `NEW_UDL`.
+fn api_diff_of_edited_repo(root_dir: &Path) -> anyhow::Result<String> {
+    let udl_dir = root_dir.join("uniffi-zcash/src/udl");
+    fs::create_dir_all(&udl_dir)?;
+    fs::create_dir_all(root_dir.join("uniffi-zcash-test/src/udl"))?;
+    fs::write(udl_dir.join("zcash.udl"), OLD_UDL)?;
+
+    for args in [
+        &["init", "-q"][..],
+        &["add", "-A"],
+        &[
+            "-c",
+            "user.name=test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-qm",
+            "old api",
+        ],
+    ] {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(root_dir)
+            .status()?;
+        anyhow::ensure!(status.success(), "git {} failed", args.join(" "));
+    }
+
+    fs::write(udl_dir.join("zcash.udl"), NEW_UDL)?;
+    api_diff_report(root_dir, "HEAD", None)
+}
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use uniffi_zcash_cli::{harness::check_bindings, PYTHON};
+
+fn root_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_owned()
+}
+
+#[test]
+fn python_bindings() -> anyhow::Result<()> {
+    let root_dir = root_dir();
+    let script = root_dir
+        .join("uniffi-zcash-cli")
+        .join("templates")
+        .join("python_test_app")
+        .join("app.py");
+    check_bindings(&root_dir, PYTHON, &script, &[])
+}
+
+/// The tests of the type stubs generated along with the bindings.
+#[test]
+fn python_type_stubs() -> anyhow::Result<()> {
+    let root_dir = root_dir();
+    let script = root_dir
+        .join("uniffi-zcash-cli")
+        .join("tests")
+        .join("python")
+        .join("type_stubs.py");
+    check_bindings(&root_dir, PYTHON, &script, &[])
+}
+
+/// The tests of the mock prover, which the library only has with the `mock-prover` feature,
+/// so that they are not part of the tests of the generated bindings.
+#[test]
+fn mock_prover_bindings() -> anyhow::Result<()> {
+    let root_dir = root_dir();
+    let script = root_dir
+        .join("uniffi-zcash")
+        .join("tests")
+        .join("python")
+        .join("mock_prover.py");
+    check_bindings(&root_dir, PYTHON, &script, &["mock-prover"])
+}
+
+/// The tests of the testnet faucet client, which the library only has with the `faucet`
+/// feature.
+#[test]
+fn faucet_bindings() -> anyhow::Result<()> {
+    let root_dir = root_dir();
+    let script = root_dir
+        .join("uniffi-zcash")
+        .join("tests")
+        .join("python")
+        .join("faucet.py");
+    check_bindings(&root_dir, PYTHON, &script, &["faucet"])
+}
+
+/// The tests against the mock lightwalletd, which the library only has with the
+/// `mock-lightwalletd` feature.
+#[test]
+fn mock_lightwalletd_bindings() -> anyhow::Result<()> {
+    let root_dir = root_dir();
+    let script = root_dir
+        .join("uniffi-zcash")
+        .join("tests")
+        .join("python")
+        .join("mock_lightwalletd.py");
+    check_bindings(&root_dir, PYTHON, &script, &["mock-lightwalletd"])
+}
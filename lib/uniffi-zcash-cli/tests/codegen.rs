@@ -0,0 +1,170 @@
+use std::fs;
+use std::path::Path;
+
+use uniffi_zcash_cli::{
+    codegen::{
+        component_interface,
+        docs::ApiDocs,
+        domains::{group_by_domain, short_name, Domain},
+        kotlin, parse_udl, ruby,
+        stability::ApiTarget,
+        swift,
+    },
+    helper::tmp_folder,
+};
+
+/// An interface with a bit of everything the generators of the companion files handle.
+const UDL: &str = r#"
+namespace zcash {
+  [Throws=ZcashError]
+  u32 download(string url, ZcashListener? listener);
+  string version();
+};
+
+[Error]
+enum ZcashError { "Unknown" };
+
+dictionary ZcashBalance {
+  u64 total;
+  string? label;
+};
+
+interface ZcashWallet {
+  constructor(string path);
+  [Throws=ZcashError]
+  ZcashBalance balance(u32 min_confirmations);
+  u32 height();
+};
+
+callback interface ZcashListener {
+  void on_progress(u32 height);
+};
+"#;
+
+#[test]
+fn docs_of_the_crate() -> anyhow::Result<()> {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../uniffi-zcash");
+    let ci = component_interface(&crate_dir.join("src/zcash.udl"))?;
+
+    let docs = ApiDocs::collect(&crate_dir.join("src"), &ci)?;
+
+    assert!(docs.get(ApiTarget::Type("ZcashWalletDb")).is_some());
+    assert!(docs
+        .get(ApiTarget::Member("ZcashWalletDb", "prune_history"))
+        .is_some());
+    Ok(())
+}
+
+#[test]
+fn docs_of_items_named_alike_in_other_modules() -> anyhow::Result<()> {
+    let ci = parse_udl("namespace zcash {}; interface ZcashWallet { constructor(); };")?;
+    let work_dir = tmp_folder()?;
+    fs::create_dir(work_dir.join("helpers"))?;
+    fs::write(
+        work_dir.join("lib.rs"),
+        "/// The wallet.\npub struct ZcashWallet;\n",
+    )?;
+    fs::write(
+        work_dir.join("helpers").join("mod.rs"),
+        "/// A wallet of the tests.\npub struct ZcashTestWallet;\n",
+    )?;
+    let docs = ApiDocs::collect(&work_dir, &ci);
+
+    // A helper of the same name as an exported type, in another module.
+    fs::write(
+        work_dir.join("helpers").join("wallet.rs"),
+        "/// Another wallet.\npub struct ZcashWallet;\n",
+    )?;
+    let ambiguous = ApiDocs::collect(&work_dir, &ci);
+
+    fs_extra::dir::remove(&work_dir)?;
+    let docs = docs?;
+    assert_eq!(
+        Some("The wallet."),
+        docs.get(ApiTarget::Type("ZcashWallet"))
+    );
+    assert!(ambiguous.is_err());
+    Ok(())
+}
+
+#[test]
+fn swift_async_overlay() -> anyhow::Result<()> {
+    let overlay = swift::async_overlay(&parse_udl(UDL)?)?;
+
+    // Only the throwing functions and methods get an `async` overload.
+    assert!(overlay.contains(
+        "public func download(url: String, listener: ZcashListener?) async throws -> UInt32 {"
+    ));
+    assert!(!overlay.contains("func version("));
+    assert!(overlay.contains(
+        "extension ZcashWallet {\n    public func balance(minConfirmations: UInt32) async throws -> ZcashBalance {"
+    ));
+    assert!(overlay.contains("try self.balance(minConfirmations: minConfirmations)"));
+    assert!(!overlay.contains("func height("));
+
+    assert!(overlay.contains("public final class ZcashListenerStream: ZcashListener {"));
+    assert!(overlay.contains("case onProgress(height: UInt32)"));
+    assert!(overlay.contains("continuation.yield(.onProgress(height: height))"));
+    Ok(())
+}
+
+#[test]
+fn ruby_signatures() -> anyhow::Result<()> {
+    let rbs = ruby::rbs_signatures(&parse_udl(UDL)?)?;
+
+    assert!(rbs.contains("module Zcash\n"));
+    assert!(rbs.contains("    class Unknown < StandardError\n"));
+    assert!(rbs.contains(
+        "  class ZcashBalance\n    attr_reader total: Integer\n    attr_reader label: String?\n"
+    ));
+    assert!(rbs.contains("    def initialize: (String path) -> void\n"));
+    assert!(rbs.contains("    def balance: (Integer min_confirmations) -> ZcashBalance\n"));
+    assert!(rbs.contains("  def self.version: () -> String\n"));
+    assert!(rbs.ends_with("end\n"));
+    Ok(())
+}
+
+#[test]
+fn domains_of_the_crate() -> anyhow::Result<()> {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../uniffi-zcash");
+    let ci = component_interface(&crate_dir.join("src/zcash.udl"))?;
+
+    let grouped = group_by_domain(&ci, &crate_dir.join("src/udl"))?;
+
+    let domain_of = |name: &str| {
+        grouped
+            .iter()
+            .find(|(_, names)| names.iter().any(|n| n == name))
+            .map(|(domain, _)| *domain)
+    };
+    assert_eq!(Some(Domain::Keys), domain_of("ZcashUnifiedSpendingKey"));
+    assert_eq!(Some(Domain::Wallet), domain_of("ZcashWalletDb"));
+    assert_eq!(Some(Domain::Tx), domain_of("ZcashTransaction"));
+    assert_eq!(Some(Domain::Net), domain_of("ZcashLightwalletdClient"));
+    // The test support types have no UDL file in the crate.
+    assert_eq!(Some(Domain::Core), domain_of("TestSupport"));
+
+    let namespaces = swift::domain_namespaces(&grouped)?;
+    assert!(namespaces.contains("public enum Wallet {\n"));
+    assert!(namespaces.contains("    public typealias WalletDb = ZcashWalletDb\n"));
+    assert!(!namespaces.contains("typealias TestSupport"));
+
+    let packages = kotlin::domain_packages(&ci, &grouped)?;
+    let (_, keys) = packages
+        .iter()
+        .find(|(domain, _)| *domain == Domain::Keys)
+        .unwrap();
+    assert!(keys.contains("package uniffi.zcash.keys\n"));
+    assert!(keys.contains("typealias UnifiedSpendingKey = uniffi.zcash.ZcashUnifiedSpendingKey\n"));
+    Ok(())
+}
+
+#[test]
+fn short_names() {
+    assert_eq!("WalletDb", short_name("ZcashWalletDb"));
+    // The error types keep their prefix, not to shadow those of the languages.
+    assert_eq!("ZcashError", short_name("ZcashError"));
+    assert_eq!("ZcashException", short_name("ZcashException"));
+    assert_eq!("Zcashd", short_name("Zcashd"));
+    assert_eq!("TestSupport", short_name("TestSupport"));
+}
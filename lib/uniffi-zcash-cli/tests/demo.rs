@@ -0,0 +1,46 @@
+use uniffi_zcash::{
+    ZcashAccountId, ZcashConsensusParameters, ZcashMockLightwalletd, ZcashMockPayment,
+    ZcashUnifiedSpendingKey,
+};
+use uniffi_zcash_cli::demo::run_demo;
+
+#[test]
+fn demo_syncs_a_wallet() -> anyhow::Result<()> {
+    let params = ZcashConsensusParameters::TestNetwork;
+    let seed = [1; 32];
+    let address =
+        ZcashUnifiedSpendingKey::from_seed(params, seed.to_vec(), ZcashAccountId { id: 0 })?
+            .sapling()
+            .default_address()
+            .address
+            .encode(params);
+    let payment = || ZcashMockPayment {
+        address: address.clone(),
+        value: 10000,
+    };
+
+    // Upstream cannot scan blocks while the Sapling tree is empty, so the first block
+    // has an output, before the birthday of the wallet, and another one is after it.
+    let lightwalletd = ZcashMockLightwalletd::new(2_200_000)?;
+    lightwalletd.push_block(vec![payment()])?;
+    for height in 2_200_001..2_200_020 {
+        let payments = if height == 2_200_015 {
+            vec![payment()]
+        } else {
+            vec![]
+        };
+        lightwalletd.push_block(payments)?;
+    }
+
+    run_demo(
+        params,
+        Some(&hex::encode(seed)),
+        Some(&lightwalletd.url()),
+        10,
+    )?;
+
+    let requests = lightwalletd.requests();
+    assert!(requests.contains(&"GetTreeState 2200008".to_string()));
+    assert!(requests.contains(&"GetBlockRange 2200009 2200019".to_string()));
+    Ok(())
+}
@@ -0,0 +1,55 @@
+import ast
+import unittest
+
+import zcash
+
+# The tests of the `zcash.pyi` type stubs the CLI generates next to the bindings, which
+# must describe the very module UniFFI generated.
+class TypeStubsTest(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        with open(zcash.__file__[:-len(".py")] + ".pyi") as stubs:
+            cls.stubs = ast.parse(stubs.read())
+
+    def stub_classes(self):
+        return {node.name: node for node in self.stubs.body if isinstance(node, ast.ClassDef)}
+
+    def test_stubs_describe_the_module(self):
+        classes = self.stub_classes()
+        self.assertIn("ZcashWalletDb", classes)
+        for name, node in classes.items():
+            runtime = getattr(zcash, name)
+            for member in node.body:
+                if isinstance(member, (ast.FunctionDef, ast.ClassDef)) and member.name != "__init__":
+                    self.assertTrue(hasattr(runtime, member.name), f"{name}.{member.name}")
+
+        functions = [node.name for node in self.stubs.body if isinstance(node, ast.FunctionDef)]
+        self.assertIn("scan_cached_blocks", functions)
+        for name in functions:
+            self.assertTrue(callable(getattr(zcash, name)), name)
+
+    def test_stub_signatures(self):
+        methods = {node.name: node for node in self.stub_classes()["ZcashWalletDb"].body
+                   if isinstance(node, ast.FunctionDef)}
+        summary = methods["get_wallet_summary"]
+        self.assertEqual(["self", "min_confirmations"], [arg.arg for arg in summary.args.args])
+        self.assertEqual("typing.Optional['ZcashWalletSummary']", ast.unparse(summary.returns))
+
+
+    def test_stability_notes(self):
+        classes = self.stub_classes()
+        standard = next(node for node in classes["ZcashFixedFeeRule"].body
+                        if isinstance(node, ast.FunctionDef) and node.name == "standard")
+        [decorator] = [d for d in standard.decorator_list if isinstance(d, ast.Call)]
+        self.assertEqual("typing_extensions.deprecated", ast.unparse(decorator.func))
+        self.assertIn("ZcashZip317FeeRule.standard()", decorator.args[0].value)
+        self.assertEqual([], classes["ZcashWalletDb"].decorator_list)
+
+        # Experimental APIs are only flagged with a comment, which the syntax tree drops.
+        with open(zcash.__file__[:-len(".py")] + ".pyi") as stubs:
+            lines = stubs.read().splitlines()
+        scan_range = lines.index("class ZcashScanRange:")
+        self.assertTrue(lines[scan_range - 1].startswith("# Experimental: "))
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,18 @@
+use std::fs;
+
+use uniffi_zcash_cli::{helper::tmp_folder, setup::verify_sapling_prover};
+
+#[test]
+fn corrupted_sapling_parameters() -> anyhow::Result<()> {
+    let work_dir = tmp_folder()?;
+    let spend_path = work_dir.join("sapling-spend.params");
+    let output_path = work_dir.join("sapling-output.params");
+    fs::write(&spend_path, [0; 64])?;
+    fs::write(&output_path, [0; 64])?;
+
+    let result = verify_sapling_prover(&spend_path, &output_path);
+
+    fs_extra::dir::remove(&work_dir)?;
+    assert!(result.is_err());
+    Ok(())
+}
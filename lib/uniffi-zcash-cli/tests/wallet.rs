@@ -0,0 +1,46 @@
+use uniffi_zcash::{create_fixture_wallet, FixtureScenario};
+use uniffi_zcash_cli::{
+    helper::tmp_folder,
+    wallet::{audit_wallet, wallet_info},
+};
+
+#[test]
+fn audit_of_a_sound_wallet() -> anyhow::Result<()> {
+    let work_dir = tmp_folder()?;
+    let db_path = work_dir.join("wallet.db");
+    create_fixture_wallet(
+        db_path.to_string_lossy().into_owned(),
+        FixtureScenario::SyncedWithFunds,
+    );
+
+    let result = audit_wallet(&db_path, "test");
+
+    fs_extra::dir::remove(&work_dir)?;
+    result
+}
+
+#[test]
+fn info_of_a_wallet() -> anyhow::Result<()> {
+    let work_dir = tmp_folder()?;
+    let db_path = work_dir.join("wallet.db");
+    let fixture = create_fixture_wallet(
+        db_path.to_string_lossy().into_owned(),
+        FixtureScenario::SyncedWithFunds,
+    );
+
+    let info = wallet_info(&db_path, "test");
+
+    fs_extra::dir::remove(&work_dir)?;
+    let info = info?;
+    assert!(info.contains("Accounts: 1\n"));
+    assert!(info.contains(&format!(
+        "  0: birthday {}, balance ",
+        fixture.birthday_height
+    )));
+    assert!(info.contains(&format!(
+        "Chain tip: {}, fully scanned: {} (0 blocks behind)",
+        fixture.chain_tip_height, fixture.chain_tip_height
+    )));
+    assert!(info.contains("Scan queue: 0 ranges to scan\n"));
+    Ok(())
+}
@@ -0,0 +1,293 @@
+use std::convert::Infallible;
+
+use hdwallet::rand_core::{OsRng, RngCore};
+use orchard::note::{ExtractedNoteCommitment, Nullifier, RandomSeed};
+use orchard::note_encryption::{OrchardDomain, OrchardNoteEncryption};
+use orchard::value::NoteValue as OrchardNoteValue;
+use secrecy::SecretVec;
+use zcash_client_backend::data_api::chain::error::Error;
+use zcash_client_backend::data_api::chain::{scan_cached_blocks, BlockSource};
+use zcash_client_backend::data_api::{AccountBirthday, WalletWrite};
+use zcash_client_backend::keys::UnifiedSpendingKey;
+use zcash_client_backend::proto::compact_formats::{
+    ChainMetadata, CompactBlock, CompactOrchardAction, CompactSaplingOutput, CompactSaplingSpend,
+    CompactTx,
+};
+use zcash_client_backend::proto::service::TreeState;
+use zcash_client_sqlite::wallet::init::init_wallet_db;
+use zcash_client_sqlite::WalletDb;
+use zcash_note_encryption::Domain;
+use zcash_primitives::consensus::{BlockHeight, TestNetwork};
+use zcash_primitives::keys::OutgoingViewingKey;
+use zcash_primitives::memo::MemoBytes;
+use zcash_primitives::sapling::note_encryption::{sapling_note_encryption, SaplingDomain};
+use zcash_primitives::sapling::util::generate_random_rseed;
+use zcash_primitives::sapling::value::NoteValue;
+use zcash_primitives::sapling::{Note, PaymentAddress};
+use zcash_primitives::zip32::Scope;
+
+/// The seed of the account of the fixture wallets.
+const SEED: [u8; 32] = [7; 32];
+/// The birthday of the account, which is the first block the wallet scans.
+const BIRTHDAY_HEIGHT: u32 = 2_200_000;
+/// The time of the birthday block. The next ones are mined every 75 seconds.
+const START_TIME: u32 = 1_700_000_000;
+/// The confirmations the balances of the fixture wallets are given for, which are the
+/// ones the wallet asks for spending by default.
+const MIN_CONFIRMATIONS: u32 = 10;
+/// The value the account receives in the birthday block.
+const FUNDS: u64 = 100_000;
+/// The value the account gets back from spending its funds.
+const CHANGE: u64 = 60_000;
+
+/// The known wallet states the language test suites start from.
+pub enum FixtureScenario {
+    /// An account was created, but no block was scanned.
+    Empty,
+    /// The account received funds, with enough confirmations to spend them.
+    SyncedWithFunds,
+    /// The account spent its funds in the last block, and its change is waiting for
+    /// confirmations.
+    PendingChange,
+}
+
+/// What the fixture wallet is known to hold, for the tests to check against.
+pub struct FixtureWallet {
+    pub seed: Vec<u8>,
+    pub birthday_height: u32,
+    pub chain_tip_height: u32,
+    /// The confirmations the balances below are given for.
+    pub min_confirmations: u32,
+    pub total_balance: u64,
+    pub spendable_balance: u64,
+    pub change_pending_confirmation: u64,
+}
+
+/// Creates a testnet wallet database at `path` in the state of the given scenario, with
+/// a single account. The state is built by scanning made up compact blocks, so the
+/// wallet is the same as one synced from a node, without checking in database files
+/// that go stale on every migration. The file must not exist yet.
+pub fn create_fixture_wallet(path: String, scenario: FixtureScenario) -> FixtureWallet {
+    let mut db = WalletDb::for_path(path, TestNetwork).unwrap();
+    init_wallet_db(&mut db, Some(SecretVec::new(SEED.to_vec()))).unwrap();
+
+    let tree_state = TreeState {
+        network: "test".to_string(),
+        height: (BIRTHDAY_HEIGHT - 1).into(),
+        sapling_tree: "000000".to_string(),
+        ..Default::default()
+    };
+    let birthday = AccountBirthday::from_treestate(tree_state, None)
+        .unwrap_or_else(|_| panic!("invalid birthday tree state"));
+    db.create_account(&SecretVec::new(SEED.to_vec()), birthday)
+        .unwrap();
+
+    let usk = UnifiedSpendingKey::from_seed(&TestNetwork, &SEED, 0.into()).unwrap();
+    let dfvk = usk.sapling().to_diversifiable_full_viewing_key();
+
+    let mut chain = FixtureChain::default();
+    let funds = chain.push_block(
+        None,
+        Some((
+            dfvk.default_address().1,
+            dfvk.to_ovk(Scope::External),
+            FUNDS,
+        )),
+    );
+    for _ in 1..MIN_CONFIRMATIONS {
+        chain.push_block(None, None);
+    }
+    if let FixtureScenario::PendingChange = scenario {
+        // The funds are the first note of the tree.
+        let nf = funds.unwrap().nf(&dfvk.fvk().vk.nk, 0);
+        chain.push_block(
+            Some(nf.0.to_vec()),
+            Some((
+                dfvk.change_address().1,
+                dfvk.to_ovk(Scope::Internal),
+                CHANGE,
+            )),
+        );
+    }
+
+    let chain_tip_height = chain.tip_height();
+    db.update_chain_tip(chain_tip_height.into()).unwrap();
+    let (total_balance, spendable_balance, change_pending_confirmation) = match scenario {
+        FixtureScenario::Empty => (0, 0, 0),
+        FixtureScenario::SyncedWithFunds => (FUNDS, FUNDS, 0),
+        FixtureScenario::PendingChange => (CHANGE, 0, CHANGE),
+    };
+    if !matches!(scenario, FixtureScenario::Empty) {
+        let block_count = chain.blocks.len();
+        scan_cached_blocks(
+            &TestNetwork,
+            &chain,
+            &mut db,
+            BIRTHDAY_HEIGHT.into(),
+            block_count,
+        )
+        .unwrap();
+    }
+
+    FixtureWallet {
+        seed: SEED.to_vec(),
+        birthday_height: BIRTHDAY_HEIGHT,
+        chain_tip_height,
+        min_confirmations: MIN_CONFIRMATIONS,
+        total_balance,
+        spendable_balance,
+        change_pending_confirmation,
+    }
+}
+
+/// Compact blocks made up for the fixture wallets, which are scanned right from memory.
+#[derive(Default)]
+struct FixtureChain {
+    blocks: Vec<CompactBlock>,
+    tree_size: u32,
+}
+
+impl FixtureChain {
+    fn tip_height(&self) -> u32 {
+        BIRTHDAY_HEIGHT + self.blocks.len() as u32 - 1
+    }
+
+    /// Adds a block with a transaction spending the given nullifier and paying the given
+    /// address, if any, returning the note it pays.
+    fn push_block(
+        &mut self,
+        spend: Option<Vec<u8>>,
+        output: Option<(PaymentAddress, OutgoingViewingKey, u64)>,
+    ) -> Option<Note> {
+        let height = BIRTHDAY_HEIGHT + self.blocks.len() as u32;
+        let mut hash = [0; 32];
+        hash[..4].copy_from_slice(&height.to_le_bytes());
+        let prev_hash = self
+            .blocks
+            .last()
+            .map_or_else(|| vec![0; 32], |block| block.hash.clone());
+
+        let (note, output) = match output {
+            Some((to, ovk, value)) => {
+                let (note, output) = fake_sapling_output(to, ovk, height, value);
+                (Some(note), Some(output))
+            }
+            None => (None, None),
+        };
+        let mut vtx = Vec::new();
+        if spend.is_some() || output.is_some() {
+            let mut txid = hash;
+            txid[31] = 1;
+            vtx.push(CompactTx {
+                hash: txid.to_vec(),
+                spends: spend
+                    .into_iter()
+                    .map(|nf| CompactSaplingSpend { nf })
+                    .collect(),
+                outputs: output.into_iter().collect(),
+                ..Default::default()
+            });
+        }
+        self.tree_size += vtx.iter().map(|tx| tx.outputs.len() as u32).sum::<u32>();
+
+        self.blocks.push(CompactBlock {
+            height: height.into(),
+            hash: hash.to_vec(),
+            prev_hash,
+            time: START_TIME + (height - BIRTHDAY_HEIGHT) * 75,
+            vtx,
+            chain_metadata: Some(ChainMetadata {
+                sapling_commitment_tree_size: self.tree_size,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        note
+    }
+}
+
+impl BlockSource for FixtureChain {
+    type Error = Infallible;
+
+    fn with_blocks<F, WalletErrT>(
+        &self,
+        from_height: Option<BlockHeight>,
+        limit: Option<usize>,
+        mut with_row: F,
+    ) -> Result<(), Error<WalletErrT, Self::Error>>
+    where
+        F: FnMut(CompactBlock) -> Result<(), Error<WalletErrT, Self::Error>>,
+    {
+        self.blocks
+            .iter()
+            .filter(|block| from_height.is_none_or(|height| block.height() >= height))
+            .take(limit.unwrap_or(usize::MAX))
+            .try_for_each(|block| with_row(block.clone()))
+    }
+}
+
+/// A compact output paying the given value to the address, along with the note it pays.
+/// Only the wallet can tell it from a real one, as it decrypts it.
+pub fn fake_sapling_output(
+    to: PaymentAddress,
+    ovk: OutgoingViewingKey,
+    height: u32,
+    value: u64,
+) -> (Note, CompactSaplingOutput) {
+    let mut rng = OsRng;
+    let rseed = generate_random_rseed(&TestNetwork, BlockHeight::from_u32(height), &mut rng);
+    let note = Note::from_parts(to, NoteValue::from_raw(value), rseed);
+    let encryptor = sapling_note_encryption::<_, TestNetwork>(
+        Some(ovk),
+        note.clone(),
+        MemoBytes::empty(),
+        &mut rng,
+    );
+
+    let output = CompactSaplingOutput {
+        cmu: note.cmu().to_bytes().to_vec(),
+        ephemeral_key: SaplingDomain::<TestNetwork>::epk_bytes(encryptor.epk())
+            .0
+            .to_vec(),
+        ciphertext: encryptor.encrypt_note_plaintext()[..52].to_vec(),
+    };
+    (note, output)
+}
+
+/// A compact Orchard action paying the given value to the address, like
+/// [`fake_sapling_output`]. The note it spends is made up, so no wallet knows its
+/// nullifier.
+pub fn fake_orchard_action(to: orchard::Address, value: u64) -> CompactOrchardAction {
+    let mut rng = OsRng;
+    let (nf, note) = loop {
+        let mut bytes = [0; 32];
+        rng.fill_bytes(&mut bytes);
+        // Nullifiers are elements of the base field, which are below 2^254.
+        bytes[31] &= 0x3f;
+        let Some(nf) = Option::from(Nullifier::from_bytes(&bytes)) else {
+            continue;
+        };
+        rng.fill_bytes(&mut bytes);
+        let note = Option::from(RandomSeed::from_bytes(bytes, &nf)).and_then(|rseed| {
+            Option::from(orchard::Note::from_parts(
+                to,
+                OrchardNoteValue::from_raw(value),
+                nf,
+                rseed,
+            ))
+        });
+        if let Some(note) = note {
+            break (nf, note);
+        }
+    };
+    let encryptor = OrchardNoteEncryption::new(None, note, [0; 512]);
+
+    CompactOrchardAction {
+        nullifier: nf.to_bytes().to_vec(),
+        cmx: ExtractedNoteCommitment::from(note.commitment())
+            .to_bytes()
+            .to_vec(),
+        ephemeral_key: OrchardDomain::epk_bytes(encryptor.epk()).0.to_vec(),
+        ciphertext: encryptor.encrypt_note_plaintext()[..52].to_vec(),
+    }
+}
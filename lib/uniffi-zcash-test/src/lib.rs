@@ -1,4 +1,5 @@
 #[cfg(feature = "test-data")]
 pub mod test_data;
 
+pub mod fixtures;
 pub mod test_support;
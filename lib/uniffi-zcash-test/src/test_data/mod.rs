@@ -1,6 +1,7 @@
 mod hdwallet;
 mod helper;
 mod orchard;
+mod reorg;
 mod secp256k1;
 mod transaction;
 mod zcash_client_backend;
@@ -12,6 +13,7 @@
 
 use self::hdwallet::write_for_hdwallet;
 use self::orchard::write_for_orchard;
+use self::reorg::write_for_reorg;
 use self::secp256k1::write_for_secp256k1;
 use self::transaction::write_for_transaction;
 use self::zcash_client_backend::write_for_zcash_client_backend;
@@ -47,6 +49,28 @@ pub fn generate_test_data(force: bool) {
     println!("done !");
 }
 
+/// Generates the golden file of the chain reorg tests, apart from the main one so that
+/// it can be regenerated without changing the rest of the test data.
+pub fn generate_reorg_test_data() {
+    let base_url = env!("CARGO_MANIFEST_DIR");
+    let path = format!("{base_url}/../uniffi-zcash/tests/reorg_test_data.csv");
+
+    println!("Generating reorg test data at: {}", base_url);
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(path)
+        .unwrap();
+
+    let mut seed = vec![0u8; 32];
+    seed[0] = 1u8;
+    write_for_reorg(&mut file, &seed);
+
+    println!("done !");
+}
+
 pub(crate) fn format_bytes(label: &str, bytes: &[u8]) -> String {
     let bytes_arr = bytes
         .iter()
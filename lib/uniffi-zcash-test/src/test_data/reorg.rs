@@ -0,0 +1,160 @@
+use std::io::Write;
+
+use prost::Message;
+use zcash_client_backend::keys::UnifiedSpendingKey;
+use zcash_client_backend::proto::compact_formats::{ChainMetadata, CompactBlock, CompactTx};
+use zcash_primitives::consensus::TestNetwork;
+use zcash_primitives::zip32::DiversifiableFullViewingKey;
+
+use super::format_bytes;
+use crate::fixtures::fake_sapling_output;
+
+/// The first block of the fork, right after the birthday of the account.
+const START_HEIGHT: u32 = 2_100_000;
+/// The time of the first block. The next ones are mined every 75 seconds.
+const START_TIME: u32 = 1_700_000_000;
+
+/// A run of blocks of the fork, paying some of them to the account.
+struct Branch {
+    name: &'static str,
+    /// Tells the blocks of each branch apart in their hashes.
+    tag: u8,
+    start_height: u32,
+    len: u32,
+    /// The heights of the blocks paying to the account, and the value they pay.
+    payments: &'static [(u32, u64)],
+}
+
+/// The blocks shared by both chains.
+const COMMON: Branch = Branch {
+    name: "common",
+    tag: 0,
+    start_height: START_HEIGHT,
+    len: 3,
+    payments: &[(START_HEIGHT, 50_000)],
+};
+
+/// The chain the wallet scans first.
+const CHAIN_A: Branch = Branch {
+    name: "chain_a",
+    tag: 0xa,
+    start_height: START_HEIGHT + 3,
+    len: 3,
+    payments: &[(START_HEIGHT + 3, 20_000)],
+};
+
+/// The longer chain replacing the first one after the reorg.
+const CHAIN_B: Branch = Branch {
+    name: "chain_b",
+    tag: 0xb,
+    start_height: START_HEIGHT + 3,
+    len: 4,
+    payments: &[(START_HEIGHT + 4, 30_000)],
+};
+
+/// Writes two competing testnet chains sharing a prefix, as compact blocks paying to the
+/// first account of the seed, along with the balance the account has on each of them.
+/// The wallet scans the first chain, rewinds to the last common block and scans the
+/// second one, which is what the rewind APIs are tested with.
+#[rustfmt::skip]
+pub fn write_for_reorg<W: Write>(mut file: W, seed: &[u8]) {
+    let usk = UnifiedSpendingKey::from_seed(&TestNetwork, seed, 0.into()).unwrap();
+    let dfvk = usk.sapling().to_diversifiable_full_viewing_key();
+
+    writeln!(file, "{}", format_bytes("reorg_seed", seed)).unwrap();
+    writeln!(file, "reorg_birthday_height:{}", START_HEIGHT).unwrap();
+    writeln!(file, "reorg_fork_height:{}", CHAIN_A.start_height - 1).unwrap();
+
+    let common_tip = write_branch(&mut file, &dfvk, &COMMON, [0; 32], 0);
+    write_branch(&mut file, &dfvk, &CHAIN_A, common_tip.0, common_tip.1);
+    write_branch(&mut file, &dfvk, &CHAIN_B, common_tip.0, common_tip.1);
+
+    let balance = |branches: &[&Branch]| -> u64 {
+        branches.iter().flat_map(|branch| branch.payments).map(|(_, value)| value).sum()
+    };
+    writeln!(file, "reorg_balance_fork:{}", balance(&[&COMMON])).unwrap();
+    writeln!(file, "reorg_balance_chain_a:{}", balance(&[&COMMON, &CHAIN_A])).unwrap();
+    writeln!(file, "reorg_balance_chain_b:{}", balance(&[&COMMON, &CHAIN_B])).unwrap();
+}
+
+/// Writes the blocks of a branch on top of the given block hash and note commitment tree
+/// size, returning those of its last block. Next to the serialized blocks go their hashes,
+/// times and output counts, which the block cache metadata needs.
+fn write_branch<W: Write>(
+    mut file: W,
+    dfvk: &DiversifiableFullViewingKey,
+    branch: &Branch,
+    mut prev_hash: [u8; 32],
+    mut tree_size: u32,
+) -> ([u8; 32], u32) {
+    let (mut hashes, mut times, mut outputs) = (Vec::new(), Vec::new(), Vec::new());
+
+    for (index, height) in (branch.start_height..branch.start_height + branch.len).enumerate() {
+        let mut hash = [0; 32];
+        hash[..4].copy_from_slice(&height.to_le_bytes());
+        hash[4] = branch.tag;
+        let time = START_TIME + (height - START_HEIGHT) * 75 + u32::from(branch.tag);
+
+        let vtx = branch
+            .payments
+            .iter()
+            .filter(|(payment_height, _)| *payment_height == height)
+            .map(|(_, value)| {
+                let mut txid = hash;
+                txid[31] = 1;
+                CompactTx {
+                    hash: txid.to_vec(),
+                    outputs: vec![
+                        fake_sapling_output(
+                            dfvk.default_address().1,
+                            dfvk.fvk().ovk,
+                            height,
+                            *value,
+                        )
+                        .1,
+                    ],
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
+        let output_count = vtx.iter().map(|tx| tx.outputs.len() as u32).sum::<u32>();
+        tree_size += output_count;
+
+        let block = CompactBlock {
+            height: height.into(),
+            hash: hash.to_vec(),
+            prev_hash: prev_hash.to_vec(),
+            time,
+            vtx,
+            chain_metadata: Some(ChainMetadata {
+                sapling_commitment_tree_size: tree_size,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let label = format!("reorg_{}_block_{}", branch.name, index);
+        writeln!(file, "{}", format_bytes(&label, &block.encode_to_vec())).unwrap();
+
+        hashes.extend_from_slice(&hash);
+        times.push(time);
+        outputs.push(output_count);
+        prev_hash = hash;
+    }
+
+    writeln!(
+        file,
+        "reorg_{}_start_height:{}",
+        branch.name, branch.start_height
+    )
+    .unwrap();
+    writeln!(
+        file,
+        "{}",
+        format_bytes(&format!("reorg_{}_block_hashes", branch.name), &hashes)
+    )
+    .unwrap();
+    writeln!(file, "reorg_{}_block_times:{:?}", branch.name, times).unwrap();
+    writeln!(file, "reorg_{}_sapling_outputs:{:?}", branch.name, outputs).unwrap();
+
+    (prev_hash, tree_size)
+}
@@ -9,8 +9,17 @@ pub struct TestSupport {
 
 impl TestSupport {
     pub fn from_csv_file() -> Self {
+        Self::from_golden_file("test_data.csv")
+    }
+
+    /// The test data of the chain reorg tests: two competing chains sharing a prefix.
+    pub fn from_reorg_csv_file() -> Self {
+        Self::from_golden_file("reorg_test_data.csv")
+    }
+
+    fn from_golden_file(name: &str) -> Self {
         let base_dir = env!("CARGO_MANIFEST_DIR");
-        let csv_path = format!("{base_dir}/../uniffi-zcash/tests/test_data.csv");
+        let csv_path = format!("{base_dir}/../uniffi-zcash/tests/{name}");
 
         let map: KeyValMap = read_to_string(csv_path)
             .expect("cannot find test data")
@@ -1,15 +1,21 @@
+use std::env;
 use std::fs::{read_to_string, OpenOptions};
 use std::io::Write;
 use std::path::Path;
 
 fn main() {
     println!("cargo:rerun-if-changed=src/udl");
+    println!("cargo:rerun-if-changed={FEATURES_UDL_DIR}");
     println!("cargo:rerun-if-changed=../uniffi-zcash-test/src/udl");
     generate_udl_file();
 
     uniffi::generate_scaffolding("./src/zcash.udl").unwrap();
 }
 
+/// The UDL of the optional features, in files named after them, which is only part of the
+/// interface when the feature is enabled.
+const FEATURES_UDL_DIR: &str = "src/udl-features";
+
 fn generate_udl_file() {
     let mut content =
         "/* Autogenerated by the `build.rs` script - do not modify */\n\n".to_string();
@@ -19,6 +25,16 @@ fn generate_udl_file() {
         "../uniffi-zcash-test/src/udl",
     ]));
 
+    let mut features_udl = Path::new(FEATURES_UDL_DIR)
+        .read_dir()
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| is_udl_file(path) && is_feature_enabled(path))
+        .collect::<Vec<_>>();
+    features_udl.sort();
+    content.push('\n');
+    content.push_str(&join_udl_files(&features_udl));
+
     content.push_str("\n/* vi:set ft=cs syn=cs: */");
 
     let mut file = OpenOptions::new()
@@ -74,3 +90,16 @@ fn is_udl_file<P: AsRef<Path>>(path: P) -> bool {
             .map(|extension| extension == "udl")
             .unwrap_or_default()
 }
+
+fn is_feature_enabled<P: AsRef<Path>>(udl_path: P) -> bool {
+    let feature = udl_path
+        .as_ref()
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    env::var_os(format!(
+        "CARGO_FEATURE_{}",
+        feature.to_uppercase().replace('-', "_")
+    ))
+    .is_some()
+}
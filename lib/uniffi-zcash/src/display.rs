@@ -0,0 +1,215 @@
+//! The strings keys, addresses, txids, amounts and proposals are displayed as, in
+//! `to_display_string` and in the string conversions of the bindings, so that logging
+//! them from foreign code is consistent and never leaks secret material.
+//!
+//! Spending keys are redacted. Viewing keys, which reveal the history of the wallet, are
+//! only displayed as a fingerprint, which tells keys apart without revealing them. The
+//! other values are displayed in full, addresses by their receivers, as they carry no
+//! network to be encoded for.
+
+use std::fmt;
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    format_zec, ZcashAccountPrivKey, ZcashAccountPubKey, ZcashAmount, ZcashConsensusParameters,
+    ZcashDiversifiableFullViewingKey, ZcashExtendedFullViewingKey, ZcashExtendedSpendingKey,
+    ZcashNonNegativeAmount, ZcashOrchardAddress, ZcashOrchardFullViewingKey,
+    ZcashOrchardIncomingViewingKey, ZcashOrchardSpendingKey, ZcashPaymentAddress, ZcashProposal,
+    ZcashTransparentAddress, ZcashTxId, ZcashUnifiedAddress, ZcashUnifiedFullViewingKey,
+    ZcashUnifiedSpendingKey,
+};
+
+/// The first bytes of the SHA-256 hash of the encoding of a key, in hex.
+fn fingerprint(encoding: &[u8]) -> String {
+    hex::encode(&Sha256::digest(encoding)[..4])
+}
+
+fn redacted(f: &mut fmt::Formatter<'_>, type_name: &str) -> fmt::Result {
+    write!(f, "{}(<redacted>)", type_name)
+}
+
+fn fingerprinted(f: &mut fmt::Formatter<'_>, type_name: &str, encoding: &[u8]) -> fmt::Result {
+    write!(f, "{}(fingerprint {})", type_name, fingerprint(encoding))
+}
+
+fn zec(zatoshis: i64) -> String {
+    // Amounts out of range are displayed in zatoshis.
+    match ZcashAmount::new(zatoshis) {
+        Ok(amount) => format!("{} ZEC", format_zec(Arc::new(amount), false)),
+        Err(_) => format!("{} zatoshis", zatoshis),
+    }
+}
+
+impl fmt::Display for ZcashUnifiedSpendingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        redacted(f, "ZcashUnifiedSpendingKey")
+    }
+}
+
+impl fmt::Display for ZcashExtendedSpendingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        redacted(f, "ZcashExtendedSpendingKey")
+    }
+}
+
+impl fmt::Display for ZcashOrchardSpendingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        redacted(f, "ZcashOrchardSpendingKey")
+    }
+}
+
+impl fmt::Display for ZcashAccountPrivKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        redacted(f, "ZcashAccountPrivKey")
+    }
+}
+
+impl fmt::Display for ZcashUnifiedFullViewingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let encoding = self.encode(ZcashConsensusParameters::MainNetwork);
+        fingerprinted(f, "ZcashUnifiedFullViewingKey", encoding.as_bytes())
+    }
+}
+
+impl fmt::Display for ZcashExtendedFullViewingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fingerprinted(f, "ZcashExtendedFullViewingKey", &self.to_bytes())
+    }
+}
+
+impl fmt::Display for ZcashDiversifiableFullViewingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fingerprinted(f, "ZcashDiversifiableFullViewingKey", &self.to_bytes())
+    }
+}
+
+impl fmt::Display for ZcashOrchardFullViewingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fingerprinted(f, "ZcashOrchardFullViewingKey", &self.to_bytes())
+    }
+}
+
+impl fmt::Display for ZcashOrchardIncomingViewingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fingerprinted(f, "ZcashOrchardIncomingViewingKey", &self.to_bytes())
+    }
+}
+
+impl fmt::Display for ZcashAccountPubKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fingerprinted(f, "ZcashAccountPubKey", &self.serialize())
+    }
+}
+
+impl fmt::Display for ZcashTransparentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.is_script() { "p2sh" } else { "p2pkh" };
+        write!(f, "{}:{}", kind, hex::encode(self.to_bytes()))
+    }
+}
+
+impl fmt::Display for ZcashPaymentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sapling:{}", hex::encode(self.to_bytes()))
+    }
+}
+
+impl fmt::Display for ZcashOrchardAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "orchard:{}", hex::encode(self.to_raw_address_bytes()))
+    }
+}
+
+impl fmt::Display for ZcashUnifiedAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let receivers = [
+            self.orchard().map(|address| address.to_string()),
+            self.sapling().map(|address| address.to_string()),
+            self.transparent().map(|address| address.to_string()),
+        ];
+        write!(
+            f,
+            "unified({})",
+            receivers
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl fmt::Display for ZcashTxId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let txid: zcash_primitives::transaction::TxId = (*self).into();
+        write!(f, "{}", txid)
+    }
+}
+
+impl fmt::Display for ZcashAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&zec(self.value()))
+    }
+}
+
+impl fmt::Display for ZcashNonNegativeAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&zec(self.value() as i64))
+    }
+}
+
+impl fmt::Display for ZcashProposal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let payments = self
+            .transaction_request()
+            .map(|request| request.payments().len())
+            .unwrap_or(0);
+        write!(
+            f,
+            "ZcashProposal(account {}, {} payments, {} sapling inputs, fee {}, change {}, target height {})",
+            self.account().id,
+            payments,
+            self.sapling_inputs().len(),
+            zec(self.fee() as i64),
+            zec(self.change().iter().sum::<u64>() as i64),
+            self.min_target_height()
+        )
+    }
+}
+
+macro_rules! display_string {
+    ($($type:ty),*) => {
+        $(
+            impl $type {
+                /// Returns the string the value is displayed as, which never contains
+                /// secret material.
+                pub fn to_display_string(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+display_string!(
+    ZcashUnifiedSpendingKey,
+    ZcashExtendedSpendingKey,
+    ZcashOrchardSpendingKey,
+    ZcashAccountPrivKey,
+    ZcashUnifiedFullViewingKey,
+    ZcashExtendedFullViewingKey,
+    ZcashDiversifiableFullViewingKey,
+    ZcashOrchardFullViewingKey,
+    ZcashOrchardIncomingViewingKey,
+    ZcashAccountPubKey,
+    ZcashTransparentAddress,
+    ZcashPaymentAddress,
+    ZcashOrchardAddress,
+    ZcashUnifiedAddress,
+    ZcashTxId,
+    ZcashAmount,
+    ZcashNonNegativeAmount,
+    ZcashProposal
+);
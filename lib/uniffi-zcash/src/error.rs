@@ -83,10 +83,143 @@ pub enum ZcashError {
     #[error("IO error occurred: {error:?}")]
     IOError { error: std::io::Error },
 
+    #[error("Sprout addresses are not supported, funds must be sent to a newer address")]
+    SproutNotSupported,
+
+    #[error("invalid argument `{param}`: {reason}")]
+    InvalidArgument { param: String, reason: String },
+
+    #[error("spending {value} zatoshis exceeds the `{policy}` limit of {limit}")]
+    PolicyViolation {
+        policy: String,
+        limit: u64,
+        value: u64,
+    },
+
+    #[error("the spend was not approved")]
+    SpendNotApproved,
+
     #[error("unknown error occurred")]
     Unknown,
 }
 
+/// The stable code and key of a [`ZcashError`] variant, so apps can map errors to
+/// localized messages and report them to analytics without parsing the messages,
+/// which are English and may change.
+pub struct ZcashErrorCode {
+    /// The variant name, as declared in the UDL.
+    pub variant: String,
+    pub code: u32,
+    /// A lowercase, snake case key, suitable for localization files.
+    pub key: String,
+}
+
+/// The codes and keys of the [`ZcashError`] variants. They never change nor get reused:
+/// new variants get the next code, and removed ones keep theirs reserved.
+const ERROR_CODES: [(&str, u32, &str); 26] = [
+    ("HDWalletError", 1001, "hdwallet"),
+    ("DerivationError", 1002, "derivation"),
+    ("DecodingError", 1003, "decoding"),
+    ("InvalidAsk", 1004, "invalid_ask"),
+    ("InvalidNsk", 1005, "invalid_nsk"),
+    ("Message", 1006, "message"),
+    ("ArrayLengthMismatch", 1007, "array_length_mismatch"),
+    ("ValueOutOfRange", 1008, "value_out_of_range"),
+    ("Secp256k1Error", 1009, "secp256k1"),
+    ("Bech32DecodeError", 1010, "bech32_decode"),
+    ("Bs58Error", 1011, "base58_decode"),
+    ("BuilderError", 1012, "builder"),
+    ("TransparentBuilderError", 1013, "transparent_builder"),
+    ("SaplingBuilderError", 1014, "sapling_builder"),
+    ("OrchardBuilderError", 1015, "orchard_builder"),
+    ("OrchardBuilderSpendError", 1016, "orchard_builder_spend"),
+    ("OrchardBuilderOutputError", 1017, "orchard_builder_output"),
+    ("InsufficientFundsError", 1018, "insufficient_funds"),
+    ("BalanceError", 1019, "balance"),
+    ("ChangeRequiredError", 1020, "change_required"),
+    ("IOError", 1021, "io"),
+    ("SproutNotSupported", 1022, "sprout_not_supported"),
+    ("Unknown", 1023, "unknown"),
+    ("InvalidArgument", 1024, "invalid_argument"),
+    ("PolicyViolation", 1025, "policy_violation"),
+    ("SpendNotApproved", 1026, "spend_not_approved"),
+];
+
+impl ZcashError {
+    /// The variant name, as declared in the UDL.
+    pub fn variant(&self) -> &'static str {
+        match self {
+            ZcashError::HDWalletError { .. } => "HDWalletError",
+            ZcashError::DerivationError { .. } => "DerivationError",
+            ZcashError::DecodingError { .. } => "DecodingError",
+            ZcashError::InvalidAsk => "InvalidAsk",
+            ZcashError::InvalidNsk => "InvalidNsk",
+            ZcashError::Message { .. } => "Message",
+            ZcashError::ArrayLengthMismatch { .. } => "ArrayLengthMismatch",
+            ZcashError::ValueOutOfRange { .. } => "ValueOutOfRange",
+            ZcashError::Secp256k1Error { .. } => "Secp256k1Error",
+            ZcashError::Bech32DecodeError { .. } => "Bech32DecodeError",
+            ZcashError::Bs58Error { .. } => "Bs58Error",
+            ZcashError::BuilderError { .. } => "BuilderError",
+            ZcashError::TransparentBuilderError { .. } => "TransparentBuilderError",
+            ZcashError::SaplingBuilderError { .. } => "SaplingBuilderError",
+            ZcashError::OrchardBuilderError { .. } => "OrchardBuilderError",
+            ZcashError::OrchardBuilderSpendError { .. } => "OrchardBuilderSpendError",
+            ZcashError::OrchardBuilderOutputError { .. } => "OrchardBuilderOutputError",
+            ZcashError::InsufficientFundsError { .. } => "InsufficientFundsError",
+            ZcashError::BalanceError { .. } => "BalanceError",
+            ZcashError::ChangeRequiredError { .. } => "ChangeRequiredError",
+            ZcashError::IOError { .. } => "IOError",
+            ZcashError::SproutNotSupported => "SproutNotSupported",
+            ZcashError::InvalidArgument { .. } => "InvalidArgument",
+            ZcashError::PolicyViolation { .. } => "PolicyViolation",
+            ZcashError::SpendNotApproved => "SpendNotApproved",
+            ZcashError::Unknown => "Unknown",
+        }
+    }
+
+    pub fn code(&self) -> u32 {
+        self.error_code().1
+    }
+
+    pub fn key(&self) -> &'static str {
+        self.error_code().2
+    }
+
+    fn error_code(&self) -> (&'static str, u32, &'static str) {
+        let variant = self.variant();
+        ERROR_CODES
+            .into_iter()
+            .find(|(name, _, _)| *name == variant)
+            .expect("every variant has a code")
+    }
+}
+
+/// Lists the codes and keys of all the error variants, i.e. for checking the
+/// localization files of an app cover them.
+pub fn error_codes() -> Vec<ZcashErrorCode> {
+    ERROR_CODES.into_iter().map(ZcashErrorCode::from).collect()
+}
+
+/// Looks up the code and key of an error variant, given its UDL name. Python and Swift
+/// keep it for the error subclass or case they throw, while Kotlin ends it in `Exception`.
+pub fn error_code_for_variant(variant: String) -> Option<ZcashErrorCode> {
+    ERROR_CODES
+        .into_iter()
+        .find(|(name, _, _)| *name == variant)
+        .map(ZcashErrorCode::from)
+}
+
+impl From<(&str, u32, &str)> for ZcashErrorCode {
+    fn from((variant, code, key): (&str, u32, &str)) -> Self {
+        ZcashErrorCode {
+            variant: variant.to_string(),
+            code,
+            key: key.to_string(),
+        }
+    }
+}
+
 impl From<hdwallet::error::Error> for ZcashError {
     fn from(error: hdwallet::error::Error) -> Self {
         ZcashError::HDWalletError { error }
@@ -238,3 +371,27 @@ fn from(value: TryFromIntError) -> Self {
         value.to_string().into()
     }
 }
+
+impl From<tonic::transport::Error> for ZcashError {
+    fn from(value: tonic::transport::Error) -> Self {
+        format!("lightwalletd connection error: {}", value).into()
+    }
+}
+
+impl From<tonic::Status> for ZcashError {
+    fn from(value: tonic::Status) -> Self {
+        format!("lightwalletd error: {}", value).into()
+    }
+}
+
+impl From<hyper::Error> for ZcashError {
+    fn from(value: hyper::Error) -> Self {
+        format!("Node connection error: {}", value).into()
+    }
+}
+
+impl From<rusqlite::Error> for ZcashError {
+    fn from(value: rusqlite::Error) -> Self {
+        format!("Wallet database error: {}", value).into()
+    }
+}
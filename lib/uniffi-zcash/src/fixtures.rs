@@ -0,0 +1,11 @@
+pub use uniffi_zcash_test::fixtures::{FixtureScenario, FixtureWallet};
+
+use crate::init_uniffi_tables;
+
+/// Creates a fixture wallet, see [`uniffi_zcash_test::fixtures::create_fixture_wallet`],
+/// along with the tables of this library, as [`crate::ZcashWalletDb::initialize`] does.
+pub fn create_fixture_wallet(path: String, scenario: FixtureScenario) -> FixtureWallet {
+    let fixture = uniffi_zcash_test::fixtures::create_fixture_wallet(path.clone(), scenario);
+    init_uniffi_tables(&path).unwrap();
+    fixture
+}
@@ -14,7 +14,7 @@
 
 impl ZcashJubjubFr {
     pub fn from_bytes(data: &[u8]) -> ZcashResult<Self> {
-        let casted_data = cast_slice(data)?;
+        let casted_data = cast_slice("data", data)?;
         let opt: Option<Fr> = Fr::from_bytes(&casted_data).into();
         match opt {
             Some(fr) => Ok(fr.into()),
@@ -4,6 +4,9 @@
 mod payment;
 pub use self::payment::*;
 
+mod runtime_config;
+pub use self::runtime_config::*;
+
 mod hdwallet;
 pub use self::hdwallet::*;
 
@@ -13,6 +16,9 @@
 mod orchard;
 pub use self::orchard::*;
 
+mod zcash_address;
+pub use self::zcash_address::*;
+
 mod zcash_client_backend;
 pub use self::zcash_client_backend::*;
 
@@ -31,9 +37,13 @@
 mod jubjub;
 pub use self::jubjub::*;
 
+mod display;
+mod fixtures;
 mod native_utils;
 mod utils;
+mod validation;
 
+pub use self::fixtures::*;
 pub use uniffi_zcash_test::test_support::*;
 
 #[cfg(feature = "rustler")]
@@ -23,19 +23,15 @@
 
 use crate::native_utils as utils;
 use crate::{
-    decrypt_and_store_transaction, scan_cached_blocks, shield_transparent_funds_main_fixed,
-    shield_transparent_funds_main_zip317, shield_transparent_funds_test_fixed,
-    shield_transparent_funds_test_zip317, spend_main_fixed, spend_main_zip317, spend_test_fixed,
-    spend_test_zip317, TupleTargetAndAnchorHeight, ZcashAccountId, ZcashAmount, ZcashBlockHeight,
-    ZcashBlockMeta, ZcashConsensusParameters, ZcashDustOutputPolicy, ZcashError, ZcashFixedFeeRule,
-    ZcashFixedSingleOutputChangeStrategy, ZcashFsBlockDb, ZcashKeysEra, ZcashLocalTxProver,
-    ZcashMainFixedGreedyInputSelector, ZcashMainZip317GreedyInputSelector, ZcashMemo,
-    ZcashMemoBytes, ZcashNonNegativeAmount, ZcashNoteId, ZcashOutPoint, ZcashOvkPolicy,
+    decrypt_and_store_transaction, scan_cached_blocks, shield_transparent_funds, spend,
+    TupleTargetAndAnchorHeight, ZcashAccountId, ZcashAmount, ZcashBlockHeight, ZcashBlockMeta,
+    ZcashChangeStrategy, ZcashConsensusParameters, ZcashDustOutputPolicy, ZcashError, ZcashFeeRule,
+    ZcashFixedFeeRule, ZcashFsBlockDb, ZcashGreedyInputSelector, ZcashKeysEra, ZcashLocalTxProver,
+    ZcashMemo, ZcashMemoBytes, ZcashNonNegativeAmount, ZcashNoteId, ZcashOutPoint, ZcashOvkPolicy,
     ZcashPayment, ZcashRecipientAddress, ZcashResult, ZcashScanRange, ZcashScript,
-    ZcashShieldedProtocol, ZcashTestFixedGreedyInputSelector, ZcashTestZip317GreedyInputSelector,
-    ZcashTransaction, ZcashTransactionRequest, ZcashTransparentAddress, ZcashTxId, ZcashTxOut,
-    ZcashUnifiedAddress, ZcashUnifiedSpendingKey, ZcashWalletDb, ZcashWalletTransparentOutput,
-    ZcashZip317FeeRule, ZcashZip317SingleOutputChangeStrategy,
+    ZcashShieldedProtocol, ZcashTransaction, ZcashTransactionRequest, ZcashTransparentAddress,
+    ZcashTxId, ZcashTxOut, ZcashUnifiedAddress, ZcashUnifiedSpendingKey, ZcashWalletDb,
+    ZcashWalletTransparentOutput, ZcashZip317FeeRule,
 };
 
 const ANCHOR_OFFSET: u32 = 10;
@@ -536,98 +532,23 @@ pub fn create_to_address(
         error: format!("Error creating transaction request: {:?}", e),
     })?;
 
-    let fixed_rule = ZcashFixedFeeRule::standard().into();
-    let zip317_rule = ZcashZip317FeeRule::standard().into();
-    let dust_policy = ZcashDustOutputPolicy::default().into();
-
-    match params {
-        ZcashConsensusParameters::MainNetwork => {
-            if use_zip317_fees {
-                let insel = ZcashMainZip317GreedyInputSelector::new(
-                    ZcashZip317SingleOutputChangeStrategy::new(zip317_rule).into(),
-                    dust_policy,
-                );
-
-                spend_main_zip317(
-                    Arc::new(db_data),
-                    params,
-                    Arc::new(prover),
-                    Arc::new(insel),
-                    Arc::new(usk),
-                    Arc::new(request),
-                    ZcashOvkPolicy::Sender,
-                    ANCHOR_OFFSET,
-                )
-                .map(|x| *x)
-                .map_err(|e| ZcashError::Message {
-                    error: format!("Error while creating transaction: {}", e),
-                })
-            } else {
-                let insel = ZcashMainFixedGreedyInputSelector::new(
-                    ZcashFixedSingleOutputChangeStrategy::new(fixed_rule).into(),
-                    dust_policy,
-                );
-
-                spend_main_fixed(
-                    Arc::new(db_data),
-                    params,
-                    Arc::new(prover),
-                    Arc::new(insel),
-                    Arc::new(usk),
-                    Arc::new(request),
-                    ZcashOvkPolicy::Sender,
-                    ANCHOR_OFFSET,
-                )
-                .map(|x| *x)
-                .map_err(|e| ZcashError::Message {
-                    error: format!("Error while creating transaction: {}", e),
-                })
-            }
-        }
-        ZcashConsensusParameters::TestNetwork => {
-            if use_zip317_fees {
-                let insel = ZcashTestZip317GreedyInputSelector::new(
-                    ZcashZip317SingleOutputChangeStrategy::new(zip317_rule).into(),
-                    dust_policy,
-                );
-
-                spend_test_zip317(
-                    Arc::new(db_data),
-                    params,
-                    Arc::new(prover),
-                    Arc::new(insel),
-                    Arc::new(usk),
-                    Arc::new(request),
-                    ZcashOvkPolicy::Sender,
-                    ANCHOR_OFFSET,
-                )
-                .map(|x| *x)
-                .map_err(|e| ZcashError::Message {
-                    error: format!("Error while creating transaction: {}", e),
-                })
-            } else {
-                let insel = ZcashTestFixedGreedyInputSelector::new(
-                    ZcashFixedSingleOutputChangeStrategy::new(fixed_rule).into(),
-                    dust_policy,
-                );
-
-                spend_test_fixed(
-                    Arc::new(db_data),
-                    params,
-                    Arc::new(prover),
-                    Arc::new(insel),
-                    Arc::new(usk),
-                    Arc::new(request),
-                    ZcashOvkPolicy::Sender,
-                    ANCHOR_OFFSET,
-                )
-                .map(|x| *x)
-                .map_err(|e| ZcashError::Message {
-                    error: format!("Error while creating transaction: {}", e),
-                })
-            }
-        }
-    }
+    spend(
+        Arc::new(db_data),
+        Arc::new(prover),
+        Arc::new(input_selector(use_zip317_fees)),
+        Arc::new(usk),
+        Arc::new(request),
+        ZcashOvkPolicy::Sender,
+        ANCHOR_OFFSET,
+        false,
+        None,
+        None,
+        None,
+    )
+    .map(|x| *x)
+    .map_err(|e| ZcashError::Message {
+        error: format!("Error while creating transaction: {}", e),
+    })
 }
 
 pub fn shield_to_address(
@@ -659,7 +580,9 @@ pub fn shield_to_address(
         })
         .and_then(|opt_anchor| {
             opt_anchor
-                .map(|TupleTargetAndAnchorHeight { anchor_height, .. }| anchor_height)
+                .map(|TupleTargetAndAnchorHeight { anchor_height, .. }| {
+                    Arc::new(ZcashBlockHeight::new(anchor_height))
+                })
                 .ok_or(ZcashError::Message {
                     error: "Anchor height not available; scan required.".to_string(),
                 })
@@ -674,8 +597,11 @@ pub fn shield_to_address(
                     ),
                 })
         })?
-        .into_keys()
-        .map(|x| ZcashTransparentAddress::decode(params, &x).expect("should decode without issues"))
+        .into_iter()
+        .map(|x| {
+            ZcashTransparentAddress::decode(params, &x.address)
+                .expect("should decode without issues")
+        })
         .map(Arc::new)
         .collect();
 
@@ -686,94 +612,36 @@ pub fn shield_to_address(
 
     let shielding_threshold = 100000;
 
-    let fixed_rule = ZcashFixedFeeRule::standard().into();
-    let fixed_strategy = ZcashFixedSingleOutputChangeStrategy::new(fixed_rule).into();
-
-    let zip317_rule = ZcashZip317FeeRule::standard().into();
-    let zip317_strategy = ZcashZip317SingleOutputChangeStrategy::new(zip317_rule).into();
-
-    let do_policy = ZcashDustOutputPolicy::default().into();
-
-    match params {
-        ZcashConsensusParameters::MainNetwork => {
-            if use_zip317_fees {
-                let insel = ZcashMainZip317GreedyInputSelector::new(zip317_strategy, do_policy);
-
-                shield_transparent_funds_main_zip317(
-                    Arc::new(db_data),
-                    params,
-                    Arc::new(prover),
-                    Arc::new(insel),
-                    shielding_threshold,
-                    Arc::new(usk),
-                    from_addrs,
-                    Arc::new(memo),
-                    min_confirmations,
-                )
-                .map(|x| *x)
-                .map_err(|e| ZcashError::Message {
-                    error: format!("Error while creating transaction: {}", e),
-                })
-            } else {
-                let insel = ZcashMainFixedGreedyInputSelector::new(fixed_strategy, do_policy);
-
-                shield_transparent_funds_main_fixed(
-                    Arc::new(db_data),
-                    params,
-                    Arc::new(prover),
-                    Arc::new(insel),
-                    shielding_threshold,
-                    Arc::new(usk),
-                    from_addrs,
-                    Arc::new(memo),
-                    min_confirmations,
-                )
-                .map(|x| *x)
-                .map_err(|e| ZcashError::Message {
-                    error: format!("Error while creating transaction: {}", e),
-                })
-            }
-        }
-        ZcashConsensusParameters::TestNetwork => {
-            if use_zip317_fees {
-                let insel = ZcashTestZip317GreedyInputSelector::new(zip317_strategy, do_policy);
-
-                shield_transparent_funds_test_zip317(
-                    Arc::new(db_data),
-                    params,
-                    Arc::new(prover),
-                    Arc::new(insel),
-                    shielding_threshold,
-                    Arc::new(usk),
-                    from_addrs,
-                    Arc::new(memo),
-                    min_confirmations,
-                )
-                .map(|x| *x)
-                .map_err(|e| ZcashError::Message {
-                    error: format!("Error while creating transaction: {}", e),
-                })
-            } else {
-                let insel = ZcashTestFixedGreedyInputSelector::new(fixed_strategy, do_policy);
-
-                shield_transparent_funds_test_fixed(
-                    Arc::new(db_data),
-                    params,
-                    Arc::new(prover),
-                    Arc::new(insel),
-                    shielding_threshold,
-                    Arc::new(usk),
-                    from_addrs,
-                    Arc::new(memo),
-                    min_confirmations,
-                )
-                .map(|x| *x)
-                .map_err(|e| ZcashError::Message {
-                    error: format!("Error while creating transaction: {}", e),
-                })
-            }
-        }
-    }
+    shield_transparent_funds(
+        Arc::new(db_data),
+        Arc::new(prover),
+        Arc::new(input_selector(use_zip317_fees)),
+        shielding_threshold,
+        Arc::new(usk),
+        from_addrs,
+        Arc::new(memo),
+        min_confirmations,
+        None,
+    )
+    .map(|x| *x)
+    .map_err(|e| ZcashError::Message {
+        error: format!("Error while creating transaction: {}", e),
+    })
+}
+
+/// The input selector of the standard ZIP 317 or fixed fees, along with the default dust
+/// output policy.
+fn input_selector(use_zip317_fees: bool) -> ZcashGreedyInputSelector {
+    let fee_rule = if use_zip317_fees {
+        ZcashFeeRule::zip317(ZcashZip317FeeRule::standard().into())
+    } else {
+        ZcashFeeRule::fixed(ZcashFixedFeeRule::standard().into())
+    };
+    ZcashGreedyInputSelector::new(
+        fee_rule.into(),
+        ZcashChangeStrategy::SingleOutput,
+        ZcashDustOutputPolicy::default().into(),
+    )
 }
 
 fn decode_usk(zusk: ZcashUnifiedSpendingKey) -> ZcashResult<ZcashUnifiedSpendingKey> {
@@ -854,7 +722,9 @@ fn get_transparent_balance(
         })
         .and_then(|opt_anchor| {
             opt_anchor
-                .map(|TupleTargetAndAnchorHeight { anchor_height, .. }| anchor_height)
+                .map(|TupleTargetAndAnchorHeight { anchor_height, .. }| {
+                    Arc::new(ZcashBlockHeight::new(anchor_height))
+                })
                 .ok_or(ZcashError::Message {
                     error: "Anchor height not available; scan required.".to_string(),
                 })
@@ -1116,8 +986,8 @@ pub fn list_transparent_receivers(
     match db_data.get_transparent_receivers(account) {
         Ok(receivers) => {
             let transparent_receivers = receivers
-                .keys()
-                .map(|x| ZcashTransparentAddress::decode(params, x).unwrap())
+                .iter()
+                .map(|x| ZcashTransparentAddress::decode(params, &x.address).unwrap())
                 .collect();
             // let taddr = match taddr {
             //     TransparentAddress::PublicKey(data) => {
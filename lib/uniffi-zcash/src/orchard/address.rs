@@ -1,3 +1,4 @@
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use orchard::Address;
@@ -7,15 +8,21 @@
 use derive_more::{From, Into};
 
 /// A shielded payment address.
-#[derive(From, Into)]
+#[derive(PartialEq, Eq, From, Into)]
 pub struct ZcashOrchardAddress(pub Address);
 
+impl Hash for ZcashOrchardAddress {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_raw_address_bytes().hash(state)
+    }
+}
+
 impl ZcashOrchardAddress {
     /// Parse an address from its "raw" encoding as specified in [Zcash Protocol Spec § 5.6.4.2: Orchard Raw Payment Addresses][orchardpaymentaddrencoding]
     ///
     /// [orchardpaymentaddrencoding]: https://zips.z.cash/protocol/protocol.pdf#orchardpaymentaddrencoding
     pub fn from_raw_address_bytes(bytes: Vec<u8>) -> ZcashResult<Self> {
-        let array = utils::cast_slice(&bytes)?;
+        let array = utils::cast_slice("bytes", &bytes)?;
         let address: Option<_> = Address::from_raw_address_bytes(&array).into();
         Ok(ZcashOrchardAddress(address.ok_or::<ZcashError>(
             "unable to parse address".to_string().into(),
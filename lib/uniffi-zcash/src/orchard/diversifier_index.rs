@@ -6,7 +6,7 @@
 
 impl ZcashOrchardDiversifierIndex {
     pub fn from_bytes(b: Vec<u8>) -> ZcashResult<Self> {
-        let array = utils::cast_slice(&b)?;
+        let array = utils::cast_slice("b", &b)?;
         Ok(ZcashOrchardDiversifierIndex(array.into()))
     }
 
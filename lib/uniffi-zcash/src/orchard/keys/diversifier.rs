@@ -8,7 +8,7 @@
 
 impl ZcashOrchardDiversifier {
     pub fn from_bytes(data: Vec<u8>) -> ZcashResult<Self> {
-        let array = utils::cast_slice(&data)?;
+        let array = utils::cast_slice("data", &data)?;
         Ok(Diversifier::from_bytes(array).into())
     }
 
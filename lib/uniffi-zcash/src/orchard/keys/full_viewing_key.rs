@@ -1,11 +1,12 @@
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use orchard::keys::{DiversifierIndex, FullViewingKey};
 
 use crate::{
-    utils, ZcashError, ZcashOrchardAddress, ZcashOrchardDiversifier, ZcashOrchardDiversifierIndex,
-    ZcashOrchardIncomingViewingKey, ZcashOrchardOutgoingViewingKey, ZcashOrchardScope,
-    ZcashOrchardSpendingKey, ZcashResult,
+    utils, validation::invalid_argument, ZcashOrchardAddress, ZcashOrchardDiversifier,
+    ZcashOrchardDiversifierIndex, ZcashOrchardIncomingViewingKey, ZcashOrchardOutgoingViewingKey,
+    ZcashOrchardScope, ZcashOrchardSpendingKey, ZcashResult,
 };
 
 use derive_more::{From, Into};
@@ -14,9 +15,15 @@
 ///
 /// This key is useful anywhere you need to maintain accurate balance, but do not want the
 /// ability to spend funds (such as a view-only wallet).
-#[derive(From, Into)]
+#[derive(PartialEq, Eq, From, Into)]
 pub struct ZcashOrchardFullViewingKey(FullViewingKey);
 
+impl Hash for ZcashOrchardFullViewingKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bytes().hash(state)
+    }
+}
+
 impl ZcashOrchardFullViewingKey {
     /// Returns the payment address for this key at the given index.
     pub fn address_at(
@@ -59,8 +66,9 @@ pub fn to_bytes(&self) -> Vec<u8> {
     ///
     /// [orchardrawfullviewingkeys]: https://zips.z.cash/protocol/protocol.pdf#orchardfullviewingkeyencoding
     pub fn from_bytes(bytes: Vec<u8>) -> ZcashResult<Self> {
-        let array = utils::cast_slice(&bytes)?;
-        let fvk = FullViewingKey::from_bytes(&array).ok_or(ZcashError::Unknown)?;
+        let array = utils::cast_slice("bytes", &bytes)?;
+        let fvk = FullViewingKey::from_bytes(&array)
+            .ok_or_else(|| invalid_argument("bytes", "not an Orchard full viewing key"))?;
         Ok(ZcashOrchardFullViewingKey(fvk))
     }
 
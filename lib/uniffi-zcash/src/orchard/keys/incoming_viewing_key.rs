@@ -1,10 +1,11 @@
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use orchard::keys::{DiversifierIndex, IncomingViewingKey};
 
 use crate::{
-    utils, ZcashError, ZcashOrchardAddress, ZcashOrchardDiversifier, ZcashOrchardDiversifierIndex,
-    ZcashResult,
+    utils, validation::invalid_argument, ZcashOrchardAddress, ZcashOrchardDiversifier,
+    ZcashOrchardDiversifierIndex, ZcashResult,
 };
 use derive_more::{From, Into};
 
@@ -13,9 +14,15 @@
 ///
 /// This key is useful in situations where you only need the capability to detect inbound
 /// payments, such as merchant terminals.
-#[derive(From, Into)]
+#[derive(PartialEq, Eq, From, Into)]
 pub struct ZcashOrchardIncomingViewingKey(pub(crate) IncomingViewingKey);
 
+impl Hash for ZcashOrchardIncomingViewingKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bytes().hash(state)
+    }
+}
+
 impl ZcashOrchardIncomingViewingKey {
     /// Serializes an Orchard incoming viewing key to its raw encoding as specified in [Zcash Protocol Spec § 5.6.4.3: Orchard Raw Incoming Viewing Keys][orchardrawinviewingkeys]
     ///
@@ -26,11 +33,11 @@ pub fn to_bytes(&self) -> Vec<u8> {
 
     /// Parses an Orchard incoming viewing key from its raw encoding.
     pub fn from_bytes(bytes: Vec<u8>) -> ZcashResult<Self> {
-        let array = utils::cast_slice(&bytes)?;
+        let array = utils::cast_slice("bytes", &bytes)?;
         let key: Option<_> = IncomingViewingKey::from_bytes(&array).into();
-        Ok(ZcashOrchardIncomingViewingKey(
-            key.ok_or(ZcashError::Unknown)?,
-        ))
+        Ok(ZcashOrchardIncomingViewingKey(key.ok_or_else(|| {
+            invalid_argument("bytes", "not an Orchard incoming viewing key")
+        })?))
     }
 
     /// Checks whether the given address was derived from this incoming viewing
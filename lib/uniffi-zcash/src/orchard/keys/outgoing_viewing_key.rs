@@ -20,7 +20,7 @@ fn from(value: &ZcashOrchardOutgoingViewingKey) -> Self {
 
 impl ZcashOrchardOutgoingViewingKey {
     pub fn from_bytes(bytes: &[u8]) -> ZcashResult<Self> {
-        let array = utils::cast_slice(bytes)?;
+        let array = utils::cast_slice("bytes", bytes)?;
         Ok(OutgoingViewingKey::from(array).into())
     }
 
@@ -2,14 +2,18 @@
 
 use orchard::keys::SpendingKey;
 
-use crate::{utils::cast_slice, ZcashError, ZcashOrchardFullViewingKey, ZcashResult};
+use crate::{
+    utils::cast_slice,
+    validation::{check_account, check_seed},
+    ZcashError, ZcashOrchardFullViewingKey, ZcashResult,
+};
 
 pub struct ZcashOrchardSpendingKey(pub(crate) SpendingKey);
 
 impl ZcashOrchardSpendingKey {
     /// Constructs an Orchard spending key from uniformly-random bytes.
     pub fn from_bytes(data: Vec<u8>) -> ZcashResult<Self> {
-        let slice = cast_slice(&data)?;
+        let slice = cast_slice("data", &data)?;
         let sk = SpendingKey::from_bytes(slice);
         if sk.is_none().into() {
             Err("Cannot generate key - invalid input".to_string().into())
@@ -29,6 +33,8 @@ pub fn to_fvk(&self) -> Arc<ZcashOrchardFullViewingKey> {
 
     /// Derives the Orchard spending key for the given seed, coin type, and account.
     pub fn from_zip32_seed(seed: Vec<u8>, coin_type: u32, account: u32) -> ZcashResult<Self> {
+        check_seed("seed", &seed)?;
+        check_account("account", account)?;
         let key = SpendingKey::from_zip32_seed(seed.as_slice(), coin_type, account)
             .map_err(ZcashError::from)?;
         Ok(key.into())
@@ -26,7 +26,7 @@ fn from(value: &ZcashOrchardNoteCommitment) -> Self {
 
 impl ZcashExtractedNoteCommitment {
     pub fn from_bytes(data: &[u8]) -> ZcashResult<Self> {
-        let casted_data = cast_slice(data)?;
+        let casted_data = cast_slice("data", data)?;
         let opt: Option<ExtractedNoteCommitment> =
             ExtractedNoteCommitment::from_bytes(&casted_data).into();
         match opt {
@@ -79,7 +79,7 @@ fn from(value: &ZcashOrchardNote) -> Self {
 impl ZcashOrchardNullifier {
     /// Deserialize the nullifier from a byte array.
     pub fn from_bytes(bytes: &[u8]) -> ZcashResult<Self> {
-        let opt: Option<Nullifier> = Nullifier::from_bytes(&cast_slice(bytes)?).into();
+        let opt: Option<Nullifier> = Nullifier::from_bytes(&cast_slice("bytes", bytes)?).into();
         match opt {
             Some(nullifier) => Ok(nullifier.into()),
             None => Err("Cannot parse from bytes".into()),
@@ -119,7 +119,7 @@ impl ZcashOrchardRandomSeed {
     /// Returns `None` if the nullifier is not for the same note as the seed.
     pub fn from_bytes(bytes: &[u8], rho: Arc<ZcashOrchardNullifier>) -> ZcashResult<Self> {
         let opt: Option<RandomSeed> =
-            RandomSeed::from_bytes(cast_slice(bytes)?, &rho.as_ref().into()).into();
+            RandomSeed::from_bytes(cast_slice("bytes", bytes)?, &rho.as_ref().into()).into();
         match opt {
             Some(rseed) => Ok(rseed.into()),
             None => Err("Cannot parse from bytes".into()),
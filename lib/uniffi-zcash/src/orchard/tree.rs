@@ -14,7 +14,7 @@
 
 impl ZcashAnchor {
     pub fn from_bytes(bytes: &[u8]) -> ZcashResult<Self> {
-        let opt: Option<Anchor> = Anchor::from_bytes(cast_slice(bytes)?).into();
+        let opt: Option<Anchor> = Anchor::from_bytes(cast_slice("bytes", bytes)?).into();
         match opt {
             Some(anchor) => Ok(anchor.into()),
             None => Err("Error parsing bytes".into()),
@@ -50,7 +50,8 @@ pub fn from_parts(
         auth_path: Vec<Arc<ZcashOrchardMerkleHash>>,
     ) -> ZcashResult<Self> {
         let inner_auth_type: Vec<MerkleHashOrchard> = auth_path.iter().map(|v| v.0).collect();
-        let casted_auth_path: [MerkleHashOrchard; 32] = cast_slice(inner_auth_type.as_slice())?;
+        let casted_auth_path: [MerkleHashOrchard; 32] =
+            cast_slice("auth_path", inner_auth_type.as_slice())?;
         let inner = MerklePath::from_parts(position, casted_auth_path);
         Ok(ZcashOrchardMerklePath {
             inner,
@@ -86,7 +87,7 @@ fn from(value: &ZcashOrchardMerklePath) -> Self {
 impl ZcashOrchardMerkleHash {
     pub fn from_bytes(data: &[u8]) -> ZcashResult<Self> {
         let opt: Option<MerkleHashOrchard> =
-            MerkleHashOrchard::from_bytes(&cast_slice(data)?).into();
+            MerkleHashOrchard::from_bytes(&cast_slice("data", data)?).into();
 
         match opt {
             Some(merkle_hash) => Ok(merkle_hash.into()),
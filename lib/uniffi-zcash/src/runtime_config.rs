@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use zcash_proofs::default_params_folder;
+
+use crate::{validation::invalid_argument, ZcashError, ZcashResult};
+
+/// The directory set by [`ZcashRuntimeConfig::set_cache_dir`], if any.
+static CACHE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// The configuration of the library shared by the whole process, for the environments
+/// where its defaults do not work.
+///
+/// All the instances share the same configuration, so that it can be set once at
+/// startup, before creating the provers and block caches using it.
+pub struct ZcashRuntimeConfig;
+
+impl ZcashRuntimeConfig {
+    pub fn new() -> Self {
+        ZcashRuntimeConfig
+    }
+
+    /// Sets the directory the library keeps its files in by default, creating it if
+    /// needed. Sandboxed apps, like mobile ones, cannot write to the per OS locations
+    /// `zcash_proofs` assumes, but to their own directories only.
+    ///
+    /// When set, the sapling parameters are looked up in its `zcash-params` subdirectory,
+    /// and the block caches opened with `ZcashFsBlockDb::with_default_location` are kept in
+    /// its `blocks` one.
+    pub fn set_cache_dir(&self, path: String) -> ZcashResult<()> {
+        if path.is_empty() {
+            return Err(invalid_argument("path", "must not be empty"));
+        }
+        let path = PathBuf::from(path);
+        fs::create_dir_all(&path)?;
+        *CACHE_DIR.write().unwrap() = Some(path);
+        Ok(())
+    }
+
+    /// Returns the directory set with [`Self::set_cache_dir`], if any.
+    pub fn cache_dir(&self) -> Option<String> {
+        cache_dir().map(|path| path.to_string_lossy().into_owned())
+    }
+
+    /// Returns the directory the sapling parameters are looked up in: the `zcash-params`
+    /// subdirectory of the cache directory if set, the default one of `zcash_proofs`
+    /// otherwise.
+    pub fn params_dir(&self) -> Option<String> {
+        params_dir().map(|path| path.to_string_lossy().into_owned())
+    }
+}
+
+impl Default for ZcashRuntimeConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) fn cache_dir() -> Option<PathBuf> {
+    CACHE_DIR.read().unwrap().clone()
+}
+
+pub(crate) fn params_dir() -> Option<PathBuf> {
+    match cache_dir() {
+        Some(dir) => Some(dir.join("zcash-params")),
+        None => default_params_folder(),
+    }
+}
+
+/// The directory the block caches are kept in by default.
+pub(crate) fn blocks_dir() -> ZcashResult<PathBuf> {
+    cache_dir()
+        .map(|dir| dir.join("blocks"))
+        .ok_or_else(|| ZcashError::Message {
+            error: "No cache directory is set, see ZcashRuntimeConfig".to_string(),
+        })
+}
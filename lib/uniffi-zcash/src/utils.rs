@@ -1,12 +1,13 @@
-/// Try to cast slice to fixed length array. Return Zcash Error on failure
-pub(crate) fn cast_slice<T, const N: usize>(slice: &[T]) -> crate::ZcashResult<[T; N]>
+/// Try to cast slice to fixed length array. Return an invalid argument error naming the
+/// `param` the slice was given as on failure.
+pub(crate) fn cast_slice<T, const N: usize>(param: &str, slice: &[T]) -> crate::ZcashResult<[T; N]>
 where
     T: Copy,
 {
-    slice
-        .try_into()
-        .map_err(|_| crate::ZcashError::ArrayLengthMismatch {
-            expected: N as u64,
-            got: slice.len() as u64,
-        })
+    slice.try_into().map_err(|_| {
+        crate::validation::invalid_argument(
+            param,
+            format!("expected {} elements, got {}", N, slice.len()),
+        )
+    })
 }
@@ -0,0 +1,70 @@
+//! Checks of the arguments given through the FFI, so that invalid ones fail upfront with
+//! a [`ZcashError::InvalidArgument`] naming them, rather than with a panic or an error
+//! from deep within librustzcash.
+
+use std::num::NonZeroU32;
+use std::ops::RangeInclusive;
+
+use crate::{ZcashError, ZcashResult};
+
+/// The allowed lengths of [ZIP 32](https://zips.z.cash/zip-0032) seeds, in bytes.
+const SEED_LENGTHS: RangeInclusive<usize> = 32..=252;
+
+/// The greatest account id, as accounts are derived at hardened ZIP 32 child indexes.
+const MAX_ACCOUNT_ID: u32 = (1 << 31) - 1;
+
+pub(crate) fn invalid_argument(param: &str, reason: impl Into<String>) -> ZcashError {
+    ZcashError::InvalidArgument {
+        param: param.to_string(),
+        reason: reason.into(),
+    }
+}
+
+/// Checks the seed has a length ZIP 32 allows.
+pub(crate) fn check_seed(param: &str, seed: &[u8]) -> ZcashResult<()> {
+    if !SEED_LENGTHS.contains(&seed.len()) {
+        return Err(invalid_argument(
+            param,
+            format!(
+                "a seed must be {} to {} bytes long, got {}",
+                SEED_LENGTHS.start(),
+                SEED_LENGTHS.end(),
+                seed.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks keys can be derived for the account.
+pub(crate) fn check_account(param: &str, account: u32) -> ZcashResult<()> {
+    if account > MAX_ACCOUNT_ID {
+        return Err(invalid_argument(
+            param,
+            format!(
+                "account ids are at most {}, got {}",
+                MAX_ACCOUNT_ID, account
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the `start..end` range does not end before it starts. `param` names both of
+/// its bounds, like `start_height..end_height`.
+pub(crate) fn check_range(param: &str, start: u32, end: u32) -> ZcashResult<()> {
+    if end < start {
+        return Err(invalid_argument(
+            param,
+            format!("the range ends at {}, before its start at {}", end, start),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the wallet is asked for at least one confirmation, as notes are only spendable
+/// once mined.
+pub(crate) fn non_zero(min_confirmations: u32) -> ZcashResult<NonZeroU32> {
+    NonZeroU32::new(min_confirmations)
+        .ok_or_else(|| invalid_argument("min_confirmations", "at least one confirmation is needed"))
+}
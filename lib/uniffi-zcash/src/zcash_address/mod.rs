@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+use zcash_address::unified::{self, Container};
+use zcash_address::{ConversionError, Network, TryFromAddress};
+use zcash_client_backend::address::RecipientAddress;
+use zcash_primitives::consensus::Parameters;
+
+use crate::{ZcashConsensusParameters, ZcashError, ZcashRecipientAddress, ZcashResult};
+
+/// The network an address is encoded for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZcashAddressNetwork {
+    Main,
+    Test,
+    /// Sprout and transparent addresses have the same encoding in the test and regtest
+    /// networks, so they are always parsed as [`ZcashAddressNetwork::Test`].
+    Regtest,
+}
+
+impl From<Network> for ZcashAddressNetwork {
+    fn from(network: Network) -> Self {
+        match network {
+            Network::Main => ZcashAddressNetwork::Main,
+            Network::Test => ZcashAddressNetwork::Test,
+            Network::Regtest => ZcashAddressNetwork::Regtest,
+        }
+    }
+}
+
+/// The kinds of Zcash addresses, including the historical ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZcashAddressKind {
+    Sprout,
+    Sapling,
+    Unified,
+    P2pkh,
+    P2sh,
+}
+
+/// A receiver of a unified address, as defined in ZIP 316, with its raw encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZcashUnifiedReceiver {
+    Orchard {
+        data: Vec<u8>,
+    },
+    Sapling {
+        data: Vec<u8>,
+    },
+    P2pkh {
+        data: Vec<u8>,
+    },
+    P2sh {
+        data: Vec<u8>,
+    },
+    /// A receiver of a type unknown to this library, kept so it can be re-encoded.
+    Unknown {
+        typecode: u32,
+        data: Vec<u8>,
+    },
+}
+
+impl From<unified::Receiver> for ZcashUnifiedReceiver {
+    fn from(receiver: unified::Receiver) -> Self {
+        match receiver {
+            unified::Receiver::Orchard(data) => ZcashUnifiedReceiver::Orchard {
+                data: data.to_vec(),
+            },
+            unified::Receiver::Sapling(data) => ZcashUnifiedReceiver::Sapling {
+                data: data.to_vec(),
+            },
+            unified::Receiver::P2pkh(data) => ZcashUnifiedReceiver::P2pkh {
+                data: data.to_vec(),
+            },
+            unified::Receiver::P2sh(data) => ZcashUnifiedReceiver::P2sh {
+                data: data.to_vec(),
+            },
+            unified::Receiver::Unknown { typecode, data } => {
+                ZcashUnifiedReceiver::Unknown { typecode, data }
+            }
+        }
+    }
+}
+
+/// The contents of a parsed address, which [`zcash_address::ZcashAddress`] only hands
+/// out through conversions.
+#[derive(Debug, Clone)]
+enum AddressData {
+    Sprout,
+    Sapling([u8; 43]),
+    Unified(unified::Address),
+    P2pkh([u8; 20]),
+    P2sh([u8; 20]),
+}
+
+struct ParsedAddress(Network, AddressData);
+
+impl TryFromAddress for ParsedAddress {
+    type Error = &'static str;
+
+    fn try_from_sprout(net: Network, _: [u8; 64]) -> Result<Self, ConversionError<&'static str>> {
+        Ok(ParsedAddress(net, AddressData::Sprout))
+    }
+
+    fn try_from_sapling(
+        net: Network,
+        data: [u8; 43],
+    ) -> Result<Self, ConversionError<&'static str>> {
+        Ok(ParsedAddress(net, AddressData::Sapling(data)))
+    }
+
+    fn try_from_unified(
+        net: Network,
+        data: unified::Address,
+    ) -> Result<Self, ConversionError<&'static str>> {
+        Ok(ParsedAddress(net, AddressData::Unified(data)))
+    }
+
+    fn try_from_transparent_p2pkh(
+        net: Network,
+        data: [u8; 20],
+    ) -> Result<Self, ConversionError<&'static str>> {
+        Ok(ParsedAddress(net, AddressData::P2pkh(data)))
+    }
+
+    fn try_from_transparent_p2sh(
+        net: Network,
+        data: [u8; 20],
+    ) -> Result<Self, ConversionError<&'static str>> {
+        Ok(ParsedAddress(net, AddressData::P2sh(data)))
+    }
+}
+
+/// Whether the string is a Sprout address, which parsing APIs refuse with
+/// [`ZcashError::SproutNotSupported`] rather than a generic decoding error.
+pub(crate) fn is_sprout_address(encoded: &str) -> bool {
+    zcash_address::ZcashAddress::try_from_encoded(encoded)
+        .ok()
+        .and_then(|address| address.convert::<ParsedAddress>().ok())
+        .is_some_and(|ParsedAddress(_, data)| matches!(data, AddressData::Sprout))
+}
+
+/// A Zcash address of any kind and network, as parsed by the `zcash_address` crate.
+///
+/// Unlike [`ZcashRecipientAddress`], it accepts every address that was ever valid,
+/// like Sprout ones, and unified addresses with receivers unknown to this library,
+/// which makes it suitable for validating user input.
+#[derive(Debug, Clone)]
+pub struct ZcashAddress {
+    address: zcash_address::ZcashAddress,
+    network: Network,
+    data: AddressData,
+}
+
+impl ZcashAddress {
+    /// Parses an encoded address of any kind.
+    pub fn parse(encoded: String) -> ZcashResult<Self> {
+        let address = zcash_address::ZcashAddress::try_from_encoded(&encoded).map_err(|e| {
+            ZcashError::Message {
+                error: format!("Invalid address: {}", e),
+            }
+        })?;
+        let ParsedAddress(network, data) =
+            address
+                .clone()
+                .convert()
+                .map_err(|e: ConversionError<&'static str>| ZcashError::Message {
+                    error: format!("Invalid address: {}", e),
+                })?;
+
+        Ok(ZcashAddress {
+            address,
+            network,
+            data,
+        })
+    }
+
+    pub fn network(&self) -> ZcashAddressNetwork {
+        self.network.into()
+    }
+
+    pub fn kind(&self) -> ZcashAddressKind {
+        match self.data {
+            AddressData::Sprout => ZcashAddressKind::Sprout,
+            AddressData::Sapling(_) => ZcashAddressKind::Sapling,
+            AddressData::Unified(_) => ZcashAddressKind::Unified,
+            AddressData::P2pkh(_) => ZcashAddressKind::P2pkh,
+            AddressData::P2sh(_) => ZcashAddressKind::P2sh,
+        }
+    }
+
+    /// Returns the receivers of the address: those of a unified address, in order of
+    /// preference, or the address itself for Sapling and transparent addresses. Sprout
+    /// addresses have no receivers, as unified addresses cannot contain them.
+    pub fn receivers(&self) -> Vec<ZcashUnifiedReceiver> {
+        match &self.data {
+            AddressData::Sprout => vec![],
+            AddressData::Sapling(data) => vec![unified::Receiver::Sapling(*data).into()],
+            AddressData::Unified(ua) => ua.items().into_iter().map(From::from).collect(),
+            AddressData::P2pkh(data) => vec![unified::Receiver::P2pkh(*data).into()],
+            AddressData::P2sh(data) => vec![unified::Receiver::P2sh(*data).into()],
+        }
+    }
+
+    /// Whether a memo can be sent to the address, which needs a shielded receiver.
+    pub fn can_receive_memo(&self) -> bool {
+        self.receivers().iter().any(|receiver| {
+            matches!(
+                receiver,
+                ZcashUnifiedReceiver::Orchard { .. } | ZcashUnifiedReceiver::Sapling { .. }
+            )
+        }) || matches!(self.data, AddressData::Sprout)
+    }
+
+    /// Converts the address into one funds can be sent to with this library, which
+    /// fails for Sprout addresses and addresses of another network.
+    pub fn to_recipient_address(
+        &self,
+        params: ZcashConsensusParameters,
+    ) -> ZcashResult<Arc<ZcashRecipientAddress>> {
+        if let AddressData::Sprout = self.data {
+            return Err(ZcashError::SproutNotSupported);
+        }
+        let network = params.address_network().expect("Unrecognized network");
+        self.address
+            .clone()
+            .convert_if_network::<RecipientAddress>(network)
+            .map(|address| Arc::new(address.into()))
+            .map_err(|e| ZcashError::Message {
+                error: format!("Cannot send funds to the address: {}", e),
+            })
+    }
+
+    pub fn encode(&self) -> String {
+        self.address.encode()
+    }
+}
@@ -3,8 +3,8 @@
 use zcash_client_backend::address::RecipientAddress;
 
 use crate::{
-    ZcashConsensusParameters, ZcashError, ZcashPaymentAddress, ZcashResult,
-    ZcashTransparentAddress, ZcashUnifiedAddress,
+    zcash_address::is_sprout_address, ZcashConsensusParameters, ZcashError, ZcashPaymentAddress,
+    ZcashResult, ZcashTransparentAddress, ZcashUnifiedAddress,
 };
 
 /// An address that funds can be sent to.
@@ -61,6 +61,9 @@ pub fn unified(addr: Arc<ZcashUnifiedAddress>) -> Self {
     }
 
     pub fn decode(params: ZcashConsensusParameters, address: &str) -> ZcashResult<Self> {
+        if is_sprout_address(address) {
+            return Err(ZcashError::SproutNotSupported);
+        }
         RecipientAddress::decode(&params, address)
             .map(From::from)
             .ok_or::<ZcashError>("unable to parse address".into())
@@ -1,18 +1,33 @@
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use zcash_client_backend::{address::UnifiedAddress, encoding::AddressCodec};
 
 use crate::{
-    ZcashConsensusParameters, ZcashError, ZcashOrchardAddress, ZcashPaymentAddress, ZcashResult,
-    ZcashTransparentAddress,
+    zcash_address::is_sprout_address, ZcashConsensusParameters, ZcashError, ZcashOrchardAddress,
+    ZcashPaymentAddress, ZcashResult, ZcashTransparentAddress,
 };
 
 use derive_more::{From, Into};
 
 /// A Unified Address.
-#[derive(Debug, Clone, From, Into)]
+#[derive(Debug, Clone, PartialEq, Eq, From, Into)]
 pub struct ZcashUnifiedAddress(UnifiedAddress);
 
+impl Hash for ZcashUnifiedAddress {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.transparent().hash(state);
+        self.0
+            .sapling()
+            .map(|address| address.to_bytes())
+            .hash(state);
+        self.0
+            .orchard()
+            .map(|address| address.to_raw_address_bytes())
+            .hash(state);
+    }
+}
+
 impl ZcashUnifiedAddress {
     /// Constructs a Unified Address from a given set of receivers.
     ///
@@ -48,6 +63,9 @@ pub fn transparent(&self) -> Option<Arc<ZcashTransparentAddress>> {
     }
 
     pub fn decode(params: ZcashConsensusParameters, addr: &str) -> ZcashResult<Self> {
+        if is_sprout_address(addr) {
+            return Err(ZcashError::SproutNotSupported);
+        }
         Ok(AddressCodec::decode(&params, addr).map(ZcashUnifiedAddress)?)
     }
 
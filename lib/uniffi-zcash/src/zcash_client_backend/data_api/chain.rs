@@ -1,15 +1,34 @@
-use std::sync::Arc;
-use zcash_client_backend::data_api::chain;
-use zcash_client_backend::data_api::chain::CommitmentTreeRoot;
-use zcash_client_sqlite::{FsBlockDb, WalletDb};
-use zcash_primitives::sapling::Node;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use prost::Message;
+use zcash_client_backend::data_api::chain::{self, error::Error, BlockSource, CommitmentTreeRoot};
+use zcash_client_backend::data_api::{NullifierQuery, ScannedBlock, WalletRead, WalletWrite};
+use zcash_client_backend::proto::compact_formats::{ChainMetadata, CompactBlock};
+use zcash_client_backend::scanning::scan_block;
+use zcash_client_sqlite::WalletDb;
+use zcash_primitives::consensus::BlockHeight;
+use zcash_primitives::sapling::{self, Node, SaplingIvk};
+use zcash_primitives::zip32::{AccountId, DiversifiableFullViewingKey};
 
 use derive_more::{From, Into};
 
 use crate::{
-    ZcashBlockHeight, ZcashConsensusParameters, ZcashError, ZcashResult, ZcashSaplingNode,
+    check_cache_network, CachedBlocks, ZcashBlockHeight, ZcashConsensusParameters, ZcashError,
+    ZcashResult, ZcashSaplingIvk, ZcashSaplingNode, ZcashScanRange,
 };
 
+/// The largest count of blocks a worker of [`scan_cached_blocks_parallel`] scans at once.
+/// Larger ranges are split, so that they are scanned concurrently too.
+const PARALLEL_SCAN_CHUNK_SIZE: u32 = 1000;
+
+/// Scans `limit` blocks of the block cache from `height`, and stores the results in the
+/// wallet. Fails if the cache was opened with [`crate::ZcashFsBlockDb::for_network`] for
+/// another network.
 pub fn scan_cached_blocks(
     params: ZcashConsensusParameters,
     fsblockdb_root: String,
@@ -17,7 +36,8 @@ pub fn scan_cached_blocks(
     height: Arc<ZcashBlockHeight>,
     limit: u32,
 ) -> ZcashResult<()> {
-    let db_cache = FsBlockDb::for_path(fsblockdb_root).expect("Cannot access FsBlockDb");
+    check_cache_network(Path::new(&fsblockdb_root), params)?;
+    let db_cache = CachedBlocks::for_path(fsblockdb_root).expect("Cannot access FsBlockDb");
     let mut db_data = WalletDb::for_path(db_data_path, params).expect("Cannot access WalletDb");
 
     chain::scan_cached_blocks(
@@ -32,6 +52,247 @@ pub fn scan_cached_blocks(
     })
 }
 
+/// Scans the given ranges of the block cache concurrently, using up to `max_parallelism`
+/// worker threads (all the available cores if 0), and stores the results in the wallet.
+///
+/// The ranges, usually the ones suggested by the wallet, are split in chunks of at most
+/// [`PARALLEL_SCAN_CHUNK_SIZE`] blocks. Chunks are scanned independently, so the cached
+/// blocks need to carry their chain metadata (`lightwalletd` provides it) unless the block
+/// before the chunk is already scanned. The wallet writes are serialized on the calling
+/// thread, where the scanned chunks get merged in the note commitment tree, in whatever
+/// order they finish. Spends of notes found in a concurrently scanned chunk are detected
+/// through the nullifier map the wallet keeps, as in any non linear scan.
+///
+/// Returns the count of scanned blocks. On error, the chunks already stored are kept. As
+/// [`scan_cached_blocks`], it fails if the cache is for another network.
+pub fn scan_cached_blocks_parallel(
+    params: ZcashConsensusParameters,
+    fsblockdb_root: String,
+    db_data_path: String,
+    ranges: Vec<Arc<ZcashScanRange>>,
+    max_parallelism: u32,
+) -> ZcashResult<u32> {
+    check_cache_network(Path::new(&fsblockdb_root), params)?;
+    let mut db_data =
+        WalletDb::for_path(&db_data_path, params).map_err(|e| ZcashError::Message {
+            error: format!("Cannot access WalletDb: {:?}", e),
+        })?;
+
+    let ufvks = db_data.get_unified_full_viewing_keys().map_err(scan_err)?;
+    let dfvks: Vec<_> = ufvks
+        .iter()
+        .filter_map(|(account, ufvk)| ufvk.sapling().map(move |k| (account, k)))
+        .collect();
+    let nullifiers = db_data
+        .get_sapling_nullifiers(NullifierQuery::Unspent)
+        .map_err(scan_err)?;
+
+    let chunks: VecDeque<(u32, u32)> = ranges
+        .iter()
+        .flat_map(|range| {
+            let (start, end) = (range.start_height(), range.end_height());
+            (start..end)
+                .step_by(PARALLEL_SCAN_CHUNK_SIZE as usize)
+                .map(move |from| (from, end.min(from + PARALLEL_SCAN_CHUNK_SIZE)))
+        })
+        .collect();
+    let workers = match max_parallelism {
+        0 => thread::available_parallelism().map_or(1, usize::from),
+        n => n as usize,
+    }
+    .min(chunks.len());
+
+    let queue = Mutex::new(chunks);
+    let failed = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let (queue, failed, dfvks, nullifiers) = (&queue, &failed, &dfvks, &nullifiers);
+            let (fsblockdb_root, db_data_path) = (&fsblockdb_root, &db_data_path);
+            scope.spawn(move || {
+                while !failed.load(Ordering::Relaxed) {
+                    let Some((from, to)) = queue.lock().unwrap().pop_front() else {
+                        break;
+                    };
+                    let scanned = scan_chunk(
+                        params,
+                        fsblockdb_root,
+                        db_data_path,
+                        dfvks,
+                        nullifiers.clone(),
+                        from,
+                        to,
+                    );
+                    if scanned.is_err() {
+                        failed.store(true, Ordering::Relaxed);
+                    }
+                    if sender.send(scanned).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut scanned_count = 0;
+        for scanned in receiver {
+            // The worker already flagged the failure.
+            let blocks = scanned?;
+            scanned_count += blocks.len() as u32;
+            if let Err(e) = db_data.put_blocks(blocks) {
+                failed.store(true, Ordering::Relaxed);
+                return Err(scan_err(e));
+            }
+        }
+        Ok(scanned_count)
+    })
+}
+
+/// Scans the `from..to` blocks of the cache with connections of its own,
+/// so it can run in any thread.
+fn scan_chunk(
+    params: ZcashConsensusParameters,
+    fsblockdb_root: &str,
+    db_data_path: &str,
+    dfvks: &[(&AccountId, &DiversifiableFullViewingKey)],
+    mut nullifiers: Vec<(AccountId, sapling::Nullifier)>,
+    from: u32,
+    to: u32,
+) -> ZcashResult<Vec<ScannedBlock<sapling::Nullifier>>> {
+    let db_cache = CachedBlocks::for_path(fsblockdb_root).map_err(|e| ZcashError::Message {
+        error: format!("Cannot access FsBlockDb: {:?}", e),
+    })?;
+    let db_data = WalletDb::for_path(db_data_path, params).map_err(|e| ZcashError::Message {
+        error: format!("Cannot access WalletDb: {:?}", e),
+    })?;
+
+    let mut prior_block_metadata = match from {
+        0 => None,
+        _ => db_data
+            .block_metadata(BlockHeight::from_u32(from - 1))
+            .map_err(scan_err)?,
+    };
+
+    let mut scanned_blocks = Vec::with_capacity((to - from) as usize);
+    db_cache
+        .with_blocks(
+            Some(BlockHeight::from_u32(from)),
+            Some((to - from) as usize),
+            |block| {
+                let scanned_block = scan_block(
+                    &params,
+                    block,
+                    dfvks,
+                    &nullifiers,
+                    prior_block_metadata.as_ref(),
+                )
+                .map_err(Error::Scan)?;
+
+                let spent: Vec<&sapling::Nullifier> = scanned_block
+                    .transactions()
+                    .iter()
+                    .flat_map(|tx| tx.sapling_spends.iter().map(|spend| spend.nf()))
+                    .collect();
+                nullifiers.retain(|(_, nf)| !spent.contains(&nf));
+                nullifiers.extend(scanned_block.transactions().iter().flat_map(|tx| {
+                    tx.sapling_outputs
+                        .iter()
+                        .map(|out| (out.account(), *out.nf()))
+                }));
+
+                prior_block_metadata = Some(*scanned_block.metadata());
+                scanned_blocks.push(scanned_block);
+                Ok::<_, Error<zcash_client_sqlite::error::SqliteClientError, _>>(())
+            },
+        )
+        .map_err(scan_err)?;
+
+    Ok(scanned_blocks)
+}
+
+/// The outcome of [`benchmark_scan`].
+pub struct ZcashScanBenchmark {
+    pub blocks: u32,
+    pub outputs: u64,
+    pub elapsed_millis: u64,
+    /// Sapling outputs trial decrypted per second, with all the given keys.
+    pub notes_per_second: f64,
+}
+
+/// Scans the given sample blocks, encoded compact blocks like the ones in the block
+/// cache, with the given keys, measuring the scanning throughput of the current device.
+/// Nothing is stored. Apps can use it to choose batch sizes and the parallelism of
+/// [`scan_cached_blocks_parallel`], and maintainers to compare devices.
+pub fn benchmark_scan(
+    params: ZcashConsensusParameters,
+    sample_blocks: Vec<Vec<u8>>,
+    ivks: Vec<Arc<ZcashSaplingIvk>>,
+) -> ZcashResult<ZcashScanBenchmark> {
+    let blocks = sample_blocks
+        .iter()
+        .map(|bytes| {
+            let mut block = CompactBlock::decode(&bytes[..]).map_err(|e| ZcashError::Message {
+                error: format!("Invalid CompactBlock: {}", e),
+            })?;
+            if block.hash.len() != 32 {
+                return Err(ZcashError::Message {
+                    error: "Invalid CompactBlock: the hash must be 32 bytes long".to_string(),
+                });
+            }
+            // Blocks are scanned on their own, so their position in the note commitment
+            // tree does not matter, but it must be known. Zero means unknown here.
+            let tree_size = block
+                .chain_metadata
+                .as_ref()
+                .map_or(0, |m| m.sapling_commitment_tree_size);
+            if tree_size == 0 {
+                block.chain_metadata = Some(ChainMetadata {
+                    sapling_commitment_tree_size: block
+                        .vtx
+                        .iter()
+                        .map(|tx| tx.outputs.len() as u32)
+                        .sum::<u32>()
+                        + 1,
+                    orchard_commitment_tree_size: 0,
+                });
+            }
+            Ok(block)
+        })
+        .collect::<ZcashResult<Vec<CompactBlock>>>()?;
+
+    let accounts: Vec<AccountId> = (0..ivks.len() as u32).map(AccountId::from).collect();
+    let ivks: Vec<SaplingIvk> = ivks.iter().map(|ivk| ivk.as_ref().into()).collect();
+    let vks: Vec<_> = accounts.iter().zip(ivks.iter()).collect();
+
+    let block_count = blocks.len() as u32;
+    let outputs: u64 = blocks
+        .iter()
+        .flat_map(|block| block.vtx.iter())
+        .map(|tx| tx.outputs.len() as u64)
+        .sum();
+
+    let start = Instant::now();
+    for block in blocks {
+        scan_block(&params, block, &vks, &[], None).map_err(scan_err)?;
+    }
+    let elapsed = start.elapsed();
+
+    Ok(ZcashScanBenchmark {
+        blocks: block_count,
+        outputs,
+        elapsed_millis: elapsed.as_millis() as u64,
+        notes_per_second: outputs as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+    })
+}
+
+fn scan_err(e: impl std::fmt::Debug) -> ZcashError {
+    ZcashError::Message {
+        error: format!("Scanning error: {:?}", e),
+    }
+}
+
 #[derive(From, Into)]
 pub struct ZcashCommitmentTreeRoot(CommitmentTreeRoot<Node>);
 
@@ -7,6 +7,9 @@
 mod scanning;
 pub use self::scanning::*;
 
+mod sync_eta;
+pub use self::sync_eta::*;
+
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -218,17 +221,36 @@ pub fn account_balances(&self) -> HashMap<String, Arc<ZcashAccountBalance>> {
             .collect()
     }
 
+    /// Returns the plain balance values of accounts in the wallet, keyed by account ID.
+    pub fn account_balance_values(&self) -> HashMap<u32, ZcashAccountBalanceValues> {
+        self.0
+            .account_balances()
+            .iter()
+            .map(|(&x, y)| (x.into(), y.into()))
+            .collect()
+    }
+
     /// Returns the height of the current chain tip.
     pub fn chain_tip_height(&self) -> Arc<ZcashBlockHeight> {
         Arc::new(self.0.chain_tip_height().into())
     }
 
+    /// Returns the height of the current chain tip, as a plain value.
+    pub fn chain_tip_height_value(&self) -> u32 {
+        self.0.chain_tip_height().into()
+    }
+
     /// Returns the height below which all blocks have been scanned by the wallet, ignoring blocks
     /// below the wallet birthday.
     pub fn fully_scanned_height(&self) -> Arc<ZcashBlockHeight> {
         Arc::new(self.0.fully_scanned_height().into())
     }
 
+    /// Returns the fully scanned height, as a plain value.
+    pub fn fully_scanned_height_value(&self) -> u32 {
+        self.0.fully_scanned_height().into()
+    }
+
     /// Returns the progress of scanning shielded outputs, in terms of the ratio between notes
     /// scanned and the total number of notes added to the chain since the wallet birthday.
     ///
@@ -243,6 +265,16 @@ pub fn scan_progress(&self) -> Option<Arc<ZcashRatio>> {
     pub fn is_synced(&self) -> bool {
         self.0.is_synced()
     }
+
+    /// Returns the blocks between the fully scanned height and the chain tip.
+    pub fn sync_lag(&self) -> u32 {
+        u32::from(self.0.chain_tip_height()).saturating_sub(self.0.fully_scanned_height().into())
+    }
+
+    /// Returns whether the wallet is at most `tolerance` blocks behind the chain tip.
+    pub fn is_synced_within(&self, tolerance: u32) -> bool {
+        self.sync_lag() <= tolerance
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, From, Into)]
@@ -291,6 +323,29 @@ pub fn unshielded(&self) -> Arc<ZcashNonNegativeAmount> {
     pub fn sapling_spendable_value(&self) -> Arc<ZcashNonNegativeAmount> {
         Arc::new(self.0.sapling_balance.spendable_value.into())
     }
+
+    /// Returns all the balance values at once, in zatoshis.
+    pub fn values(&self) -> ZcashAccountBalanceValues {
+        (&self.0).into()
+    }
+}
+
+/// The values of an account balance, in zatoshis.
+pub struct ZcashAccountBalanceValues {
+    pub total: u64,
+    pub sapling_spendable: u64,
+    pub unshielded: u64,
+}
+
+impl From<&AccountBalance> for ZcashAccountBalanceValues {
+    fn from(balance: &AccountBalance) -> Self {
+        let zats = |amount: NonNegativeAmount| ZcashNonNegativeAmount::from(amount).value();
+        ZcashAccountBalanceValues {
+            total: zats(balance.total()),
+            sapling_spendable: zats(balance.sapling_balance.spendable_value),
+            unshielded: zats(balance.unshielded),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, From, Into)]
@@ -5,7 +5,7 @@
 
 use derive_more::{From, Into};
 
-use crate::ZcashBlockHeight;
+use crate::{validation::check_range, ZcashBlockHeight, ZcashResult};
 
 /// Scanning range priority levels.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -78,11 +78,29 @@ pub fn from_parts(
         start_block: Arc<ZcashBlockHeight>,
         end_block: Arc<ZcashBlockHeight>,
         priority: ZcashScanPriority,
-    ) -> Self {
+    ) -> ZcashResult<Self> {
+        check_range(
+            "start_block..end_block",
+            start_block.value(),
+            end_block.value(),
+        )?;
         let start: BlockHeight = (*start_block).into();
         let end: BlockHeight = (*end_block).into();
 
-        Self(ScanRange::from_parts(start..end, priority.into()))
+        Ok(Self(ScanRange::from_parts(start..end, priority.into())))
+    }
+
+    /// Constructs a scan range from plain block heights, the end one being exclusive.
+    pub fn from_heights(
+        start_height: u32,
+        end_height: u32,
+        priority: ZcashScanPriority,
+    ) -> ZcashResult<Self> {
+        check_range("start_height..end_height", start_height, end_height)?;
+        Ok(Self(ScanRange::from_parts(
+            BlockHeight::from_u32(start_height)..BlockHeight::from_u32(end_height),
+            priority.into(),
+        )))
     }
 
     /// Returns the range of block heights to be scanned.
@@ -93,6 +111,16 @@ pub fn block_range(&self) -> Vec<Arc<ZcashBlockHeight>> {
         vec![Arc::new(start), Arc::new(end)]
     }
 
+    /// Returns the height of the first block in the scan range.
+    pub fn start_height(&self) -> u32 {
+        self.0.block_range().start.into()
+    }
+
+    /// Returns the height following the last block in the scan range.
+    pub fn end_height(&self) -> u32 {
+        self.0.block_range().end.into()
+    }
+
     /// Returns the priority with which the scan range should be scanned.
     pub fn priority(&self) -> ZcashScanPriority {
         self.0.priority().into()
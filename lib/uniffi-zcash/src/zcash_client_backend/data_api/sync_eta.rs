@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::ZcashWalletSummary;
+
+/// An estimate of the time left until the wallet is synced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZcashSyncEta {
+    /// The blocks between the fully scanned height and the chain tip.
+    pub blocks_remaining: u32,
+    /// The scanning throughput over the recent window.
+    pub blocks_per_second: f64,
+    /// `None` until some scanned blocks were recorded in the recent window.
+    pub seconds_remaining: Option<u64>,
+}
+
+/// Receives the sync estimates computed by a [`ZcashSyncEtaEstimator`].
+pub trait ZcashSyncStatusListener: Send + Sync {
+    fn on_sync_eta(&self, eta: ZcashSyncEta);
+}
+
+struct Throughput {
+    /// When the oldest sample of the window started.
+    window_start: Instant,
+    /// The count of blocks scanned up to each instant.
+    samples: VecDeque<(Instant, u32)>,
+}
+
+/// Estimates the remaining sync time from the recent scanning throughput, so UIs can
+/// show the time left without duplicating the math.
+///
+/// The sync loop records the blocks it scans, and asks for the estimate with the wallet
+/// summary after each batch. The throughput only considers the last `window_seconds`,
+/// so the estimate follows the changes of block density along the chain.
+pub struct ZcashSyncEtaEstimator {
+    window: Duration,
+    throughput: Mutex<Throughput>,
+    listener: Option<Box<dyn ZcashSyncStatusListener>>,
+}
+
+impl ZcashSyncEtaEstimator {
+    pub fn new(window_seconds: u32, listener: Option<Box<dyn ZcashSyncStatusListener>>) -> Self {
+        Self {
+            window: Duration::from_secs(window_seconds.max(1).into()),
+            throughput: Mutex::new(Throughput {
+                window_start: Instant::now(),
+                samples: VecDeque::new(),
+            }),
+            listener,
+        }
+    }
+
+    /// Records that `count` more blocks were scanned.
+    pub fn record_scanned_blocks(&self, count: u32) {
+        let now = Instant::now();
+        let mut throughput = self.throughput.lock().unwrap();
+        throughput.samples.push_back((now, count));
+        while let Some(&(at, _)) = throughput.samples.front() {
+            if now.duration_since(at) <= self.window {
+                break;
+            }
+            throughput.window_start = at;
+            throughput.samples.pop_front();
+        }
+    }
+
+    /// Computes the estimate for the given wallet state, notifying the listener, if any.
+    pub fn sync_eta(&self, summary: &ZcashWalletSummary) -> ZcashSyncEta {
+        let blocks_remaining = summary.sync_lag();
+
+        let blocks_per_second = {
+            let throughput = self.throughput.lock().unwrap();
+            let scanned: u32 = throughput.samples.iter().map(|(_, count)| count).sum();
+            let elapsed = throughput.window_start.elapsed().as_secs_f64();
+            scanned as f64 / elapsed.max(f64::EPSILON)
+        };
+
+        let seconds_remaining = match blocks_remaining {
+            0 => Some(0),
+            _ if blocks_per_second > 0.0 => {
+                Some((blocks_remaining as f64 / blocks_per_second).ceil() as u64)
+            }
+            _ => None,
+        };
+
+        let eta = ZcashSyncEta {
+            blocks_remaining,
+            blocks_per_second,
+            seconds_remaining,
+        };
+        if let Some(listener) = &self.listener {
+            listener.on_sync_eta(eta);
+        }
+        eta
+    }
+}
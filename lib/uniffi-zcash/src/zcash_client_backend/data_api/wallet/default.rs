@@ -1,371 +1,343 @@
+use std::collections::BTreeSet;
+use std::convert::Infallible;
+use std::fmt::Debug;
 use std::num::NonZeroU32;
 use std::sync::Arc;
 
-use zcash_client_backend::data_api::wallet;
+use rusqlite::Connection;
+use zcash_client_backend::data_api::wallet::{self, input_selection::InputSelector};
+use zcash_client_backend::data_api::{DecryptedTransaction, WalletRead, WalletWrite};
 use zcash_client_backend::keys::UnifiedSpendingKey;
+use zcash_client_backend::wallet::OvkPolicy;
+use zcash_client_backend::zip321::TransactionRequest;
+use zcash_client_backend::{decrypt_transaction, TransferType};
 use zcash_client_sqlite::WalletDb;
-use zcash_primitives::consensus;
+use zcash_primitives::consensus::{NetworkUpgrade, Parameters};
 use zcash_primitives::legacy::TransparentAddress;
-use zcash_proofs::prover::LocalTxProver;
+use zcash_primitives::memo::MemoBytes;
+use zcash_primitives::transaction::components::amount::NonNegativeAmount;
+use zcash_primitives::transaction::fees::FeeRule;
+use zcash_primitives::transaction::{Transaction, TxId};
 
+use super::UpstreamGreedyInputSelector;
 use crate::{
-    MainFixedGreedyInputSelector, MainZip317GreedyInputSelector, TestFixedGreedyInputSelector,
-    TestZip317GreedyInputSelector, ZcashConsensusParameters, ZcashError, ZcashLocalTxProver,
-    ZcashMainFixedGreedyInputSelector, ZcashMainZip317GreedyInputSelector, ZcashMemoBytes,
-    ZcashNonNegativeAmount, ZcashOvkPolicy, ZcashResult, ZcashTestFixedGreedyInputSelector,
-    ZcashTestZip317GreedyInputSelector, ZcashTransaction, ZcashTransactionRequest,
-    ZcashTransparentAddress, ZcashTxId, ZcashUnifiedSpendingKey, ZcashWalletDb,
+    check_expiry_delta, create_proposed_transaction_expiring, validation::non_zero, ZcashAccountId,
+    ZcashConsensusParameters, ZcashError, ZcashGreedyInputSelector, ZcashLocalTxProver,
+    ZcashMemoBytes, ZcashNonNegativeAmount, ZcashOvkPolicy, ZcashProposal, ZcashResult,
+    ZcashSpendApprover, ZcashTransaction, ZcashTransactionRequest, ZcashTransparentAddress,
+    ZcashTxId, ZcashUnifiedSpendingKey, ZcashWalletDb, DEFAULT_TX_EXPIRY_DELTA,
 };
 
+/// What [`decrypt_and_store_transaction`] found in the transaction for the wallet. Only
+/// its Sapling outputs are decrypted, so a transaction which is only relevant for its
+/// transparent part has no account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZcashStoredTransactionSummary {
+    /// The accounts which received or sent any of the outputs, in order.
+    pub accounts: Vec<ZcashAccountId>,
+    /// The notes the wallet received, change included.
+    pub notes_received: u32,
+    /// The value the wallet received, in zatoshis, change excluded.
+    pub value_received: u64,
+}
+
 /// Scans a [`Transaction`] for any information that can be decrypted by the accounts in
-/// the wallet, and saves it to the wallet.
+/// the wallet, and saves it to the wallet. Returns what was found, so that it can be told
+/// whether the transaction was relevant for the wallet at all.
 pub fn decrypt_and_store_transaction(
     params: ZcashConsensusParameters,
     z_db_data: Arc<ZcashWalletDb>,
     tx: Arc<ZcashTransaction>,
-) -> ZcashResult<()> {
+) -> ZcashResult<ZcashStoredTransactionSummary> {
     let mut db_data = WalletDb::for_path(&z_db_data.path, params).unwrap();
+    let tx: Transaction = (*tx).clone().into();
 
-    match wallet::decrypt_and_store_transaction(&params, &mut db_data, &((*tx).clone().into())) {
-        Ok(_) => Ok(()),
-        Err(x) => Err(ZcashError::Message {
+    // The same as `wallet::decrypt_and_store_transaction`, keeping the decrypted outputs.
+    let sapling_outputs = db_data
+        .get_unified_full_viewing_keys()
+        .and_then(|ufvks| {
+            let height = db_data
+                .get_tx_height(tx.txid())?
+                .or(db_data.chain_height()?.map(|max_height| max_height + 1))
+                .or_else(|| params.activation_height(NetworkUpgrade::Sapling))
+                .expect("Sapling activation height must be known.");
+            Ok(decrypt_transaction(&params, height, &tx, &ufvks))
+        })
+        .and_then(|sapling_outputs| {
+            db_data.store_decrypted_tx(DecryptedTransaction {
+                tx: &tx,
+                sapling_outputs: &sapling_outputs,
+            })?;
+            Ok(sapling_outputs)
+        })
+        .map_err(|x| ZcashError::Message {
             error: format!("decrypt and store transaction error: {:?}", x),
-        }),
-    }
-}
+        })?;
 
-#[allow(clippy::too_many_arguments)]
-#[allow(clippy::type_complexity)]
-pub fn spend_main_fixed(
-    z_db_data: Arc<ZcashWalletDb>,
-    params: ZcashConsensusParameters,
-    prover: Arc<ZcashLocalTxProver>,
-    input_selector: Arc<ZcashMainFixedGreedyInputSelector>,
-    usk: Arc<ZcashUnifiedSpendingKey>,
-    request: Arc<ZcashTransactionRequest>,
-    ovk_policy: ZcashOvkPolicy,
-    min_confirmations: u32,
-) -> ZcashResult<Arc<ZcashTxId>> {
-    let min_confirmations = NonZeroU32::new(min_confirmations).unwrap();
-
-    let mut db_data = WalletDb::for_path(&z_db_data.path, consensus::MAIN_NETWORK)
-        .expect("Cannot unwrap db_data!");
-
-    match wallet::spend(
-        &mut db_data,
-        &params,
-        <ZcashLocalTxProver as Into<LocalTxProver>>::into((*prover).clone()),
-        &<ZcashMainFixedGreedyInputSelector as Into<MainFixedGreedyInputSelector>>::into(
-            (*input_selector).clone(),
-        ),
-        &((*usk).clone().into()),
-        (*request).clone().into(),
-        ovk_policy.into(),
-        min_confirmations,
-    ) {
-        Ok(txid) => {
-            let x: ZcashTxId = txid.into();
-            Ok(Arc::new(x))
-        }
-        Err(x) => Err(ZcashError::Message {
-            error: format!("spending error (spend_main): {:?}", x),
-        }),
-    }
+    let accounts: BTreeSet<u32> = sapling_outputs
+        .iter()
+        .map(|output| output.account.into())
+        .collect();
+    let received = sapling_outputs
+        .iter()
+        .filter(|output| output.transfer_type != TransferType::Outgoing);
+    Ok(ZcashStoredTransactionSummary {
+        accounts: accounts
+            .into_iter()
+            .map(|id| ZcashAccountId { id })
+            .collect(),
+        notes_received: received.clone().count() as u32,
+        value_received: received
+            .filter(|output| output.transfer_type == TransferType::Incoming)
+            .map(|output| output.note.value().inner())
+            .sum(),
+    })
 }
 
+/// Spends from the account of `usk` to make the requested payments, selecting the notes
+/// with `input_selector`, on the network of the wallet. With `split_notes`, each shielded
+/// payment is split into several notes, see [`ZcashTransactionRequest::with_split_notes`].
+/// The transaction expires `expiry_delta` blocks after its target height, by default the
+/// [`DEFAULT_TX_EXPIRY_DELTA`].
 #[allow(clippy::too_many_arguments)]
-#[allow(clippy::type_complexity)]
-pub fn spend_test_fixed(
+pub fn spend(
     z_db_data: Arc<ZcashWalletDb>,
-    params: ZcashConsensusParameters,
     prover: Arc<ZcashLocalTxProver>,
-    input_selector: Arc<ZcashTestFixedGreedyInputSelector>,
+    input_selector: Arc<ZcashGreedyInputSelector>,
     usk: Arc<ZcashUnifiedSpendingKey>,
     request: Arc<ZcashTransactionRequest>,
     ovk_policy: ZcashOvkPolicy,
     min_confirmations: u32,
+    split_notes: bool,
+    change_memo: Option<Arc<ZcashMemoBytes>>,
+    approver: Option<Box<dyn ZcashSpendApprover>>,
+    expiry_delta: Option<u32>,
 ) -> ZcashResult<Arc<ZcashTxId>> {
-    let min_confirmations = NonZeroU32::new(min_confirmations).unwrap();
-
-    let mut db_data = WalletDb::for_path(&z_db_data.path, consensus::TEST_NETWORK)
-        .expect("Cannot unwrap db_data!");
-
-    match wallet::spend(
-        &mut db_data,
-        &params,
-        <ZcashLocalTxProver as Into<LocalTxProver>>::into((*prover).clone()),
-        &<ZcashTestFixedGreedyInputSelector as Into<TestFixedGreedyInputSelector>>::into(
-            (*input_selector).clone(),
-        ),
-        &((*usk).clone().into()),
-        (*request).clone().into(),
-        ovk_policy.into(),
+    let min_confirmations = non_zero(min_confirmations)?;
+    let expiry_delta = expiry_delta.unwrap_or(DEFAULT_TX_EXPIRY_DELTA);
+    check_expiry_delta(expiry_delta)?;
+    let mut db_data = WalletDb::for_path(&z_db_data.path, z_db_data.params)?;
+    let usk: UnifiedSpendingKey = (*usk).clone().into();
+    let request = requested_payments(&request, split_notes)?;
+    let change_memo: Option<MemoBytes> = change_memo.map(|memo| memo.as_ref().into());
+    let spending = Spending {
+        prover: (*prover).clone(),
+        usk: &usk,
+        ovk_policy: ovk_policy.into(),
         min_confirmations,
-    ) {
-        Ok(txid) => {
-            let x: ZcashTxId = txid.into();
-            Ok(Arc::new(x))
-        }
-        Err(x) => Err(ZcashError::Message {
-            error: format!("spending error (spend test): {:?}", x),
-        }),
-    }
-}
-
-#[allow(clippy::too_many_arguments)]
-#[allow(clippy::type_complexity)]
-pub fn spend_main_zip317(
-    z_db_data: Arc<ZcashWalletDb>,
-    params: ZcashConsensusParameters,
-    prover: Arc<ZcashLocalTxProver>,
-    input_selector: Arc<ZcashMainZip317GreedyInputSelector>,
-    usk: Arc<ZcashUnifiedSpendingKey>,
-    request: Arc<ZcashTransactionRequest>,
-    ovk_policy: ZcashOvkPolicy,
-    min_confirmations: u32,
-) -> ZcashResult<Arc<ZcashTxId>> {
-    let min_confirmations = NonZeroU32::new(min_confirmations).unwrap();
+        change_memo,
+        approver,
+        expiry_delta,
+    };
 
-    let mut db_data = WalletDb::for_path(&z_db_data.path, consensus::MAIN_NETWORK)
-        .expect("Cannot unwrap db_data!");
-
-    match wallet::spend(
-        &mut db_data,
-        &params,
-        <ZcashLocalTxProver as Into<LocalTxProver>>::into((*prover).clone()),
-        &<ZcashMainZip317GreedyInputSelector as Into<MainZip317GreedyInputSelector>>::into(
-            (*input_selector).clone(),
-        ),
-        &((*usk).clone().into()),
-        (*request).clone().into(),
-        ovk_policy.into(),
-        min_confirmations,
-    ) {
-        Ok(txid) => {
-            let x: ZcashTxId = txid.into();
-            Ok(Arc::new(x))
+    let result = match input_selector.to_upstream() {
+        UpstreamGreedyInputSelector::Fixed(input_selector) => {
+            propose_and_spend(&z_db_data, &mut db_data, &input_selector, request, spending)
         }
-        Err(x) => Err(ZcashError::Message {
-            error: format!("spending error (spend_main): {:?}", x),
-        }),
-    }
-}
-
-#[allow(clippy::too_many_arguments)]
-#[allow(clippy::type_complexity)]
-pub fn spend_test_zip317(
-    z_db_data: Arc<ZcashWalletDb>,
-    params: ZcashConsensusParameters,
-    prover: Arc<ZcashLocalTxProver>,
-    input_selector: Arc<ZcashTestZip317GreedyInputSelector>,
-    usk: Arc<ZcashUnifiedSpendingKey>,
-    request: Arc<ZcashTransactionRequest>,
-    ovk_policy: ZcashOvkPolicy,
-    min_confirmations: u32,
-) -> ZcashResult<Arc<ZcashTxId>> {
-    let min_confirmations = NonZeroU32::new(min_confirmations).unwrap();
-
-    let mut db_data = WalletDb::for_path(&z_db_data.path, consensus::TEST_NETWORK)
-        .expect("Cannot unwrap db_data!");
-
-    match wallet::spend(
-        &mut db_data,
-        &params,
-        <ZcashLocalTxProver as Into<LocalTxProver>>::into((*prover).clone()),
-        &<ZcashTestZip317GreedyInputSelector as Into<TestZip317GreedyInputSelector>>::into(
-            (*input_selector).clone(),
-        ),
-        &((*usk).clone().into()),
-        (*request).clone().into(),
-        ovk_policy.into(),
-        min_confirmations,
-    ) {
-        Ok(txid) => {
-            let x: ZcashTxId = txid.into();
-            Ok(Arc::new(x))
+        UpstreamGreedyInputSelector::Zip317(input_selector) => {
+            propose_and_spend(&z_db_data, &mut db_data, &input_selector, request, spending)
         }
-        Err(x) => Err(ZcashError::Message {
-            error: format!("spending error (spend test): {:?}", x),
-        }),
-    }
+    };
+    result.map(|txid| Arc::new(txid.into()))
 }
 
+/// Shields the transparent funds of the given addresses of the account of `usk` above
+/// `shielding_threshold`, selecting them with `input_selector`, on the network of the
+/// wallet. The transaction expires `expiry_delta` blocks after its target height, by
+/// default the [`DEFAULT_TX_EXPIRY_DELTA`].
 #[allow(clippy::too_many_arguments)]
-#[allow(clippy::type_complexity)]
-pub fn shield_transparent_funds_main_fixed(
+pub fn shield_transparent_funds(
     z_db_data: Arc<ZcashWalletDb>,
-    params: ZcashConsensusParameters,
     prover: Arc<ZcashLocalTxProver>,
-    input_selector: Arc<ZcashMainFixedGreedyInputSelector>,
+    input_selector: Arc<ZcashGreedyInputSelector>,
     shielding_threshold: u64,
     usk: Arc<ZcashUnifiedSpendingKey>,
     from_addrs: Vec<Arc<ZcashTransparentAddress>>,
     memo: Arc<ZcashMemoBytes>,
     min_confirmations: u32,
+    expiry_delta: Option<u32>,
 ) -> ZcashResult<Arc<ZcashTxId>> {
-    let min_confirmations = NonZeroU32::new(min_confirmations).unwrap();
-    let shielding_threshold = ZcashNonNegativeAmount::from_u64(shielding_threshold).unwrap();
+    let min_confirmations = non_zero(min_confirmations)?;
+    let expiry_delta = expiry_delta.unwrap_or(DEFAULT_TX_EXPIRY_DELTA);
+    check_expiry_delta(expiry_delta)?;
+    let shielding_threshold: NonNegativeAmount =
+        ZcashNonNegativeAmount::from_u64(shielding_threshold)?.into();
     let addresses = from_addrs
         .iter()
         .map(|x| x.as_ref().into())
         .collect::<Vec<TransparentAddress>>();
+    let mut db_data = WalletDb::for_path(&z_db_data.path, z_db_data.params)?;
+    let usk: UnifiedSpendingKey = (*usk).clone().into();
+    let memo: MemoBytes = (*memo).clone().into();
+    let spending = Spending {
+        prover: (*prover).clone(),
+        usk: &usk,
+        ovk_policy: OvkPolicy::Sender,
+        min_confirmations,
+        change_memo: Some(memo),
+        approver: None,
+        expiry_delta,
+    };
 
-    let mut db_data = WalletDb::for_path(&z_db_data.path, consensus::MAIN_NETWORK).unwrap();
-
-    match wallet::shield_transparent_funds(
-        &mut db_data,
-        &params,
-        <ZcashLocalTxProver as Into<LocalTxProver>>::into((*prover).clone()),
-        &<ZcashMainFixedGreedyInputSelector as Into<MainFixedGreedyInputSelector>>::into(
-            (*input_selector).clone(),
+    let result = match input_selector.to_upstream() {
+        UpstreamGreedyInputSelector::Fixed(input_selector) => propose_and_shield(
+            &mut db_data,
+            z_db_data.params,
+            &input_selector,
+            shielding_threshold,
+            &addresses,
+            spending,
         ),
-        shielding_threshold.into(),
-        &<ZcashUnifiedSpendingKey as Into<UnifiedSpendingKey>>::into((*usk).clone()),
-        &addresses[..],
-        &((*memo).clone().into()),
-        min_confirmations,
-    ) {
-        Ok(txid) => {
-            let x: ZcashTxId = txid.into();
-            Ok(Arc::new(x))
-        }
-        Err(x) => Err(ZcashError::Message {
-            error: format!("spending error: {:?}", x),
-        }),
+        UpstreamGreedyInputSelector::Zip317(input_selector) => propose_and_shield(
+            &mut db_data,
+            z_db_data.params,
+            &input_selector,
+            shielding_threshold,
+            &addresses,
+            spending,
+        ),
+    };
+    result.map(|txid| Arc::new(txid.into()))
+}
+
+fn spend_error<E: Debug>(error: E) -> ZcashError {
+    ZcashError::Message {
+        error: format!("spending error: {:?}", error),
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-#[allow(clippy::type_complexity)]
-pub fn shield_transparent_funds_test_fixed(
-    z_db_data: Arc<ZcashWalletDb>,
-    params: ZcashConsensusParameters,
-    prover: Arc<ZcashLocalTxProver>,
-    input_selector: Arc<ZcashTestFixedGreedyInputSelector>,
-    shielding_threshold: u64,
-    usk: Arc<ZcashUnifiedSpendingKey>,
-    from_addrs: Vec<Arc<ZcashTransparentAddress>>,
-    memo: Arc<ZcashMemoBytes>,
-    min_confirmations: u32,
-) -> ZcashResult<Arc<ZcashTxId>> {
-    let min_confirmations = NonZeroU32::new(min_confirmations).unwrap();
-    let shielding_threshold = ZcashNonNegativeAmount::from_u64(shielding_threshold).unwrap();
-    let addresses = from_addrs
-        .iter()
-        .map(|x| x.as_ref().into())
-        .collect::<Vec<TransparentAddress>>();
+fn shielding_error<E: Debug>(error: E) -> ZcashError {
+    ZcashError::Message {
+        error: format!("shielding error: {:?}", error),
+    }
+}
 
-    let mut db_data = WalletDb::for_path(&z_db_data.path, consensus::TEST_NETWORK).unwrap();
+/// How many notes each shielded payment is split into at most, when spending with
+/// `split_notes`. See [`ZcashTransactionRequest::with_split_notes`].
+pub const SPLIT_NOTES_MAX: u32 = 4;
 
-    match wallet::shield_transparent_funds(
-        &mut db_data,
-        &params,
-        <ZcashLocalTxProver as Into<LocalTxProver>>::into((*prover).clone()),
-        &<ZcashTestFixedGreedyInputSelector as Into<TestFixedGreedyInputSelector>>::into(
-            (*input_selector).clone(),
-        ),
-        shielding_threshold.into(),
-        &<ZcashUnifiedSpendingKey as Into<UnifiedSpendingKey>>::into((*usk).clone()),
-        &addresses[..],
-        &((*memo).clone().into()),
-        min_confirmations,
-    ) {
-        Ok(txid) => {
-            let x: ZcashTxId = txid.into();
-            Ok(Arc::new(x))
-        }
-        Err(x) => Err(ZcashError::Message {
-            error: format!("spending error: {:?}", x),
-        }),
+fn requested_payments(
+    request: &ZcashTransactionRequest,
+    split_notes: bool,
+) -> ZcashResult<TransactionRequest> {
+    if !split_notes {
+        return Ok(request.clone().into());
     }
+    let split = request
+        .with_split_notes(SPLIT_NOTES_MAX)
+        .map_err(|e| ZcashError::Message {
+            error: format!("Cannot split the payments: {:?}", e),
+        })?;
+    Ok((*split).clone().into())
 }
 
-#[allow(clippy::too_many_arguments)]
-#[allow(clippy::type_complexity)]
-pub fn shield_transparent_funds_main_zip317(
-    z_db_data: Arc<ZcashWalletDb>,
-    params: ZcashConsensusParameters,
-    prover: Arc<ZcashLocalTxProver>,
-    input_selector: Arc<ZcashMainZip317GreedyInputSelector>,
-    shielding_threshold: u64,
-    usk: Arc<ZcashUnifiedSpendingKey>,
-    from_addrs: Vec<Arc<ZcashTransparentAddress>>,
-    memo: Arc<ZcashMemoBytes>,
-    min_confirmations: u32,
-) -> ZcashResult<Arc<ZcashTxId>> {
-    let min_confirmations = NonZeroU32::new(min_confirmations).unwrap();
-    let shielding_threshold = ZcashNonNegativeAmount::from_u64(shielding_threshold).unwrap();
-    let addresses = from_addrs
-        .iter()
-        .map(|x| x.as_ref().into())
-        .collect::<Vec<TransparentAddress>>();
+type UpstreamWalletDb = WalletDb<Connection, ZcashConsensusParameters>;
+
+/// How the transaction of a proposal is created, besides what it spends and pays.
+struct Spending<'a> {
+    prover: ZcashLocalTxProver,
+    usk: &'a UnifiedSpendingKey,
+    ovk_policy: OvkPolicy,
+    min_confirmations: NonZeroU32,
+    change_memo: Option<MemoBytes>,
+    approver: Option<Box<dyn ZcashSpendApprover>>,
+    expiry_delta: u32,
+}
 
-    let mut db_data = WalletDb::for_path(&z_db_data.path, consensus::MAIN_NETWORK).unwrap();
+/// The same as [`wallet::spend`], but putting `change_memo` in the change output, like a
+/// self-memo tagging the transaction for the other devices sharing the seed, asking the
+/// `approver` before signing, and expiring the transaction `expiry_delta` blocks after
+/// its target height. The spend must be within the spending limits of the account, as set
+/// in `z_db_data`.
+fn propose_and_spend<InputsT>(
+    z_db_data: &ZcashWalletDb,
+    wallet_db: &mut UpstreamWalletDb,
+    input_selector: &InputsT,
+    request: TransactionRequest,
+    spending: Spending,
+) -> ZcashResult<TxId>
+where
+    InputsT: InputSelector<DataSource = UpstreamWalletDb>,
+    InputsT::Error: Debug,
+    <InputsT::FeeRule as FeeRule>::Error: Debug,
+{
+    let params = z_db_data.params;
+    let account = wallet_db
+        .get_account_for_ufvk(&spending.usk.to_unified_full_viewing_key())
+        .map_err(spend_error)?
+        .ok_or_else(|| spend_error("The spending key is not of any account of the wallet"))?;
 
-    match wallet::shield_transparent_funds(
-        &mut db_data,
+    let proposal = wallet::propose_transfer::<_, _, _, Infallible>(
+        wallet_db,
         &params,
-        <ZcashLocalTxProver as Into<LocalTxProver>>::into((*prover).clone()),
-        &<ZcashMainZip317GreedyInputSelector as Into<MainZip317GreedyInputSelector>>::into(
-            (*input_selector).clone(),
-        ),
-        shielding_threshold.into(),
-        &<ZcashUnifiedSpendingKey as Into<UnifiedSpendingKey>>::into((*usk).clone()),
-        &addresses[..],
-        &((*memo).clone().into()),
-        min_confirmations,
-    ) {
-        Ok(txid) => {
-            let x: ZcashTxId = txid.into();
-            Ok(Arc::new(x))
+        account,
+        input_selector,
+        request,
+        spending.min_confirmations,
+    )
+    .map_err(spend_error)?;
+    z_db_data.check_spending_limits(account.into(), &proposal)?;
+
+    if let Some(approver) = &spending.approver {
+        let summary = ZcashProposal::from_upstream(
+            params,
+            account.into(),
+            spending.min_confirmations.get(),
+            spending.expiry_delta,
+            &proposal,
+        )?;
+        if !approver.approve(Arc::new(summary)) {
+            return Err(ZcashError::SpendNotApproved);
         }
-        Err(x) => Err(ZcashError::Message {
-            error: format!("spending error: {:?}", x),
-        }),
     }
+
+    create_proposed_transaction_expiring(
+        wallet_db,
+        params,
+        spending.prover,
+        spending.usk,
+        spending.ovk_policy,
+        &proposal,
+        spending.min_confirmations,
+        spending.change_memo,
+        spending.expiry_delta,
+    )
 }
 
-#[allow(clippy::too_many_arguments)]
-#[allow(clippy::type_complexity)]
-pub fn shield_transparent_funds_test_zip317(
-    z_db_data: Arc<ZcashWalletDb>,
+/// The same as [`wallet::shield_transparent_funds`], but expiring the transaction
+/// `expiry_delta` blocks after its target height.
+fn propose_and_shield<InputsT>(
+    wallet_db: &mut UpstreamWalletDb,
     params: ZcashConsensusParameters,
-    prover: Arc<ZcashLocalTxProver>,
-    input_selector: Arc<ZcashTestZip317GreedyInputSelector>,
-    shielding_threshold: u64,
-    usk: Arc<ZcashUnifiedSpendingKey>,
-    from_addrs: Vec<Arc<ZcashTransparentAddress>>,
-    memo: Arc<ZcashMemoBytes>,
-    min_confirmations: u32,
-) -> ZcashResult<Arc<ZcashTxId>> {
-    let min_confirmations = NonZeroU32::new(min_confirmations).unwrap();
-    let shielding_threshold = ZcashNonNegativeAmount::from_u64(shielding_threshold).unwrap();
-    let addresses = from_addrs
-        .iter()
-        .map(|x| x.as_ref().into())
-        .collect::<Vec<TransparentAddress>>();
-
-    let mut db_data = WalletDb::for_path(&z_db_data.path, consensus::TEST_NETWORK).unwrap();
-
-    match wallet::shield_transparent_funds(
-        &mut db_data,
+    input_selector: &InputsT,
+    shielding_threshold: NonNegativeAmount,
+    from_addrs: &[TransparentAddress],
+    spending: Spending,
+) -> ZcashResult<TxId>
+where
+    InputsT: InputSelector<DataSource = UpstreamWalletDb>,
+    InputsT::Error: Debug,
+    <InputsT::FeeRule as FeeRule>::Error: Debug,
+{
+    let proposal = wallet::propose_shielding::<_, _, _, Infallible>(
+        wallet_db,
         &params,
-        <ZcashLocalTxProver as Into<LocalTxProver>>::into((*prover).clone()),
-        &<ZcashTestZip317GreedyInputSelector as Into<TestZip317GreedyInputSelector>>::into(
-            (*input_selector).clone(),
-        ),
-        shielding_threshold.into(),
-        &<ZcashUnifiedSpendingKey as Into<UnifiedSpendingKey>>::into((*usk).clone()),
-        &addresses[..],
-        &((*memo).clone().into()),
-        min_confirmations,
-    ) {
-        Ok(txid) => {
-            let x: ZcashTxId = txid.into();
-            Ok(Arc::new(x))
-        }
-        Err(x) => Err(ZcashError::Message {
-            error: format!("spending error: {:?}", x),
-        }),
-    }
+        input_selector,
+        shielding_threshold,
+        from_addrs,
+        spending.min_confirmations,
+    )
+    .map_err(shielding_error)?;
+
+    create_proposed_transaction_expiring(
+        wallet_db,
+        params,
+        spending.prover,
+        spending.usk,
+        spending.ovk_policy,
+        &proposal,
+        spending.min_confirmations,
+        spending.change_memo,
+        spending.expiry_delta,
+    )
 }
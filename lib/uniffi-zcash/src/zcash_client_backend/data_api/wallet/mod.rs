@@ -3,3 +3,6 @@
 
 mod default;
 pub use self::default::*;
+
+mod proposed;
+pub(crate) use self::proposed::*;
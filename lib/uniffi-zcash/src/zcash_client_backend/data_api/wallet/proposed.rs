@@ -0,0 +1,255 @@
+use std::fmt::Debug;
+use std::num::NonZeroU32;
+
+use rusqlite::Connection;
+use zcash_client_backend::address::RecipientAddress;
+use zcash_client_backend::data_api::wallet::input_selection::Proposal;
+use zcash_client_backend::data_api::{
+    PoolType, Recipient, SentTransaction, SentTransactionOutput, ShieldedProtocol,
+    WalletCommitmentTrees, WalletRead, WalletWrite,
+};
+use zcash_client_backend::fees::ChangeValue;
+use zcash_client_backend::keys::UnifiedSpendingKey;
+use zcash_client_backend::wallet::{OvkPolicy, WalletTransparentOutput};
+use zcash_client_sqlite::{ReceivedNoteId, WalletDb};
+use zcash_primitives::memo::MemoBytes;
+use zcash_primitives::sapling::keys::OutgoingViewingKey;
+use zcash_primitives::sapling::note_encryption::{
+    try_sapling_note_decryption, PreparedIncomingViewingKey,
+};
+use zcash_primitives::sapling::prover::TxProver;
+use zcash_primitives::sapling::{self, Node};
+use zcash_primitives::transaction::builder::Builder;
+use zcash_primitives::transaction::fees::FeeRule;
+use zcash_primitives::transaction::TxId;
+use zcash_primitives::zip32::Scope;
+
+use crate::{builder_target_height, ZcashConsensusParameters, ZcashError, ZcashResult};
+
+/// The same as upstream `wallet::create_proposed_transaction`, but creating the
+/// transaction to expire `expiry_delta` blocks after the target height of the proposal,
+/// where upstream always uses the [`DEFAULT_TX_EXPIRY_DELTA`].
+///
+/// [`DEFAULT_TX_EXPIRY_DELTA`]: crate::DEFAULT_TX_EXPIRY_DELTA
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_proposed_transaction_expiring<FeeRuleT>(
+    db: &mut WalletDb<Connection, ZcashConsensusParameters>,
+    params: ZcashConsensusParameters,
+    prover: impl TxProver,
+    usk: &UnifiedSpendingKey,
+    ovk_policy: OvkPolicy,
+    proposal: &Proposal<FeeRuleT, ReceivedNoteId>,
+    min_confirmations: NonZeroU32,
+    change_memo: Option<MemoBytes>,
+    expiry_delta: u32,
+) -> ZcashResult<TxId>
+where
+    FeeRuleT: FeeRule,
+    FeeRuleT::Error: Debug,
+{
+    let builder_height = builder_target_height(params, proposal.min_target_height(), expiry_delta)?;
+
+    let account = db
+        .get_account_for_ufvk(&usk.to_unified_full_viewing_key())
+        .map_err(creation_err)?
+        .ok_or("The spending key is not of any account of the wallet")?;
+    let dfvk = usk.sapling().to_diversifiable_full_viewing_key();
+
+    let external_ovk = match ovk_policy {
+        OvkPolicy::Sender => Some(dfvk.to_ovk(Scope::External)),
+        OvkPolicy::Custom(ovk) => Some(ovk),
+        OvkPolicy::Discard => None,
+    };
+    // Shielding transactions pay to the account itself, with the transparent internal OVK.
+    let internal_ovk = if proposal.is_shielding() {
+        OutgoingViewingKey(
+            usk.transparent()
+                .to_account_pubkey()
+                .internal_ovk()
+                .as_bytes(),
+        )
+    } else {
+        dfvk.to_ovk(Scope::Internal)
+    };
+
+    let mut builder = Builder::new(params, builder_height, None);
+
+    let checkpoint_depth = db
+        .get_checkpoint_depth(min_confirmations)
+        .map_err(creation_err)?;
+    let spends = db
+        .with_sapling_tree_mut(|tree| {
+            let anchor = tree.root_at_checkpoint(checkpoint_depth)?;
+            proposal
+                .sapling_inputs()
+                .iter()
+                .map(|input| {
+                    tree.witness_caching(input.note_commitment_tree_position, checkpoint_depth)
+                        .map(|merkle_path| (input, merkle_path))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(|spends| (anchor, spends))
+        })
+        .map_err(creation_err);
+    let (anchor, spends) = spends?;
+    for (input, merkle_path) in spends {
+        // The note is received at an external or an internal address of the account, the
+        // one whose note commitment is in the tree.
+        let note_at = |address: Option<sapling::PaymentAddress>| {
+            address
+                .map(|address| address.create_note(input.note_value.into(), input.rseed))
+                .filter(|note| merkle_path.root(Node::from_cmu(&note.cmu())) == anchor)
+        };
+        let (note, extsk) = match note_at(dfvk.diversified_address(input.diversifier)) {
+            Some(note) => (note, usk.sapling().clone()),
+            None => note_at(dfvk.diversified_change_address(input.diversifier))
+                .map(|note| (note, usk.sapling().derive_internal()))
+                .ok_or_else(|| ZcashError::Message {
+                    error: format!(
+                        "The note at {} is not of the account",
+                        u64::from(input.note_commitment_tree_position)
+                    ),
+                })?,
+        };
+        builder.add_sapling_spend(extsk, input.diversifier, note, merkle_path)?;
+    }
+
+    let known_addrs = db
+        .get_transparent_receivers(account)
+        .map_err(creation_err)?;
+    let utxos: Vec<WalletTransparentOutput> = proposal.transparent_inputs().to_vec();
+    for utxo in &utxos {
+        let child_index = known_addrs
+            .get(utxo.recipient_address())
+            .and_then(|metadata| u32::try_from(*metadata.diversifier_index()).ok())
+            .ok_or("The proposal spends outputs of unknown addresses")?;
+        let secret_key = usk.transparent().derive_external_secret_key(child_index)?;
+        builder.add_transparent_input(secret_key, utxo.outpoint().clone(), utxo.txout().clone())?;
+    }
+
+    let mut sapling_output_meta = vec![];
+    let mut transparent_output_meta = vec![];
+    for payment in proposal.transaction_request().payments() {
+        let memo = || payment.memo.clone().unwrap_or_else(MemoBytes::empty);
+        match &payment.recipient_address {
+            RecipientAddress::Unified(ua) => {
+                let address = ua
+                    .sapling()
+                    .ok_or("Only the Sapling receivers of unified addresses can be paid")?;
+                builder.add_sapling_output(external_ovk, *address, payment.amount, memo())?;
+                sapling_output_meta.push((
+                    Recipient::Unified(ua.clone(), PoolType::Shielded(ShieldedProtocol::Sapling)),
+                    payment.amount,
+                    Some(memo()),
+                ));
+            }
+            RecipientAddress::Shielded(address) => {
+                builder.add_sapling_output(external_ovk, *address, payment.amount, memo())?;
+                sapling_output_meta.push((
+                    Recipient::Sapling(*address),
+                    payment.amount,
+                    Some(memo()),
+                ));
+            }
+            RecipientAddress::Transparent(address) => {
+                if payment.memo.is_some() {
+                    return Err("Transparent payments cannot have a memo".into());
+                }
+                builder.add_transparent_output(address, payment.amount)?;
+                transparent_output_meta.push((*address, payment.amount));
+            }
+        }
+    }
+
+    for change in proposal.balance().proposed_change() {
+        match change {
+            ChangeValue::Sapling(amount) => {
+                let memo = change_memo.clone().unwrap_or_else(MemoBytes::empty);
+                builder.add_sapling_output(
+                    Some(internal_ovk),
+                    dfvk.change_address().1,
+                    *amount,
+                    memo.clone(),
+                )?;
+                sapling_output_meta.push((
+                    Recipient::InternalAccount(
+                        account,
+                        PoolType::Shielded(ShieldedProtocol::Sapling),
+                    ),
+                    *amount,
+                    Some(memo),
+                ));
+            }
+        }
+    }
+
+    let (tx, sapling_build_meta) = builder
+        .build(&prover, proposal.fee_rule())
+        .map_err(creation_err)?;
+
+    let internal_ivk = PreparedIncomingViewingKey::new(&dfvk.to_ivk(Scope::Internal));
+    let mut outputs = Vec::with_capacity(sapling_output_meta.len() + transparent_output_meta.len());
+    for (index, (recipient, value, memo)) in sapling_output_meta.into_iter().enumerate() {
+        let output_index = sapling_build_meta
+            .output_index(index)
+            .ok_or("An output is missing from the transaction")?;
+        let received_as = match recipient {
+            Recipient::InternalAccount(account, PoolType::Shielded(ShieldedProtocol::Sapling)) => {
+                tx.sapling_bundle().and_then(|bundle| {
+                    try_sapling_note_decryption(
+                        &params,
+                        proposal.min_target_height(),
+                        &internal_ivk,
+                        &bundle.shielded_outputs()[output_index],
+                    )
+                    .map(|(note, _, _)| (account, note))
+                })
+            }
+            _ => None,
+        };
+        outputs.push(SentTransactionOutput::from_parts(
+            output_index,
+            recipient,
+            value,
+            memo,
+            received_as,
+        ));
+    }
+    for (address, value) in transparent_output_meta {
+        let script = address.script();
+        let output_index = tx
+            .transparent_bundle()
+            .and_then(|bundle| {
+                bundle
+                    .vout
+                    .iter()
+                    .position(|txout| txout.script_pubkey == script)
+            })
+            .ok_or("An output is missing from the transaction")?;
+        outputs.push(SentTransactionOutput::from_parts(
+            output_index,
+            Recipient::Transparent(address),
+            value,
+            None,
+            None,
+        ));
+    }
+
+    db.store_sent_tx(&SentTransaction {
+        tx: &tx,
+        created: time::OffsetDateTime::now_utc(),
+        account,
+        outputs,
+        fee_amount: proposal.balance().fee_required(),
+        utxos_spent: utxos.iter().map(|utxo| utxo.outpoint().clone()).collect(),
+    })
+    .map_err(creation_err)?;
+
+    Ok(tx.txid())
+}
+
+fn creation_err(error: impl Debug) -> ZcashError {
+    ZcashError::Message {
+        error: format!("Cannot create the proposed transaction: {:?}", error),
+    }
+}
@@ -3,8 +3,9 @@
 use zcash_client_backend::encoding;
 
 use crate::{
-    ZcashConsensusParameters, ZcashError, ZcashExtendedFullViewingKey, ZcashExtendedSpendingKey,
-    ZcashPaymentAddress, ZcashResult, ZcashTransparentAddress,
+    zcash_address::is_sprout_address, ZcashConsensusParameters, ZcashError,
+    ZcashExtendedFullViewingKey, ZcashExtendedSpendingKey, ZcashPaymentAddress, ZcashResult,
+    ZcashTransparentAddress,
 };
 
 /// Writes an [`ExtendedSpendingKey`] as a Bech32-encoded string.
@@ -59,6 +60,9 @@ pub fn encode_payment_address_p(
 
 /// Decodes a [`PaymentAddress`] from a Bech32-encoded string.
 pub fn decode_payment_address(hrp: &str, s: &str) -> ZcashResult<Arc<ZcashPaymentAddress>> {
+    if is_sprout_address(s) {
+        return Err(ZcashError::SproutNotSupported);
+    }
     encoding::decode_payment_address(hrp, s)
         .map_err(From::from)
         .map(From::from)
@@ -90,6 +94,9 @@ pub fn decode_transparent_address(
     script_version: &[u8],
     s: &str,
 ) -> ZcashResult<Arc<ZcashTransparentAddress>> {
+    if is_sprout_address(s) {
+        return Err(ZcashError::SproutNotSupported);
+    }
     encoding::decode_transparent_address(pubkey_version, script_version, s)
         .map_err(ZcashError::from)?
         .map(From::from)
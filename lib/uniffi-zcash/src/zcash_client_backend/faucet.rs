@@ -0,0 +1,154 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use hyper::client::HttpConnector;
+use hyper::header::{HeaderValue, CONTENT_TYPE, USER_AGENT};
+use hyper::{Body, Client, Method, Request, Uri};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use rusqlite::{named_params, Connection, OptionalExtension};
+use serde_json::{json, Value};
+use tokio::runtime::Runtime;
+use zcash_primitives::transaction::TxId;
+
+use super::node_rpc::parse_txid;
+use crate::{
+    ZcashConsensusParameters, ZcashError, ZcashNetworkConfig, ZcashRecipientAddress, ZcashResult,
+    ZcashTxId, ZcashWalletDb,
+};
+
+/// How often [`ZcashTestnetFaucet::wait_for_funding`] looks for the funding transaction.
+const FUNDING_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Syncs the wallet while waiting for the funding transaction, as the app would do it,
+/// like by downloading and scanning the new blocks.
+pub trait ZcashWalletSyncer: Send + Sync {
+    fn sync(&self);
+}
+
+/// A client for a testnet faucet, for example apps and integration tests to fund their
+/// wallets. Only part of the library built with the `faucet` feature, so that release
+/// builds cannot request funds.
+///
+/// The faucet is asked funds with a `POST` of `{"address": ..., "amount": ...}` to its
+/// url, the amount being in zatoshis and omitted to get the default one of the faucet. It
+/// replies with `{"txid": ...}`, the id being in the byte order nodes show them.
+pub struct ZcashTestnetFaucet {
+    runtime: Runtime,
+    client: Client<HttpsConnector<HttpConnector>>,
+    uri: Uri,
+    user_agent: Option<HeaderValue>,
+}
+
+impl ZcashTestnetFaucet {
+    /// Targets the faucet at the given `http` or `https` url.
+    pub fn new(url: String, config: ZcashNetworkConfig) -> ZcashResult<Self> {
+        let uri: Uri = url.parse().map_err(|e| ZcashError::Message {
+            error: format!("Invalid faucet url: {}", e),
+        })?;
+        let user_agent = config
+            .user_agent
+            .map(|user_agent| HeaderValue::try_from(user_agent).map_err(|_| "Invalid user agent"))
+            .transpose()?;
+        let connector = HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_or_http()
+            .enable_http1()
+            .build();
+
+        Ok(Self {
+            runtime: Runtime::new()?,
+            client: Client::builder().build(connector),
+            uri,
+            user_agent,
+        })
+    }
+
+    /// Requests the given amount of zatoshis, or the default one of the faucet, to be sent
+    /// to the given testnet address. Returns the id of the funding transaction.
+    pub fn request_funds(
+        &self,
+        address: String,
+        amount: Option<u64>,
+    ) -> ZcashResult<Arc<ZcashTxId>> {
+        ZcashRecipientAddress::decode(ZcashConsensusParameters::TestNetwork, &address)?;
+
+        let mut body = json!({ "address": address });
+        if let Some(amount) = amount {
+            body["amount"] = json!(amount);
+        }
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(self.uri.clone())
+            .header(CONTENT_TYPE, "application/json");
+        if let Some(user_agent) = &self.user_agent {
+            request = request.header(USER_AGENT, user_agent);
+        }
+        let request =
+            request
+                .body(Body::from(body.to_string()))
+                .map_err(|e| ZcashError::Message {
+                    error: format!("Invalid faucet request: {}", e),
+                })?;
+
+        let (status, bytes) = self.runtime.block_on(async {
+            let response = self.client.request(request).await?;
+            let status = response.status();
+            hyper::body::to_bytes(response.into_body())
+                .await
+                .map(|bytes| (status, bytes))
+        })?;
+        if !status.is_success() {
+            return Err(ZcashError::Message {
+                error: format!(
+                    "Faucet replied with status {}: {}",
+                    status,
+                    String::from_utf8_lossy(&bytes)
+                ),
+            });
+        }
+
+        let reply: Value = serde_json::from_slice(&bytes).map_err(|_| "Invalid faucet reply")?;
+        Ok(Arc::new(parse_txid(&reply["txid"])?))
+    }
+
+    /// Waits for the funding transaction to be in the testnet wallet, syncing it with the
+    /// given syncer, if any, before each look. Returns the height the transaction was
+    /// mined at, or none if the wallet only has it from the mempool. Fails if it is still
+    /// not in the wallet after `timeout_secs` seconds.
+    pub fn wait_for_funding(
+        &self,
+        wallet_db: Arc<ZcashWalletDb>,
+        txid: Arc<ZcashTxId>,
+        timeout_secs: u32,
+        syncer: Option<Box<dyn ZcashWalletSyncer>>,
+    ) -> ZcashResult<Option<u32>> {
+        if wallet_db.params != ZcashConsensusParameters::TestNetwork {
+            return Err("The faucet only funds testnet wallets".into());
+        }
+        let txid: TxId = (*txid).into();
+        let deadline = Instant::now() + Duration::from_secs(timeout_secs.into());
+        loop {
+            if let Some(syncer) = &syncer {
+                syncer.sync();
+            }
+            let conn = Connection::open(&wallet_db.path)?;
+            let height = conn
+                .query_row(
+                    "SELECT block FROM transactions WHERE txid = :txid",
+                    named_params![":txid": txid.as_ref()],
+                    |row| row.get::<_, Option<u32>>(0),
+                )
+                .optional()?;
+            if let Some(height) = height {
+                return Ok(height);
+            }
+            if Instant::now() >= deadline {
+                return Err(ZcashError::Message {
+                    error: format!("The funding transaction {} is not in the wallet", txid),
+                });
+            }
+            thread::sleep(FUNDING_POLL_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hmac::Hmac;
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha512;
+use zeroize::Zeroizing;
+
+use crate::{latest_keys_era, ZcashResult, ZcashUnifiedSpendingKey};
+
+/// The version of the backup format, the first byte of the backups.
+const BACKUP_VERSION: u8 = 1;
+/// The PBKDF2-HMAC-SHA512 iterations new backups are encrypted with, as recommended by
+/// OWASP. The count is stored in the backups, so it can be raised later on.
+const KDF_ITERATIONS: u32 = 210_000;
+/// The most iterations backups are decrypted with, so that a tampered backup cannot make
+/// the import run for hours.
+const MAX_KDF_ITERATIONS: u32 = 10_000_000;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+/// The version, the iteration count, the salt and the nonce.
+const HEADER_LENGTH: usize = 1 + 4 + SALT_LENGTH + NONCE_LENGTH;
+
+/// Encrypts the spending key with the passphrase, so that it can be backed up where it
+/// may be read by others, like a cloud storage.
+///
+/// The key is derived from the passphrase with PBKDF2-HMAC-SHA512 and a random salt, and
+/// the spending key, in the latest keys era, is encrypted with XChaCha20-Poly1305. The
+/// header of the backup, holding the KDF parameters, is authenticated along with it.
+pub fn export_usk_encrypted(
+    usk: Arc<ZcashUnifiedSpendingKey>,
+    passphrase: String,
+) -> ZcashResult<Vec<u8>> {
+    let passphrase = Zeroizing::new(passphrase);
+    let mut header = Vec::with_capacity(HEADER_LENGTH);
+    header.push(BACKUP_VERSION);
+    header.extend_from_slice(&KDF_ITERATIONS.to_le_bytes());
+    let mut random = [0u8; SALT_LENGTH + NONCE_LENGTH];
+    OsRng.fill_bytes(&mut random);
+    header.extend_from_slice(&random);
+
+    let (salt, nonce) = random.split_at(SALT_LENGTH);
+    let cipher = backup_cipher(&passphrase, salt, KDF_ITERATIONS);
+    let plaintext = Zeroizing::new(usk.to_bytes(latest_keys_era()));
+    let ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &header,
+            },
+        )
+        .map_err(|_| "Unable to encrypt the spending key")?;
+
+    header.extend(ciphertext);
+    Ok(header)
+}
+
+/// Decrypts a spending key backed up with [`export_usk_encrypted`].
+pub fn import_usk_encrypted(
+    blob: Vec<u8>,
+    passphrase: String,
+) -> ZcashResult<Arc<ZcashUnifiedSpendingKey>> {
+    let passphrase = Zeroizing::new(passphrase);
+    if blob.len() < HEADER_LENGTH {
+        return Err("The backup is truncated".into());
+    }
+    let (header, ciphertext) = blob.split_at(HEADER_LENGTH);
+    if header[0] != BACKUP_VERSION {
+        return Err("Unsupported backup version".into());
+    }
+    let iterations = u32::from_le_bytes(header[1..5].try_into().expect("4 bytes"));
+    if iterations > MAX_KDF_ITERATIONS {
+        return Err("Invalid backup key derivation parameters".into());
+    }
+    let (salt, nonce) = header[5..].split_at(SALT_LENGTH);
+
+    let cipher = backup_cipher(&passphrase, salt, iterations);
+    let plaintext = Zeroizing::new(
+        cipher
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: header,
+                },
+            )
+            .map_err(|_| "Wrong passphrase or corrupted backup")?,
+    );
+
+    ZcashUnifiedSpendingKey::decode(plaintext.to_vec())
+        .map(Arc::new)
+        .map_err(|e| format!("Invalid backed up key: {}", e).into())
+}
+
+fn backup_cipher(passphrase: &str, salt: &[u8], iterations: u32) -> XChaCha20Poly1305 {
+    let mut key = Zeroizing::new([0u8; 32]);
+    pbkdf2::pbkdf2::<Hmac<Sha512>>(passphrase.as_bytes(), salt, iterations, key.as_mut());
+    XChaCha20Poly1305::new(key.as_ref().into())
+}
@@ -1,4 +1,7 @@
 use zcash_client_backend::keys::Era;
+use zcash_primitives::consensus::BranchId;
+
+use crate::ZcashDecodingError;
 
 /// A version identifier for the encoding of unified spending keys.
 ///
@@ -6,12 +9,23 @@
 /// parsed from an encoded form tagged with that era's identifier is expected to provide
 /// sufficient spending authority to spend any non-Sprout shielded note created in a transaction
 /// within the era's block range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ZcashKeysEra {
     /// The Orchard era begins at Orchard activation, and will end if a new pool that requires a
     /// change to unified spending keys is introduced.
     Orchard,
 }
 
+impl ZcashKeysEra {
+    /// The identifier the encoding of keys is tagged with, which is the consensus branch id
+    /// of the network upgrade that introduced the era.
+    fn id(&self) -> u32 {
+        match self {
+            ZcashKeysEra::Orchard => u32::from(BranchId::Nu5),
+        }
+    }
+}
+
 impl From<ZcashKeysEra> for Era {
     fn from(value: ZcashKeysEra) -> Self {
         match value {
@@ -19,3 +33,35 @@ fn from(value: ZcashKeysEra) -> Self {
         }
     }
 }
+
+impl From<Era> for ZcashKeysEra {
+    fn from(value: Era) -> Self {
+        match value {
+            Era::Orchard => ZcashKeysEra::Orchard,
+        }
+    }
+}
+
+/// The era new keys are encoded in. Keys of older eras should be decoded and encoded
+/// again in this era.
+pub fn latest_keys_era() -> ZcashKeysEra {
+    ZcashKeysEra::Orchard
+}
+
+/// Returns the era an encoded unified spending key is tagged with, without decoding
+/// the key itself.
+pub fn decode_keys_era(encoded: Vec<u8>) -> Result<ZcashKeysEra, ZcashDecodingError> {
+    let tag = encoded
+        .get(..4)
+        .ok_or(ZcashDecodingError::ReadError {
+            v: "era".to_string(),
+        })?
+        .try_into()
+        .map(u32::from_le_bytes)
+        .expect("the tag is 4 bytes long");
+
+    [ZcashKeysEra::Orchard]
+        .into_iter()
+        .find(|era| era.id() == tag)
+        .ok_or(ZcashDecodingError::EraInvalid)
+}
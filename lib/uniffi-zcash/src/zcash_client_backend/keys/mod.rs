@@ -1,19 +1,68 @@
+use zcash_client_backend::keys::DecodingError;
+
+mod backup;
+pub use self::backup::*;
+
 mod era;
 pub use self::era::*;
 
+mod seed_shares;
+pub use self::seed_shares::*;
+
+mod unified_address_request;
+pub use self::unified_address_request::*;
+
 mod unified_full_viewing_key;
 pub use self::unified_full_viewing_key::*;
 
 mod unified_spending_key;
 pub use self::unified_spending_key::*;
 
-// #[derive(Debug, PartialEq, Eq)]
+/// Errors decoding keys from their serialized representations.
+#[derive(Debug, thiserror::Error)]
 pub enum ZcashDecodingError {
-    // ReadError(&'static str),
-    // EraInvalid,
-    EraMismatch(self::era::ZcashKeysEra), // TypecodeInvalid,
-                                          // LengthInvalid,
-                                          // LengthMismatch(Typecode, u32),
-                                          // InsufficientData(Typecode),
-                                          // KeyDataInvalid(Typecode),
+    /// The encoding ended before the named part could be read.
+    #[error("Cannot read the {v} of the key")]
+    ReadError { v: String },
+    /// The era tag is not one of a known [`ZcashKeysEra`].
+    #[error("The key is tagged with an unknown era")]
+    EraInvalid,
+    /// The key is tagged with another era than the expected one, wrapped.
+    #[error("The key is tagged with the {v:?} era")]
+    EraMismatch { v: ZcashKeysEra },
+    #[error("The key has an invalid typecode")]
+    TypecodeInvalid,
+    #[error("The key has an invalid length")]
+    LengthInvalid,
+    /// The key of the given typecode has an unexpected length.
+    #[error("The key of typecode {typecode} has an invalid length {length}")]
+    LengthMismatch { typecode: u32, length: u32 },
+    /// The key of the wrapped typecode is truncated.
+    #[error("The key of typecode {v} is truncated")]
+    InsufficientData { v: u32 },
+    /// The key of the wrapped typecode is not a valid key.
+    #[error("The key of typecode {v} is invalid")]
+    KeyDataInvalid { v: u32 },
+}
+
+impl From<DecodingError> for ZcashDecodingError {
+    fn from(error: DecodingError) -> Self {
+        match error {
+            DecodingError::ReadError(v) => ZcashDecodingError::ReadError { v: v.to_string() },
+            DecodingError::EraInvalid => ZcashDecodingError::EraInvalid,
+            DecodingError::EraMismatch(era) => ZcashDecodingError::EraMismatch { v: era.into() },
+            DecodingError::TypecodeInvalid => ZcashDecodingError::TypecodeInvalid,
+            DecodingError::LengthInvalid => ZcashDecodingError::LengthInvalid,
+            DecodingError::LengthMismatch(typecode, length) => ZcashDecodingError::LengthMismatch {
+                typecode: typecode.into(),
+                length,
+            },
+            DecodingError::InsufficientData(typecode) => {
+                ZcashDecodingError::InsufficientData { v: typecode.into() }
+            }
+            DecodingError::KeyDataInvalid(typecode) => {
+                ZcashDecodingError::KeyDataInvalid { v: typecode.into() }
+            }
+        }
+    }
 }
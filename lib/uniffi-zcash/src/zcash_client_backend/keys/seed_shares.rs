@@ -0,0 +1,166 @@
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::ZcashResult;
+
+/// The version of the share format, the first byte of the shares.
+const SHARE_VERSION: u8 = 1;
+/// The version, the identifier, the threshold and the index of the share.
+const SHARE_HEADER_LENGTH: usize = 1 + 2 + 1 + 1;
+/// The bytes of the SHA-256 digest of the seed that are split along with it, so that
+/// combining wrong shares is detected.
+const CHECKSUM_LENGTH: usize = 4;
+
+/// Splits the seed into `share_count` shares, any `threshold` of which recover it with
+/// [`combine_seed_shares`], while fewer tell nothing about it, for social recovery
+/// backups.
+///
+/// As in SLIP-39, it is Shamir's secret sharing over GF(256), but the shares are binary
+/// rather than mnemonics. Each share holds a random identifier of the split, the
+/// threshold and its index, so that shares of different splits are not mixed up.
+pub fn split_seed(seed: Vec<u8>, threshold: u8, share_count: u8) -> ZcashResult<Vec<Vec<u8>>> {
+    let seed = Zeroizing::new(seed);
+    if seed.is_empty() {
+        return Err("The seed is empty".into());
+    }
+    if threshold == 0 || threshold > share_count {
+        return Err("The threshold must be between 1 and the number of shares".into());
+    }
+    if share_count == u8::MAX {
+        return Err("At most 254 shares can be made".into());
+    }
+
+    let mut secret = Zeroizing::new(seed.to_vec());
+    secret.extend_from_slice(&Sha256::digest(&seed[..])[..CHECKSUM_LENGTH]);
+    let mut identifier = [0u8; 2];
+    OsRng.fill_bytes(&mut identifier);
+
+    let mut shares: Vec<Vec<u8>> = (1..=share_count)
+        .map(|index| {
+            let mut share = Vec::with_capacity(SHARE_HEADER_LENGTH + secret.len());
+            share.push(SHARE_VERSION);
+            share.extend_from_slice(&identifier);
+            share.push(threshold);
+            share.push(index);
+            share
+        })
+        .collect();
+
+    // Each byte of the secret is the constant term of a random polynomial of degree
+    // `threshold - 1`, and each share holds the value of the polynomial at its index.
+    let mut coefficients = Zeroizing::new(vec![0u8; usize::from(threshold)]);
+    for &byte in secret.iter() {
+        OsRng.fill_bytes(&mut coefficients[1..]);
+        coefficients[0] = byte;
+        for share in shares.iter_mut() {
+            let x = share[SHARE_HEADER_LENGTH - 1];
+            let y = coefficients
+                .iter()
+                .rev()
+                .fold(0, |acc, &coefficient| gf_mul(acc, x) ^ coefficient);
+            share.push(y);
+        }
+    }
+
+    Ok(shares)
+}
+
+/// Recovers the seed from at least the threshold of the shares [`split_seed`] made.
+///
+/// Fails if there are not enough shares, if they are from different splits, or if
+/// they do not recover the original seed, as when one of them is corrupted.
+pub fn combine_seed_shares(shares: Vec<Vec<u8>>) -> ZcashResult<Vec<u8>> {
+    let first = shares.first().ok_or("No shares")?;
+    if first.len() <= SHARE_HEADER_LENGTH + CHECKSUM_LENGTH {
+        return Err("The share is truncated".into());
+    }
+    if first[0] != SHARE_VERSION {
+        return Err("Unsupported share version".into());
+    }
+    let header = &first[..SHARE_HEADER_LENGTH - 1];
+    let threshold = usize::from(header[3]);
+
+    let mut selected: Vec<&Vec<u8>> = Vec::with_capacity(threshold);
+    for share in &shares {
+        if share.len() != first.len() || share[..SHARE_HEADER_LENGTH - 1] != *header {
+            return Err("The shares are from different splits".into());
+        }
+        let index = share[SHARE_HEADER_LENGTH - 1];
+        if index == 0 {
+            return Err("Invalid share index".into());
+        }
+        if selected.len() < threshold
+            && selected
+                .iter()
+                .all(|other| other[SHARE_HEADER_LENGTH - 1] != index)
+        {
+            selected.push(share);
+        }
+    }
+    if threshold == 0 || selected.len() < threshold {
+        return Err(format!("{} distinct shares are needed", threshold).into());
+    }
+
+    // Lagrange interpolation of the polynomials at zero.
+    let xs: Vec<u8> = selected
+        .iter()
+        .map(|share| share[SHARE_HEADER_LENGTH - 1])
+        .collect();
+    let weights: Vec<u8> = xs
+        .iter()
+        .enumerate()
+        .map(|(i, &xi)| {
+            let (numerator, denominator) = xs
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold((1, 1), |(num, den), (_, &xj)| {
+                    (gf_mul(num, xj), gf_mul(den, xi ^ xj))
+                });
+            gf_mul(numerator, gf_inv(denominator))
+        })
+        .collect();
+    let secret = Zeroizing::new(
+        (SHARE_HEADER_LENGTH..first.len())
+            .map(|position| {
+                selected
+                    .iter()
+                    .zip(&weights)
+                    .fold(0, |acc, (share, &weight)| {
+                        acc ^ gf_mul(share[position], weight)
+                    })
+            })
+            .collect::<Vec<u8>>(),
+    );
+
+    let (seed, checksum) = secret.split_at(secret.len() - CHECKSUM_LENGTH);
+    if Sha256::digest(seed)[..CHECKSUM_LENGTH] != *checksum {
+        return Err("The shares do not recover the seed".into());
+    }
+    Ok(seed.to_vec())
+}
+
+/// Multiplication in GF(256) modulo the AES polynomial, without secret dependent
+/// branches or table lookups.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    for _ in 0..8 {
+        product ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    product
+}
+
+/// The multiplicative inverse in GF(256), as `a^254`, that is `a^2 * a^4 * ... * a^128`.
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1;
+    let mut power = a;
+    for _ in 1..8 {
+        power = gf_mul(power, power);
+        result = gf_mul(result, power);
+    }
+    result
+}
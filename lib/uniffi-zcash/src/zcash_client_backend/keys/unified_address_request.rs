@@ -0,0 +1,47 @@
+use zcash_client_backend::address::UnifiedAddress;
+
+use crate::{ZcashError, ZcashResult};
+
+/// Specification for how a unified address should be generated from a unified viewing
+/// key: the receivers it must include. At least one shielded receiver is required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZcashUnifiedAddressRequest {
+    pub has_orchard: bool,
+    pub has_sapling: bool,
+    pub has_p2pkh: bool,
+}
+
+impl ZcashUnifiedAddressRequest {
+    pub(crate) fn validate(&self) -> ZcashResult<()> {
+        if !self.has_orchard && !self.has_sapling {
+            return Err(ZcashError::Message {
+                error: "A unified address requires at least one shielded receiver".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Keeps the requested receivers of the given address, failing if the address lacks
+    /// any of them.
+    pub(crate) fn select_receivers(&self, addr: &UnifiedAddress) -> ZcashResult<UnifiedAddress> {
+        self.validate()?;
+
+        let missing = |receiver: &str| ZcashError::Message {
+            error: format!("The unified address has no {} receiver", receiver),
+        };
+        let orchard = match self.has_orchard {
+            true => Some(*addr.orchard().ok_or_else(|| missing("orchard"))?),
+            false => None,
+        };
+        let sapling = match self.has_sapling {
+            true => Some(*addr.sapling().ok_or_else(|| missing("sapling"))?),
+            false => None,
+        };
+        let transparent = match self.has_p2pkh {
+            true => Some(*addr.transparent().ok_or_else(|| missing("p2pkh"))?),
+            false => None,
+        };
+
+        UnifiedAddress::from_receivers(orchard, sapling, transparent).ok_or(ZcashError::Unknown)
+    }
+}
@@ -1,19 +1,49 @@
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
+use zcash_address::unified::{Encoding, Fvk, Ufvk};
+use zcash_address::Network;
 use zcash_client_backend::keys::UnifiedFullViewingKey;
 
 use crate::{
-    ZcashAccountPubKey, ZcashConsensusParameters, ZcashDiversifiableFullViewingKey,
-    ZcashDiversifierIndex, ZcashError, ZcashOrchardFullViewingKey, ZcashResult,
-    ZcashUnifiedAddress, ZcashUnifiedAddressAndDiversifierIndex,
+    validation::invalid_argument, ZcashAccountPubKey, ZcashConsensusParameters,
+    ZcashDiversifiableFullViewingKey, ZcashDiversifierIndex, ZcashError,
+    ZcashOrchardFullViewingKey, ZcashResult, ZcashUnifiedAddress,
+    ZcashUnifiedAddressAndDiversifierIndex,
 };
 
 use derive_more::{From, Into};
 
+/// The components of a unified full viewing key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZcashUfvkItem {
+    Transparent,
+    Sapling,
+    Orchard,
+}
+
 /// A [ZIP 316](https://zips.z.cash/zip-0316) unified full viewing key.
 #[derive(Clone, Debug, From, Into)]
 pub struct ZcashUnifiedFullViewingKey(UnifiedFullViewingKey);
 
+/// Upstream keys cannot be compared, so their mainnet encodings are.
+impl PartialEq for ZcashUnifiedFullViewingKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.encode(&ZcashConsensusParameters::MainNetwork)
+            == other.0.encode(&ZcashConsensusParameters::MainNetwork)
+    }
+}
+
+impl Eq for ZcashUnifiedFullViewingKey {}
+
+impl Hash for ZcashUnifiedFullViewingKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0
+            .encode(&ZcashConsensusParameters::MainNetwork)
+            .hash(state)
+    }
+}
+
 impl ZcashUnifiedFullViewingKey {
     /// Construct a new unified full viewing key, if the required components are present.
     pub fn new(
@@ -39,6 +69,49 @@ pub fn decode(params: ZcashConsensusParameters, encoding: &str) -> ZcashResult<S
         Ok(key.into())
     }
 
+    /// Returns a key with only the given components of this one, for sharing a limited
+    /// viewing capability, like the Orchard component only with a service watching the
+    /// incoming payments. As unified keys always have a shielded component, the items
+    /// need to hold one, and this key needs to have all the components asked for.
+    pub fn to_ufvk_with(&self, items: Vec<ZcashUfvkItem>) -> ZcashResult<Arc<Self>> {
+        let missing = |item: ZcashUfvkItem| {
+            invalid_argument("items", format!("the key has no {:?} component", item))
+        };
+        let mut fvks = Vec::new();
+        if items.contains(&ZcashUfvkItem::Orchard) {
+            let fvk = self
+                .0
+                .orchard()
+                .ok_or_else(|| missing(ZcashUfvkItem::Orchard))?;
+            fvks.push(Fvk::Orchard(fvk.to_bytes()));
+        }
+        if items.contains(&ZcashUfvkItem::Sapling) {
+            let dfvk = self
+                .0
+                .sapling()
+                .ok_or_else(|| missing(ZcashUfvkItem::Sapling))?;
+            fvks.push(Fvk::Sapling(dfvk.to_bytes()));
+        }
+        if items.contains(&ZcashUfvkItem::Transparent) {
+            let apk = self
+                .0
+                .transparent()
+                .ok_or_else(|| missing(ZcashUfvkItem::Transparent))?;
+            let bytes = apk
+                .serialize()
+                .try_into()
+                .map_err(|_| "Invalid transparent key")?;
+            fvks.push(Fvk::P2pkh(bytes));
+        }
+        let ufvk = Ufvk::try_from_items(fvks)
+            .map_err(|_| invalid_argument("items", "a Sapling or Orchard component is required"))?;
+
+        // The upstream key can only be made without a Sapling component by parsing it.
+        let params = ZcashConsensusParameters::MainNetwork;
+        let key = UnifiedFullViewingKey::decode(&params, &ufvk.encode(&Network::Main))?;
+        Ok(Arc::new(key.into()))
+    }
+
     /// Returns the string encoding of this `UnifiedFullViewingKey` for the given network.
     pub fn encode(&self, params: ZcashConsensusParameters) -> String {
         self.0.encode(&params)
@@ -1,10 +1,14 @@
 use std::sync::Arc;
 
 use zcash_client_backend::keys::UnifiedSpendingKey;
+use zcash_primitives::zip32::AccountId;
 
 use crate::{
-    ZcashAccountId, ZcashAccountPrivKey, ZcashConsensusParameters, ZcashExtendedSpendingKey,
-    ZcashKeysEra, ZcashOrchardSpendingKey, ZcashResult, ZcashUnifiedFullViewingKey,
+    decode_keys_era,
+    validation::{check_account, check_range, check_seed},
+    TupleAccountIdAndUnifiedSpendingKey, ZcashAccountId, ZcashAccountPrivKey,
+    ZcashConsensusParameters, ZcashDecodingError, ZcashExtendedSpendingKey, ZcashKeysEra,
+    ZcashOrchardSpendingKey, ZcashResult, ZcashUnifiedFullViewingKey,
 };
 
 use derive_more::{From, Into};
@@ -20,6 +24,8 @@ pub fn from_seed(
         seed: Vec<u8>,
         account: ZcashAccountId,
     ) -> ZcashResult<Self> {
+        check_seed("seed", &seed)?;
+        check_account("account", account.id)?;
         let key = UnifiedSpendingKey::from_seed(&params, &seed, account.into())?;
         Ok(key.into())
     }
@@ -57,6 +63,16 @@ pub fn to_bytes(&self, era: ZcashKeysEra) -> Vec<u8> {
         self.0.to_bytes(era.into())
     }
 
+    /// Decodes a [`UnifiedSpendingKey`] value from its serialized representation, in the
+    /// era it is tagged with, so that keys persisted in older eras can still be decoded
+    /// and then migrated by encoding them in the [`latest_keys_era`].
+    pub fn decode(encoded: Vec<u8>) -> Result<Self, ZcashDecodingError> {
+        let era = decode_keys_era(encoded.clone())?;
+        UnifiedSpendingKey::from_bytes(era.into(), &encoded)
+            .map_err(From::from)
+            .map(From::from)
+    }
+
     /// Decodes a [`UnifiedSpendingKey`] value from its serialized representation.
     ///
     /// See [`to_bytes`] for additional detail about the encoded form.
@@ -66,3 +82,27 @@ pub fn from_bytes(era: ZcashKeysEra, encoded: &[u8]) -> ZcashResult<Self> {
             .map(From::from)
     }
 }
+
+/// Derives the unified spending keys of the `start_account..end_account` accounts
+/// from the same seed, in a single call.
+pub fn derive_accounts(
+    params: ZcashConsensusParameters,
+    seed: &[u8],
+    start_account: u32,
+    end_account: u32,
+) -> ZcashResult<Vec<TupleAccountIdAndUnifiedSpendingKey>> {
+    check_seed("seed", seed)?;
+    check_range("start_account..end_account", start_account, end_account)?;
+    if end_account > start_account {
+        check_account("end_account", end_account - 1)?;
+    }
+    (start_account..end_account)
+        .map(|id| {
+            let key = UnifiedSpendingKey::from_seed(&params, seed, AccountId::from(id))?;
+            Ok(TupleAccountIdAndUnifiedSpendingKey {
+                account_id: ZcashAccountId { id },
+                unified_spending_key: Arc::new(key.into()),
+            })
+        })
+        .collect()
+}
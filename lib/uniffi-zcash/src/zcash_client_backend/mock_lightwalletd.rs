@@ -0,0 +1,419 @@
+// The calls fail with the `Status` of gRPC, as those of the generated servers do.
+#![allow(clippy::result_large_err)]
+
+use std::convert::Infallible;
+use std::future::{ready, Ready};
+use std::sync::{Arc, Mutex};
+
+use prost::Message;
+use tokio::net::TcpListener;
+use tokio::runtime::Runtime;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::{http, tokio_stream, Body, BoxFuture, Context, Poll, StdError};
+use tonic::server::{Grpc, NamedService};
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+use tower::{service_fn, Service};
+use uniffi_zcash_test::fixtures::{fake_orchard_action, fake_sapling_output};
+use zcash_client_backend::address::RecipientAddress;
+use zcash_client_backend::data_api::chain::CommitmentTreeRoot;
+use zcash_client_backend::encoding::AddressCodec;
+use zcash_client_backend::proto::compact_formats::{ChainMetadata, CompactBlock, CompactTx};
+use zcash_client_backend::proto::service::{
+    BlockId, BlockRange, ChainSpec, Empty, GetSubtreeRootsArg, LightdInfo, RawTransaction,
+    SendResponse, SubtreeRoot, TransparentAddressBlockFilter, TreeState,
+};
+use zcash_primitives::consensus::{NetworkUpgrade, Parameters, TestNetwork};
+use zcash_primitives::keys::OutgoingViewingKey;
+use zcash_primitives::merkle_tree::{write_commitment_tree, HashSer};
+use zcash_primitives::sapling::{self, CommitmentTree};
+
+use crate::{ZcashCommitmentTreeRoot, ZcashError, ZcashResult};
+
+/// A payment made by a block of [`ZcashMockLightwalletd`].
+pub struct ZcashMockPayment {
+    /// A testnet Sapling, unified or transparent address. Unified addresses are paid to
+    /// their Orchard receiver, if any, and to their Sapling one otherwise.
+    pub address: String,
+    pub value: u64,
+}
+
+/// A `lightwalletd` server serving a made up testnet chain, for testing the wallet against
+/// it without a network. Only part of the library built with the `mock-lightwalletd`
+/// feature.
+///
+/// The chain starts at the given height, with empty note commitment trees, and gets the
+/// blocks of [`Self::push_block`]. Their shielded outputs are made like those of the
+/// fixture wallets, so only their recipients can tell them from real ones. Transparent
+/// payments are not in the compact blocks, so they are only known by the transaction
+/// ids lookups of their addresses.
+///
+/// It serves the calls the library makes, and fails the others as unimplemented.
+pub struct ZcashMockLightwalletd {
+    // Serves the calls until dropped.
+    _runtime: Runtime,
+    url: String,
+    chain: Arc<Mutex<MockChain>>,
+}
+
+impl ZcashMockLightwalletd {
+    pub fn new(start_height: u32) -> ZcashResult<Self> {
+        let sapling_activation = TestNetwork
+            .activation_height(NetworkUpgrade::Sapling)
+            .expect("Sapling is active on testnet");
+        if start_height <= u32::from(sapling_activation) {
+            return Err(ZcashError::Message {
+                error: format!(
+                    "The chain must start after the activation of Sapling at {}",
+                    sapling_activation
+                ),
+            });
+        }
+
+        let chain = Arc::new(Mutex::new(MockChain {
+            start_height,
+            blocks: vec![],
+            sapling_trees: vec![CommitmentTree::empty()],
+            orchard_tree_size: 0,
+            taddress_txs: vec![],
+            subtree_roots: vec![],
+            requests: vec![],
+        }));
+
+        let runtime = Runtime::new()?;
+        let listener = runtime.block_on(TcpListener::bind("127.0.0.1:0"))?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let incoming =
+            TcpIncoming::from_listener(listener, true, None).map_err(|e| ZcashError::Message {
+                error: format!("Cannot serve the mock lightwalletd: {}", e),
+            })?;
+        runtime.spawn(
+            Server::builder()
+                .add_service(CompactTxStreamer(chain.clone()))
+                .serve_with_incoming(incoming),
+        );
+
+        Ok(Self {
+            _runtime: runtime,
+            url,
+            chain,
+        })
+    }
+
+    /// The url to connect the lightwalletd clients to.
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Adds a block making the given payments, each in a transaction of its own. Returns
+    /// its height.
+    pub fn push_block(&self, payments: Vec<ZcashMockPayment>) -> ZcashResult<u32> {
+        self.chain.lock().unwrap().push_block(payments)
+    }
+
+    /// Adds the root of the next completed subtree of the Sapling note commitment tree.
+    /// They are made up, and need not match the blocks of the chain.
+    pub fn add_sapling_subtree_root(&self, root: Arc<ZcashCommitmentTreeRoot>) {
+        let root: CommitmentTreeRoot<sapling::Node> = (*root).clone().into();
+        let mut root_hash = vec![];
+        root.root_hash()
+            .write(&mut root_hash)
+            .expect("writing to a vector does not fail");
+        self.chain.lock().unwrap().subtree_roots.push(SubtreeRoot {
+            root_hash,
+            completing_block_hash: vec![],
+            completing_block_height: u32::from(root.subtree_end_height()).into(),
+        });
+    }
+
+    /// The calls received so far, in order, as their method name followed by the heights
+    /// or indexes they were made with, like `GetTreeState 2199999`.
+    pub fn requests(&self) -> Vec<String> {
+        self.chain.lock().unwrap().requests.clone()
+    }
+}
+
+struct MockChain {
+    start_height: u32,
+    blocks: Vec<CompactBlock>,
+    /// The Sapling tree as of each block, after the one before the chain.
+    sapling_trees: Vec<CommitmentTree>,
+    orchard_tree_size: u32,
+    /// The transparent addresses paid, with the heights they were paid at.
+    taddress_txs: Vec<(String, u32)>,
+    subtree_roots: Vec<SubtreeRoot>,
+    requests: Vec<String>,
+}
+
+impl MockChain {
+    fn tip_height(&self) -> u32 {
+        self.start_height + self.blocks.len() as u32 - 1
+    }
+
+    fn block_hash(&self, height: u32) -> Vec<u8> {
+        let mut hash = vec![0; 32];
+        if height >= self.start_height {
+            hash[..4].copy_from_slice(&height.to_le_bytes());
+        }
+        hash
+    }
+
+    fn push_block(&mut self, payments: Vec<ZcashMockPayment>) -> ZcashResult<u32> {
+        let height = self.start_height + self.blocks.len() as u32;
+        let mut sapling_tree = self.sapling_trees.last().unwrap().clone();
+        let mut vtx = vec![];
+        for (index, payment) in payments.iter().enumerate() {
+            let mut tx = CompactTx {
+                index: index as u64,
+                hash: self.block_hash(height),
+                ..Default::default()
+            };
+            tx.hash[31] = index as u8 + 1;
+
+            match RecipientAddress::decode(&TestNetwork, &payment.address) {
+                Some(RecipientAddress::Shielded(to)) => {
+                    tx.outputs.push(sapling_output(to, height, payment.value))
+                }
+                Some(RecipientAddress::Unified(ua)) => match (ua.orchard(), ua.sapling()) {
+                    (Some(to), _) => tx.actions.push(fake_orchard_action(*to, payment.value)),
+                    (None, Some(to)) => tx.outputs.push(sapling_output(*to, height, payment.value)),
+                    (None, None) => {
+                        return Err(format!("No shielded receiver in {}", payment.address).into())
+                    }
+                },
+                Some(RecipientAddress::Transparent(to)) => {
+                    self.taddress_txs.push((to.encode(&TestNetwork), height));
+                    continue;
+                }
+                None => return Err(format!("Invalid testnet address {}", payment.address).into()),
+            }
+
+            for output in &tx.outputs {
+                let cmu = sapling::note::ExtractedNoteCommitment::from_bytes(
+                    &output.cmu[..].try_into().unwrap(),
+                )
+                .unwrap();
+                sapling_tree
+                    .append(sapling::Node::from_cmu(&cmu))
+                    .map_err(|_| "The Sapling tree of the mock chain is full")?;
+            }
+            self.orchard_tree_size += tx.actions.len() as u32;
+            vtx.push(tx);
+        }
+
+        self.blocks.push(CompactBlock {
+            height: height.into(),
+            hash: self.block_hash(height),
+            prev_hash: self.block_hash(height - 1),
+            time: height,
+            vtx,
+            chain_metadata: Some(ChainMetadata {
+                sapling_commitment_tree_size: sapling_tree.size() as u32,
+                orchard_commitment_tree_size: self.orchard_tree_size,
+            }),
+            ..Default::default()
+        });
+        self.sapling_trees.push(sapling_tree);
+        Ok(height)
+    }
+
+    fn latest_block(&mut self, _: ChainSpec) -> Result<BlockId, Status> {
+        self.requests.push("GetLatestBlock".to_string());
+        Ok(BlockId {
+            height: self.tip_height().into(),
+            hash: self.block_hash(self.tip_height()),
+        })
+    }
+
+    fn lightd_info(&mut self, _: Empty) -> Result<LightdInfo, Status> {
+        self.requests.push("GetLightdInfo".to_string());
+        Ok(LightdInfo {
+            chain_name: "test".to_string(),
+            sapling_activation_height: u32::from(
+                TestNetwork
+                    .activation_height(NetworkUpgrade::Sapling)
+                    .unwrap(),
+            )
+            .into(),
+            block_height: self.tip_height().into(),
+            ..Default::default()
+        })
+    }
+
+    fn block_range(&mut self, range: BlockRange) -> Result<Vec<CompactBlock>, Status> {
+        let start = range.start.map_or(0, |start| start.height);
+        let end = range.end.map_or(0, |end| end.height);
+        self.requests
+            .push(format!("GetBlockRange {} {}", start, end));
+        Ok(self
+            .blocks
+            .iter()
+            .filter(|block| (start..=end).contains(&block.height))
+            .cloned()
+            .collect())
+    }
+
+    fn tree_state(&mut self, block_id: BlockId) -> Result<TreeState, Status> {
+        self.requests
+            .push(format!("GetTreeState {}", block_id.height));
+        let height = u32::try_from(block_id.height)
+            .map_err(|_| Status::invalid_argument("Invalid height"))?;
+        let index = (height + 1)
+            .checked_sub(self.start_height)
+            .filter(|index| (*index as usize) < self.sapling_trees.len())
+            .ok_or_else(|| Status::not_found(format!("No block at {}", height)))?;
+
+        let mut sapling_tree = vec![];
+        write_commitment_tree(&self.sapling_trees[index as usize], &mut sapling_tree)
+            .expect("writing to a vector does not fail");
+        Ok(TreeState {
+            network: "test".to_string(),
+            height: height.into(),
+            hash: hex::encode(self.block_hash(height)),
+            time: height,
+            sapling_tree: hex::encode(sapling_tree),
+            orchard_tree: String::new(),
+        })
+    }
+
+    fn subtree_roots(&mut self, arg: GetSubtreeRootsArg) -> Result<Vec<SubtreeRoot>, Status> {
+        self.requests
+            .push(format!("GetSubtreeRoots {}", arg.start_index));
+        let max_entries = match arg.max_entries {
+            0 => usize::MAX,
+            max_entries => max_entries as usize,
+        };
+        Ok(self
+            .subtree_roots
+            .iter()
+            .skip(arg.start_index as usize)
+            .take(max_entries)
+            .cloned()
+            .collect())
+    }
+
+    fn taddress_txids(
+        &mut self,
+        filter: TransparentAddressBlockFilter,
+    ) -> Result<Vec<RawTransaction>, Status> {
+        self.requests
+            .push(format!("GetTaddressTxids {}", filter.address));
+        let (start, end) = filter.range.map_or((0, u64::MAX), |range| {
+            (
+                range.start.map_or(0, |start| start.height),
+                range.end.map_or(u64::MAX, |end| end.height),
+            )
+        });
+        Ok(self
+            .taddress_txs
+            .iter()
+            .filter(|(address, height)| {
+                *address == filter.address && (start..=end).contains(&u64::from(*height))
+            })
+            .map(|(_, height)| RawTransaction {
+                data: vec![],
+                height: u64::from(*height),
+            })
+            .collect())
+    }
+
+    fn send_transaction(&mut self, tx: RawTransaction) -> Result<SendResponse, Status> {
+        self.requests
+            .push(format!("SendTransaction {}", tx.encoded_len()));
+        Ok(SendResponse {
+            error_code: 0,
+            error_message: String::new(),
+        })
+    }
+}
+
+fn sapling_output(
+    to: sapling::PaymentAddress,
+    height: u32,
+    value: u64,
+) -> zcash_client_backend::proto::compact_formats::CompactSaplingOutput {
+    fake_sapling_output(to, OutgoingViewingKey([0; 32]), height, value).1
+}
+
+/// The `CompactTxStreamer` gRPC service of the mock chain.
+#[derive(Clone)]
+struct CompactTxStreamer(Arc<Mutex<MockChain>>);
+
+impl NamedService for CompactTxStreamer {
+    const NAME: &'static str = "cash.z.wallet.sdk.rpc.CompactTxStreamer";
+}
+
+impl<B> Service<http::Request<B>> for CompactTxStreamer
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let chain = self.0.clone();
+        Box::pin(async move {
+            let method = req.uri().path().rsplit('/').next().unwrap_or_default();
+            Ok(match method {
+                "GetLatestBlock" => unary(req, chain, MockChain::latest_block).await,
+                "GetLightdInfo" => unary(req, chain, MockChain::lightd_info).await,
+                "GetTreeState" => unary(req, chain, MockChain::tree_state).await,
+                "SendTransaction" => unary(req, chain, MockChain::send_transaction).await,
+                "GetBlockRange" => streaming(req, chain, MockChain::block_range).await,
+                "GetSubtreeRoots" => streaming(req, chain, MockChain::subtree_roots).await,
+                "GetTaddressTxids" => streaming(req, chain, MockChain::taddress_txids).await,
+                _ => Status::unimplemented(method).to_http(),
+            })
+        })
+    }
+}
+
+async fn unary<B, Req, Resp>(
+    req: http::Request<B>,
+    chain: Arc<Mutex<MockChain>>,
+    handler: fn(&mut MockChain, Req) -> Result<Resp, Status>,
+) -> http::Response<BoxBody>
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+    Req: Message + Default + Send + 'static,
+    Resp: Message + Send + 'static,
+{
+    let service = service_fn(move |request: Request<Req>| -> Ready<Result<_, Status>> {
+        ready(handler(&mut chain.lock().unwrap(), request.into_inner()).map(Response::new))
+    });
+    Grpc::new(ProstCodec::<Resp, Req>::default())
+        .unary(service, req)
+        .await
+}
+
+async fn streaming<B, Req, Resp>(
+    req: http::Request<B>,
+    chain: Arc<Mutex<MockChain>>,
+    handler: fn(&mut MockChain, Req) -> Result<Vec<Resp>, Status>,
+) -> http::Response<BoxBody>
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+    Req: Message + Default + Send + 'static,
+    Resp: Message + Send + 'static,
+{
+    let service = service_fn(move |request: Request<Req>| -> Ready<Result<_, Status>> {
+        ready(
+            handler(&mut chain.lock().unwrap(), request.into_inner()).map(|items| {
+                Response::new(tokio_stream::iter(items.into_iter().map(Ok::<_, Status>)))
+            }),
+        )
+    });
+    Grpc::new(ProstCodec::<Resp, Req>::default())
+        .server_streaming(service, req)
+        .await
+}
@@ -17,6 +17,19 @@
 mod keys;
 pub use self::keys::*;
 
+mod node_rpc;
+pub use self::node_rpc::*;
+
+#[cfg(feature = "mock-lightwalletd")]
+mod mock_lightwalletd;
+#[cfg(feature = "mock-lightwalletd")]
+pub use self::mock_lightwalletd::*;
+
+#[cfg(feature = "faucet")]
+mod faucet;
+#[cfg(feature = "faucet")]
+pub use self::faucet::*;
+
 mod fees;
 pub use self::fees::*;
 
@@ -32,6 +45,9 @@
 mod service;
 pub use self::service::*;
 
+mod value_pools;
+pub use self::value_pools::*;
+
 pub struct ZcashUnifiedAddressAndDiversifierIndex {
     pub address: Arc<ZcashUnifiedAddress>,
     pub diversifier_index: Arc<ZcashDiversifierIndex>,
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use base64::Engine;
+use hyper::client::HttpConnector;
+use hyper::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
+use hyper::{Body, Client, Method, Request, Uri};
+use serde_json::{json, Value};
+use tokio::runtime::Runtime;
+use zcash_primitives::consensus::{BlockHeight, BranchId};
+use zcash_primitives::transaction::{Transaction, TxId};
+
+use crate::{
+    ZcashConsensusParameters, ZcashError, ZcashNetworkConfig, ZcashResult, ZcashTransaction,
+    ZcashTxId,
+};
+
+/// A JSON-RPC client for a `zebrad` or `zcashd` node, so that users running their own
+/// node can broadcast and look up transactions without a `lightwalletd` server.
+///
+/// Nodes serve RPC over plain HTTP, so only `http` urls are supported. Like
+/// [`crate::ZcashLightwalletdClient`], it owns the async runtime its calls are executed
+/// in, so they are blocking from the foreign language point of view.
+pub struct ZcashNodeRpcClient {
+    runtime: Runtime,
+    client: Client<HttpConnector>,
+    uri: Uri,
+    authorization: Option<String>,
+    user_agent: Option<HeaderValue>,
+    params: ZcashConsensusParameters,
+}
+
+impl ZcashNodeRpcClient {
+    /// Targets the node at the given url, like `http://127.0.0.1:8232`, on the given
+    /// network. The credentials are those of the `rpcuser` and `rpcpassword` options of
+    /// `zcashd`, or of its cookie file. `zebrad` needs none.
+    pub fn new(
+        url: String,
+        params: ZcashConsensusParameters,
+        username: Option<String>,
+        password: Option<String>,
+        config: ZcashNetworkConfig,
+    ) -> ZcashResult<Self> {
+        let uri: Uri = url.parse().map_err(|e| ZcashError::Message {
+            error: format!("Invalid node url: {}", e),
+        })?;
+        if uri.scheme_str() != Some("http") {
+            return Err("Node RPC is only served over http".into());
+        }
+        let authorization = username.map(|username| {
+            let credentials = format!("{}:{}", username, password.unwrap_or_default());
+            format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(credentials)
+            )
+        });
+        let user_agent = config
+            .user_agent
+            .map(|user_agent| HeaderValue::try_from(user_agent).map_err(|_| "Invalid user agent"))
+            .transpose()?;
+
+        Ok(Self {
+            runtime: Runtime::new()?,
+            client: Client::new(),
+            uri,
+            authorization,
+            user_agent,
+            params,
+        })
+    }
+
+    /// Broadcasts the transaction, returning its id once the node accepted it in its
+    /// mempool.
+    pub fn send_raw_transaction(&self, tx: Arc<ZcashTransaction>) -> ZcashResult<Arc<ZcashTxId>> {
+        let result = self.call("sendrawtransaction", json!([hex::encode(tx.to_bytes()?)]))?;
+        Ok(Arc::new(parse_txid(&result)?))
+    }
+
+    /// Returns the height of the tip of the best chain of the node.
+    pub fn get_block_count(&self) -> ZcashResult<u32> {
+        let result = self.call("getblockcount", json!([]))?;
+        let height = result.as_u64().ok_or("Invalid block count")?;
+        Ok(u32::try_from(height)?)
+    }
+
+    /// Returns the transaction, as found in the mempool or the chain of the node.
+    /// `zcashd` only finds mined transactions with its `txindex` option.
+    pub fn get_raw_transaction(&self, txid: Arc<ZcashTxId>) -> ZcashResult<Arc<ZcashTransaction>> {
+        let result = self.call("getrawtransaction", json!([txid.to_hex_string()?, 1]))?;
+        let bytes = result["hex"]
+            .as_str()
+            .and_then(|tx_hex| hex::decode(tx_hex).ok())
+            .ok_or("Invalid transaction hex")?;
+
+        // Transactions before v5 are parsed with the branch they were mined in, or the
+        // one of the next block for those in the mempool.
+        let height = match result["height"].as_u64() {
+            Some(height) => height,
+            None => u64::from(self.get_block_count()?) + 1,
+        };
+        let branch_id = BranchId::for_height(&self.params, BlockHeight::from_u32(height as u32));
+        let tx = Transaction::read(&bytes[..], branch_id)?;
+        Ok(Arc::new(tx.into()))
+    }
+
+    /// Calls the given method, returning its result, or the error the node replied.
+    pub(super) fn call(&self, method: &str, params: Value) -> ZcashResult<Value> {
+        let body = json!({
+            "jsonrpc": "1.0",
+            "id": "uniffi-zcash",
+            "method": method,
+            "params": params,
+        });
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(self.uri.clone())
+            .header(CONTENT_TYPE, "application/json");
+        if let Some(authorization) = &self.authorization {
+            request = request.header(AUTHORIZATION, authorization);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            request = request.header(USER_AGENT, user_agent);
+        }
+        let request =
+            request
+                .body(Body::from(body.to_string()))
+                .map_err(|e| ZcashError::Message {
+                    error: format!("Invalid node request: {}", e),
+                })?;
+
+        let (status, bytes) = self.runtime.block_on(async {
+            let response = self.client.request(request).await?;
+            let status = response.status();
+            hyper::body::to_bytes(response.into_body())
+                .await
+                .map(|bytes| (status, bytes))
+        })?;
+
+        // Nodes reply errors with an error status, but still with a JSON-RPC body.
+        let reply: Value = serde_json::from_slice(&bytes).map_err(|_| ZcashError::Message {
+            error: format!("Node replied with status {}", status),
+        })?;
+        match &reply["error"] {
+            Value::Null => Ok(reply["result"].clone()),
+            error => Err(ZcashError::Message {
+                error: format!(
+                    "Node error {}: {}",
+                    error["code"],
+                    error["message"].as_str().unwrap_or_default()
+                ),
+            }),
+        }
+    }
+}
+
+/// Parses a transaction id in the byte order nodes show them, which is reversed.
+pub(super) fn parse_txid(value: &Value) -> ZcashResult<ZcashTxId> {
+    let mut bytes: [u8; 32] = value
+        .as_str()
+        .and_then(|txid| hex::decode(txid).ok())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("Invalid transaction id")?;
+    bytes.reverse();
+    Ok(TxId::from_bytes(bytes).into())
+}
@@ -1,7 +1,20 @@
-use crate::{ZcashError, ZcashResult};
+use std::sync::Arc;
+
+use crate::{
+    ZcashCommitmentTreeRoot, ZcashConsensusParameters, ZcashError, ZcashResult, ZcashTransaction,
+};
 use derive_more::{From, Into};
 use prost::Message;
-use zcash_client_backend::proto::service::TreeState;
+use tokio::runtime::Runtime;
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use zcash_client_backend::data_api::chain::CommitmentTreeRoot;
+use zcash_client_backend::proto::service::{
+    compact_tx_streamer_client::CompactTxStreamerClient, BlockId, ChainSpec, Empty,
+    GetSubtreeRootsArg, RawTransaction, SendResponse, ShieldedProtocol, TreeState,
+};
+use zcash_primitives::consensus::BlockHeight;
+use zcash_primitives::merkle_tree::HashSer;
+use zcash_primitives::sapling;
 
 #[derive(Clone, From, Into)]
 pub struct ZcashTreeState(TreeState);
@@ -32,3 +45,298 @@ pub fn from_bytes(bytes: Vec<u8>) -> ZcashResult<Self> {
         Ok(Self(treestate))
     }
 }
+
+/// The settings shared by the network clients.
+///
+/// By default, they send no metadata identifying the user or the app: no user agent
+/// but the name and version of the gRPC library `lightwalletd` requests always carry,
+/// no device or wallet details and no telemetry of any kind.
+#[derive(Clone, Debug, Default)]
+pub struct ZcashNetworkConfig {
+    /// The user agent the requests are sent with. For `lightwalletd`, the gRPC library
+    /// appends its own name and version to it.
+    pub user_agent: Option<String>,
+}
+
+/// A client for the `CompactTxStreamer` gRPC service of a `lightwalletd` server.
+///
+/// It owns the async runtime its calls are executed in, so they are blocking
+/// from the foreign language point of view.
+pub struct ZcashLightwalletdClient {
+    pub(crate) runtime: Runtime,
+    pub(crate) client: CompactTxStreamerClient<Channel>,
+}
+
+impl ZcashLightwalletdClient {
+    /// Connects to the server at the given url, like `https://mainnet.lightwalletd.com:9067`.
+    /// TLS is used for `https` urls.
+    pub fn new(url: String) -> ZcashResult<Self> {
+        Self::with_config(url, ZcashNetworkConfig::default())
+    }
+
+    /// Same as [`Self::new`], with the given network settings.
+    pub fn with_config(url: String, config: ZcashNetworkConfig) -> ZcashResult<Self> {
+        let runtime = Runtime::new()?;
+
+        let mut endpoint = Endpoint::from_shared(url.clone())?;
+        if let Some(user_agent) = config.user_agent {
+            endpoint = endpoint.user_agent(user_agent)?;
+        }
+        if url.starts_with("https") {
+            endpoint = endpoint.tls_config(ClientTlsConfig::new())?;
+        }
+        let channel = runtime.block_on(endpoint.connect())?;
+
+        Ok(Self {
+            runtime,
+            client: CompactTxStreamerClient::new(channel),
+        })
+    }
+
+    /// Returns the height of the latest block known by the server.
+    pub fn latest_block_height(&self) -> ZcashResult<u32> {
+        let mut client = self.client.clone();
+        let block = self
+            .runtime
+            .block_on(client.get_latest_block(ChainSpec {}))?
+            .into_inner();
+
+        Ok(u32::try_from(block.height)?)
+    }
+
+    /// Fails if the server is not on the network of the given parameters.
+    pub(crate) fn check_network(&self, params: ZcashConsensusParameters) -> ZcashResult<()> {
+        let mut client = self.client.clone();
+        let info = self
+            .runtime
+            .block_on(client.get_lightd_info(Empty {}))?
+            .into_inner();
+        let expected = match params {
+            ZcashConsensusParameters::MainNetwork => "main",
+            ZcashConsensusParameters::TestNetwork => "test",
+        };
+        if info.chain_name != expected {
+            return Err(ZcashError::Message {
+                error: format!(
+                    "The lightwalletd server is on the {} network, not the {} one",
+                    info.chain_name, expected
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the state of the note commitment trees as of the block at the given height,
+    /// as needed for the birthday of an account created at the next height.
+    pub fn get_tree_state(&self, height: u32) -> ZcashResult<Arc<ZcashTreeState>> {
+        let mut client = self.client.clone();
+        let block_id = BlockId {
+            height: height.into(),
+            hash: vec![],
+        };
+        let tree_state = self
+            .runtime
+            .block_on(client.get_tree_state(block_id))?
+            .into_inner();
+
+        Ok(Arc::new(tree_state.into()))
+    }
+
+    /// Returns the roots of the completed subtrees of the Sapling note commitment tree,
+    /// from the one at `start_index`, with the heights of the blocks completing them.
+    pub fn get_sapling_subtree_roots(
+        &self,
+        start_index: u32,
+    ) -> ZcashResult<Vec<Arc<ZcashCommitmentTreeRoot>>> {
+        let mut client = self.client.clone();
+        let request = GetSubtreeRootsArg {
+            start_index,
+            shielded_protocol: ShieldedProtocol::Sapling.into(),
+            max_entries: 0,
+        };
+        self.runtime.block_on(async {
+            let mut stream = client.get_subtree_roots(request).await?.into_inner();
+            let mut roots = Vec::new();
+            while let Some(root) = stream.message().await? {
+                let root_hash = sapling::Node::read(&root.root_hash[..])?;
+                let height = BlockHeight::from_u32(u32::try_from(root.completing_block_height)?);
+                roots.push(Arc::new(
+                    CommitmentTreeRoot::from_parts(height, root_hash).into(),
+                ));
+            }
+            Ok(roots)
+        })
+    }
+
+    /// Broadcasts the transaction, returning whether the node behind the server accepted
+    /// it in its mempool or why it did not. Failing to reach the server is an error.
+    pub fn send_transaction(&self, tx: Arc<ZcashTransaction>) -> ZcashResult<ZcashBroadcastResult> {
+        let mut client = self.client.clone();
+        let raw_tx = RawTransaction {
+            data: tx.to_bytes()?,
+            height: 0,
+        };
+        let response = self
+            .runtime
+            .block_on(client.send_transaction(raw_tx))?
+            .into_inner();
+
+        Ok(response.into())
+    }
+}
+
+/// The outcome of a broadcast, after the error the node replied through `lightwalletd`,
+/// so that apps know whether to retry it. The variants with an error carry its code and
+/// message as received, for showing or logging them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ZcashBroadcastResult {
+    Accepted,
+    /// The transaction was already broadcast, so there is nothing to retry.
+    AlreadyInMempool,
+    /// The transaction is already mined, so there is nothing to retry.
+    AlreadyMined,
+    /// The fee is below what the node relays, so the transaction needs to be created again
+    /// with a higher one.
+    FeeTooLow {
+        error_code: i32,
+        error_message: String,
+    },
+    /// The expiry height is reached, or about to be, so the transaction needs to be
+    /// created again.
+    Expired {
+        error_code: i32,
+        error_message: String,
+    },
+    /// Any other error, as for invalid transactions or nodes unable to broadcast.
+    Rejected {
+        error_code: i32,
+        error_message: String,
+    },
+}
+
+/// The reject reasons of nodes asking for a higher fee. Others mentioning the fee, as
+/// `absurdly-high-fee` or `bad-txns-fee-outofrange`, must not be retried with a higher one.
+const FEE_TOO_LOW_REASONS: [&str; 4] = [
+    "min relay fee not met",
+    "mempool min fee not met",
+    "insufficient fee",
+    "unpaid action",
+];
+
+impl From<SendResponse> for ZcashBroadcastResult {
+    /// `lightwalletd` passes on the error code and message of the node, which differ
+    /// between `zcashd` and `zebrad`, so the message is what tells the error apart.
+    fn from(response: SendResponse) -> Self {
+        let SendResponse {
+            error_code,
+            error_message,
+        } = response;
+        let message = error_message.to_lowercase();
+        if error_code == 0 {
+            Self::Accepted
+        } else if message.contains("already") && message.contains("mempool") {
+            Self::AlreadyInMempool
+        } else if message.contains("already") && message.contains("chain") {
+            Self::AlreadyMined
+        } else if FEE_TOO_LOW_REASONS
+            .iter()
+            .any(|reason| message.contains(reason))
+        {
+            Self::FeeTooLow {
+                error_code,
+                error_message,
+            }
+        } else if message.contains("expir") {
+            Self::Expired {
+                error_code,
+                error_message,
+            }
+        } else {
+            Self::Rejected {
+                error_code,
+                error_message,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn broadcast_result(error_code: i32, error_message: &str) -> ZcashBroadcastResult {
+        SendResponse {
+            error_code,
+            error_message: error_message.to_string(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn accepted() {
+        // `lightwalletd` replies the id of the transaction as the message of successes.
+        let txid = "d5a6b5c7a3c9e1f0b2a4c6e8f0a2b4c6d8e0f2a4b6c8d0e2f4a6b8c0d2e4f6a8";
+        assert_eq!(ZcashBroadcastResult::Accepted, broadcast_result(0, txid));
+    }
+
+    #[test]
+    fn already_known() {
+        assert_eq!(
+            ZcashBroadcastResult::AlreadyInMempool,
+            broadcast_result(-26, "txn-already-in-mempool")
+        );
+        assert_eq!(
+            ZcashBroadcastResult::AlreadyInMempool,
+            broadcast_result(-25, "transaction is already in the mempool")
+        );
+        assert_eq!(
+            ZcashBroadcastResult::AlreadyMined,
+            broadcast_result(-27, "transaction already in block chain")
+        );
+    }
+
+    #[test]
+    fn fee_too_low() {
+        for message in [
+            "66: min relay fee not met",
+            "66: mempool min fee not met",
+            "66: insufficient fee",
+            "tx unpaid action limit exceeded",
+        ] {
+            assert!(matches!(
+                broadcast_result(-26, message),
+                ZcashBroadcastResult::FeeTooLow {
+                    error_code: -26,
+                    ..
+                }
+            ));
+        }
+        for message in ["256: absurdly-high-fee", "16: bad-txns-fee-outofrange"] {
+            assert!(matches!(
+                broadcast_result(-26, message),
+                ZcashBroadcastResult::Rejected { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn expired() {
+        for message in ["16: tx-overwinter-expired", "tx-expiring-soon"] {
+            assert!(matches!(
+                broadcast_result(-26, message),
+                ZcashBroadcastResult::Expired { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn rejected() {
+        assert_eq!(
+            ZcashBroadcastResult::Rejected {
+                error_code: -26,
+                error_message: "16: bad-txns-sapling-duplicate-nullifier".to_string(),
+            },
+            broadcast_result(-26, "16: bad-txns-sapling-duplicate-nullifier")
+        );
+    }
+}
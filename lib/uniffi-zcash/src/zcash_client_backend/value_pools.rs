@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use serde_json::json;
+use zcash_primitives::transaction::Transaction;
+
+use crate::{validation::invalid_argument, ZcashNodeRpcClient, ZcashResult, ZcashTransaction};
+
+/// The value, in zatoshis, in the shielded pools of the chain as of a block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZcashPoolTotals {
+    pub height: u32,
+    pub sapling: u64,
+    pub orchard: u64,
+}
+
+/// The value, in zatoshis, the transactions moved into the shielded pools, negative when
+/// more value left them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ZcashPoolDeltas {
+    pub sapling: i64,
+    pub orchard: i64,
+}
+
+/// Sums how much the transactions moved into or out of the shielded pools. Given all
+/// those of a block, fetched from `lightwalletd` by their ids in its compact block for
+/// example, these are the changes of the pool totals at that block.
+pub fn pool_deltas(txs: Vec<Arc<ZcashTransaction>>) -> ZcashPoolDeltas {
+    txs.iter().fold(ZcashPoolDeltas::default(), |deltas, tx| {
+        let tx: &Transaction = tx.as_ref().into();
+        ZcashPoolDeltas {
+            sapling: deltas.sapling
+                - tx.sapling_bundle()
+                    .map_or(0, |bundle| i64::from(*bundle.value_balance())),
+            orchard: deltas.orchard
+                - tx.orchard_bundle()
+                    .map_or(0, |bundle| i64::from(*bundle.value_balance())),
+        }
+    })
+}
+
+impl ZcashNodeRpcClient {
+    /// Returns the value in the shielded pools as of the block at the given height, as
+    /// reported by nodes tracking it in their blocks `valuePools`, like `zcashd`.
+    pub fn get_pool_totals(&self, height: u32) -> ZcashResult<ZcashPoolTotals> {
+        let block = self.call("getblock", json!([height.to_string(), 1]))?;
+        let pools = block["valuePools"]
+            .as_array()
+            .ok_or("The node does not report the value pools")?;
+        let chain_value = |id: &str| -> ZcashResult<u64> {
+            pools
+                .iter()
+                .find(|pool| pool["id"] == id)
+                .and_then(|pool| pool["chainValueZat"].as_u64())
+                .ok_or_else(|| format!("The node does not monitor the {} pool", id).into())
+        };
+
+        Ok(ZcashPoolTotals {
+            height,
+            sapling: chain_value("sapling")?,
+            orchard: chain_value("orchard")?,
+        })
+    }
+
+    /// Returns the pool totals from `start_height` to `end_height` included, every
+    /// `interval` blocks, for charting them over time.
+    pub fn get_pool_totals_history(
+        &self,
+        start_height: u32,
+        end_height: u32,
+        interval: u32,
+    ) -> ZcashResult<Vec<ZcashPoolTotals>> {
+        if interval == 0 {
+            return Err(invalid_argument("interval", "must not be zero"));
+        }
+        if start_height > end_height {
+            return Err(invalid_argument(
+                "start_height",
+                "must not be above the end height",
+            ));
+        }
+        (start_height..=end_height)
+            .step_by(interval as usize)
+            .map(|height| self.get_pool_totals(height))
+            .collect()
+    }
+}
@@ -1,10 +1,17 @@
 use std::fmt;
+use std::sync::Arc;
 
 use derive_more::{From, Into};
+use rand::{rngs::OsRng, Rng};
 
-use zcash_client_backend::zip321::{TransactionRequest, Zip321Error};
+use zcash_client_backend::address::RecipientAddress;
+use zcash_client_backend::zip321::{Payment, TransactionRequest, Zip321Error};
+use zcash_primitives::transaction::components::Amount;
 
-use crate::{ZcashConsensusParameters, ZcashPayment};
+use crate::{
+    zcash_address::is_sprout_address, ZcashConsensusParameters, ZcashError, ZcashNonNegativeAmount,
+    ZcashPayment, ZcashRecipientAddress, ZcashResult,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ZcashZip321Error {
@@ -25,6 +32,8 @@ pub enum ZcashZip321Error {
     RecipientMissing { v: u32 },
     /// The ZIP 321 URI was malformed and failed to parse.
     ParseError { v: String },
+    /// The payment at the wrapped index is to a Sprout address, which is not supported.
+    SproutNotSupported { v: u32 },
 }
 
 impl fmt::Display for ZcashZip321Error {
@@ -35,6 +44,7 @@ fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             ZcashZip321Error::TransparentMemo { v } => write!(f, "{}", v),
             ZcashZip321Error::TooManyPayments { v } => write!(f, "{}", v),
             ZcashZip321Error::ParseError { v } => write!(f, "{}", v),
+            ZcashZip321Error::SproutNotSupported { v } => write!(f, "{}", v),
         }
     }
 }
@@ -57,6 +67,9 @@ fn from(value: ZcashZip321Error) -> Self {
                 "DuplicateParameter" => Zip321Error::ParseError("MemoBytesError".to_string()),
                 _ => Zip321Error::ParseError(v),
             },
+            ZcashZip321Error::SproutNotSupported { v } => {
+                Zip321Error::ParseError(format!("Sprout address in payment {}", v))
+            }
         }
     }
 }
@@ -89,6 +102,26 @@ fn from(value: Zip321Error) -> Self {
 
 pub type ZcashZip321Result<T> = Result<T, ZcashZip321Error>;
 
+/// The index of the first payment of a ZIP 321 URI to a Sprout address, either the
+/// address of the URI path or an `address` or `address.<index>` query parameter.
+fn sprout_payment_index(uri: &str) -> Option<u32> {
+    let rest = uri.strip_prefix("zcash:")?;
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let addresses = std::iter::once(Some((0, path))).chain(query.split('&').map(|param| {
+        let (key, value) = param.split_once('=')?;
+        match key.strip_prefix("address") {
+            Some("") => Some((0, value)),
+            Some(index) => Some((index.strip_prefix('.')?.parse().ok()?, value)),
+            None => None,
+        }
+    }));
+
+    addresses
+        .flatten()
+        .find(|(_, address)| is_sprout_address(address))
+        .map(|(index, _)| index)
+}
+
 /// A ZIP321 transaction request.
 ///
 /// A ZIP 321 request may include one or more such requests for payment.
@@ -112,9 +145,14 @@ pub fn new(payments: Vec<ZcashPayment>) -> ZcashZip321Result<Self> {
     }
 
     /// Parse the provided URI to a payment request value.
+    ///
+    /// Payments to Sprout addresses are refused with [`ZcashZip321Error::SproutNotSupported`].
     pub fn from_uri(params: ZcashConsensusParameters, uri: &str) -> ZcashZip321Result<Self> {
         TransactionRequest::from_uri(&params, uri)
-            .map_err(From::from)
+            .map_err(|e| match sprout_payment_index(uri) {
+                Some(v) => ZcashZip321Error::SproutNotSupported { v },
+                None => e.into(),
+            })
             .map(From::from)
     }
 
@@ -129,6 +167,58 @@ pub fn payments(&self) -> Vec<ZcashPayment> {
     pub fn to_uri(&self, params: ZcashConsensusParameters) -> Option<String> {
         self.0.to_uri(&params)
     }
+
+    /// Returns a request where each payment to a shielded recipient is split into up to
+    /// `max_notes` notes of random values, so the amount cannot be linked to other
+    /// transactions by the value of its notes. Each note is worth at least
+    /// [`MIN_SPLIT_NOTE_VALUE`], and payments too small to split are kept as is, as are
+    /// transparent ones. The memo is only sent along with the first note of a payment.
+    pub fn with_split_notes(&self, max_notes: u32) -> ZcashZip321Result<Arc<Self>> {
+        let mut payments = Vec::with_capacity(self.0.payments().len());
+        for payment in self.0.payments() {
+            let shielded = !matches!(payment.recipient_address, RecipientAddress::Transparent(_));
+            let value = i64::from(payment.amount);
+            let count = i64::from(max_notes).min(value / MIN_SPLIT_NOTE_VALUE);
+            let parts = match shielded && count >= 2 {
+                true => split_value(value, count as usize),
+                false => vec![value],
+            };
+
+            for (i, part) in parts.into_iter().enumerate() {
+                payments.push(Payment {
+                    recipient_address: payment.recipient_address.clone(),
+                    amount: Amount::from_i64(part).expect("parts are below the payment value"),
+                    memo: payment.memo.clone().filter(|_| i == 0),
+                    label: payment.label.clone(),
+                    message: payment.message.clone(),
+                    other_params: payment.other_params.clone(),
+                });
+            }
+        }
+
+        TransactionRequest::new(payments)
+            .map(|request| Arc::new(request.into()))
+            .map_err(From::from)
+    }
+}
+
+/// The minimum value of the notes [`ZcashTransactionRequest::with_split_notes`] creates,
+/// ten times the ZIP 317 marginal fee, so each extra note costs at most a tenth of its value.
+pub const MIN_SPLIT_NOTE_VALUE: i64 = 50_000;
+
+/// Splits the value into a random count of parts, from 2 to `max_parts`, adding up to it.
+/// Each part is worth at least [`MIN_SPLIT_NOTE_VALUE`], the rest being spread randomly.
+fn split_value(value: i64, max_parts: usize) -> Vec<i64> {
+    let parts = OsRng.gen_range(2..=max_parts);
+    let spread = value - MIN_SPLIT_NOTE_VALUE * parts as i64;
+
+    let mut cuts: Vec<i64> = (1..parts).map(|_| OsRng.gen_range(0..=spread)).collect();
+    cuts.push(0);
+    cuts.push(spread);
+    cuts.sort_unstable();
+    cuts.windows(2)
+        .map(|w| MIN_SPLIT_NOTE_VALUE + w[1] - w[0])
+        .collect()
 }
 
 impl Clone for ZcashTransactionRequest {
@@ -136,3 +226,78 @@ fn clone(&self) -> Self {
         Self::new(self.payments()).expect("Cannot clone from payments!")
     }
 }
+
+/// What a scanned QR code asks to pay, either a bare address, the amount being left to
+/// the payer, or a ZIP 321 payment request, even if it only holds an address. Exactly one
+/// of them is set.
+pub struct ZcashQrPayload {
+    pub address: Option<Arc<ZcashRecipientAddress>>,
+    pub request: Option<Arc<ZcashTransactionRequest>>,
+}
+
+/// Returns the text to encode in a QR code asking for a payment to the address: the
+/// address itself, or a ZIP 321 URI when an amount is requested, as the other Zcash
+/// wallets render and expect them.
+pub fn encode_qr_payload(
+    params: ZcashConsensusParameters,
+    address: Arc<ZcashRecipientAddress>,
+    amount: Option<Arc<ZcashNonNegativeAmount>>,
+) -> ZcashResult<String> {
+    let amount = match amount.filter(|amount| amount.value() > 0) {
+        Some(amount) => Amount::from_u64(amount.value()).map_err(|_| "Invalid amount")?,
+        None => return Ok(address.encode(params)),
+    };
+    let payment = Payment {
+        recipient_address: (*address).clone().into(),
+        amount,
+        memo: None,
+        label: None,
+        message: None,
+        other_params: vec![],
+    };
+
+    TransactionRequest::new(vec![payment])
+        .ok()
+        .and_then(|request| request.to_uri(&params))
+        .ok_or_else(|| "Unable to encode the payment request".into())
+}
+
+/// Parses the text of a scanned QR code, either a bare address or a ZIP 321 URI.
+///
+/// Scanners may return the text uppercased, as some wallets encode it so to fit QR codes
+/// in alphanumeric mode, so the `zcash:` scheme and Bech32 addresses are accepted in any
+/// case. Sprout addresses are refused with [`ZcashError::SproutNotSupported`].
+pub fn parse_qr_payload(
+    params: ZcashConsensusParameters,
+    payload: String,
+) -> ZcashResult<ZcashQrPayload> {
+    let payload = payload.trim();
+
+    if payload.len() >= 6 && payload[..6].eq_ignore_ascii_case("zcash:") {
+        // Parameter names are lowercase, so URIs without lowercase letters were uppercased.
+        let rest = &payload[6..];
+        let uri = match rest.chars().any(|c| c.is_ascii_lowercase()) {
+            true => format!("zcash:{}", rest),
+            false => format!("zcash:{}", rest.to_ascii_lowercase()),
+        };
+        return match ZcashTransactionRequest::from_uri(params, &uri) {
+            Ok(request) => Ok(ZcashQrPayload {
+                address: None,
+                request: Some(Arc::new(request)),
+            }),
+            Err(ZcashZip321Error::SproutNotSupported { .. }) => Err(ZcashError::SproutNotSupported),
+            Err(e) => Err(ZcashError::Message {
+                error: format!("Invalid payment request: {:?}", e),
+            }),
+        };
+    }
+
+    // Base58 transparent addresses are case sensitive, unlike Bech32 ones.
+    let address = ZcashRecipientAddress::decode(params, payload).or_else(|e| {
+        ZcashRecipientAddress::decode(params, &payload.to_ascii_lowercase()).map_err(|_| e)
+    })?;
+    Ok(ZcashQrPayload {
+        address: Some(Arc::new(address)),
+        request: None,
+    })
+}
@@ -0,0 +1,232 @@
+use incrementalmerkletree::Position;
+use rusqlite::{named_params, Connection, OptionalExtension};
+use zcash_client_backend::data_api::WalletCommitmentTrees;
+use zcash_client_sqlite::WalletDb;
+
+use super::scan_queue::{replace_queue_range, PRIORITY_IGNORED};
+use crate::{ZcashAccountId, ZcashError, ZcashResult, ZcashWalletDb};
+
+/// The outcome of [`ZcashWalletDb::delete_account`]: what was, or in dry runs would be,
+/// removed along with the account.
+pub struct ZcashAccountDeletionReport {
+    pub account: ZcashAccountId,
+    pub addresses: u32,
+    pub received_notes: u32,
+    pub transparent_outputs: u32,
+    /// The notes sent from the account. Those sent to it from other accounts are kept.
+    pub sent_notes: u32,
+    /// The transactions no other account of the wallet is involved in.
+    pub transactions: u32,
+    /// Whether the account was deleted. It never is in dry runs.
+    pub applied: bool,
+}
+
+impl ZcashWalletDb {
+    /// Flags an account as archived, so apps can hide it. Unlike deleting it, this can be
+    /// undone: the account keeps all its data and it is still scanned.
+    pub fn archive_account(&self, account: ZcashAccountId) -> ZcashResult<()> {
+        let conn = Connection::open(&self.path)?;
+        ensure_account_exists(&conn, account)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO uniffi_archived_accounts (account) VALUES (:account)",
+            named_params![":account": account.id],
+        )?;
+        Ok(())
+    }
+
+    pub fn unarchive_account(&self, account: ZcashAccountId) -> ZcashResult<()> {
+        let conn = Connection::open(&self.path)?;
+        conn.execute(
+            "DELETE FROM uniffi_archived_accounts WHERE account = :account",
+            named_params![":account": account.id],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_archived_accounts(&self) -> ZcashResult<Vec<ZcashAccountId>> {
+        let conn = Connection::open(&self.path)?;
+        let accounts = conn
+            .prepare("SELECT account FROM uniffi_archived_accounts ORDER BY account")?
+            .query_map([], |row| Ok(ZcashAccountId { id: row.get(0)? }))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(accounts)
+    }
+
+    /// Deletes an account along with its addresses, received notes, transparent outputs,
+    /// the notes it sent and the transactions only it is involved in, and unmarks its notes
+    /// in the note commitment tree. This cannot be undone: restoring the account means creating
+    /// it again and rescanning from its birthday. With `dry_run`, the report tells what
+    /// would be removed but nothing is, so apps can ask the user to confirm.
+    ///
+    /// Notes sent to the account from other accounts of the wallet are kept, addressed to
+    /// the account default address. If the account had the earliest birthday of the
+    /// wallet, the blocks no remaining account needs are no longer scanned.
+    pub fn delete_account(
+        &self,
+        account: ZcashAccountId,
+        dry_run: bool,
+    ) -> ZcashResult<ZcashAccountDeletionReport> {
+        let mut conn = Connection::open(&self.path)?;
+        let tx = conn.transaction()?;
+        let params = named_params![":account": account.id];
+
+        let birthday_height: u32 = ensure_account_exists(&tx, account)?;
+        // The positions of the notes of the account in the note commitment tree, which are
+        // marked there so that the notes can be witnessed.
+        let positions = tx
+            .prepare(
+                "SELECT commitment_tree_position FROM sapling_received_notes
+                WHERE account = :account AND commitment_tree_position IS NOT NULL",
+            )?
+            .query_map(params, |row| row.get::<_, u64>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        // The transactions of the account, which are removed once none of the remaining
+        // accounts is involved in them.
+        tx.execute_batch(
+            "CREATE TEMP TABLE IF NOT EXISTS account_transactions (id_tx INTEGER PRIMARY KEY);
+            DELETE FROM account_transactions;",
+        )?;
+        tx.execute(
+            "INSERT OR IGNORE INTO account_transactions (id_tx)
+            SELECT tx FROM sapling_received_notes WHERE account = :account
+            UNION SELECT spent FROM sapling_received_notes
+                WHERE account = :account AND spent IS NOT NULL
+            UNION SELECT tx FROM sent_notes WHERE from_account = :account
+            UNION SELECT spent_in_tx FROM utxos
+                WHERE received_by_account = :account AND spent_in_tx IS NOT NULL
+            UNION SELECT id_tx FROM transactions
+                JOIN utxos ON utxos.prevout_txid = transactions.txid
+                WHERE utxos.received_by_account = :account",
+            params,
+        )?;
+        let default_address: Option<String> = tx
+            .query_row(
+                "SELECT address FROM addresses WHERE account = :account
+                ORDER BY diversifier_index_be ASC LIMIT 1",
+                params,
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        tx.execute(
+            "UPDATE sent_notes SET to_address = :address, to_account = NULL
+            WHERE to_account = :account AND from_account != :account",
+            named_params![":address": default_address, ":account": account.id],
+        )?;
+        let sent_notes = tx.execute(
+            "DELETE FROM sent_notes WHERE from_account = :account",
+            params,
+        )?;
+        tx.execute(
+            "DELETE FROM sapling_witnesses WHERE note IN (
+                SELECT id_note FROM sapling_received_notes WHERE account = :account
+            )",
+            params,
+        )?;
+        let received_notes = tx.execute(
+            "DELETE FROM sapling_received_notes WHERE account = :account",
+            params,
+        )?;
+        let transparent_outputs = tx.execute(
+            "DELETE FROM utxos WHERE received_by_account = :account",
+            params,
+        )?;
+        let addresses = tx.execute("DELETE FROM addresses WHERE account = :account", params)?;
+        tx.execute(
+            "DELETE FROM uniffi_device_derivations WHERE account = :account",
+            params,
+        )?;
+        tx.execute(
+            "DELETE FROM uniffi_invoices WHERE account = :account",
+            params,
+        )?;
+        tx.execute(
+            "DELETE FROM uniffi_spending_limits WHERE account = :account",
+            params,
+        )?;
+        tx.execute(
+            "DELETE FROM uniffi_archived_accounts WHERE account = :account",
+            params,
+        )?;
+        tx.execute("DELETE FROM accounts WHERE account = :account", params)?;
+
+        let transactions = tx.execute(
+            "DELETE FROM transactions WHERE id_tx IN (
+                SELECT id_tx FROM account_transactions
+                EXCEPT SELECT tx FROM sapling_received_notes
+                EXCEPT SELECT spent FROM sapling_received_notes WHERE spent IS NOT NULL
+                EXCEPT SELECT tx FROM sent_notes
+                EXCEPT SELECT spent_in_tx FROM utxos WHERE spent_in_tx IS NOT NULL
+                EXCEPT SELECT id_tx FROM transactions
+                    JOIN utxos ON utxos.prevout_txid = transactions.txid
+            )",
+            [],
+        )?;
+        tx.execute(
+            "DELETE FROM uniffi_replaced_transactions
+            WHERE txid NOT IN (SELECT txid FROM transactions)
+            AND replaced_by NOT IN (SELECT txid FROM transactions)",
+            [],
+        )?;
+        tx.execute("DROP TABLE account_transactions", [])?;
+
+        // The scan queue starts at the earliest birthday of the wallet.
+        let wallet_birthday: Option<u32> =
+            tx.query_row("SELECT MIN(birthday_height) FROM accounts", [], |row| {
+                row.get(0)
+            })?;
+        match wallet_birthday {
+            Some(height) if height > birthday_height => {
+                replace_queue_range(&tx, birthday_height..height, PRIORITY_IGNORED)?;
+            }
+            Some(_) => {}
+            None => {
+                tx.execute("DELETE FROM scan_queue", [])?;
+            }
+        }
+
+        let applied = !dry_run;
+        if applied {
+            tx.commit()?;
+            // The tree is only written through the upstream wallet, with a connection of
+            // its own, so the marks are removed once the notes are gone. Marks left behind
+            // by a failure here only keep some tree nodes from being pruned.
+            let mut db = WalletDb::for_path(&self.path, self.params)?;
+            db.with_sapling_tree_mut(|tree| {
+                positions.iter().try_for_each(|position| {
+                    tree.remove_mark(Position::from(*position), None)
+                        .map(|_| ())
+                })
+            })
+            .map_err(|e| ZcashError::Message {
+                error: format!("ShardTreeError: {:?}", e),
+            })?;
+        }
+
+        Ok(ZcashAccountDeletionReport {
+            account,
+            addresses: addresses as u32,
+            received_notes: received_notes as u32,
+            transparent_outputs: transparent_outputs as u32,
+            sent_notes: sent_notes as u32,
+            transactions: transactions as u32,
+            applied,
+        })
+    }
+}
+
+/// Returns the birthday height of the account, failing if the wallet does not have it.
+pub(super) fn ensure_account_exists(
+    conn: &Connection,
+    account: ZcashAccountId,
+) -> ZcashResult<u32> {
+    conn.query_row(
+        "SELECT birthday_height FROM accounts WHERE account = :account",
+        named_params![":account": account.id],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| ZcashError::Message {
+        error: format!("The wallet has no account {}", account.id),
+    })
+}
@@ -0,0 +1,46 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use rusqlite::Connection;
+use zcash_client_backend::data_api::{WalletRead, WalletSummary};
+use zcash_client_sqlite::WalletDb;
+
+use super::account_removal::ensure_account_exists;
+use super::cast_err;
+use crate::{ZcashAccountId, ZcashResult, ZcashWalletDb, ZcashWalletSummary};
+
+impl ZcashWalletDb {
+    /// Same as [`Self::get_wallet_summary`], but only with the balance of the given account,
+    /// for apps showing one account at a time.
+    ///
+    /// Scanning is done for the whole wallet, so the heights and scan progress are those
+    /// of the wallet.
+    pub fn get_account_summary(
+        &self,
+        account: ZcashAccountId,
+        min_confirmations: u32,
+    ) -> ZcashResult<Option<Arc<ZcashWalletSummary>>> {
+        ensure_account_exists(&Connection::open(&self.path)?, account)?;
+        let summary = WalletDb::for_path(&self.path, self.params)
+            .expect("Cannot access the DB!")
+            .get_wallet_summary(min_confirmations)
+            .map_err(cast_err)?;
+
+        Ok(summary.map(|summary| {
+            let balances = summary
+                .account_balances()
+                .get(&account.into())
+                .map(|balance| BTreeMap::from([(account.into(), *balance)]))
+                .unwrap_or_default();
+            Arc::new(
+                WalletSummary::new(
+                    balances,
+                    summary.chain_tip_height(),
+                    summary.fully_scanned_height(),
+                    summary.scan_progress(),
+                )
+                .into(),
+            )
+        }))
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use rusqlite::{named_params, Connection, OptionalExtension};
+use zcash_client_backend::address::{AddressMetadata, RecipientAddress};
+use zcash_client_backend::data_api::WalletRead;
+use zcash_client_sqlite::WalletDb;
+use zcash_primitives::zip32::{DiversifierIndex, Scope};
+
+use super::{cast_err, diversifier_index_from_be};
+use crate::{
+    ZcashAccountId, ZcashAddressMetadata, ZcashDiversifierIndex, ZcashError, ZcashResult,
+    ZcashUnifiedAddress, ZcashWalletDb,
+};
+
+impl ZcashWalletDb {
+    /// Returns the address the wallet generated for the account at the diversifier index,
+    /// or none if it did not generate one there.
+    pub fn get_address_for_diversifier_index(
+        &self,
+        account: ZcashAccountId,
+        diversifier_index: Arc<ZcashDiversifierIndex>,
+    ) -> ZcashResult<Option<Arc<ZcashUnifiedAddress>>> {
+        let conn = Connection::open(&self.path)?;
+        let mut di_be = DiversifierIndex::from(&*diversifier_index).0;
+        di_be.reverse();
+
+        let address: Option<String> = conn
+            .query_row(
+                "SELECT address FROM addresses
+                WHERE account = :account AND diversifier_index_be = :diversifier_index_be",
+                named_params![
+                    ":account": account.id,
+                    ":diversifier_index_be": &di_be[..],
+                ],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        address
+            .map(
+                |address| match RecipientAddress::decode(&self.params, &address) {
+                    Some(RecipientAddress::Unified(ua)) => Ok(Arc::new(ua.into())),
+                    _ => Err(ZcashError::Message {
+                        error: format!("{} is not a unified address", address),
+                    }),
+                },
+            )
+            .transpose()
+    }
+
+    /// Returns the account and diversifier index the address was derived at, so that
+    /// payments to invoice specific addresses can be matched to their invoices.
+    ///
+    /// The addresses the wallet generated, and their transparent receivers, are looked
+    /// up in the database. Other Sapling and Orchard addresses are decrypted with the
+    /// viewing keys of the accounts, which finds the external addresses of any index.
+    /// Returns none for the addresses of other wallets.
+    pub fn get_diversifier_index_for_address(
+        &self,
+        address: String,
+    ) -> ZcashResult<Option<Arc<ZcashAddressMetadata>>> {
+        let conn = Connection::open(&self.path)?;
+        let stored: Option<(u32, Vec<u8>)> = conn
+            .query_row(
+                "SELECT account, diversifier_index_be FROM addresses
+                WHERE address = :address OR cached_transparent_receiver_address = :address",
+                named_params![":address": address],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        if let Some((account, di_be)) = stored {
+            let metadata = AddressMetadata::new(account.into(), diversifier_index_from_be(&di_be)?);
+            return Ok(Some(Arc::new(metadata.into())));
+        }
+
+        let (sapling, orchard) = match RecipientAddress::decode(&self.params, &address) {
+            Some(RecipientAddress::Shielded(addr)) => (Some(addr), None),
+            Some(RecipientAddress::Unified(ua)) => (ua.sapling().cloned(), ua.orchard().cloned()),
+            Some(RecipientAddress::Transparent(_)) => return Ok(None),
+            None => return Err("Unable to parse the address".into()),
+        };
+
+        let db = WalletDb::for_path(&self.path, self.params)?;
+        let mut ufvks: Vec<_> = db
+            .get_unified_full_viewing_keys()
+            .map_err(cast_err)?
+            .into_iter()
+            .collect();
+        ufvks.sort_by_key(|(account, _)| u32::from(*account));
+
+        for (account, ufvk) in ufvks {
+            let sapling_index = sapling.as_ref().and_then(|addr| {
+                match ufvk.sapling()?.decrypt_diversifier(addr)? {
+                    (index, Scope::External) => Some(index),
+                    (_, Scope::Internal) => None,
+                }
+            });
+            let orchard_index = orchard.as_ref().and_then(|addr| {
+                let ivk = ufvk.orchard()?.to_ivk(orchard::keys::Scope::External);
+                ivk.diversifier_index(addr)
+                    .map(|index| DiversifierIndex(*index.to_bytes()))
+            });
+
+            if let Some(index) = sapling_index.or(orchard_index) {
+                let metadata = AddressMetadata::new(account, index);
+                return Ok(Some(Arc::new(metadata.into())));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use rusqlite::{named_params, Connection};
+use zcash_client_backend::address::RecipientAddress;
+use zcash_client_backend::encoding::AddressCodec;
+
+use super::diversifier_index_from_be;
+use crate::{ZcashAccountId, ZcashDiversifierIndex, ZcashError, ZcashResult, ZcashWalletDb};
+
+/// How many times an address generated by the wallet has received funds.
+pub struct ZcashAddressUsage {
+    pub address: String,
+    pub diversifier_index: Arc<ZcashDiversifierIndex>,
+    pub transparent_receiver: Option<String>,
+    /// Notes received by the shielded receiver, not counting change.
+    pub shielded_receipts: u32,
+    /// Outputs received by the transparent receiver.
+    pub transparent_receipts: u32,
+}
+
+impl ZcashWalletDb {
+    /// Summarizes how many times each address of the account, and its transparent
+    /// receiver, has received funds, ordered by diversifier index. Addresses having
+    /// received more than once are reused, which privacy focused wallets can warn about.
+    pub fn address_usage(&self, account: ZcashAccountId) -> ZcashResult<Vec<ZcashAddressUsage>> {
+        let conn = Connection::open(&self.path)?;
+
+        let addresses = conn
+            .prepare(
+                "SELECT address, diversifier_index_be FROM addresses
+                WHERE account = :account
+                ORDER BY diversifier_index_be ASC",
+            )?
+            .query_map(named_params![":account": account.id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut shielded_query = conn.prepare(
+            "SELECT COUNT(*) FROM sapling_received_notes
+            WHERE account = :account AND diversifier = :diversifier AND is_change = 0",
+        )?;
+        let mut transparent_query = conn.prepare(
+            "SELECT COUNT(*) FROM utxos
+            WHERE received_by_account = :account AND address = :address",
+        )?;
+
+        addresses
+            .into_iter()
+            .map(|(address, di_be)| {
+                let ua = match RecipientAddress::decode(&self.params, &address) {
+                    Some(RecipientAddress::Unified(ua)) => ua,
+                    _ => {
+                        return Err(ZcashError::Message {
+                            error: format!("{} is not a unified address", address),
+                        })
+                    }
+                };
+
+                let shielded_receipts = match ua.sapling() {
+                    Some(sapling) => shielded_query.query_row(
+                        named_params![
+                            ":account": account.id,
+                            ":diversifier": &sapling.diversifier().0[..],
+                        ],
+                        |row| row.get(0),
+                    )?,
+                    None => 0,
+                };
+
+                let transparent_receiver = ua.transparent().map(|t| t.encode(&self.params));
+                let transparent_receipts = match &transparent_receiver {
+                    Some(taddr) => transparent_query.query_row(
+                        named_params![":account": account.id, ":address": taddr],
+                        |row| row.get(0),
+                    )?,
+                    None => 0,
+                };
+
+                Ok(ZcashAddressUsage {
+                    address,
+                    diversifier_index: Arc::new(diversifier_index_from_be(&di_be)?.into()),
+                    transparent_receiver,
+                    shielded_receipts,
+                    transparent_receipts,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::BTreeMap;
+
+use group::ff::PrimeField;
+use incrementalmerkletree::Position;
+use rusqlite::{named_params, Connection};
+use zcash_client_backend::{
+    data_api::{WalletCommitmentTrees, WalletRead},
+    keys::UnifiedFullViewingKey,
+};
+use zcash_client_sqlite::WalletDb;
+use zcash_primitives::{
+    sapling::{Diversifier, Node, Rseed},
+    transaction::components::Amount,
+};
+
+use super::history_pruning::PRUNING_DEPTH;
+use super::sync_status::chain_tip_height;
+use crate::{ZcashError, ZcashResult, ZcashWalletDb};
+
+/// The kinds of checks of [`ZcashWalletDb::audit`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ZcashWalletAuditCheck {
+    /// The commitment of an unspent note, computed from its stored parts, is not the leaf
+    /// of the note commitment tree at its position.
+    NoteCommitment,
+    /// The balance of an account is not the sum of its unspent notes and outputs.
+    Balance,
+    /// A row refers to an account or transaction the wallet does not have.
+    OrphanedRow,
+}
+
+/// An inconsistency found by [`ZcashWalletDb::audit`].
+pub struct ZcashWalletAuditIssue {
+    pub check: ZcashWalletAuditCheck,
+    /// The table of the inconsistent row.
+    pub table: String,
+    /// The id of the row, like `id_note` for notes or the account for balances.
+    pub row_id: i64,
+    pub description: String,
+}
+
+/// The outcome of [`ZcashWalletDb::audit`].
+pub struct ZcashWalletAuditReport {
+    /// How many unspent Sapling notes had their commitment checked.
+    pub notes_checked: u64,
+    /// How many accounts had their balance checked, none before the chain tip is known.
+    pub accounts_checked: u64,
+    /// The inconsistencies found, none for a sound wallet.
+    pub issues: Vec<ZcashWalletAuditIssue>,
+}
+
+/// The references between the wallet tables checked for orphaned rows: the table, its
+/// row id, the referencing column and the referenced table and column.
+const REFERENCES: &[(&str, &str, &str, &str, &str)] = &[
+    ("addresses", "rowid", "account", "accounts", "account"),
+    (
+        "sapling_received_notes",
+        "id_note",
+        "account",
+        "accounts",
+        "account",
+    ),
+    (
+        "sapling_received_notes",
+        "id_note",
+        "tx",
+        "transactions",
+        "id_tx",
+    ),
+    (
+        "sapling_received_notes",
+        "id_note",
+        "spent",
+        "transactions",
+        "id_tx",
+    ),
+    (
+        "utxos",
+        "id_utxo",
+        "received_by_account",
+        "accounts",
+        "account",
+    ),
+    ("utxos", "id_utxo", "spent_in_tx", "transactions", "id_tx"),
+    (
+        "sent_notes",
+        "id_note",
+        "from_account",
+        "accounts",
+        "account",
+    ),
+    ("sent_notes", "id_note", "tx", "transactions", "id_tx"),
+];
+
+impl ZcashWalletDb {
+    /// Checks the integrity of the wallet database, for support teams investigating
+    /// wrong balances or failing spends. It is read only, and reports what it finds
+    /// rather than failing on it:
+    ///
+    /// - the commitments of the unspent Sapling notes, computed from their stored value,
+    ///   diversifier and randomness, must be the leaves of the note commitment tree at
+    ///   their positions, or the notes cannot be spent;
+    /// - the balances of the accounts, as in [`Self::get_wallet_summary`], must be the sums
+    ///   of their unspent notes and outputs;
+    /// - the notes, outputs and addresses must belong to existing accounts and
+    ///   transactions.
+    ///
+    /// It reads all the unspent notes, so it takes a while on large wallets.
+    pub fn audit(&self) -> ZcashResult<ZcashWalletAuditReport> {
+        let conn = Connection::open(&self.path)?;
+        let mut issues = vec![];
+
+        let notes_checked = self.audit_note_commitments(&conn, &mut issues)?;
+        let accounts_checked = self.audit_balances(&conn, &mut issues)?;
+        audit_references(&conn, &mut issues)?;
+
+        Ok(ZcashWalletAuditReport {
+            notes_checked,
+            accounts_checked,
+            issues,
+        })
+    }
+
+    fn audit_note_commitments(
+        &self,
+        conn: &Connection,
+        issues: &mut Vec<ZcashWalletAuditIssue>,
+    ) -> ZcashResult<u64> {
+        let ufvks: BTreeMap<u32, UnifiedFullViewingKey> = conn
+            .prepare("SELECT account, ufvk FROM accounts")?
+            .query_map([], |row| Ok((row.get(0)?, row.get::<_, String>(1)?)))?
+            .map(|row| {
+                let (account, ufvk) = row?;
+                UnifiedFullViewingKey::decode(&self.params, &ufvk)
+                    .map(|ufvk| (account, ufvk))
+                    .map_err(|error| ZcashError::Message { error })
+            })
+            .collect::<Result<_, _>>()?;
+
+        // Spent notes are left out, as their leaves are pruned from the tree.
+        let notes = conn
+            .prepare(
+                "SELECT id_note, account, diversifier, value, rcm, commitment_tree_position
+                FROM sapling_received_notes
+                WHERE spent IS NULL AND commitment_tree_position IS NOT NULL
+                ORDER BY id_note",
+            )?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, u32>(1)?,
+                    row.get::<_, Vec<u8>>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, Vec<u8>>(4)?,
+                    row.get::<_, u64>(5)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut db = WalletDb::for_path(&self.path, self.params).expect("Cannot access the DB!");
+        for (id_note, account, diversifier, value, rcm, position) in &notes {
+            let mut issue = |description: String| {
+                issues.push(ZcashWalletAuditIssue {
+                    check: ZcashWalletAuditCheck::NoteCommitment,
+                    table: "sapling_received_notes".to_string(),
+                    row_id: *id_note,
+                    description,
+                })
+            };
+
+            let Some(dfvk) = ufvks.get(account).and_then(|ufvk| ufvk.sapling()) else {
+                issue(format!("The account {} has no Sapling key", account));
+                continue;
+            };
+            let (Ok(diversifier), Ok(rcm)) = (
+                <[u8; 11]>::try_from(&diversifier[..]),
+                <[u8; 32]>::try_from(&rcm[..]),
+            ) else {
+                issue("The diversifier or randomness of the note is malformed".to_string());
+                continue;
+            };
+            let Some(rcm) = Option::from(jubjub::Fr::from_repr(rcm)) else {
+                issue("The randomness of the note is not a scalar".to_string());
+                continue;
+            };
+
+            let leaf = db
+                .with_sapling_tree_mut(|tree| tree.get_marked_leaf(Position::from(*position)))
+                .map_err(|e| ZcashError::Message {
+                    error: format!("ShardTreeError: {:?}", e),
+                })?;
+            let Some(leaf) = leaf else {
+                issue(format!(
+                    "The note commitment tree has no marked leaf at position {}",
+                    position
+                ));
+                continue;
+            };
+
+            // The change is received at the internal addresses of the account.
+            let diversifier = Diversifier(diversifier);
+            let matches = [
+                dfvk.diversified_address(diversifier),
+                dfvk.diversified_change_address(diversifier),
+            ]
+            .into_iter()
+            .flatten()
+            .any(|address| {
+                let note = address.create_note(*value as u64, Rseed::BeforeZip212(rcm));
+                Node::from_cmu(&note.cmu()) == leaf
+            });
+            if !matches {
+                issue(format!(
+                    "The commitment of the note is not the leaf at position {}",
+                    position
+                ));
+            }
+        }
+        Ok(notes.len() as u64)
+    }
+
+    fn audit_balances(
+        &self,
+        conn: &Connection,
+        issues: &mut Vec<ZcashWalletAuditIssue>,
+    ) -> ZcashResult<u64> {
+        let Some(chain_tip_height) = chain_tip_height(conn)? else {
+            return Ok(0);
+        };
+        let summary = WalletDb::for_path(&self.path, self.params)
+            .expect("Cannot access the DB!")
+            .get_wallet_summary(1)
+            .map_err(super::cast_err)?;
+        let balances: BTreeMap<u32, i64> = summary
+            .map(|summary| {
+                summary
+                    .account_balances()
+                    .iter()
+                    .map(|(account, balance)| {
+                        (
+                            u32::from(*account),
+                            i64::from(Amount::from(balance.total())),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // The same rules as the balances, but the rows whose transactions are missing count.
+        let sums: BTreeMap<u32, i64> = conn
+            .prepare(
+                "SELECT account, SUM(value) FROM (
+                    SELECT n.account, n.value
+                    FROM sapling_received_notes n
+                    LEFT OUTER JOIN transactions t ON t.id_tx = n.tx
+                    WHERE n.spent IS NULL
+                    AND (
+                        t.expiry_height IS NULL
+                        OR t.block IS NOT NULL
+                        OR t.expiry_height >= :chain_tip_height
+                    )
+                    UNION ALL
+                    SELECT u.received_by_account, u.value_zat
+                    FROM utxos u
+                    LEFT OUTER JOIN transactions tx ON tx.id_tx = u.spent_in_tx
+                    WHERE u.height <= :chain_tip_height
+                    AND (
+                        u.spent_in_tx IS NULL
+                        OR (tx.block IS NULL AND tx.expiry_height <= :stable_height)
+                    )
+                )
+                GROUP BY account",
+            )?
+            .query_map(
+                named_params![
+                    ":chain_tip_height": chain_tip_height,
+                    ":stable_height": chain_tip_height.saturating_sub(PRUNING_DEPTH),
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?
+            .collect::<Result<_, _>>()?;
+
+        let accounts: Vec<u32> = conn
+            .prepare("SELECT account FROM accounts ORDER BY account")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        for account in &accounts {
+            let balance = balances.get(account).copied().unwrap_or(0);
+            let sum = sums.get(account).copied().unwrap_or(0);
+            if balance != sum {
+                issues.push(ZcashWalletAuditIssue {
+                    check: ZcashWalletAuditCheck::Balance,
+                    table: "accounts".to_string(),
+                    row_id: (*account).into(),
+                    description: format!(
+                        "The balance is {} zatoshis, but the unspent notes and outputs sum {}",
+                        balance, sum
+                    ),
+                });
+            }
+        }
+        Ok(accounts.len() as u64)
+    }
+}
+
+fn audit_references(conn: &Connection, issues: &mut Vec<ZcashWalletAuditIssue>) -> ZcashResult<()> {
+    for (table, id, column, referenced_table, referenced_column) in REFERENCES {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {id}, {column} FROM {table}
+            WHERE {column} IS NOT NULL
+            AND {column} NOT IN (SELECT {referenced_column} FROM {referenced_table})
+            ORDER BY {id}"
+        ))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (row_id, value) in rows {
+            issues.push(ZcashWalletAuditIssue {
+                check: ZcashWalletAuditCheck::OrphanedRow,
+                table: table.to_string(),
+                row_id,
+                description: format!(
+                    "The {} {} is not in the {} table",
+                    column, value, referenced_table
+                ),
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use rusqlite::{named_params, Connection};
+use zcash_primitives::transaction::TxId;
+
+use crate::{ZcashAccountId, ZcashResult, ZcashTxId, ZcashWalletDb};
+
+/// How a transaction changed the balance of an account, in zatoshis, per pool.
+///
+/// The changes are what the account received in the pool, change included, minus what
+/// it spent from it, so their sum is the change of the balance of the account. The fee is
+/// part of it, and is only given for the accounts which spent something in the
+/// transaction, so that accounting integrations can book it as an expense of its sender.
+pub struct ZcashTransactionBalanceChange {
+    pub account: ZcashAccountId,
+    pub transparent: i64,
+    pub sapling: i64,
+    /// Always zero, as the wallet does not track Orchard notes yet.
+    pub orchard: i64,
+    /// The fee paid by the transaction, if known.
+    pub fee: Option<u64>,
+}
+
+impl ZcashWalletDb {
+    /// Returns how the given transaction changed the balances of the accounts of the
+    /// wallet, ordered by account. Unlike the lookup of the transaction, it does not need
+    /// its raw data, so it also works for transactions whose history was pruned.
+    pub fn get_transaction_balance_changes(
+        &self,
+        txid: Arc<ZcashTxId>,
+    ) -> ZcashResult<Vec<ZcashTransactionBalanceChange>> {
+        let conn = Connection::open(&self.path)?;
+        balance_changes(&conn, &(*txid).into())
+    }
+}
+
+pub(super) fn balance_changes(
+    conn: &Connection,
+    txid: &TxId,
+) -> ZcashResult<Vec<ZcashTransactionBalanceChange>> {
+    let mut stmt = conn.prepare(
+        "WITH tx AS (SELECT id_tx, txid, fee FROM transactions WHERE txid = :txid),
+        changes AS (
+            SELECT n.account AS account, 0 AS transparent, n.value AS sapling, 0 AS spent
+            FROM sapling_received_notes n JOIN tx ON n.tx = tx.id_tx
+            UNION ALL
+            SELECT n.account, 0, -n.value, 1
+            FROM sapling_received_notes n JOIN tx ON n.spent = tx.id_tx
+            UNION ALL
+            SELECT u.received_by_account, u.value_zat, 0, 0
+            FROM utxos u JOIN tx ON u.prevout_txid = tx.txid
+            UNION ALL
+            SELECT u.received_by_account, -u.value_zat, 0, 1
+            FROM utxos u JOIN tx ON u.spent_in_tx = tx.id_tx
+        )
+        SELECT account, SUM(transparent), SUM(sapling), MAX(spent), (SELECT fee FROM tx)
+        FROM changes
+        GROUP BY account
+        ORDER BY account",
+    )?;
+    let changes = stmt
+        .query_map(named_params![":txid": &txid.as_ref()[..]], |row| {
+            let spent: bool = row.get(3)?;
+            Ok(ZcashTransactionBalanceChange {
+                account: ZcashAccountId { id: row.get(0)? },
+                transparent: row.get(1)?,
+                sapling: row.get(2)?,
+                orchard: 0,
+                fee: if spent { row.get(4)? } else { None },
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(changes)
+}
@@ -0,0 +1,76 @@
+use std::sync::{Arc, Mutex};
+
+use zcash_client_backend::data_api::{ScannedBlock, WalletWrite};
+use zcash_client_sqlite::WalletDb;
+use zcash_primitives::sapling;
+
+use super::cast_err;
+use crate::{ZcashConsensusParameters, ZcashResult, ZcashScannedBlock, ZcashWalletDb};
+
+/// Scanned blocks which are written to the wallet all together when committed, or not at
+/// all, so that a sync killed midway does not leave the wallet with only part of them.
+///
+/// The blocks are kept in memory until [`Self::commit`], which writes them with a single
+/// `put_blocks` call, in a single transaction on a single connection, like the other
+/// writes to the wallet. Nothing is written before, so nothing written meanwhile by the
+/// rest of the app is lost. Dropping the batch rolls it back.
+///
+/// Only blocks are batched: upstream runs `update_chain_tip` and `store_decrypted_tx` in
+/// transactions of their own, on connections of their own, which cannot join the one of
+/// `put_blocks`. They are atomic by themselves and can be called again after a crash, as
+/// the scan queue then still has the blocks of the batch to scan.
+pub struct ZcashBlockBatch {
+    wallet_path: String,
+    params: ZcashConsensusParameters,
+    blocks: Mutex<Option<Vec<ZcashScannedBlock>>>,
+}
+
+impl ZcashWalletDb {
+    /// Starts a batch of scanned blocks to write to the wallet, see [`ZcashBlockBatch`].
+    pub fn begin_block_batch(&self) -> Arc<ZcashBlockBatch> {
+        Arc::new(ZcashBlockBatch {
+            wallet_path: self.path.clone(),
+            params: self.params,
+            blocks: Mutex::new(Some(vec![])),
+        })
+    }
+}
+
+impl ZcashBlockBatch {
+    /// Adds the given blocks to the batch. They must follow the blocks already in it.
+    pub fn put_blocks(&self, blocks: Vec<Arc<ZcashScannedBlock>>) -> ZcashResult<()> {
+        self.blocks
+            .lock()
+            .unwrap()
+            .as_mut()
+            .ok_or(BATCH_FINISHED)?
+            .extend(blocks.iter().map(|x| (**x).clone()));
+        Ok(())
+    }
+
+    /// Writes all the blocks of the batch to the wallet at once. The batch cannot be used
+    /// anymore afterwards, unless this fails, as when its blocks are not sequential or the
+    /// wallet is busy: then nothing is written, and it can be retried.
+    pub fn commit(&self) -> ZcashResult<()> {
+        let mut blocks = self.blocks.lock().unwrap();
+        let pending: Vec<ScannedBlock<sapling::Nullifier>> = blocks
+            .as_ref()
+            .ok_or(BATCH_FINISHED)?
+            .iter()
+            .map(|x| x.clone().into())
+            .collect();
+        WalletDb::for_path(&self.wallet_path, self.params)?
+            .put_blocks(pending)
+            .map_err(cast_err)?;
+        blocks.take();
+        Ok(())
+    }
+
+    /// Discards all the blocks of the batch. The batch cannot be used anymore afterwards.
+    pub fn rollback(&self) -> ZcashResult<()> {
+        self.blocks.lock().unwrap().take().ok_or(BATCH_FINISHED)?;
+        Ok(())
+    }
+}
+
+const BATCH_FINISHED: &str = "The batch is already committed or rolled back";
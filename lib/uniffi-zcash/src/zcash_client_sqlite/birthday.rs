@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use incrementalmerkletree::Retention;
+use rusqlite::{named_params, Connection, OptionalExtension};
+use zcash_client_backend::data_api::{AccountBirthday, WalletCommitmentTrees};
+use zcash_client_sqlite::WalletDb;
+use zcash_primitives::consensus::{NetworkUpgrade, Parameters};
+
+use super::scan_queue::{queue_ranges, raise_queue_range, restore_queue_range, PRIORITY_HISTORIC};
+use crate::{ZcashAccountBirthday, ZcashAccountId, ZcashError, ZcashResult, ZcashWalletDb};
+
+impl ZcashWalletDb {
+    /// Lowers the birthday of an account, for when older transactions of the account turn
+    /// up, so the wallet does not need to be recreated from scratch. The note commitment
+    /// tree gets the frontier of the new birthday, and the blocks between the new and the
+    /// former birthday are queued for scanning, even those scanned before for other
+    /// accounts, unless they are already queued with a higher priority.
+    pub fn lower_account_birthday(
+        &self,
+        account: ZcashAccountId,
+        birthday: Arc<ZcashAccountBirthday>,
+    ) -> ZcashResult<()> {
+        let birthday: AccountBirthday = (*birthday).clone().into();
+        let new_height = u32::from(birthday.height());
+
+        let current_height: u32 = Connection::open(&self.path)?
+            .query_row(
+                "SELECT birthday_height FROM accounts WHERE account = :account",
+                named_params![":account": account.id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| ZcashError::Message {
+                error: format!("The wallet has no account {}", account.id),
+            })?;
+
+        if new_height >= current_height {
+            return Err(ZcashError::Message {
+                error: format!(
+                    "The new birthday {} is not lower than the current one {}",
+                    new_height, current_height
+                ),
+            });
+        }
+        if let Some(sapling_activation) = self.params.activation_height(NetworkUpgrade::Sapling) {
+            if birthday.height() < sapling_activation {
+                return Err(ZcashError::Message {
+                    error: format!(
+                        "The new birthday {} is before the Sapling activation {}",
+                        new_height, sapling_activation
+                    ),
+                });
+            }
+        }
+
+        let mut conn = Connection::open(&self.path)?;
+        let tx = conn.transaction()?;
+        let former_recover_until: Option<u32> = tx.query_row(
+            "SELECT recover_until_height FROM accounts WHERE account = :account",
+            named_params![":account": account.id],
+            |row| row.get(0),
+        )?;
+        tx.execute(
+            "UPDATE accounts
+            SET birthday_height = :birthday_height,
+                recover_until_height = COALESCE(:recover_until_height, recover_until_height)
+            WHERE account = :account",
+            named_params![
+                ":birthday_height": new_height,
+                ":recover_until_height": birthday.recover_until().map(u32::from),
+                ":account": account.id,
+            ],
+        )?;
+
+        // Even the blocks scanned for other accounts are scanned again for this one, while
+        // those already queued with a higher priority keep it.
+        let former_queue = queue_ranges(&tx, new_height..current_height)?;
+        raise_queue_range(&tx, new_height..current_height, PRIORITY_HISTORIC)?;
+        tx.commit()?;
+
+        // The tree is written once the birthday is stored. If that fails, the birthday and
+        // the scan queue are put back as they were.
+        if let Err(e) = self.insert_birthday_frontier(&birthday) {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "UPDATE accounts
+                SET birthday_height = :birthday_height,
+                    recover_until_height = :recover_until_height
+                WHERE account = :account",
+                named_params![
+                    ":birthday_height": current_height,
+                    ":recover_until_height": former_recover_until,
+                    ":account": account.id,
+                ],
+            )?;
+            restore_queue_range(&tx, new_height..current_height, &former_queue)?;
+            tx.commit()?;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Inserts the frontier of the birthday in the note commitment tree, as upstream does
+    /// when creating an account: it is the tree state at the end of the block before the
+    /// birthday. The tree is only written through the upstream wallet, with a connection
+    /// of its own. The frontier and its checkpoint can be inserted again.
+    pub(super) fn insert_birthday_frontier(&self, birthday: &AccountBirthday) -> ZcashResult<()> {
+        if let Some(frontier) = birthday.sapling_frontier().value() {
+            WalletDb::for_path(&self.path, self.params)?
+                .with_sapling_tree_mut(|tree| {
+                    tree.insert_frontier_nodes(
+                        frontier.clone(),
+                        Retention::Checkpoint {
+                            id: birthday.height() - 1,
+                            is_marked: false,
+                        },
+                    )
+                })
+                .map_err(|e| ZcashError::Message {
+                    error: format!("ShardTreeError: {:?}", e),
+                })?;
+        }
+        Ok(())
+    }
+}
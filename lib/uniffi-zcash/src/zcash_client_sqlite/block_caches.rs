@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use zcash_client_sqlite::chain::init::init_blockmeta_db;
+use zcash_client_sqlite::FsBlockDb;
+
+use crate::{
+    runtime_config, validation::invalid_argument, ZcashConsensusParameters, ZcashError,
+    ZcashFsBlockDb, ZcashResult,
+};
+
+/// The file of a cache directory naming the network of its blocks, so that the functions
+/// given the directory alone can check it.
+const NETWORK_FILE: &str = "network";
+
+fn network_name(params: ZcashConsensusParameters) -> &'static str {
+    match params {
+        ZcashConsensusParameters::MainNetwork => "mainnet",
+        ZcashConsensusParameters::TestNetwork => "testnet",
+    }
+}
+
+/// The directory of the block cache of the given network and lightwalletd server, under
+/// the root shared by all the caches of the app. Each network gets its own directory, and
+/// each server its own one inside it, so that their blocks never get mixed. Servers whose
+/// directory would be the network one, or its parent, are refused.
+fn cache_dir(
+    cache_root: &Path,
+    params: ZcashConsensusParameters,
+    server: Option<&str>,
+) -> ZcashResult<PathBuf> {
+    let dir = cache_root.join(network_name(params));
+    let Some(server) = server else {
+        return Ok(dir.join("default"));
+    };
+    let name = server
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if matches!(name.as_str(), "" | "." | "..") {
+        return Err(invalid_argument(
+            "server",
+            format!("{:?} cannot name a cache directory", server),
+        ));
+    }
+    Ok(dir.join(name))
+}
+
+/// Fails if the cache at `cache_root` holds the blocks of another network than the given
+/// one. The caches which were not opened with [`ZcashFsBlockDb::for_network`] are not
+/// checked, as their network is unknown.
+pub(crate) fn check_cache_network(
+    cache_root: &Path,
+    params: ZcashConsensusParameters,
+) -> ZcashResult<()> {
+    match fs::read_to_string(cache_root.join(NETWORK_FILE)) {
+        Ok(network) if network != network_name(params) => Err(ZcashError::Message {
+            error: format!(
+                "The block cache is for the {} network, not the {} one",
+                network,
+                network_name(params)
+            ),
+        }),
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl ZcashFsBlockDb {
+    /// Opens the block cache of the given network and lightwalletd server under the
+    /// `cache_root` directory, creating and initializing it if needed. Several caches can
+    /// share the same root, as they are kept in different directories.
+    pub fn for_network(
+        cache_root: String,
+        params: ZcashConsensusParameters,
+        server: Option<String>,
+    ) -> ZcashResult<Self> {
+        let root = cache_dir(Path::new(&cache_root), params, server.as_deref())?;
+        fs::create_dir_all(&root)?;
+        check_cache_network(&root, params)?;
+        fs::write(root.join(NETWORK_FILE), network_name(params))?;
+
+        let mut db = FsBlockDb::for_path(&root).map_err(|e| ZcashError::Message {
+            error: format!("FsBlockDbError: {:?}", e),
+        })?;
+        init_blockmeta_db(&mut db).map_err(|e| ZcashError::Message {
+            error: format!("MigratorError: {:?}", e),
+        })?;
+
+        Ok(ZcashFsBlockDb {
+            fs_block_db: Mutex::new(db),
+            fsblockdb_root: root,
+            params: Some(params),
+            compression_level: Mutex::new(None),
+        })
+    }
+
+    /// Opens the block cache of the given network and lightwalletd server in the `blocks`
+    /// subdirectory of the cache directory set with `ZcashRuntimeConfig::set_cache_dir`.
+    pub fn with_default_location(
+        params: ZcashConsensusParameters,
+        server: Option<String>,
+    ) -> ZcashResult<Self> {
+        let cache_root = runtime_config::blocks_dir()?;
+        Self::for_network(cache_root.to_string_lossy().into_owned(), params, server)
+    }
+
+    /// The directory of the cache, to be given to the functions scanning it.
+    pub fn root(&self) -> String {
+        self.fsblockdb_root.to_string_lossy().into_owned()
+    }
+
+    /// The network of the cache, if it was opened with [`ZcashFsBlockDb::for_network`].
+    pub fn network(&self) -> Option<ZcashConsensusParameters> {
+        self.params
+    }
+
+    /// Fails if the cache holds the blocks of another network than the given one, see
+    /// [`check_cache_network`].
+    pub(crate) fn check_network(&self, params: ZcashConsensusParameters) -> ZcashResult<()> {
+        check_cache_network(&self.fsblockdb_root, params)
+    }
+}
+
+/// The network and lightwalletd server of a block cache.
+type CacheKey = (ZcashConsensusParameters, Option<String>);
+
+/// The block caches of an app, one per network and lightwalletd server, all kept under the
+/// same root directory. The same cache is returned every time for a network and server,
+/// so the wallets syncing concurrently from it share its lock.
+pub struct ZcashBlockCaches {
+    cache_root: String,
+    caches: Mutex<HashMap<CacheKey, Arc<ZcashFsBlockDb>>>,
+}
+
+impl ZcashBlockCaches {
+    pub fn new(cache_root: String) -> Self {
+        ZcashBlockCaches {
+            cache_root,
+            caches: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cache of the given network and server, opening it on first use.
+    pub fn get(
+        &self,
+        params: ZcashConsensusParameters,
+        server: Option<String>,
+    ) -> ZcashResult<Arc<ZcashFsBlockDb>> {
+        let mut caches = self.caches.lock().unwrap();
+        let key = (params, server);
+        if let Some(cache) = caches.get(&key) {
+            return Ok(cache.clone());
+        }
+        let cache = Arc::new(ZcashFsBlockDb::for_network(
+            self.cache_root.clone(),
+            params,
+            key.1.clone(),
+        )?);
+        caches.insert(key, cache.clone());
+        Ok(cache)
+    }
+}
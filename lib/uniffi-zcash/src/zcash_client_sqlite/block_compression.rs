@@ -0,0 +1,166 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use prost::Message;
+use rusqlite::{params, Connection};
+use zcash_client_backend::data_api::chain::{error::Error, BlockSource};
+use zcash_client_backend::proto::compact_formats::CompactBlock;
+use zcash_client_sqlite::chain::BlockMeta;
+use zcash_client_sqlite::FsBlockDbError;
+use zcash_primitives::block::BlockHash;
+use zcash_primitives::consensus::BlockHeight;
+
+use crate::{validation::invalid_argument, ZcashBlockMeta, ZcashFsBlockDb, ZcashResult};
+
+/// The magic number every zstd frame starts with. Encoded compact blocks cannot start
+/// with it, as their first field is never the block time, so compressed block files are
+/// told apart from the plain ones by it.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+impl ZcashFsBlockDb {
+    /// Sets the zstd compression level of the block files stored from now on, or disables
+    /// compression if `None`. Compact blocks are mostly made of note ciphertexts, so
+    /// compressing them roughly halves the space the cache takes during the first sync of
+    /// a wallet, at the cost of some CPU time when downloading and scanning. The files
+    /// already stored are kept as they are: scanning reads both kinds.
+    pub fn set_compression_level(&self, level: Option<i32>) -> ZcashResult<()> {
+        if let Some(level) = level {
+            let levels = zstd::compression_level_range();
+            if !levels.contains(&level) {
+                return Err(invalid_argument(
+                    "level",
+                    format!("must be between {} and {}", levels.start(), levels.end()),
+                ));
+            }
+        }
+        *self.compression_level.lock().unwrap() = level;
+        Ok(())
+    }
+
+    /// The zstd compression level of the block files, `None` if they are not compressed.
+    pub fn compression_level(&self) -> Option<i32> {
+        *self.compression_level.lock().unwrap()
+    }
+
+    /// Stores the given encoded compact block in the file of its metadata, compressed if
+    /// a compression level is set. Its metadata must be written afterwards for the block
+    /// to be scanned.
+    pub fn write_block_file(
+        &self,
+        block_meta: Arc<ZcashBlockMeta>,
+        block: Vec<u8>,
+    ) -> ZcashResult<()> {
+        self.write_block(&block_meta.0, &block)
+    }
+
+    pub(crate) fn write_block(&self, meta: &BlockMeta, block: &[u8]) -> ZcashResult<()> {
+        let path = meta.block_file_path(&self.fsblockdb_root.join("blocks"));
+        match self.compression_level() {
+            Some(level) => fs::write(path, zstd::encode_all(block, level)?)?,
+            None => fs::write(path, block)?,
+        }
+        Ok(())
+    }
+}
+
+/// The blocks of a block cache, as read by the scanning functions. It reads the blocks the
+/// way `FsBlockDb` does, decompressing the compressed ones.
+pub(crate) struct CachedBlocks {
+    conn: Connection,
+    blocks_dir: PathBuf,
+}
+
+impl CachedBlocks {
+    pub(crate) fn for_path<P: AsRef<Path>>(fsblockdb_root: P) -> Result<Self, FsBlockDbError> {
+        let root = fsblockdb_root.as_ref();
+        if !fs::metadata(root)?.is_dir() {
+            return Err(FsBlockDbError::InvalidBlockstoreRoot(root.to_path_buf()));
+        }
+        Ok(CachedBlocks {
+            conn: Connection::open(root.join("blockmeta.sqlite"))?,
+            blocks_dir: root.join("blocks"),
+        })
+    }
+
+    fn read_block(&self, meta: &BlockMeta) -> Result<CompactBlock, FsBlockDbError> {
+        let data = fs::read(meta.block_file_path(&self.blocks_dir))?;
+        let block = if data.starts_with(&ZSTD_MAGIC) {
+            CompactBlock::decode(&zstd::decode_all(&data[..])?[..])?
+        } else {
+            CompactBlock::decode(&data[..])?
+        };
+
+        if block.height() != meta.height {
+            return Err(FsBlockDbError::CorruptedData(format!(
+                "Block height {} did not match row's height field value {}",
+                block.height(),
+                meta.height
+            )));
+        }
+        Ok(block)
+    }
+}
+
+impl BlockSource for CachedBlocks {
+    type Error = FsBlockDbError;
+
+    fn with_blocks<F, DbErrT>(
+        &self,
+        from_height: Option<BlockHeight>,
+        limit: Option<usize>,
+        mut with_row: F,
+    ) -> Result<(), Error<DbErrT, Self::Error>>
+    where
+        F: FnMut(CompactBlock) -> Result<(), Error<DbErrT, Self::Error>>,
+    {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT height, blockhash, time, sapling_outputs_count, orchard_actions_count
+                FROM compactblocks_meta
+                WHERE height >= ?
+                ORDER BY height ASC LIMIT ?",
+            )
+            .map_err(|e| Error::BlockSource(e.into()))?;
+        let rows = stmt
+            .query_map(
+                params![
+                    from_height.map_or(0, u32::from),
+                    limit
+                        .and_then(|l| u32::try_from(l).ok())
+                        .unwrap_or(u32::MAX)
+                ],
+                |row| {
+                    Ok(BlockMeta {
+                        height: BlockHeight::from_u32(row.get(0)?),
+                        block_hash: BlockHash::from_slice(&row.get::<_, Vec<u8>>(1)?),
+                        block_time: row.get(2)?,
+                        sapling_outputs_count: row.get(3)?,
+                        orchard_actions_count: row.get(4)?,
+                    })
+                },
+            )
+            .map_err(|e| Error::BlockSource(e.into()))?;
+
+        // As with `FsBlockDb`, the cache must hold the first requested block.
+        let mut from_height_found = from_height.is_none();
+        for meta in rows {
+            let meta = meta.map_err(|e| Error::BlockSource(e.into()))?;
+            if !from_height_found {
+                if Some(meta.height) != from_height {
+                    break;
+                }
+                from_height_found = true;
+            }
+            with_row(self.read_block(&meta).map_err(Error::BlockSource)?)?;
+        }
+
+        match from_height {
+            Some(from_height) if !from_height_found => {
+                Err(Error::BlockSource(FsBlockDbError::CacheMiss(from_height)))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use rusqlite::{named_params, Connection, OptionalExtension};
+use zcash_primitives::transaction::TxId;
+
+use crate::{ZcashBlockHeight, ZcashResult, ZcashTxId, ZcashWalletDb};
+
+/// The number of blocks whose times give the median time past of a block, as in the
+/// consensus rules: the block itself and the ten before it.
+const MEDIAN_TIME_SPAN: u32 = 11;
+
+/// The times of a scanned block, as stored by the wallet when scanning it, so history
+/// lists can show dates without fetching the block headers again.
+pub struct ZcashBlockTimes {
+    pub height: Arc<ZcashBlockHeight>,
+    /// The time in the block header, in seconds since the Unix epoch.
+    pub block_time: u32,
+    /// The median of the times of the block and the ten before it, which unlike the
+    /// block time always increases along the chain. It is unknown unless the wallet
+    /// scanned all of them.
+    pub median_time_past: Option<u32>,
+}
+
+impl ZcashWalletDb {
+    /// Returns the times of the given block, if the wallet scanned it.
+    pub fn get_block_times(
+        &self,
+        height: Arc<ZcashBlockHeight>,
+    ) -> ZcashResult<Option<ZcashBlockTimes>> {
+        let conn = Connection::open(&self.path)?;
+        block_times(&conn, height.value())
+    }
+
+    /// Returns the times of the block that mined the given transaction, if the wallet
+    /// scanned it. Unmined transactions have none.
+    pub fn get_tx_block_times(&self, txid: Arc<ZcashTxId>) -> ZcashResult<Option<ZcashBlockTimes>> {
+        let txid: TxId = (*txid).into();
+        let conn = Connection::open(&self.path)?;
+        let height: Option<u32> = conn
+            .query_row(
+                "SELECT block FROM transactions WHERE txid = :txid",
+                named_params![":txid": &txid.as_ref()[..]],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        match height {
+            Some(height) => block_times(&conn, height),
+            None => Ok(None),
+        }
+    }
+}
+
+pub(super) fn block_times(conn: &Connection, height: u32) -> ZcashResult<Option<ZcashBlockTimes>> {
+    let block_time: Option<u32> = conn
+        .query_row(
+            "SELECT time FROM blocks WHERE height = :height",
+            named_params![":height": height],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let block_time = match block_time {
+        Some(time) => time,
+        None => return Ok(None),
+    };
+
+    // Near the genesis block there are fewer blocks to take the median of.
+    let start = height.saturating_sub(MEDIAN_TIME_SPAN - 1);
+    let mut times = conn
+        .prepare("SELECT time FROM blocks WHERE height >= :start AND height <= :end")?
+        .query_map(named_params![":start": start, ":end": height], |row| {
+            row.get::<_, u32>(0)
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let median_time_past = if times.len() as u32 == height - start + 1 {
+        times.sort_unstable();
+        Some(times[times.len() / 2])
+    } else {
+        None
+    };
+
+    Ok(Some(ZcashBlockTimes {
+        height: Arc::new(ZcashBlockHeight::new(height)),
+        block_time,
+        median_time_past,
+    }))
+}
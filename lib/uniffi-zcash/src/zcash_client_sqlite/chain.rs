@@ -1,10 +1,22 @@
+use prost::Message;
+use rusqlite::Connection;
+use std::fs;
 use std::sync::Arc;
+use zcash_client_backend::data_api::AccountBirthday;
+use zcash_client_backend::proto::service::{BlockId, BlockRange};
 use zcash_client_sqlite::chain::BlockMeta;
 
-use crate::{ZcashBlockHash, ZcashBlockHeight};
+use crate::{
+    validation::check_range, ZcashAccountBirthday, ZcashBlockHash, ZcashBlockHeight,
+    ZcashFsBlockDb, ZcashLightwalletdClient, ZcashResult, ZcashWalletDb,
+};
 
 use derive_more::{From, Into};
 
+/// How many downloaded blocks get their metadata written at once. Progress is reported
+/// after each of these batches, rather than after every block.
+const DOWNLOAD_BATCH_SIZE: usize = 100;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, From, Into)]
 pub struct ZcashBlockMeta(pub BlockMeta);
 
@@ -32,3 +44,154 @@ pub fn block_file_path(&self, blocks_dir: String) -> String {
             .to_string()
     }
 }
+
+/// Receives the progress of [`download_blocks`].
+pub trait ZcashBlockDownloadListener: Send + Sync {
+    /// Called each time a batch of blocks is stored, with the height of the last stored
+    /// block and the count of blocks stored so far, out of the total to download.
+    fn on_progress(&self, height: u32, downloaded: u32, total: u32);
+}
+
+/// Streams the compact blocks in the `start_height..end_height` range from lightwalletd,
+/// storing them in the given block cache, along with their metadata. Blocks never cross
+/// the FFI. Returns the count of stored blocks. Fails if the cache was opened with
+/// [`ZcashFsBlockDb::for_network`] for another network than the one of the server.
+pub fn download_blocks(
+    client: Arc<ZcashLightwalletdClient>,
+    fs_block_db: Arc<ZcashFsBlockDb>,
+    start_height: u32,
+    end_height: u32,
+    progress_listener: Option<Box<dyn ZcashBlockDownloadListener>>,
+) -> ZcashResult<u32> {
+    check_range("start_height..end_height", start_height, end_height)?;
+    if end_height == start_height {
+        return Ok(0);
+    }
+    let total = end_height - start_height;
+    if let Some(params) = fs_block_db.params {
+        client.check_network(params)?;
+    }
+
+    let blocks_dir = fs_block_db.fsblockdb_root.join("blocks");
+    fs::create_dir_all(&blocks_dir)?;
+
+    // lightwalletd ranges include their end.
+    let range = BlockRange {
+        start: Some(BlockId {
+            height: start_height.into(),
+            hash: vec![],
+        }),
+        end: Some(BlockId {
+            height: (end_height - 1).into(),
+            hash: vec![],
+        }),
+    };
+
+    let mut grpc = client.client.clone();
+    client.runtime.block_on(async {
+        let mut stream = grpc.get_block_range(range).await?.into_inner();
+        let mut batch: Vec<BlockMeta> = Vec::with_capacity(DOWNLOAD_BATCH_SIZE);
+        let mut downloaded = 0;
+
+        loop {
+            let block = stream.message().await?;
+            if let Some(block) = &block {
+                let meta = BlockMeta {
+                    height: block.height(),
+                    block_hash: block.hash(),
+                    block_time: block.time,
+                    sapling_outputs_count: block.vtx.iter().map(|tx| tx.outputs.len() as u32).sum(),
+                    orchard_actions_count: block.vtx.iter().map(|tx| tx.actions.len() as u32).sum(),
+                };
+                fs_block_db.write_block(&meta, &block.encode_to_vec())?;
+                batch.push(meta);
+            }
+
+            if batch.len() == DOWNLOAD_BATCH_SIZE || (block.is_none() && !batch.is_empty()) {
+                fs_block_db
+                    .fs_block_db
+                    .lock()
+                    .unwrap()
+                    .write_block_metadata(&batch)
+                    .map_err(|e| format!("FsBlockDbError: {:?}", e))?;
+                downloaded += batch.len() as u32;
+                if let Some(listener) = &progress_listener {
+                    listener.on_progress(batch.last().unwrap().height.into(), downloaded, total);
+                }
+                batch.clear();
+            }
+
+            if block.is_none() {
+                return Ok(downloaded);
+            }
+        }
+    })
+}
+
+/// What [`fast_forward_to_birthday`] set the wallet up with.
+pub struct ZcashFastForwardReport {
+    /// The count of Sapling subtree roots stored.
+    pub subtree_roots: u32,
+    pub birthday_height: u32,
+    pub chain_tip_height: u32,
+}
+
+/// Sets the wallet up for its first sync, so that scanning starts at its birthday rather
+/// than at the activation of Sapling: stores the roots of the completed subtrees of the
+/// Sapling note commitment tree, letting the notes be witnessed without scanning the
+/// blocks before the birthday, then updates the chain tip, which queues the ranges from
+/// the birthday to the tip for scanning.
+///
+/// The accounts must be created first. The frontier of the tree at the birthday of the
+/// wallet is inserted from the tree state of the block before it, as creating an account
+/// does, so it is there even for accounts created before the tree state was known. This
+/// wallet version keeps no Orchard tree, so only the Sapling one is set up. Calling it
+/// again later only fetches and stores the subtree roots the wallet does not know yet and
+/// updates the chain tip.
+pub fn fast_forward_to_birthday(
+    client: Arc<ZcashLightwalletdClient>,
+    wallet_db: Arc<ZcashWalletDb>,
+) -> ZcashResult<ZcashFastForwardReport> {
+    let birthday_height = wallet_db
+        .get_wallet_birthday()?
+        .ok_or("The wallet has no account to fast forward to the birthday of")?
+        .value();
+
+    let start_index = known_sapling_subtree_roots(&wallet_db.path)?;
+    let roots = client.get_sapling_subtree_roots(start_index)?;
+    let subtree_roots = roots.len() as u32;
+    wallet_db.put_sapling_subtree_roots(start_index.into(), roots)?;
+
+    let tree_state = client.get_tree_state(birthday_height - 1)?;
+    let birthday: AccountBirthday = ZcashAccountBirthday::from_treestate(tree_state, None)?.into();
+    wallet_db.insert_birthday_frontier(&birthday)?;
+
+    let chain_tip_height = client.latest_block_height()?;
+    wallet_db.update_chain_tip(chain_tip_height)?;
+
+    Ok(ZcashFastForwardReport {
+        subtree_roots,
+        birthday_height,
+        chain_tip_height,
+    })
+}
+
+/// The index of the first subtree whose root the wallet does not know. Those completed by
+/// scanning after it do not count, so that the roots before them are still fetched.
+fn known_sapling_subtree_roots(wallet_path: &str) -> ZcashResult<u32> {
+    let conn = Connection::open(wallet_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT shard_index FROM sapling_tree_shards
+        WHERE subtree_end_height IS NOT NULL
+        ORDER BY shard_index",
+    )?;
+    let indexes = stmt.query_map([], |row| row.get::<_, u32>(0))?;
+    let mut count = 0;
+    for index in indexes {
+        if index? != count {
+            break;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
@@ -0,0 +1,152 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use rusqlite::Connection;
+use zcash_client_backend::data_api::WalletRead;
+use zcash_client_sqlite::WalletDb;
+use zcash_primitives::transaction::{Transaction, TxId};
+
+use super::cast_err;
+use crate::{ZcashConsensusParameters, ZcashResult, ZcashTxId, ZcashWalletDb};
+
+/// A note or output spent by a transaction, identified the same way in any transaction
+/// spending it.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum SpentInput {
+    SaplingNullifier([u8; 32]),
+    OrchardNullifier([u8; 32]),
+    TransparentOutPoint([u8; 32], u32),
+}
+
+/// The notes and outputs spent by the transactions of the wallet it has in full, to find
+/// which transactions spend the same ones. Only one of them can be mined, the others
+/// being replaced, like by a fee bump, or invalidated.
+pub(super) struct ConflictIndex {
+    inputs: HashMap<TxId, Vec<SpentInput>>,
+    spenders: HashMap<SpentInput, BTreeSet<TxId>>,
+}
+
+impl ConflictIndex {
+    pub(super) fn build(
+        db: &WalletDb<Connection, ZcashConsensusParameters>,
+        conn: &Connection,
+    ) -> ZcashResult<Self> {
+        let txids = conn
+            .prepare("SELECT txid FROM transactions WHERE raw IS NOT NULL")?
+            .query_map([], |row| row.get::<_, Vec<u8>>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut index = ConflictIndex {
+            inputs: HashMap::new(),
+            spenders: HashMap::new(),
+        };
+        for txid in txids {
+            let txid = TxId::from_bytes(txid.try_into().map_err(|_| "Invalid stored txid")?);
+            let inputs = spent_inputs(&db.get_transaction(txid).map_err(cast_err)?);
+            for input in &inputs {
+                index
+                    .spenders
+                    .entry(input.clone())
+                    .or_default()
+                    .insert(txid);
+            }
+            index.inputs.insert(txid, inputs);
+        }
+        Ok(index)
+    }
+
+    /// The other transactions spending any of the notes or outputs the transaction spends,
+    /// in txid order.
+    pub(super) fn conflicts_of(&self, txid: &TxId) -> Vec<TxId> {
+        let mut conflicts = BTreeSet::new();
+        for input in self.inputs.get(txid).into_iter().flatten() {
+            conflicts.extend(self.spenders[input].iter().filter(|other| *other != txid));
+        }
+        conflicts.into_iter().collect()
+    }
+
+    /// The groups of transactions which conflict with each other, directly or through
+    /// others of the group.
+    fn conflict_sets(&self) -> Vec<Vec<TxId>> {
+        let mut visited = BTreeSet::new();
+        let mut sets = vec![];
+        let mut txids: Vec<&TxId> = self.inputs.keys().collect();
+        txids.sort();
+        for txid in txids {
+            if visited.contains(txid) || self.conflicts_of(txid).is_empty() {
+                continue;
+            }
+            let mut set = BTreeSet::new();
+            let mut pending = vec![*txid];
+            while let Some(txid) = pending.pop() {
+                if set.insert(txid) {
+                    pending.extend(self.conflicts_of(&txid));
+                }
+            }
+            visited.extend(set.iter().copied());
+            sets.push(set.into_iter().collect());
+        }
+        sets
+    }
+}
+
+fn spent_inputs(tx: &Transaction) -> Vec<SpentInput> {
+    let mut inputs = vec![];
+    if let Some(bundle) = tx.transparent_bundle() {
+        inputs.extend(
+            bundle.vin.iter().map(|txin| {
+                SpentInput::TransparentOutPoint(*txin.prevout.hash(), txin.prevout.n())
+            }),
+        );
+    }
+    if let Some(bundle) = tx.sapling_bundle() {
+        inputs.extend(
+            bundle
+                .shielded_spends()
+                .iter()
+                .map(|spend| SpentInput::SaplingNullifier(spend.nullifier().0)),
+        );
+    }
+    if let Some(bundle) = tx.orchard_bundle() {
+        inputs.extend(
+            bundle
+                .actions()
+                .iter()
+                .map(|action| SpentInput::OrchardNullifier(action.nullifier().to_bytes())),
+        );
+    }
+    inputs
+}
+
+impl ZcashWalletDb {
+    /// Returns the other transactions of the wallet spending any of the notes or outputs
+    /// the transaction spends, of which at most one can be mined. Only the transactions
+    /// the wallet has in full are checked: those it created or decrypted and stored.
+    pub fn get_conflicting_transactions(
+        &self,
+        txid: Arc<ZcashTxId>,
+    ) -> ZcashResult<Vec<Arc<ZcashTxId>>> {
+        let db = WalletDb::for_path(&self.path, self.params)?;
+        let conn = Connection::open(&self.path)?;
+        let index = ConflictIndex::build(&db, &conn)?;
+        Ok(index
+            .conflicts_of(&(*txid).into())
+            .into_iter()
+            .map(|txid| Arc::new(txid.into()))
+            .collect())
+    }
+
+    /// Returns the groups of transactions of the wallet spending the same notes or outputs,
+    /// for history lists to show which ones replace each other, like after a fee bump or a
+    /// reorg.
+    pub fn get_conflict_sets(&self) -> ZcashResult<Vec<Vec<Arc<ZcashTxId>>>> {
+        let db = WalletDb::for_path(&self.path, self.params)?;
+        let conn = Connection::open(&self.path)?;
+        let index = ConflictIndex::build(&db, &conn)?;
+        Ok(index
+            .conflict_sets()
+            .into_iter()
+            .map(|set| set.into_iter().map(|txid| Arc::new(txid.into())).collect())
+            .collect())
+    }
+}
@@ -0,0 +1,142 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::Connection;
+use serde_json::json;
+
+use super::scan_queue::priority_name;
+use crate::{ZcashConsensusParameters, ZcashError, ZcashResult, ZcashWalletDb};
+
+impl ZcashWalletDb {
+    /// Generates a JSON report of the wallet state, for users to send to support teams
+    /// diagnosing sync problems: the applied schema migrations, the accounts and their
+    /// birthdays, the scan queue, the ranges of scanned blocks, the note commitment tree
+    /// and the counts of notes, outputs and transactions.
+    ///
+    /// It never has secrets, nor addresses or transaction contents. With `redact_keys`,
+    /// neither the viewing keys of the accounts, which reveal all their history.
+    pub fn debug_report(&self, redact_keys: bool) -> ZcashResult<String> {
+        let conn = Connection::open(&self.path)?;
+
+        // The migrations are identified by UUIDs, as in the upstream sources.
+        let migrations = conn
+            .prepare("SELECT id FROM schemer_migrations ORDER BY id")?
+            .query_map([], |row| row.get::<_, Vec<u8>>(0))?
+            .map(|id| id.map(|id| uuid_string(&id)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let accounts = conn
+            .prepare(
+                "SELECT account, birthday_height, recover_until_height, ufvk,
+                    (SELECT COUNT(*) FROM addresses WHERE addresses.account = accounts.account)
+                FROM accounts ORDER BY account",
+            )?
+            .query_map([], |row| {
+                Ok(json!({
+                    "account": row.get::<_, u32>(0)?,
+                    "birthday_height": row.get::<_, u32>(1)?,
+                    "recover_until_height": row.get::<_, Option<u32>>(2)?,
+                    "ufvk": if redact_keys { None } else { Some(row.get::<_, String>(3)?) },
+                    "addresses": row.get::<_, u32>(4)?,
+                }))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let scan_queue = conn
+            .prepare(
+                "SELECT block_range_start, block_range_end, priority FROM scan_queue
+                ORDER BY block_range_start",
+            )?
+            .query_map([], |row| {
+                Ok(json!({
+                    "start": row.get::<_, u32>(0)?,
+                    "end": row.get::<_, u32>(1)?,
+                    "priority": priority_name(row.get(2)?),
+                }))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The scanned blocks are grouped in runs of consecutive heights.
+        let scanned_ranges = conn
+            .prepare(
+                "SELECT MIN(height), MAX(height) + 1 FROM (
+                    SELECT height, height - ROW_NUMBER() OVER (ORDER BY height) AS run
+                    FROM blocks
+                )
+                GROUP BY run ORDER BY 1",
+            )?
+            .query_map([], |row| {
+                Ok(json!({
+                    "start": row.get::<_, u32>(0)?,
+                    "end": row.get::<_, u32>(1)?,
+                }))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let count = |query: &str| conn.query_row(query, [], |row| row.get::<_, Option<u32>>(0));
+
+        let report = json!({
+            "generated_at": SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or_default(),
+            "network": match self.params {
+                ZcashConsensusParameters::MainNetwork => "main",
+                ZcashConsensusParameters::TestNetwork => "test",
+            },
+            "schema": {
+                "migrations": migrations,
+            },
+            "accounts": accounts,
+            "chain": {
+                "scanned_blocks": count("SELECT COUNT(*) FROM blocks")?,
+                "min_scanned_height": count("SELECT MIN(height) FROM blocks")?,
+                "max_scanned_height": count("SELECT MAX(height) FROM blocks")?,
+                "scanned_ranges": scanned_ranges,
+                "scan_queue": scan_queue,
+            },
+            "sapling_tree": {
+                "shards": count("SELECT COUNT(*) FROM sapling_tree_shards")?,
+                "checkpoints": count("SELECT COUNT(*) FROM sapling_tree_checkpoints")?,
+                "min_checkpoint": count("SELECT MIN(checkpoint_id) FROM sapling_tree_checkpoints")?,
+                "max_checkpoint": count("SELECT MAX(checkpoint_id) FROM sapling_tree_checkpoints")?,
+            },
+            "notes": {
+                "sapling_received": count("SELECT COUNT(*) FROM sapling_received_notes")?,
+                "sapling_unspent": count(
+                    "SELECT COUNT(*) FROM sapling_received_notes WHERE spent IS NULL"
+                )?,
+                "sapling_without_position": count(
+                    "SELECT COUNT(*) FROM sapling_received_notes WHERE commitment_tree_position IS NULL"
+                )?,
+                "transparent_received": count("SELECT COUNT(*) FROM utxos")?,
+                "transparent_unspent": count(
+                    "SELECT COUNT(*) FROM utxos WHERE spent_in_tx IS NULL"
+                )?,
+                "sent": count("SELECT COUNT(*) FROM sent_notes")?,
+            },
+            "transactions": {
+                "total": count("SELECT COUNT(*) FROM transactions")?,
+                "unmined": count("SELECT COUNT(*) FROM transactions WHERE block IS NULL")?,
+            },
+        });
+
+        serde_json::to_string_pretty(&report).map_err(|e| ZcashError::Message {
+            error: format!("Cannot serialize the debug report: {}", e),
+        })
+    }
+}
+
+fn uuid_string(bytes: &[u8]) -> String {
+    let hex = hex::encode(bytes);
+    if hex.len() != 32 {
+        return hex;
+    }
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
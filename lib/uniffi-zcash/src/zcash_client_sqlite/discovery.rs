@@ -0,0 +1,271 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use orchard::keys::{
+    FullViewingKey as OrchardFvk, PreparedIncomingViewingKey as OrchardIvk, Scope as OrchardScope,
+};
+use orchard::note::{ExtractedNoteCommitment, Nullifier};
+use orchard::note_encryption::{CompactAction, OrchardDomain};
+use zcash_client_backend::data_api::chain::{error::Error, BlockSource};
+use zcash_client_backend::encoding::AddressCodec;
+use zcash_client_backend::keys::UnifiedSpendingKey;
+use zcash_client_backend::proto::compact_formats::CompactOrchardAction;
+use zcash_client_backend::proto::service::{
+    BlockId, BlockRange, ChainSpec, TransparentAddressBlockFilter,
+};
+use zcash_note_encryption::{try_compact_note_decryption, EphemeralKeyBytes};
+use zcash_primitives::consensus::BlockHeight;
+use zcash_primitives::legacy::keys::IncomingViewingKey;
+use zcash_primitives::sapling::note_encryption::{
+    try_sapling_compact_note_decryption, PreparedIncomingViewingKey,
+};
+use zcash_primitives::transaction::components::sapling::CompactOutputDescription;
+use zcash_primitives::zip32::{AccountId, Scope};
+
+use crate::{
+    validation::check_seed, CachedBlocks, TupleAccountIdAndUnifiedSpendingKey,
+    ZcashAccountBirthday, ZcashConsensusParameters, ZcashError, ZcashFsBlockDb,
+    ZcashLightwalletdClient, ZcashResult, ZcashWalletDb,
+};
+
+/// The external transparent addresses of an account looked up for activity, from the first
+/// one, as the gap limit of BIP 44 wallets.
+const TRANSPARENT_GAP_LIMIT: u32 = 20;
+
+/// Restores the accounts of a seed, the way HD wallets do: successive account indexes,
+/// starting after the last account of the wallet, are probed for on-chain activity, in
+/// windows of `gap_limit` accounts. The accounts up to the last used one are created in
+/// the wallet, and discovery stops at the first window without any used account.
+///
+/// Transparent activity is looked up in lightwalletd, for the first
+/// [`TRANSPARENT_GAP_LIMIT`] external addresses of each account, while shielded activity
+/// is looked up in the block cache, by trial decrypting the Sapling outputs and Orchard
+/// actions of the cached blocks from the birthday height on. At least one of them is
+/// required.
+///
+/// Returns the created accounts.
+pub fn discover_accounts(
+    wallet_db: Arc<ZcashWalletDb>,
+    seed: Vec<u8>,
+    birthday: Arc<ZcashAccountBirthday>,
+    gap_limit: u32,
+    client: Option<Arc<ZcashLightwalletdClient>>,
+    fs_block_db: Option<Arc<ZcashFsBlockDb>>,
+) -> ZcashResult<Vec<TupleAccountIdAndUnifiedSpendingKey>> {
+    check_seed("seed", &seed)?;
+    if gap_limit == 0 {
+        return Err(ZcashError::Message {
+            error: "The gap limit must be greater than zero".to_string(),
+        });
+    }
+    if client.is_none() && fs_block_db.is_none() {
+        return Err(ZcashError::Message {
+            error: "Either a lightwalletd client or a block cache is needed to discover accounts"
+                .to_string(),
+        });
+    }
+
+    let params = wallet_db.params;
+    if let Some(fs_block_db) = &fs_block_db {
+        fs_block_db.check_network(params)?;
+    }
+    let from_height = birthday.height().value();
+    let mut next = wallet_db
+        .get_unified_full_viewing_keys()?
+        .iter()
+        .map(|ufvk| ufvk.account.id + 1)
+        .max()
+        .unwrap_or(0);
+
+    let mut created = Vec::new();
+    loop {
+        let candidates = (next..next.saturating_add(gap_limit))
+            .map(|id| {
+                UnifiedSpendingKey::from_seed(&params, &seed, AccountId::from(id))
+                    .map(|usk| (id, usk))
+                    .map_err(ZcashError::from)
+            })
+            .collect::<ZcashResult<Vec<_>>>()?;
+
+        let mut used = BTreeSet::new();
+        if let Some(client) = &client {
+            used.extend(transparent_activity(
+                client,
+                &params,
+                from_height,
+                &candidates,
+            )?);
+        }
+        if let Some(fs_block_db) = &fs_block_db {
+            used.extend(shielded_activity(
+                fs_block_db,
+                &params,
+                from_height,
+                &candidates,
+            )?);
+        }
+
+        let Some(last_used) = used.last().copied() else {
+            return Ok(created);
+        };
+
+        // The wallet creates accounts in sequence, so the unused ones up to the last
+        // used account are created as well.
+        for id in next..=last_used {
+            let account = wallet_db.create_account(seed.clone(), birthday.clone())?;
+            if account.account_id.id != id {
+                return Err(ZcashError::Message {
+                    error: format!(
+                        "Expected to create account {}, but account {} was created",
+                        id, account.account_id.id
+                    ),
+                });
+            }
+            created.push(account);
+        }
+        next = last_used + 1;
+    }
+}
+
+/// The candidate accounts any of whose first [`TRANSPARENT_GAP_LIMIT`] external
+/// transparent addresses has a transaction since the given height, according to
+/// lightwalletd.
+fn transparent_activity(
+    client: &ZcashLightwalletdClient,
+    params: &ZcashConsensusParameters,
+    from_height: u32,
+    candidates: &[(u32, UnifiedSpendingKey)],
+) -> ZcashResult<Vec<u32>> {
+    let addresses = candidates
+        .iter()
+        .map(|(id, usk)| {
+            let ivk = usk
+                .transparent()
+                .to_account_pubkey()
+                .derive_external_ivk()?;
+            let addresses = (0..TRANSPARENT_GAP_LIMIT)
+                .map(|index| ivk.derive_address(index).map(|taddr| taddr.encode(params)))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((*id, addresses))
+        })
+        .collect::<ZcashResult<Vec<_>>>()?;
+
+    let mut grpc = client.client.clone();
+    client.runtime.block_on(async {
+        let tip = grpc
+            .get_latest_block(ChainSpec {})
+            .await?
+            .into_inner()
+            .height;
+
+        let mut used = Vec::new();
+        for (id, addresses) in addresses {
+            for address in addresses {
+                let filter = TransparentAddressBlockFilter {
+                    address,
+                    range: Some(BlockRange {
+                        start: Some(BlockId {
+                            height: from_height.into(),
+                            hash: vec![],
+                        }),
+                        end: Some(BlockId {
+                            height: tip,
+                            hash: vec![],
+                        }),
+                    }),
+                };
+                let mut txids = grpc.get_taddress_txids(filter).await?.into_inner();
+                if txids.message().await?.is_some() {
+                    used.push(id);
+                    break;
+                }
+            }
+        }
+        Ok(used)
+    })
+}
+
+/// The candidate accounts that received any Sapling or Orchard note in the cached blocks
+/// since the given height, either from others or as change.
+fn shielded_activity(
+    fs_block_db: &ZcashFsBlockDb,
+    params: &ZcashConsensusParameters,
+    from_height: u32,
+    candidates: &[(u32, UnifiedSpendingKey)],
+) -> ZcashResult<Vec<u32>> {
+    let ivks: Vec<(u32, PreparedIncomingViewingKey)> = candidates
+        .iter()
+        .flat_map(|(id, usk)| {
+            let dfvk = usk.sapling().to_diversifiable_full_viewing_key();
+            [Scope::External, Scope::Internal]
+                .map(|scope| (*id, PreparedIncomingViewingKey::new(&dfvk.to_ivk(scope))))
+        })
+        .collect();
+    let orchard_ivks: Vec<(u32, OrchardIvk)> = candidates
+        .iter()
+        .flat_map(|(id, usk)| {
+            let fvk = OrchardFvk::from(usk.orchard());
+            [OrchardScope::External, OrchardScope::Internal]
+                .map(|scope| (*id, OrchardIvk::new(&fvk.to_ivk(scope))))
+        })
+        .collect();
+
+    let mut used = BTreeSet::new();
+    CachedBlocks::for_path(&fs_block_db.fsblockdb_root)
+        .map_err(|e| ZcashError::Message {
+            error: format!("Cannot read the block cache: {:?}", e),
+        })?
+        .with_blocks::<_, ()>(Some(BlockHeight::from_u32(from_height)), None, |block| {
+            let height = block.height();
+            for tx in block.vtx {
+                for output in tx.outputs {
+                    let Ok(output) = CompactOutputDescription::try_from(output) else {
+                        continue;
+                    };
+                    for (id, ivk) in &ivks {
+                        if !used.contains(id)
+                            && try_sapling_compact_note_decryption(params, height, ivk, &output)
+                                .is_some()
+                        {
+                            used.insert(*id);
+                        }
+                    }
+                }
+                for action in &tx.actions {
+                    let Some(action) = compact_orchard_action(action) else {
+                        continue;
+                    };
+                    let domain = OrchardDomain::for_nullifier(action.nullifier());
+                    for (id, ivk) in &orchard_ivks {
+                        if !used.contains(id)
+                            && try_compact_note_decryption(&domain, ivk, &action).is_some()
+                        {
+                            used.insert(*id);
+                        }
+                    }
+                }
+            }
+            Ok::<_, Error<(), _>>(())
+        })
+        .map_err(|e| ZcashError::Message {
+            error: format!("Cannot read the block cache: {:?}", e),
+        })?;
+
+    Ok(used.into_iter().collect())
+}
+
+/// The Orchard action of a compact block, or none if it is malformed.
+fn compact_orchard_action(action: &CompactOrchardAction) -> Option<CompactAction> {
+    let nullifier = Option::from(Nullifier::from_bytes(
+        &action.nullifier[..].try_into().ok()?,
+    ))?;
+    let cmx = Option::from(ExtractedNoteCommitment::from_bytes(
+        &action.cmx[..].try_into().ok()?,
+    ))?;
+    Some(CompactAction::from_parts(
+        nullifier,
+        cmx,
+        EphemeralKeyBytes(action.ephemeral_key[..].try_into().ok()?),
+        action.ciphertext[..].try_into().ok()?,
+    ))
+}
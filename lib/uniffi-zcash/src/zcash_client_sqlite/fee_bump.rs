@@ -0,0 +1,336 @@
+use std::sync::Arc;
+
+use rusqlite::{named_params, Connection, OptionalExtension};
+use zcash_client_backend::data_api::{
+    Recipient, SentTransaction, SentTransactionOutput, WalletRead, WalletWrite,
+};
+use zcash_client_backend::keys::UnifiedSpendingKey;
+use zcash_client_backend::wallet::WalletTransparentOutput;
+use zcash_client_sqlite::WalletDb;
+use zcash_primitives::consensus::BlockHeight;
+use zcash_primitives::legacy::{Script, TransparentAddress};
+use zcash_primitives::transaction::builder::Builder;
+use zcash_primitives::transaction::components::{Amount, OutPoint, TxOut};
+use zcash_primitives::transaction::fees::{fixed, zip317, FeeRule};
+use zcash_primitives::transaction::TxId;
+
+use super::cast_err;
+use crate::{
+    builder_target_height, ZcashError, ZcashLocalTxProver, ZcashResult, ZcashTransaction,
+    ZcashTxId, ZcashUnifiedSpendingKey, ZcashWalletDb, DEFAULT_TX_EXPIRY_DELTA,
+};
+
+/// A wallet output spent by the transaction to replace.
+struct SpentOutput {
+    outpoint: OutPoint,
+    coin: TxOut,
+    height: BlockHeight,
+}
+
+impl ZcashWalletDb {
+    /// Rebuilds an expired, transparent only, transaction of the wallet with a higher fee,
+    /// spending the same outputs to the same recipients, and stores it in place of the
+    /// original one, which is marked as replaced.
+    ///
+    /// Zcash nodes have no replace by fee: they keep the first of two transactions spending
+    /// the same outputs in their mempool, so a replacement broadcast while the original can
+    /// still be mined would be dropped, or the original mined anyway. The original must
+    /// thus have expired, which is when the next block is above its expiry height, and
+    /// transactions without an expiry height cannot be replaced. Both spend the same
+    /// outputs, so at most one of them gets mined.
+    ///
+    /// The fee increase is taken from the change, the output paying back to the account.
+    /// By default, the new fee is the ZIP 317 one of the transaction, or a marginal fee
+    /// more than the original one if this already paid it. The new transaction expires
+    /// `expiry_delta` blocks after the next one, by default the [`DEFAULT_TX_EXPIRY_DELTA`].
+    /// Returns it, for the app to broadcast it. Once the original is replaced, bumping it
+    /// again returns the same replacement.
+    pub fn bump_transparent_fee(
+        &self,
+        txid: Arc<ZcashTxId>,
+        usk: Arc<ZcashUnifiedSpendingKey>,
+        prover: Arc<ZcashLocalTxProver>,
+        fee: Option<u64>,
+        expiry_delta: Option<u32>,
+    ) -> ZcashResult<Arc<ZcashTransaction>> {
+        let txid: TxId = (*txid).into();
+        let usk: UnifiedSpendingKey = (*usk).clone().into();
+        let mut db = WalletDb::for_path(&self.path, self.params).expect("Cannot access the DB!");
+
+        let message = |error: &str| ZcashError::Message {
+            error: format!("Cannot bump the fee of {}: {}", txid, error),
+        };
+
+        // Upstream `get_tx_height` fails on unmined transactions, which have no block.
+        let conn = Connection::open(&self.path)?;
+        let mined = conn
+            .query_row(
+                "SELECT block FROM transactions WHERE txid = :txid",
+                named_params![":txid": &txid.as_ref()[..]],
+                |row| row.get::<_, Option<u32>>(0),
+            )
+            .optional()?
+            .flatten()
+            .is_some();
+        if mined {
+            return Err(message("it is mined"));
+        }
+        let original = db.get_transaction(txid).map_err(cast_err)?;
+        let target_height = db
+            .chain_height()
+            .map_err(cast_err)?
+            .ok_or_else(|| message("the wallet chain height is unknown"))?
+            + 1;
+        match u32::from(original.expiry_height()) {
+            0 => return Err(message("it never expires")),
+            expiry_height if target_height <= BlockHeight::from_u32(expiry_height) => {
+                return Err(message(&format!(
+                    "it can still be mined until its expiry height {}",
+                    expiry_height
+                )))
+            }
+            _ => {}
+        }
+        if original.sapling_bundle().is_some()
+            || original.orchard_bundle().is_some()
+            || original.sprout_bundle().is_some()
+        {
+            return Err(message("it is not transparent only"));
+        }
+        let bundle = original
+            .transparent_bundle()
+            .ok_or_else(|| message("it has no transparent inputs"))?;
+
+        let account = db
+            .get_account_for_ufvk(&usk.to_unified_full_viewing_key())
+            .map_err(cast_err)?
+            .ok_or_else(|| message("the spending key is not of any account of the wallet"))?;
+        let receivers = db.get_transparent_receivers(account).map_err(cast_err)?;
+
+        let mut spent = Vec::with_capacity(bundle.vin.len());
+        let mut spent_by = Vec::with_capacity(bundle.vin.len());
+        for txin in &bundle.vin {
+            let (script, value, height, received_by, spent_in) = conn
+                .query_row(
+                    "SELECT u.script, u.value_zat, u.height, u.received_by_account, t.txid
+                    FROM utxos u
+                    LEFT JOIN transactions t ON t.id_tx = u.spent_in_tx
+                    WHERE u.prevout_txid = :txid AND u.prevout_idx = :idx",
+                    named_params![
+                        ":txid": &txin.prevout.hash()[..],
+                        ":idx": txin.prevout.n(),
+                    ],
+                    |row| {
+                        Ok((
+                            row.get::<_, Vec<u8>>(0)?,
+                            row.get::<_, i64>(1)?,
+                            row.get::<_, u32>(2)?,
+                            row.get::<_, u32>(3)?,
+                            row.get::<_, Option<Vec<u8>>>(4)?,
+                        ))
+                    },
+                )
+                .optional()?
+                .ok_or_else(|| message("it spends outputs the wallet does not have"))?;
+            if received_by != u32::from(account) {
+                return Err(message("it spends outputs of other accounts"));
+            }
+            let value = Amount::from_nonnegative_i64(value).map_err(|_| {
+                message(&format!(
+                    "the wallet has an invalid value {} for the output {}:{}",
+                    value,
+                    TxId::from_bytes(*txin.prevout.hash()),
+                    txin.prevout.n()
+                ))
+            })?;
+            spent.push(SpentOutput {
+                outpoint: txin.prevout.clone(),
+                coin: TxOut {
+                    script_pubkey: Script(script),
+                    value,
+                },
+                height: BlockHeight::from_u32(height),
+            });
+            spent_by.push(spent_in.filter(|spender| spender[..] != txid.as_ref()[..]));
+        }
+
+        // The outputs are already spent by another transaction of the wallet when the
+        // original was replaced before, and the app is retrying a bump which stored the
+        // replacement without linking it to the original: the link is repaired, and the
+        // replacement returned again, rather than building a third transaction.
+        if let Some(spender) = spent_by.iter().flatten().next() {
+            let replacement = db
+                .get_transaction(ZcashTxId::from_bytes(spender)?.into())
+                .map_err(cast_err)?;
+            let same_outputs = spent_by.iter().all(|other| other.as_ref() == Some(spender))
+                && replacement.transparent_bundle().is_some_and(|replacing| {
+                    replacing.vin.len() == bundle.vin.len()
+                        && replacing
+                            .vin
+                            .iter()
+                            .zip(&bundle.vin)
+                            .all(|(new, old)| new.prevout == old.prevout)
+                });
+            if !same_outputs {
+                return Err(message("its outputs are spent by another transaction"));
+            }
+            link_replacement(&conn, &txid, &replacement.txid())?;
+            return Ok(Arc::new(replacement.into()));
+        }
+
+        let input_value = sum(spent.iter().map(|output| output.coin.value))?;
+        let output_value = sum(bundle.vout.iter().map(|txout| txout.value))?;
+        let original_fee = (input_value - output_value)
+            .filter(|fee| !fee.is_negative())
+            .ok_or_else(|| message("its outputs exceed its inputs"))?;
+
+        let inputs = spent
+            .iter()
+            .filter_map(|output| {
+                WalletTransparentOutput::from_parts(
+                    output.outpoint.clone(),
+                    output.coin.clone(),
+                    output.height,
+                )
+            })
+            .collect::<Vec<_>>();
+        let zip317_rule = zip317::FeeRule::standard();
+        let zip317_fee = zip317_rule
+            .fee_required(&self.params, target_height, &inputs, &bundle.vout, 0, 0, 0)
+            .map_err(|e| message(&format!("{:?}", e)))?;
+
+        let new_fee = match fee {
+            Some(fee) => Amount::from_u64(fee).map_err(|_| message("invalid fee"))?,
+            None if original_fee < zip317_fee => zip317_fee,
+            None => {
+                (original_fee + zip317_rule.marginal_fee()).ok_or_else(|| message("invalid fee"))?
+            }
+        };
+        if new_fee <= original_fee || new_fee < zip317_fee {
+            return Err(message(&format!(
+                "the fee must be higher than the original {} and at least the ZIP 317 one {}",
+                i64::from(original_fee),
+                i64::from(zip317_fee)
+            )));
+        }
+
+        // The change is the last output paying back to the account.
+        let change_index = bundle
+            .vout
+            .iter()
+            .rposition(|txout| {
+                txout
+                    .recipient_address()
+                    .is_some_and(|address| receivers.contains_key(&address))
+            })
+            .ok_or_else(|| message("it has no change output to take the fee increase from"))?;
+        let change_value = (bundle.vout[change_index].value - new_fee + original_fee)
+            .filter(|value| !value.is_negative())
+            .ok_or_else(|| message("its change cannot cover the fee increase"))?;
+
+        let builder_height = builder_target_height(
+            self.params,
+            target_height,
+            expiry_delta.unwrap_or(DEFAULT_TX_EXPIRY_DELTA),
+        )?;
+        let mut builder = Builder::new(self.params, builder_height, None);
+        for output in &spent {
+            let address = output
+                .coin
+                .recipient_address()
+                .ok_or_else(|| message("it spends outputs of unknown scripts"))?;
+            let child_index = receivers
+                .get(&address)
+                .and_then(|metadata| u32::try_from(*metadata.diversifier_index()).ok())
+                .ok_or_else(|| message("it spends outputs of unknown addresses"))?;
+            let sk = usk.transparent().derive_external_secret_key(child_index)?;
+            builder.add_transparent_input(sk, output.outpoint.clone(), output.coin.clone())?;
+        }
+
+        let mut recipients: Vec<(TransparentAddress, Amount)> = Vec::new();
+        for (index, txout) in bundle.vout.iter().enumerate() {
+            let value = if index == change_index {
+                change_value
+            } else {
+                txout.value
+            };
+            // A change fully spent on the fee is dropped.
+            if index == change_index && value == Amount::zero() {
+                continue;
+            }
+            let address = txout
+                .recipient_address()
+                .ok_or_else(|| message("it pays to non standard scripts"))?;
+            builder.add_transparent_output(&address, value)?;
+            recipients.push((address, value));
+        }
+
+        let (tx, _) = builder
+            .build(prover.as_ref(), &fixed::FeeRule::non_standard(new_fee))
+            .map_err(ZcashError::from)?;
+
+        db.store_sent_tx(&SentTransaction {
+            tx: &tx,
+            created: time::OffsetDateTime::now_utc(),
+            account,
+            outputs: recipients
+                .into_iter()
+                .enumerate()
+                .map(|(index, (address, value))| {
+                    SentTransactionOutput::from_parts(
+                        index,
+                        Recipient::Transparent(address),
+                        value,
+                        None,
+                        None,
+                    )
+                })
+                .collect(),
+            fee_amount: new_fee,
+            utxos_spent: spent.iter().map(|output| output.outpoint.clone()).collect(),
+        })
+        .map_err(cast_err)?;
+
+        link_replacement(&conn, &txid, &tx.txid())?;
+
+        Ok(Arc::new(tx.into()))
+    }
+
+    /// Returns the transaction replacing the given one after a fee bump, if any.
+    pub fn get_replacement_transaction(
+        &self,
+        txid: Arc<ZcashTxId>,
+    ) -> ZcashResult<Option<Arc<ZcashTxId>>> {
+        let txid: TxId = (*txid).into();
+        let conn = Connection::open(&self.path)?;
+
+        let replaced_by: Option<Vec<u8>> = conn
+            .query_row(
+                "SELECT replaced_by FROM uniffi_replaced_transactions WHERE txid = :txid",
+                named_params![":txid": &txid.as_ref()[..]],
+                |row| row.get(0),
+            )
+            .optional()?;
+        replaced_by
+            .map(|bytes| ZcashTxId::from_bytes(&bytes).map(Arc::new))
+            .transpose()
+    }
+}
+
+fn sum(amounts: impl Iterator<Item = Amount>) -> ZcashResult<Amount> {
+    amounts
+        .sum::<Option<Amount>>()
+        .ok_or_else(|| "Amount overflow".into())
+}
+
+fn link_replacement(conn: &Connection, txid: &TxId, replaced_by: &TxId) -> ZcashResult<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO uniffi_replaced_transactions (txid, replaced_by)
+        VALUES (:txid, :replaced_by)",
+        named_params![
+            ":txid": &txid.as_ref()[..],
+            ":replaced_by": &replaced_by.as_ref()[..],
+        ],
+    )?;
+    Ok(())
+}
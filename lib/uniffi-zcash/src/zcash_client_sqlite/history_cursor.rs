@@ -0,0 +1,141 @@
+use std::sync::{Arc, Mutex};
+
+use rusqlite::{named_params, Connection, OpenFlags, Row};
+use zcash_primitives::transaction::TxId;
+
+use super::account_removal::ensure_account_exists;
+use crate::{
+    validation::invalid_argument, ZcashAccountId, ZcashError, ZcashResult, ZcashTxId, ZcashWalletDb,
+};
+
+/// A transaction of the history of an account, as in the `v_transactions` view of
+/// `zcash_client_sqlite`.
+pub struct ZcashHistoryEntry {
+    pub account: ZcashAccountId,
+    pub txid: Arc<ZcashTxId>,
+    /// `None` for transactions not mined yet.
+    pub mined_height: Option<u32>,
+    /// The time in the header of the block the transaction was mined in, if scanned.
+    pub block_time: Option<u32>,
+    pub expiry_height: Option<u32>,
+    /// How the transaction changed the balance of the account, in zatoshis.
+    pub account_balance_delta: i64,
+    pub fee_paid: Option<u64>,
+    pub has_change: bool,
+    pub sent_note_count: u32,
+    pub received_note_count: u32,
+    pub memo_count: u32,
+    /// Whether the transaction expired without being mined.
+    pub expired_unmined: bool,
+}
+
+/// The position of an entry in the order the history is listed in, as a row value
+/// comparable in SQL: unmined transactions last, then the mined height, the index in the
+/// block, the transaction id and the account.
+type HistoryKey = (bool, u32, i64, Vec<u8>, u32);
+
+/// Iterates the history of the wallet a page at a time, so that services can export
+/// histories of millions of transactions without getting them all in one call.
+///
+/// Each page is read by a short query resuming after the last entry of the previous
+/// page, so no read transaction is kept open between pages and the wallet can keep on
+/// syncing while a slow consumer reads the history. Entries written or moved (as when an
+/// unmined transaction gets mined) between two pages may thus be skipped or listed twice.
+pub struct ZcashHistoryCursor {
+    path: String,
+    account: Option<ZcashAccountId>,
+    last_key: Mutex<Option<HistoryKey>>,
+}
+
+impl ZcashHistoryCursor {
+    /// Returns the next `n` entries of the history, less for the last page, and none once
+    /// the history is exhausted.
+    pub fn next_page(&self, n: u32) -> ZcashResult<Vec<ZcashHistoryEntry>> {
+        if n == 0 {
+            return Err(invalid_argument("n", "must be positive"));
+        }
+        let mut last_key = self.last_key.lock().unwrap();
+        let conn = Connection::open_with_flags(&self.path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut stmt = conn.prepare(
+            "SELECT account_id, txid, mined_height, block_time, expiry_height,
+                account_balance_delta, fee_paid, has_change, sent_note_count,
+                received_note_count, memo_count, expired_unmined,
+                mined_height IS NULL, COALESCE(mined_height, 0), COALESCE(tx_index, -1)
+            FROM v_transactions
+            WHERE (:account IS NULL OR account_id = :account)
+                AND (:after IS NULL OR (mined_height IS NULL, COALESCE(mined_height, 0),
+                    COALESCE(tx_index, -1), txid, account_id)
+                    > (:unmined, :height, :tx_index, :txid, :account_id))
+            ORDER BY mined_height IS NULL, COALESCE(mined_height, 0),
+                COALESCE(tx_index, -1), txid, account_id
+            LIMIT :limit",
+        )?;
+        let after = last_key.clone();
+        let mut rows = stmt.query(named_params![
+            ":account": self.account.map(|account| account.id),
+            ":after": after.as_ref().map(|_| 1),
+            ":unmined": after.as_ref().map(|key| key.0),
+            ":height": after.as_ref().map(|key| key.1),
+            ":tx_index": after.as_ref().map(|key| key.2),
+            ":txid": after.as_ref().map(|key| key.3.clone()),
+            ":account_id": after.as_ref().map(|key| key.4),
+            ":limit": n,
+        ])?;
+
+        let mut page = Vec::new();
+        while let Some(row) = rows.next()? {
+            page.push(history_entry(row)?);
+            *last_key = Some((
+                row.get(12)?,
+                row.get(13)?,
+                row.get(14)?,
+                row.get(1)?,
+                row.get(0)?,
+            ));
+        }
+        Ok(page)
+    }
+}
+
+impl ZcashWalletDb {
+    /// Opens a cursor over the history of the given account, or of all the accounts,
+    /// oldest transactions first and the unmined ones last. Each transaction is listed
+    /// once per account it involves.
+    pub fn open_history_cursor(
+        &self,
+        account: Option<ZcashAccountId>,
+    ) -> ZcashResult<Arc<ZcashHistoryCursor>> {
+        if let Some(account) = account {
+            let conn = Connection::open_with_flags(&self.path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            ensure_account_exists(&conn, account)?;
+        }
+        Ok(Arc::new(ZcashHistoryCursor {
+            path: self.path.clone(),
+            account,
+            last_key: Mutex::new(None),
+        }))
+    }
+}
+
+fn history_entry(row: &Row) -> ZcashResult<ZcashHistoryEntry> {
+    let txid: [u8; 32] = row
+        .get::<_, Vec<u8>>(1)?
+        .try_into()
+        .map_err(|_| ZcashError::Message {
+            error: "Transaction id is not a 32-byte value".to_string(),
+        })?;
+    Ok(ZcashHistoryEntry {
+        account: ZcashAccountId { id: row.get(0)? },
+        txid: Arc::new(TxId::from_bytes(txid).into()),
+        mined_height: row.get(2)?,
+        block_time: row.get(3)?,
+        expiry_height: row.get(4)?,
+        account_balance_delta: row.get(5)?,
+        fee_paid: row.get(6)?,
+        has_change: row.get(7)?,
+        sent_note_count: row.get(8)?,
+        received_note_count: row.get(9)?,
+        memo_count: row.get(10)?,
+        expired_unmined: row.get::<_, Option<bool>>(11)?.unwrap_or(false),
+    })
+}
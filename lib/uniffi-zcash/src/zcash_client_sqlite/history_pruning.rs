@@ -0,0 +1,135 @@
+use rusqlite::{named_params, Connection, OptionalExtension};
+use zcash_primitives::transaction::TxId;
+
+use crate::{validation::invalid_argument, ZcashResult, ZcashWalletDb};
+
+/// The blocks below which the wallet keeps no reorg data, as in `zcash_client_sqlite`.
+pub(super) const PRUNING_DEPTH: u32 = 100;
+
+/// The outcome of [`ZcashWalletDb::prune_history`]: what was, or in dry runs would be,
+/// forgotten.
+pub struct ZcashHistoryPruningReport {
+    /// The transactions whose raw data was dropped.
+    pub transactions: u32,
+    /// The memos of the received and sent notes.
+    pub memos: u32,
+    /// The received notes and transparent outputs which were spent.
+    pub spent_notes: u32,
+    /// Whether the history was pruned. It never is in dry runs.
+    pub applied: bool,
+}
+
+impl ZcashWalletDb {
+    /// Forgets the history of the transactions mined below `before_height`, for users
+    /// wanting the wallet to keep as little as possible of it on the device: their raw
+    /// data, the memos of their notes, and the notes and outputs they received which were
+    /// spent below that height too. The balance is unchanged, as the unspent notes are all
+    /// kept, with the raw data of the transactions they were received in, but the other
+    /// transactions can no longer be shown in full, nor sent again: looking them up tells
+    /// their data was pruned. Unmined transactions, as those whose fee can be bumped, are
+    /// never pruned.
+    ///
+    /// The height must be at least 100 blocks below the chain tip, so that what is pruned
+    /// cannot be reorged. With `dry_run`, the report tells what would be forgotten but
+    /// nothing is. This cannot be undone, other than by rescanning from below the height.
+    pub fn prune_history(
+        &self,
+        before_height: u32,
+        dry_run: bool,
+    ) -> ZcashResult<ZcashHistoryPruningReport> {
+        let mut conn = Connection::open(&self.path)?;
+        let chain_tip_height: Option<u32> = conn.query_row(
+            "SELECT MAX(block_range_end) - 1 FROM scan_queue",
+            [],
+            |row| row.get(0),
+        )?;
+        let stable_height = chain_tip_height.unwrap_or(0).saturating_sub(PRUNING_DEPTH);
+        if before_height > stable_height {
+            return Err(invalid_argument(
+                "before_height",
+                format!(
+                    "history can only be pruned below height {}, {} blocks below the chain tip",
+                    stable_height, PRUNING_DEPTH
+                ),
+            ));
+        }
+
+        let tx = conn.transaction()?;
+        let params = named_params![":height": before_height];
+        // The transactions with outputs the wallet has not spent yet are kept in full.
+        let transactions = tx.execute(
+            "UPDATE transactions SET raw = NULL
+            WHERE block < :height AND raw IS NOT NULL
+            AND id_tx NOT IN (SELECT tx FROM sapling_received_notes WHERE spent IS NULL)
+            AND txid NOT IN (SELECT prevout_txid FROM utxos WHERE spent_in_tx IS NULL)",
+            params,
+        )?;
+        let memos = tx.execute(
+            "UPDATE sapling_received_notes SET memo = NULL
+            WHERE memo IS NOT NULL
+            AND tx IN (SELECT id_tx FROM transactions WHERE block < :height)",
+            params,
+        )? + tx.execute(
+            "UPDATE sent_notes SET memo = NULL
+            WHERE memo IS NOT NULL
+            AND tx IN (SELECT id_tx FROM transactions WHERE block < :height)",
+            params,
+        )?;
+        tx.execute(
+            "DELETE FROM sapling_witnesses WHERE note IN (
+                SELECT n.id_note FROM sapling_received_notes n
+                JOIN transactions t ON t.id_tx = n.tx
+                JOIN transactions s ON s.id_tx = n.spent
+                WHERE t.block < :height AND s.block < :height
+            )",
+            params,
+        )?;
+        let spent_notes = tx.execute(
+            "DELETE FROM sapling_received_notes WHERE id_note IN (
+                SELECT n.id_note FROM sapling_received_notes n
+                JOIN transactions t ON t.id_tx = n.tx
+                JOIN transactions s ON s.id_tx = n.spent
+                WHERE t.block < :height AND s.block < :height
+            )",
+            params,
+        )? + tx.execute(
+            "DELETE FROM utxos WHERE height < :height AND spent_in_tx IN (
+                SELECT id_tx FROM transactions WHERE block < :height
+            )",
+            params,
+        )?;
+
+        let applied = !dry_run;
+        if applied {
+            tx.commit()?;
+            // Gives the freed space back to the device.
+            conn.execute("VACUUM", [])?;
+        }
+
+        Ok(ZcashHistoryPruningReport {
+            transactions: transactions as u32,
+            memos: memos as u32,
+            spent_notes: spent_notes as u32,
+            applied,
+        })
+    }
+}
+
+/// Why the content of a transaction of the wallet cannot be looked up, when the wallet has
+/// it without its raw data. `None` when it has its data, or does not have it at all.
+pub(super) fn missing_raw_data(conn: &Connection, txid: &TxId) -> rusqlite::Result<Option<String>> {
+    let missing: Option<bool> = conn
+        .query_row(
+            "SELECT raw IS NULL FROM transactions WHERE txid = :txid",
+            named_params![":txid": &txid.as_ref()[..]],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(missing.filter(|missing| *missing).map(|_| {
+        format!(
+            "The data of the transaction {} is not in the wallet: its history was pruned, \
+            or it was only found by scanning",
+            txid
+        )
+    }))
+}
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use rusqlite::{named_params, Connection, OptionalExtension};
+use zcash_client_backend::data_api::AccountBirthday;
+use zcash_client_backend::encoding::AddressCodec;
+use zcash_client_backend::keys::UnifiedFullViewingKey;
+use zcash_primitives::consensus::{NetworkUpgrade, Parameters};
+
+use super::scan_queue::{
+    queue_ranges, raise_queue_range, restore_queue_range, PRIORITY_HISTORIC, PRIORITY_IGNORED,
+};
+use crate::{
+    validation::invalid_argument, ZcashAccountBirthday, ZcashAccountId, ZcashError, ZcashResult,
+    ZcashUnifiedFullViewingKey, ZcashWalletDb,
+};
+
+/// Where the keys of an imported account live, like a hardware wallet, and how they
+/// are derived there, so apps can ask the right device to sign for the account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZcashDeviceDerivation {
+    /// The device holding the spending keys, like `"Ledger Nano S Plus"`.
+    pub device: String,
+    /// The ZIP 32 path of the account in the device, like `"m/32'/133'/0'"`.
+    pub derivation_path: String,
+    /// An identifier of the seed in the device, if it provides any.
+    pub seed_fingerprint: Option<Vec<u8>>,
+}
+
+impl ZcashWalletDb {
+    /// Imports an account whose spending keys live in an external signer, like a
+    /// hardware wallet: only its unified full viewing key is stored, along with the
+    /// derivation metadata of the device. The account is scanned as any other from its
+    /// birthday on, but the wallet cannot spend from it.
+    pub fn import_account_ufvk(
+        &self,
+        ufvk: Arc<ZcashUnifiedFullViewingKey>,
+        birthday: Arc<ZcashAccountBirthday>,
+        derivation: ZcashDeviceDerivation,
+    ) -> ZcashResult<ZcashAccountId> {
+        if let Some(account) = self.get_account_for_ufvk(ufvk.clone())? {
+            return Err(ZcashError::Message {
+                error: format!("The key is already imported as account {}", account.id),
+            });
+        }
+
+        let ufvk: UnifiedFullViewingKey = (*ufvk).clone().into();
+        let birthday: AccountBirthday = (*birthday).clone().into();
+        let birthday_height = u32::from(birthday.height());
+        let sapling_activation = self
+            .params
+            .activation_height(NetworkUpgrade::Sapling)
+            .map_or(0, u32::from);
+        if birthday_height < sapling_activation {
+            return Err(invalid_argument(
+                "birthday",
+                format!("is before the Sapling activation {}", sapling_activation),
+            ));
+        }
+
+        // The rows upstream writes when creating an account from a seed, with the imported
+        // keys, and the derivation metadata, all in a single transaction.
+        let mut conn = Connection::open(&self.path)?;
+        let tx = conn.transaction()?;
+        let account: u32 = tx.query_row(
+            "SELECT IFNULL(MAX(account) + 1, 0) FROM accounts",
+            [],
+            |row| row.get(0),
+        )?;
+        tx.execute(
+            "INSERT INTO accounts (account, ufvk, birthday_height, recover_until_height)
+            VALUES (:account, :ufvk, :birthday_height, :recover_until_height)",
+            named_params![
+                ":account": account,
+                ":ufvk": ufvk.encode(&self.params),
+                ":birthday_height": birthday_height,
+                ":recover_until_height": birthday.recover_until().map(u32::from),
+            ],
+        )?;
+
+        let (address, mut diversifier_index) = ufvk.default_address();
+        diversifier_index.0.reverse();
+        tx.execute(
+            "INSERT INTO addresses (
+                account, diversifier_index_be, address, cached_transparent_receiver_address
+            )
+            VALUES (:account, :diversifier_index_be, :address, :transparent_address)",
+            named_params![
+                ":account": account,
+                ":diversifier_index_be": &diversifier_index.0[..],
+                ":address": address.encode(&self.params),
+                ":transparent_address": address.transparent().map(|t| t.encode(&self.params)),
+            ],
+        )?;
+
+        tx.execute(
+            "INSERT INTO uniffi_device_derivations (
+                account, device, derivation_path, seed_fingerprint
+            )
+            VALUES (:account, :device, :derivation_path, :seed_fingerprint)",
+            named_params![
+                ":account": account,
+                ":device": derivation.device,
+                ":derivation_path": derivation.derivation_path,
+                ":seed_fingerprint": derivation.seed_fingerprint,
+            ],
+        )?;
+
+        // The blocks before the birthday are not scanned for the account, and those from
+        // the birthday to the chain tip are, even if they were scanned for other accounts.
+        let queue_end: Option<u32> =
+            tx.query_row("SELECT MAX(block_range_end) FROM scan_queue", [], |row| {
+                row.get(0)
+            })?;
+        let queued = sapling_activation..queue_end.unwrap_or(0).max(birthday_height);
+        let former_queue = queue_ranges(&tx, queued.clone())?;
+        raise_queue_range(&tx, sapling_activation..birthday_height, PRIORITY_IGNORED)?;
+        if let Some(queue_end) = queue_end {
+            raise_queue_range(&tx, birthday_height..queue_end, PRIORITY_HISTORIC)?;
+        }
+        tx.commit()?;
+
+        // The tree is written once the account is stored. If that fails, the account is
+        // removed and the scan queue put back as it was.
+        if let Err(e) = self.insert_birthday_frontier(&birthday) {
+            let tx = conn.transaction()?;
+            let params = named_params![":account": account];
+            tx.execute(
+                "DELETE FROM uniffi_device_derivations WHERE account = :account",
+                params,
+            )?;
+            tx.execute("DELETE FROM addresses WHERE account = :account", params)?;
+            tx.execute("DELETE FROM accounts WHERE account = :account", params)?;
+            restore_queue_range(&tx, queued, &former_queue)?;
+            tx.commit()?;
+            return Err(e);
+        }
+
+        Ok(ZcashAccountId { id: account })
+    }
+
+    /// Returns the device derivation metadata of an account imported with
+    /// [`ZcashWalletDb::import_account_ufvk`], or `None` for accounts created from seeds.
+    pub fn get_device_derivation(
+        &self,
+        account: ZcashAccountId,
+    ) -> ZcashResult<Option<ZcashDeviceDerivation>> {
+        let conn = Connection::open(&self.path)?;
+
+        Ok(conn
+            .query_row(
+                "SELECT device, derivation_path, seed_fingerprint
+                FROM uniffi_device_derivations WHERE account = :account",
+                named_params![":account": account.id],
+                |row| {
+                    Ok(ZcashDeviceDerivation {
+                        device: row.get(0)?,
+                        derivation_path: row.get(1)?,
+                        seed_fingerprint: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+}
@@ -0,0 +1,190 @@
+use rusqlite::{named_params, Connection, OptionalExtension};
+use zcash_client_backend::address::RecipientAddress;
+use zcash_client_backend::data_api::WalletWrite;
+use zcash_client_backend::encoding::AddressCodec;
+use zcash_client_backend::zip321::{Payment, TransactionRequest};
+use zcash_client_sqlite::WalletDb;
+use zcash_primitives::memo::{Memo, MemoBytes};
+use zcash_primitives::transaction::components::Amount;
+
+use super::account_removal::ensure_account_exists;
+use super::cast_err;
+use crate::{ZcashAccountId, ZcashError, ZcashResult, ZcashWalletDb};
+
+/// A payment request tracked by the wallet, paid to an address of its own.
+pub struct ZcashInvoice {
+    pub id: u64,
+    pub account: ZcashAccountId,
+    pub address: String,
+    /// The requested value, in zatoshis.
+    pub amount: u64,
+    pub memo_tag: Option<String>,
+    /// The ZIP 321 URI to show the payer, as a link or a QR code.
+    pub payment_uri: String,
+}
+
+/// How much of an invoice was paid.
+pub struct ZcashInvoiceStatus {
+    pub invoice_id: u64,
+    pub amount: u64,
+    /// The value received with enough confirmations.
+    pub received: u64,
+    /// The value received in transactions without enough confirmations yet.
+    pub pending: u64,
+    /// Whether the received value covers the requested one.
+    pub paid: bool,
+}
+
+impl ZcashWalletDb {
+    /// Creates an invoice of the account for the value, in zatoshis, to be paid to a fresh
+    /// address, so that payments can be told apart by the address they are sent to. The
+    /// `memo_tag`, like an order number, is put in the memo of the payment request.
+    pub fn create_invoice(
+        &self,
+        account: ZcashAccountId,
+        amount: u64,
+        memo_tag: Option<String>,
+    ) -> ZcashResult<ZcashInvoice> {
+        let value = Amount::from_u64(amount).map_err(|_| "Invalid amount")?;
+        let memo = memo_tag
+            .as_ref()
+            .map(|tag| {
+                tag.parse::<Memo>()
+                    .map(|memo| MemoBytes::from(&memo))
+                    .map_err(|_| "The memo tag does not fit in a memo")
+            })
+            .transpose()?;
+        let conn = Connection::open(&self.path)?;
+        ensure_account_exists(&conn, account)?;
+
+        let ua = WalletDb::for_path(&self.path, self.params)?
+            .get_next_available_address(account.into())
+            .map_err(cast_err)?
+            .ok_or("The account has no addresses left")?;
+        let address = ua.encode(&self.params);
+        let payment = Payment {
+            recipient_address: RecipientAddress::Unified(ua),
+            amount: value,
+            memo,
+            label: None,
+            message: None,
+            other_params: vec![],
+        };
+        let payment_uri = TransactionRequest::new(vec![payment])
+            .ok()
+            .and_then(|request| request.to_uri(&self.params))
+            .ok_or("Unable to encode the payment request")?;
+
+        conn.execute(
+            "INSERT INTO uniffi_invoices (account, address, amount, memo_tag)
+            VALUES (:account, :address, :amount, :memo_tag)",
+            named_params![
+                ":account": account.id,
+                ":address": address,
+                ":amount": amount,
+                ":memo_tag": memo_tag,
+            ],
+        )?;
+
+        Ok(ZcashInvoice {
+            id: conn.last_insert_rowid() as u64,
+            account,
+            address,
+            amount,
+            memo_tag,
+            payment_uri,
+        })
+    }
+
+    /// Sums what the wallet received at the address of the invoice, once the blocks are
+    /// scanned: notes to its Sapling receiver and outputs to its transparent one. Change
+    /// and expired transactions do not count.
+    pub fn check_invoice_paid(
+        &self,
+        invoice_id: u64,
+        min_confirmations: u32,
+    ) -> ZcashResult<ZcashInvoiceStatus> {
+        let conn = Connection::open(&self.path)?;
+        let (account, address, amount): (u32, String, u64) = conn
+            .query_row(
+                "SELECT account, address, amount FROM uniffi_invoices WHERE id = :id",
+                named_params![":id": invoice_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?
+            .ok_or_else(|| ZcashError::Message {
+                error: format!("Invoice {} not found", invoice_id),
+            })?;
+        let ua = match RecipientAddress::decode(&self.params, &address) {
+            Some(RecipientAddress::Unified(ua)) => ua,
+            _ => {
+                return Err(ZcashError::Message {
+                    error: format!("{} is not a unified address", address),
+                })
+            }
+        };
+
+        let chain_tip_height: Option<u32> = conn.query_row(
+            "SELECT MAX(block_range_end) - 1 FROM scan_queue",
+            [],
+            |row| row.get(0),
+        )?;
+        let chain_tip_height = chain_tip_height.unwrap_or(0);
+        let is_confirmed = |height: Option<u32>| {
+            min_confirmations == 0
+                || height.is_some_and(|height| height + min_confirmations <= chain_tip_height + 1)
+        };
+
+        let mut payments: Vec<(u64, Option<u32>)> = vec![];
+        if let Some(sapling) = ua.sapling() {
+            let mut notes = conn.prepare(
+                "SELECT n.value, t.block
+                FROM sapling_received_notes n
+                JOIN transactions t ON t.id_tx = n.tx
+                WHERE n.account = :account
+                AND n.diversifier = :diversifier
+                AND n.is_change = 0
+                AND (t.block IS NOT NULL OR IFNULL(t.expiry_height, 0) = 0 OR t.expiry_height > :tip)",
+            )?;
+            let rows = notes.query_map(
+                named_params![
+                    ":account": account,
+                    ":diversifier": &sapling.diversifier().0[..],
+                    ":tip": chain_tip_height,
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            for row in rows {
+                payments.push(row?);
+            }
+        }
+        if let Some(transparent) = ua.transparent() {
+            let mut outputs = conn.prepare(
+                "SELECT value_zat, height FROM utxos
+                WHERE received_by_account = :account AND address = :address",
+            )?;
+            let rows = outputs.query_map(
+                named_params![
+                    ":account": account,
+                    ":address": transparent.encode(&self.params),
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            for row in rows {
+                payments.push(row?);
+            }
+        }
+
+        let (confirmed, pending): (Vec<_>, Vec<_>) = payments
+            .into_iter()
+            .partition(|(_, height)| is_confirmed(*height));
+        let received = confirmed.iter().map(|(value, _)| value).sum();
+        Ok(ZcashInvoiceStatus {
+            invoice_id,
+            amount,
+            received,
+            pending: pending.iter().map(|(value, _)| value).sum(),
+            paid: received >= amount,
+        })
+    }
+}
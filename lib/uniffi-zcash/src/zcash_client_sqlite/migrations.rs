@@ -0,0 +1,115 @@
+//! The tables this library keeps in the wallet database, next to the upstream ones, for
+//! what the upstream schema has no notion of. They are created and migrated with schemer,
+//! like the upstream tables, but their migrations are tracked in a table of their own, so
+//! that both sets of migrations never get mixed.
+
+use std::collections::HashSet;
+
+use rusqlite::{Connection, Transaction};
+use schemer::{Migration, Migrator};
+use schemer_rusqlite::{RusqliteAdapter, RusqliteMigration};
+use uuid::Uuid;
+
+use crate::{ZcashError, ZcashResult};
+
+const MIGRATIONS_TABLE: &str = "uniffi_schemer_migrations";
+
+/// Creates the tables of this library, or migrates them to the current version. Called
+/// after the upstream migrations, by the functions initializing the wallet database.
+pub(crate) fn init_uniffi_tables(path: &str) -> ZcashResult<()> {
+    let mut conn = Connection::open(path)?;
+    let adapter = RusqliteAdapter::<rusqlite::Error>::new(&mut conn, Some(MIGRATIONS_TABLE.into()));
+    adapter.init()?;
+
+    let mut migrator = Migrator::new(adapter);
+    migrator
+        .register_multiple(all_migrations())
+        .map_err(migration_err)?;
+    migrator.up(None).map_err(migration_err)
+}
+
+fn migration_err(e: impl std::fmt::Debug) -> ZcashError {
+    ZcashError::Message {
+        error: format!("Error while migrating the tables of the library: {:?}", e),
+    }
+}
+
+fn all_migrations() -> Vec<Box<dyn RusqliteMigration<Error = rusqlite::Error>>> {
+    vec![Box::new(SideTables)]
+}
+
+/// The tables which were created on first use, before they got migrations. They are only
+/// created if missing, so that those of older wallets are kept.
+struct SideTables;
+
+const SIDE_TABLES_ID: Uuid = Uuid::from_u128(0x57137e90_8d5e_40c8_9e2a_6423c474cc77);
+
+impl Migration for SideTables {
+    fn id(&self) -> Uuid {
+        SIDE_TABLES_ID
+    }
+
+    fn dependencies(&self) -> HashSet<Uuid> {
+        HashSet::new()
+    }
+
+    fn description(&self) -> &'static str {
+        "Creates the archived accounts, device derivations, invoices, spending limits, \
+        P2SH scripts and outputs, and replaced transactions tables."
+    }
+}
+
+impl RusqliteMigration for SideTables {
+    type Error = rusqlite::Error;
+
+    fn up(&self, transaction: &Transaction) -> Result<(), Self::Error> {
+        transaction.execute_batch(
+            "CREATE TABLE IF NOT EXISTS uniffi_archived_accounts (
+                account INTEGER PRIMARY KEY,
+                archived_at INTEGER NOT NULL DEFAULT (unixepoch()),
+                FOREIGN KEY (account) REFERENCES accounts(account)
+            );
+            CREATE TABLE IF NOT EXISTS uniffi_device_derivations (
+                account INTEGER PRIMARY KEY,
+                device TEXT NOT NULL,
+                derivation_path TEXT NOT NULL,
+                seed_fingerprint BLOB,
+                FOREIGN KEY (account) REFERENCES accounts(account)
+            );
+            CREATE TABLE IF NOT EXISTS uniffi_invoices (
+                id INTEGER PRIMARY KEY,
+                account INTEGER NOT NULL,
+                address TEXT NOT NULL,
+                amount INTEGER NOT NULL,
+                memo_tag TEXT,
+                created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+                FOREIGN KEY (account) REFERENCES accounts(account)
+            );
+            CREATE TABLE IF NOT EXISTS uniffi_spending_limits (
+                account INTEGER PRIMARY KEY,
+                max_per_transaction INTEGER,
+                max_per_day INTEGER,
+                FOREIGN KEY (account) REFERENCES accounts(account)
+            );
+            CREATE TABLE IF NOT EXISTS uniffi_p2sh_scripts (
+                address TEXT PRIMARY KEY,
+                redeem_script BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS uniffi_p2sh_utxos (
+                prevout_txid BLOB NOT NULL,
+                prevout_idx INTEGER NOT NULL,
+                address TEXT NOT NULL,
+                script BLOB NOT NULL,
+                value_zat INTEGER NOT NULL,
+                height INTEGER NOT NULL,
+                spent_in_tx BLOB,
+                PRIMARY KEY (prevout_txid, prevout_idx),
+                FOREIGN KEY (address) REFERENCES uniffi_p2sh_scripts(address)
+            );
+            CREATE TABLE IF NOT EXISTS uniffi_replaced_transactions (
+                txid BLOB PRIMARY KEY,
+                replaced_by BLOB NOT NULL
+            );",
+        )
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::num::NonZeroU32;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 use zcash_client_backend::address::AddressMetadata;
@@ -12,37 +13,133 @@
 use zcash_client_backend::wallet::WalletTransparentOutput;
 
 use zcash_client_sqlite::chain::init::init_blockmeta_db;
-use zcash_client_sqlite::wallet::init::init_wallet_db;
+use zcash_client_sqlite::error::SqliteClientError;
+use zcash_client_sqlite::wallet::init::{init_wallet_db, WalletMigrationError};
 use zcash_client_sqlite::{chain::BlockMeta, FsBlockDb, ReceivedNoteId, WalletDb};
 
+use zcash_primitives::consensus::BlockHeight;
 use zcash_primitives::legacy::TransparentAddress;
 use zcash_primitives::sapling;
+use zcash_primitives::transaction::components::amount::NonNegativeAmount;
 use zcash_primitives::transaction::components::{Amount, OutPoint};
-use zcash_primitives::zip32::AccountId;
+use zcash_primitives::transaction::TxId;
+use zcash_primitives::zip32::{AccountId, DiversifierIndex};
 
 use derive_more::{From, Into};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use secrecy::SecretVec;
 
+mod account_removal;
+pub use self::account_removal::*;
+
+mod account_summary;
+
+mod audit;
+pub use self::audit::*;
+
+mod balance_changes;
+pub use self::balance_changes::*;
+
+mod batch;
+pub use self::batch::*;
+
+mod birthday;
+
+mod block_caches;
+pub use self::block_caches::*;
+
+mod block_compression;
+pub(crate) use self::block_compression::CachedBlocks;
+
+mod block_times;
+pub use self::block_times::*;
+
 mod chain;
 pub use self::chain::*;
 
+mod conflicts;
+
+mod debug_report;
+
+mod discovery;
+pub use self::discovery::*;
+
+mod fee_bump;
+
+mod address_index;
+
+mod address_usage;
+pub use self::address_usage::*;
+
+mod history_cursor;
+pub use self::history_cursor::*;
+
+mod history_pruning;
+pub use self::history_pruning::*;
+
+mod invoices;
+pub use self::invoices::*;
+
+mod imported_account;
+pub use self::imported_account::*;
+
+mod migrations;
+pub(crate) use self::migrations::init_uniffi_tables;
+
+mod notifications;
+pub use self::notifications::*;
+
+mod p2sh;
+
+mod proposal;
+pub use self::proposal::*;
+
+mod readonly_query;
+pub use self::readonly_query::*;
+
+mod scan_queue;
+
+mod self_memos;
+
+mod shard_snapshots;
+
+mod shielding;
+
+mod signing_payload;
+pub use self::signing_payload::*;
+
+mod spending_limits;
+pub use self::spending_limits::*;
+
+mod sql_logging;
+pub use self::sql_logging::*;
+
+mod sync_status;
+
+mod ufvk_replacement;
+pub use self::ufvk_replacement::*;
+
 mod wallet;
 pub use self::wallet::*;
 
+mod wallet_stats;
+pub use self::wallet_stats::*;
+
 use crate::{
+    validation::{check_range, check_seed},
     ZcashAccountBirthday, ZcashAccountId, ZcashAddressMetadata, ZcashAmount, ZcashBlockHash,
     ZcashBlockHeight, ZcashBlockMetadata, ZcashCommitmentTreeRoot, ZcashConsensusParameters,
     ZcashDecryptedTransaction, ZcashError, ZcashExtendedFullViewingKey, ZcashMemo,
-    ZcashNullifierQuery, ZcashOutPoint, ZcashReceivedSaplingNote, ZcashResult,
-    ZcashSaplingNullifier, ZcashScanRange, ZcashScannedBlock, ZcashSentTransaction,
-    ZcashShieldedProtocol, ZcashTransaction, ZcashTransparentAddress, ZcashTxId,
-    ZcashUnifiedAddress, ZcashUnifiedFullViewingKey, ZcashUnifiedSpendingKey, ZcashWalletSummary,
-    ZcashWalletTransparentOutput,
+    ZcashNonNegativeAmount, ZcashNullifierQuery, ZcashOutPoint, ZcashReceivedSaplingNote,
+    ZcashResult, ZcashScanRange, ZcashScannedBlock, ZcashSentTransaction, ZcashShieldedProtocol,
+    ZcashTransaction, ZcashTransparentAddress, ZcashTxId, ZcashUnifiedAddress,
+    ZcashUnifiedAddressRequest, ZcashUnifiedFullViewingKey, ZcashUnifiedSpendingKey,
+    ZcashWalletSummary, ZcashWalletTransparentOutput,
 };
 
 pub struct TupleTargetAndAnchorHeight {
-    pub target_height: Arc<ZcashBlockHeight>,
-    pub anchor_height: Arc<ZcashBlockHeight>,
+    pub target_height: u32,
+    pub anchor_height: u32,
 }
 
 pub struct TupleAccountIdAndUnifiedSpendingKey {
@@ -52,12 +149,49 @@ pub struct TupleAccountIdAndUnifiedSpendingKey {
 
 pub struct TupleAccountIdAndSaplingNullifier {
     pub account_id: ZcashAccountId,
-    pub sapling_nullifier: Arc<ZcashSaplingNullifier>,
+    pub sapling_nullifier: Vec<u8>,
 }
 
 pub struct TupleBlockHeightAndHash {
-    pub block_height: Arc<ZcashBlockHeight>,
-    pub block_hash: Arc<ZcashBlockHash>,
+    pub block_height: u32,
+    pub block_hash: Vec<u8>,
+}
+
+/// The outcome of looking up a single height in a batched block lookup.
+/// `block_meta` is `None` both when the block is not cached and when `error` is set.
+pub struct ZcashFindBlockResult {
+    pub height: u32,
+    pub block_meta: Option<Arc<chain::ZcashBlockMeta>>,
+    pub error: Option<String>,
+}
+
+/// The outcome of looking up a single height in a batched block hash lookup.
+pub struct ZcashBlockHashResult {
+    pub height: u32,
+    pub block_hash: Option<Vec<u8>>,
+    pub error: Option<String>,
+}
+
+/// The outcome of looking up a single transaction in a batched transaction lookup.
+/// `transaction` is `None` when `error` is set, like for transactions the wallet does not have.
+/// Those it has without their raw data, as those whose history was pruned, still have the
+/// other fields.
+pub struct ZcashTransactionLookupResult {
+    pub height: Option<Arc<ZcashBlockHeight>>,
+    /// The time of the block that mined the transaction, if the wallet scanned it.
+    pub block_time: Option<u32>,
+    pub transaction: Option<Arc<ZcashTransaction>>,
+    /// The other transactions of the wallet spending the same notes or outputs.
+    pub conflicts: Vec<Arc<ZcashTxId>>,
+    /// How the transaction changed the balances of the accounts, per pool.
+    pub balance_changes: Vec<ZcashTransactionBalanceChange>,
+    pub error: Option<String>,
+}
+
+/// The outcome of writing the metadata of a single block in a batched write.
+pub struct ZcashWriteBlockMetaResult {
+    pub height: u32,
+    pub error: Option<String>,
 }
 
 /// A wrapper for the SQLite connection to the wallet database.
@@ -66,17 +200,47 @@ pub struct ZcashWalletDb {
     pub params: ZcashConsensusParameters,
 }
 
-fn cast_err(e: zcash_client_sqlite::error::SqliteClientError) -> ZcashError {
+fn cast_err(e: SqliteClientError) -> ZcashError {
     ZcashError::Message {
         error: format!("Err: {:?}", e),
     }
 }
 
-type UFVKMap = HashMap<ZcashAccountId, Arc<ZcashUnifiedFullViewingKey>>;
+/// Reads a diversifier index the way the wallet database stores it, in big endian.
+fn diversifier_index_from_be(di_be: &[u8]) -> ZcashResult<DiversifierIndex> {
+    let mut di: [u8; 11] = di_be.try_into().map_err(|_| ZcashError::Message {
+        error: "Diversifier index is not an 11-byte value".to_string(),
+    })?;
+    di.reverse();
+    Ok(DiversifierIndex(di))
+}
+
+/// The viewing key of an account, as listed by
+/// [`ZcashWalletDb::get_unified_full_viewing_keys`].
+pub struct ZcashAccountUfvk {
+    pub account: ZcashAccountId,
+    pub ufvk: Arc<ZcashUnifiedFullViewingKey>,
+}
+
+/// A transparent address of an account, as listed by
+/// [`ZcashWalletDb::get_transparent_receivers`].
+pub struct ZcashTransparentReceiver {
+    pub address: String,
+    pub metadata: Arc<ZcashAddressMetadata>,
+}
 
-type TransparentReceiversMap = HashMap<String, Arc<ZcashAddressMetadata>>;
+/// The balance of a transparent address, as listed by
+/// [`ZcashWalletDb::get_transparent_balances`].
+pub struct ZcashTransparentBalance {
+    pub address: String,
+    pub balance: Arc<ZcashAmount>,
+}
 
-type TransparentBalancesMap = HashMap<String, Arc<ZcashAmount>>;
+/// Like [`ZcashTransparentBalance`], in zatoshis.
+pub struct ZcashTransparentBalanceValue {
+    pub address: String,
+    pub value: i64,
+}
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, From, Into)]
 pub struct ZcashReceivedNoteId(ReceivedNoteId);
@@ -90,6 +254,15 @@ pub fn new(txid: Arc<ZcashTxId>, zsp: ZcashShieldedProtocol, output_index: u16)
     }
 }
 
+/// The outcome of [`ZcashWalletDb::init_without_seed`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ZcashWalletInitResult {
+    Initialized,
+    /// A migration needs the wallet seed, so [`ZcashWalletDb::init_with_seed`] must be
+    /// called. Nothing was migrated.
+    SeedRequired,
+}
+
 impl ZcashWalletDb {
     /// Construct a connection to the wallet database stored at the specified path.
     pub fn for_path(path: String, params: ZcashConsensusParameters) -> ZcashResult<Self> {
@@ -98,13 +271,42 @@ pub fn for_path(path: String, params: ZcashConsensusParameters) -> ZcashResult<S
 
     /// From wallet::init
     pub fn initialize(&self, seed: Vec<u8>) -> ZcashResult<()> {
+        check_seed("seed", &seed)?;
         let mut db_data =
             WalletDb::for_path(&self.path, self.params).expect("Cannot access the DB!");
         let secvec = SecretVec::new(seed);
 
         init_wallet_db(&mut db_data, Some(secvec)).map_err(|e| ZcashError::Message {
             error: format!("Error while initializing data DB: {:?}", e),
-        })
+        })?;
+        init_uniffi_tables(&self.path)
+    }
+
+    /// Initializes or migrates the database without the seed, which most migrations do
+    /// not need, so apps only ask users for their seed phrase when the result says so.
+    pub fn init_without_seed(&self) -> ZcashResult<ZcashWalletInitResult> {
+        let mut db_data =
+            WalletDb::for_path(&self.path, self.params).expect("Cannot access the DB!");
+
+        match init_wallet_db(&mut db_data, None) {
+            Ok(()) => {
+                init_uniffi_tables(&self.path)?;
+                Ok(ZcashWalletInitResult::Initialized)
+            }
+            Err(schemer::MigratorError::Migration {
+                error: WalletMigrationError::SeedRequired,
+                ..
+            }) => Ok(ZcashWalletInitResult::SeedRequired),
+            Err(e) => Err(ZcashError::Message {
+                error: format!("Error while initializing data DB: {:?}", e),
+            }),
+        }
+    }
+
+    /// Initializes or migrates the database with the seed, after
+    /// [`ZcashWalletDb::init_without_seed`] reported it is required.
+    pub fn init_with_seed(&self, seed: Vec<u8>) -> ZcashResult<()> {
+        self.initialize(seed)
     }
 
     // ####################################
@@ -170,8 +372,8 @@ pub fn get_target_and_anchor_heights(
         {
             Ok(None) => Ok(None),
             Ok(Some((target_height, anchor_height))) => Ok(Some(TupleTargetAndAnchorHeight {
-                target_height: Arc::new(target_height.into()),
-                anchor_height: Arc::new(anchor_height.into()),
+                target_height: target_height.into(),
+                anchor_height: anchor_height.into(),
             })),
             Err(e) => Err(ZcashError::Message {
                 error: format!("Err: {}", e),
@@ -198,14 +400,44 @@ pub fn get_block_hash(
             .map_err(cast_err)
     }
 
+    /// Returns the hashes of the blocks in the `start_height..end_height` range, one result
+    /// per height, opening the database only once.
+    pub fn get_block_hashes(
+        &self,
+        start_height: u32,
+        end_height: u32,
+    ) -> ZcashResult<Vec<ZcashBlockHashResult>> {
+        check_range("start_height..end_height", start_height, end_height)?;
+        let db = WalletDb::for_path(&self.path, self.params).map_err(|e| ZcashError::Message {
+            error: format!("Cannot access the DB: {:?}", e),
+        })?;
+
+        Ok((start_height..end_height)
+            .map(
+                |height| match db.get_block_hash(BlockHeight::from_u32(height)) {
+                    Ok(hash) => ZcashBlockHashResult {
+                        height,
+                        block_hash: hash.map(|hash| hash.0.to_vec()),
+                        error: None,
+                    },
+                    Err(e) => ZcashBlockHashResult {
+                        height,
+                        block_hash: None,
+                        error: Some(format!("Err: {:?}", e)),
+                    },
+                },
+            )
+            .collect())
+    }
+
     pub fn get_max_height_hash(&self) -> ZcashResult<Option<TupleBlockHeightAndHash>> {
         WalletDb::for_path(&self.path, self.params)
             .expect("Cannot access the DB!")
             .get_max_height_hash()
             .map(|x| {
                 x.map(|(height, hash)| TupleBlockHeightAndHash {
-                    block_height: Arc::new(height.into()),
-                    block_hash: Arc::new(hash.into()),
+                    block_height: height.into(),
+                    block_hash: hash.0.to_vec(),
                 })
             })
             .map_err(cast_err)
@@ -222,6 +454,37 @@ pub fn get_tx_height(
             .map_err(cast_err)
     }
 
+    /// Returns the height the transaction expires at, if the wallet has it. Transactions
+    /// which never expire, or only known from their blocks, have none.
+    pub fn get_tx_expiry_height(&self, txid: Arc<ZcashTxId>) -> ZcashResult<Option<u32>> {
+        let txid: TxId = (*txid).into();
+        let conn = Connection::open(&self.path)?;
+        let expiry_height: Option<u32> = conn
+            .query_row(
+                "SELECT expiry_height FROM transactions WHERE txid = :txid",
+                named_params![":txid": txid.as_ref()],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(expiry_height.filter(|height| *height != 0))
+    }
+
+    /// Returns how many blocks have confirmed the transaction, counting the one mining
+    /// it, as of the wallet chain height. Unmined transactions have no confirmations.
+    pub fn get_tx_confirmations(&self, txid: Arc<ZcashTxId>) -> ZcashResult<u32> {
+        let db = WalletDb::for_path(&self.path, self.params).expect("Cannot access the DB!");
+
+        let tx_height = db.get_tx_height((*txid).into()).map_err(cast_err)?;
+        let chain_height = db.chain_height().map_err(cast_err)?;
+        Ok(match (tx_height, chain_height) {
+            (Some(tx_height), Some(chain_height)) if tx_height <= chain_height => {
+                u32::from(chain_height) - u32::from(tx_height) + 1
+            }
+            _ => 0,
+        })
+    }
+
     pub fn get_wallet_birthday(&self) -> ZcashResult<Option<Arc<ZcashBlockHeight>>> {
         WalletDb::for_path(&self.path, self.params)
             .expect("Cannot access the DB!")
@@ -253,11 +516,19 @@ pub fn get_current_address(
             .map_err(cast_err)
     }
 
-    pub fn get_unified_full_viewing_keys(&self) -> ZcashResult<UFVKMap> {
-        let convert_hm = |hm: HashMap<AccountId, UnifiedFullViewingKey>| -> UFVKMap {
-            hm.into_iter()
-                .map(|(x, y)| (x.into(), Arc::new(y.into())))
-                .collect()
+    /// The viewing keys of the accounts, sorted by account, so that they are listed in
+    /// the same order on every call.
+    pub fn get_unified_full_viewing_keys(&self) -> ZcashResult<Vec<ZcashAccountUfvk>> {
+        let convert_hm = |hm: HashMap<AccountId, UnifiedFullViewingKey>| {
+            let mut ufvks: Vec<ZcashAccountUfvk> = hm
+                .into_iter()
+                .map(|(x, y)| ZcashAccountUfvk {
+                    account: x.into(),
+                    ufvk: Arc::new(y.into()),
+                })
+                .collect();
+            ufvks.sort_by_key(|x| x.account);
+            ufvks
         };
 
         WalletDb::for_path(&self.path, self.params)
@@ -308,7 +579,13 @@ pub fn get_memo(&self, id_note: Arc<ZcashNoteId>) -> ZcashResult<ZcashMemo> {
             .map_err(cast_err)
     }
 
+    /// Fails for the transactions the wallet has without their raw data, as those whose
+    /// history was pruned with [`ZcashWalletDb::prune_history`].
     pub fn get_transaction(&self, txid: Arc<ZcashTxId>) -> ZcashResult<Arc<ZcashTransaction>> {
+        let conn = Connection::open(&self.path)?;
+        if let Some(error) = history_pruning::missing_raw_data(&conn, &(*txid).into())? {
+            return Err(ZcashError::Message { error });
+        }
         WalletDb::for_path(&self.path, self.params)
             .expect("Cannot access the DB!")
             .get_transaction((*txid).into())
@@ -317,6 +594,81 @@ pub fn get_transaction(&self, txid: Arc<ZcashTxId>) -> ZcashResult<Arc<ZcashTran
             .map_err(cast_err)
     }
 
+    /// Returns the mined height, block time and content of the given transactions, keyed
+    /// by their hex encoded ids, opening the database only once. Meant for history lists,
+    /// which otherwise cost a couple of calls per row.
+    pub fn get_transactions_by_ids(
+        &self,
+        txids: Vec<Arc<ZcashTxId>>,
+    ) -> HashMap<String, ZcashTransactionLookupResult> {
+        let db = WalletDb::for_path(&self.path, self.params).expect("Cannot access the DB!");
+        let conn = Connection::open(&self.path).expect("Cannot access the DB!");
+        let conflicts = conflicts::ConflictIndex::build(&db, &conn);
+
+        txids
+            .into_iter()
+            .map(|txid| {
+                let txid: TxId = (*txid).into();
+                // The upstream `get_tx_height` fails for unmined transactions.
+                let height = conn
+                    .query_row(
+                        "SELECT block FROM transactions WHERE txid = :txid",
+                        named_params![":txid": &txid.as_ref()[..]],
+                        |row| row.get::<_, Option<u32>>(0),
+                    )
+                    .map(|height| height.map(BlockHeight::from_u32))
+                    .map_err(SqliteClientError::DbError);
+                // Those without their raw data, as those whose history was pruned, are
+                // still found, with the error telling why their content is missing.
+                let transaction = history_pruning::missing_raw_data(&conn, &txid)
+                    .map_err(SqliteClientError::DbError)
+                    .and_then(|missing| match missing {
+                        None => db.get_transaction(txid).map(|tx| (Some(tx), None)),
+                        Some(error) => Ok((None, Some(error))),
+                    });
+                let result = match (height, transaction, &conflicts) {
+                    (Ok(height), Ok((transaction, error)), Ok(conflicts)) => {
+                        ZcashTransactionLookupResult {
+                            height: height.map(From::from).map(Arc::new),
+                            block_time: height
+                                .and_then(|height| {
+                                    block_times::block_times(&conn, height.into()).ok()
+                                })
+                                .flatten()
+                                .map(|times| times.block_time),
+                            transaction: transaction.map(|tx| Arc::new(tx.into())),
+                            conflicts: conflicts
+                                .conflicts_of(&txid)
+                                .into_iter()
+                                .map(|txid| Arc::new(txid.into()))
+                                .collect(),
+                            balance_changes: balance_changes::balance_changes(&conn, &txid)
+                                .unwrap_or_default(),
+                            error,
+                        }
+                    }
+                    (Err(e), _, _) | (_, Err(e), _) => ZcashTransactionLookupResult {
+                        height: None,
+                        block_time: None,
+                        transaction: None,
+                        conflicts: vec![],
+                        balance_changes: vec![],
+                        error: Some(format!("SqliteClientError: {:?}", e)),
+                    },
+                    (_, _, Err(e)) => ZcashTransactionLookupResult {
+                        height: None,
+                        block_time: None,
+                        transaction: None,
+                        conflicts: vec![],
+                        balance_changes: vec![],
+                        error: Some(format!("Cannot find the conflicts: {}", e)),
+                    },
+                };
+                (txid.to_string(), result)
+            })
+            .collect()
+    }
+
     pub fn get_sapling_nullifiers(
         &self,
         query: ZcashNullifierQuery,
@@ -328,7 +680,7 @@ pub fn get_sapling_nullifiers(
                 x.iter()
                     .map(|(aid, nf)| TupleAccountIdAndSaplingNullifier {
                         account_id: (*aid).into(),
-                        sapling_nullifier: Arc::new((*nf).into()),
+                        sapling_nullifier: nf.to_vec(),
                     })
                     .collect()
             })
@@ -371,16 +723,56 @@ pub fn select_spendable_sapling_notes(
             .map_err(cast_err)
     }
 
+    /// Returns the value of the Sapling notes of the account the input selector could
+    /// spend with the given confirmations, leaving the excluded ones out. Coin control
+    /// screens can show it as what is available with the current selection: the same
+    /// anchor and the same notes are used as when spending.
+    pub fn get_spendable_value(
+        &self,
+        account: ZcashAccountId,
+        min_confirmations: u32,
+        excluded_notes: Vec<Arc<ZcashReceivedNoteId>>,
+    ) -> ZcashResult<Arc<ZcashNonNegativeAmount>> {
+        let min_confirmations =
+            NonZeroU32::new(min_confirmations).ok_or("At least one confirmation is required")?;
+        let exclude: Vec<ReceivedNoteId> = excluded_notes.iter().map(|x| (**x).into()).collect();
+        let db = WalletDb::for_path(&self.path, self.params)?;
+
+        let anchor_height = match db
+            .get_target_and_anchor_heights(min_confirmations)
+            .map_err(cast_err)?
+        {
+            Some((_, anchor_height)) => anchor_height,
+            None => return Ok(Arc::new(NonNegativeAmount::ZERO.into())),
+        };
+        let value = db
+            .get_spendable_sapling_notes(account.into(), anchor_height, &exclude[..])
+            .map_err(cast_err)?
+            .iter()
+            .try_fold(NonNegativeAmount::ZERO, |sum, note| {
+                sum + NonNegativeAmount::try_from(note.note_value).ok()?
+            })
+            .ok_or("Invalid spendable value")?;
+        Ok(Arc::new(value.into()))
+    }
+
+    /// The transparent addresses of the account, sorted by address, so that they are
+    /// listed in the same order on every call.
     pub fn get_transparent_receivers(
         &self,
         aid: ZcashAccountId,
-    ) -> ZcashResult<TransparentReceiversMap> {
-        let convert_hm =
-            |hm: HashMap<TransparentAddress, AddressMetadata>| -> TransparentReceiversMap {
-                hm.into_iter()
-                    .map(|(x, y)| (x.encode(&self.params), Arc::new(y.into())))
-                    .collect()
-            };
+    ) -> ZcashResult<Vec<ZcashTransparentReceiver>> {
+        let convert_hm = |hm: HashMap<TransparentAddress, AddressMetadata>| {
+            let mut receivers: Vec<ZcashTransparentReceiver> = hm
+                .into_iter()
+                .map(|(x, y)| ZcashTransparentReceiver {
+                    address: x.encode(&self.params),
+                    metadata: Arc::new(y.into()),
+                })
+                .collect();
+            receivers.sort_by(|x, y| x.address.cmp(&y.address));
+            receivers
+        };
 
         WalletDb::for_path(&self.path, self.params)
             .expect("Cannot access the DB!")
@@ -414,15 +806,23 @@ pub fn get_unspent_transparent_outputs(
             .map_err(cast_err)
     }
 
+    /// The balances of the transparent addresses of the account, sorted by address, so
+    /// that they are listed in the same order on every call.
     pub fn get_transparent_balances(
         &self,
         account: ZcashAccountId,
         max_height: Arc<ZcashBlockHeight>,
-    ) -> ZcashResult<TransparentBalancesMap> {
-        let convert_hm = |hm: HashMap<TransparentAddress, Amount>| -> TransparentBalancesMap {
-            hm.into_iter()
-                .map(|(x, y)| (x.encode(&self.params), Arc::new(y.into())))
-                .collect()
+    ) -> ZcashResult<Vec<ZcashTransparentBalance>> {
+        let convert_hm = |hm: HashMap<TransparentAddress, Amount>| {
+            let mut balances: Vec<ZcashTransparentBalance> = hm
+                .into_iter()
+                .map(|(x, y)| ZcashTransparentBalance {
+                    address: x.encode(&self.params),
+                    balance: Arc::new(y.into()),
+                })
+                .collect();
+            balances.sort_by(|x, y| x.address.cmp(&y.address));
+            balances
         };
 
         WalletDb::for_path(&self.path, self.params)
@@ -432,6 +832,29 @@ pub fn get_transparent_balances(
             .map_err(cast_err)
     }
 
+    /// Like [`Self::get_transparent_balances`], with plain heights and zatoshi values.
+    pub fn get_transparent_balance_values(
+        &self,
+        account: ZcashAccountId,
+        max_height: u32,
+    ) -> ZcashResult<Vec<ZcashTransparentBalanceValue>> {
+        WalletDb::for_path(&self.path, self.params)
+            .expect("Cannot access the DB!")
+            .get_transparent_balances(account.into(), BlockHeight::from_u32(max_height))
+            .map(|hm| {
+                let mut balances: Vec<ZcashTransparentBalanceValue> = hm
+                    .into_iter()
+                    .map(|(x, y)| ZcashTransparentBalanceValue {
+                        address: x.encode(&self.params),
+                        value: y.into(),
+                    })
+                    .collect();
+                balances.sort_by(|x, y| x.address.cmp(&y.address));
+                balances
+            })
+            .map_err(cast_err)
+    }
+
     // ####################################
     // WalletWrite implementation methods #
     // ####################################
@@ -441,6 +864,7 @@ pub fn create_account(
         seed: Vec<u8>,
         birthday: Arc<ZcashAccountBirthday>,
     ) -> ZcashResult<TupleAccountIdAndUnifiedSpendingKey> {
+        check_seed("seed", &seed)?;
         WalletDb::for_path(&self.path, self.params)
             .expect("Cannot access the DB!")
             .create_account(&SecretVec::new(seed), (*birthday).clone().into())
@@ -451,15 +875,36 @@ pub fn create_account(
             .map_err(cast_err)
     }
 
+    /// Generates and stores the next address of the account. All the receivers the
+    /// account supports are included, unless a `request` restricts them, like for
+    /// addresses without a transparent receiver. The wallet stores the address with all
+    /// its receivers, which share the diversifier index of the returned one.
     pub fn get_next_available_address(
         &self,
         account: ZcashAccountId,
+        request: Option<ZcashUnifiedAddressRequest>,
     ) -> ZcashResult<Option<Arc<ZcashUnifiedAddress>>> {
-        WalletDb::for_path(&self.path, self.params)
+        // Checked upfront, as each generated address uses up a diversifier index.
+        if let Some(request) = &request {
+            request.validate()?;
+            if request.has_orchard {
+                return Err(ZcashError::Message {
+                    error: "The wallet does not generate addresses with orchard receivers yet"
+                        .to_string(),
+                });
+            }
+        }
+
+        let addr = WalletDb::for_path(&self.path, self.params)
             .expect("Cannot access the DB!")
             .get_next_available_address(account.into())
-            .map(|addr| addr.map(From::from).map(Arc::new))
-            .map_err(cast_err)
+            .map_err(cast_err)?;
+
+        let addr = match (addr, request) {
+            (Some(addr), Some(request)) => Some(request.select_receivers(&addr)?),
+            (addr, _) => addr,
+        };
+        Ok(addr.map(From::from).map(Arc::new))
     }
 
     pub fn put_blocks(&self, blocks: Vec<Arc<ZcashScannedBlock>>) -> ZcashResult<()> {
@@ -495,13 +940,17 @@ pub fn store_sent_tx(&self, sent_tx: ZcashSentTransaction) -> ZcashResult<()> {
             .map_err(cast_err)
     }
 
+    /// Rewinds the wallet to the given height, as after a reorg, including the outputs of
+    /// the watched P2SH addresses mined above it. If it fails after rewinding the wallet,
+    /// it can be called again.
     pub fn truncate_to_height(&self, block_height: u32) -> ZcashResult<()> {
         let zheight = ZcashBlockHeight::new(block_height).into();
 
         WalletDb::for_path(&self.path, self.params)
             .expect("Cannot access the DB!")
             .truncate_to_height(zheight)
-            .map_err(cast_err)
+            .map_err(cast_err)?;
+        self.truncate_p2sh_utxos(block_height)
     }
 
     pub fn put_received_transparent_utxo(
@@ -552,12 +1001,18 @@ pub fn get_checkpoint_depth(&self, min_confirmations: u32) -> ZcashResult<u32> {
 
 pub struct ZcashFsBlockDb {
     pub fs_block_db: Mutex<FsBlockDb>,
+    pub(crate) fsblockdb_root: PathBuf,
+    pub(crate) params: Option<ZcashConsensusParameters>,
+    pub(crate) compression_level: Mutex<Option<i32>>,
 }
 
 impl ZcashFsBlockDb {
     pub fn for_path(fsblockdb_root: String) -> ZcashResult<Self> {
         Ok(ZcashFsBlockDb {
-            fs_block_db: Mutex::new(FsBlockDb::for_path(fsblockdb_root).unwrap()),
+            fs_block_db: Mutex::new(FsBlockDb::for_path(&fsblockdb_root).unwrap()),
+            fsblockdb_root: fsblockdb_root.into(),
+            params: None,
+            compression_level: Mutex::new(None),
         })
     }
 
@@ -592,6 +1047,30 @@ pub fn find_block(
         }
     }
 
+    /// Returns the metadata for the blocks with the given heights, one result per height,
+    /// taking the database lock only once.
+    pub fn find_blocks(&self, heights: Vec<u32>) -> Vec<ZcashFindBlockResult> {
+        let db = self.fs_block_db.lock().unwrap();
+
+        heights
+            .into_iter()
+            .map(
+                |height| match db.find_block(BlockHeight::from_u32(height)) {
+                    Ok(opt) => ZcashFindBlockResult {
+                        height,
+                        block_meta: opt.map(From::from).map(Arc::new),
+                        error: None,
+                    },
+                    Err(e) => ZcashFindBlockResult {
+                        height,
+                        block_meta: None,
+                        error: Some(format!("FsBlockDbError: {:?}", e)),
+                    },
+                },
+            )
+            .collect()
+    }
+
     pub fn get_max_cached_height(&self) -> ZcashResult<Option<Arc<ZcashBlockHeight>>> {
         match self.fs_block_db.lock().unwrap().get_max_cached_height() {
             Ok(opt) => Ok(opt.map(From::from).map(Arc::new)),
@@ -601,6 +1080,18 @@ pub fn get_max_cached_height(&self) -> ZcashResult<Option<Arc<ZcashBlockHeight>>
         }
     }
 
+    /// Removes the metadata of the blocks above the given height, so that the blocks of
+    /// another chain can be cached in their place after a reorg. The block files are kept.
+    pub fn truncate_to_height(&self, block_height: u32) -> ZcashResult<()> {
+        self.fs_block_db
+            .lock()
+            .unwrap()
+            .truncate_to_height(BlockHeight::from_u32(block_height))
+            .map_err(|e| ZcashError::Message {
+                error: format!("FsBlockDbError: {:?}", e),
+            })
+    }
+
     // NOTE why I can't export ZcashBlockMeta in the whole crate?
     pub fn write_block_metadata(
         &self,
@@ -620,4 +1111,27 @@ pub fn write_block_metadata(
                 error: format!("FsBlockDbError: {:?}", e),
             })
     }
+
+    /// Writes the metadata of each block on its own, so that a failing block does not
+    /// prevent the others from being written, returning one result per block.
+    pub fn write_block_metadata_each(
+        &self,
+        block_meta: Vec<Arc<chain::ZcashBlockMeta>>,
+    ) -> Vec<ZcashWriteBlockMetaResult> {
+        let db = self.fs_block_db.lock().unwrap();
+
+        block_meta
+            .into_iter()
+            .map(|meta| {
+                let meta: BlockMeta = (*meta).into();
+                ZcashWriteBlockMetaResult {
+                    height: meta.height.into(),
+                    error: db
+                        .write_block_metadata(&[meta])
+                        .err()
+                        .map(|e| format!("FsBlockDbError: {:?}", e)),
+                }
+            })
+            .collect()
+    }
 }
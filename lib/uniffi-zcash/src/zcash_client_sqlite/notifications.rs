@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use rusqlite::{named_params, Connection};
+use zcash_client_backend::address::RecipientAddress;
+use zcash_client_backend::encoding::AddressCodec;
+use zcash_primitives::memo::{Memo, MemoBytes};
+
+use crate::{
+    validation::check_range, ZcashAccountId, ZcashConsensusParameters, ZcashError, ZcashResult,
+    ZcashTxId, ZcashWalletDb,
+};
+
+/// The conditions a received payment must meet to be notified. Those left unset always
+/// hold, so a rule without conditions matches every payment.
+#[derive(Clone, Debug)]
+pub struct ZcashNotificationRule {
+    pub account: Option<ZcashAccountId>,
+    /// The least value of the payment, in zatoshis.
+    pub min_value: Option<u64>,
+    /// What the text memo of the payment starts with. Payments without a known text memo,
+    /// like transparent ones, do not match.
+    pub memo_prefix: Option<String>,
+    /// The address, or one of the receivers of the unified address, paid to.
+    pub address: Option<String>,
+}
+
+/// A received payment matching a rule.
+pub struct ZcashNotificationMatch {
+    pub rule_id: u32,
+    pub account: ZcashAccountId,
+    pub txid: Arc<ZcashTxId>,
+    pub height: u32,
+    /// The value received, in zatoshis.
+    pub value: u64,
+    pub memo: Option<String>,
+}
+
+/// Receives the matches of a [`ZcashNotificationMatcher`].
+pub trait ZcashNotificationListener: Send + Sync {
+    fn on_match(&self, notification: ZcashNotificationMatch);
+}
+
+struct Rule {
+    spec: ZcashNotificationRule,
+    sapling_diversifier: Option<[u8; 11]>,
+    transparent_address: Option<String>,
+}
+
+/// A payment received by the wallet, a Sapling note or a transparent output.
+struct Payment {
+    account: u32,
+    sapling_diversifier: Option<Vec<u8>>,
+    transparent_address: Option<String>,
+    value: u64,
+    memo: Option<String>,
+    txid: Vec<u8>,
+    height: u32,
+}
+
+impl Rule {
+    fn matches(&self, payment: &Payment) -> bool {
+        let address_matches = match (&self.sapling_diversifier, &self.transparent_address) {
+            (None, None) => self.spec.address.is_none(),
+            (diversifier, address) => {
+                diversifier.is_some_and(|d| payment.sapling_diversifier.as_deref() == Some(&d[..]))
+                    || address.is_some() && payment.transparent_address == *address
+            }
+        };
+
+        address_matches
+            && self
+                .spec
+                .account
+                .is_none_or(|account| account.id == payment.account)
+            && self
+                .spec
+                .min_value
+                .is_none_or(|min_value| payment.value >= min_value)
+            && self.spec.memo_prefix.as_ref().is_none_or(|prefix| {
+                payment
+                    .memo
+                    .as_ref()
+                    .is_some_and(|memo| memo.starts_with(prefix.as_str()))
+            })
+    }
+}
+
+/// Evaluates rules on the payments the wallet received in the blocks it just scanned,
+/// notifying the matches, so that push notification services only look at new payments.
+///
+/// The sync loop calls [`Self::evaluate`] with the range of each scanned batch. Change
+/// is never notified. Memos are not part of compact blocks, so memo rules only match
+/// once the transactions are decrypted in full, with `decrypt_and_store_transaction`:
+/// evaluating the range again then notifies all its matches again.
+pub struct ZcashNotificationMatcher {
+    params: ZcashConsensusParameters,
+    rules: Mutex<BTreeMap<u32, Rule>>,
+    listener: Box<dyn ZcashNotificationListener>,
+}
+
+impl ZcashNotificationMatcher {
+    pub fn new(
+        params: ZcashConsensusParameters,
+        listener: Box<dyn ZcashNotificationListener>,
+    ) -> Self {
+        Self {
+            params,
+            rules: Mutex::new(BTreeMap::new()),
+            listener,
+        }
+    }
+
+    /// Registers the rule, returning its id, which the matches carry.
+    pub fn add_rule(&self, rule: ZcashNotificationRule) -> ZcashResult<u32> {
+        let (sapling_diversifier, transparent_address) = match &rule.address {
+            None => (None, None),
+            Some(address) => match RecipientAddress::decode(&self.params, address) {
+                Some(RecipientAddress::Shielded(addr)) => (Some(addr.diversifier().0), None),
+                Some(RecipientAddress::Transparent(addr)) => {
+                    (None, Some(addr.encode(&self.params)))
+                }
+                Some(RecipientAddress::Unified(ua)) => (
+                    ua.sapling().map(|addr| addr.diversifier().0),
+                    ua.transparent().map(|addr| addr.encode(&self.params)),
+                ),
+                None => {
+                    return Err(ZcashError::Message {
+                        error: format!("Invalid address {}", address),
+                    })
+                }
+            },
+        };
+        if rule.address.is_some() && sapling_diversifier.is_none() && transparent_address.is_none()
+        {
+            return Err("The address has no receiver the wallet can be paid to".into());
+        }
+
+        let mut rules = self.rules.lock().unwrap();
+        let id = rules.keys().next_back().map_or(1, |id| id + 1);
+        rules.insert(
+            id,
+            Rule {
+                spec: rule,
+                sapling_diversifier,
+                transparent_address,
+            },
+        );
+        Ok(id)
+    }
+
+    pub fn remove_rule(&self, rule_id: u32) {
+        self.rules.lock().unwrap().remove(&rule_id);
+    }
+
+    /// Notifies the listener of each rule a payment received in the
+    /// `from_height..to_height` blocks matches, in the order of the blocks. Returns the
+    /// count of matches.
+    pub fn evaluate(
+        &self,
+        db: Arc<ZcashWalletDb>,
+        from_height: u32,
+        to_height: u32,
+    ) -> ZcashResult<u32> {
+        check_range("from_height..to_height", from_height, to_height)?;
+        let payments = received_payments(&db, from_height, to_height)?;
+
+        let matches: Vec<ZcashNotificationMatch> = {
+            let rules = self.rules.lock().unwrap();
+            payments
+                .iter()
+                .flat_map(|payment| {
+                    rules
+                        .iter()
+                        .filter(|(_, rule)| rule.matches(payment))
+                        .map(move |(&rule_id, _)| (rule_id, payment))
+                })
+                .map(|(rule_id, payment)| {
+                    Ok(ZcashNotificationMatch {
+                        rule_id,
+                        account: ZcashAccountId {
+                            id: payment.account,
+                        },
+                        txid: Arc::new(ZcashTxId::from_bytes(&payment.txid)?),
+                        height: payment.height,
+                        value: payment.value,
+                        memo: payment.memo.clone(),
+                    })
+                })
+                .collect::<ZcashResult<_>>()?
+        };
+
+        let count = matches.len() as u32;
+        for notification in matches {
+            self.listener.on_match(notification);
+        }
+        Ok(count)
+    }
+}
+
+fn received_payments(
+    db: &ZcashWalletDb,
+    from_height: u32,
+    to_height: u32,
+) -> ZcashResult<Vec<Payment>> {
+    let conn = Connection::open(&db.path)?;
+    let range = named_params![":from_height": from_height, ":to_height": to_height];
+
+    let mut notes = conn.prepare(
+        "SELECT n.account, n.diversifier, n.value, n.memo, t.txid, t.block
+        FROM sapling_received_notes n
+        JOIN transactions t ON t.id_tx = n.tx
+        WHERE n.is_change = 0
+        AND t.block >= :from_height AND t.block < :to_height
+        ORDER BY t.block, n.id_note",
+    )?;
+    let mut payments = notes
+        .query_map(range, |row| {
+            let memo: Option<Vec<u8>> = row.get(3)?;
+            Ok(Payment {
+                account: row.get(0)?,
+                sapling_diversifier: Some(row.get(1)?),
+                transparent_address: None,
+                value: row.get(2)?,
+                memo: memo.as_deref().and_then(text_memo),
+                txid: row.get(4)?,
+                height: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut outputs = conn.prepare(
+        "SELECT received_by_account, address, value_zat, prevout_txid, height
+        FROM utxos
+        WHERE height >= :from_height AND height < :to_height
+        ORDER BY height, id_utxo",
+    )?;
+    let transparent = outputs
+        .query_map(range, |row| {
+            Ok(Payment {
+                account: row.get(0)?,
+                sapling_diversifier: None,
+                transparent_address: Some(row.get(1)?),
+                value: row.get(2)?,
+                memo: None,
+                txid: row.get(3)?,
+                height: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    payments.extend(transparent);
+    payments.sort_by_key(|payment| payment.height);
+    Ok(payments)
+}
+
+fn text_memo(bytes: &[u8]) -> Option<String> {
+    let memo = MemoBytes::from_bytes(bytes).ok()?;
+    match Memo::try_from(memo).ok()? {
+        Memo::Text(text) => Some(text.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use rusqlite::{named_params, Connection, OptionalExtension};
+use zcash_client_backend::encoding::AddressCodec;
+use zcash_client_backend::wallet::WalletTransparentOutput;
+use zcash_primitives::consensus::BlockHeight;
+use zcash_primitives::legacy::{Script, TransparentAddress};
+use zcash_primitives::transaction::components::{Amount, OutPoint, TxOut};
+
+use crate::{
+    ZcashError, ZcashOutPoint, ZcashResult, ZcashTransparentAddress, ZcashTxId, ZcashWalletDb,
+    ZcashWalletTransparentOutput,
+};
+
+impl ZcashWalletDb {
+    /// Starts watching the pay-to-script-hash address of a redeem script, like a
+    /// multisig script shared with other cosigners. Its outputs are tracked apart from
+    /// the accounts of the wallet, and are never selected to fund its transactions.
+    ///
+    /// The wallet holds none of their keys: they are spent with a
+    /// [`ZcashP2shTransactionBuilder`], which the cosigners sign out of the wallet, and the
+    /// wallet is told with [`ZcashWalletDb::mark_p2sh_output_spent`].
+    ///
+    /// [`ZcashP2shTransactionBuilder`]: crate::ZcashP2shTransactionBuilder
+    ///
+    /// Returns the watched address.
+    pub fn watch_p2sh_script(
+        &self,
+        redeem_script: Vec<u8>,
+    ) -> ZcashResult<Arc<ZcashTransparentAddress>> {
+        let address = ZcashTransparentAddress::from_redeem_script(redeem_script.clone());
+        let taddr: TransparentAddress = address.into();
+
+        let conn = Connection::open(&self.path)?;
+        conn.execute(
+            "INSERT INTO uniffi_p2sh_scripts (address, redeem_script)
+            VALUES (:address, :redeem_script)
+            ON CONFLICT (address) DO NOTHING",
+            named_params![
+                ":address": taddr.encode(&self.params),
+                ":redeem_script": redeem_script,
+            ],
+        )?;
+
+        Ok(Arc::new(address))
+    }
+
+    /// Returns the addresses watched with [`ZcashWalletDb::watch_p2sh_script`].
+    pub fn get_watched_p2sh_addresses(&self) -> ZcashResult<Vec<Arc<ZcashTransparentAddress>>> {
+        let conn = Connection::open(&self.path)?;
+        let mut stmt = conn.prepare("SELECT address FROM uniffi_p2sh_scripts ORDER BY address")?;
+        let addresses = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        addresses
+            .iter()
+            .map(|address| Ok(Arc::new(self.decode_p2sh_address(address)?.into())))
+            .collect()
+    }
+
+    /// Returns the redeem script of a watched address, or `None` if it is not watched.
+    pub fn get_p2sh_redeem_script(
+        &self,
+        address: Arc<ZcashTransparentAddress>,
+    ) -> ZcashResult<Option<Vec<u8>>> {
+        let taddr: TransparentAddress = (*address).into();
+        let conn = Connection::open(&self.path)?;
+        Ok(conn
+            .query_row(
+                "SELECT redeem_script FROM uniffi_p2sh_scripts WHERE address = :address",
+                named_params![":address": taddr.encode(&self.params)],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Stores an output received by a watched address, as found by the app in
+    /// lightwalletd. Storing an output again updates its mined height.
+    pub fn put_received_p2sh_utxo(
+        &self,
+        output: Arc<ZcashWalletTransparentOutput>,
+    ) -> ZcashResult<()> {
+        let output = &output.0;
+        let address = output.recipient_address().encode(&self.params);
+
+        let conn = Connection::open(&self.path)?;
+        let watched = conn
+            .query_row(
+                "SELECT 1 FROM uniffi_p2sh_scripts WHERE address = :address",
+                named_params![":address": address],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if !watched {
+            return Err(ZcashError::Message {
+                error: format!("The address {} is not a watched P2SH address", address),
+            });
+        }
+
+        let mut script = Vec::new();
+        output.txout().script_pubkey.write(&mut script)?;
+        conn.execute(
+            "INSERT INTO uniffi_p2sh_utxos (
+                prevout_txid, prevout_idx, address, script, value_zat, height
+            )
+            VALUES (:prevout_txid, :prevout_idx, :address, :script, :value_zat, :height)
+            ON CONFLICT (prevout_txid, prevout_idx) DO UPDATE SET height = :height",
+            named_params![
+                ":prevout_txid": &output.outpoint().hash()[..],
+                ":prevout_idx": output.outpoint().n(),
+                ":address": address,
+                ":script": script,
+                ":value_zat": i64::from(output.txout().value),
+                ":height": u32::from(output.height()),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the outputs of a watched address mined at or below `max_height` that are
+    /// not marked as spent.
+    pub fn get_unspent_p2sh_outputs(
+        &self,
+        address: Arc<ZcashTransparentAddress>,
+        max_height: u32,
+    ) -> ZcashResult<Vec<Arc<ZcashWalletTransparentOutput>>> {
+        let taddr: TransparentAddress = (*address).into();
+        let conn = Connection::open(&self.path)?;
+        let mut stmt = conn.prepare(
+            "SELECT prevout_txid, prevout_idx, script, value_zat, height
+            FROM uniffi_p2sh_utxos
+            WHERE address = :address AND height <= :max_height AND spent_in_tx IS NULL
+            ORDER BY height, prevout_txid, prevout_idx",
+        )?;
+        let rows = stmt
+            .query_map(
+                named_params![":address": taddr.encode(&self.params), ":max_height": max_height],
+                |row| {
+                    Ok((
+                        row.get::<_, Vec<u8>>(0)?,
+                        row.get::<_, u32>(1)?,
+                        row.get::<_, Vec<u8>>(2)?,
+                        row.get::<_, i64>(3)?,
+                        row.get::<_, u32>(4)?,
+                    ))
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(txid, idx, script, value, height)| {
+                let txid: [u8; 32] = txid.try_into().map_err(|_| ZcashError::Message {
+                    error: "Invalid transaction id in the P2SH outputs".to_string(),
+                })?;
+                let txout = TxOut {
+                    value: Amount::from_i64(value).map_err(|_| ZcashError::Message {
+                        error: format!("Invalid value in the P2SH outputs: {}", value),
+                    })?,
+                    script_pubkey: Script::read(&script[..])?,
+                };
+                WalletTransparentOutput::from_parts(
+                    OutPoint::new(txid, idx),
+                    txout,
+                    BlockHeight::from_u32(height),
+                )
+                .map(|output| Arc::new(output.into()))
+                .ok_or_else(|| ZcashError::Message {
+                    error: "Invalid script in the P2SH outputs".to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Marks an output of a watched address as spent by a transaction, as one built with
+    /// a [`ZcashP2shTransactionBuilder`](crate::ZcashP2shTransactionBuilder).
+    pub fn mark_p2sh_output_spent(
+        &self,
+        outpoint: Arc<ZcashOutPoint>,
+        spent_in: Arc<ZcashTxId>,
+    ) -> ZcashResult<()> {
+        let outpoint: OutPoint = (*outpoint).clone().into();
+        let conn = Connection::open(&self.path)?;
+        let updated = conn.execute(
+            "UPDATE uniffi_p2sh_utxos SET spent_in_tx = :spent_in_tx
+            WHERE prevout_txid = :prevout_txid AND prevout_idx = :prevout_idx",
+            named_params![
+                ":spent_in_tx": spent_in.to_bytes()?,
+                ":prevout_txid": &outpoint.hash()[..],
+                ":prevout_idx": outpoint.n(),
+            ],
+        )?;
+        if updated == 0 {
+            return Err(ZcashError::Message {
+                error: "The output is not a known P2SH output".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Removes the outputs of the watched addresses mined above the given height, as
+    /// upstream does for the outputs of the wallet when it is rewound, so that those of
+    /// another chain are stored in their place. Whether they are spent is kept, as the
+    /// spending transactions are not tracked by the wallet.
+    pub(super) fn truncate_p2sh_utxos(&self, block_height: u32) -> ZcashResult<()> {
+        Connection::open(&self.path)?.execute(
+            "DELETE FROM uniffi_p2sh_utxos WHERE height > :height",
+            named_params![":height": block_height],
+        )?;
+        Ok(())
+    }
+
+    fn decode_p2sh_address(&self, address: &str) -> ZcashResult<TransparentAddress> {
+        TransparentAddress::decode(&self.params, address).map_err(|e| ZcashError::Message {
+            error: format!("Invalid address in the P2SH scripts: {}", e),
+        })
+    }
+}
@@ -0,0 +1,403 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use zcash_client_backend::data_api::wallet::input_selection::{GreedyInputSelector, Proposal};
+use zcash_client_backend::data_api::wallet::propose_transfer;
+use zcash_client_backend::data_api::WalletRead;
+use zcash_client_backend::fees::zip317::SingleOutputChangeStrategy;
+use zcash_client_backend::fees::{ChangeValue, DustOutputPolicy};
+use zcash_client_backend::keys::UnifiedSpendingKey;
+use zcash_client_sqlite::{ReceivedNoteId, WalletDb};
+use zcash_primitives::transaction::fees::zip317::FeeRule;
+
+use super::cast_err;
+use crate::{
+    builder_target_height, create_proposed_transaction_expiring, validation::non_zero,
+    ZcashAccountId, ZcashConsensusParameters, ZcashError, ZcashLocalTxProver, ZcashMemoBytes,
+    ZcashOvkPolicy, ZcashResult, ZcashTransactionRequest, ZcashTxId, ZcashUnifiedSpendingKey,
+    ZcashWalletDb, DEFAULT_TX_EXPIRY_DELTA,
+};
+
+/// The version of the serialized proposals, to be bumped on incompatible changes.
+const PROPOSAL_VERSION: u64 = 1;
+
+type ProposalSelector =
+    GreedyInputSelector<WalletDb<Connection, ZcashConsensusParameters>, SingleOutputChangeStrategy>;
+
+/// Approves the transactions the wallet is about to sign, like after asking the user for a
+/// second factor, so apps can enforce it however the spend was requested.
+pub trait ZcashSpendApprover: Send + Sync {
+    /// Whether the transaction of the proposal can be signed.
+    fn approve(&self, proposal: Arc<ZcashProposal>) -> bool;
+}
+
+/// A Sapling note spent by a proposal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZcashProposalSaplingInput {
+    pub value: u64,
+    /// The position of the note in the note commitment tree, which identifies it.
+    pub position: u64,
+}
+
+/// A transaction the wallet proposes to make, with the notes it spends, the fee and the
+/// change, for it to be reviewed before being created. Proposals can be serialized, so
+/// they can be made on one machine, approved on others and created later, by the wallet
+/// they were made for.
+pub struct ZcashProposal {
+    params: ZcashConsensusParameters,
+    account: u32,
+    request: String,
+    min_confirmations: u32,
+    min_target_height: u32,
+    min_anchor_height: u32,
+    expiry_delta: u32,
+    sapling_inputs: Vec<ZcashProposalSaplingInput>,
+    fee: u64,
+    change: Vec<u64>,
+}
+
+impl ZcashProposal {
+    /// Reconstructs a proposal serialized with [`ZcashProposal::to_json`].
+    pub fn from_json(json: String) -> ZcashResult<Self> {
+        let value: Value = serde_json::from_str(&json).map_err(|e| ZcashError::Message {
+            error: format!("Invalid proposal: {}", e),
+        })?;
+        let field = |name: &str| {
+            value.get(name).ok_or_else(|| ZcashError::Message {
+                error: format!("Invalid proposal: missing {}", name),
+            })
+        };
+        let invalid = |name: &str| ZcashError::Message {
+            error: format!("Invalid proposal: invalid {}", name),
+        };
+        let u64_field = |name: &str| field(name)?.as_u64().ok_or_else(|| invalid(name));
+        let u32_field =
+            |name: &str| u64_field(name).and_then(|v| u32::try_from(v).map_err(|_| invalid(name)));
+
+        if u64_field("version")? != PROPOSAL_VERSION {
+            return Err("Unsupported proposal version".into());
+        }
+        let params = match field("network")?.as_str() {
+            Some("main") => ZcashConsensusParameters::MainNetwork,
+            Some("test") => ZcashConsensusParameters::TestNetwork,
+            _ => return Err(invalid("network")),
+        };
+        let request = field("request")?
+            .as_str()
+            .ok_or_else(|| invalid("request"))?
+            .to_string();
+        ZcashTransactionRequest::from_uri(params, &request).map_err(|_| invalid("request"))?;
+        let sapling_inputs = field("sapling_inputs")?
+            .as_array()
+            .ok_or_else(|| invalid("sapling_inputs"))?
+            .iter()
+            .map(|input| {
+                let value = input.get("value").and_then(Value::as_u64);
+                let position = input.get("position").and_then(Value::as_u64);
+                match (value, position) {
+                    (Some(value), Some(position)) => {
+                        Ok(ZcashProposalSaplingInput { value, position })
+                    }
+                    _ => Err(invalid("sapling_inputs")),
+                }
+            })
+            .collect::<ZcashResult<Vec<_>>>()?;
+        let change = field("change")?
+            .as_array()
+            .ok_or_else(|| invalid("change"))?
+            .iter()
+            .map(|value| value.as_u64().ok_or_else(|| invalid("change")))
+            .collect::<ZcashResult<Vec<_>>>()?;
+
+        let min_target_height = u32_field("min_target_height")?;
+        // Proposals serialized before the expiry delta was chosen have the default one.
+        let expiry_delta = match value.get("expiry_delta") {
+            Some(_) => u32_field("expiry_delta")?,
+            None => DEFAULT_TX_EXPIRY_DELTA,
+        };
+        builder_target_height(params, min_target_height.into(), expiry_delta)?;
+
+        Ok(ZcashProposal {
+            params,
+            account: u32_field("account")?,
+            request,
+            min_confirmations: u32_field("min_confirmations")?,
+            min_target_height,
+            min_anchor_height: u32_field("min_anchor_height")?,
+            expiry_delta,
+            sapling_inputs,
+            fee: u64_field("fee")?,
+            change,
+        })
+    }
+
+    /// Reconstructs a proposal serialized with [`ZcashProposal::to_bytes`].
+    pub fn from_bytes(bytes: Vec<u8>) -> ZcashResult<Self> {
+        let json = String::from_utf8(bytes).map_err(|_| "Invalid proposal: not UTF-8")?;
+        Self::from_json(json)
+    }
+
+    /// Serializes the proposal as JSON, which approvers can read as is.
+    pub fn to_json(&self) -> String {
+        json!({
+            "version": PROPOSAL_VERSION,
+            "network": match self.params {
+                ZcashConsensusParameters::MainNetwork => "main",
+                ZcashConsensusParameters::TestNetwork => "test",
+            },
+            "account": self.account,
+            "request": self.request,
+            "min_confirmations": self.min_confirmations,
+            "min_target_height": self.min_target_height,
+            "min_anchor_height": self.min_anchor_height,
+            "expiry_delta": self.expiry_delta,
+            "sapling_inputs": self.sapling_inputs.iter().map(|input| json!({
+                "value": input.value,
+                "position": input.position,
+            })).collect::<Vec<_>>(),
+            "fee": self.fee,
+            "change": self.change,
+        })
+        .to_string()
+    }
+
+    /// Serializes the proposal as bytes, which are those of its JSON in UTF-8.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_json().into_bytes()
+    }
+
+    pub fn account(&self) -> ZcashAccountId {
+        ZcashAccountId { id: self.account }
+    }
+
+    /// The payments the transaction makes.
+    pub fn transaction_request(&self) -> ZcashResult<Arc<ZcashTransactionRequest>> {
+        ZcashTransactionRequest::from_uri(self.params, &self.request)
+            .map(Arc::new)
+            .map_err(|e| ZcashError::Message {
+                error: format!("Invalid proposal request: {:?}", e),
+            })
+    }
+
+    pub fn min_confirmations(&self) -> u32 {
+        self.min_confirmations
+    }
+
+    /// The height the transaction is made for, at the time of the proposal.
+    pub fn min_target_height(&self) -> u32 {
+        self.min_target_height
+    }
+
+    /// How many blocks after its target height the transaction expires, the
+    /// [`DEFAULT_TX_EXPIRY_DELTA`] unless another one was given to
+    /// [`ZcashWalletDb::propose_transfer`].
+    pub fn expiry_delta(&self) -> u32 {
+        self.expiry_delta
+    }
+
+    /// The height the transaction expires at, when created at the
+    /// [`ZcashProposal::min_target_height`].
+    pub fn expiry_height(&self) -> u32 {
+        self.min_target_height.saturating_add(self.expiry_delta)
+    }
+
+    /// The height of the note commitment tree the spends are proven against, at the time
+    /// of the proposal.
+    pub fn min_anchor_height(&self) -> u32 {
+        self.min_anchor_height
+    }
+
+    pub fn sapling_inputs(&self) -> Vec<ZcashProposalSaplingInput> {
+        self.sapling_inputs.clone()
+    }
+
+    pub fn fee(&self) -> u64 {
+        self.fee
+    }
+
+    /// The values paid back to the account.
+    pub fn change(&self) -> Vec<u64> {
+        self.change.clone()
+    }
+
+    pub(crate) fn from_upstream<FeeRuleT>(
+        params: ZcashConsensusParameters,
+        account: u32,
+        min_confirmations: u32,
+        expiry_delta: u32,
+        proposal: &Proposal<FeeRuleT, ReceivedNoteId>,
+    ) -> ZcashResult<Self> {
+        let request = proposal
+            .transaction_request()
+            .to_uri(&params)
+            .ok_or("The proposal pays nothing")?;
+        let balance = proposal.balance();
+        Ok(ZcashProposal {
+            params,
+            account,
+            request,
+            min_confirmations,
+            min_target_height: proposal.min_target_height().into(),
+            min_anchor_height: proposal.min_anchor_height().into(),
+            expiry_delta,
+            sapling_inputs: proposal
+                .sapling_inputs()
+                .iter()
+                .map(|note| ZcashProposalSaplingInput {
+                    value: i64::from(note.note_value) as u64,
+                    position: note.note_commitment_tree_position.into(),
+                })
+                .collect(),
+            fee: u64::from(balance.fee_required()),
+            change: balance
+                .proposed_change()
+                .iter()
+                .map(|change| match change {
+                    ChangeValue::Sapling(value) => u64::from(*value),
+                })
+                .collect(),
+        })
+    }
+
+    /// Whether both proposals make the same transaction, besides the heights it is made for.
+    fn same_transaction(&self, other: &ZcashProposal) -> bool {
+        self.account == other.account
+            && self.request == other.request
+            && self.sapling_inputs == other.sapling_inputs
+            && self.fee == other.fee
+            && self.change == other.change
+    }
+}
+
+impl ZcashWalletDb {
+    /// Proposes a transaction of the account making the requested payments, paying the
+    /// ZIP 317 fee, to expire `expiry_delta` blocks after its target height, by default the
+    /// [`DEFAULT_TX_EXPIRY_DELTA`]. Nothing is stored, the transaction is made with
+    /// [`ZcashWalletDb::create_proposed_transaction`].
+    pub fn propose_transfer(
+        &self,
+        account: ZcashAccountId,
+        request: Arc<ZcashTransactionRequest>,
+        min_confirmations: u32,
+        expiry_delta: Option<u32>,
+    ) -> ZcashResult<Arc<ZcashProposal>> {
+        let expiry_delta = expiry_delta.unwrap_or(DEFAULT_TX_EXPIRY_DELTA);
+        let mut db = WalletDb::for_path(&self.path, self.params)?;
+        let proposal = self.propose(&mut db, account.id, request.as_ref(), min_confirmations)?;
+        builder_target_height(self.params, proposal.min_target_height(), expiry_delta)?;
+        ZcashProposal::from_upstream(
+            self.params,
+            account.id,
+            min_confirmations,
+            expiry_delta,
+            &proposal,
+        )
+        .map(Arc::new)
+    }
+
+    /// Creates and stores the transaction of the proposal, with `change_memo` in its change
+    /// output, expiring the [`ZcashProposal::expiry_delta`] blocks after its target height.
+    /// The wallet must still make the same transaction as when the proposal was made,
+    /// spending the same notes for the same fee, so that what was approved is what is
+    /// created: otherwise, as when some of the notes were spent since, it fails and a new
+    /// proposal has to be made.
+    ///
+    /// The `approver` is asked right before signing, with the proposal as it is then, and
+    /// it fails with [`ZcashError::SpendNotApproved`] if it declines.
+    pub fn create_proposed_transaction(
+        &self,
+        proposal: Arc<ZcashProposal>,
+        usk: Arc<ZcashUnifiedSpendingKey>,
+        prover: Arc<ZcashLocalTxProver>,
+        ovk_policy: ZcashOvkPolicy,
+        change_memo: Option<Arc<ZcashMemoBytes>>,
+        approver: Option<Box<dyn ZcashSpendApprover>>,
+    ) -> ZcashResult<Arc<ZcashTxId>> {
+        let usk: UnifiedSpendingKey = (*usk).clone().into();
+        let mut db = WalletDb::for_path(&self.path, self.params)?;
+        let account = db
+            .get_account_for_ufvk(&usk.to_unified_full_viewing_key())
+            .map_err(cast_err)?;
+        if account.map(u32::from) != Some(proposal.account) {
+            return Err("The spending key is not of the account of the proposal".into());
+        }
+
+        let (current, current_summary) = self.propose_again(&mut db, &proposal)?;
+        if let Some(approver) = approver {
+            if !approver.approve(Arc::new(current_summary)) {
+                return Err(ZcashError::SpendNotApproved);
+            }
+        }
+
+        create_proposed_transaction_expiring(
+            &mut db,
+            self.params,
+            (*prover).clone(),
+            &usk,
+            ovk_policy.into(),
+            &current,
+            non_zero(proposal.min_confirmations)?,
+            change_memo.map(|memo| memo.as_ref().into()),
+            proposal.expiry_delta,
+        )
+        .map(|txid| Arc::new(txid.into()))
+    }
+
+    /// Proposes the transaction of the proposal again, as the wallet is now, failing if it
+    /// would not be the same transaction anymore.
+    pub(super) fn propose_again(
+        &self,
+        db: &mut WalletDb<Connection, ZcashConsensusParameters>,
+        proposal: &ZcashProposal,
+    ) -> ZcashResult<(Proposal<FeeRule, ReceivedNoteId>, ZcashProposal)> {
+        if self.params != proposal.params {
+            return Err("The proposal is for another network".into());
+        }
+        let request = proposal.transaction_request()?;
+        let current = self.propose(
+            db,
+            proposal.account,
+            request.as_ref(),
+            proposal.min_confirmations,
+        )?;
+        let current_summary = ZcashProposal::from_upstream(
+            self.params,
+            proposal.account,
+            proposal.min_confirmations,
+            proposal.expiry_delta,
+            &current,
+        )?;
+        if !proposal.same_transaction(&current_summary) {
+            return Err("The wallet changed since the proposal was made".into());
+        }
+        Ok((current, current_summary))
+    }
+
+    fn propose(
+        &self,
+        db: &mut WalletDb<Connection, ZcashConsensusParameters>,
+        account: u32,
+        request: &ZcashTransactionRequest,
+        min_confirmations: u32,
+    ) -> ZcashResult<Proposal<FeeRule, ReceivedNoteId>> {
+        let selector: ProposalSelector = GreedyInputSelector::new(
+            SingleOutputChangeStrategy::new(FeeRule::standard()),
+            DustOutputPolicy::default(),
+        );
+        let proposal = propose_transfer::<_, _, _, Infallible>(
+            db,
+            &self.params,
+            account.into(),
+            &selector,
+            request.clone().into(),
+            non_zero(min_confirmations)?,
+        )
+        .map_err(|e| ZcashError::Message {
+            error: format!("Cannot propose the transfer: {:?}", e),
+        })?;
+        self.check_spending_limits(account, &proposal)?;
+        Ok(proposal)
+    }
+}
@@ -0,0 +1,120 @@
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params_from_iter, Connection, ErrorCode, OpenFlags};
+
+use crate::{ZcashError, ZcashResult, ZcashWalletDb};
+
+/// How long [`ZcashWalletDb::query_readonly`] queries may run before being interrupted.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A value of an SQL query parameter or result column.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ZcashSqlValue {
+    Null,
+    Integer { v: i64 },
+    Real { v: f64 },
+    Text { v: String },
+    Blob { v: Vec<u8> },
+}
+
+impl From<ZcashSqlValue> for Value {
+    fn from(value: ZcashSqlValue) -> Self {
+        match value {
+            ZcashSqlValue::Null => Value::Null,
+            ZcashSqlValue::Integer { v } => Value::Integer(v),
+            ZcashSqlValue::Real { v } => Value::Real(v),
+            ZcashSqlValue::Text { v } => Value::Text(v),
+            ZcashSqlValue::Blob { v } => Value::Blob(v),
+        }
+    }
+}
+
+impl From<ValueRef<'_>> for ZcashSqlValue {
+    fn from(value: ValueRef<'_>) -> Self {
+        match value {
+            ValueRef::Null => ZcashSqlValue::Null,
+            ValueRef::Integer(v) => ZcashSqlValue::Integer { v },
+            ValueRef::Real(v) => ZcashSqlValue::Real { v },
+            ValueRef::Text(v) => ZcashSqlValue::Text {
+                v: String::from_utf8_lossy(v).into_owned(),
+            },
+            ValueRef::Blob(v) => ZcashSqlValue::Blob { v: v.to_vec() },
+        }
+    }
+}
+
+/// The rows of a [`ZcashWalletDb::query_readonly`] query, with their values in the order
+/// of the columns.
+pub struct ZcashQueryResult {
+    /// The names of the columns, in the order of the `SELECT`. Several columns may have
+    /// the same name.
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<ZcashSqlValue>>,
+}
+
+impl ZcashWalletDb {
+    /// Runs a single `SELECT` query on the wallet database, binding the positional
+    /// parameters to it, and returns its columns and rows. This lets power users answer
+    /// one-off questions the API has no method for.
+    ///
+    /// The database is opened read-only and statements writing to it are refused, so the
+    /// wallet cannot be altered. Queries running for more than [`QUERY_TIMEOUT`] are
+    /// interrupted. The schema is the one of `zcash_client_sqlite`, which may change
+    /// between versions.
+    pub fn query_readonly(
+        &self,
+        sql: String,
+        params: Vec<ZcashSqlValue>,
+    ) -> ZcashResult<ZcashQueryResult> {
+        let conn = Connection::open_with_flags(
+            &self.path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        let mut stmt = conn.prepare(&sql)?;
+        if !stmt.readonly() || stmt.column_count() == 0 {
+            return Err("Only SELECT queries are allowed".into());
+        }
+        let columns: Vec<String> = stmt.column_names().into_iter().map(From::from).collect();
+
+        let interrupt = conn.get_interrupt_handle();
+        let (done, timer) = mpsc::channel::<()>();
+        thread::spawn(move || {
+            if let Err(RecvTimeoutError::Timeout) = timer.recv_timeout(QUERY_TIMEOUT) {
+                interrupt.interrupt();
+            }
+        });
+
+        let mut rows = stmt.query(params_from_iter(params.into_iter().map(Value::from)))?;
+        let mut result = vec![];
+        loop {
+            let row = match rows.next() {
+                Ok(Some(row)) => row,
+                Ok(None) => break,
+                Err(rusqlite::Error::SqliteFailure(e, _))
+                    if e.code == ErrorCode::OperationInterrupted =>
+                {
+                    return Err(ZcashError::Message {
+                        error: format!(
+                            "The query took more than {} seconds",
+                            QUERY_TIMEOUT.as_secs()
+                        ),
+                    })
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let values = (0..columns.len())
+                .map(|i| Ok(row.get_ref(i)?.into()))
+                .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+            result.push(values);
+        }
+        drop(done);
+
+        Ok(ZcashQueryResult {
+            columns,
+            rows: result,
+        })
+    }
+}
@@ -0,0 +1,142 @@
+use std::ops::Range;
+
+use rusqlite::{named_params, Connection, Transaction};
+
+// The codes of the scan priorities in the upstream `scan_queue` table.
+pub(super) const PRIORITY_IGNORED: i64 = 0;
+pub(super) const PRIORITY_SCANNED: i64 = 10;
+pub(super) const PRIORITY_HISTORIC: i64 = 20;
+
+/// The name of a scan priority code, as upstream names the priorities.
+pub(super) fn priority_name(code: i64) -> &'static str {
+    match code {
+        0 => "Ignored",
+        10 => "Scanned",
+        20 => "Historic",
+        30 => "OpenAdjacent",
+        40 => "FoundNote",
+        50 => "ChainTip",
+        60 => "Verify",
+        _ => "Unknown",
+    }
+}
+
+/// Gives a range of blocks the given scan priority. Scan queue ranges never overlap, so
+/// the ranges around the new one are cut to its bounds, keeping their priorities.
+pub(super) fn replace_queue_range(
+    tx: &Transaction,
+    range: Range<u32>,
+    priority: i64,
+) -> rusqlite::Result<()> {
+    let overlapping = tx
+        .prepare(
+            "SELECT block_range_start, block_range_end, priority FROM scan_queue
+            WHERE block_range_start < :end AND block_range_end > :start",
+        )?
+        .query_map(
+            named_params![":start": range.start, ":end": range.end],
+            |row| {
+                Ok((
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, u32>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    tx.execute(
+        "DELETE FROM scan_queue
+        WHERE block_range_start < :end AND block_range_end > :start",
+        named_params![":start": range.start, ":end": range.end],
+    )?;
+
+    let mut ranges = vec![(range.start, range.end, priority)];
+    for (start, end, priority) in overlapping {
+        if start < range.start {
+            ranges.push((start, range.start, priority));
+        }
+        if end > range.end {
+            ranges.push((range.end, end, priority));
+        }
+    }
+    for (start, end, priority) in ranges {
+        tx.execute(
+            "INSERT INTO scan_queue (block_range_start, block_range_end, priority)
+            VALUES (:start, :end, :priority)",
+            named_params![":start": start, ":end": end, ":priority": priority],
+        )?;
+    }
+    Ok(())
+}
+
+/// Gives the blocks of a range at least the given scan priority: the parts of the range
+/// which are not queued or have a lower priority get it, the others keep theirs.
+pub(super) fn raise_queue_range(
+    tx: &Transaction,
+    range: Range<u32>,
+    priority: i64,
+) -> rusqlite::Result<()> {
+    let mut raised = vec![];
+    let mut start = range.start;
+    for (queued_start, queued_end, queued_priority) in queue_ranges(tx, range.clone())? {
+        if queued_start > start {
+            raised.push(start..queued_start);
+        }
+        if queued_priority < priority {
+            raised.push(queued_start..queued_end);
+        }
+        start = queued_end;
+    }
+    if start < range.end {
+        raised.push(start..range.end);
+    }
+    for range in raised {
+        replace_queue_range(tx, range, priority)?;
+    }
+    Ok(())
+}
+
+/// The scan queue ranges within a range, cut to its bounds, ordered by height.
+pub(super) fn queue_ranges(
+    conn: &Connection,
+    range: Range<u32>,
+) -> rusqlite::Result<Vec<(u32, u32, i64)>> {
+    conn.prepare(
+        "SELECT MAX(block_range_start, :start), MIN(block_range_end, :end), priority
+        FROM scan_queue
+        WHERE block_range_start < :end AND block_range_end > :start
+        ORDER BY block_range_start",
+    )?
+    .query_map(
+        named_params![":start": range.start, ":end": range.end],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?
+    .collect()
+}
+
+/// Puts back the scan queue of a range as returned by [`queue_ranges`], undoing the
+/// changes made to it since.
+pub(super) fn restore_queue_range(
+    tx: &Transaction,
+    range: Range<u32>,
+    ranges: &[(u32, u32, i64)],
+) -> rusqlite::Result<()> {
+    if range.is_empty() {
+        return Ok(());
+    }
+    // Cuts the ranges around it, then leaves the range out of the queue.
+    replace_queue_range(tx, range.clone(), PRIORITY_IGNORED)?;
+    tx.execute(
+        "DELETE FROM scan_queue WHERE block_range_start = :start AND block_range_end = :end",
+        named_params![":start": range.start, ":end": range.end],
+    )?;
+    for (start, end, priority) in ranges {
+        tx.execute(
+            "INSERT INTO scan_queue (block_range_start, block_range_end, priority)
+            VALUES (:start, :end, :priority)",
+            named_params![":start": start, ":end": end, ":priority": priority],
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use rusqlite::{named_params, Connection};
+use zcash_primitives::memo::MemoBytes;
+use zcash_primitives::transaction::TxId;
+
+use crate::{ZcashMemoBytes, ZcashResult, ZcashTxId, ZcashWalletDb};
+
+impl ZcashWalletDb {
+    /// Returns the tags of the self-memos in the change outputs of the transaction, as
+    /// put there by [`ZcashMemoBytes::self_memo`], in the order of the outputs. Memos are
+    /// not in compact blocks, so the wallet only has them for transactions it created
+    /// or decrypted and stored in full.
+    pub fn get_self_memo_tags(&self, txid: Arc<ZcashTxId>) -> ZcashResult<Vec<String>> {
+        let txid: TxId = (*txid).into();
+        let conn = Connection::open(&self.path)?;
+        let memos = conn
+            .prepare(
+                "SELECT n.memo FROM sapling_received_notes n
+                JOIN transactions t ON t.id_tx = n.tx
+                WHERE t.txid = :txid AND n.is_change = 1 AND n.memo IS NOT NULL
+                ORDER BY n.output_index",
+            )?
+            .query_map(named_params![":txid": &txid.as_ref()[..]], |row| {
+                row.get::<_, Vec<u8>>(0)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(memos
+            .iter()
+            .filter_map(|memo| MemoBytes::from_bytes(memo).ok())
+            .filter_map(|memo| ZcashMemoBytes::from(memo).self_memo_tag())
+            .collect())
+    }
+}
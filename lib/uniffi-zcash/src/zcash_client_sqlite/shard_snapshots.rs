@@ -0,0 +1,85 @@
+use std::io::Read;
+
+use rusqlite::Connection;
+use zcash_client_backend::data_api::{chain::CommitmentTreeRoot, WalletCommitmentTrees};
+use zcash_client_sqlite::WalletDb;
+use zcash_primitives::{consensus::BlockHeight, merkle_tree::HashSer, sapling};
+
+use crate::{validation::invalid_argument, ZcashError, ZcashResult, ZcashWalletDb};
+
+impl ZcashWalletDb {
+    /// Imports the roots of complete Sapling shards, the subtrees of 2^16 note commitments,
+    /// from a snapshot, as [`ZcashWalletDb::export_sapling_shards`] writes them. This lets
+    /// wallets bootstrap their note commitment tree from a trusted distribution channel,
+    /// rather than fetching the subtree roots from `lightwalletd`. Returns how many roots
+    /// were imported.
+    ///
+    /// The snapshot is the index of its first shard, as a little endian `u64`, followed by
+    /// the shards in order, each one its 32 bytes root and the height of the block which
+    /// completed it, as a little endian `u32`.
+    pub fn import_sapling_shards(&self, serialized_shards: Vec<u8>) -> ZcashResult<u32> {
+        let (start_index, roots) = read_shards(&serialized_shards)
+            .map_err(|e| invalid_argument("serialized_shards", e.to_string()))?;
+
+        WalletDb::for_path(&self.path, self.params)
+            .expect("Cannot access the DB!")
+            .put_sapling_subtree_roots(start_index, &roots)
+            .map_err(|e| ZcashError::Message {
+                error: format!("ShardTreeError: {:?}", e),
+            })?;
+        Ok(roots.len() as u32)
+    }
+
+    /// Exports the roots of the complete Sapling shards of the wallet, from the first one
+    /// up to the first which is not complete yet, in the format
+    /// [`ZcashWalletDb::import_sapling_shards`] reads, for distributing them to other
+    /// wallets.
+    pub fn export_sapling_shards(&self) -> ZcashResult<Vec<u8>> {
+        let conn = Connection::open(&self.path)?;
+        let mut stmt = conn.prepare(
+            "SELECT shard_index, subtree_end_height, root_hash FROM sapling_tree_shards
+            WHERE root_hash IS NOT NULL AND subtree_end_height IS NOT NULL
+            ORDER BY shard_index",
+        )?;
+        let shards = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, u64>(0)?,
+                    row.get::<_, u32>(1)?,
+                    row.get::<_, Vec<u8>>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let start_index = shards.first().map_or(0, |(index, _, _)| *index);
+        let mut snapshot = start_index.to_le_bytes().to_vec();
+        for (i, (index, end_height, root_hash)) in shards.iter().enumerate() {
+            if *index != start_index + i as u64 {
+                break;
+            }
+            snapshot.extend_from_slice(root_hash);
+            snapshot.extend_from_slice(&end_height.to_le_bytes());
+        }
+        Ok(snapshot)
+    }
+}
+
+fn read_shards(
+    mut reader: &[u8],
+) -> std::io::Result<(u64, Vec<CommitmentTreeRoot<sapling::Node>>)> {
+    let mut start_index = [0u8; 8];
+    reader.read_exact(&mut start_index)?;
+    let mut roots = vec![];
+    while !reader.is_empty() {
+        let mut root_hash = [0u8; 32];
+        reader.read_exact(&mut root_hash)?;
+        let root_hash = sapling::Node::read(&root_hash[..])?;
+        let mut end_height = [0u8; 4];
+        reader.read_exact(&mut end_height)?;
+        roots.push(CommitmentTreeRoot::from_parts(
+            BlockHeight::from_u32(u32::from_le_bytes(end_height)),
+            root_hash,
+        ));
+    }
+    Ok((u64::from_le_bytes(start_index), roots))
+}
@@ -0,0 +1,67 @@
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+use zcash_client_backend::data_api::wallet::input_selection::{
+    GreedyInputSelector, InputSelector, InputSelectorError,
+};
+use zcash_client_backend::data_api::WalletRead;
+use zcash_client_backend::fees::zip317::SingleOutputChangeStrategy;
+use zcash_client_backend::fees::DustOutputPolicy;
+use zcash_client_sqlite::WalletDb;
+use zcash_primitives::legacy::TransparentAddress;
+use zcash_primitives::transaction::components::amount::NonNegativeAmount;
+use zcash_primitives::transaction::fees::zip317::FeeRule;
+
+use super::cast_err;
+use crate::{ZcashAccountId, ZcashError, ZcashResult, ZcashWalletDb, ZcashWalletTransparentOutput};
+
+impl ZcashWalletDb {
+    /// Returns the transparent outputs of the account that shielding its funds would
+    /// spend, so auto-shielding screens can show them beforehand. They are selected as
+    /// the `shield_transparent_funds_*_zip317` functions do with the standard ZIP 317
+    /// fee rule: the outputs with enough confirmations, leaving out those worth less than
+    /// the fee to spend them.
+    ///
+    /// Returns none when their value, net of the fee, is below `shielding_threshold`, as
+    /// then nothing would be shielded.
+    pub fn get_shielding_candidates(
+        &self,
+        account: ZcashAccountId,
+        shielding_threshold: u64,
+        min_confirmations: u32,
+    ) -> ZcashResult<Vec<Arc<ZcashWalletTransparentOutput>>> {
+        let shielding_threshold = NonNegativeAmount::from_u64(shielding_threshold)
+            .map_err(|_| "Invalid shielding threshold")?;
+        let min_confirmations =
+            NonZeroU32::new(min_confirmations).ok_or("At least one confirmation is required")?;
+        let db = WalletDb::for_path(&self.path, self.params)?;
+
+        let addresses: Vec<TransparentAddress> = db
+            .get_transparent_receivers(account.into())
+            .map_err(cast_err)?
+            .into_keys()
+            .collect();
+        let input_selector = GreedyInputSelector::<WalletDb<_, _>, _>::new(
+            SingleOutputChangeStrategy::new(FeeRule::standard()),
+            DustOutputPolicy::default(),
+        );
+
+        match input_selector.propose_shielding(
+            &self.params,
+            &db,
+            shielding_threshold,
+            &addresses,
+            min_confirmations,
+        ) {
+            Ok(proposal) => Ok(proposal
+                .transparent_inputs()
+                .iter()
+                .map(|output| Arc::new(output.clone().into()))
+                .collect()),
+            Err(InputSelectorError::InsufficientFunds { .. }) => Ok(vec![]),
+            Err(e) => Err(ZcashError::Message {
+                error: format!("Shielding selection error: {}", e),
+            }),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use zcash_client_backend::data_api::{WalletCommitmentTrees, WalletRead};
+use zcash_client_sqlite::WalletDb;
+use zcash_primitives::merkle_tree::HashSer;
+use zcash_primitives::sapling::{self, Rseed};
+use zcash_primitives::zip32::AccountId;
+
+use super::cast_err;
+use crate::{
+    validation::non_zero, ZcashDeviceDerivation, ZcashError, ZcashProposal, ZcashResult,
+    ZcashWalletDb,
+};
+
+/// The version of the serialized signing payloads, to be bumped on incompatible changes.
+const SIGNING_PAYLOAD_VERSION: u64 = 1;
+
+/// A Sapling note spent by a signing payload, with what an external signer needs to
+/// spend it besides the keys of the account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZcashSigningSaplingSpend {
+    pub value: u64,
+    pub position: u64,
+    /// The diversifier of the address the note was received at.
+    pub diversifier: Vec<u8>,
+    /// The `rseed` of the note after ZIP 212, or its `rcm`.
+    pub rseed: Vec<u8>,
+    /// Whether `rseed` is the `rseed` of the note rather than its `rcm`. It never is for
+    /// the notes found by scanning, as the wallet only keeps their `rcm`.
+    pub zip212: bool,
+    /// Whether the note was received at an internal address of the account, as change is,
+    /// so that it is spent with the internal spending key.
+    pub internal: bool,
+    /// The Merkle path of the note commitment to the anchor, from the leaf up.
+    pub merkle_path: Vec<Vec<u8>>,
+}
+
+/// What an external signer, like a hardware wallet, needs to create and sign the
+/// transaction of a proposal of an account whose spending keys it holds: the proposal, the
+/// notes it spends with their Merkle paths, and the derivation of the account in the
+/// device. It is serialized as JSON with [`ZcashSigningPayload::to_json`].
+pub struct ZcashSigningPayload {
+    proposal: Arc<ZcashProposal>,
+    derivation: Option<ZcashDeviceDerivation>,
+    anchor: Vec<u8>,
+    sapling_spends: Vec<ZcashSigningSaplingSpend>,
+}
+
+impl ZcashSigningPayload {
+    pub fn proposal(&self) -> Arc<ZcashProposal> {
+        self.proposal.clone()
+    }
+
+    /// The derivation of the account in its device, `None` if it was not imported with
+    /// [`ZcashWalletDb::import_account_ufvk`].
+    pub fn derivation(&self) -> Option<ZcashDeviceDerivation> {
+        self.derivation.clone()
+    }
+
+    /// The root of the note commitment tree the spends are proven against.
+    pub fn anchor(&self) -> Vec<u8> {
+        self.anchor.clone()
+    }
+
+    pub fn sapling_spends(&self) -> Vec<ZcashSigningSaplingSpend> {
+        self.sapling_spends.clone()
+    }
+
+    /// Serializes the payload as JSON, with the bytes hex encoded and the proposal as
+    /// [`ZcashProposal::to_json`] serializes it.
+    pub fn to_json(&self) -> String {
+        let proposal: Value =
+            serde_json::from_str(&self.proposal.to_json()).expect("proposals are valid JSON");
+        json!({
+            "version": SIGNING_PAYLOAD_VERSION,
+            "proposal": proposal,
+            "derivation": self.derivation.as_ref().map(|derivation| json!({
+                "device": derivation.device,
+                "derivation_path": derivation.derivation_path,
+                "seed_fingerprint": derivation.seed_fingerprint.as_ref().map(hex::encode),
+            })),
+            "anchor": hex::encode(&self.anchor),
+            "sapling_spends": self.sapling_spends.iter().map(|spend| json!({
+                "value": spend.value,
+                "position": spend.position,
+                "diversifier": hex::encode(&spend.diversifier),
+                "rseed": hex::encode(&spend.rseed),
+                "zip212": spend.zip212,
+                "internal": spend.internal,
+                "merkle_path": spend.merkle_path.iter().map(hex::encode).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })
+        .to_string()
+    }
+}
+
+impl ZcashWalletDb {
+    /// Makes the payload an external signer needs to create the transaction of the
+    /// proposal, for accounts imported with [`ZcashWalletDb::import_account_ufvk`]. As when
+    /// creating the transaction, the wallet must still make the same one as when the
+    /// proposal was made, otherwise a new proposal has to be made.
+    pub fn create_signing_payload(
+        &self,
+        proposal: Arc<ZcashProposal>,
+    ) -> ZcashResult<Arc<ZcashSigningPayload>> {
+        let mut db = WalletDb::for_path(&self.path, self.params)?;
+        let (current, _) = self.propose_again(&mut db, &proposal)?;
+
+        let account = proposal.account().id;
+        let dfvk = db
+            .get_unified_full_viewing_keys()
+            .map_err(cast_err)?
+            .remove(&AccountId::from(account))
+            .and_then(|ufvk| ufvk.sapling().cloned())
+            .ok_or_else(|| ZcashError::Message {
+                error: format!("The account {} has no Sapling key", account),
+            })?;
+        let checkpoint_depth = db
+            .get_checkpoint_depth(non_zero(proposal.min_confirmations())?)
+            .map_err(tree_err)?;
+
+        let (anchor, merkle_paths) = db
+            .with_sapling_tree_mut(|tree| {
+                tree.root_at_checkpoint(checkpoint_depth)
+                    .and_then(|anchor| {
+                        current
+                            .sapling_inputs()
+                            .iter()
+                            .map(|input| {
+                                tree.witness_caching(
+                                    input.note_commitment_tree_position,
+                                    checkpoint_depth,
+                                )
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                            .map(|paths| (anchor, paths))
+                    })
+            })
+            .map_err(tree_err)?;
+
+        let sapling_spends = current
+            .sapling_inputs()
+            .iter()
+            .zip(merkle_paths)
+            .map(|(input, merkle_path)| {
+                // The note is received at an external or an internal address of the account,
+                // the one whose note commitment is in the tree.
+                let received_at = |address: Option<sapling::PaymentAddress>| {
+                    address
+                        .map(|address| address.create_note(input.note_value.into(), input.rseed))
+                        .filter(|note| {
+                            merkle_path.root(sapling::Node::from_cmu(&note.cmu())) == anchor
+                        })
+                        .is_some()
+                };
+                let internal = if received_at(dfvk.diversified_address(input.diversifier)) {
+                    false
+                } else if received_at(dfvk.diversified_change_address(input.diversifier)) {
+                    true
+                } else {
+                    return Err(ZcashError::Message {
+                        error: format!(
+                            "The note at {} is not of the account",
+                            u64::from(input.note_commitment_tree_position)
+                        ),
+                    });
+                };
+                let (rseed, zip212) = match input.rseed {
+                    Rseed::BeforeZip212(rcm) => (rcm.to_bytes().to_vec(), false),
+                    Rseed::AfterZip212(rseed) => (rseed.to_vec(), true),
+                };
+                Ok(ZcashSigningSaplingSpend {
+                    value: i64::from(input.note_value) as u64,
+                    position: input.note_commitment_tree_position.into(),
+                    diversifier: input.diversifier.0.to_vec(),
+                    rseed,
+                    zip212,
+                    internal,
+                    merkle_path: merkle_path.path_elems().iter().map(node_bytes).collect(),
+                })
+            })
+            .collect::<ZcashResult<Vec<_>>>()?;
+
+        Ok(Arc::new(ZcashSigningPayload {
+            derivation: self.get_device_derivation(proposal.account())?,
+            proposal,
+            anchor: node_bytes(&anchor),
+            sapling_spends,
+        }))
+    }
+}
+
+fn node_bytes(node: &sapling::Node) -> Vec<u8> {
+    let mut bytes = vec![];
+    node.write(&mut bytes)
+        .expect("writing to a vector does not fail");
+    bytes
+}
+
+fn tree_err(e: impl std::fmt::Debug) -> ZcashError {
+    ZcashError::Message {
+        error: format!("ShardTreeError: {:?}", e),
+    }
+}
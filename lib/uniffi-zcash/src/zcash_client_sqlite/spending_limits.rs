@@ -0,0 +1,150 @@
+use rusqlite::{named_params, Connection, OptionalExtension};
+use zcash_client_backend::data_api::wallet::input_selection::Proposal;
+use zcash_primitives::transaction::components::Amount;
+
+use super::account_removal::ensure_account_exists;
+use crate::{ZcashAccountId, ZcashError, ZcashResult, ZcashWalletDb};
+
+/// How much an account can spend, in zatoshis, counting the payments and the fees but not
+/// the change. Limits which are not set do not apply.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ZcashSpendingLimits {
+    pub max_per_transaction: Option<u64>,
+    /// Over the last 24 hours, counting the transactions which are mined or not expired.
+    pub max_per_day: Option<u64>,
+}
+
+impl ZcashWalletDb {
+    /// Sets the spending limits of the account, which the wallet then enforces when
+    /// proposing and creating its transactions, for custodial or kid wallets for example.
+    /// Limits without any value set remove those of the account.
+    pub fn set_spending_limits(
+        &self,
+        account: ZcashAccountId,
+        limits: ZcashSpendingLimits,
+    ) -> ZcashResult<()> {
+        let to_sql = |limit: Option<u64>| {
+            limit
+                .map(|limit| {
+                    i64::try_from(limit)
+                        .ok()
+                        .filter(|limit| Amount::from_i64(*limit).is_ok())
+                        .ok_or("Invalid spending limit")
+                })
+                .transpose()
+        };
+        let max_per_transaction = to_sql(limits.max_per_transaction)?;
+        let max_per_day = to_sql(limits.max_per_day)?;
+
+        let conn = Connection::open(&self.path)?;
+        ensure_account_exists(&conn, account)?;
+        if max_per_transaction.is_none() && max_per_day.is_none() {
+            conn.execute(
+                "DELETE FROM uniffi_spending_limits WHERE account = :account",
+                named_params![":account": account.id],
+            )?;
+        } else {
+            conn.execute(
+                "INSERT OR REPLACE INTO uniffi_spending_limits
+                (account, max_per_transaction, max_per_day)
+                VALUES (:account, :max_per_transaction, :max_per_day)",
+                named_params![
+                    ":account": account.id,
+                    ":max_per_transaction": max_per_transaction,
+                    ":max_per_day": max_per_day,
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The spending limits of the account, without any value set if it has none.
+    pub fn get_spending_limits(&self, account: ZcashAccountId) -> ZcashResult<ZcashSpendingLimits> {
+        let conn = Connection::open(&self.path)?;
+        let limits = conn
+            .query_row(
+                "SELECT max_per_transaction, max_per_day FROM uniffi_spending_limits
+                WHERE account = :account",
+                named_params![":account": account.id],
+                |row| {
+                    Ok(ZcashSpendingLimits {
+                        max_per_transaction: row.get(0)?,
+                        max_per_day: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(limits.unwrap_or_default())
+    }
+
+    /// How much the account spent over the last 24 hours, as counted against its
+    /// `max_per_day` limit.
+    pub fn get_spent_last_day(&self, account: ZcashAccountId) -> ZcashResult<u64> {
+        let conn = Connection::open(&self.path)?;
+        let chain_tip_height: Option<u32> = conn.query_row(
+            "SELECT MAX(block_range_end) - 1 FROM scan_queue",
+            [],
+            |row| row.get(0),
+        )?;
+        let spent: i64 = conn.query_row(
+            "SELECT IFNULL(SUM(IFNULL(t.fee, 0) + (
+                SELECT IFNULL(SUM(s.value), 0) FROM sent_notes s
+                WHERE s.tx = t.id_tx AND s.from_account = :account AND s.to_account IS NULL
+            )), 0)
+            FROM transactions t
+            WHERE unixepoch(t.created) > unixepoch() - 86400
+            AND EXISTS (
+                SELECT 1 FROM sent_notes s WHERE s.tx = t.id_tx AND s.from_account = :account
+            )
+            AND (t.block IS NOT NULL OR IFNULL(t.expiry_height, 0) = 0 OR t.expiry_height > :tip)",
+            named_params![
+                ":account": account.id,
+                ":tip": chain_tip_height.unwrap_or(0),
+            ],
+            |row| row.get(0),
+        )?;
+        Ok(spent.max(0) as u64)
+    }
+
+    /// Fails with a [`ZcashError::PolicyViolation`] if the account cannot spend the value of
+    /// the proposal, its payments and fee, under its limits.
+    pub(crate) fn check_spending_limits<FeeRuleT, NoteRef>(
+        &self,
+        account: u32,
+        proposal: &Proposal<FeeRuleT, NoteRef>,
+    ) -> ZcashResult<()> {
+        let account = ZcashAccountId { id: account };
+        let limits = self.get_spending_limits(account)?;
+        if limits == ZcashSpendingLimits::default() {
+            return Ok(());
+        }
+        let value = proposal
+            .transaction_request()
+            .payments()
+            .iter()
+            .map(|payment| i64::from(payment.amount) as u64)
+            .sum::<u64>()
+            + u64::from(proposal.balance().fee_required());
+
+        if let Some(limit) = limits.max_per_transaction {
+            if value > limit {
+                return Err(ZcashError::PolicyViolation {
+                    policy: "max_per_transaction".to_string(),
+                    limit,
+                    value,
+                });
+            }
+        }
+        if let Some(limit) = limits.max_per_day {
+            let value = self.get_spent_last_day(account)? + value;
+            if value > limit {
+                return Err(ZcashError::PolicyViolation {
+                    policy: "max_per_day".to_string(),
+                    limit,
+                    value,
+                });
+            }
+        }
+        Ok(())
+    }
+}
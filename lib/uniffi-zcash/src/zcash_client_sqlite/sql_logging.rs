@@ -0,0 +1,87 @@
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Once;
+
+use rusqlite::ffi;
+use tracing::info;
+
+/// Whether the executed statements are logged.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// The time statements must take to be logged, in nanoseconds.
+static MIN_DURATION_NS: AtomicU64 = AtomicU64::new(0);
+static REGISTER_TRACE: Once = Once::new();
+
+/// Logs the SQL statements run on the wallet and block cache databases, along with the
+/// time they took, through `tracing`, as the rest of the library logs. Only statements
+/// taking at least `min_duration_ms` are logged, so slow queries can be told apart in
+/// large wallets, where logging all of them is too much.
+///
+/// This covers the connections opened afterwards, by this library and by
+/// `zcash_client_sqlite` alike. The statements are logged without the values bound to
+/// them, so no keys or addresses end up in the logs.
+pub fn enable_sql_logging(min_duration_ms: u32) {
+    REGISTER_TRACE.call_once(|| {
+        // SQLite runs the auto extensions on every connection it opens.
+        let entry_point: unsafe extern "C" fn(
+            *mut ffi::sqlite3,
+            *mut *mut c_char,
+            *const ffi::sqlite3_api_routines,
+        ) -> c_int = trace_connection;
+        unsafe {
+            ffi::sqlite3_auto_extension(Some(std::mem::transmute::<
+                *const (),
+                unsafe extern "C" fn(),
+            >(entry_point as *const ())));
+        }
+    });
+    MIN_DURATION_NS.store(u64::from(min_duration_ms) * 1_000_000, Ordering::Relaxed);
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Stops logging the SQL statements, on all connections.
+pub fn disable_sql_logging() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+unsafe extern "C" fn trace_connection(
+    db: *mut ffi::sqlite3,
+    _err_msg: *mut *mut c_char,
+    _api: *const ffi::sqlite3_api_routines,
+) -> c_int {
+    ffi::sqlite3_trace_v2(
+        db,
+        ffi::SQLITE_TRACE_PROFILE as c_uint,
+        Some(log_statement),
+        std::ptr::null_mut(),
+    );
+    ffi::SQLITE_OK
+}
+
+/// For profile events, `statement` is the statement run and `elapsed` points to the
+/// nanoseconds it took.
+unsafe extern "C" fn log_statement(
+    _event: c_uint,
+    _context: *mut c_void,
+    statement: *mut c_void,
+    elapsed: *mut c_void,
+) -> c_int {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return 0;
+    }
+    let elapsed_ns = *(elapsed as *const i64) as u64;
+    if elapsed_ns < MIN_DURATION_NS.load(Ordering::Relaxed) {
+        return 0;
+    }
+    let sql = ffi::sqlite3_sql(statement as *mut ffi::sqlite3_stmt);
+    if !sql.is_null() {
+        let sql = CStr::from_ptr(sql).to_string_lossy();
+        info!(
+            target: "zcash::sql",
+            "{:.3} ms: {}",
+            elapsed_ns as f64 / 1_000_000.0,
+            sql.trim()
+        );
+    }
+    0
+}
@@ -0,0 +1,63 @@
+use rusqlite::{named_params, Connection, OptionalExtension};
+
+use crate::{ZcashResult, ZcashWalletDb};
+
+impl ZcashWalletDb {
+    /// Returns how many blocks the wallet is behind the chain tip it was last updated with,
+    /// i.e the blocks between the fully scanned height and the chain tip. It is `None`
+    /// before the chain tip is known or an account is added, as there is nothing to scan.
+    ///
+    /// It is computed from the scan queue and the scanned blocks only, so it is much
+    /// cheaper than [`Self::get_wallet_summary`] for polling the sync status.
+    pub fn sync_lag(&self) -> ZcashResult<Option<u32>> {
+        let conn = Connection::open(&self.path)?;
+        let chain_tip_height = match chain_tip_height(&conn)? {
+            Some(height) => height,
+            None => return Ok(None),
+        };
+        let birthday_height: Option<u32> =
+            conn.query_row("SELECT MIN(birthday_height) FROM accounts", [], |row| {
+                row.get(0)
+            })?;
+        match birthday_height {
+            Some(birthday_height) => Ok(Some(
+                chain_tip_height.saturating_sub(fully_scanned_height(&conn, birthday_height)?),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns whether the wallet is at most `tolerance` blocks behind the chain tip, so
+    /// that a new block being mined does not flag a wallet as out of sync. It is never
+    /// synced before the chain tip is known.
+    pub fn is_synced(&self, tolerance: u32) -> ZcashResult<bool> {
+        Ok(self.sync_lag()?.is_some_and(|lag| lag <= tolerance))
+    }
+}
+
+/// The height of the chain tip the wallet was last updated with, if any.
+pub(super) fn chain_tip_height(conn: &Connection) -> ZcashResult<Option<u32>> {
+    Ok(conn.query_row(
+        "SELECT MAX(block_range_end) - 1 FROM scan_queue",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+/// The last block of the first contiguous run of scanned blocks from the birthday on,
+/// below which the wallet knows everything.
+pub(super) fn fully_scanned_height(conn: &Connection, birthday_height: u32) -> ZcashResult<u32> {
+    let height: Option<u32> = conn
+        .query_row(
+            "SELECT MAX(height) FROM (
+                SELECT height, ROW_NUMBER() OVER (ORDER BY height) - height AS grp
+                FROM blocks
+            )
+            GROUP BY grp
+            HAVING :birthday_height BETWEEN MIN(height) AND MAX(height)",
+            named_params![":birthday_height": birthday_height],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(height.unwrap_or(birthday_height - 1))
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use rusqlite::{named_params, Connection, OptionalExtension};
+use zcash_client_backend::keys::UnifiedFullViewingKey;
+
+use super::diversifier_index_from_be;
+use crate::{ZcashAccountId, ZcashError, ZcashResult, ZcashUnifiedFullViewingKey, ZcashWalletDb};
+
+/// The outcome of [`ZcashWalletDb::replace_ufvk`].
+pub struct ZcashUfvkReplacementReport {
+    pub account: ZcashAccountId,
+    /// How many of the addresses stored for the account were derived again.
+    pub checked_addresses: u32,
+    /// The stored addresses the new key derives differently, or cannot derive at all.
+    pub mismatched_addresses: Vec<String>,
+    /// Whether the new key was stored. It never is in dry runs, nor with mismatches.
+    pub applied: bool,
+}
+
+impl ZcashWalletDb {
+    /// Replaces the stored unified full viewing key of an account, like after upstream key
+    /// format changes. As a check of integrity, the new key must derive the very same
+    /// addresses the wallet has generated for the account, at the same diversifier indexes.
+    /// With `dry_run`, the checks are reported but nothing is stored.
+    pub fn replace_ufvk(
+        &self,
+        account: ZcashAccountId,
+        ufvk: Arc<ZcashUnifiedFullViewingKey>,
+        dry_run: bool,
+    ) -> ZcashResult<ZcashUfvkReplacementReport> {
+        let ufvk: UnifiedFullViewingKey = (*ufvk).clone().into();
+        let mut conn = Connection::open(&self.path)?;
+        let tx = conn.transaction()?;
+
+        tx.query_row(
+            "SELECT account FROM accounts WHERE account = :account",
+            named_params![":account": account.id],
+            |_| Ok(()),
+        )
+        .optional()?
+        .ok_or_else(|| ZcashError::Message {
+            error: format!("The wallet has no account {}", account.id),
+        })?;
+
+        let addresses = tx
+            .prepare(
+                "SELECT address, diversifier_index_be FROM addresses
+                WHERE account = :account
+                ORDER BY diversifier_index_be ASC",
+            )?
+            .query_map(named_params![":account": account.id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut mismatched_addresses = Vec::new();
+        for (address, di_be) in &addresses {
+            let derived = ufvk
+                .address(diversifier_index_from_be(di_be)?)
+                .map(|addr| addr.encode(&self.params));
+            if derived.as_ref() != Some(address) {
+                mismatched_addresses.push(address.clone());
+            }
+        }
+
+        let applied = !dry_run && mismatched_addresses.is_empty();
+        if applied {
+            tx.execute(
+                "UPDATE accounts SET ufvk = :ufvk WHERE account = :account",
+                named_params![":ufvk": ufvk.encode(&self.params), ":account": account.id],
+            )?;
+            tx.commit()?;
+        }
+
+        Ok(ZcashUfvkReplacementReport {
+            account,
+            checked_addresses: addresses.len() as u32,
+            mismatched_addresses,
+            applied,
+        })
+    }
+}
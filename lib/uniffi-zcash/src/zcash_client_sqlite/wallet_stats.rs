@@ -0,0 +1,62 @@
+use std::fs;
+
+use rusqlite::{named_params, Connection};
+
+use super::scan_queue::PRIORITY_SCANNED;
+use crate::{ZcashResult, ZcashWalletDb};
+
+/// Counters of the wallet database, to be exported as metrics by the services tracking it.
+pub struct ZcashWalletStats {
+    /// Sapling notes received by all accounts, including spent ones.
+    pub sapling_notes: u64,
+    pub unspent_sapling_notes: u64,
+    /// Transparent outputs received by all accounts, including spent ones.
+    pub utxos: u64,
+    pub unspent_utxos: u64,
+    pub transactions: u64,
+    /// The size of the database file, in bytes, not counting its write-ahead log.
+    pub db_size: u64,
+    /// The ranges of the scan queue still to be scanned, and the blocks in them.
+    pub scan_queue_ranges: u64,
+    pub blocks_to_scan: u64,
+    pub last_scanned_height: Option<u32>,
+    /// The time in the header of the last scanned block, in seconds since the Unix epoch.
+    /// The wallet does not store when it scanned blocks, but this trails the current time
+    /// by about a block interval when the wallet is in sync.
+    pub last_scanned_block_time: Option<u32>,
+}
+
+impl ZcashWalletDb {
+    /// Returns the counters of the wallet database. They are cheap to compute, so the
+    /// services tracking the wallet can poll them as often as they scrape metrics.
+    pub fn get_wallet_stats(&self) -> ZcashResult<ZcashWalletStats> {
+        let conn = Connection::open(&self.path)?;
+        let count = |query: &str| conn.query_row(query, [], |row| row.get::<_, u64>(0));
+
+        let (scan_queue_ranges, blocks_to_scan) = conn.query_row(
+            "SELECT COUNT(*), IFNULL(SUM(block_range_end - block_range_start), 0)
+            FROM scan_queue WHERE priority > :scanned",
+            named_params![":scanned": PRIORITY_SCANNED],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let (last_scanned_height, last_scanned_block_time) =
+            conn.query_row("SELECT MAX(height), time FROM blocks", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?;
+
+        Ok(ZcashWalletStats {
+            sapling_notes: count("SELECT COUNT(*) FROM sapling_received_notes")?,
+            unspent_sapling_notes: count(
+                "SELECT COUNT(*) FROM sapling_received_notes WHERE spent IS NULL",
+            )?,
+            utxos: count("SELECT COUNT(*) FROM utxos")?,
+            unspent_utxos: count("SELECT COUNT(*) FROM utxos WHERE spent_in_tx IS NULL")?,
+            transactions: count("SELECT COUNT(*) FROM transactions")?,
+            db_size: fs::metadata(&self.path)?.len(),
+            scan_queue_ranges,
+            blocks_to_scan,
+            last_scanned_height,
+            last_scanned_block_time,
+        })
+    }
+}
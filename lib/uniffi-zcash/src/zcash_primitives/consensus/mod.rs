@@ -1,3 +1,6 @@
+mod network_info;
+pub use self::network_info::*;
+
 mod parameters;
 pub use self::parameters::*;
 
@@ -0,0 +1,75 @@
+use zcash_primitives::consensus::{NetworkUpgrade, Parameters};
+
+use crate::ZcashConsensusParameters;
+
+/// The constants of a network, as binding consumers need them to encode and recognize
+/// its keys and addresses, and to connect to it.
+pub struct ZcashNetworkInfo {
+    pub network: ZcashConsensusParameters,
+    /// The name of the network, as nodes report it in `getblockchaininfo`.
+    pub name: String,
+    /// The ZIP 32 coin type, the account derivation path being `m/32'/coin_type'/account'`.
+    pub coin_type: u32,
+    pub hrp_sapling_extended_spending_key: String,
+    pub hrp_sapling_extended_full_viewing_key: String,
+    pub hrp_sapling_payment_address: String,
+    /// The ZIP 316 human readable parts of the unified addresses and viewing keys.
+    pub hrp_unified_address: String,
+    pub hrp_unified_full_viewing_key: String,
+    pub hrp_unified_incoming_viewing_key: String,
+    /// The two bytes prefixing the base58 encoding of transparent P2PKH addresses.
+    pub b58_pubkey_address_prefix: Vec<u8>,
+    /// The two bytes prefixing the base58 encoding of transparent P2SH addresses.
+    pub b58_script_address_prefix: Vec<u8>,
+    pub sapling_activation_height: u32,
+    /// The lightwalletd servers of the Electric Coin Company, which are known to serve
+    /// the network. Apps should still let their users choose their own.
+    pub default_lightwalletd_urls: Vec<String>,
+}
+
+/// Returns the constants of the given network.
+pub fn network_info(params: ZcashConsensusParameters) -> ZcashNetworkInfo {
+    let (name, hrp_unified, lightwalletd_url) = match params {
+        ZcashConsensusParameters::MainNetwork => (
+            "main",
+            ["u", "uview", "uivk"],
+            "https://mainnet.lightwalletd.com:9067",
+        ),
+        ZcashConsensusParameters::TestNetwork => (
+            "test",
+            ["utest", "uviewtest", "uivktest"],
+            "https://lightwalletd.testnet.electriccoin.co:9067",
+        ),
+    };
+
+    ZcashNetworkInfo {
+        network: params,
+        name: name.to_string(),
+        coin_type: params.coin_type(),
+        hrp_sapling_extended_spending_key: params.hrp_sapling_extended_spending_key().to_string(),
+        hrp_sapling_extended_full_viewing_key: params
+            .hrp_sapling_extended_full_viewing_key()
+            .to_string(),
+        hrp_sapling_payment_address: params.hrp_sapling_payment_address().to_string(),
+        hrp_unified_address: hrp_unified[0].to_string(),
+        hrp_unified_full_viewing_key: hrp_unified[1].to_string(),
+        hrp_unified_incoming_viewing_key: hrp_unified[2].to_string(),
+        b58_pubkey_address_prefix: params.b58_pubkey_address_prefix().to_vec(),
+        b58_script_address_prefix: params.b58_script_address_prefix().to_vec(),
+        sapling_activation_height: params
+            .activation_height(NetworkUpgrade::Sapling)
+            .map_or(0, u32::from),
+        default_lightwalletd_urls: vec![lightwalletd_url.to_string()],
+    }
+}
+
+/// Returns the constants of all the networks the library supports, mainnet first.
+pub fn supported_networks() -> Vec<ZcashNetworkInfo> {
+    [
+        ZcashConsensusParameters::MainNetwork,
+        ZcashConsensusParameters::TestNetwork,
+    ]
+    .into_iter()
+    .map(network_info)
+    .collect()
+}
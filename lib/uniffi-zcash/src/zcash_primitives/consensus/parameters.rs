@@ -6,7 +6,7 @@
 };
 
 /// Zcash consensus parameters.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum ZcashConsensusParameters {
     /// Marker struct for the production network.
     MainNetwork,
@@ -3,6 +3,8 @@
 use zcash_primitives::legacy::keys::AccountPrivKey;
 
 use crate::{
+    validation::invalid_argument,
+    validation::{check_account, check_seed},
     SecpSecretKey, ZcashAccountId, ZcashAccountPubKey, ZcashConsensusParameters, ZcashError,
     ZcashExtendedPrivKey, ZcashResult,
 };
@@ -28,6 +30,8 @@ pub fn from_seed(
         seed: Vec<u8>,
         account: ZcashAccountId,
     ) -> ZcashResult<Self> {
+        check_seed("seed", &seed)?;
+        check_account("account", account.id)?;
         let key = AccountPrivKey::from_seed(&params, &seed, account.into())?;
 
         Ok(key.into())
@@ -73,6 +77,6 @@ pub fn to_bytes(&self) -> Vec<u8> {
     pub fn from_bytes(bytes: Vec<u8>) -> ZcashResult<Self> {
         zcash_primitives::legacy::keys::AccountPrivKey::from_bytes(&bytes)
             .map(ZcashAccountPrivKey::from)
-            .ok_or(ZcashError::Unknown)
+            .ok_or_else(|| invalid_argument("bytes", "not a BIP 32 extended private key"))
     }
 }
@@ -16,7 +16,7 @@
 
 impl ZcashAccountPubKey {
     pub fn new(data: Vec<u8>) -> ZcashResult<Self> {
-        let casted_data = cast_slice(data.as_slice())?;
+        let casted_data = cast_slice("data", data.as_slice())?;
         let key = AccountPubKey::deserialize(&casted_data).map_err(ZcashError::from)?;
         Ok(key.into())
     }
@@ -33,7 +33,7 @@ pub fn to_bytes(&self) -> Vec<u8> {
     }
 
     pub fn from_bytes(data: &[u8]) -> ZcashResult<Self> {
-        let array = utils::cast_slice(data)?;
+        let array = utils::cast_slice("data", data)?;
         ExternalIvk::deserialize(&array)
             .map_err(From::from)
             .map(From::from)
@@ -69,7 +69,7 @@ pub fn to_bytes(&self) -> Vec<u8> {
     }
 
     pub fn from_bytes(data: &[u8]) -> ZcashResult<Self> {
-        let array = utils::cast_slice(data)?;
+        let array = utils::cast_slice("data", data)?;
         InternalIvk::deserialize(&array)
             .map_err(From::from)
             .map(From::from)
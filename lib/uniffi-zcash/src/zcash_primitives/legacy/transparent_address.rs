@@ -1,14 +1,19 @@
 use std::sync::Arc;
 
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
 use zcash_client_backend::encoding;
 use zcash_primitives::{consensus::Parameters, legacy::TransparentAddress};
 
-use crate::{utils, ZcashConsensusParameters, ZcashError, ZcashResult, ZcashScript};
+use crate::{
+    utils, validation::invalid_argument, zcash_address::is_sprout_address,
+    ZcashConsensusParameters, ZcashError, ZcashResult, ZcashScript,
+};
 
 use derive_more::{From, Into};
 
 /// A transparent address corresponding to either a public key or a `Script`.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, From, Into)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, From, Into)]
 pub struct ZcashTransparentAddress(TransparentAddress);
 
 impl From<&ZcashTransparentAddress> for TransparentAddress {
@@ -20,29 +25,39 @@ fn from(value: &ZcashTransparentAddress) -> Self {
 impl ZcashTransparentAddress {
     /// Create new transparent address corresponding to public key
     pub fn from_public_key(input: Vec<u8>) -> ZcashResult<Self> {
-        let buf = utils::cast_slice(&input)?;
+        let buf = utils::cast_slice("input", &input)?;
         Ok(TransparentAddress::PublicKey(buf).into())
     }
 
     /// Create new transparent address corresponding to script
     pub fn from_script(input: Vec<u8>) -> ZcashResult<Self> {
-        let buf = utils::cast_slice(&input)?;
+        let buf = utils::cast_slice("input", &input)?;
         Ok(TransparentAddress::Script(buf).into())
     }
 
+    /// Create new pay-to-script-hash address spendable with the given redeem script,
+    /// like a multisig script from [`multisig_redeem_script`].
+    pub fn from_redeem_script(redeem_script: Vec<u8>) -> Self {
+        let hash = Ripemd160::digest(Sha256::digest(redeem_script));
+        TransparentAddress::Script(hash.into()).into()
+    }
+
     pub fn script(&self) -> Arc<ZcashScript> {
         Arc::new(self.0.script().into())
     }
 
     /// Decodes a [`TransparentAddress`] from a Base58Check-encoded string.
     pub fn decode(params: ZcashConsensusParameters, input: &str) -> ZcashResult<Self> {
+        if is_sprout_address(input) {
+            return Err(ZcashError::SproutNotSupported);
+        }
         encoding::decode_transparent_address(
             &params.b58_pubkey_address_prefix(),
             &params.b58_script_address_prefix(),
             input,
         )
-        .map_err(|_| ZcashError::Unknown)?
-        .ok_or(ZcashError::Unknown)
+        .map_err(|e| invalid_argument("input", format!("not a transparent address: {}", e)))?
+        .ok_or_else(|| invalid_argument("input", "not a transparent address of the network"))
         .map(Into::into)
     }
 
@@ -71,3 +86,46 @@ pub fn to_bytes(&self) -> Vec<u8> {
         }
     }
 }
+
+/// The largest number of keys in a standard multisig script.
+const MAX_MULTISIG_KEYS: usize = 16;
+
+const OP_1: u8 = 0x51;
+const OP_CHECKMULTISIG: u8 = 0xae;
+
+/// Builds the standard redeem script of a `threshold`-of-`pubkeys` multisig, to be
+/// used with [`ZcashTransparentAddress::from_redeem_script`]. The keys are serialized
+/// secp256k1 public keys, and are kept in the given order, which must be the same for
+/// all the cosigners.
+pub fn multisig_redeem_script(threshold: u8, pubkeys: Vec<Vec<u8>>) -> ZcashResult<Vec<u8>> {
+    if pubkeys.is_empty() || pubkeys.len() > MAX_MULTISIG_KEYS {
+        return Err(ZcashError::Message {
+            error: format!(
+                "A multisig script needs between 1 and {} keys, {} given",
+                MAX_MULTISIG_KEYS,
+                pubkeys.len()
+            ),
+        });
+    }
+    if threshold == 0 || threshold as usize > pubkeys.len() {
+        return Err(ZcashError::Message {
+            error: format!(
+                "The threshold must be between 1 and the number of keys, {} given",
+                threshold
+            ),
+        });
+    }
+
+    let mut script = vec![OP_1 + threshold - 1];
+    for pubkey in &pubkeys {
+        ::secp256k1::PublicKey::from_slice(pubkey).map_err(|e| ZcashError::Message {
+            error: format!("Invalid public key: {}", e),
+        })?;
+        script.push(pubkey.len() as u8);
+        script.extend_from_slice(pubkey);
+    }
+    script.push(OP_1 + pubkeys.len() as u8 - 1);
+    script.push(OP_CHECKMULTISIG);
+
+    Ok(script)
+}
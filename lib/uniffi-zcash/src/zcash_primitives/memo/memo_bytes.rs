@@ -1,6 +1,9 @@
-use zcash_primitives::memo::MemoBytes;
+use zcash_primitives::memo::{Memo, MemoBytes};
 
-use crate::{ZcashError, ZcashResult};
+use crate::{validation::invalid_argument, ZcashError, ZcashResult};
+
+/// What self-memos start with, telling them apart from the text memos written by users.
+const SELF_MEMO_PREFIX: &str = "zcash-self-memo:";
 
 use derive_more::{From, Into};
 
@@ -18,9 +21,11 @@ impl ZcashMemoBytes {
     /// case is [`MemoBytes::empty`], which uses a specific encoding to indicate that no
     /// memo is present.
     pub fn new(data: &[u8]) -> ZcashResult<Self> {
-        let memo = MemoBytes::from_bytes(data).map_err(|_| ZcashError::ArrayLengthMismatch {
-            expected: 512,
-            got: data.len() as u64,
+        let memo = MemoBytes::from_bytes(data).map_err(|_| {
+            invalid_argument(
+                "data",
+                format!("memos are at most 512 bytes, got {}", data.len()),
+            )
         })?;
 
         Ok(ZcashMemoBytes(memo))
@@ -35,6 +40,32 @@ pub fn empty() -> Self {
     pub fn data(&self) -> Vec<u8> {
         self.0.as_slice().to_owned()
     }
+
+    /// Creates a self-memo, a text memo carrying `tag`, which must be JSON, for the wallet
+    /// to put in the change outputs of its own transactions. Like the identifier of the
+    /// invoice a payment settles, so the devices sharing the seed can do their bookkeeping
+    /// when they find the change.
+    pub fn self_memo(tag: String) -> ZcashResult<Self> {
+        let tag: serde_json::Value =
+            serde_json::from_str(&tag).map_err(|e| ZcashError::Message {
+                error: format!("The self-memo tag is not JSON: {}", e),
+            })?;
+        let memo: Memo = format!("{}{}", SELF_MEMO_PREFIX, tag)
+            .parse()
+            .map_err(|_| "The self-memo tag does not fit in a memo")?;
+        Ok(ZcashMemoBytes((&memo).into()))
+    }
+
+    /// Returns the JSON tag of a self-memo, or `None` for any other memo.
+    pub fn self_memo_tag(&self) -> Option<String> {
+        match Memo::try_from(self.0.clone()) {
+            Ok(Memo::Text(text)) => text
+                .strip_prefix(SELF_MEMO_PREFIX)
+                .filter(|tag| serde_json::from_str::<serde_json::Value>(tag).is_ok())
+                .map(String::from),
+            _ => None,
+        }
+    }
 }
 
 impl From<&ZcashMemoBytes> for MemoBytes {
@@ -9,7 +9,7 @@
 
 impl ZcashDiversifier {
     pub fn new(bytes: Vec<u8>) -> ZcashResult<Self> {
-        let array = utils::cast_slice(&bytes)?;
+        let array = utils::cast_slice("bytes", &bytes)?;
         Ok(Diversifier(array).into())
     }
 
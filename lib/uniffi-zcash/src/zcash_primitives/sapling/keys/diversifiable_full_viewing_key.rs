@@ -3,9 +3,10 @@
 use zcash_primitives::zip32::DiversifiableFullViewingKey;
 
 use crate::{
-    utils, ZcashDiversifier, ZcashDiversifierIndex, ZcashDiversifierIndexAndPaymentAddress,
-    ZcashDiversifierIndexAndScope, ZcashError, ZcashFullViewingKey, ZcashNullifierDerivingKey,
-    ZcashOutgoingViewingKey, ZcashPaymentAddress, ZcashResult, ZcashSaplingIvk, ZcashScope,
+    utils, validation::invalid_argument, ZcashDiversifier, ZcashDiversifierIndex,
+    ZcashDiversifierIndexAndPaymentAddress, ZcashDiversifierIndexAndScope, ZcashFullViewingKey,
+    ZcashNullifierDerivingKey, ZcashOutgoingViewingKey, ZcashPaymentAddress, ZcashResult,
+    ZcashSaplingIvk, ZcashScope,
 };
 
 /// A Sapling key that provides the capability to view incoming and outgoing transactions.
@@ -37,8 +38,10 @@ impl ZcashDiversifiableFullViewingKey {
     /// Returns `None` if the bytes do not contain a valid encoding of a diversifiable
     /// Sapling full viewing key.
     pub fn from_bytes(bytes: Vec<u8>) -> ZcashResult<Self> {
-        let array = utils::cast_slice(&bytes)?;
-        let key = DiversifiableFullViewingKey::from_bytes(&array).ok_or(ZcashError::Unknown)?;
+        let array = utils::cast_slice("bytes", &bytes)?;
+        let key = DiversifiableFullViewingKey::from_bytes(&array).ok_or_else(|| {
+            invalid_argument("bytes", "not a Sapling diversifiable full viewing key")
+        })?;
 
         Ok(ZcashDiversifiableFullViewingKey(key))
     }
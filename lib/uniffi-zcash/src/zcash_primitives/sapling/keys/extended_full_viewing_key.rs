@@ -1,3 +1,4 @@
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use zcash_client_backend::encoding;
@@ -14,6 +15,20 @@
 #[derive(Clone, From, Into)]
 pub struct ZcashExtendedFullViewingKey(ExtendedFullViewingKey);
 
+impl PartialEq for ZcashExtendedFullViewingKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for ZcashExtendedFullViewingKey {}
+
+impl Hash for ZcashExtendedFullViewingKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state)
+    }
+}
+
 impl ZcashExtendedFullViewingKey {
     /// Writes an [`ExtendedFullViewingKey`] as a Bech32-encoded string.
     pub fn encode(&self, params: ZcashConsensusParameters) -> String {
@@ -14,7 +14,7 @@ fn from(key: NullifierDerivingKey) -> Self {
 
 impl ZcashNullifierDerivingKey {
     pub fn from_bytes(bytes: &[u8]) -> ZcashResult<Self> {
-        let array = utils::cast_slice(bytes)?;
+        let array = utils::cast_slice("bytes", bytes)?;
         let group: Option<_> = SubgroupPoint::from_bytes(&array).into();
         let group = group.ok_or("unable to parse nullifier deriving key")?;
         Ok(ZcashNullifierDerivingKey(NullifierDerivingKey(group)))
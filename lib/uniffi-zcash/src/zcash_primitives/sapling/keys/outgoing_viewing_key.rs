@@ -10,7 +10,7 @@
 
 impl ZcashOutgoingViewingKey {
     pub fn from_bytes(b: &[u8]) -> ZcashResult<Self> {
-        let array = utils::cast_slice(b)?;
+        let array = utils::cast_slice("b", b)?;
         Ok(ZcashOutgoingViewingKey(OutgoingViewingKey(array)))
     }
 
@@ -16,6 +16,9 @@
 mod note;
 pub use self::note::*;
 
+mod note_encryption;
+pub use self::note_encryption::*;
+
 mod tree;
 pub use self::tree::*;
 
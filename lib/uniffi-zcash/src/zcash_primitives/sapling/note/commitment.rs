@@ -12,7 +12,7 @@ impl ZcashSaplingExtractedNoteCommitment {
     ///
     /// [cmucanon]: https://zips.z.cash/protocol/protocol.pdf#outputencodingandconsensus
     pub fn new(data: &[u8]) -> ZcashResult<Self> {
-        let casted_data = cast_slice(data)?;
+        let casted_data = cast_slice("data", data)?;
         let option: Option<ExtractedNoteCommitment> =
             ExtractedNoteCommitment::from_bytes(&casted_data).into();
         match option {
@@ -52,6 +52,23 @@ pub fn value(&self) -> Arc<ZcashSaplingNoteValue> {
     pub fn cmu(&self) -> Arc<ZcashSaplingExtractedNoteCommitment> {
         Arc::new(self.0.cmu().into())
     }
+
+    /// Returns the note randomness, which tells whether the note was created before or
+    /// after [ZIP 212](https://zips.z.cash/zip-0212).
+    pub fn rseed(&self) -> ZcashRseed {
+        self.0.rseed().into()
+    }
+
+    /// Returns the lead byte of the note plaintext this note was decrypted from, or is
+    /// encrypted to: `0x01` for notes before ZIP 212, `0x02` for those after it. Notes
+    /// received after the ZIP 212 grace period must have the latter, see
+    /// `sapling_plaintext_version_is_valid`.
+    pub fn plaintext_lead_byte(&self) -> u8 {
+        match self.0.rseed() {
+            Rseed::BeforeZip212(_) => 0x01,
+            Rseed::AfterZip212(_) => 0x02,
+        }
+    }
 }
 
 impl From<&ZcashSaplingNote> for Note {
@@ -76,6 +93,19 @@ pub enum ZcashRseed {
     },
 }
 
+impl From<&Rseed> for ZcashRseed {
+    fn from(value: &Rseed) -> Self {
+        match value {
+            Rseed::BeforeZip212(rcm) => ZcashRseed::BeforeZip212 {
+                fr_data: rcm.to_bytes().to_vec(),
+            },
+            Rseed::AfterZip212(data) => ZcashRseed::AfterZip212 {
+                data: data.to_vec(),
+            },
+        }
+    }
+}
+
 impl TryFrom<ZcashRseed> for Rseed {
     type Error = ZcashError;
 
@@ -85,7 +115,7 @@ fn try_from(value: ZcashRseed) -> Result<Self, Self::Error> {
                 ZcashJubjubFr::from_bytes(fr_data.as_slice())?.into(),
             )),
             ZcashRseed::AfterZip212 { data } => {
-                let casted_data = cast_slice(data.as_slice())?;
+                let casted_data = cast_slice("data", data.as_slice())?;
                 Ok(Rseed::AfterZip212(casted_data))
             }
         }
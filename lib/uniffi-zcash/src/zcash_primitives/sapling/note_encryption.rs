@@ -0,0 +1,17 @@
+use std::sync::Arc;
+
+use zcash_primitives::sapling::note_encryption::plaintext_version_is_valid;
+
+use crate::{ZcashBlockHeight, ZcashConsensusParameters};
+
+/// Whether a Sapling note plaintext lead byte is valid for an output mined at the given
+/// height. From Canopy, and for a grace period of 32256 blocks, both the `0x01` lead byte
+/// of notes before [ZIP 212](https://zips.z.cash/zip-0212) and the `0x02` one of notes
+/// after it are valid, while only `0x02` is afterwards. Before Canopy, only `0x01` is.
+pub fn sapling_plaintext_version_is_valid(
+    params: ZcashConsensusParameters,
+    height: Arc<ZcashBlockHeight>,
+    lead_byte: u8,
+) -> bool {
+    plaintext_version_is_valid(&params, height.as_ref().into(), lead_byte)
+}
@@ -1,10 +1,12 @@
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 use zcash_client_backend::encoding;
 use zcash_primitives::{consensus::Parameters, sapling::PaymentAddress};
 
 use crate::{
-    utils, ZcashConsensusParameters, ZcashDiversifier, ZcashError, ZcashResult, ZcashRseed,
+    utils, validation::invalid_argument, zcash_address::is_sprout_address,
+    ZcashConsensusParameters, ZcashDiversifier, ZcashError, ZcashResult, ZcashRseed,
     ZcashSaplingDiversifiedTransmissionKey, ZcashSaplingNote,
 };
 
@@ -16,12 +18,21 @@
 ///
 /// `pk_d` is guaranteed to be prime-order (i.e. in the prime-order subgroup of Jubjub,
 /// and not the identity).
-#[derive(Debug, Clone, From, Into)]
+#[derive(Debug, Clone, PartialEq, Eq, From, Into)]
 pub struct ZcashPaymentAddress(PaymentAddress);
 
+impl Hash for ZcashPaymentAddress {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bytes().hash(state)
+    }
+}
+
 impl ZcashPaymentAddress {
     /// Decodes a [`PaymentAddress`] from a Bech32-encoded string.
     pub fn decode(params: ZcashConsensusParameters, string: &str) -> ZcashResult<Self> {
+        if is_sprout_address(string) {
+            return Err(ZcashError::SproutNotSupported);
+        }
         encoding::decode_payment_address(params.hrp_sapling_payment_address(), string)
             .map_err(From::from)
             .map(From::from)
@@ -29,10 +40,10 @@ pub fn decode(params: ZcashConsensusParameters, string: &str) -> ZcashResult<Sel
 
     /// Parses a PaymentAddress from bytes.
     pub fn from_bytes(bytes: &[u8]) -> ZcashResult<Self> {
-        let bytes = utils::cast_slice(bytes)?;
+        let bytes = utils::cast_slice("bytes", bytes)?;
         PaymentAddress::from_bytes(&bytes)
             .map(ZcashPaymentAddress)
-            .ok_or(ZcashError::Unknown)
+            .ok_or_else(|| invalid_argument("bytes", "not a Sapling payment address"))
     }
 
     /// Encode payment address into string
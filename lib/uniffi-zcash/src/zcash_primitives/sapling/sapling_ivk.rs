@@ -12,6 +12,12 @@ fn from(ivk: SaplingIvk) -> Self {
     }
 }
 
+impl From<&ZcashSaplingIvk> for SaplingIvk {
+    fn from(value: &ZcashSaplingIvk) -> Self {
+        value.0.clone()
+    }
+}
+
 impl ZcashSaplingIvk {
     pub fn to_payment_address(
         &self,
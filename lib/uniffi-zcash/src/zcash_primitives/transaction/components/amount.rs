@@ -1,6 +1,6 @@
 use std::sync::Arc;
 use zcash_primitives::transaction::components::{
-    amount::{NonNegativeAmount, MAX_MONEY},
+    amount::{NonNegativeAmount, COIN, MAX_MONEY},
     Amount,
 };
 
@@ -130,6 +130,80 @@ fn try_from(value: ZcashAmount) -> Result<Self, Self::Error> {
     }
 }
 
+/// The ways [`parse_zec_string`] rejects an amount.
+#[derive(Debug, thiserror::Error)]
+pub enum ZcashAmountParseError {
+    /// The input is not made of digits, optionally followed by a dot and more digits.
+    #[error("invalid ZEC amount: {v:?}")]
+    InvalidFormat { v: String },
+    /// The input has the wrapped count of decimals, more than the 8 a zatoshi allows.
+    #[error("too many decimals in ZEC amount: {v}, at most 8 are allowed")]
+    TooManyDecimals { v: u32 },
+    /// The amount is above `MAX_MONEY`.
+    #[error("ZEC amount above the 21 million ZEC total supply")]
+    Overflow,
+    /// The amount is negative.
+    #[error("negative ZEC amount")]
+    Negative,
+}
+
+/// Parses an amount of ZEC, like `"1.2345"`, into zatoshis. The same strict format is
+/// accepted whatever the locale: digits, optionally followed by a dot and up to 8 more
+/// digits. No signs, whitespace, exponents or digit grouping are allowed.
+pub fn parse_zec_string(input: String) -> Result<Arc<ZcashAmount>, ZcashAmountParseError> {
+    if input.starts_with('-') {
+        return Err(ZcashAmountParseError::Negative);
+    }
+    let invalid = || ZcashAmountParseError::InvalidFormat { v: input.clone() };
+
+    let (whole, fraction) = match input.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (input.as_str(), None),
+    };
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !is_digits(whole) || fraction.is_some_and(|f| !is_digits(f)) {
+        return Err(invalid());
+    }
+
+    let fraction = fraction.unwrap_or_default();
+    if fraction.len() > 8 {
+        return Err(ZcashAmountParseError::TooManyDecimals {
+            v: fraction.len() as u32,
+        });
+    }
+    let fraction: i64 = format!("{:0<8}", fraction).parse().map_err(|_| invalid())?;
+
+    let zatoshis = whole
+        .parse::<i64>()
+        .ok()
+        .and_then(|whole| whole.checked_mul(COIN))
+        .and_then(|whole| whole.checked_add(fraction))
+        .and_then(|zatoshis| Amount::from_i64(zatoshis).ok())
+        .ok_or(ZcashAmountParseError::Overflow)?;
+
+    Ok(Arc::new(ZcashAmount(zatoshis)))
+}
+
+/// Formats an amount in ZEC, with a dot as decimal separator whatever the locale, the
+/// inverse of [`parse_zec_string`]. With `trailing_zeros`, all the 8 decimals are
+/// written, like `"1.50000000"`, otherwise only the significant ones, like `"1.5"`.
+pub fn format_zec(amount: Arc<ZcashAmount>, trailing_zeros: bool) -> String {
+    let zatoshis = amount.value();
+    let sign = if zatoshis < 0 { "-" } else { "" };
+    let zatoshis = zatoshis.unsigned_abs();
+    let coin = COIN as u64;
+
+    let fraction = format!("{:08}", zatoshis % coin);
+    let fraction = match trailing_zeros {
+        true => fraction.as_str(),
+        false => fraction.trim_end_matches('0'),
+    };
+    match fraction {
+        "" => format!("{}{}", sign, zatoshis / coin),
+        _ => format!("{}{}.{}", sign, zatoshis / coin, fraction),
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ZcashBalance(Balance);
 
@@ -1,11 +1,17 @@
 use std::sync::Arc;
 
-use zcash_primitives::transaction::components::{
-    transparent::{Authorized, Bundle, TxIn},
-    OutPoint, TxOut,
+use zcash_primitives::transaction::{
+    components::{
+        transparent::{Authorized, Bundle, TxIn},
+        OutPoint, TxOut,
+    },
+    TxId,
 };
 
-use crate::{utils::cast_slice, ZcashAmount, ZcashResult, ZcashScript, ZcashTransparentAddress};
+use crate::{
+    utils::cast_slice, ZcashAmount, ZcashError, ZcashResult, ZcashScript, ZcashTransparentAddress,
+    ZcashTxId,
+};
 
 use derive_more::{From, Into};
 
@@ -14,9 +20,36 @@
 
 impl ZcashOutPoint {
     pub fn new(hash: &[u8], n: u32) -> ZcashResult<Self> {
-        let casted_data = cast_slice(hash)?;
+        let casted_data = cast_slice("hash", hash)?;
         Ok(OutPoint::new(casted_data, n).into())
     }
+
+    /// Parses an outpoint as block explorers and indexers display it, `txid:vout`, with
+    /// the transaction id in its usual, byte reversed, hex encoding.
+    pub fn from_hex(outpoint: &str) -> ZcashResult<Self> {
+        let (txid, index) = outpoint
+            .split_once(':')
+            .ok_or_else(|| ZcashError::Message {
+                error: format!("Expected an outpoint as txid:vout, got {}", outpoint),
+            })?;
+
+        let mut hash = hex::decode(txid).map_err(|e| ZcashError::Message {
+            error: format!("Invalid outpoint txid {}: {}", txid, e),
+        })?;
+        hash.reverse();
+        let index = index.parse::<u32>().map_err(|e| ZcashError::Message {
+            error: format!("Invalid outpoint index {}: {}", index, e),
+        })?;
+        Self::new(&hash, index)
+    }
+
+    pub fn txid(&self) -> Arc<ZcashTxId> {
+        Arc::new(TxId::from_bytes(*self.0.hash()).into())
+    }
+
+    pub fn index(&self) -> u32 {
+        self.0.n()
+    }
 }
 
 #[derive(From)]
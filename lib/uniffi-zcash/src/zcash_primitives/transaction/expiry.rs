@@ -0,0 +1,64 @@
+use zcash_primitives::consensus::{BlockHeight, BranchId};
+
+use crate::{validation::invalid_argument, ZcashConsensusParameters, ZcashResult};
+
+/// How many blocks after the height they target transactions expire by default, since
+/// Blossom. See [ZIP 203](https://zips.z.cash/zip-0203).
+pub const DEFAULT_TX_EXPIRY_DELTA: u32 = 40;
+
+/// Nodes reject the transactions expiring within this many blocks of the chain tip.
+const TX_EXPIRING_SOON_THRESHOLD: u32 = 3;
+
+/// The greatest expiry height ZIP 203 allows.
+const MAX_EXPIRY_HEIGHT: u32 = 499_999_999;
+
+pub fn default_tx_expiry_delta() -> u32 {
+    DEFAULT_TX_EXPIRY_DELTA
+}
+
+/// Checks nodes accept transactions expiring `expiry_delta` blocks after their target.
+pub(crate) fn check_expiry_delta(expiry_delta: u32) -> ZcashResult<()> {
+    if expiry_delta <= TX_EXPIRING_SOON_THRESHOLD {
+        return Err(invalid_argument(
+            "expiry_delta",
+            format!(
+                "transactions must expire more than {} blocks after their target height",
+                TX_EXPIRING_SOON_THRESHOLD
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// The height to give the librustzcash builder for the transaction targeting
+/// `target_height` to expire `expiry_delta` blocks after it. The builder always sets the
+/// expiry [`DEFAULT_TX_EXPIRY_DELTA`] blocks after the height it is given, which otherwise
+/// only selects the consensus rules: both heights must then be within the same network
+/// upgrade, as transactions cannot outlive the upgrade they are built for anyway.
+pub(crate) fn builder_target_height(
+    params: ZcashConsensusParameters,
+    target_height: BlockHeight,
+    expiry_delta: u32,
+) -> ZcashResult<BlockHeight> {
+    check_expiry_delta(expiry_delta)?;
+    let expiry_height = u32::from(target_height)
+        .checked_add(expiry_delta)
+        .filter(|height| *height <= MAX_EXPIRY_HEIGHT)
+        .ok_or_else(|| invalid_argument("expiry_delta", "the expiry height is too high"))?;
+    let builder_height = expiry_height
+        .checked_sub(DEFAULT_TX_EXPIRY_DELTA)
+        .map(BlockHeight::from_u32)
+        .ok_or_else(|| invalid_argument("expiry_delta", "the expiry height is too low"))?;
+
+    if BranchId::for_height(&params, builder_height) != BranchId::for_height(&params, target_height)
+    {
+        return Err(invalid_argument(
+            "expiry_delta",
+            format!(
+                "a transaction targeting height {} and expiring at {} would span a network upgrade",
+                target_height, expiry_height
+            ),
+        ));
+    }
+    Ok(builder_height)
+}
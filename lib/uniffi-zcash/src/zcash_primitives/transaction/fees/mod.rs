@@ -1,5 +1,24 @@
+use std::sync::Arc;
+
 mod fixed;
 pub use self::fixed::*;
 
 mod zip317;
 pub use self::zip317::*;
+
+/// The fee rule of the transactions, for picking it at runtime, as when selecting their
+/// inputs with a [`crate::ZcashGreedyInputSelector`].
+pub enum ZcashFeeRule {
+    Fixed(ZcashFixedFeeRule),
+    Zip317(ZcashZip317FeeRule),
+}
+
+impl ZcashFeeRule {
+    pub fn fixed(fee_rule: Arc<ZcashFixedFeeRule>) -> Self {
+        Self::Fixed((*fee_rule).clone())
+    }
+
+    pub fn zip317(fee_rule: Arc<ZcashZip317FeeRule>) -> Self {
+        Self::Zip317((*fee_rule).clone())
+    }
+}
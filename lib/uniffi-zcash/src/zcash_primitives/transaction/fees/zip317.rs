@@ -1,8 +1,11 @@
+use std::cmp::max;
 use std::sync::Arc;
 
+use prost::Message;
+use zcash_client_backend::proto::compact_formats::CompactBlock;
 use zcash_primitives::transaction::fees::zip317::FeeRule;
 
-use crate::{ZcashAmount, ZcashResult};
+use crate::{ZcashAmount, ZcashError, ZcashResult};
 
 use derive_more::{From, Into};
 
@@ -42,4 +45,83 @@ pub fn non_standard(
     pub fn marginal_fee(&self) -> Arc<ZcashAmount> {
         Arc::new(self.0.marginal_fee().into())
     }
+
+    /// Computes the fee of the planned transaction before proposing it, telling whether
+    /// the conventional fee, the one of transactions within the grace actions, suffices.
+    ///
+    /// The given recent blocks, encoded compact blocks like the ones in the block cache,
+    /// are summarized for comparison. Compact blocks carry no transparent data, so only
+    /// the shielded logical actions of their transactions are accounted.
+    pub fn estimate_fee(
+        &self,
+        planned: ZcashTransactionShape,
+        recent_blocks: Vec<Vec<u8>>,
+    ) -> ZcashResult<ZcashFeeEstimate> {
+        let marginal_fee = i64::from(self.0.marginal_fee()) as u64;
+        let grace_actions = self.0.grace_actions() as u64;
+
+        let logical_actions = max(
+            (planned.transparent_inputs as usize * 150)
+                .div_ceil(self.0.p2pkh_standard_input_size()),
+            (planned.transparent_outputs as usize * 34)
+                .div_ceil(self.0.p2pkh_standard_output_size()),
+        ) as u64
+            + max(planned.sapling_spends, planned.sapling_outputs) as u64
+            + planned.orchard_actions as u64;
+
+        let mut recent_transactions = 0u32;
+        let mut recent_logical_actions = 0u64;
+        let mut recent_above_grace = 0u32;
+        for bytes in &recent_blocks {
+            let block = CompactBlock::decode(&bytes[..]).map_err(|e| ZcashError::Message {
+                error: format!("Invalid CompactBlock: {}", e),
+            })?;
+            for tx in &block.vtx {
+                let actions = (max(tx.spends.len(), tx.outputs.len()) + tx.actions.len()) as u64;
+                recent_transactions += 1;
+                recent_logical_actions += actions;
+                if actions > grace_actions {
+                    recent_above_grace += 1;
+                }
+            }
+        }
+
+        Ok(ZcashFeeEstimate {
+            conventional_fee: marginal_fee * grace_actions,
+            fee: marginal_fee * max(grace_actions, logical_actions),
+            logical_actions: logical_actions as u32,
+            conventional_fee_suffices: logical_actions <= grace_actions,
+            recent_transactions,
+            recent_mean_logical_actions: match recent_transactions {
+                0 => 0.0,
+                n => recent_logical_actions as f64 / n as f64,
+            },
+            recent_above_grace_actions: recent_above_grace,
+        })
+    }
+}
+
+/// The inputs and outputs of a planned transaction, see [`ZcashZip317FeeRule::estimate_fee`].
+/// Transparent inputs and outputs are assumed to be P2PKH ones.
+pub struct ZcashTransactionShape {
+    pub transparent_inputs: u32,
+    pub transparent_outputs: u32,
+    pub sapling_spends: u32,
+    pub sapling_outputs: u32,
+    pub orchard_actions: u32,
+}
+
+/// The outcome of [`ZcashZip317FeeRule::estimate_fee`]. Amounts are in zatoshis.
+pub struct ZcashFeeEstimate {
+    pub conventional_fee: u64,
+    /// The fee the planned transaction requires.
+    pub fee: u64,
+    pub logical_actions: u32,
+    pub conventional_fee_suffices: bool,
+    /// How many transactions the recent blocks hold.
+    pub recent_transactions: u32,
+    pub recent_mean_logical_actions: f64,
+    /// How many of the recent transactions exceed the grace actions, paying more than the
+    /// conventional fee.
+    pub recent_above_grace_actions: u32,
 }
@@ -1,19 +1,35 @@
 mod components;
 pub use self::components::*;
 
+mod expiry;
+pub use self::expiry::*;
+
 mod fees;
 pub use self::fees::*;
 
+mod p2sh;
+pub use self::p2sh::*;
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
 use std::sync::{Arc, RwLock};
 
 use derive_more::{From, Into};
 
-use hdwallet::rand_core::OsRng;
+use hdwallet::rand_core::{CryptoRng, OsRng, RngCore};
 use orchard::{
     builder::{InProgress, Unauthorized, Unproven},
     keys::{SpendAuthorizingKey, SpendingKey},
 };
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use zcash_client_backend::keys::UnifiedFullViewingKey;
+use zcash_primitives::memo::MemoBytes;
+use zcash_primitives::sapling::note_encryption::{
+    try_sapling_note_decryption, PreparedIncomingViewingKey,
+};
 use zcash_primitives::transaction::TxId;
+use zcash_primitives::zip32::Scope;
 use zcash_primitives::{
     consensus::BranchId,
     transaction::{
@@ -32,11 +48,16 @@
     ZcashLocalTxProver, ZcashMemoBytes, ZcashOrchardAddress, ZcashOrchardFullViewingKey,
     ZcashOrchardMerklePath, ZcashOrchardNote, ZcashOrchardOutgoingViewingKey,
     ZcashOrchardSpendingKey, ZcashOutgoingViewingKey, ZcashPaymentAddress, ZcashResult,
-    ZcashSaplingMerklePath, ZcashSaplingNote, ZcashTransparentAddress,
+    ZcashSaplingMerklePath, ZcashSaplingNote, ZcashTransparentAddress, ZcashUnifiedFullViewingKey,
 };
 
 pub use self::components::*;
 
+/// The sizes ZIP 317 counts transparent inputs and outputs in, those of the standard
+/// P2PKH ones.
+const P2PKH_STANDARD_INPUT_SIZE: usize = 150;
+const P2PKH_STANDARD_OUTPUT_SIZE: usize = 34;
+
 pub struct ZcashTransactionBuilder {
     parameters: ZcashConsensusParameters,
     target_height: Arc<ZcashBlockHeight>,
@@ -44,6 +65,8 @@ pub struct ZcashTransactionBuilder {
     sapling_outputs: SaplingOutputs,
     transparent_input: TransparentInput,
     transparent_output: TransparentOutput,
+    expiry_delta: RwLock<u32>,
+    rng_seed: Option<[u8; 32]>,
 }
 
 impl ZcashTransactionBuilder {
@@ -55,9 +78,45 @@ pub fn new(parameters: ZcashConsensusParameters, target_height: Arc<ZcashBlockHe
             sapling_outputs: RwLock::new(Vec::new()),
             transparent_input: RwLock::new(Vec::new()),
             transparent_output: RwLock::new(Vec::new()),
+            expiry_delta: RwLock::new(DEFAULT_TX_EXPIRY_DELTA),
+            rng_seed: None,
         }
     }
 
+    /// Creates a builder drawing its randomness from the given 32 bytes seed instead of
+    /// the operating system, so that the same inputs build the same transaction, as
+    /// golden file tests need. The Sapling proofs are still randomized by the prover, so
+    /// transactions with Sapling parts only keep the same id, which does not cover them.
+    ///
+    /// WARNING: this is for tests only. Transactions built with a known seed reveal
+    /// their secrets, like the randomness of their notes, to whoever knows it.
+    pub fn test_only_with_rng_seed(
+        parameters: ZcashConsensusParameters,
+        target_height: Arc<ZcashBlockHeight>,
+        rng_seed: Vec<u8>,
+    ) -> ZcashResult<Self> {
+        Ok(Self {
+            rng_seed: Some(cast_slice("rng_seed", &rng_seed)?),
+            ..Self::new(parameters, target_height)
+        })
+    }
+
+    /// Makes the transaction expire the given count of blocks after its target height,
+    /// instead of the [`DEFAULT_TX_EXPIRY_DELTA`], like for batches of payments which may
+    /// take longer to be mined.
+    pub fn set_expiry_delta(&self, expiry_delta: u32) -> ZcashResult<()> {
+        builder_target_height(self.parameters, (*self.target_height).into(), expiry_delta)?;
+        *self.expiry_delta.write().unwrap() = expiry_delta;
+        Ok(())
+    }
+
+    /// The height the transaction will expire at.
+    pub fn expiry_height(&self) -> u32 {
+        self.target_height
+            .value()
+            .saturating_add(*self.expiry_delta.read().unwrap())
+    }
+
     pub fn add_sapling_spend(
         &self,
         extsk: Arc<ZcashExtendedSpendingKey>,
@@ -109,8 +168,24 @@ pub fn build(
         prover: Arc<ZcashLocalTxProver>,
         fee_rule: ZcashFeeRules,
     ) -> ZcashResult<ZcashTransactionAndSaplingMetadata> {
-        // CHANGED
-        let mut builder = Builder::new(self.parameters, (*self.target_height).into(), None);
+        match self.rng_seed {
+            Some(seed) => self.build_with_rng(prover, fee_rule, ChaCha20Rng::from_seed(seed)),
+            None => self.build_with_rng(prover, fee_rule, OsRng),
+        }
+    }
+
+    fn build_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        prover: Arc<ZcashLocalTxProver>,
+        fee_rule: ZcashFeeRules,
+        rng: R,
+    ) -> ZcashResult<ZcashTransactionAndSaplingMetadata> {
+        let target_height = builder_target_height(
+            self.parameters,
+            (*self.target_height).into(),
+            *self.expiry_delta.read().unwrap(),
+        )?;
+        let mut builder = Builder::new_with_rng(self.parameters, target_height, None, rng);
 
         self.sapling_spends.read().unwrap().iter().try_for_each(
             |(extsk, diversifier, note, merkle_path)| {
@@ -160,7 +235,7 @@ pub fn build(
             ZcashFeeRules::FixedStandard => {
                 let fee = zcash_primitives::transaction::fees::zip317::FeeRule::standard();
                 let result = builder
-                    .build(&prover.internal, &fee)
+                    .build(prover.as_ref(), &fee)
                     .map_err(ZcashError::from)?;
                 Ok(result.into())
             }
@@ -168,14 +243,14 @@ pub fn build(
                 let amount = Amount::from_u64(amount).or(Err("Error parsing amount"))?;
                 let fee = zcash_primitives::transaction::fees::fixed::FeeRule::non_standard(amount);
                 let result = builder
-                    .build(&prover.internal, &fee)
+                    .build(prover.as_ref(), &fee)
                     .map_err(ZcashError::from)?;
                 Ok(result.into())
             }
             ZcashFeeRules::Zip317Standard => {
                 let fee = zcash_primitives::transaction::fees::zip317::FeeRule::standard();
                 let result = builder
-                    .build(&prover.internal, &fee)
+                    .build(prover.as_ref(), &fee)
                     .map_err(ZcashError::from)?;
                 Ok(result.into())
             }
@@ -195,7 +270,7 @@ pub fn build(
                     None => return Err("p2pkh_standard_input_size and p2pkh_standard_output_size should not be zero".into()),
                 };
                 let result = builder
-                    .build(&prover.internal, &fee)
+                    .build(prover.as_ref(), &fee)
                     .map_err(ZcashError::from)?;
                 Ok(result.into())
             }
@@ -267,6 +342,35 @@ pub fn from_bytes(
         Ok(tx.into())
     }
 
+    /// Same as [`Self::to_bytes`], but exported as a native byte buffer
+    /// (`ByteArray`, `Data`, `bytes` ...) instead of a list of bytes.
+    pub fn to_byte_buffer(&self) -> ZcashResult<Vec<u8>> {
+        self.to_bytes()
+    }
+
+    /// Same as [`Self::from_bytes`], but taking a native byte buffer.
+    pub fn from_byte_buffer(
+        data: &[u8],
+        consensus_branch_id: ZcashBranchId,
+    ) -> ZcashResult<ZcashTransaction> {
+        Self::from_bytes(data, consensus_branch_id)
+    }
+
+    /// Reads the transaction from a file, so its bytes do not cross the FFI at all.
+    pub fn from_file(path: &str, consensus_branch_id: ZcashBranchId) -> ZcashResult<Self> {
+        let file = BufReader::new(File::open(path)?);
+        let tx = Transaction::read(file, consensus_branch_id.into())?;
+        Ok(tx.into())
+    }
+
+    /// Writes the transaction to a file, so its bytes do not cross the FFI at all.
+    pub fn write_to_file(&self, path: &str) -> ZcashResult<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.0.write(&mut file)?;
+        file.flush()?;
+        Ok(())
+    }
+
     pub fn txid(&self) -> Arc<ZcashTxId> {
         Arc::new(self.0.txid().into())
     }
@@ -287,6 +391,52 @@ pub fn expiry_height(&self) -> Arc<ZcashBlockHeight> {
         Arc::new(self.0.expiry_height().into())
     }
 
+    /// Returns the size of the serialized transaction, in bytes, for checking it against
+    /// size limits before broadcasting it.
+    pub fn serialized_size(&self) -> ZcashResult<u64> {
+        Ok(self.to_bytes()?.len() as u64)
+    }
+
+    /// Returns the number of logical actions of the transaction, as ZIP 317 counts them to
+    /// compute the conventional fee: the transparent inputs and outputs in units of their
+    /// standard P2PKH sizes, the larger of the Sapling spend and output counts, the Orchard
+    /// actions, and two per Sprout JoinSplit.
+    pub fn logical_action_count(&self) -> ZcashResult<u32> {
+        let (t_in_size, t_out_size) = match self.0.transparent_bundle() {
+            Some(bundle) => {
+                let mut vin = Vec::new();
+                for txin in &bundle.vin {
+                    txin.write(&mut vin)?;
+                }
+                let mut vout = Vec::new();
+                for txout in &bundle.vout {
+                    txout.write(&mut vout)?;
+                }
+                (vin.len(), vout.len())
+            }
+            None => (0, 0),
+        };
+        let transparent = t_in_size
+            .div_ceil(P2PKH_STANDARD_INPUT_SIZE)
+            .max(t_out_size.div_ceil(P2PKH_STANDARD_OUTPUT_SIZE));
+        let sprout = self
+            .0
+            .sprout_bundle()
+            .map_or(0, |bundle| 2 * bundle.joinsplits.len());
+        let sapling = self.0.sapling_bundle().map_or(0, |bundle| {
+            bundle
+                .shielded_spends()
+                .len()
+                .max(bundle.shielded_outputs().len())
+        });
+        let orchard = self
+            .0
+            .orchard_bundle()
+            .map_or(0, |bundle| bundle.actions().len());
+
+        Ok((transparent + sprout + sapling + orchard) as u32)
+    }
+
     /// Returns the total fees paid by the transaction, given a function that can be used to
     /// retrieve the value of previous transactions' transparent outputs that are being spent in
     /// this transaction.
@@ -307,6 +457,53 @@ pub fn sapling_bundle(&self) -> Option<Arc<ZcashSaplingBundle>> {
     pub fn orchard_bundle(&self) -> Option<Arc<ZcashOrchardBundle>> {
         self.0.orchard_bundle().map(From::from).map(Arc::new)
     }
+
+    /// Decrypts the memos of the outputs received by the given viewing key, on its
+    /// external or internal addresses, without the full decryption of the transaction
+    /// [`crate::decrypt_transaction`] does, i.e. for showing the memo of a transaction
+    /// on demand. The Sapling memos come first, then the Orchard ones, each in the order
+    /// of the outputs, while empty memos are left out. The height is the one the
+    /// transaction was mined at, or the next one for unmined ones.
+    pub fn decrypt_memos_with_ufvk(
+        &self,
+        params: ZcashConsensusParameters,
+        height: Arc<ZcashBlockHeight>,
+        ufvk: Arc<ZcashUnifiedFullViewingKey>,
+    ) -> Vec<Arc<ZcashMemoBytes>> {
+        let ufvk: UnifiedFullViewingKey = (*ufvk).clone().into();
+        let mut memos = Vec::new();
+
+        if let (Some(bundle), Some(dfvk)) = (self.0.sapling_bundle(), ufvk.sapling()) {
+            let ivks = [Scope::External, Scope::Internal]
+                .map(|scope| PreparedIncomingViewingKey::new(&dfvk.to_ivk(scope)));
+            memos.extend(bundle.shielded_outputs().iter().filter_map(|output| {
+                ivks.iter().find_map(|ivk| {
+                    try_sapling_note_decryption(&params, height.as_ref().into(), ivk, output)
+                        .map(|(_, _, memo)| memo)
+                })
+            }));
+        }
+
+        if let (Some(bundle), Some(fvk)) = (self.0.orchard_bundle(), ufvk.orchard()) {
+            let ivks = [
+                orchard::keys::Scope::External,
+                orchard::keys::Scope::Internal,
+            ]
+            .map(|scope| fvk.to_ivk(scope));
+            memos.extend(
+                bundle
+                    .decrypt_outputs_with_keys(&ivks)
+                    .into_iter()
+                    .filter_map(|(_, _, _, _, memo)| MemoBytes::from_bytes(&memo).ok()),
+            );
+        }
+
+        memos
+            .into_iter()
+            .filter(|memo| *memo != MemoBytes::empty())
+            .map(|memo| Arc::new(memo.into()))
+            .collect()
+    }
 }
 
 impl<'a> From<&'a ZcashTransaction> for &'a Transaction {
@@ -318,11 +515,14 @@ fn from(inner: &'a ZcashTransaction) -> Self {
 pub struct ZcashTransactionAndSaplingMetadata {
     pub transaction: Arc<ZcashTransaction>,
     pub sapling_metadata: Arc<ZcashSaplingMetadata>,
+    /// The height the transaction expires at.
+    pub expiry_height: u32,
 }
 
 impl From<(Transaction, SaplingMetadata)> for ZcashTransactionAndSaplingMetadata {
     fn from((transaction, sapling_metadata): (Transaction, SaplingMetadata)) -> Self {
         ZcashTransactionAndSaplingMetadata {
+            expiry_height: transaction.expiry_height().into(),
             transaction: Arc::new(transaction.into()),
             sapling_metadata: Arc::new(sapling_metadata.into()),
         }
@@ -334,7 +534,7 @@ fn from((transaction, sapling_metadata): (Transaction, SaplingMetadata)) -> Self
 
 impl ZcashTxId {
     pub fn from_bytes(data: &[u8]) -> ZcashResult<Self> {
-        Ok(TxId::from_bytes(cast_slice(data)?).into())
+        Ok(TxId::from_bytes(cast_slice("data", data)?).into())
     }
 
     pub fn to_bytes(&self) -> ZcashResult<Vec<u8>> {
@@ -350,6 +550,12 @@ pub fn to_hex_string(&self) -> ZcashResult<String> {
         data.reverse();
         Ok(hex::encode(data))
     }
+
+    /// Compares the txids by their bytes, for sorting them or keeping them in ordered sets.
+    /// The order is arbitrary, like that of hashes, and unrelated to when they were mined.
+    pub fn compare(&self, other: &ZcashTxId) -> i8 {
+        self.cmp(other) as i8
+    }
 }
 
 pub struct ZcashOrchardTransactionBuilder {
@@ -360,6 +566,7 @@ pub struct ZcashOrchardTransactionBuilder {
     flags: Arc<ZcashOrchardFlags>,
     spends: OrchardSpends,
     outputs: OrchardOutputs,
+    rng_seed: Option<[u8; 32]>,
 }
 
 impl ZcashOrchardTransactionBuilder {
@@ -378,9 +585,30 @@ pub fn new(
             flags,
             spends: RwLock::new(Vec::new()),
             outputs: RwLock::new(Vec::new()),
+            rng_seed: None,
         }
     }
 
+    /// Creates a builder drawing its randomness from the given 32 bytes seed instead of
+    /// the operating system, so that the same inputs build the same transaction byte for
+    /// byte, proof included, as golden file tests need.
+    ///
+    /// WARNING: this is for tests only. Transactions built with a known seed reveal
+    /// their secrets, like the randomness of their notes, to whoever knows it.
+    pub fn test_only_with_rng_seed(
+        parameters: ZcashConsensusParameters,
+        target_height: Arc<ZcashBlockHeight>,
+        expiry_height: Arc<ZcashBlockHeight>,
+        anchor: Arc<ZcashAnchor>,
+        flags: Arc<ZcashOrchardFlags>,
+        rng_seed: Vec<u8>,
+    ) -> ZcashResult<Self> {
+        Ok(Self {
+            rng_seed: Some(cast_slice("rng_seed", &rng_seed)?),
+            ..Self::new(parameters, target_height, expiry_height, anchor, flags)
+        })
+    }
+
     pub fn add_spend(
         &self,
         fvk: Arc<ZcashOrchardFullViewingKey>,
@@ -398,7 +626,7 @@ pub fn add_recipient(
         memo: Option<Vec<u8>>,
     ) -> ZcashResult<()> {
         let m = match memo {
-            Some(m) => Some(cast_slice(m.as_slice())?),
+            Some(m) => Some(cast_slice("memo", m.as_slice())?),
             None => None,
         };
 
@@ -413,6 +641,18 @@ pub fn build(
         &self,
         keys: Vec<Arc<ZcashOrchardSpendingKey>>,
         sighash: Vec<u8>,
+    ) -> ZcashResult<Arc<ZcashTransaction>> {
+        match self.rng_seed {
+            Some(seed) => self.build_with_rng(keys, sighash, ChaCha20Rng::from_seed(seed)),
+            None => self.build_with_rng(keys, sighash, OsRng),
+        }
+    }
+
+    fn build_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        keys: Vec<Arc<ZcashOrchardSpendingKey>>,
+        sighash: Vec<u8>,
+        mut rng: R,
     ) -> ZcashResult<Arc<ZcashTransaction>> {
         let mut builder =
             orchard::builder::Builder::new(self.flags.as_ref().into(), self.anchor.as_ref().into());
@@ -443,11 +683,11 @@ pub fn build(
             })?;
 
         let bundle: orchard::Bundle<InProgress<Unproven, Unauthorized>, Amount> =
-            builder.build(OsRng).unwrap();
+            builder.build(&mut rng).unwrap();
 
         let pk = orchard::circuit::ProvingKey::build();
-        let casted_sighash: [u8; 32] = cast_slice(sighash.as_slice())?;
-        let proved_bundle = bundle.create_proof(&pk, OsRng)?;
+        let casted_sighash: [u8; 32] = cast_slice("sighash", sighash.as_slice())?;
+        let proved_bundle = bundle.create_proof(&pk, &mut rng)?;
 
         let inner_keys = keys
             .iter()
@@ -459,7 +699,7 @@ pub fn build(
             .collect::<Vec<SpendAuthorizingKey>>();
 
         let authorized_bundle =
-            proved_bundle.apply_signatures(OsRng, casted_sighash, inner_keys.as_slice())?;
+            proved_bundle.apply_signatures(rng, casted_sighash, inner_keys.as_slice())?;
 
         let consensus_branch_id =
             BranchId::for_height(&self.parameters, self.target_height.as_ref().into());
@@ -0,0 +1,267 @@
+use std::sync::{Arc, RwLock};
+
+use zcash_primitives::consensus::{BlockHeight, BranchId};
+use zcash_primitives::legacy::{Script, TransparentAddress};
+use zcash_primitives::transaction::components::{
+    sapling, transparent, Amount, OutPoint, TxIn, TxOut,
+};
+use zcash_primitives::transaction::sighash::{
+    signature_hash, SignableInput, TransparentAuthorizingContext, SIGHASH_ALL,
+};
+use zcash_primitives::transaction::txid::TxIdDigester;
+use zcash_primitives::transaction::{self, TransactionData, TxVersion};
+
+use crate::{
+    builder_target_height, ZcashAmount, ZcashBlockHeight, ZcashConsensusParameters, ZcashError,
+    ZcashOutPoint, ZcashResult, ZcashTransaction, ZcashTransparentAddress, ZcashTxOut,
+    DEFAULT_TX_EXPIRY_DELTA,
+};
+
+/// The sequence number of the inputs, which have no relative lock time.
+const INPUT_SEQUENCE: u32 = 0xffff_ffff;
+
+/// A pay-to-script-hash output spent by a [`ZcashP2shTransactionBuilder`].
+struct P2shInput {
+    outpoint: OutPoint,
+    coin: TxOut,
+    redeem_script: Script,
+    /// The data pushed before the redeem script in the `script_sig`, once signed.
+    signatures: Option<Vec<Vec<u8>>>,
+}
+
+/// The transparent part of a transaction whose inputs are not signed yet, with what the
+/// ZIP 244 signature hashes commit to of the outputs they spend.
+#[derive(Debug)]
+struct UnsignedInputs {
+    coins: Vec<TxOut>,
+}
+
+impl transparent::Authorization for UnsignedInputs {
+    type ScriptSig = ();
+}
+
+impl TransparentAuthorizingContext for UnsignedInputs {
+    fn input_amounts(&self) -> Vec<Amount> {
+        self.coins.iter().map(|coin| coin.value).collect()
+    }
+
+    fn input_scriptpubkeys(&self) -> Vec<Script> {
+        self.coins
+            .iter()
+            .map(|coin| coin.script_pubkey.clone())
+            .collect()
+    }
+}
+
+struct Unsigned;
+
+impl transaction::Authorization for Unsigned {
+    type TransparentAuth = UnsignedInputs;
+    type SaplingAuth = sapling::Authorized;
+    type OrchardAuth = orchard::bundle::Authorized;
+}
+
+/// Builds transparent transactions spending pay-to-script-hash outputs, like those of
+/// the multisig addresses watched with [`ZcashWalletDb::watch_p2sh_script`], with
+/// signatures made out of the wallet, as by the cosigners or a hardware security module.
+///
+/// The inputs and outputs are added first, then the signature hash of each input is
+/// handed to its signers, and their signatures are set back before building. The
+/// signatures commit to all the inputs and outputs, so any change to them after signing
+/// makes them invalid. The fee is what the inputs have in excess of the outputs.
+///
+/// [`ZcashWalletDb::watch_p2sh_script`]: crate::ZcashWalletDb::watch_p2sh_script
+pub struct ZcashP2shTransactionBuilder {
+    parameters: ZcashConsensusParameters,
+    target_height: BlockHeight,
+    expiry_height: BlockHeight,
+    inputs: RwLock<Vec<P2shInput>>,
+    outputs: RwLock<Vec<TxOut>>,
+}
+
+impl ZcashP2shTransactionBuilder {
+    /// Creates a builder of a transaction targeting the given height, to expire
+    /// `expiry_delta` blocks after it, by default the [`DEFAULT_TX_EXPIRY_DELTA`].
+    pub fn new(
+        parameters: ZcashConsensusParameters,
+        target_height: Arc<ZcashBlockHeight>,
+        expiry_delta: Option<u32>,
+    ) -> ZcashResult<Self> {
+        let target_height: BlockHeight = (*target_height).into();
+        let expiry_delta = expiry_delta.unwrap_or(DEFAULT_TX_EXPIRY_DELTA);
+        builder_target_height(parameters, target_height, expiry_delta)?;
+        Ok(Self {
+            parameters,
+            target_height,
+            expiry_height: target_height + expiry_delta,
+            inputs: RwLock::new(Vec::new()),
+            outputs: RwLock::new(Vec::new()),
+        })
+    }
+
+    /// Adds an input spending `coin`, the output at `outpoint` paying to the
+    /// pay-to-script-hash address of `redeem_script`.
+    pub fn add_input(
+        &self,
+        outpoint: Arc<ZcashOutPoint>,
+        coin: Arc<ZcashTxOut>,
+        redeem_script: Vec<u8>,
+    ) -> ZcashResult<()> {
+        let coin: TxOut = coin.as_ref().into();
+        let address: TransparentAddress =
+            ZcashTransparentAddress::from_redeem_script(redeem_script.clone()).into();
+        if coin.script_pubkey != address.script() {
+            return Err("The output does not pay to the address of the redeem script".into());
+        }
+        self.inputs.write().unwrap().push(P2shInput {
+            outpoint: (*outpoint).clone().into(),
+            coin,
+            redeem_script: Script(redeem_script),
+            signatures: None,
+        });
+        Ok(())
+    }
+
+    pub fn add_output(
+        &self,
+        to: Arc<ZcashTransparentAddress>,
+        value: Arc<ZcashAmount>,
+    ) -> ZcashResult<()> {
+        let value: Amount = value.as_ref().into();
+        if value.is_negative() {
+            return Err("The value of outputs cannot be negative".into());
+        }
+        let to: TransparentAddress = to.as_ref().into();
+        self.outputs.write().unwrap().push(TxOut {
+            value,
+            script_pubkey: to.script(),
+        });
+        Ok(())
+    }
+
+    /// The fee the transaction pays, in zatoshis.
+    pub fn fee(&self) -> ZcashResult<u64> {
+        let inputs = self.inputs.read().unwrap();
+        let input_value = sum(inputs.iter().map(|input| input.coin.value))?;
+        let output_value = sum(self.outputs.read().unwrap().iter().map(|txout| txout.value))?;
+        (input_value - output_value)
+            .filter(|fee| !fee.is_negative())
+            .map(u64::from)
+            .ok_or_else(|| "The outputs exceed the inputs".into())
+    }
+
+    /// The hash the signers of the input at `index` sign with `SIGHASH_ALL`, as of
+    /// ZIP 244, or ZIP 243 for the networks before NU5.
+    pub fn signature_hash(&self, index: u32) -> ZcashResult<Vec<u8>> {
+        let inputs = self.inputs.read().unwrap();
+        let input = inputs
+            .get(index as usize)
+            .ok_or("There is no input at this index")?;
+        let tx = self.unsigned_transaction(&inputs);
+        let txid_parts = tx.digest(TxIdDigester);
+        let sighash = signature_hash(
+            &tx,
+            &SignableInput::Transparent {
+                hash_type: SIGHASH_ALL,
+                index: index as usize,
+                script_code: &input.redeem_script,
+                script_pubkey: &input.coin.script_pubkey,
+                value: input.coin.value,
+            },
+            &txid_parts,
+        );
+        Ok(sighash.as_ref().to_vec())
+    }
+
+    /// Sets what the `script_sig` of the input at `index` pushes before its redeem script:
+    /// for a multisig redeem script, an empty value, which `OP_CHECKMULTISIG` consumes,
+    /// followed by the signatures in the order of their public keys in the script. The
+    /// signatures are DER encoded with the `SIGHASH_ALL` byte appended, as for any
+    /// transparent input.
+    pub fn set_signatures(&self, index: u32, signatures: Vec<Vec<u8>>) -> ZcashResult<()> {
+        let mut inputs = self.inputs.write().unwrap();
+        let input = inputs
+            .get_mut(index as usize)
+            .ok_or("There is no input at this index")?;
+        input.signatures = Some(signatures);
+        Ok(())
+    }
+
+    /// Builds the transaction, once the signatures of all the inputs are set.
+    pub fn build(&self) -> ZcashResult<Arc<ZcashTransaction>> {
+        self.fee()?;
+        let inputs = self.inputs.read().unwrap();
+        let vin = inputs
+            .iter()
+            .enumerate()
+            .map(|(index, input)| {
+                let signatures = input
+                    .signatures
+                    .as_ref()
+                    .ok_or_else(|| ZcashError::Message {
+                        error: format!("The input at {} is not signed", index),
+                    })?;
+                let script_sig = signatures
+                    .iter()
+                    .fold(Script::default(), |script, data| script << &data[..])
+                    << &input.redeem_script.0[..];
+                Ok(TxIn {
+                    prevout: input.outpoint.clone(),
+                    script_sig,
+                    sequence: INPUT_SEQUENCE,
+                })
+            })
+            .collect::<ZcashResult<Vec<_>>>()?;
+
+        let branch_id = BranchId::for_height(&self.parameters, self.target_height);
+        let tx = TransactionData::<transaction::Authorized>::from_parts(
+            TxVersion::suggested_for_branch(branch_id),
+            branch_id,
+            0,
+            self.expiry_height,
+            Some(transparent::Bundle {
+                vin,
+                vout: self.outputs.read().unwrap().clone(),
+                authorization: transparent::Authorized,
+            }),
+            None,
+            None,
+            None,
+        )
+        .freeze()?;
+        Ok(Arc::new(tx.into()))
+    }
+
+    fn unsigned_transaction(&self, inputs: &[P2shInput]) -> TransactionData<Unsigned> {
+        let branch_id = BranchId::for_height(&self.parameters, self.target_height);
+        TransactionData::from_parts(
+            TxVersion::suggested_for_branch(branch_id),
+            branch_id,
+            0,
+            self.expiry_height,
+            Some(transparent::Bundle {
+                vin: inputs
+                    .iter()
+                    .map(|input| TxIn {
+                        prevout: input.outpoint.clone(),
+                        script_sig: (),
+                        sequence: INPUT_SEQUENCE,
+                    })
+                    .collect(),
+                vout: self.outputs.read().unwrap().clone(),
+                authorization: UnsignedInputs {
+                    coins: inputs.iter().map(|input| input.coin.clone()).collect(),
+                },
+            }),
+            None,
+            None,
+            None,
+        )
+    }
+}
+
+fn sum(amounts: impl Iterator<Item = Amount>) -> ZcashResult<Amount> {
+    amounts
+        .sum::<Option<Amount>>()
+        .ok_or_else(|| "Amount overflow".into())
+}
@@ -0,0 +1,102 @@
+use group::GroupEncoding;
+use hdwallet::rand_core::OsRng;
+use zcash_primitives::constants::{SPENDING_KEY_GENERATOR, VALUE_COMMITMENT_RANDOMNESS_GENERATOR};
+use zcash_primitives::sapling::prover::TxProver;
+use zcash_primitives::sapling::redjubjub::{PublicKey, Signature};
+use zcash_primitives::sapling::value::{
+    NoteValue, TrapdoorSum, ValueCommitTrapdoor, ValueCommitment,
+};
+use zcash_primitives::sapling::{
+    Diversifier, MerklePath, PaymentAddress, ProofGenerationKey, Rseed,
+};
+use zcash_primitives::transaction::components::{Amount, GROTH_PROOF_SIZE};
+
+use std::sync::Arc;
+
+use crate::ZcashLocalTxProver;
+
+/// A Sapling prover for tests, which does not need the Sapling parameters and takes no
+/// time: the proofs it makes are all zeros, and so invalid. The rest of the transaction is
+/// as with a real prover, value commitments and binding signature included, so it goes
+/// through the whole spend pipeline of the wallet. Only part of the library built with the
+/// `mock-prover` feature, as nodes reject the transactions it proves.
+///
+/// Unlike the upstream `MockTxProver`, it can sign the transactions it proves.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ZcashMockTxProver;
+
+impl ZcashMockTxProver {
+    pub fn new() -> Self {
+        ZcashMockTxProver
+    }
+
+    /// A prover proving with this one, so that tests can pass it to every API taking a
+    /// prover, like the spend functions.
+    pub fn to_local_prover(&self) -> Arc<ZcashLocalTxProver> {
+        Arc::new(ZcashLocalTxProver::from_mock(*self))
+    }
+}
+
+impl TxProver for ZcashMockTxProver {
+    /// The sum of the value commitment trapdoors, which the binding signature is made with.
+    type SaplingProvingContext = TrapdoorSum;
+
+    fn new_sapling_proving_context(&self) -> Self::SaplingProvingContext {
+        TrapdoorSum::zero()
+    }
+
+    fn spend_proof(
+        &self,
+        ctx: &mut Self::SaplingProvingContext,
+        proof_generation_key: ProofGenerationKey,
+        _diversifier: Diversifier,
+        _rseed: Rseed,
+        ar: jubjub::Fr,
+        value: u64,
+        _anchor: bls12_381::Scalar,
+        _merkle_path: MerklePath,
+    ) -> Result<([u8; GROTH_PROOF_SIZE], ValueCommitment, PublicKey), ()> {
+        let rcv = ValueCommitTrapdoor::random(OsRng);
+        *ctx += &rcv;
+        let cv = ValueCommitment::derive(NoteValue::from_raw(value), rcv);
+        let rk = PublicKey(proof_generation_key.ak.into()).randomize(ar, SPENDING_KEY_GENERATOR);
+
+        Ok(([0u8; GROTH_PROOF_SIZE], cv, rk))
+    }
+
+    fn output_proof(
+        &self,
+        ctx: &mut Self::SaplingProvingContext,
+        _esk: jubjub::Fr,
+        _payment_address: PaymentAddress,
+        _rcm: jubjub::Fr,
+        value: u64,
+    ) -> ([u8; GROTH_PROOF_SIZE], ValueCommitment) {
+        let rcv = ValueCommitTrapdoor::random(OsRng);
+        *ctx -= &rcv;
+        let cv = ValueCommitment::derive(NoteValue::from_raw(value), rcv);
+
+        ([0u8; GROTH_PROOF_SIZE], cv)
+    }
+
+    fn binding_sig(
+        &self,
+        ctx: &mut Self::SaplingProvingContext,
+        _value_balance: Amount,
+        sighash: &[u8; 32],
+    ) -> Result<Signature, ()> {
+        let bsk = ctx.into_bsk();
+        let bvk = PublicKey::from_private(&bsk, VALUE_COMMITMENT_RANDOMNESS_GENERATOR);
+
+        // As the real prover, it signs the binding verification key along with the sighash.
+        let mut data_to_be_signed = [0u8; 64];
+        data_to_be_signed[0..32].copy_from_slice(&bvk.0.to_bytes());
+        data_to_be_signed[32..64].copy_from_slice(&sighash[..]);
+
+        Ok(bsk.sign(
+            &data_to_be_signed,
+            &mut OsRng,
+            VALUE_COMMITMENT_RANDOMNESS_GENERATOR,
+        ))
+    }
+}
@@ -1,2 +1,7 @@
+#[cfg(feature = "mock-prover")]
+mod mock;
+#[cfg(feature = "mock-prover")]
+pub use self::mock::*;
+
 mod prover;
 pub use self::prover::*;
@@ -1,14 +1,32 @@
-// use std::sync::Arc;
 use std::path::Path;
 
+use zcash_primitives::sapling::prover::TxProver;
+use zcash_primitives::sapling::redjubjub::{PublicKey, Signature};
+use zcash_primitives::sapling::value::ValueCommitment;
+use zcash_primitives::sapling::{
+    Diversifier, MerklePath, PaymentAddress, ProofGenerationKey, Rseed,
+};
+use zcash_primitives::transaction::components::{Amount, GROTH_PROOF_SIZE};
 use zcash_proofs::prover::LocalTxProver;
+use zcash_proofs::{SAPLING_OUTPUT_NAME, SAPLING_SPEND_NAME};
 
-use crate::ZcashResult;
+#[cfg(feature = "mock-prover")]
+use crate::ZcashMockTxProver;
+use crate::{runtime_config, ZcashResult};
 
 pub struct ZcashLocalTxProver {
-    pub(crate) internal: LocalTxProver,
-    spend_path: String,
-    output_path: String,
+    pub(crate) internal: Prover,
+}
+
+/// The prover behind a [`ZcashLocalTxProver`]: the real one, or the mock one of the tests.
+pub(crate) enum Prover {
+    Local {
+        prover: Box<LocalTxProver>,
+        spend_path: String,
+        output_path: String,
+    },
+    #[cfg(feature = "mock-prover")]
+    Mock(ZcashMockTxProver),
 }
 
 impl ZcashLocalTxProver {
@@ -18,49 +36,176 @@ impl ZcashLocalTxProver {
     /// the expected hashes.
     pub fn new(spend_path: &str, output_path: &str) -> Self {
         Self {
-            internal: LocalTxProver::new(Path::new(spend_path), Path::new(output_path)),
-            spend_path: spend_path.to_string(),
-            output_path: output_path.to_string(),
+            internal: Prover::Local {
+                prover: Box::new(LocalTxProver::new(
+                    Path::new(spend_path),
+                    Path::new(output_path),
+                )),
+                spend_path: spend_path.to_string(),
+                output_path: output_path.to_string(),
+            },
         }
     }
 
     /// Creates a `LocalTxProver` using parameters specified as byte arrays.
     pub fn from_bytes(spend_param_bytes: &[u8], output_param_bytes: &[u8]) -> Self {
-        Self {
-            internal: LocalTxProver::from_bytes(spend_param_bytes, output_param_bytes),
-            spend_path: "".to_string(),
-            output_path: "".to_string(),
-        }
+        LocalTxProver::from_bytes(spend_param_bytes, output_param_bytes).into()
+    }
+
+    /// Same as [`Self::from_bytes`], but taking native byte buffers, which are much cheaper
+    /// to pass than lists of bytes for the tens of megabytes the parameters take.
+    /// Prefer [`Self::new`] when the parameters are available as files.
+    pub fn from_byte_buffers(spend_param_bytes: &[u8], output_param_bytes: &[u8]) -> Self {
+        Self::from_bytes(spend_param_bytes, output_param_bytes)
     }
 
     /// Attempts to create a `LocalTxProver` using parameters from the default local
-    /// location.
+    /// location, the one of the cache directory of `ZcashRuntimeConfig` if set.
     pub fn with_default_location() -> ZcashResult<Self> {
-        match LocalTxProver::with_default_location() {
-            Some(prover) => Ok(prover.into()),
-            None => Err("Parameters cannot be found in default location".into()),
+        let params_dir = runtime_config::params_dir()
+            .ok_or("The default location of the parameters is unknown")?;
+        let spend_path = params_dir.join(SAPLING_SPEND_NAME);
+        let output_path = params_dir.join(SAPLING_OUTPUT_NAME);
+        if !(spend_path.exists() && output_path.exists()) {
+            return Err("Parameters cannot be found in default location".into());
+        }
+        Ok(Self::new(
+            &spend_path.to_string_lossy(),
+            &output_path.to_string_lossy(),
+        ))
+    }
+
+    /// Proves with the given mock prover instead.
+    #[cfg(feature = "mock-prover")]
+    pub(crate) fn from_mock(mock: ZcashMockTxProver) -> Self {
+        Self {
+            internal: Prover::Mock(mock),
         }
     }
 }
 
 impl Clone for ZcashLocalTxProver {
     fn clone(&self) -> Self {
-        Self::new(&self.spend_path, &self.output_path)
+        match &self.internal {
+            Prover::Local {
+                spend_path,
+                output_path,
+                ..
+            } => Self::new(spend_path, output_path),
+            #[cfg(feature = "mock-prover")]
+            Prover::Mock(mock) => Self::from_mock(*mock),
+        }
     }
 }
 
 impl From<LocalTxProver> for ZcashLocalTxProver {
     fn from(inner: LocalTxProver) -> Self {
         ZcashLocalTxProver {
-            internal: inner,
-            spend_path: "".to_string(),
-            output_path: "".to_string(),
+            internal: Prover::Local {
+                prover: Box::new(inner),
+                spend_path: "".to_string(),
+                output_path: "".to_string(),
+            },
         }
     }
 }
 
-impl From<ZcashLocalTxProver> for LocalTxProver {
-    fn from(value: ZcashLocalTxProver) -> Self {
-        value.internal
+/// The proving context of the prover in use.
+pub enum ProvingContext {
+    Local(<LocalTxProver as TxProver>::SaplingProvingContext),
+    #[cfg(feature = "mock-prover")]
+    Mock(<ZcashMockTxProver as TxProver>::SaplingProvingContext),
+}
+
+impl TxProver for ZcashLocalTxProver {
+    type SaplingProvingContext = ProvingContext;
+
+    fn new_sapling_proving_context(&self) -> Self::SaplingProvingContext {
+        match &self.internal {
+            Prover::Local { prover, .. } => {
+                ProvingContext::Local(prover.new_sapling_proving_context())
+            }
+            #[cfg(feature = "mock-prover")]
+            Prover::Mock(mock) => ProvingContext::Mock(mock.new_sapling_proving_context()),
+        }
+    }
+
+    fn spend_proof(
+        &self,
+        ctx: &mut Self::SaplingProvingContext,
+        proof_generation_key: ProofGenerationKey,
+        diversifier: Diversifier,
+        rseed: Rseed,
+        ar: jubjub::Fr,
+        value: u64,
+        anchor: bls12_381::Scalar,
+        merkle_path: MerklePath,
+    ) -> Result<([u8; GROTH_PROOF_SIZE], ValueCommitment, PublicKey), ()> {
+        match (&self.internal, ctx) {
+            (Prover::Local { prover, .. }, ProvingContext::Local(ctx)) => prover.spend_proof(
+                ctx,
+                proof_generation_key,
+                diversifier,
+                rseed,
+                ar,
+                value,
+                anchor,
+                merkle_path,
+            ),
+            #[cfg(feature = "mock-prover")]
+            (Prover::Mock(mock), ProvingContext::Mock(ctx)) => mock.spend_proof(
+                ctx,
+                proof_generation_key,
+                diversifier,
+                rseed,
+                ar,
+                value,
+                anchor,
+                merkle_path,
+            ),
+            #[cfg(feature = "mock-prover")]
+            _ => Err(()),
+        }
+    }
+
+    fn output_proof(
+        &self,
+        ctx: &mut Self::SaplingProvingContext,
+        esk: jubjub::Fr,
+        payment_address: PaymentAddress,
+        rcm: jubjub::Fr,
+        value: u64,
+    ) -> ([u8; GROTH_PROOF_SIZE], ValueCommitment) {
+        match (&self.internal, ctx) {
+            (Prover::Local { prover, .. }, ProvingContext::Local(ctx)) => {
+                prover.output_proof(ctx, esk, payment_address, rcm, value)
+            }
+            #[cfg(feature = "mock-prover")]
+            (Prover::Mock(mock), ProvingContext::Mock(ctx)) => {
+                mock.output_proof(ctx, esk, payment_address, rcm, value)
+            }
+            // Contexts are only made by the prover using them.
+            #[cfg(feature = "mock-prover")]
+            _ => unreachable!("proving context of another prover"),
+        }
+    }
+
+    fn binding_sig(
+        &self,
+        ctx: &mut Self::SaplingProvingContext,
+        value_balance: Amount,
+        sighash: &[u8; 32],
+    ) -> Result<Signature, ()> {
+        match (&self.internal, ctx) {
+            (Prover::Local { prover, .. }, ProvingContext::Local(ctx)) => {
+                prover.binding_sig(ctx, value_balance, sighash)
+            }
+            #[cfg(feature = "mock-prover")]
+            (Prover::Mock(mock), ProvingContext::Mock(ctx)) => {
+                mock.binding_sig(ctx, value_balance, sighash)
+            }
+            #[cfg(feature = "mock-prover")]
+            _ => Err(()),
+        }
     }
 }
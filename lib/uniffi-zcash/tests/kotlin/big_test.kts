@@ -337,7 +337,7 @@ fun testOrchardSpendingKeyArrayMismatch() {
     var thrown = false;
     try {
         ZcashOrchardSpendingKey.fromBytes(keyBytes)
-    } catch (e: ZcashException.ArrayLengthMismatch) {
+    } catch (e: ZcashException.InvalidArgument) {
         thrown = true;
     }
     assert(thrown)
@@ -1163,7 +1163,7 @@ fun testMemoBytesNew() {
     try {
         val memoBytesTooLong = supp.getAsU8Array("memo_bytes_too_long")
         val memoBytes = ZcashMemoBytes(memoBytesTooLong)
-    } catch (e: ZcashException.ArrayLengthMismatch) {
+    } catch (e: ZcashException.InvalidArgument) {
         thrown = true;
     }
     assert(thrown)
@@ -2217,4 +2217,120 @@ class TransactionExplorationTest(supp: TestSupport) {
         testOrchardBundleCrypto()
     }
 }
-TransactionExplorationTest(supp).execute()
\ No newline at end of file
+TransactionExplorationTest(supp).execute()
+class WalletFixtureTest {
+
+    fun testSummaryOfFixtureWallet(scenario: FixtureScenario) {
+        val dbPath = java.nio.file.Files.createTempDirectory("zcash").resolve("wallet.db").toString()
+        val fixture = createFixtureWallet(dbPath, scenario)
+        val db = ZcashWalletDb.forPath(dbPath, ZcashConsensusParameters.TEST_NETWORK)
+
+        val summary = db.getWalletSummary(fixture.minConfirmations)!!
+        val balance = summary.accountBalances()["0"]!!
+        assert(fixture.chainTipHeight == summary.chainTipHeightValue())
+        assert(fixture.totalBalance == balance.total().value())
+        assert(fixture.spendableBalance == balance.saplingSpendableValue().value())
+    }
+
+    fun execute(){
+        testSummaryOfFixtureWallet(FixtureScenario.SYNCED_WITH_FUNDS)
+        testSummaryOfFixtureWallet(FixtureScenario.PENDING_CHANGE)
+    }
+}
+WalletFixtureTest().execute()
+class WalletPolicyTest {
+    val params = ZcashConsensusParameters.TEST_NETWORK
+    val account = ZcashAccountId(0u)
+
+    fun fixtureWallet(scenario: FixtureScenario): Pair<FixtureWallet, ZcashWalletDb> {
+        val dbPath = java.nio.file.Files.createTempDirectory("zcash").resolve("wallet.db").toString()
+        return Pair(createFixtureWallet(dbPath, scenario), ZcashWalletDb.forPath(dbPath, params))
+    }
+
+    fun paymentRequest(): ZcashTransactionRequest {
+        val recipient = ZcashUnifiedSpendingKey.fromSeed(params, List(32) { 2u }, account)
+            .toUnifiedFullViewingKey().defaultAddress().address
+        return ZcashTransactionRequest(listOf(
+            ZcashPayment(ZcashRecipientAddress.unified(recipient), ZcashAmount(20000), null, null, null, listOf())
+        ))
+    }
+
+    class Approver(val approved: Boolean) : ZcashSpendApprover {
+        val proposals = mutableListOf<ZcashProposal>()
+
+        override fun approve(proposal: ZcashProposal): Boolean {
+            proposals.add(proposal)
+            return approved
+        }
+    }
+
+    fun testSpendApprover() {
+        val (fixture, db) = fixtureWallet(FixtureScenario.SYNCED_WITH_FUNDS)
+        val usk = ZcashUnifiedSpendingKey.fromSeed(params, fixture.seed, account)
+        val proposal = db.proposeTransfer(account, paymentRequest(), fixture.minConfirmations)
+
+        val rejecting = Approver(false)
+        try {
+            db.createProposedTransaction(proposal, usk, ZcashLocalTxProver.withDefaultLocation(),
+                ZcashOvkPolicy.Sender, null, rejecting)
+            throw AssertionError("The spend was not approved")
+        } catch (e: ZcashException.SpendNotApproved) {
+        }
+        assert(listOf(proposal.fee()) == rejecting.proposals.map { it.fee() })
+        assert(1026u == errorCodeForVariant("SpendNotApproved")!!.code)
+    }
+
+    fun testSpendingLimits() {
+        val (fixture, db) = fixtureWallet(FixtureScenario.SYNCED_WITH_FUNDS)
+        assert(ZcashSpendingLimits(null, null) == db.getSpendingLimits(account))
+
+        db.setSpendingLimits(account, ZcashSpendingLimits(20000u, null))
+        try {
+            db.proposeTransfer(account, paymentRequest(), fixture.minConfirmations)
+            throw AssertionError("The limit per transaction was not enforced")
+        } catch (e: ZcashException.PolicyViolation) {
+            assert(e.message!!.contains("`max_per_transaction` limit of 20000"))
+        }
+
+        db.setSpendingLimits(account, ZcashSpendingLimits(100000u, 100000u))
+        assert(ZcashSpendingLimits(100000u, 100000u) == db.getSpendingLimits(account))
+        db.proposeTransfer(account, paymentRequest(), fixture.minConfirmations)
+    }
+
+    fun testBumpTransparentFee() {
+        val (fixture, db) = fixtureWallet(FixtureScenario.SYNCED_WITH_FUNDS)
+        val usk = ZcashUnifiedSpendingKey.fromSeed(params, fixture.seed, account)
+        val receiver = db.getTransparentReceivers(account)[0]
+        val address = ZcashTransparentAddress.decode(params, receiver.address)
+        val txout = ZcashTxOut(ZcashAmount(100000), address.script())
+        val outpoint = ZcashOutPoint(List(32) { 1u }, 0u)
+        db.putReceivedTransparentUtxo(ZcashWalletTransparentOutput.fromParts(
+            outpoint, txout, ZcashBlockHeight(fixture.birthdayHeight)))
+
+        // A payment to another wallet, with the change back to the account.
+        val other = ZcashUnifiedSpendingKey.fromSeed(params, List(32) { 9u }, account).transparent()
+            .toAccountPubkey().deriveExternalIvk().deriveAddress(0u)
+        val builder = ZcashTransactionBuilder(params, ZcashBlockHeight(fixture.chainTipHeight + 1u))
+        builder.addTransparentInput(
+            usk.transparent().deriveExternalSecretKey(receiver.metadata.diversifierIndex().toU32()),
+            outpoint, txout)
+        builder.addTransparentOutput(other, ZcashAmount(50000))
+        builder.addTransparentOutput(address, ZcashAmount(40000))
+        val prover = ZcashLocalTxProver.withDefaultLocation()
+        val tx = builder.build(prover, ZcashFeeRules.FixedNonStandard(10000u)).transaction
+        decryptAndStoreTransaction(params, db, tx)
+        assert(null == db.getReplacementTransaction(tx.txid()))
+
+        db.updateChainTip(tx.expiryHeight().value())
+        val bumped = db.bumpTransparentFee(tx.txid(), usk, prover)
+        assert(bumped.txid().toBytes() == db.getReplacementTransaction(tx.txid())!!.toBytes())
+        assert(listOf(50000L, 35000L) == bumped.transparentBundle()!!.vout().map { it.value().value() })
+    }
+
+    fun execute(){
+        testSpendApprover()
+        testSpendingLimits()
+        testBumpTransparentFee()
+    }
+}
+WalletPolicyTest().execute()
@@ -0,0 +1,62 @@
+import json
+import os
+import sqlite3
+import tempfile
+import threading
+import unittest
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from zcash import *
+
+# The tests of the testnet faucet client, which the library only has when built with the
+# `faucet` feature.
+class FaucetTest(unittest.TestCase):
+    def test_testnet_faucet(self):
+        requests = []
+
+        class Faucet(BaseHTTPRequestHandler):
+            def do_POST(self):
+                requests.append(json.loads(self.rfile.read(int(self.headers["Content-Length"]))))
+                body = json.dumps({"txid": "01" + "00" * 31}).encode()
+                self.send_response(200)
+                self.send_header("Content-Length", str(len(body)))
+                self.end_headers()
+                self.wfile.write(body)
+
+            def log_message(self, *args):
+                pass
+
+        server = HTTPServer(("127.0.0.1", 0), Faucet)
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        try:
+            url = "http://127.0.0.1:%d" % server.server_port
+            faucet = ZcashTestnetFaucet(url, ZcashNetworkConfig())
+
+            params = ZcashConsensusParameters.TEST_NETWORK
+            address = ZcashUnifiedSpendingKey.from_seed(params, [2] * 32, ZcashAccountId(0)) \
+                .to_unified_full_viewing_key().default_address().address.encode(params)
+            txid = faucet.request_funds(address, 100000)
+            self.assertEqual({"address": address, "amount": 100000}, requests[-1])
+            self.assertEqual([0] * 31 + [1], txid.to_bytes())
+            with self.assertRaises(ZcashError):
+                faucet.request_funds(address.replace("utest", "u"), None)
+
+            with tempfile.TemporaryDirectory() as folder:
+                db_path = os.path.join(folder, "wallet.db")
+                db = ZcashWalletDb.for_path(db_path, params)
+                db.initialize([1] + [0] * 31)
+                with self.assertRaises(ZcashError):
+                    faucet.wait_for_funding(db, txid, 0, None)
+
+                class Syncer(ZcashWalletSyncer):
+                    def sync(self):
+                        with sqlite3.connect(db_path) as conn:
+                            conn.execute("INSERT OR IGNORE INTO transactions (txid) VALUES (?)",
+                                         (bytes(txid.to_bytes()),))
+
+                self.assertIsNone(faucet.wait_for_funding(db, txid, 10, Syncer()))
+        finally:
+            server.shutdown()
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,15 @@
+import os
+import tempfile
+from contextlib import contextmanager
+
+from zcash import *
+
+
+@contextmanager
+def fixture_wallet(scenario, params=ZcashConsensusParameters.TEST_NETWORK):
+    """Creates the fixture wallet of the scenario in a temporary directory, which is
+    removed on exit, and gives the fixture along with the wallet database."""
+    with tempfile.TemporaryDirectory() as folder:
+        db_path = os.path.join(folder, "wallet.db")
+        fixture = create_fixture_wallet(db_path, scenario)
+        yield fixture, ZcashWalletDb.for_path(db_path, params)
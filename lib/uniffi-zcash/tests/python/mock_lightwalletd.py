@@ -0,0 +1,183 @@
+import os
+import tempfile
+import unittest
+from zcash import *
+
+# The tests against the made up chain of the mock lightwalletd, which the library only has
+# when built with the `mock-lightwalletd` feature.
+class MockLightwalletdTest(unittest.TestCase):
+    def test_discover_accounts(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        seed = [3] * 32
+        usks = [ZcashUnifiedSpendingKey.from_seed(params, seed, ZcashAccountId(i)) for i in range(5)]
+
+        # Account 0 only used a transparent address after its default one, account 2 only
+        # received Orchard funds and account 4 only Sapling ones.
+        taddr = usks[0].transparent().to_account_pubkey().derive_external_ivk() \
+            .derive_address(15).encode(params)
+        orchard = usks[2].to_unified_full_viewing_key().default_address().address.encode(params)
+        sapling = usks[4].sapling().default_address().address.encode(params)
+        lightwalletd = ZcashMockLightwalletd(2200000)
+        lightwalletd.push_block([])
+        lightwalletd.push_block([ZcashMockPayment(taddr, 10000), ZcashMockPayment(orchard, 20000)])
+        tip = lightwalletd.push_block([ZcashMockPayment(sapling, 30000)])
+        client = ZcashLightwalletdClient(lightwalletd.url())
+
+        with tempfile.TemporaryDirectory() as folder:
+            caches = ZcashBlockCaches(folder)
+            with self.assertRaisesRegex(ZcashError, "test network"):
+                download_blocks(client, caches.get(ZcashConsensusParameters.MAIN_NETWORK), 2200000, tip + 1, None)
+            cache = caches.get(params)
+            self.assertEqual(3, download_blocks(client, cache, 2200000, tip + 1, None))
+            birthday = ZcashAccountBirthday.from_treestate(client.get_tree_state(2199999), None)
+
+            # Without lightwalletd, the first window of accounts looks unused.
+            db = ZcashWalletDb.for_path(os.path.join(folder, "shielded.db"), params)
+            db.initialize(seed)
+            self.assertEqual([], discover_accounts(db, seed, birthday, 2, None, cache))
+
+            # The accounts between the used ones are created as well, as accounts are
+            # created in sequence.
+            db = ZcashWalletDb.for_path(os.path.join(folder, "wallet.db"), params)
+            db.initialize(seed)
+            created = discover_accounts(db, seed, birthday, 2, client, cache)
+            self.assertEqual([0, 1, 2, 3, 4], [account.account_id.id for account in created])
+            self.assertIn("GetTaddressTxids " + taddr, lightwalletd.requests())
+            self.assertEqual([], discover_accounts(db, seed, birthday, 2, client, cache))
+
+
+    def test_download_blocks(self):
+        lightwalletd = ZcashMockLightwalletd(2200000)
+        for _ in range(150):
+            tip = lightwalletd.push_block([])
+        client = ZcashLightwalletdClient(lightwalletd.url())
+        self.assertEqual(tip, client.latest_block_height())
+
+        class Listener(ZcashBlockDownloadListener):
+            def __init__(self):
+                self.progress = []
+
+            def on_progress(self, height, downloaded, total):
+                self.progress.append((height, downloaded, total))
+
+        with tempfile.TemporaryDirectory() as folder:
+            cache = ZcashBlockCaches(folder).get(ZcashConsensusParameters.TEST_NETWORK)
+            listener = Listener()
+            self.assertEqual(150, download_blocks(client, cache, 2200000, tip + 1, listener))
+            # The progress is reported once per batch of stored blocks.
+            self.assertEqual([(2200099, 100, 150), (tip, 150, 150)], listener.progress)
+            self.assertEqual(tip, cache.get_max_cached_height().value())
+            self.assertEqual(150, len(os.listdir(os.path.join(cache.root(), "blocks"))))
+
+            self.assertEqual(0, download_blocks(client, cache, tip + 1, tip + 1, None))
+            with self.assertRaises(ZcashError.InvalidArgument):
+                download_blocks(client, cache, tip + 1, tip, None)
+
+    def test_scan_cached_blocks_parallel(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        seed = [5] * 32
+        address = ZcashUnifiedSpendingKey.from_seed(params, seed, ZcashAccountId(0)) \
+            .sapling().default_address().address.encode(params)
+        other = ZcashUnifiedSpendingKey.from_seed(params, [6] * 32, ZcashAccountId(0)) \
+            .sapling().default_address().address.encode(params)
+
+        # The payment in the first block is before the birthday, so the Sapling tree is not
+        # empty when scanning starts.
+        lightwalletd = ZcashMockLightwalletd(2200000)
+        lightwalletd.push_block([ZcashMockPayment(other, 10000)])
+        for height in range(2200001, 2200010):
+            payments = [ZcashMockPayment(address, 20000)] if height in [2200003, 2200007] else []
+            tip = lightwalletd.push_block(payments)
+        client = ZcashLightwalletdClient(lightwalletd.url())
+
+        with tempfile.TemporaryDirectory() as folder:
+            cache = ZcashBlockCaches(folder).get(params)
+            download_blocks(client, cache, 2200000, tip + 1, None)
+            db_path = os.path.join(folder, "wallet.db")
+            db = ZcashWalletDb.for_path(db_path, params)
+            db.initialize(seed)
+            db.create_account(seed, ZcashAccountBirthday.from_treestate(client.get_tree_state(2200000), None))
+            db.update_chain_tip(tip)
+
+            # The later range is given first, each one having a payment.
+            ranges = [ZcashScanRange.from_heights(2200005, tip + 1, ZcashScanPriority.HISTORIC),
+                      ZcashScanRange.from_heights(2200001, 2200005, ZcashScanPriority.HISTORIC)]
+            self.assertEqual(tip - 2200000, scan_cached_blocks_parallel(params, cache.root(), db_path, ranges, 2))
+            summary = db.get_wallet_summary(1)
+            self.assertEqual(40000, summary.account_balances()["0"].total().value())
+            self.assertEqual(tip, summary.fully_scanned_height_value())
+
+            with self.assertRaisesRegex(ZcashError, "testnet network"):
+                scan_cached_blocks_parallel(ZcashConsensusParameters.MAIN_NETWORK, cache.root(), db_path, ranges, 2)
+
+    def test_benchmark_scan(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        usk = ZcashUnifiedSpendingKey.from_seed(params, [7] * 32, ZcashAccountId(0))
+        address = usk.sapling().default_address().address.encode(params)
+        lightwalletd = ZcashMockLightwalletd(2200000)
+        lightwalletd.push_block([ZcashMockPayment(address, 10000)])
+        lightwalletd.push_block([])
+        tip = lightwalletd.push_block([ZcashMockPayment(address, 20000)])
+        client = ZcashLightwalletdClient(lightwalletd.url())
+
+        with tempfile.TemporaryDirectory() as folder:
+            cache = ZcashBlockCaches(folder).get(params)
+            download_blocks(client, cache, 2200000, tip + 1, None)
+            blocks_dir = os.path.join(cache.root(), "blocks")
+            sample_blocks = []
+            for name in sorted(os.listdir(blocks_dir)):
+                with open(os.path.join(blocks_dir, name), "rb") as block_file:
+                    sample_blocks.append(block_file.read())
+
+        ivk = usk.sapling().to_diversifiable_full_viewing_key().to_ivk(ZcashScope.EXTERNAL)
+        benchmark = benchmark_scan(params, sample_blocks, [ivk])
+        self.assertEqual(3, benchmark.blocks)
+        self.assertEqual(2, benchmark.outputs)
+        self.assertGreater(benchmark.notes_per_second, 0)
+
+        with self.assertRaisesRegex(ZcashError, "Invalid CompactBlock"):
+            benchmark_scan(params, [b"\x00\x01"], [ivk])
+
+    def test_fast_forward_to_birthday(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        seed = [4] * 32
+        usk = ZcashUnifiedSpendingKey.from_seed(params, seed, ZcashAccountId(0))
+        address = usk.sapling().default_address().address
+
+        # The roots are made up, from notes of different values to tell them apart.
+        def root(height):
+            note = address.create_note(height, ZcashRseed.AFTER_ZIP212([0] * 32))
+            return ZcashCommitmentTreeRoot.from_parts(
+                ZcashBlockHeight(height), ZcashSaplingNode.from_cmu(note.cmu()))
+
+        lightwalletd = ZcashMockLightwalletd(2200000)
+        lightwalletd.push_block([ZcashMockPayment(address.encode(params), 10000)])
+        lightwalletd.push_block([])
+        tip = lightwalletd.push_block([])
+        lightwalletd.add_sapling_subtree_root(root(2100000))
+        lightwalletd.add_sapling_subtree_root(root(2150000))
+        client = ZcashLightwalletdClient(lightwalletd.url())
+
+        with tempfile.TemporaryDirectory() as folder:
+            db = ZcashWalletDb.for_path(os.path.join(folder, "wallet.db"), params)
+            db.initialize(seed)
+            birthday = ZcashAccountBirthday.from_treestate(client.get_tree_state(2200000), None)
+            db.create_account(seed, birthday)
+
+            report = fast_forward_to_birthday(client, db)
+            self.assertEqual(2, report.subtree_roots)
+            self.assertEqual(2200001, report.birthday_height)
+            self.assertEqual(tip, report.chain_tip_height)
+            self.assertIn("GetSubtreeRoots 0", lightwalletd.requests())
+            self.assertIn("GetTreeState 2200000", lightwalletd.requests())
+            self.assertEqual(2200001, db.suggest_scan_ranges()[0].start_height())
+
+            # Once the wallet has them, only the new roots are fetched.
+            lightwalletd.add_sapling_subtree_root(root(2190000))
+            report = fast_forward_to_birthday(client, db)
+            self.assertEqual(1, report.subtree_roots)
+            self.assertIn("GetSubtreeRoots 2", lightwalletd.requests())
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,429 @@
+import json
+import os
+import sqlite3
+import tempfile
+import threading
+import unittest
+from functools import cmp_to_key
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from fixtures import fixture_wallet
+from zcash import *
+
+# The tests building Sapling transactions with the mock prover, which the library only has
+# when built with the `mock-prover` feature.
+class MockProverTest(unittest.TestCase):
+    def test_sapling_with_mock_prover(self):
+        prover = ZcashMockTxProver().to_local_prover()
+
+        zts = TestSupport.from_csv_file()
+
+        key = ZcashUnifiedSpendingKey.from_bytes(ZcashKeysEra.ORCHARD,
+                                                 zts.get_as_u8_array("unified_spending_key"))
+
+        extsk = key.sapling()
+        payment_address = extsk.default_address().address
+        note = payment_address.create_note(200, ZcashRseed.AFTER_ZIP212([0] * 32))
+        tree = ZcashCommitmentTree.empty()
+        tree.append(ZcashSaplingNode.from_cmu(note.cmu()))
+        witness = ZcashIncrementalWitness.from_tree(tree)
+
+        builder = ZcashTransactionBuilder(
+            ZcashConsensusParameters.MAIN_NETWORK, ZcashBlockHeight(2030820))
+        builder.add_sapling_spend(
+            extsk, payment_address.diversifier(), note, witness.path())
+        ovk = key.sapling().to_diversifiable_full_viewing_key().to_ovk(ZcashScope.INTERNAL)
+        builder.add_sapling_output(
+            ovk, payment_address, ZcashAmount(200), ZcashMemoBytes.empty())
+
+        result = builder.build(prover, ZcashFeeRules.FIXED_NON_STANDARD(0))
+        # Same size as with the real prover, as the mock proofs are just zeros.
+        self.assertEqual(len(result.transaction.to_bytes()), 2377)
+
+    def test_spend_approver(self):
+        prover = ZcashMockTxProver().to_local_prover()
+
+        params = ZcashConsensusParameters.TEST_NETWORK
+        recipient = ZcashUnifiedSpendingKey.from_seed(params, [2] * 32, ZcashAccountId(0)) \
+            .to_unified_full_viewing_key().default_address().address
+        request = ZcashTransactionRequest([
+            ZcashPayment(ZcashRecipientAddress.unified(recipient), ZcashAmount(20000),
+                None, None, None, []),
+        ])
+
+        class Approver(ZcashSpendApprover):
+            def __init__(self, approved):
+                self.approved = approved
+                self.proposals = []
+
+            def approve(self, proposal):
+                self.proposals.append(proposal)
+                return self.approved
+
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            usk = ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, ZcashAccountId(0))
+            proposal = db.propose_transfer(ZcashAccountId(0), request, fixture.min_confirmations)
+
+            rejecting = Approver(False)
+            with self.assertRaises(ZcashError.SpendNotApproved):
+                db.create_proposed_transaction(proposal, usk, prover, ZcashOvkPolicy.SENDER(),
+                                               approver=rejecting)
+            self.assertEqual([proposal.fee()], [p.fee() for p in rejecting.proposals])
+            with self.assertRaises(ZcashError.SpendNotApproved) as error:
+                spend(db, prover,
+                      ZcashGreedyInputSelector(
+                          ZcashFeeRule.zip317(ZcashZip317FeeRule.standard()),
+                          ZcashChangeStrategy.SINGLE_OUTPUT,
+                          ZcashDustOutputPolicy(ZcashDustAction.REJECT, None)),
+                      usk, request, ZcashOvkPolicy.SENDER(), fixture.min_confirmations,
+                      approver=rejecting)
+            self.assertEqual(1026, error_code_for_variant(type(error.exception).__name__).code)
+            self.assertEqual(2, len(rejecting.proposals))
+
+            approving = Approver(True)
+            txid = db.create_proposed_transaction(
+                proposal, usk, prover, ZcashOvkPolicy.SENDER(),
+                change_memo=ZcashMemoBytes.self_memo('{"invoice": 7}'), approver=approving)
+            self.assertEqual(1, len(approving.proposals))
+            self.assertEqual(['{"invoice":7}'], db.get_self_memo_tags(txid))
+
+    def test_greedy_input_selector(self):
+        prover = ZcashMockTxProver().to_local_prover()
+
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            usk = ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, ZcashAccountId(0))
+            request = ZcashTransactionRequest([
+                ZcashPayment(ZcashRecipientAddress.unified(
+                    usk.to_unified_full_viewing_key().default_address().address),
+                    ZcashAmount(20000), None, None, None, []),
+            ])
+            input_selector = ZcashGreedyInputSelector(
+                ZcashFeeRule.fixed(ZcashFixedFeeRule.standard()),
+                ZcashChangeStrategy.SINGLE_OUTPUT,
+                ZcashDustOutputPolicy(ZcashDustAction.REJECT, None))
+
+            with self.assertRaises(ZcashError.InvalidArgument):
+                spend(db, prover, input_selector, usk, request, ZcashOvkPolicy.SENDER(), 0)
+            txid = spend(db, prover, input_selector, usk, request, ZcashOvkPolicy.SENDER(),
+                         fixture.min_confirmations)
+            self.assertIsNotNone(db.get_tx_expiry_height(txid))
+
+    def test_conflicting_transactions(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            fixture = create_fixture_wallet(db_path, FixtureScenario.SYNCED_WITH_FUNDS)
+            db = ZcashWalletDb.for_path(db_path, params)
+            self.assertEqual([], db.get_conflict_sets())
+
+            prover = ZcashMockTxProver().to_local_prover()
+
+            recipient = ZcashUnifiedSpendingKey.from_seed(params, [2] * 32, ZcashAccountId(0)) \
+                .to_unified_full_viewing_key().default_address().address
+            request = ZcashTransactionRequest([
+                ZcashPayment(ZcashRecipientAddress.unified(recipient), ZcashAmount(20000),
+                    None, None, None, []),
+            ])
+            usk = ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, ZcashAccountId(0))
+
+            def spend():
+                proposal = db.propose_transfer(ZcashAccountId(0), request, fixture.min_confirmations)
+                return db.create_proposed_transaction(proposal, usk, prover, ZcashOvkPolicy.SENDER())
+
+            first = spend()
+            self.assertEqual([], db.get_conflicting_transactions(first))
+            # As if the first transaction was dropped from the mempool, so the note can
+            # be spent again.
+            with sqlite3.connect(db_path) as conn:
+                conn.execute("UPDATE sapling_received_notes SET spent = NULL")
+            conn.close()
+            second = spend()
+
+            self.assertEqual([second], db.get_conflicting_transactions(first))
+            self.assertEqual([first], db.get_conflicting_transactions(second))
+            self.assertEqual([sorted([first, second], key=cmp_to_key(ZcashTxId.compare))],
+                             db.get_conflict_sets())
+            lookup = db.get_transactions_by_ids([first])
+            self.assertIsNone(list(lookup.values())[0].error)
+            self.assertEqual([second], list(lookup.values())[0].conflicts)
+
+    def test_transaction_balance_changes(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            self.assertEqual([], db.get_transaction_balance_changes(ZcashTxId.from_bytes([0] * 32)))
+
+            prover = ZcashMockTxProver().to_local_prover()
+
+            recipient = ZcashUnifiedSpendingKey.from_seed(params, [2] * 32, ZcashAccountId(0)) \
+                .to_unified_full_viewing_key().default_address().address
+            request = ZcashTransactionRequest([
+                ZcashPayment(ZcashRecipientAddress.unified(recipient), ZcashAmount(20000),
+                    None, None, None, []),
+            ])
+            usk = ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, ZcashAccountId(0))
+            proposal = db.propose_transfer(ZcashAccountId(0), request, fixture.min_confirmations)
+            txid = db.create_proposed_transaction(proposal, usk, prover, ZcashOvkPolicy.SENDER())
+
+            [change] = db.get_transaction_balance_changes(txid)
+            self.assertEqual(ZcashAccountId(0), change.account)
+            self.assertEqual(0, change.transparent)
+            self.assertEqual(0, change.orchard)
+            self.assertIsNotNone(change.fee)
+            self.assertEqual(-(20000 + change.fee), change.sapling)
+
+            lookup = db.get_transactions_by_ids([txid])
+            self.assertEqual([change], lookup[txid.to_hex_string()].balance_changes)
+
+    def test_proposal_expiry_height(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            recipient = ZcashUnifiedSpendingKey.from_seed(params, [2] * 32, ZcashAccountId(0)) \
+                .to_unified_full_viewing_key().default_address().address
+            request = ZcashTransactionRequest([
+                ZcashPayment(ZcashRecipientAddress.unified(recipient), ZcashAmount(20000),
+                    None, None, None, []),
+            ])
+            proposal = db.propose_transfer(ZcashAccountId(0), request, fixture.min_confirmations)
+            self.assertEqual(proposal.min_target_height() + default_tx_expiry_delta(),
+                             proposal.expiry_height())
+
+            prover = ZcashMockTxProver().to_local_prover()
+            usk = ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, ZcashAccountId(0))
+            txid = db.create_proposed_transaction(proposal, usk, prover, ZcashOvkPolicy.SENDER())
+            self.assertEqual(proposal.expiry_height(), db.get_tx_expiry_height(txid))
+            self.assertIsNone(db.get_tx_expiry_height(ZcashTxId.from_bytes([0] * 32)))
+
+    def test_expiry_delta_of_wallet_spends(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        prover = ZcashMockTxProver().to_local_prover()
+        input_selector = ZcashGreedyInputSelector(
+            ZcashFeeRule.zip317(ZcashZip317FeeRule.standard()),
+            ZcashChangeStrategy.SINGLE_OUTPUT,
+            ZcashDustOutputPolicy(ZcashDustAction.REJECT, None))
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            usk = ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, ZcashAccountId(0))
+            recipient = ZcashUnifiedSpendingKey.from_seed(params, [2] * 32, ZcashAccountId(0)) \
+                .to_unified_full_viewing_key().default_address().address
+            request = ZcashTransactionRequest([
+                ZcashPayment(ZcashRecipientAddress.unified(recipient), ZcashAmount(20000),
+                    None, None, None, []),
+            ])
+            target_height = fixture.chain_tip_height + 1
+
+            with self.assertRaises(ZcashError.InvalidArgument):
+                db.propose_transfer(ZcashAccountId(0), request, fixture.min_confirmations, 3)
+            proposal = db.propose_transfer(ZcashAccountId(0), request, fixture.min_confirmations, 100)
+            self.assertEqual((100, target_height + 100),
+                             (proposal.expiry_delta(), proposal.expiry_height()))
+            # The delta is part of what is approved, so it survives serialization.
+            self.assertEqual(100, ZcashProposal.from_json(proposal.to_json()).expiry_delta())
+            txid = db.create_proposed_transaction(proposal, usk, prover, ZcashOvkPolicy.SENDER())
+            self.assertEqual(target_height + 100, db.get_tx_expiry_height(txid))
+
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            with self.assertRaises(ZcashError.InvalidArgument):
+                spend(db, prover, input_selector, usk, request, ZcashOvkPolicy.SENDER(),
+                      fixture.min_confirmations, expiry_delta=3)
+            txid = spend(db, prover, input_selector, usk, request, ZcashOvkPolicy.SENDER(),
+                         fixture.min_confirmations, expiry_delta=200)
+            self.assertEqual(target_height + 200, db.get_tx_expiry_height(txid))
+
+            address = ZcashTransparentAddress.decode(
+                params, db.get_transparent_receivers(ZcashAccountId(0))[0].address)
+            db.put_received_transparent_utxo(ZcashWalletTransparentOutput.from_parts(
+                ZcashOutPoint([1] * 32, 0), ZcashTxOut(ZcashAmount(50000), address.script()),
+                ZcashBlockHeight(fixture.birthday_height)))
+            # The ZIP 317 fee of shieldings is off by the padding of the Sapling outputs
+            # in this version of the builder, so their fee is fixed.
+            fixed_fee_selector = ZcashGreedyInputSelector(
+                ZcashFeeRule.fixed(ZcashFixedFeeRule.standard()),
+                ZcashChangeStrategy.SINGLE_OUTPUT,
+                ZcashDustOutputPolicy(ZcashDustAction.REJECT, None))
+            txid = shield_transparent_funds(db, prover, fixed_fee_selector, 10000, usk, [address],
+                                            ZcashMemoBytes.empty(), fixture.min_confirmations,
+                                            expiry_delta=300)
+            self.assertEqual(target_height + 300, db.get_tx_expiry_height(txid))
+
+    def test_spending_limits(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            account = ZcashAccountId(0)
+            self.assertEqual(ZcashSpendingLimits(None, None), db.get_spending_limits(account))
+
+            recipient = ZcashUnifiedSpendingKey.from_seed(params, [2] * 32, ZcashAccountId(0)) \
+                .to_unified_full_viewing_key().default_address().address
+            request = ZcashTransactionRequest([
+                ZcashPayment(ZcashRecipientAddress.unified(recipient), ZcashAmount(20000),
+                    None, None, None, []),
+            ])
+            db.set_spending_limits(account, ZcashSpendingLimits(20000, None))
+            with self.assertRaises(ZcashError.PolicyViolation) as cm:
+                db.propose_transfer(account, request, fixture.min_confirmations)
+            self.assertIn("`max_per_transaction` limit of 20000", str(cm.exception))
+            db.set_spending_limits(account, ZcashSpendingLimits(None, 20000))
+            with self.assertRaises(ZcashError.PolicyViolation) as cm:
+                db.propose_transfer(account, request, fixture.min_confirmations)
+            self.assertIn("`max_per_day` limit of 20000", str(cm.exception))
+
+            db.set_spending_limits(account, ZcashSpendingLimits(100000, 100000))
+            self.assertEqual(ZcashSpendingLimits(100000, 100000), db.get_spending_limits(account))
+            proposal = db.propose_transfer(account, request, fixture.min_confirmations)
+            self.assertEqual(0, db.get_spent_last_day(account))
+
+            with self.assertRaises(ZcashError):
+                db.set_spending_limits(ZcashAccountId(9), ZcashSpendingLimits(1, None))
+            db.set_spending_limits(account, ZcashSpendingLimits(None, None))
+            self.assertEqual(ZcashSpendingLimits(None, None), db.get_spending_limits(account))
+
+            prover = ZcashMockTxProver().to_local_prover()
+            usk = ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, account)
+            db.create_proposed_transaction(proposal, usk, prover, ZcashOvkPolicy.SENDER())
+            self.assertEqual(20000 + proposal.fee(), db.get_spent_last_day(account))
+
+    def test_decrypt_and_store_transaction_summary(self):
+        def birthday(height):
+            return ZcashAccountBirthday.from_treestate(ZcashTreeState("test", height, "", 0, "000000", ""), None)
+
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            fixture = create_fixture_wallet(db_path, FixtureScenario.SYNCED_WITH_FUNDS)
+            db = ZcashWalletDb.for_path(db_path, params)
+            recipient_db = ZcashWalletDb.for_path(os.path.join(folder, "recipient.db"), params)
+            recipient_db.initialize([2] * 32)
+            recipient_db.create_account([2] * 32, birthday(fixture.birthday_height))
+            recipient_db.update_chain_tip(fixture.chain_tip_height)
+            other_db = ZcashWalletDb.for_path(os.path.join(folder, "other.db"), params)
+            other_db.initialize([3] * 32)
+            other_db.create_account([3] * 32, birthday(fixture.birthday_height))
+
+            prover = ZcashMockTxProver().to_local_prover()
+            recipient = ZcashUnifiedSpendingKey.from_seed(params, [2] * 32, ZcashAccountId(0)) \
+                .to_unified_full_viewing_key().default_address().address
+            request = ZcashTransactionRequest([
+                ZcashPayment(ZcashRecipientAddress.unified(recipient), ZcashAmount(20000),
+                    None, None, None, []),
+            ])
+            proposal = db.propose_transfer(ZcashAccountId(0), request, fixture.min_confirmations)
+            usk = ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, ZcashAccountId(0))
+            txid = db.create_proposed_transaction(proposal, usk, prover, ZcashOvkPolicy.SENDER())
+            tx = db.get_transaction(txid)
+
+            summary = decrypt_and_store_transaction(params, recipient_db, tx)
+            self.assertEqual([ZcashAccountId(0)], summary.accounts)
+            self.assertEqual(1, summary.notes_received)
+            self.assertEqual(20000, summary.value_received)
+            summary = decrypt_and_store_transaction(params, other_db, tx)
+            self.assertEqual([], summary.accounts)
+            self.assertEqual(0, summary.notes_received)
+
+    def test_pool_totals(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+
+        class Node(BaseHTTPRequestHandler):
+            def do_POST(self):
+                request = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+                height = int(request["params"][0])
+                pools = [
+                    {"id": "sprout", "monitored": True, "chainValueZat": 7},
+                    {"id": "sapling", "monitored": True, "chainValueZat": height * 10},
+                    {"id": "orchard", "monitored": height >= 20},
+                ]
+                if height >= 20:
+                    pools[2]["chainValueZat"] = height
+                body = json.dumps({"result": {"height": height, "valuePools": pools}, "error": None}).encode()
+                self.send_response(200)
+                self.send_header("Content-Length", str(len(body)))
+                self.end_headers()
+                self.wfile.write(body)
+
+            def log_message(self, *args):
+                pass
+
+        server = HTTPServer(("127.0.0.1", 0), Node)
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        try:
+            url = "http://127.0.0.1:%d" % server.server_port
+            client = ZcashNodeRpcClient(url, params, None, None, ZcashNetworkConfig())
+            self.assertEqual(ZcashPoolTotals(30, 300, 30), client.get_pool_totals(30))
+            self.assertEqual([30, 35, 40], [totals.height for totals in client.get_pool_totals_history(30, 40, 5)])
+            with self.assertRaises(ZcashError):
+                client.get_pool_totals(10)
+            with self.assertRaises(ZcashError.InvalidArgument):
+                client.get_pool_totals_history(30, 40, 0)
+        finally:
+            server.shutdown()
+
+        self.assertEqual(ZcashPoolDeltas(0, 0), pool_deltas([]))
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            prover = ZcashMockTxProver().to_local_prover()
+            recipient = ZcashUnifiedSpendingKey.from_seed(params, [2] * 32, ZcashAccountId(0)) \
+                .to_unified_full_viewing_key().default_address().address
+            request = ZcashTransactionRequest([
+                ZcashPayment(ZcashRecipientAddress.unified(recipient), ZcashAmount(20000),
+                    None, None, None, []),
+            ])
+            proposal = db.propose_transfer(ZcashAccountId(0), request, fixture.min_confirmations)
+            usk = ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, ZcashAccountId(0))
+            txid = db.create_proposed_transaction(proposal, usk, prover, ZcashOvkPolicy.SENDER())
+            # The payment and the change stay in the Sapling pool, only the fee leaves it.
+            self.assertEqual(ZcashPoolDeltas(-proposal.fee(), 0), pool_deltas([db.get_transaction(txid)]))
+
+    def test_bump_transparent_fee(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            fixture = create_fixture_wallet(db_path, FixtureScenario.SYNCED_WITH_FUNDS)
+            db = ZcashWalletDb.for_path(db_path, params)
+            account = ZcashAccountId(0)
+            usk = ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, account)
+            receiver = db.get_transparent_receivers(account)[0]
+            address = ZcashTransparentAddress.decode(params, receiver.address)
+            txout = ZcashTxOut(ZcashAmount(100000), address.script())
+            outpoint = ZcashOutPoint([1] * 32, 0)
+            db.put_received_transparent_utxo(ZcashWalletTransparentOutput.from_parts(
+                outpoint, txout, ZcashBlockHeight(fixture.birthday_height)))
+
+            # A payment to another wallet, with the change back to the account, paying the
+            # ZIP 317 fee.
+            other = ZcashUnifiedSpendingKey.from_seed(params, [9] * 32, account).transparent() \
+                .to_account_pubkey().derive_external_ivk().derive_address(0)
+            builder = ZcashTransactionBuilder(params, ZcashBlockHeight(fixture.chain_tip_height + 1))
+            builder.add_transparent_input(
+                usk.transparent().derive_external_secret_key(receiver.metadata.diversifier_index().to_u32()),
+                outpoint, txout)
+            builder.add_transparent_output(other, ZcashAmount(50000))
+            builder.add_transparent_output(address, ZcashAmount(40000))
+            prover = ZcashMockTxProver().to_local_prover()
+            tx = builder.build(prover, ZcashFeeRules.FIXED_NON_STANDARD(10000)).transaction
+            decrypt_and_store_transaction(params, db, tx)
+
+            # It can still be mined, so it is not replaced.
+            with self.assertRaises(ZcashError) as error:
+                db.bump_transparent_fee(tx.txid(), usk, prover, None, None)
+            self.assertIn("expiry height", str(error.exception))
+            self.assertIsNone(db.get_replacement_transaction(tx.txid()))
+
+            db.update_chain_tip(tx.expiry_height().value())
+            bumped = db.bump_transparent_fee(tx.txid(), usk, prover, None, None)
+            self.assertEqual(bumped.txid().to_bytes(), db.get_replacement_transaction(tx.txid()).to_bytes())
+            values = [txout.value().value() for txout in bumped.transparent_bundle().vout()]
+            self.assertEqual([50000, 35000], values)
+
+            # A bump interrupted after storing the replacement, but before linking it to the
+            # original, is repaired when retried, rather than replaced again.
+            with sqlite3.connect(db_path) as conn:
+                conn.execute("DELETE FROM uniffi_replaced_transactions")
+            self.assertIsNone(db.get_replacement_transaction(tx.txid()))
+            retried = db.bump_transparent_fee(tx.txid(), usk, prover, None, None)
+            self.assertEqual(bumped.txid().to_bytes(), retried.txid().to_bytes())
+            self.assertEqual(bumped.txid().to_bytes(), db.get_replacement_transaction(tx.txid()).to_bytes())
+
+            with sqlite3.connect(db_path) as conn:
+                conn.execute("UPDATE utxos SET value_zat = -1")
+            with self.assertRaisesRegex(ZcashError, "invalid value -1"):
+                db.bump_transparent_fee(tx.txid(), usk, prover, None, None)
+
+
+if __name__ == '__main__':
+    unittest.main()
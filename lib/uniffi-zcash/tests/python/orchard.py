@@ -13,7 +13,7 @@ class Test(unittest.TestCase):
     def test_spending_key_array_mismatch(self):
         key_bytes = [0, 1]
 
-        with self.assertRaises(ZcashError.ArrayLengthMismatch):
+        with self.assertRaises(ZcashError.InvalidArgument):
             ZcashOrchardSpendingKey.from_bytes(key_bytes)
 
     def test_spending_key_from_zip32_seed(self):
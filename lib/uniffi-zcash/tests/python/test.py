@@ -1,4 +1,11 @@
+import json
+import os
+import sqlite3
+import tempfile
+import threading
 import unittest
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from fixtures import fixture_wallet
 from zcash import *
 
 class Test(unittest.TestCase):
@@ -15,6 +22,100 @@ class Test(unittest.TestCase):
         self.assertEqual(unified_spending_key.to_bytes(
             ZcashKeysEra.ORCHARD), expected)
 
+    def test_unified_spending_key_era_tagged_decoding(self):
+        seed = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+
+        unified_spending_key = ZcashUnifiedSpendingKey.from_seed(
+            ZcashConsensusParameters.MAIN_NETWORK,
+            seed,
+            ZcashAccountId(0))
+        encoded = unified_spending_key.to_bytes(latest_keys_era())
+
+        self.assertEqual(ZcashKeysEra.ORCHARD, decode_keys_era(encoded))
+        self.assertEqual(encoded, ZcashUnifiedSpendingKey.decode(encoded).to_bytes(ZcashKeysEra.ORCHARD))
+
+        with self.assertRaises(ZcashDecodingError.ReadError):
+            ZcashUnifiedSpendingKey.decode(encoded[:2])
+
+        with self.assertRaises(ZcashDecodingError.EraInvalid):
+            ZcashUnifiedSpendingKey.decode([1, 2, 3, 4] + encoded[4:])
+
+        with self.assertRaises(ZcashDecodingError.InsufficientData):
+            ZcashUnifiedSpendingKey.decode(encoded[:40])
+
+    def test_derive_accounts(self):
+        seed = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+
+        accounts = derive_accounts(ZcashConsensusParameters.MAIN_NETWORK, seed, 0, 3)
+
+        self.assertEqual([a.account_id.id for a in accounts], [0, 1, 2])
+        for account in accounts:
+            expected = ZcashUnifiedSpendingKey.from_seed(
+                ZcashConsensusParameters.MAIN_NETWORK,
+                seed,
+                account.account_id)
+            self.assertEqual(account.unified_spending_key.to_bytes(ZcashKeysEra.ORCHARD),
+                expected.to_bytes(ZcashKeysEra.ORCHARD))
+
+    def test_parse_zec_string(self):
+        self.assertEqual(parse_zec_string("1.2345").value(), 123450000)
+        self.assertEqual(parse_zec_string("0.00000001").value(), 1)
+        self.assertEqual(parse_zec_string("21000000").value(), 2100000000000000)
+
+        with self.assertRaises(ZcashAmountParseError.TooManyDecimals):
+            parse_zec_string("0.000000001")
+        with self.assertRaises(ZcashAmountParseError.Overflow):
+            parse_zec_string("21000000.00000001")
+        with self.assertRaises(ZcashAmountParseError.Negative):
+            parse_zec_string("-1")
+        for invalid in ["", "1,5", ".5", "1.", " 1", "1e8", "+1"]:
+            with self.assertRaises(ZcashAmountParseError.InvalidFormat):
+                parse_zec_string(invalid)
+
+    def test_format_zec(self):
+        self.assertEqual(format_zec(ZcashAmount(150000000), False), "1.5")
+        self.assertEqual(format_zec(ZcashAmount(150000000), True), "1.50000000")
+        self.assertEqual(format_zec(ZcashAmount(-1), False), "-0.00000001")
+        self.assertEqual(format_zec(ZcashAmount.zero(), False), "0")
+
+    def test_transaction_request_with_split_notes(self):
+        seed = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        address = ZcashUnifiedSpendingKey.from_seed(
+            ZcashConsensusParameters.MAIN_NETWORK,
+            seed,
+            ZcashAccountId(0)).to_unified_full_viewing_key().default_address().address
+
+        request = ZcashTransactionRequest([
+            ZcashPayment(ZcashRecipientAddress.unified(address), ZcashAmount(100000000),
+                ZcashMemoBytes([1, 2, 3]), None, None, []),
+            ZcashPayment(ZcashRecipientAddress.transparent(address.transparent()),
+                ZcashAmount(100000000), None, None, None, []),
+        ])
+
+        payments = request.with_split_notes(4).payments()
+        notes = payments[:-1]
+
+        self.assertTrue(2 <= len(notes) <= 4)
+        self.assertEqual(sum(note.amount.value() for note in notes), 100000000)
+        self.assertTrue(all(note.amount.value() >= 50000 for note in notes))
+        self.assertEqual([note.memo is not None for note in notes],
+            [True] + [False] * (len(notes) - 1))
+        self.assertEqual(payments[-1].amount.value(), 100000000)
+
+    def test_zip317_estimate_fee(self):
+        rule = ZcashZip317FeeRule.standard()
+
+        estimate = rule.estimate_fee(ZcashTransactionShape(0, 0, 1, 2, 0), [])
+        self.assertEqual(estimate.fee, 10000)
+        self.assertEqual(estimate.conventional_fee, 10000)
+        self.assertTrue(estimate.conventional_fee_suffices)
+        self.assertEqual(estimate.recent_transactions, 0)
+
+        estimate = rule.estimate_fee(ZcashTransactionShape(5, 0, 0, 1, 0), [])
+        self.assertEqual(estimate.logical_actions, 6)
+        self.assertEqual(estimate.fee, 30000)
+        self.assertFalse(estimate.conventional_fee_suffices)
+
     def test_unified_full_viewing_key_encode(self):
         seed = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
 
@@ -28,6 +129,29 @@ class Test(unittest.TestCase):
         self.assertEqual(unified_spending_key.to_unified_full_viewing_key()
             .encode(ZcashConsensusParameters.MAIN_NETWORK), expected)
 
+    def test_unified_full_viewing_key_with_items(self):
+        params = ZcashConsensusParameters.MAIN_NETWORK
+        ufvk = ZcashUnifiedSpendingKey.from_seed(params, [1] + [0] * 31, ZcashAccountId(0)) \
+            .to_unified_full_viewing_key()
+
+        orchard_only = ufvk.to_ufvk_with([ZcashUfvkItem.ORCHARD])
+        self.assertIsNone(orchard_only.transparent())
+        self.assertIsNone(orchard_only.sapling())
+        self.assertEqual(ufvk.orchard().to_bytes(), orchard_only.orchard().to_bytes())
+        self.assertEqual(orchard_only.encode(params),
+                         ZcashUnifiedFullViewingKey.decode(params, orchard_only.encode(params)).encode(params))
+
+        sapling_only = ufvk.to_ufvk_with([ZcashUfvkItem.SAPLING, ZcashUfvkItem.SAPLING])
+        self.assertIsNone(sapling_only.orchard())
+        self.assertIsNotNone(sapling_only.sapling())
+        self.assertEqual(ufvk.encode(params), ufvk.to_ufvk_with(
+            [ZcashUfvkItem.ORCHARD, ZcashUfvkItem.TRANSPARENT, ZcashUfvkItem.SAPLING]).encode(params))
+
+        with self.assertRaises(ZcashError.InvalidArgument):
+            ufvk.to_ufvk_with([ZcashUfvkItem.TRANSPARENT])
+        with self.assertRaises(ZcashError.InvalidArgument):
+            sapling_only.to_ufvk_with([ZcashUfvkItem.ORCHARD])
+
     def test_sapling_ivk_to_payment_address(self):
         seed = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
 
@@ -115,6 +239,33 @@ class Test(unittest.TestCase):
 
         self.assertEqual(bytes, ZcashPaymentAddress.from_bytes(bytes).to_bytes())
 
+    def test_sapling_note_plaintext_version(self):
+        seed = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        net = ZcashConsensusParameters.TEST_NETWORK
+        address = ZcashUnifiedSpendingKey.from_seed(net, seed, ZcashAccountId(0)) \
+            .to_unified_full_viewing_key().sapling().to_ivk(ZcashScope.EXTERNAL) \
+            .to_payment_address(ZcashDiversifier([0] * 11))
+
+        after = address.create_note(1000, ZcashRseed.AFTER_ZIP212([7] * 32))
+        self.assertEqual(0x02, after.plaintext_lead_byte())
+        self.assertEqual([7] * 32, after.rseed().data)
+
+        rcm = [1] + [0] * 31
+        before = address.create_note(1000, ZcashRseed.BEFORE_ZIP212(rcm))
+        self.assertEqual(0x01, before.plaintext_lead_byte())
+        self.assertEqual(rcm, before.rseed().fr_data)
+        self.assertEqual(before.cmu().to_bytes(), address.create_note(1000, before.rseed()).cmu().to_bytes())
+
+        # Testnet Canopy activation and the end of the ZIP 212 grace period.
+        canopy = ZcashBlockHeight(1028500)
+        grace_end = ZcashBlockHeight(1028500 + 32256)
+        self.assertTrue(sapling_plaintext_version_is_valid(net, ZcashBlockHeight(1028499), 0x01))
+        self.assertFalse(sapling_plaintext_version_is_valid(net, ZcashBlockHeight(1028499), 0x02))
+        self.assertTrue(sapling_plaintext_version_is_valid(net, canopy, 0x01))
+        self.assertTrue(sapling_plaintext_version_is_valid(net, canopy, 0x02))
+        self.assertFalse(sapling_plaintext_version_is_valid(net, grace_end, 0x01))
+        self.assertTrue(sapling_plaintext_version_is_valid(net, grace_end, 0x02))
+
     def test_unified_address_parsing(self):
         seed = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
 
@@ -233,5 +384,1470 @@ class Test(unittest.TestCase):
         assert parsed.is_script()
         self.assertEqual(input, parsed.encode(net))
 
+    def test_address_parsing_of_any_kind(self):
+        net = ZcashConsensusParameters.MAIN_NETWORK
+        seed = [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+
+        unified_spending_key = ZcashUnifiedSpendingKey.from_seed(net, seed, ZcashAccountId(0))
+        orchard = unified_spending_key.to_unified_full_viewing_key() \
+            .orchard().to_ivk(ZcashOrchardScope.EXTERNAL) \
+            .address(ZcashOrchardDiversifier.from_bytes([0] * 11))
+        transparent = ZcashTransparentAddress.from_public_key(range(1, 21))
+        encoded = ZcashUnifiedAddress(orchard, None, transparent).encode(net)
+
+        parsed = ZcashAddress.parse(encoded)
+
+        self.assertEqual(ZcashAddressNetwork.MAIN, parsed.network())
+        self.assertEqual(ZcashAddressKind.UNIFIED, parsed.kind())
+        self.assertEqual([
+            ZcashUnifiedReceiver.ORCHARD(orchard.to_raw_address_bytes()),
+            ZcashUnifiedReceiver.P2PKH(list(range(1, 21))),
+        ], parsed.receivers())
+        assert parsed.can_receive_memo()
+        self.assertEqual(encoded, parsed.encode())
+        self.assertEqual(encoded, parsed.to_recipient_address(net).encode(net))
+
+        parsed = ZcashAddress.parse("t26YoyZ1iPgiMEWL4zGUm74eVWfhyDMXzY2")
+
+        self.assertEqual(ZcashAddressNetwork.TEST, parsed.network())
+        self.assertEqual(ZcashAddressKind.P2SH, parsed.kind())
+        assert not parsed.can_receive_memo()
+
+        with self.assertRaises(ZcashError):
+            parsed.to_recipient_address(net)
+
+        with self.assertRaises(ZcashError):
+            ZcashAddress.parse("not an address")
+
+    def test_sprout_addresses_are_refused(self):
+        net = ZcashConsensusParameters.MAIN_NETWORK
+        sprout = "zc8E5gYid86n4bo2Usdq1cpr7PpfoJGzttwBHEEgGhGkLUg7SPPVFNB2AkRFXZ7usfphup5426dt1buMmY3fkYeRrQGLa8y"
+
+        self.assertEqual(ZcashAddressKind.SPROUT, ZcashAddress.parse(sprout).kind())
+
+        with self.assertRaises(ZcashError.SproutNotSupported):
+            ZcashRecipientAddress.decode(net, sprout)
+
+        with self.assertRaises(ZcashError.SproutNotSupported):
+            ZcashPaymentAddress.decode(net, sprout)
+
+        with self.assertRaises(ZcashError.SproutNotSupported):
+            ZcashAddress.parse(sprout).to_recipient_address(net)
+
+        uri = "zcash:?address=t3RXqXRqj9W9NRY6gY2ibUZzwDe3wmy8zpF&amount=1&address.1=" + sprout + "&amount.1=2"
+        with self.assertRaises(ZcashZip321Error.SproutNotSupported) as error:
+            ZcashTransactionRequest.from_uri(net, uri)
+        self.assertEqual(1, error.exception.v)
+
+    def test_qr_payloads(self):
+        net = ZcashConsensusParameters.MAIN_NETWORK
+        seed = [1] + [0] * 31
+        address = ZcashUnifiedSpendingKey.from_seed(net, seed, ZcashAccountId(0)) \
+            .to_unified_full_viewing_key().default_address().address
+        recipient = ZcashRecipientAddress.unified(address)
+        encoded = recipient.encode(net)
+
+        self.assertEqual(encoded, encode_qr_payload(net, recipient, None))
+        self.assertEqual(encoded, encode_qr_payload(net, recipient, ZcashNonNegativeAmount.zero()))
+        payload = encode_qr_payload(net, recipient, ZcashNonNegativeAmount.from_u64(150000000))
+        self.assertEqual("zcash:" + encoded + "?amount=1.5", payload)
+
+        scanned = parse_qr_payload(net, payload.upper())
+        self.assertIsNone(scanned.address)
+        payment = scanned.request.payments()[0]
+        self.assertEqual(encoded, payment.recipient_address.encode(net))
+        self.assertEqual(150000000, payment.amount.value())
+
+        scanned = parse_qr_payload(net, " " + encoded.upper() + "\n")
+        self.assertIsNone(scanned.request)
+        self.assertEqual(encoded, scanned.address.encode(net))
+
+        transparent = "t3RXqXRqj9W9NRY6gY2ibUZzwDe3wmy8zpF"
+        self.assertEqual(transparent, parse_qr_payload(net, transparent).address.encode(net))
+
+        with self.assertRaises(ZcashError):
+            parse_qr_payload(net, "zcash:" + encoded + "?amount=abc")
+        with self.assertRaises(ZcashError):
+            parse_qr_payload(net, "not an address")
+
+    def test_encrypted_usk_backup(self):
+        net = ZcashConsensusParameters.MAIN_NETWORK
+        usk = ZcashUnifiedSpendingKey.from_seed(net, [1] + [0] * 31, ZcashAccountId(0))
+
+        blob = export_usk_encrypted(usk, "correct horse")
+        self.assertNotEqual(blob, export_usk_encrypted(usk, "correct horse"))
+        restored = import_usk_encrypted(blob, "correct horse")
+        era = latest_keys_era()
+        self.assertEqual(usk.to_bytes(era), restored.to_bytes(era))
+
+        with self.assertRaises(ZcashError):
+            import_usk_encrypted(blob, "wrong horse")
+        with self.assertRaises(ZcashError):
+            import_usk_encrypted(blob[:-1] + [blob[-1] ^ 1], "correct horse")
+        with self.assertRaises(ZcashError):
+            import_usk_encrypted(blob[:10], "correct horse")
+
+    def test_seed_shares(self):
+        seed = list(range(32))
+        shares = split_seed(seed, 3, 5)
+        self.assertEqual(5, len(shares))
+
+        self.assertEqual(seed, combine_seed_shares(shares[:3]))
+        self.assertEqual(seed, combine_seed_shares([shares[4], shares[1], shares[2]]))
+        self.assertEqual(seed, combine_seed_shares(shares))
+
+        with self.assertRaises(ZcashError):
+            combine_seed_shares(shares[:2])
+        with self.assertRaises(ZcashError):
+            combine_seed_shares([shares[0], shares[0], shares[1]])
+        with self.assertRaises(ZcashError):
+            combine_seed_shares(shares[:2] + [split_seed(seed, 3, 5)[2]])
+        corrupted = shares[2][:-1] + [shares[2][-1] ^ 1]
+        with self.assertRaises(ZcashError):
+            combine_seed_shares(shares[:2] + [corrupted])
+
+        self.assertEqual(seed, combine_seed_shares(split_seed(seed, 1, 1)))
+        with self.assertRaises(ZcashError):
+            split_seed(seed, 4, 3)
+
+    def test_error_codes(self):
+        codes = error_codes()
+        self.assertEqual(len(codes), len(set(c.code for c in codes)))
+        self.assertEqual(len(codes), len(set(c.key for c in codes)))
+
+        with self.assertRaises(ZcashError.SproutNotSupported) as error:
+            ZcashPaymentAddress.decode(ZcashConsensusParameters.MAIN_NETWORK, "zc8E5gYid86n4bo2Usdq1cpr7PpfoJGzttwBHEEgGhGkLUg7SPPVFNB2AkRFXZ7usfphup5426dt1buMmY3fkYeRrQGLa8y")
+        code = error_code_for_variant(type(error.exception).__name__)
+        self.assertEqual(1022, code.code)
+        self.assertEqual("sprout_not_supported", code.key)
+
+        self.assertIsNone(error_code_for_variant("NotAnError"))
+
+    def test_transparent_address_from_multisig_redeem_script(self):
+        net = ZcashConsensusParameters.MAIN_NETWORK
+        pubkeys = [
+            bytes.fromhex("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+            bytes.fromhex("02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5"),
+        ]
+        redeem_script = multisig_redeem_script(1, pubkeys)
+
+        self.assertEqual(0x51, redeem_script[0])
+        self.assertEqual([0x52, 0xae], redeem_script[-2:])
+
+        address = ZcashTransparentAddress.from_redeem_script(redeem_script)
+
+        assert address.is_script()
+        self.assertEqual("t3RXqXRqj9W9NRY6gY2ibUZzwDe3wmy8zpF", address.encode(net))
+
+        with self.assertRaises(ZcashError):
+            multisig_redeem_script(3, pubkeys)
+
+    def test_watched_p2sh_outputs(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        pubkeys = [
+            bytes.fromhex("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+            bytes.fromhex("02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5"),
+        ]
+        redeem_script = multisig_redeem_script(2, pubkeys)
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            address = db.watch_p2sh_script(redeem_script)
+            self.assertEqual([address.encode(params)],
+                             [watched.encode(params) for watched in db.get_watched_p2sh_addresses()])
+            self.assertEqual(redeem_script, db.get_p2sh_redeem_script(address))
+
+            def put_utxo(n, height):
+                txout = ZcashTxOut(ZcashAmount(10000 * n), address.script())
+                db.put_received_p2sh_utxo(ZcashWalletTransparentOutput.from_parts(
+                    ZcashOutPoint([n] * 32, 0), txout, ZcashBlockHeight(height)))
+
+            def unspent_values():
+                outputs = db.get_unspent_p2sh_outputs(address, fixture.chain_tip_height)
+                return [output.value().value() for output in outputs]
+
+            put_utxo(1, fixture.birthday_height + 2)
+            put_utxo(2, fixture.birthday_height + 4)
+            put_utxo(3, fixture.birthday_height + 8)
+            db.mark_p2sh_output_spent(ZcashOutPoint([2] * 32, 0), ZcashTxId.from_bytes([9] * 32))
+            self.assertEqual([10000, 30000], unspent_values())
+
+            # The outputs mined above the height the wallet is rewound to are gone with
+            # their blocks, and are found again on the new chain.
+            db.truncate_to_height(fixture.birthday_height + 5)
+            self.assertEqual([10000], unspent_values())
+            put_utxo(3, fixture.birthday_height + 6)
+            self.assertEqual([10000, 30000], unspent_values())
+
+            with self.assertRaises(ZcashError):
+                db.mark_p2sh_output_spent(ZcashOutPoint([4] * 32, 0), ZcashTxId.from_bytes([9] * 32))
+
+    def test_p2sh_transaction_builder(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        pubkeys = [
+            bytes.fromhex("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+            bytes.fromhex("02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5"),
+        ]
+        redeem_script = multisig_redeem_script(2, pubkeys)
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            address = db.watch_p2sh_script(redeem_script)
+            for n in [1, 2]:
+                db.put_received_p2sh_utxo(ZcashWalletTransparentOutput.from_parts(
+                    ZcashOutPoint([n] * 32, 0), ZcashTxOut(ZcashAmount(20000), address.script()),
+                    ZcashBlockHeight(fixture.birthday_height)))
+            outputs = db.get_unspent_p2sh_outputs(address, fixture.chain_tip_height)
+            recipient = ZcashTransparentAddress.decode(
+                params, db.get_transparent_receivers(ZcashAccountId(0))[0].address)
+
+            builder = ZcashP2shTransactionBuilder(
+                params, ZcashBlockHeight(fixture.chain_tip_height + 1), 100)
+            for output in outputs:
+                builder.add_input(output.outpoint(), output.txout(), redeem_script)
+            with self.assertRaises(ZcashError):
+                builder.add_input(outputs[0].outpoint(), outputs[0].txout(), redeem_script[1:])
+            builder.add_output(recipient, ZcashAmount(25000))
+            self.assertEqual(15000, builder.fee())
+
+            sighashes = [builder.signature_hash(index) for index in [0, 1]]
+            self.assertEqual([32, 32], [len(sighash) for sighash in sighashes])
+            self.assertNotEqual(sighashes[0], sighashes[1])
+            self.assertEqual(sighashes[0], builder.signature_hash(0))
+            with self.assertRaises(ZcashError):
+                builder.signature_hash(2)
+
+            with self.assertRaises(ZcashError):
+                builder.build()
+            # The signatures of the cosigners, in the order of their keys, after the dummy
+            # value `OP_CHECKMULTISIG` consumes.
+            signatures = [bytes([0x30] * 70) + b"\x01", bytes([0x31] * 71) + b"\x01"]
+            for index in [0, 1]:
+                builder.set_signatures(index, [b""] + signatures)
+            tx = builder.build()
+
+            self.assertEqual(fixture.chain_tip_height + 101, tx.expiry_height().value())
+            script_sig = bytes([0x00, 71]) + signatures[0] + bytes([72]) + signatures[1] \
+                + bytes([len(redeem_script)]) + bytes(redeem_script)
+            vin = tx.transparent_bundle().vin()
+            self.assertEqual(
+                bytes([1] * 32) + bytes(4) + bytes([len(script_sig)]) + script_sig + bytes([0xff] * 4),
+                bytes(vin[0].to_bytes()))
+            self.assertEqual(2, len(vin))
+
+            for output in outputs:
+                db.mark_p2sh_output_spent(output.outpoint(), tx.txid())
+            self.assertEqual([], db.get_unspent_p2sh_outputs(address, fixture.chain_tip_height))
+
+    def test_outpoint_from_hex(self):
+        txid = "01" + "00" * 31
+        outpoint = ZcashOutPoint.from_hex(txid + ":3")
+        self.assertEqual(txid, outpoint.txid().to_hex_string())
+        self.assertEqual(3, outpoint.index())
+        self.assertEqual(outpoint.txid().to_bytes(), ZcashOutPoint(outpoint.txid().to_bytes(), 3).txid().to_bytes())
+
+        with self.assertRaises(ZcashError):
+            ZcashOutPoint.from_hex(txid)
+
+    def test_sync_eta_estimation(self):
+        class Listener(ZcashSyncStatusListener):
+            def __init__(self):
+                self.etas = []
+
+            def on_sync_eta(self, eta):
+                self.etas.append(eta)
+
+        summary = ZcashWalletSummary({}, ZcashBlockHeight(2000), ZcashBlockHeight(1000), None)
+        listener = Listener()
+        estimator = ZcashSyncEtaEstimator(60, listener)
+
+        eta = estimator.sync_eta(summary)
+        self.assertEqual(1000, eta.blocks_remaining)
+        self.assertIsNone(eta.seconds_remaining)
+
+        estimator.record_scanned_blocks(500)
+        eta = estimator.sync_eta(summary)
+        self.assertEqual(1000, eta.blocks_remaining)
+        self.assertGreater(eta.blocks_per_second, 0)
+        self.assertIsNotNone(eta.seconds_remaining)
+        self.assertEqual(2, len(listener.etas))
+
+        synced = ZcashWalletSummary({}, ZcashBlockHeight(2000), ZcashBlockHeight(2000), None)
+        self.assertEqual(0, estimator.sync_eta(synced).seconds_remaining)
+
+    def test_plain_value_accessors(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            summary = db.get_wallet_summary(fixture.min_confirmations)
+            values = ZcashAccountBalanceValues(fixture.total_balance, fixture.spendable_balance, 0)
+            self.assertEqual({0: values}, summary.account_balance_values())
+            self.assertEqual(values, summary.account_balances()["0"].values())
+            self.assertEqual(fixture.chain_tip_height, summary.chain_tip_height_value())
+            self.assertEqual(fixture.chain_tip_height, summary.fully_scanned_height_value())
+
+            scan_range = ZcashScanRange.from_heights(fixture.birthday_height, fixture.chain_tip_height,
+                                                     ZcashScanPriority.HISTORIC)
+            self.assertEqual((fixture.birthday_height, fixture.chain_tip_height),
+                             (scan_range.start_height(), scan_range.end_height()))
+
+            address = db.get_transparent_receivers(ZcashAccountId(0))[0].address
+            txout = ZcashTxOut(ZcashAmount(50000), ZcashTransparentAddress.decode(params, address).script())
+            db.put_received_transparent_utxo(ZcashWalletTransparentOutput.from_parts(
+                ZcashOutPoint([1] * 32, 0), txout, ZcashBlockHeight(fixture.birthday_height)))
+            self.assertEqual([ZcashTransparentBalanceValue(address, 50000)],
+                             db.get_transparent_balance_values(ZcashAccountId(0), fixture.chain_tip_height))
+
+    def test_sync_lag(self):
+        summary = ZcashWalletSummary({}, ZcashBlockHeight(2000), ZcashBlockHeight(1990), None)
+        self.assertEqual(10, summary.sync_lag())
+        self.assertFalse(summary.is_synced())
+        self.assertTrue(summary.is_synced_within(10))
+        self.assertFalse(summary.is_synced_within(9))
+
+        with tempfile.TemporaryDirectory() as folder:
+            db = ZcashWalletDb.for_path(os.path.join(folder, "bare.db"), ZcashConsensusParameters.TEST_NETWORK)
+            db.init_without_seed()
+            self.assertIsNone(db.sync_lag())
+            self.assertFalse(db.is_synced(100))
+
+            db_path = os.path.join(folder, "wallet.db")
+            fixture = create_fixture_wallet(db_path, FixtureScenario.EMPTY)
+            db = ZcashWalletDb.for_path(db_path, ZcashConsensusParameters.TEST_NETWORK)
+            db.update_chain_tip(fixture.birthday_height + 9999)
+            self.assertEqual(10000, db.sync_lag())
+            self.assertEqual(db.get_wallet_summary(0).sync_lag(), db.sync_lag())
+            self.assertTrue(db.is_synced(10000))
+            self.assertFalse(db.is_synced(9999))
+
+    def test_wallet_db_two_phase_init(self):
+        with tempfile.TemporaryDirectory() as folder:
+            db = ZcashWalletDb.for_path(os.path.join(folder, "wallet.db"), ZcashConsensusParameters.TEST_NETWORK)
+
+            # A fresh database has no account to migrate, so no seed is needed.
+            self.assertEqual(ZcashWalletInitResult.INITIALIZED, db.init_without_seed())
+            db.init_with_seed([1] + [0] * 31)
+            self.assertEqual(ZcashWalletInitResult.INITIALIZED, db.init_without_seed())
+
+    def test_lower_account_birthday(self):
+        def birthday(height):
+            return ZcashAccountBirthday.from_treestate(ZcashTreeState("test", height - 1, "", 0, "000000", ""), None)
+
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            b = create_fixture_wallet(db_path, FixtureScenario.EMPTY).birthday_height
+            db = ZcashWalletDb.for_path(db_path, ZcashConsensusParameters.TEST_NETWORK)
+            account = ZcashAccountId(0)
+
+            db.lower_account_birthday(account, birthday(b - 10000))
+            self.assertEqual(b - 10000, db.get_account_birthday(account).value())
+            self.assertTrue(any(
+                r.start_height() <= b - 10000 < r.end_height() for r in db.suggest_scan_ranges()
+            ))
+
+            with self.assertRaises(ZcashError):
+                db.lower_account_birthday(account, birthday(b - 5000))
+
+            # The blocks already queued with a higher priority than Historic keep it.
+            db_path = os.path.join(folder, "other.db")
+            b = create_fixture_wallet(db_path, FixtureScenario.EMPTY).birthday_height
+            db = ZcashWalletDb.for_path(db_path, ZcashConsensusParameters.TEST_NETWORK)
+            with sqlite3.connect(db_path) as conn:
+                conn.execute("DELETE FROM scan_queue WHERE block_range_end = ?", (b,))
+                conn.executemany("INSERT INTO scan_queue VALUES (?, ?, ?)", [
+                    (b - 20000, b - 5000, 0), (b - 5000, b - 4900, 40), (b - 4900, b, 0),
+                ])
+            db.lower_account_birthday(account, birthday(b - 10000))
+            queue = [(r.start_height(), r.end_height(), r.priority()) for r in db.suggest_scan_ranges()]
+            self.assertIn((b - 5000, b - 4900, ZcashScanPriority.FOUND_NOTE), queue)
+            self.assertIn((b - 10000, b - 5000, ZcashScanPriority.HISTORIC), queue)
+            self.assertIn((b - 4900, b, ZcashScanPriority.HISTORIC), queue)
+
+    def test_import_account_ufvk(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+
+        def birthday(height):
+            return ZcashAccountBirthday.from_treestate(ZcashTreeState("test", height - 1, "", 0, "000000", ""), None)
+
+        def queue(db):
+            return [(r.start_height(), r.end_height(), r.priority()) for r in db.suggest_scan_ranges()]
+
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            b = create_fixture_wallet(db_path, FixtureScenario.EMPTY).birthday_height
+            db = ZcashWalletDb.for_path(db_path, params)
+            db.update_chain_tip(b + 10000)
+            scan_ranges = queue(db)
+
+            ufvk = ZcashUnifiedSpendingKey.from_seed(params, [2] + [0] * 31, ZcashAccountId(0)) \
+                .to_unified_full_viewing_key()
+            derivation = ZcashDeviceDerivation("Ledger Nano S Plus", "m/32'/1'/0'", [1, 2, 3, 4])
+            account = db.import_account_ufvk(ufvk, birthday(b + 5000), derivation)
+            self.assertEqual(ZcashAccountId(1), account)
+            self.assertEqual(derivation, db.get_device_derivation(account))
+            self.assertIsNone(db.get_device_derivation(ZcashAccountId(0)))
+            self.assertEqual(account, db.get_account_for_ufvk(ufvk))
+            self.assertEqual(b + 5000, db.get_account_birthday(account).value())
+            self.assertEqual(ufvk.default_address().address.encode(params),
+                db.get_current_address(account).encode(params))
+            # The blocks from the birthday on were already queued, with higher priorities.
+            self.assertEqual(scan_ranges, queue(db))
+
+            with self.assertRaises(ZcashError):
+                db.import_account_ufvk(ufvk, birthday(b + 5000), derivation)
+            other = ZcashUnifiedSpendingKey.from_seed(params, [3] + [0] * 31, ZcashAccountId(0)) \
+                .to_unified_full_viewing_key()
+            # Birthdays before the Sapling activation are refused.
+            with self.assertRaises(ZcashError):
+                db.import_account_ufvk(other, birthday(200000), derivation)
+            self.assertEqual(2, len(db.get_unified_full_viewing_keys()))
+
+            # Importing an account with an earlier birthday queues the blocks up to it.
+            other_account = db.import_account_ufvk(other, birthday(b - 10000), derivation)
+            self.assertTrue(any(
+                r.start_height() <= b - 10000 < r.end_height() for r in db.suggest_scan_ranges()
+            ))
+            self.assertEqual(ZcashAccountId(2), other_account)
+
+    def test_diversifier_index_lookup(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            seed = create_fixture_wallet(db_path, FixtureScenario.EMPTY).seed
+            db = ZcashWalletDb.for_path(db_path, params)
+            account = ZcashAccountId(0)
+
+            current = db.get_current_address(account)
+            metadata = db.get_diversifier_index_for_address(current.encode(params))
+            self.assertEqual(account, metadata.account())
+            index = metadata.diversifier_index()
+            self.assertEqual(current.encode(params),
+                db.get_address_for_diversifier_index(account, index).encode(params))
+            transparent = encode_transparent_address_p(params, current.transparent())
+            self.assertEqual(index.to_bytes(),
+                db.get_diversifier_index_for_address(transparent).diversifier_index().to_bytes())
+
+            # Addresses the wallet did not generate are found by decryption.
+            ufvk = ZcashUnifiedSpendingKey.from_seed(params, seed, account).to_unified_full_viewing_key()
+            j = 1000
+            while ufvk.address(ZcashDiversifierIndex.from_u32(j)) is None:
+                j += 1
+            address = ufvk.address(ZcashDiversifierIndex.from_u32(j))
+            self.assertIsNone(db.get_address_for_diversifier_index(account, ZcashDiversifierIndex.from_u32(j)))
+            metadata = db.get_diversifier_index_for_address(address.encode(params))
+            self.assertEqual(j, metadata.diversifier_index().to_u32())
+            sapling = encode_payment_address_p(params, address.sapling())
+            self.assertEqual(j, db.get_diversifier_index_for_address(sapling).diversifier_index().to_u32())
+
+            other = ZcashUnifiedSpendingKey.from_seed(params, [2] + [0] * 31, account) \
+                .to_unified_full_viewing_key().default_address().address
+            self.assertIsNone(db.get_diversifier_index_for_address(other.encode(params)))
+
+    def test_invoices(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with fixture_wallet(FixtureScenario.EMPTY) as (_, db):
+            account = ZcashAccountId(0)
+
+            first = db.create_invoice(account, 150000000, "order-42")
+            second = db.create_invoice(account, 1000, None)
+            self.assertNotEqual(first.id, second.id)
+            self.assertNotEqual(first.address, second.address)
+            self.assertEqual(account, db.get_diversifier_index_for_address(first.address).account())
+
+            request = ZcashTransactionRequest.from_uri(params, first.payment_uri)
+            payment = request.payments()[0]
+            self.assertEqual(first.address, payment.recipient_address.encode(params))
+            self.assertEqual(150000000, payment.amount.value())
+            self.assertIsNotNone(payment.memo)
+            self.assertEqual("zcash:" + second.address + "?amount=0.00001", second.payment_uri)
+
+            status = db.check_invoice_paid(first.id, 10)
+            self.assertEqual((first.id, 150000000, 0, 0, False),
+                (status.invoice_id, status.amount, status.received, status.pending, status.paid))
+
+            with self.assertRaises(ZcashError):
+                db.check_invoice_paid(1000, 10)
+            with self.assertRaises(ZcashError):
+                db.create_invoice(account, 1000, "x" * 513)
+
+    def test_unified_address_request(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with fixture_wallet(FixtureScenario.EMPTY) as (_, db):
+            account = ZcashAccountId(0)
+
+            address = db.get_next_available_address(account)
+            self.assertIsNotNone(address.sapling())
+            self.assertIsNotNone(address.transparent())
+
+            address = db.get_next_available_address(account, ZcashUnifiedAddressRequest(False, True, False))
+            self.assertIsNotNone(address.sapling())
+            self.assertIsNone(address.transparent())
+            self.assertEqual(account, db.get_diversifier_index_for_address(address.encode(params)).account())
+
+            with self.assertRaisesRegex(ZcashError, "shielded receiver"):
+                db.get_next_available_address(account, ZcashUnifiedAddressRequest(False, False, True))
+            with self.assertRaisesRegex(ZcashError, "orchard"):
+                db.get_next_available_address(account, ZcashUnifiedAddressRequest(True, True, True))
+
+    def test_replace_ufvk(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with fixture_wallet(FixtureScenario.EMPTY) as (fixture, db):
+            account = ZcashAccountId(0)
+            db.get_next_available_address(account)
+
+            ufvk = ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, account).to_unified_full_viewing_key()
+            report = db.replace_ufvk(account, ufvk, True)
+            self.assertEqual((account, 2, [], False),
+                (report.account, report.checked_addresses, report.mismatched_addresses, report.applied))
+            self.assertTrue(db.replace_ufvk(account, ufvk, False).applied)
+            self.assertEqual(account, db.get_account_for_ufvk(ufvk))
+
+            # Another key derives other addresses, so it is not stored.
+            other = ZcashUnifiedSpendingKey.from_seed(params, [2] * 32, account).to_unified_full_viewing_key()
+            report = db.replace_ufvk(account, other, False)
+            self.assertEqual(2, len(report.mismatched_addresses))
+            self.assertFalse(report.applied)
+            self.assertIsNone(db.get_account_for_ufvk(other))
+
+            with self.assertRaises(ZcashError):
+                db.replace_ufvk(ZcashAccountId(1), ufvk, True)
+
+    def test_address_usage(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            account = ZcashAccountId(0)
+            db.get_next_available_address(account)
+
+            # The funds of the fixture were received by the default address, whose
+            # transparent receiver is reused here.
+            [default, unused] = db.address_usage(account)
+            taddr = ZcashTransparentAddress.decode(params, default.transparent_receiver)
+            for n in [1, 2]:
+                txout = ZcashTxOut(ZcashAmount(10000), taddr.script())
+                db.put_received_transparent_utxo(ZcashWalletTransparentOutput.from_parts(
+                    ZcashOutPoint([n] * 32, 0), txout, ZcashBlockHeight(fixture.birthday_height)))
+
+            usage = db.address_usage(account)
+            self.assertEqual([default.address, unused.address], [u.address for u in usage])
+            self.assertLess(usage[0].diversifier_index.to_u32(), usage[1].diversifier_index.to_u32())
+            self.assertEqual([(1, 2), (0, 0)], [(u.shielded_receipts, u.transparent_receipts) for u in usage])
+            self.assertEqual([], db.address_usage(ZcashAccountId(1)))
+
+    def test_account_archive_and_deletion(self):
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            fixture = create_fixture_wallet(db_path, FixtureScenario.EMPTY)
+            db = ZcashWalletDb.for_path(db_path, ZcashConsensusParameters.TEST_NETWORK)
+            tree_state = ZcashTreeState("test", fixture.birthday_height - 10001, "", 0, "000000", "")
+            first = db.create_account(fixture.seed, ZcashAccountBirthday.from_treestate(tree_state, None)).account_id
+            second = ZcashAccountId(0)
+
+            db.archive_account(second)
+            self.assertEqual([second], db.get_archived_accounts())
+            db.unarchive_account(second)
+            self.assertEqual([], db.get_archived_accounts())
+
+            report = db.delete_account(first, True)
+            self.assertFalse(report.applied)
+            self.assertEqual(1, report.addresses)
+            self.assertEqual(fixture.birthday_height - 10000, db.get_account_birthday(first).value())
+
+            report = db.delete_account(first, False)
+            self.assertTrue(report.applied)
+            with self.assertRaises(ZcashError):
+                db.get_account_birthday(first)
+            self.assertEqual(fixture.birthday_height, db.get_wallet_birthday().value())
+            self.assertTrue(all(r.start_height() >= fixture.birthday_height for r in db.suggest_scan_ranges()))
+
+            with self.assertRaises(ZcashError):
+                db.delete_account(first, False)
+
+            # The transactions of the account and what refers to them go along with it.
+            db_path = os.path.join(folder, "change.db")
+            create_fixture_wallet(db_path, FixtureScenario.PENDING_CHANGE)
+            with sqlite3.connect(db_path) as conn:
+                conn.execute("""INSERT INTO uniffi_replaced_transactions (txid, replaced_by)
+                    SELECT txid, txid FROM transactions LIMIT 1""")
+            db = ZcashWalletDb.for_path(db_path, ZcashConsensusParameters.TEST_NETWORK)
+            report = db.delete_account(ZcashAccountId(0), False)
+            self.assertEqual(2, report.transactions)
+            self.assertEqual(2, report.received_notes)
+            with sqlite3.connect(db_path) as conn:
+                for table in ["transactions", "uniffi_replaced_transactions"]:
+                    self.assertEqual(0, conn.execute(f"SELECT COUNT(*) FROM {table}").fetchone()[0])
+
+    def test_unmined_tx_confirmations(self):
+        with fixture_wallet(FixtureScenario.EMPTY) as (_, db):
+            self.assertEqual(0, db.get_tx_confirmations(ZcashTxId.from_bytes([0] * 32)))
+
+    def test_transactions_batch_lookup(self):
+        with fixture_wallet(FixtureScenario.EMPTY) as (_, db):
+            txid = ZcashTxId.from_bytes([1] + [0] * 31)
+            results = db.get_transactions_by_ids([txid])
+            self.assertEqual([txid.to_hex_string()], list(results.keys()))
+            self.assertIsNone(results[txid.to_hex_string()].transaction)
+            self.assertIsNotNone(results[txid.to_hex_string()].error)
+
+    def test_block_times(self):
+        with tempfile.TemporaryDirectory() as folder:
+            path = os.path.join(folder, "wallet.db")
+            create_fixture_wallet(path, FixtureScenario.EMPTY)
+            db = ZcashWalletDb.for_path(path, ZcashConsensusParameters.TEST_NETWORK)
+            self.assertIsNone(db.get_block_times(ZcashBlockHeight(300000)))
+            self.assertIsNone(db.get_tx_block_times(ZcashTxId.from_bytes([0] * 32)))
+
+            # Block times are not monotonic, the median time past is.
+            times = [1000, 1300, 1100, 1200, 1500, 1400, 1700, 1600, 1900, 1800, 2000, 2100]
+            with sqlite3.connect(path) as conn:
+                conn.executemany(
+                    "INSERT INTO blocks (height, hash, time, sapling_tree) VALUES (?, ?, ?, x'00')",
+                    [(300000 + i, bytes([i] * 32), time) for i, time in enumerate(times)],
+                )
+
+            times_at = db.get_block_times(ZcashBlockHeight(300010))
+            self.assertEqual(300010, times_at.height.value())
+            self.assertEqual(2000, times_at.block_time)
+            self.assertEqual(1500, times_at.median_time_past)
+            self.assertEqual(1600, db.get_block_times(ZcashBlockHeight(300011)).median_time_past)
+            self.assertIsNone(db.get_block_times(ZcashBlockHeight(300005)).median_time_past)
+
+    def test_chain_reorg(self):
+        zts = TestSupport.from_reorg_csv_file()
+        params = ZcashConsensusParameters.TEST_NETWORK
+        birthday_height = zts.get_as_u32("reorg_birthday_height")
+        fork_height = zts.get_as_u32("reorg_fork_height")
+
+        def cache_branch(block_db, blocks_dir, name):
+            start_height = zts.get_as_u32("reorg_" + name + "_start_height")
+            hashes = zts.get_as_u8_array("reorg_" + name + "_block_hashes")
+            times = zts.get_as_u32_array("reorg_" + name + "_block_times")
+            outputs = zts.get_as_u32_array("reorg_" + name + "_sapling_outputs")
+            metas = []
+            for i in range(len(times)):
+                meta = ZcashBlockMeta(ZcashBlockHeight(start_height + i),
+                                      ZcashBlockHash.from_slice(hashes[i * 32:(i + 1) * 32]),
+                                      times[i], outputs[i], 0)
+                with open(meta.block_file_path(blocks_dir), "wb") as block_file:
+                    block_file.write(bytes(zts.get_as_u8_array("reorg_" + name + "_block_" + str(i))))
+                metas.append(meta)
+            block_db.write_block_metadata(metas)
+            return start_height + len(times) - 1
+
+        def balance(db):
+            summary = db.get_wallet_summary(1)
+            return sum(b.total().value() for b in summary.account_balances().values())
+
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            cache_root = os.path.join(folder, "cache")
+            blocks_dir = os.path.join(cache_root, "blocks")
+            os.makedirs(blocks_dir)
+
+            db = ZcashWalletDb.for_path(db_path, params)
+            db.initialize(zts.get_as_u8_array("reorg_seed"))
+            tree_state = ZcashTreeState("test", birthday_height - 1, "", 0, "000000", "")
+            db.create_account(zts.get_as_u8_array("reorg_seed"), ZcashAccountBirthday.from_treestate(tree_state, None))
+            block_db = ZcashFsBlockDb.for_path(cache_root)
+            block_db.initialize(cache_root)
+
+            cache_branch(block_db, blocks_dir, "common")
+            tip = cache_branch(block_db, blocks_dir, "chain_a")
+            db.update_chain_tip(tip)
+            scan_cached_blocks(params, cache_root, db_path, ZcashBlockHeight(birthday_height), 100)
+            self.assertEqual(zts.get_as_u64("reorg_balance_chain_a"), balance(db))
+
+            db.truncate_to_height(fork_height)
+            block_db.truncate_to_height(fork_height)
+            self.assertEqual(fork_height, block_db.get_max_cached_height().value())
+            self.assertEqual(zts.get_as_u64("reorg_balance_fork"), balance(db))
+
+            tip = cache_branch(block_db, blocks_dir, "chain_b")
+            db.update_chain_tip(tip)
+            scan_cached_blocks(params, cache_root, db_path, ZcashBlockHeight(fork_height + 1), 100)
+            self.assertEqual(zts.get_as_u64("reorg_balance_chain_b"), balance(db))
+            self.assertEqual(tip, db.block_max_scanned().block_height().value())
+
+    def test_bulk_block_lookups(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            fixture = create_fixture_wallet(db_path, FixtureScenario.SYNCED_WITH_FUNDS)
+            db = ZcashWalletDb.for_path(db_path, params)
+
+            tip = fixture.chain_tip_height
+            results = db.get_block_hashes(tip - 1, tip + 2)
+            self.assertEqual([tip - 1, tip, tip + 1], [r.height for r in results])
+            self.assertEqual(db.get_max_height_hash().block_hash, results[1].block_hash)
+            self.assertIsNone(results[2].block_hash)
+            self.assertTrue(all(r.error is None for r in results))
+            with self.assertRaises(ZcashError.InvalidArgument):
+                db.get_block_hashes(tip, tip - 1)
+
+            cache_root = os.path.join(folder, "cache")
+            os.makedirs(os.path.join(cache_root, "blocks"))
+            block_db = ZcashFsBlockDb.for_path(cache_root)
+            block_db.initialize(cache_root)
+            metas = [ZcashBlockMeta(ZcashBlockHeight(height), ZcashBlockHash.from_slice([i] * 32), 0, 1, 0)
+                     for i, height in enumerate([tip - 1, tip])]
+            # Only the file of the last block is cached, which does not fail the others.
+            with open(metas[1].block_file_path(os.path.join(cache_root, "blocks")), "wb") as block_file:
+                block_file.write(b"\x00")
+            results = block_db.write_block_metadata_each(metas)
+            self.assertEqual([tip - 1, tip], [r.height for r in results])
+            self.assertIn("MissingBlockPath", results[0].error)
+            self.assertIsNone(results[1].error)
+
+            found = block_db.find_blocks([tip, tip + 1])
+            self.assertEqual([tip, tip + 1], [r.height for r in found])
+            self.assertEqual(metas[1].block_file_path(""), found[0].block_meta.block_file_path(""))
+            self.assertIsNone(found[1].block_meta)
+            self.assertIsNone(found[1].error)
+
+    def test_compressed_block_cache(self):
+        zts = TestSupport.from_reorg_csv_file()
+        params = ZcashConsensusParameters.TEST_NETWORK
+        birthday_height = zts.get_as_u32("reorg_birthday_height")
+
+        def cache_branch(block_db, name):
+            start_height = zts.get_as_u32("reorg_" + name + "_start_height")
+            hashes = zts.get_as_u8_array("reorg_" + name + "_block_hashes")
+            times = zts.get_as_u32_array("reorg_" + name + "_block_times")
+            outputs = zts.get_as_u32_array("reorg_" + name + "_sapling_outputs")
+            paths = []
+            for i in range(len(times)):
+                meta = ZcashBlockMeta(ZcashBlockHeight(start_height + i),
+                                      ZcashBlockHash.from_slice(hashes[i * 32:(i + 1) * 32]),
+                                      times[i], outputs[i], 0)
+                block_db.write_block_file(meta, zts.get_as_u8_array("reorg_" + name + "_block_" + str(i)))
+                block_db.write_block_metadata([meta])
+                paths.append(meta.block_file_path(os.path.join(block_db.root(), "blocks")))
+            return paths, start_height + len(times) - 1
+
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            block_db = ZcashFsBlockDb.for_network(os.path.join(folder, "cache"), params)
+
+            db = ZcashWalletDb.for_path(db_path, params)
+            db.initialize(zts.get_as_u8_array("reorg_seed"))
+            tree_state = ZcashTreeState("test", birthday_height - 1, "", 0, "000000", "")
+            db.create_account(zts.get_as_u8_array("reorg_seed"), ZcashAccountBirthday.from_treestate(tree_state, None))
+
+            # The plain blocks cached before compression is enabled are still read.
+            self.assertIsNone(block_db.compression_level())
+            plain, _ = cache_branch(block_db, "common")
+            block_db.set_compression_level(19)
+            self.assertEqual(19, block_db.compression_level())
+            compressed, tip = cache_branch(block_db, "chain_a")
+
+            zstd_magic = b"\x28\xb5\x2f\xfd"
+            with open(plain[0], "rb") as block_file:
+                self.assertNotEqual(zstd_magic, block_file.read(4))
+            with open(compressed[-1], "rb") as block_file:
+                self.assertEqual(zstd_magic, block_file.read(4))
+
+            db.update_chain_tip(tip)
+            scan_cached_blocks(params, block_db.root(), db_path, ZcashBlockHeight(birthday_height), 100)
+            summary = db.get_wallet_summary(1)
+            balance = sum(b.total().value() for b in summary.account_balances().values())
+            self.assertEqual(zts.get_as_u64("reorg_balance_chain_a"), balance)
+
+            with self.assertRaises(ZcashError):
+                block_db.set_compression_level(100)
+            self.assertEqual(19, block_db.compression_level())
+
+    def test_block_caches_per_network(self):
+        with tempfile.TemporaryDirectory() as folder:
+            caches = ZcashBlockCaches(folder)
+            mainnet = caches.get(ZcashConsensusParameters.MAIN_NETWORK, None)
+            testnet = caches.get(ZcashConsensusParameters.TEST_NETWORK, None)
+            server = caches.get(ZcashConsensusParameters.TEST_NETWORK, "https://lwd.example:9067")
+
+            self.assertEqual(ZcashConsensusParameters.MAIN_NETWORK, mainnet.network())
+            self.assertEqual(ZcashConsensusParameters.TEST_NETWORK, testnet.network())
+            self.assertEqual(3, len({mainnet.root(), testnet.root(), server.root()}))
+            self.assertEqual(testnet.root(), caches.get(ZcashConsensusParameters.TEST_NETWORK).root())
+            self.assertIsNone(testnet.get_max_cached_height())
+            self.assertIsNone(ZcashFsBlockDb.for_path(folder).network())
+
+            db_path = os.path.join(folder, "wallet.db")
+            fixture = create_fixture_wallet(db_path, FixtureScenario.EMPTY)
+            db = ZcashWalletDb.for_path(db_path, ZcashConsensusParameters.TEST_NETWORK)
+            birthday = ZcashAccountBirthday.from_treestate(
+                ZcashTreeState("test", fixture.birthday_height - 1, "", 0, "000000", ""), None)
+            with self.assertRaises(ZcashError):
+                discover_accounts(db, fixture.seed, birthday, 1, None, mainnet)
+            with self.assertRaisesRegex(ZcashError, "mainnet network"):
+                scan_cached_blocks(ZcashConsensusParameters.TEST_NETWORK, mainnet.root(),
+                                   db_path, ZcashBlockHeight(fixture.birthday_height), 10)
+
+            # Servers cannot name the directories around those of the caches.
+            for server in ["", ".", ".."]:
+                with self.assertRaises(ZcashError.InvalidArgument):
+                    caches.get(ZcashConsensusParameters.TEST_NETWORK, server)
+
+    def test_keys_and_addresses_equality(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+
+        def ufvk(seed):
+            return ZcashUnifiedSpendingKey.from_seed(params, seed, ZcashAccountId(0)) \
+                .to_unified_full_viewing_key()
+
+        first, same, other = ufvk([1] * 32), ufvk([1] * 32), ufvk([2] * 32)
+        self.assertEqual(first, same)
+        self.assertNotEqual(first, other)
+        self.assertEqual({first: "first", other: "other"}[same], "first")
+
+        address = first.default_address().address
+        decoded = ZcashUnifiedAddress.decode(params, address.encode(params))
+        self.assertEqual(address, decoded)
+        self.assertEqual(1, len({address, decoded, same.default_address().address}))
+        self.assertNotEqual(address, other.default_address().address)
+
+        self.assertEqual(address.sapling(), decoded.sapling())
+        self.assertEqual(hash(address.sapling()), hash(decoded.sapling()))
+        self.assertEqual(address.orchard(), decoded.orchard())
+        self.assertEqual(first.orchard(), same.orchard())
+        self.assertEqual(first.orchard().to_ivk(ZcashOrchardScope.EXTERNAL),
+                         same.orchard().to_ivk(ZcashOrchardScope.EXTERNAL))
+
+        transparent = ZcashTransparentAddress.from_public_key([1] * 20)
+        self.assertIn(ZcashTransparentAddress.from_public_key([1] * 20), {transparent})
+        self.assertNotEqual(transparent, ZcashTransparentAddress.from_script([1] * 20))
+
+    def test_display_strings(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        usk = ZcashUnifiedSpendingKey.from_seed(params, [1] * 32, ZcashAccountId(0))
+        self.assertEqual("ZcashUnifiedSpendingKey(<redacted>)", usk.to_display_string())
+        self.assertEqual(usk.to_display_string(), str(usk))
+        self.assertEqual("ZcashOrchardSpendingKey(<redacted>)", str(usk.orchard()))
+
+        ufvk = usk.to_unified_full_viewing_key()
+        same = ZcashUnifiedSpendingKey.from_seed(params, [1] * 32, ZcashAccountId(0)) \
+            .to_unified_full_viewing_key()
+        self.assertRegex(str(ufvk), r"^ZcashUnifiedFullViewingKey\(fingerprint [0-9a-f]{8}\)$")
+        self.assertEqual(str(ufvk), str(same))
+        self.assertNotIn(ufvk.encode(params), str(ufvk))
+
+        address = ufvk.default_address().address
+        self.assertTrue(str(address).startswith("unified("))
+        self.assertIn(str(address.sapling()), str(address))
+
+        transparent = ZcashTransparentAddress.from_public_key([1] * 20)
+        self.assertEqual("p2pkh:" + "01" * 20, str(transparent))
+
+        txid = ZcashTxId.from_bytes([1] * 32)
+        self.assertEqual(txid.to_hex_string(), str(txid))
+        self.assertEqual("0.001 ZEC", ZcashAmount(100000).to_display_string())
+
+    def test_network_info(self):
+        networks = supported_networks()
+        self.assertEqual([ZcashConsensusParameters.MAIN_NETWORK, ZcashConsensusParameters.TEST_NETWORK],
+                         [info.network for info in networks])
+        self.assertEqual(networks[1], network_info(ZcashConsensusParameters.TEST_NETWORK))
+
+        main = networks[0]
+        self.assertEqual("main", main.name)
+        self.assertEqual(133, main.coin_type)
+        self.assertEqual("zs", main.hrp_sapling_payment_address)
+        self.assertEqual("uview", main.hrp_unified_full_viewing_key)
+        self.assertEqual([0x1c, 0xb8], main.b58_pubkey_address_prefix)
+        self.assertEqual(419200, main.sapling_activation_height)
+
+        test = networks[1]
+        self.assertEqual(1, test.coin_type)
+        self.assertEqual("ztestsapling", test.hrp_sapling_payment_address)
+        self.assertEqual("utest", test.hrp_unified_address)
+        params = ZcashConsensusParameters.TEST_NETWORK
+        address = ZcashUnifiedSpendingKey.from_seed(params, [1] * 32, ZcashAccountId(0)) \
+            .to_unified_full_viewing_key().default_address().address.encode(params)
+        self.assertTrue(address.startswith(test.hrp_unified_address + "1"))
+        self.assertNotEqual([], test.default_lightwalletd_urls)
+
+    def test_runtime_config_cache_dir(self):
+        with tempfile.TemporaryDirectory() as folder:
+            cache_dir = os.path.join(folder, "cache")
+            config = ZcashRuntimeConfig()
+            with self.assertRaises(ZcashError.InvalidArgument):
+                config.set_cache_dir("")
+            config.set_cache_dir(cache_dir)
+
+            self.assertTrue(os.path.isdir(cache_dir))
+            self.assertEqual(cache_dir, ZcashRuntimeConfig().cache_dir())
+            self.assertEqual(os.path.join(cache_dir, "zcash-params"), config.params_dir())
+            with self.assertRaises(ZcashError):
+                ZcashLocalTxProver.with_default_location()
+
+            cache = ZcashFsBlockDb.with_default_location(ZcashConsensusParameters.TEST_NETWORK)
+            self.assertTrue(cache.root().startswith(os.path.join(cache_dir, "blocks")))
+            self.assertEqual(ZcashConsensusParameters.TEST_NETWORK, cache.network())
+
+    def test_fixture_wallets(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        for scenario in [FixtureScenario.EMPTY, FixtureScenario.SYNCED_WITH_FUNDS, FixtureScenario.PENDING_CHANGE]:
+            with tempfile.TemporaryDirectory() as folder:
+                db_path = os.path.join(folder, "wallet.db")
+                fixture = create_fixture_wallet(db_path, scenario)
+
+                db = ZcashWalletDb.for_path(db_path, params)
+                summary = db.get_wallet_summary(fixture.min_confirmations)
+                self.assertEqual(fixture.chain_tip_height, summary.chain_tip_height().value())
+                balance = summary.account_balances()["0"]
+                self.assertEqual(fixture.total_balance, balance.total().value())
+                self.assertEqual(fixture.spendable_balance, balance.sapling_spendable_value().value())
+                self.assertEqual(fixture.change_pending_confirmation,
+                                 fixture.total_balance - fixture.spendable_balance)
+
+    def test_notification_rules(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+
+        class Listener(ZcashNotificationListener):
+            def __init__(self):
+                self.matches = []
+
+            def on_match(self, notification):
+                self.matches.append(notification)
+
+        with fixture_wallet(FixtureScenario.PENDING_CHANGE) as (fixture, db):
+            address = ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, ZcashAccountId(0)) \
+                .to_unified_full_viewing_key().default_address().address.encode(params)
+
+            listener = Listener()
+            matcher = ZcashNotificationMatcher(params, listener)
+            everything = matcher.add_rule(ZcashNotificationRule())
+            to_address = matcher.add_rule(ZcashNotificationRule(address=address))
+            matcher.add_rule(ZcashNotificationRule(min_value=200000))
+            matcher.add_rule(ZcashNotificationRule(memo_prefix="order"))
+            matcher.add_rule(ZcashNotificationRule(account=ZcashAccountId(1)))
+
+            # The change of the later spend is not notified.
+            self.assertEqual(2, matcher.evaluate(db, fixture.birthday_height, fixture.chain_tip_height + 1))
+            self.assertEqual([everything, to_address], [m.rule_id for m in listener.matches])
+            self.assertEqual(100000, listener.matches[0].value)
+            self.assertEqual(fixture.birthday_height, listener.matches[0].height)
+            self.assertIsNone(listener.matches[0].memo)
+
+            matcher.remove_rule(everything)
+            self.assertEqual(1, matcher.evaluate(db, fixture.birthday_height, fixture.chain_tip_height + 1))
+            self.assertEqual(0, matcher.evaluate(db, fixture.birthday_height + 1, fixture.chain_tip_height + 1))
+
+            with self.assertRaises(ZcashError):
+                matcher.add_rule(ZcashNotificationRule(address="not an address"))
+
+    def test_self_memos(self):
+        memo = ZcashMemoBytes.self_memo('{"invoice": 42}')
+        self.assertEqual('{"invoice":42}', memo.self_memo_tag())
+        self.assertIsNone(ZcashMemoBytes(list(b'{"invoice": 42}')).self_memo_tag())
+        self.assertIsNone(ZcashMemoBytes.empty().self_memo_tag())
+        with self.assertRaises(ZcashError):
+            ZcashMemoBytes.self_memo("invoice 42")
+        with self.assertRaises(ZcashError):
+            ZcashMemoBytes.self_memo('"%s"' % ("x" * 512))
+
+        with fixture_wallet(FixtureScenario.PENDING_CHANGE) as (_, db):
+            # The fixture is scanned from compact blocks, which have no memos.
+            for [txid] in db.query_readonly("SELECT txid FROM transactions", []).rows:
+                self.assertEqual([], db.get_self_memo_tags(ZcashTxId.from_bytes(txid.v)))
+
+    def test_proposal_serialization(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        recipient = ZcashUnifiedSpendingKey.from_seed(params, [2] * 32, ZcashAccountId(0)) \
+            .to_unified_full_viewing_key().default_address().address
+        request = ZcashTransactionRequest([
+            ZcashPayment(ZcashRecipientAddress.unified(recipient), ZcashAmount(20000),
+                None, None, None, []),
+        ])
+
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            proposal = db.propose_transfer(ZcashAccountId(0), request, fixture.min_confirmations)
+            self.assertEqual(1, len(proposal.sapling_inputs()))
+            self.assertEqual(fixture.total_balance, proposal.sapling_inputs()[0].value)
+            self.assertEqual(10000, proposal.fee())
+            self.assertEqual([fixture.total_balance - 20000 - 10000], proposal.change())
+            self.assertEqual(request.to_uri(params), proposal.transaction_request().to_uri(params))
+
+            self.assertEqual(proposal.to_json(), ZcashProposal.from_json(proposal.to_json()).to_json())
+            self.assertEqual(proposal.to_json(), ZcashProposal.from_bytes(proposal.to_bytes()).to_json())
+            self.assertEqual(fixture.min_confirmations, json.loads(proposal.to_json())["min_confirmations"])
+            with self.assertRaises(ZcashError):
+                ZcashProposal.from_json("{}")
+            with self.assertRaises(ZcashError):
+                ZcashProposal.from_bytes([0xff])
+
+            with self.assertRaises(ZcashError.InvalidArgument) as error:
+                db.propose_transfer(ZcashAccountId(0), request, 0)
+            self.assertIn("`min_confirmations`", str(error.exception))
+
+    def test_signing_payload(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        recipient = ZcashUnifiedSpendingKey.from_seed(params, [2] + [0] * 31, ZcashAccountId(0)) \
+            .to_unified_full_viewing_key().default_address().address
+        request = ZcashTransactionRequest([
+            ZcashPayment(ZcashRecipientAddress.unified(recipient), ZcashAmount(20000),
+                None, None, None, []),
+        ])
+
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            proposal = db.propose_transfer(ZcashAccountId(0), request, fixture.min_confirmations)
+            payload = db.create_signing_payload(proposal)
+            self.assertEqual(proposal.to_json(), payload.proposal().to_json())
+            self.assertIsNone(payload.derivation())
+            self.assertEqual(32, len(payload.anchor()))
+            [spend] = payload.sapling_spends()
+            self.assertEqual(proposal.sapling_inputs()[0].value, spend.value)
+            self.assertEqual(proposal.sapling_inputs()[0].position, spend.position)
+            self.assertEqual(11, len(spend.diversifier))
+            self.assertEqual(32, len(spend.rseed))
+            self.assertFalse(spend.zip212)
+            self.assertFalse(spend.internal)
+            self.assertEqual(32, len(spend.merkle_path))
+            self.assertTrue(all(len(node) == 32 for node in spend.merkle_path))
+
+            serialized = json.loads(payload.to_json())
+            self.assertEqual(json.loads(proposal.to_json()), serialized["proposal"])
+            self.assertEqual(bytes(payload.anchor()).hex(), serialized["anchor"])
+            self.assertEqual([bytes(node).hex() for node in spend.merkle_path],
+                serialized["sapling_spends"][0]["merkle_path"])
+
+            # Proposals which the wallet would not make anymore are rejected.
+            stale = json.loads(proposal.to_json())
+            stale["fee"] += 1
+            with self.assertRaises(ZcashError):
+                db.create_signing_payload(ZcashProposal.from_json(json.dumps(stale)))
+
+    def test_txid_ordering(self):
+        a = ZcashTxId.from_bytes([1] + [0] * 31)
+        b = ZcashTxId.from_bytes([2] + [0] * 31)
+        self.assertEqual(a, ZcashTxId.from_bytes([1] + [0] * 31))
+        self.assertNotEqual(a, b)
+        self.assertEqual(1, len({a, ZcashTxId.from_bytes([1] + [0] * 31)}))
+        self.assertEqual(-1, a.compare(b))
+        self.assertEqual(1, b.compare(a))
+        self.assertEqual(0, a.compare(a))
+
+    def test_block_batch(self):
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            tip = db.block_max_scanned()
+            db.update_chain_tip(fixture.chain_tip_height + 10)
+
+            def empty_block(offset):
+                height = tip.block_height().value() + offset
+                metadata = ZcashBlockMetadata.from_parts(
+                    ZcashBlockHeight(height),
+                    ZcashBlockHash.from_slice([offset] * 32),
+                    tip.sapling_tree_size(),
+                )
+                return ZcashScannedBlock.from_parts(metadata, 0, [], [], [])
+
+            batch = db.begin_block_batch()
+            batch.put_blocks([empty_block(1)])
+            batch.rollback()
+            self.assertEqual(tip.block_height().value(), db.block_max_scanned().block_height().value())
+            with self.assertRaises(ZcashError):
+                batch.put_blocks([empty_block(1)])
+
+            # Blocks which are not sequential are rejected, without writing any of them.
+            batch = db.begin_block_batch()
+            batch.put_blocks([empty_block(1)])
+            batch.put_blocks([empty_block(3)])
+            with self.assertRaises(ZcashError):
+                batch.commit()
+            self.assertEqual(tip.block_height().value(), db.block_max_scanned().block_height().value())
+
+            batch = db.begin_block_batch()
+            batch.put_blocks([empty_block(1)])
+            # Writes made meanwhile are kept.
+            db.update_chain_tip(fixture.chain_tip_height + 20)
+            batch.put_blocks([empty_block(2)])
+            self.assertEqual(tip.block_height().value(), db.block_max_scanned().block_height().value())
+            batch.commit()
+            self.assertEqual(tip.block_height().value() + 2, db.block_max_scanned().block_height().value())
+            self.assertEqual(fixture.chain_tip_height + 20, db.chain_height().value())
+            with self.assertRaises(ZcashError):
+                batch.commit()
+
+    def test_prune_history(self):
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            with self.assertRaises(ZcashError.InvalidArgument):
+                db.prune_history(fixture.chain_tip_height - 99, False)
+
+            db.update_chain_tip(fixture.chain_tip_height + 200)
+            unspent = "SELECT SUM(value) AS value FROM sapling_received_notes WHERE spent IS NULL"
+            report = db.prune_history(fixture.chain_tip_height + 100, True)
+            self.assertFalse(report.applied)
+            self.assertEqual(0, report.spent_notes)
+            report = db.prune_history(fixture.chain_tip_height + 100, False)
+            self.assertTrue(report.applied)
+            self.assertEqual([[ZcashSqlValue.INTEGER(fixture.total_balance)]],
+                             db.query_readonly(unspent, []).rows)
+
+    def test_pruned_transaction_lookup(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            fixture = create_fixture_wallet(db_path, FixtureScenario.PENDING_CHANGE)
+            db = ZcashWalletDb.for_path(db_path, params)
+
+            # The fixture transactions are only scanned, so they are given the raw data of
+            # another transaction.
+            raw = bytes(TestSupport.from_csv_file().get_as_u8_array("transaction_standard_fee"))
+            with sqlite3.connect(db_path) as conn:
+                conn.execute("UPDATE transactions SET raw = ?", (raw,))
+                spent, change = [bytes(row[0]) for row in conn.execute(
+                    "SELECT txid FROM transactions ORDER BY block")]
+
+            # The transaction whose note is spent is pruned, the one of the change is kept.
+            db.update_chain_tip(fixture.chain_tip_height + 200)
+            report = db.prune_history(fixture.chain_tip_height + 100, False)
+            self.assertEqual(1, report.transactions)
+            with sqlite3.connect(db_path) as conn:
+                self.assertEqual([(change,)], conn.execute(
+                    "SELECT txid FROM transactions WHERE raw IS NOT NULL").fetchall())
+
+            txid = ZcashTxId.from_bytes(list(spent))
+            with self.assertRaisesRegex(ZcashError, "history was pruned"):
+                db.get_transaction(txid)
+            result = db.get_transactions_by_ids([txid])[txid.to_hex_string()]
+            self.assertEqual(fixture.birthday_height, result.height.value())
+            self.assertIsNone(result.transaction)
+            self.assertIn("history was pruned", result.error)
+
+    def test_sapling_shards_snapshot(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with tempfile.TemporaryDirectory() as folder:
+            create_fixture_wallet(os.path.join(folder, "source.db"), FixtureScenario.EMPTY)
+            source = ZcashWalletDb.for_path(os.path.join(folder, "source.db"), params)
+            self.assertEqual(list((0).to_bytes(8, "little")), source.export_sapling_shards())
+
+            # Two shards starting at index 0, with small field elements as roots.
+            snapshot = list((0).to_bytes(8, "little"))
+            for i, height in [(1, 400_000), (2, 410_000)]:
+                snapshot += list(i.to_bytes(32, "little")) + list(height.to_bytes(4, "little"))
+            self.assertEqual(2, source.import_sapling_shards(snapshot))
+            self.assertEqual(snapshot, source.export_sapling_shards())
+
+            create_fixture_wallet(os.path.join(folder, "target.db"), FixtureScenario.EMPTY)
+            target = ZcashWalletDb.for_path(os.path.join(folder, "target.db"), params)
+            self.assertEqual(2, target.import_sapling_shards(source.export_sapling_shards()))
+            self.assertEqual(snapshot, target.export_sapling_shards())
+
+            with self.assertRaises(ZcashError.InvalidArgument):
+                target.import_sapling_shards(snapshot[:-1])
+
+    def test_query_readonly(self):
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            result = db.query_readonly(
+                "SELECT COUNT(*) AS n, ? AS label, x'0102' AS raw, 2 AS n FROM accounts",
+                [ZcashSqlValue.TEXT("accounts")])
+            self.assertEqual(["n", "label", "raw", "n"], result.columns)
+            self.assertEqual([[ZcashSqlValue.INTEGER(1), ZcashSqlValue.TEXT("accounts"),
+                               ZcashSqlValue.BLOB([1, 2]), ZcashSqlValue.INTEGER(2)]], result.rows)
+
+            result = db.query_readonly(
+                "SELECT value FROM sapling_received_notes WHERE value >= ?",
+                [ZcashSqlValue.INTEGER(fixture.total_balance)])
+            self.assertEqual(["value"], result.columns)
+            self.assertEqual([[ZcashSqlValue.INTEGER(fixture.total_balance)]], result.rows)
+
+            with self.assertRaises(ZcashError):
+                db.query_readonly("DELETE FROM accounts", [])
+            with self.assertRaises(ZcashError):
+                db.query_readonly("CREATE TABLE t (x INTEGER)", [])
+            with self.assertRaises(ZcashError):
+                db.query_readonly("SELECT * FROM no_such_table", [])
+            with self.assertRaises(ZcashError):
+                db.query_readonly(
+                    "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c) "
+                    "SELECT COUNT(*) FROM c", [])
+
+    def test_account_summary(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        for scenario in [FixtureScenario.EMPTY, FixtureScenario.SYNCED_WITH_FUNDS, FixtureScenario.PENDING_CHANGE]:
+            with tempfile.TemporaryDirectory() as folder:
+                db_path = os.path.join(folder, "wallet.db")
+                fixture = create_fixture_wallet(db_path, scenario)
+                db = ZcashWalletDb.for_path(db_path, params)
+                tree_state = ZcashTreeState("test", fixture.birthday_height - 1, "", 0, "000000", "")
+                db.create_account(fixture.seed, ZcashAccountBirthday.from_treestate(tree_state, None))
+
+                wallet_summary = db.get_wallet_summary(fixture.min_confirmations)
+                account_summary = db.get_account_summary(ZcashAccountId(0), fixture.min_confirmations)
+                self.assertEqual(["0"], list(account_summary.account_balances().keys()))
+                self.assertEqual(wallet_summary.account_balance_values()[0],
+                                 account_summary.account_balance_values()[0])
+                self.assertEqual(wallet_summary.chain_tip_height_value(), account_summary.chain_tip_height_value())
+                self.assertEqual(wallet_summary.fully_scanned_height_value(),
+                                 account_summary.fully_scanned_height_value())
+                self.assertEqual(wallet_summary.is_synced(), account_summary.is_synced())
+                progress = [(s.numerator(), s.denominator()) if s else None
+                            for s in [wallet_summary.scan_progress(), account_summary.scan_progress()]]
+                self.assertEqual(progress[0], progress[1])
+
+                other_summary = db.get_account_summary(ZcashAccountId(1), fixture.min_confirmations)
+                self.assertEqual(0, other_summary.account_balances()["1"].total().value())
+
+                with self.assertRaises(ZcashError.Message):
+                    db.get_account_summary(ZcashAccountId(2), fixture.min_confirmations)
+
+    def test_spendable_value(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        for scenario in [FixtureScenario.EMPTY, FixtureScenario.SYNCED_WITH_FUNDS, FixtureScenario.PENDING_CHANGE]:
+            with tempfile.TemporaryDirectory() as folder:
+                db_path = os.path.join(folder, "wallet.db")
+                fixture = create_fixture_wallet(db_path, scenario)
+                db = ZcashWalletDb.for_path(db_path, params)
+                account = ZcashAccountId(0)
+
+                value = db.get_spendable_value(account, fixture.min_confirmations, [])
+                self.assertEqual(fixture.spendable_balance, value.value())
+
+                heights = db.get_target_and_anchor_heights(fixture.min_confirmations)
+                notes = db.get_spendable_sapling_notes(account, ZcashBlockHeight(heights.anchor_height), [])
+                excluded = [note.internal_note_id() for note in notes]
+                value = db.get_spendable_value(account, fixture.min_confirmations, excluded)
+                self.assertEqual(0, value.value())
+
+    def test_height_and_hash_records(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            fixture = create_fixture_wallet(db_path, FixtureScenario.SYNCED_WITH_FUNDS)
+            db = ZcashWalletDb.for_path(db_path, params)
+
+            heights = db.get_target_and_anchor_heights(fixture.min_confirmations)
+            self.assertEqual(fixture.chain_tip_height + 1, heights.target_height)
+            self.assertLess(heights.anchor_height, heights.target_height)
+
+            tip = db.get_max_height_hash()
+            self.assertEqual(fixture.chain_tip_height, tip.block_height)
+            with sqlite3.connect(db_path) as conn:
+                [block_hash] = conn.execute("SELECT hash FROM blocks WHERE height = ?", (tip.block_height,)).fetchone()
+            self.assertEqual(list(block_hash), tip.block_hash)
+
+            [nullifier] = db.get_sapling_nullifiers(ZcashNullifierQuery.UNSPENT)
+            self.assertEqual(ZcashAccountId(0), nullifier.account_id)
+            self.assertEqual(32, len(nullifier.sapling_nullifier))
+
+    def test_ordered_listings(self):
+        with fixture_wallet(FixtureScenario.EMPTY) as (fixture, db):
+            tree_state = ZcashTreeState("test", fixture.birthday_height - 1, "", 0, "000000", "")
+            for _ in range(2):
+                db.create_account(fixture.seed, ZcashAccountBirthday.from_treestate(tree_state, None))
+            account = ZcashAccountId(0)
+            for _ in range(3):
+                db.get_next_available_address(account)
+
+            ufvks = db.get_unified_full_viewing_keys()
+            self.assertEqual([0, 1, 2], [ufvk.account.id for ufvk in ufvks])
+            receivers = [receiver.address for receiver in db.get_transparent_receivers(account)]
+            self.assertGreater(len(receivers), 1)
+            self.assertEqual(sorted(receivers), receivers)
+            for _ in range(5):
+                self.assertEqual(receivers, [receiver.address for receiver in db.get_transparent_receivers(account)])
+            self.assertEqual([], db.get_transparent_balance_values(account, fixture.chain_tip_height))
+
+    def test_shielding_candidates(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+            account = ZcashAccountId(0)
+            address = ZcashTransparentAddress.decode(params, db.get_transparent_receivers(account)[0].address)
+
+            def put_utxo(n, value, height):
+                txout = ZcashTxOut(ZcashAmount(value), address.script())
+                db.put_received_transparent_utxo(ZcashWalletTransparentOutput.from_parts(
+                    ZcashOutPoint([n] * 32, 0), txout, ZcashBlockHeight(height)))
+
+            # Mature, dust and unconfirmed outputs.
+            put_utxo(1, 100000, fixture.birthday_height)
+            put_utxo(2, 1000, fixture.birthday_height)
+            put_utxo(3, 50000, fixture.chain_tip_height)
+
+            candidates = db.get_shielding_candidates(account, 10000, fixture.min_confirmations)
+            self.assertEqual([100000], [c.value().value() for c in candidates])
+            self.assertEqual([], db.get_shielding_candidates(account, 1000000, fixture.min_confirmations))
+
+    def test_sql_logging(self):
+        enable_sql_logging(0)
+        try:
+            with fixture_wallet(FixtureScenario.SYNCED_WITH_FUNDS) as (fixture, db):
+                summary = db.get_wallet_summary(fixture.min_confirmations)
+                self.assertEqual(fixture.total_balance, summary.account_balances()["0"].total().value())
+        finally:
+            disable_sql_logging()
+
+    def test_transaction_without_replacement(self):
+        with fixture_wallet(FixtureScenario.EMPTY) as (_, db):
+            self.assertIsNone(db.get_replacement_transaction(ZcashTxId.from_bytes([0] * 32)))
+
+    def test_wallet_debug_report(self):
+        with fixture_wallet(FixtureScenario.EMPTY) as (_, db):
+            report = json.loads(db.debug_report())
+            self.assertEqual("test", report["network"])
+            self.assertEqual(1, len(report["accounts"]))
+            self.assertIsNone(report["accounts"][0]["ufvk"])
+            self.assertTrue(report["chain"]["scan_queue"])
+            self.assertIsNotNone(json.loads(db.debug_report(False))["accounts"][0]["ufvk"])
+
+    def test_wallet_stats(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "empty.db")
+            create_fixture_wallet(db_path, FixtureScenario.EMPTY)
+            stats = ZcashWalletDb.for_path(db_path, params).get_wallet_stats()
+            self.assertEqual(0, stats.sapling_notes)
+            self.assertEqual(0, stats.utxos)
+            self.assertEqual(0, stats.transactions)
+            self.assertEqual(os.path.getsize(db_path), stats.db_size)
+            self.assertGreater(stats.scan_queue_ranges, 0)
+            self.assertGreater(stats.blocks_to_scan, 0)
+            self.assertIsNone(stats.last_scanned_height)
+            self.assertIsNone(stats.last_scanned_block_time)
+
+            db_path = os.path.join(folder, "change.db")
+            fixture = create_fixture_wallet(db_path, FixtureScenario.PENDING_CHANGE)
+            stats = ZcashWalletDb.for_path(db_path, params).get_wallet_stats()
+            self.assertEqual(2, stats.sapling_notes)
+            self.assertEqual(1, stats.unspent_sapling_notes)
+            self.assertEqual(0, stats.unspent_utxos)
+            self.assertEqual(2, stats.transactions)
+            self.assertEqual(0, stats.blocks_to_scan)
+            self.assertEqual(fixture.chain_tip_height, stats.last_scanned_height)
+            self.assertIsNotNone(stats.last_scanned_block_time)
+
+    def test_wallet_audit(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with tempfile.TemporaryDirectory() as folder:
+            for scenario in [FixtureScenario.EMPTY, FixtureScenario.PENDING_CHANGE]:
+                db_path = os.path.join(folder, f"{scenario.name}.db")
+                create_fixture_wallet(db_path, scenario)
+                report = ZcashWalletDb.for_path(db_path, params).audit()
+                self.assertEqual([], report.issues)
+
+            db_path = os.path.join(folder, "funds.db")
+            create_fixture_wallet(db_path, FixtureScenario.SYNCED_WITH_FUNDS)
+            db = ZcashWalletDb.for_path(db_path, params)
+            report = db.audit()
+            self.assertEqual([], report.issues)
+            self.assertGreater(report.notes_checked, 0)
+            self.assertEqual(1, report.accounts_checked)
+
+            with sqlite3.connect(db_path) as conn:
+                conn.execute("UPDATE sapling_received_notes SET value = value + 1 WHERE spent IS NULL")
+            issues = db.audit().issues
+            self.assertEqual({ZcashWalletAuditCheck.NOTE_COMMITMENT}, {issue.check for issue in issues})
+            self.assertEqual("sapling_received_notes", issues[0].table)
+
+            with sqlite3.connect(db_path) as conn:
+                conn.execute("UPDATE sapling_received_notes SET value = value - 1 WHERE spent IS NULL")
+                conn.execute("UPDATE sapling_received_notes SET tx = 1000 WHERE spent IS NULL")
+            checks = {(issue.check, issue.table) for issue in db.audit().issues}
+            self.assertEqual({
+                (ZcashWalletAuditCheck.BALANCE, "accounts"),
+                (ZcashWalletAuditCheck.ORPHANED_ROW, "sapling_received_notes"),
+            }, checks)
+
+    def test_history_cursor(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with tempfile.TemporaryDirectory() as folder:
+            db_path = os.path.join(folder, "wallet.db")
+            fixture = create_fixture_wallet(db_path, FixtureScenario.PENDING_CHANGE)
+            db = ZcashWalletDb.for_path(db_path, params)
+            txids = [bytes(txid.v) for [txid] in db.query_readonly(
+                "SELECT txid FROM transactions ORDER BY block, tx_index", []).rows]
+
+            cursor = db.open_history_cursor(ZcashAccountId(0))
+            entries = cursor.next_page(1)
+            # No lock is held between pages, so the wallet can keep on syncing.
+            with sqlite3.connect(db_path, timeout=0) as conn:
+                conn.execute("UPDATE transactions SET expiry_height = expiry_height")
+            entries += cursor.next_page(1)
+            self.assertEqual([], cursor.next_page(10))
+            self.assertEqual([], cursor.next_page(10))
+            self.assertEqual(txids, [bytes(e.txid.to_bytes()) for e in entries])
+            self.assertEqual(fixture.birthday_height, entries[0].mined_height)
+            self.assertEqual(100000, entries[0].account_balance_delta)
+            self.assertLess(entries[1].account_balance_delta, 0)
+            self.assertTrue(entries[1].has_change)
+
+            everything = db.open_history_cursor(None).next_page(1000)
+            self.assertEqual(txids, [bytes(e.txid.to_bytes()) for e in everything])
+
+            cursor_of_all = db.open_history_cursor(None)
+            paged = [e for _ in everything for e in cursor_of_all.next_page(1)]
+            self.assertEqual(txids, [bytes(e.txid.to_bytes()) for e in paged])
+
+            with self.assertRaises(ZcashError):
+                cursor.next_page(0)
+            with self.assertRaises(ZcashError):
+                db.open_history_cursor(ZcashAccountId(5))
+
+    def test_node_rpc_client(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        with self.assertRaises(ZcashError):
+            ZcashNodeRpcClient("https://127.0.0.1:18232", params, None, None, ZcashNetworkConfig())
+
+        user_agents = []
+
+        class Node(BaseHTTPRequestHandler):
+            def do_POST(self):
+                user_agents.append(self.headers.get("User-Agent"))
+                request = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+                if self.headers.get("Authorization") != "Basic dXNlcjpwYXNz":
+                    reply = {"result": None, "error": {"code": -1, "message": "unauthorized"}}
+                elif request["method"] == "getblockcount":
+                    reply = {"result": 2500000, "error": None}
+                else:
+                    reply = {"result": None, "error": {"code": -32601, "message": "Method not found"}}
+                body = json.dumps(reply).encode()
+                self.send_response(200)
+                self.send_header("Content-Length", str(len(body)))
+                self.end_headers()
+                self.wfile.write(body)
+
+            def log_message(self, *args):
+                pass
+
+        server = HTTPServer(("127.0.0.1", 0), Node)
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        try:
+            url = "http://127.0.0.1:%d" % server.server_port
+            client = ZcashNodeRpcClient(url, params, "user", "pass", ZcashNetworkConfig())
+            self.assertEqual(2500000, client.get_block_count())
+            self.assertIsNone(user_agents[-1])
+            with self.assertRaises(ZcashError):
+                client.get_raw_transaction(ZcashTxId.from_bytes([0] * 32))
+            with self.assertRaises(ZcashError):
+                ZcashNodeRpcClient(url, params, None, None, ZcashNetworkConfig()).get_block_count()
+            config = ZcashNetworkConfig(user_agent="wallet/1.0")
+            ZcashNodeRpcClient(url, params, "user", "pass", config).get_block_count()
+            self.assertEqual("wallet/1.0", user_agents[-1])
+        finally:
+            server.shutdown()
+
+    def test_invalid_arguments(self):
+        params = ZcashConsensusParameters.TEST_NETWORK
+        seed = [1] + [0] * 31
+
+        with self.assertRaises(ZcashError.InvalidArgument) as error:
+            ZcashUnifiedSpendingKey.from_seed(params, [1] * 16, ZcashAccountId(0))
+        self.assertIn("`seed`", str(error.exception))
+        with self.assertRaises(ZcashError.InvalidArgument):
+            ZcashUnifiedSpendingKey.from_seed(params, seed, ZcashAccountId(2 ** 31))
+        with self.assertRaises(ZcashError.InvalidArgument):
+            derive_accounts(params, seed, 2, 1)
+        self.assertEqual(1, len(derive_accounts(params, seed, 2 ** 31 - 1, 2 ** 31)))
+
+        with self.assertRaises(ZcashError.InvalidArgument) as error:
+            ZcashTxId.from_bytes([0] * 31)
+        self.assertIn("`data`", str(error.exception))
+        with self.assertRaises(ZcashError.InvalidArgument):
+            ZcashMemoBytes([0] * 513)
+        with self.assertRaises(ZcashError.InvalidArgument):
+            ZcashScanRange.from_heights(10, 5, ZcashScanPriority.HISTORIC)
+        self.assertEqual(5, ZcashScanRange.from_heights(5, 10, ZcashScanPriority.HISTORIC).len())
+
+        with tempfile.TemporaryDirectory() as folder:
+            db = ZcashWalletDb.for_path(os.path.join(folder, "wallet.db"), params)
+            with self.assertRaises(ZcashError.InvalidArgument):
+                db.initialize([])
+
+        code = error_code_for_variant("InvalidArgument")
+        self.assertEqual(1024, code.code)
+        self.assertEqual("invalid_argument", code.key)
+
 if __name__ == '__main__':
     unittest.main()
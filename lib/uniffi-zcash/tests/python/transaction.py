@@ -34,6 +34,38 @@ class TransactionBuilderTest(unittest.TestCase):
         self.assertEqual(result.transaction.to_bytes(),
                          expected_transaction_bytes)
 
+    def test_transparent_with_expiry_delta(self):
+        zts = TestSupport.from_csv_file()
+
+        key = ZcashUnifiedSpendingKey.from_bytes(ZcashKeysEra.ORCHARD,
+                                                 zts.get_as_u8_array("unified_spending_key"))
+        address = key.transparent().to_account_pubkey(
+        ).derive_external_ivk().derive_address(0)
+        secret_key = key.transparent().derive_external_secret_key(0)
+
+        builder = ZcashTransactionBuilder(
+            ZcashConsensusParameters.MAIN_NETWORK, ZcashBlockHeight(2030820))
+        self.assertEqual(2030820 + default_tx_expiry_delta(), builder.expiry_height())
+        builder.set_expiry_delta(1000)
+        self.assertEqual(2031820, builder.expiry_height())
+        with self.assertRaises(ZcashError.InvalidArgument):
+            builder.set_expiry_delta(3)
+
+        builder.add_transparent_input(
+            secret_key, ZcashOutPoint([0] * 32, 1), ZcashTxOut(ZcashAmount(200), address.script()))
+        builder.add_transparent_output(address, ZcashAmount(200))
+        result = builder.build(
+            ZcashLocalTxProver.with_default_location(), ZcashFeeRules.FIXED_NON_STANDARD(0))
+
+        self.assertEqual(2031820, result.expiry_height)
+        self.assertEqual(2031820, result.transaction.expiry_height().value())
+
+        # NU5 activates at 1687104 on the main network.
+        builder = ZcashTransactionBuilder(
+            ZcashConsensusParameters.MAIN_NETWORK, ZcashBlockHeight(1687000))
+        with self.assertRaises(ZcashError.InvalidArgument):
+            builder.set_expiry_delta(200)
+
     def test_transparent_with_standard_fees(self):
         zts = TestSupport.from_csv_file()
 
@@ -160,6 +192,30 @@ class TransactionBuilderTest(unittest.TestCase):
         # This asserts the size, as its deterministic.
         self.assertEqual(len(result.transaction.to_bytes()), 2377)
 
+    def test_sapling_with_rng_seed(self):
+        zts = TestSupport.from_csv_file()
+
+        key = ZcashUnifiedSpendingKey.from_bytes(ZcashKeysEra.ORCHARD,
+                                                 zts.get_as_u8_array("unified_spending_key"))
+        payment_address = key.sapling().default_address().address
+        ovk = key.sapling().to_diversifiable_full_viewing_key().to_ovk(ZcashScope.EXTERNAL)
+        prover = ZcashLocalTxProver.with_default_location()
+
+        def build(rng_seed):
+            builder = ZcashTransactionBuilder.test_only_with_rng_seed(
+                ZcashConsensusParameters.MAIN_NETWORK, ZcashBlockHeight(2030820), rng_seed)
+            builder.add_sapling_output(
+                ovk, payment_address, ZcashAmount(0), ZcashMemoBytes.empty())
+            return builder.build(prover, ZcashFeeRules.FIXED_NON_STANDARD(0)).transaction
+
+        # The proofs differ each time, but the transaction id does not cover them.
+        self.assertEqual(build([1] * 32).txid().to_bytes(), build([1] * 32).txid().to_bytes())
+        self.assertNotEqual(build([1] * 32).txid().to_bytes(), build([2] * 32).txid().to_bytes())
+
+        with self.assertRaises(ZcashError):
+            ZcashTransactionBuilder.test_only_with_rng_seed(
+                ZcashConsensusParameters.MAIN_NETWORK, ZcashBlockHeight(2030820), [1] * 31)
+
 
 class OrchardTransactionBuilderTest(unittest.TestCase):
     def test_transaction_generation(self):
@@ -197,6 +253,28 @@ class OrchardTransactionBuilderTest(unittest.TestCase):
 
         self.assertEqual(len(transaction.to_bytes()), 9165)
 
+    def test_transaction_generation_with_rng_seed(self):
+        zts = TestSupport.from_csv_file()
+
+        key = ZcashUnifiedSpendingKey.from_bytes(ZcashKeysEra.ORCHARD,
+                                                 zts.get_as_u8_array("unified_spending_key"))
+        fvk = key.to_unified_full_viewing_key().orchard()
+        ovk = fvk.to_ovk(ZcashOrchardScope.EXTERNAL)
+        address = fvk.to_ivk(ZcashOrchardScope.EXTERNAL).address(
+            ZcashOrchardDiversifier.from_bytes([0] * 11))
+        anchor = ZcashAnchor.from_bytes([0] * 32)
+        flags = ZcashOrchardFlags.from_parts(False, True)
+
+        def build():
+            builder = ZcashOrchardTransactionBuilder.test_only_with_rng_seed(
+                ZcashConsensusParameters.MAIN_NETWORK, ZcashBlockHeight(2030820),
+                ZcashBlockHeight(2030820+100), anchor, flags, [1] * 32)
+            builder.add_recipient(ovk, address, ZcashOrchardNoteValue.from_raw(15), None)
+            return builder.build([], [0]*32)
+
+        # The proof included.
+        self.assertEqual(build().to_bytes(), build().to_bytes())
+
 
 class TransactionSerializationTest(unittest.TestCase):
     def test_transaction_from_bytes(self):
@@ -229,6 +307,18 @@ class TransactionExplorationTest(unittest.TestCase):
         # expiry height
         self.assertEqual(2030860, tx.expiry_height().value())
 
+    def test_size_and_logical_actions(self):
+        zts = TestSupport.from_csv_file()
+
+        # One P2PKH input and output, one Sapling spend and two outputs, two Orchard actions.
+        for key, logical_actions in [("transaction_standard_fee", 1), ("transaction_sapling", 2),
+                                     ("transaction_orchard", 2)]:
+            tx_bytes = zts.get_as_u8_array(key)
+            tx = ZcashTransaction.from_bytes(tx_bytes, ZcashBranchId.NU5)
+
+            self.assertEqual(len(tx_bytes), tx.serialized_size())
+            self.assertEqual(logical_actions, tx.logical_action_count())
+
     def test_transparent_bundle(self):
 
         zts = TestSupport.from_csv_file()
@@ -396,6 +486,26 @@ class TransactionExplorationTest(unittest.TestCase):
         self.assertEqual(zts.get_as_u8_array(
             "testnet_transaction_orchard_memo"), the_output.data)
 
+    def test_decrypt_memos_with_ufvk(self):
+        zts = TestSupport.from_csv_file()
+
+        key = ZcashUnifiedSpendingKey.from_bytes(ZcashKeysEra.ORCHARD,
+                                                 zts.get_as_u8_array("testnet_unified_spending_key"))
+
+        tx_bytes = zts.get_as_u8_array("testnet_transaction_orchard")
+        tx = ZcashTransaction.from_bytes(tx_bytes, ZcashBranchId.NU5)
+
+        memos = tx.decrypt_memos_with_ufvk(ZcashConsensusParameters.TEST_NETWORK,
+                                           ZcashBlockHeight(2000000), key.to_unified_full_viewing_key())
+        # The empty memo of the change output is left out.
+        self.assertEqual(1, len(memos))
+        self.assertEqual(b"A fullprivacy one", bytes(memos[0].data()))
+
+        other_key = ZcashUnifiedSpendingKey.from_bytes(ZcashKeysEra.ORCHARD,
+                                                       zts.get_as_u8_array("unified_spending_key"))
+        self.assertEqual([], tx.decrypt_memos_with_ufvk(ZcashConsensusParameters.TEST_NETWORK,
+                                                        ZcashBlockHeight(2000000), other_key.to_unified_full_viewing_key()))
+
 
 if __name__ == '__main__':
     unittest.main()
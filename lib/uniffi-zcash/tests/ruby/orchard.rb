@@ -31,7 +31,7 @@ class TestApk < Test::Unit::TestCase
 
     def test_spending_key_array_mismatch
         key_bytes = [0, 1].as_bytes
-        assert_raise(Zcash::ZcashError::ArrayLengthMismatch) {  Zcash::ZcashOrchardSpendingKey.from_bytes(key_bytes)}
+        assert_raise(Zcash::ZcashError::InvalidArgument) {  Zcash::ZcashOrchardSpendingKey.from_bytes(key_bytes)}
     end
 
     def test_spending_key_from_zip32_seed
@@ -1,4 +1,5 @@
 require "test/unit"
+require "tmpdir"
 require "zcash"
 
 # Ruby inverts bytes for some reason, so we need to patch it back
@@ -276,4 +277,101 @@ class TestApk < Test::Unit::TestCase
     assert parsed.is_script
     assert_equal input, parsed.encode(net)
   end
+
+  def summary_of_fixture_wallet(scenario)
+    Dir.mktmpdir do |folder|
+      db_path = File.join(folder, "wallet.db")
+      fixture = Zcash.create_fixture_wallet(db_path, scenario)
+      db = Zcash::ZcashWalletDb.for_path(db_path, Zcash::ZcashConsensusParameters::TEST_NETWORK)
+
+      summary = db.get_wallet_summary(fixture.min_confirmations)
+      balance = summary.account_balances["0"]
+      assert_equal fixture.chain_tip_height, summary.chain_tip_height_value
+      assert_equal fixture.total_balance, balance.total.value
+      assert_equal fixture.spendable_balance, balance.sapling_spendable_value.value
+    end
+  end
+
+  def test_fixture_wallet_summary
+    summary_of_fixture_wallet(Zcash::FixtureScenario::SYNCED_WITH_FUNDS)
+    summary_of_fixture_wallet(Zcash::FixtureScenario::PENDING_CHANGE)
+  end
+
+  def with_fixture_wallet(scenario)
+    Dir.mktmpdir do |folder|
+      db_path = File.join(folder, "wallet.db")
+      fixture = Zcash.create_fixture_wallet(db_path, scenario)
+      yield fixture, Zcash::ZcashWalletDb.for_path(db_path, Zcash::ZcashConsensusParameters::TEST_NETWORK)
+    end
+  end
+
+  def payment_request
+    recipient = Zcash::ZcashUnifiedSpendingKey.from_seed(
+      Zcash::ZcashConsensusParameters::TEST_NETWORK, [2] * 32, Zcash::ZcashAccountId.new(0)
+    ).to_unified_full_viewing_key.default_address.address
+    Zcash::ZcashTransactionRequest.new([
+      Zcash::ZcashPayment.new(Zcash::ZcashRecipientAddress.unified(recipient), Zcash::ZcashAmount.new(20000),
+        nil, nil, nil, []),
+    ])
+  end
+
+  # Ruby bindings have no callback interfaces, so approvers cannot be implemented in Ruby,
+  # but the error they cause is still mapped.
+  def test_spend_not_approved_code
+    code = Zcash.error_code_for_variant("SpendNotApproved")
+    assert_equal 1026, code.code
+    assert_equal "spend_not_approved", code.key
+  end
+
+  def test_spending_limits
+    with_fixture_wallet(Zcash::FixtureScenario::SYNCED_WITH_FUNDS) do |fixture, db|
+      account = Zcash::ZcashAccountId.new(0)
+      limits = db.get_spending_limits(account)
+      assert_nil limits.max_per_transaction
+      assert_nil limits.max_per_day
+
+      db.set_spending_limits(account, Zcash::ZcashSpendingLimits.new(20000, nil))
+      error = assert_raise Zcash::ZcashError::PolicyViolation do
+        db.propose_transfer(account, payment_request, fixture.min_confirmations)
+      end
+      assert_match "`max_per_transaction` limit of 20000", error.message
+
+      db.set_spending_limits(account, Zcash::ZcashSpendingLimits.new(100000, 100000))
+      assert_equal 100000, db.get_spending_limits(account).max_per_day
+      db.propose_transfer(account, payment_request, fixture.min_confirmations)
+    end
+  end
+
+  def test_bump_transparent_fee
+    params = Zcash::ZcashConsensusParameters::TEST_NETWORK
+    with_fixture_wallet(Zcash::FixtureScenario::SYNCED_WITH_FUNDS) do |fixture, db|
+      account = Zcash::ZcashAccountId.new(0)
+      usk = Zcash::ZcashUnifiedSpendingKey.from_seed(params, fixture.seed, account)
+      receiver = db.get_transparent_receivers(account)[0]
+      address = Zcash::ZcashTransparentAddress.decode(params, receiver.address)
+      txout = Zcash::ZcashTxOut.new(Zcash::ZcashAmount.new(100000), address.script())
+      outpoint = Zcash::ZcashOutPoint.new([1] * 32, 0)
+      db.put_received_transparent_utxo(Zcash::ZcashWalletTransparentOutput.from_parts(
+        outpoint, txout, Zcash::ZcashBlockHeight.new(fixture.birthday_height)))
+
+      # A payment to another wallet, with the change back to the account.
+      other = Zcash::ZcashUnifiedSpendingKey.from_seed(params, [9] * 32, account).transparent()
+        .to_account_pubkey().derive_external_ivk().derive_address(0)
+      builder = Zcash::ZcashTransactionBuilder.new(params, Zcash::ZcashBlockHeight.new(fixture.chain_tip_height + 1))
+      builder.add_transparent_input(
+        usk.transparent().derive_external_secret_key(receiver.metadata.diversifier_index().to_u32()),
+        outpoint, txout)
+      builder.add_transparent_output(other, Zcash::ZcashAmount.new(50000))
+      builder.add_transparent_output(address, Zcash::ZcashAmount.new(40000))
+      prover = Zcash::ZcashLocalTxProver.with_default_location()
+      tx = builder.build(prover, Zcash::ZcashFeeRules::FIXED_NON_STANDARD.new(10000)).transaction
+      Zcash.decrypt_and_store_transaction(params, db, tx)
+      assert_nil db.get_replacement_transaction(tx.txid())
+
+      db.update_chain_tip(tx.expiry_height().value())
+      bumped = db.bump_transparent_fee(tx.txid(), usk, prover)
+      assert_equal bumped.txid().to_bytes(), db.get_replacement_transaction(tx.txid()).to_bytes()
+      assert_equal [50000, 35000], bumped.transparent_bundle().vout().map { |txout| txout.value().value() }
+    end
+  end
 end
@@ -17,7 +17,7 @@ func testSpendingKeyArrayMismatch() {
     var thrown = false
     do {
         _ = try ZcashOrchardSpendingKey.fromBytes(data: keyBytes)
-    } catch ZcashError.ArrayLengthMismatch {
+    } catch ZcashError.InvalidArgument {
         thrown = true
     } catch {}
     assert(thrown)
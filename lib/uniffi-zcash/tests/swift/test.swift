@@ -1,3 +1,4 @@
+import Foundation
 import zcash
 
 func testUnifiedSpendingKeyFromSeed() {
@@ -271,3 +272,141 @@ func testTransparentAddressParsing() {
     assert(input2 == parsed2.encode(params: net))
 }
 testTransparentAddressParsing()
+
+func testFixtureWalletSummary() {
+    for scenario in [FixtureScenario.syncedWithFunds, FixtureScenario.pendingChange] {
+        let folder = FileManager.default.temporaryDirectory.appendingPathComponent(UUID().uuidString)
+        try! FileManager.default.createDirectory(at: folder, withIntermediateDirectories: true)
+        defer { try? FileManager.default.removeItem(at: folder) }
+        let dbPath = folder.appendingPathComponent("wallet.db").path
+
+        let fixture = createFixtureWallet(path: dbPath, scenario: scenario)
+        let db = try! ZcashWalletDb.forPath(path: dbPath, params: ZcashConsensusParameters.testNetwork)
+
+        let summary = try! db.getWalletSummary(minConfirmations: fixture.minConfirmations)!
+        let balance = summary.accountBalances()["0"]!
+        assert(fixture.chainTipHeight == summary.chainTipHeightValue())
+        assert(fixture.totalBalance == balance.total().value())
+        assert(fixture.spendableBalance == balance.saplingSpendableValue().value())
+    }
+}
+testFixtureWalletSummary()
+
+func fixtureWallet(scenario: FixtureScenario) -> (FixtureWallet, ZcashWalletDb, URL) {
+    let folder = FileManager.default.temporaryDirectory.appendingPathComponent(UUID().uuidString)
+    try! FileManager.default.createDirectory(at: folder, withIntermediateDirectories: true)
+    let dbPath = folder.appendingPathComponent("wallet.db").path
+
+    let fixture = createFixtureWallet(path: dbPath, scenario: scenario)
+    let db = try! ZcashWalletDb.forPath(path: dbPath, params: ZcashConsensusParameters.testNetwork)
+    return (fixture, db, folder)
+}
+
+func paymentRequest() -> ZcashTransactionRequest {
+    let recipient = try! ZcashUnifiedSpendingKey.fromSeed(
+        params: ZcashConsensusParameters.testNetwork,
+        seed: Array(repeating: 2, count: 32),
+        accountId: ZcashAccountId(id: 0)
+    ).toUnifiedFullViewingKey().defaultAddress().address
+    return try! ZcashTransactionRequest(payments: [
+        ZcashPayment(recipientAddress: ZcashRecipientAddress.unified(addr: recipient),
+                     amount: try! ZcashAmount(amount: 20000), memo: nil, label: nil, message: nil, otherParams: []),
+    ])
+}
+
+class Approver: ZcashSpendApprover {
+    let approved: Bool
+    var proposals: [ZcashProposal] = []
+
+    init(approved: Bool) {
+        self.approved = approved
+    }
+
+    func approve(proposal: ZcashProposal) -> Bool {
+        proposals.append(proposal)
+        return approved
+    }
+}
+
+func testSpendApprover() {
+    let (fixture, db, folder) = fixtureWallet(scenario: FixtureScenario.syncedWithFunds)
+    defer { try? FileManager.default.removeItem(at: folder) }
+    let account = ZcashAccountId(id: 0)
+    let usk = try! ZcashUnifiedSpendingKey.fromSeed(
+        params: ZcashConsensusParameters.testNetwork, seed: fixture.seed, accountId: account)
+    let proposal = try! db.proposeTransfer(
+        account: account, request: paymentRequest(), minConfirmations: fixture.minConfirmations)
+
+    let rejecting = Approver(approved: false)
+    do {
+        _ = try db.createProposedTransaction(
+            proposal: proposal, usk: usk, prover: try! ZcashLocalTxProver.withDefaultLocation(),
+            ovkPolicy: ZcashOvkPolicy.sender, approver: rejecting)
+        assertionFailure("The spend was not approved")
+    } catch ZcashError.SpendNotApproved {
+    } catch {
+        assertionFailure("Unexpected error \(error)")
+    }
+    assert([proposal.fee()] == rejecting.proposals.map { $0.fee() })
+    assert(1026 == errorCodeForVariant(variant: "SpendNotApproved")!.code)
+}
+testSpendApprover()
+
+func testSpendingLimits() {
+    let (fixture, db, folder) = fixtureWallet(scenario: FixtureScenario.syncedWithFunds)
+    defer { try? FileManager.default.removeItem(at: folder) }
+    let account = ZcashAccountId(id: 0)
+    assert(ZcashSpendingLimits(maxPerTransaction: nil, maxPerDay: nil) == try! db.getSpendingLimits(account: account))
+
+    try! db.setSpendingLimits(account: account, limits: ZcashSpendingLimits(maxPerTransaction: 20000, maxPerDay: nil))
+    do {
+        _ = try db.proposeTransfer(
+            account: account, request: paymentRequest(), minConfirmations: fixture.minConfirmations)
+        assertionFailure("The limit per transaction was not enforced")
+    } catch let ZcashError.PolicyViolation(message) {
+        assert(message.contains("`max_per_transaction` limit of 20000"))
+    } catch {
+        assertionFailure("Unexpected error \(error)")
+    }
+
+    let limits = ZcashSpendingLimits(maxPerTransaction: 100000, maxPerDay: 100000)
+    try! db.setSpendingLimits(account: account, limits: limits)
+    assert(limits == try! db.getSpendingLimits(account: account))
+    _ = try! db.proposeTransfer(
+        account: account, request: paymentRequest(), minConfirmations: fixture.minConfirmations)
+}
+testSpendingLimits()
+
+func testBumpTransparentFee() {
+    let params = ZcashConsensusParameters.testNetwork
+    let (fixture, db, folder) = fixtureWallet(scenario: FixtureScenario.syncedWithFunds)
+    defer { try? FileManager.default.removeItem(at: folder) }
+    let account = ZcashAccountId(id: 0)
+    let usk = try! ZcashUnifiedSpendingKey.fromSeed(params: params, seed: fixture.seed, accountId: account)
+    let receiver = try! db.getTransparentReceivers(aid: account)[0]
+    let address = try! ZcashTransparentAddress.decode(params: params, input: receiver.address)
+    let txout = ZcashTxOut(value: try! ZcashAmount(amount: 100000), scriptPubkey: address.script())
+    let outpoint = try! ZcashOutPoint(hash: Array(repeating: 1, count: 32), n: 0)
+    _ = try! db.putReceivedTransparentUtxo(output: try! ZcashWalletTransparentOutput.fromParts(
+        outpoint: outpoint, txout: txout, height: ZcashBlockHeight(v: fixture.birthdayHeight)))
+
+    // A payment to another wallet, with the change back to the account.
+    let other = try! ZcashUnifiedSpendingKey.fromSeed(params: params, seed: Array(repeating: 9, count: 32), accountId: account)
+        .transparent().toAccountPubkey().deriveExternalIvk().deriveAddress(childIndex: 0)
+    let builder = ZcashTransactionBuilder(parameters: params, blockHeight: ZcashBlockHeight(v: fixture.chainTipHeight + 1))
+    builder.addTransparentInput(
+        sk: try! usk.transparent().deriveExternalSecretKey(childIndex: try! receiver.metadata.diversifierIndex().toU32()),
+        utxo: outpoint, coin: txout)
+    builder.addTransparentOutput(to: other, value: try! ZcashAmount(amount: 50000))
+    builder.addTransparentOutput(to: address, value: try! ZcashAmount(amount: 40000))
+    let prover = try! ZcashLocalTxProver.withDefaultLocation()
+    let tx = try! builder.build(prover: prover, feeRule: ZcashFeeRules.fixedNonStandard(amount: 10000)).transaction
+    _ = try! decryptAndStoreTransaction(params: params, zDbData: db, tx: tx)
+    assert(nil == (try! db.getReplacementTransaction(txid: tx.txid())))
+
+    try! db.updateChainTip(tipHeight: tx.expiryHeight().value())
+    let bumped = try! db.bumpTransparentFee(txid: tx.txid(), usk: usk, prover: prover)
+    assert(try! bumped.txid().toBytes() == (try! db.getReplacementTransaction(txid: tx.txid())!.toBytes()))
+    assert([50000, 35000] == bumped.transparentBundle()!.vout().map { $0.value().value() })
+}
+testBumpTransparentFee()
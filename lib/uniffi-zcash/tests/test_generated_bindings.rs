@@ -4,7 +4,11 @@
     // so we keep all tests in one single file for performance reasons.
     // See. https://github.com/mozilla/uniffi-rs/blob/f0be561e98afea00242504f8fdcdef1384574ff1/uniffi_macros/src/test.rs#L11-L43
     "tests/kotlin/big_test.kts",
-    // Python tests
+    // Python tests. Those of the mock prover, of the faucet and against the mock lightwalletd,
+    // `tests/python/mock_prover.py`, `tests/python/faucet.py` and
+    // `tests/python/mock_lightwalletd.py`, need the library built with the feature of the
+    // same name, which the test harness of uniffi cannot build, so they are run by the
+    // `bindings` tests of the CLI. `tests/python/fixtures.py` is a helper of the others.
     "tests/python/test.py",
     "tests/python/sapling.py",
     "tests/python/orchard.py",